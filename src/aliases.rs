@@ -0,0 +1,195 @@
+//! Memorable names for documents (`rfc alias add bgp rfc4271`), kept in
+//! `aliases.json` beside `config.toml`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DocumentType, InvalidIdentifier};
+
+/// Alias name to canonical document name (e.g. `bgp` -> `rfc4271`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Aliases(BTreeMap<String, String>);
+
+impl Aliases {
+    /// Default location of the aliases file, if the platform has one.
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "rfc").map(|dirs| dirs.config_dir().join("aliases.json"))
+    }
+
+    /// Load aliases from their default location; none if the file is
+    /// missing.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load aliases from `path`, treating a missing file as empty.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid aliases file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read aliases file {}", path.display()))
+            }
+        }
+    }
+
+    /// Write aliases to their default location.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path().context("No config directory on this platform")?;
+        self.save_to(&path)
+    }
+
+    /// Write aliases to `path`, creating its directory.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write aliases file {}", path.display()))
+    }
+
+    /// Point `name` at `doc`, returning what it named before. Names are
+    /// case-insensitive and may not look like a document identifier,
+    /// since those never consult aliases.
+    pub fn set(&mut self, name: &str, doc: &DocumentType) -> Result<Option<DocumentType>> {
+        let name = normalize(name);
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            anyhow::bail!("Alias names must be a single word");
+        }
+        if let Some(shadowed) = DocumentType::parse_identifier(&name) {
+            anyhow::bail!("'{}' already names {}; pick another alias", name, shadowed);
+        }
+        Ok(self
+            .0
+            .insert(name, doc.name())
+            .map(|old| DocumentType::from_canonical_name(&old)))
+    }
+
+    /// Remove `name`, returning what it named.
+    pub fn remove(&mut self, name: &str) -> Option<DocumentType> {
+        self.0
+            .remove(&normalize(name))
+            .map(|doc| DocumentType::from_canonical_name(&doc))
+    }
+
+    /// The document `name` refers to, if it's an alias.
+    pub fn get(&self, name: &str) -> Option<DocumentType> {
+        self.0
+            .get(&normalize(name))
+            .map(|doc| DocumentType::from_canonical_name(doc))
+    }
+
+    /// Every alias and its document, sorted by alias.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, DocumentType)> {
+        self.0
+            .iter()
+            .map(|(name, doc)| (name.as_str(), DocumentType::from_canonical_name(doc)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Turn a document argument into a document. Input that parses as
+    /// an RFC or draft is used as is; otherwise an alias is tried before
+    /// falling back to [`DocumentType::from_user_input`].
+    pub fn resolve(&self, input: &str) -> Result<DocumentType, InvalidIdentifier> {
+        match DocumentType::parse_identifier(input).or_else(|| self.get(input)) {
+            Some(doc) => Ok(doc),
+            None => DocumentType::from_user_input(input),
+        }
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn resolves_aliases_case_insensitively() {
+        let mut aliases = Aliases::default();
+        aliases.set("BGP", &DocumentType::Rfc(4271)).unwrap();
+
+        assert_eq!(aliases.resolve("bgp").unwrap(), DocumentType::Rfc(4271));
+        assert_eq!(aliases.resolve(" Bgp ").unwrap(), DocumentType::Rfc(4271));
+        assert_eq!(
+            aliases.resolve("ospf").unwrap(),
+            DocumentType::Draft("draft-ospf".to_string())
+        );
+    }
+
+    #[test]
+    fn identifiers_are_never_shadowed() {
+        let mut aliases = Aliases::default();
+        assert!(aliases.set("9000", &DocumentType::Rfc(1)).is_err());
+        assert!(aliases.set("rfc9000", &DocumentType::Rfc(1)).is_err());
+        assert!(aliases.set("draft-x", &DocumentType::Rfc(1)).is_err());
+        assert!(aliases.set("two words", &DocumentType::Rfc(1)).is_err());
+
+        // Even a hand-edited file can't redirect an identifier.
+        let aliases: Aliases = serde_json::from_str(r#"{"rfc9000": "rfc1"}"#).unwrap();
+        assert_eq!(aliases.resolve("rfc9000").unwrap(), DocumentType::Rfc(9000));
+    }
+
+    #[test]
+    fn set_and_remove_report_previous_target() {
+        let mut aliases = Aliases::default();
+        let quic = DocumentType::Draft("draft-ietf-quic-transport-34".to_string());
+        assert_eq!(aliases.set("quic", &quic).unwrap(), None);
+        assert_eq!(
+            aliases.set("quic", &DocumentType::Rfc(9000)).unwrap(),
+            Some(quic)
+        );
+        assert_eq!(aliases.remove("QUIC"), Some(DocumentType::Rfc(9000)));
+        assert_eq!(aliases.remove("quic"), None);
+        assert!(aliases.is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("aliases.json");
+        assert!(Aliases::load_from(&path).unwrap().is_empty());
+
+        let mut aliases = Aliases::default();
+        aliases.set("bgp", &DocumentType::Rfc(4271)).unwrap();
+        aliases.set("tls", &DocumentType::Rfc(8446)).unwrap();
+        aliases.save_to(&path).unwrap();
+
+        let loaded = Aliases::load_from(&path).unwrap();
+        assert_eq!(loaded, aliases);
+        let listed: Vec<_> = loaded.iter().map(|(n, d)| (n.to_string(), d)).collect();
+        assert_eq!(
+            listed,
+            [
+                ("bgp".to_string(), DocumentType::Rfc(4271)),
+                ("tls".to_string(), DocumentType::Rfc(8446))
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_file_names_the_path() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("aliases.json");
+        fs::write(&path, "not json").unwrap();
+        let err = Aliases::load_from(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("aliases.json"));
+    }
+}
@@ -0,0 +1,514 @@
+//! Structure of a document's plain text: pagination, section headings,
+//! the abstract, RFC and draft references, and the counts behind
+//! `rfc stats`.
+//!
+//! Everything works on the RFC Editor's paginated text format and is
+//! tolerant of drafts and older RFCs that only roughly follow it.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use serde::Serialize;
+
+/// RFC 2119 (and RFC 8174) requirement keywords, in the order RFC 2119
+/// lists them.
+pub const REQUIREMENT_KEYWORDS: [&str; 11] = [
+    "MUST",
+    "MUST NOT",
+    "REQUIRED",
+    "SHALL",
+    "SHALL NOT",
+    "SHOULD",
+    "SHOULD NOT",
+    "RECOMMENDED",
+    "NOT RECOMMENDED",
+    "MAY",
+    "OPTIONAL",
+];
+
+/// Reading speed used for [`TextStats::reading_minutes`]; slower than
+/// prose, since specs are read carefully.
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Counts describing a document's text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextStats {
+    pub lines: usize,
+    /// Whitespace-separated tokens with a letter or digit in them, so
+    /// table-of-contents dot leaders don't count
+    pub words: usize,
+    /// Page footers (`[Page N]`) seen; 0 for unpaginated text. Not
+    /// serialized: an index's page count, when there is one, is better.
+    #[serde(skip)]
+    pub pages: usize,
+    /// Numbered top-level sections (appendices not included)
+    pub sections: usize,
+    /// Entries in the References section(s)
+    pub references: usize,
+    pub reading_minutes: usize,
+    /// Uppercase requirement keywords outside quotes, so the RFC 2119
+    /// boilerplate doesn't count. Every keyword is present, even at 0.
+    pub keywords: BTreeMap<&'static str, usize>,
+}
+
+/// Compute [`TextStats`] for `text`.
+pub fn text_stats(text: &str) -> TextStats {
+    let words = text
+        .split_whitespace()
+        .filter(|word| word.contains(char::is_alphanumeric))
+        .count();
+    let lines = strip_pagination(text);
+    let in_references = lines_in_sections(&lines, |title| title.contains("References"));
+    TextStats {
+        lines: text.lines().count(),
+        words,
+        pages: text.lines().filter(|l| is_footer(l)).count(),
+        sections: lines
+            .iter()
+            .filter_map(|l| heading(l))
+            .filter(|(number, _)| number.chars().all(|c| c.is_ascii_digit()))
+            .count(),
+        references: lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| in_references[*i] && is_reference_entry(line))
+            .count(),
+        reading_minutes: words.div_ceil(WORDS_PER_MINUTE),
+        keywords: keyword_counts(text),
+    }
+}
+
+/// The document's lines without page footers (`... [Page 12]`), form
+/// feeds, running headers, and the blank lines around them, so blocks
+/// that straddle a page break come out whole.
+pub fn strip_pagination(text: &str) -> Vec<&str> {
+    let mut out: Vec<&str> = Vec::new();
+    let mut lines = text
+        .lines()
+        .map(|l| l.trim_start_matches('\x0c'))
+        .peekable();
+    while let Some(line) = lines.next() {
+        if !is_footer(line) {
+            out.push(line);
+            continue;
+        }
+        while out.last().is_some_and(|l| l.trim().is_empty()) {
+            out.pop();
+        }
+        while lines.peek().is_some_and(|l| l.trim().is_empty()) {
+            lines.next();
+        }
+        if lines.peek().is_some_and(|l| is_header(l)) {
+            lines.next();
+        }
+        while lines.peek().is_some_and(|l| l.trim().is_empty()) {
+            lines.next();
+        }
+    }
+    out
+}
+
+fn is_footer(line: &str) -> bool {
+    let line = line.trim_end();
+    line.ends_with(']') && line.contains("[Page ")
+}
+
+fn is_header(line: &str) -> bool {
+    line.starts_with("RFC ") || line.starts_with("Internet-Draft")
+}
+
+/// A section heading's number (`4.2`, or `A.1` for appendices) and title.
+pub fn heading(line: &str) -> Option<(String, &str)> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let line = line.strip_prefix("Appendix ").unwrap_or(line);
+    let (number, title) = line.split_once(char::is_whitespace)?;
+    let number = number.trim_end_matches('.');
+    let mut parts = number.split('.');
+    let first = parts.next()?;
+    let valid_first = first.chars().all(|c| c.is_ascii_digit())
+        || (first.len() == 1 && first.chars().all(|c| c.is_ascii_uppercase()));
+    let valid = !first.is_empty()
+        && valid_first
+        && parts.all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    let title = title.trim();
+    (valid && !title.is_empty()).then(|| (number.to_string(), title))
+}
+
+/// Which `lines` fall under a section whose title satisfies `wanted`, or
+/// under a subsection of one. Headings themselves are excluded.
+pub fn lines_in_sections(lines: &[&str], wanted: impl Fn(&str) -> bool) -> Vec<bool> {
+    let mut inside = vec![false; lines.len()];
+    let mut current: Option<String> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((number, title)) = heading(line) {
+            let nested = current
+                .as_ref()
+                .is_some_and(|parent| number.starts_with(&format!("{}.", parent)));
+            if !nested {
+                current = wanted(title).then_some(number);
+            }
+            continue;
+        }
+        inside[i] = current.is_some();
+    }
+    inside
+}
+
+/// The paragraphs under the unnumbered `Abstract` heading, dedented, or
+/// `None` if the text has no such section.
+pub fn abstract_section(text: &str) -> Option<String> {
+    let lines = strip_pagination(text);
+    let start = lines.iter().position(|l| l.trim_end() == "Abstract")? + 1;
+    let len = lines[start..]
+        .iter()
+        .position(|l| !l.trim().is_empty() && !l.starts_with(char::is_whitespace))
+        .unwrap_or(lines.len() - start);
+    let body = &lines[start..start + len];
+    let indent = body
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()?;
+    let dedented: Vec<&str> = body
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    Some(dedented.join("\n").trim_matches('\n').to_string())
+}
+
+/// `[RFC2119]  Bradner, S., ...`: a citation tag starting a line.
+fn is_reference_entry(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    line.len() - trimmed.len() <= 6
+        && trimmed.starts_with('[')
+        && trimmed.find(']').is_some_and(|end| end > 1)
+}
+
+/// Every mention of an RFC by number (`RFC 9000`, `RFC9000`, `[RFC9000]`)
+/// with its byte range in `text`.
+pub fn rfc_references(text: &str) -> Vec<(Range<usize>, u32)> {
+    let mut found = Vec::new();
+    for (at, _) in text.match_indices("RFC") {
+        if text[..at].ends_with(|c: char| c.is_alphanumeric()) {
+            continue;
+        }
+        let after = &text[at + "RFC".len()..];
+        let spaced = after.strip_prefix(' ').unwrap_or(after);
+        let digits = spaced.len()
+            - spaced
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        let end = text.len() - spaced.len() + digits;
+        if text[end..].starts_with(|c: char| c.is_alphanumeric()) {
+            continue;
+        }
+        if let Ok(num) = spaced[..digits].parse() {
+            found.push((at..end, num));
+        }
+    }
+    found
+}
+
+/// Every draft cited by name (`draft-ietf-quic-transport-34`) or by
+/// citation tag (`[I-D.ietf-quic-transport]`), with its byte range in
+/// `text` and the draft name it refers to, in order of appearance.
+pub fn draft_references(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut found = Vec::new();
+    for prefix in ["draft-", "I-D."] {
+        for (at, _) in text.match_indices(prefix) {
+            if text[..at].ends_with(|c: char| c.is_alphanumeric() || c == '-' || c == '.') {
+                continue;
+            }
+            let rest = &text[at + prefix.len()..];
+            let len = rest.len()
+                - rest
+                    .trim_start_matches(|c: char| {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'
+                    })
+                    .len();
+            // A trailing hyphen is the line's, not the name's.
+            let name = rest[..len].trim_end_matches('-');
+            if !name.contains('-') {
+                continue;
+            }
+            found.push((
+                at..at + prefix.len() + name.len(),
+                format!("draft-{}", name),
+            ));
+        }
+    }
+    found.sort_by_key(|(range, _)| range.start);
+    found
+}
+
+fn keyword_counts(text: &str) -> BTreeMap<&'static str, usize> {
+    let mut counts: BTreeMap<&'static str, usize> =
+        REQUIREMENT_KEYWORDS.iter().map(|k| (*k, 0)).collect();
+    let words = words_with_quotes(text);
+    let mut i = 0;
+    while i < words.len() {
+        let (word, quoted) = words[i];
+        let pair = words
+            .get(i + 1)
+            .map(|(next, _)| format!("{} {}", word, next));
+        let (keyword, len) = match pair.as_deref().and_then(keyword) {
+            Some(keyword) => (keyword, 2),
+            None => match keyword(word) {
+                Some(keyword) => (keyword, 1),
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+        };
+        if !quoted {
+            *counts.entry(keyword).or_default() += 1;
+        }
+        i += len;
+    }
+    counts
+}
+
+fn keyword(word: &str) -> Option<&'static str> {
+    REQUIREMENT_KEYWORDS.iter().copied().find(|k| *k == word)
+}
+
+/// Runs of letters in `text`, each flagged if it directly follows a `"`.
+fn words_with_quotes(text: &str) -> Vec<(&str, bool)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        match (c.is_alphabetic(), start) {
+            (true, None) => start = Some((i, previous == '"')),
+            (false, Some((from, quoted))) => {
+                words.push((&text[from..i], quoted));
+                start = None;
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    if let Some((from, quoted)) = start {
+        words.push((&text[from..], quoted));
+    }
+    words
+}
+
+/// The line ranges to show for matches on `matches` (0-based, ascending)
+/// with `before` and `after` lines of context, in a text of `lines`
+/// lines. Hunks that overlap or touch are merged into one, as `grep -C`
+/// does.
+pub fn context_hunks(
+    matches: &[usize],
+    before: usize,
+    after: usize,
+    lines: usize,
+) -> Vec<Range<usize>> {
+    let mut hunks: Vec<Range<usize>> = Vec::new();
+    for &line in matches.iter().filter(|&&line| line < lines) {
+        let hunk =
+            line.saturating_sub(before)..line.saturating_add(after).saturating_add(1).min(lines);
+        match hunks.last_mut() {
+            Some(last) if hunk.start <= last.end => last.end = last.end.max(hunk.end),
+            _ => hunks.push(hunk),
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+Internet Engineering Task Force (IETF)                         A. Author
+Request for Comments: 9999                                      May 2021
+
+                          An Example Protocol
+
+Table of Contents
+
+   1.  Introduction  . . . . . . . . . . . . . . . . . . . . . . .   1
+   2.  Protocol  . . . . . . . . . . . . . . . . . . . . . . . . .   2
+
+1.  Introduction
+
+   The key words \"MUST\", \"MUST NOT\", \"SHOULD\", and \"MAY\" in this
+   document are to be interpreted as described in BCP 14 [RFC2119].
+
+   Senders MUST send a greeting and MUST NOT
+   send two.  They SHOULD wait, and may retry.
+
+
+
+Author                       Standards Track                    [Page 1]
+\x0c
+RFC 9999                    Example Protocol                    May 2021
+
+
+2.  Protocol
+
+2.1.  Messages
+
+   Receivers MAY ignore greetings.  Using RFC 9000 or RFC793 is
+   NOT RECOMMENDED; see RFC 8200 and not RFC 90001.
+
+3.  References
+
+3.1.  Normative References
+
+   [RFC2119]  Bradner, S., \"Key words for use in RFCs to Indicate
+              Requirement Levels\", BCP 14, RFC 2119, March 1997.
+
+   [RFC9000]  Iyengar, J., Ed. and M. Thomson, Ed., \"QUIC\", RFC 9000.
+
+3.2.  Informative References
+
+   [QUIC-RECOVERY]
+              Iyengar, J., Ed. and I. Swett, Ed., \"Recovery\".
+
+Appendix A.  Acknowledgments
+
+   Thanks.  [Not a reference]
+";
+
+    #[test]
+    fn fixture_stats() {
+        let stats = text_stats(FIXTURE);
+        assert_eq!(stats.lines, FIXTURE.lines().count());
+        // TOC dot leaders aren't words.
+        assert_eq!(stats.words, 153);
+        assert_eq!(stats.pages, 1);
+        assert_eq!(stats.sections, 3);
+        assert_eq!(stats.references, 3);
+        assert_eq!(stats.reading_minutes, 1);
+    }
+
+    #[test]
+    fn keywords_skip_quoted_boilerplate_and_lowercase() {
+        let stats = text_stats(FIXTURE);
+        let count = |k: &str| stats.keywords[k];
+        assert_eq!(count("MUST"), 1);
+        // Split across a line break, still one keyword.
+        assert_eq!(count("MUST NOT"), 1);
+        assert_eq!(count("SHOULD"), 1);
+        assert_eq!(count("MAY"), 1);
+        assert_eq!(count("NOT RECOMMENDED"), 1);
+        assert_eq!(count("RECOMMENDED"), 0);
+        assert_eq!(stats.keywords.len(), REQUIREMENT_KEYWORDS.len());
+    }
+
+    #[test]
+    fn pagination_is_removed() {
+        let lines = strip_pagination(FIXTURE);
+        assert!(!lines.iter().any(|l| l.contains("[Page")));
+        assert!(!lines.iter().any(|l| l.starts_with("RFC 9999")));
+        let at = lines.iter().position(|l| l.contains("send two")).unwrap();
+        assert_eq!(lines[at + 1], "2.  Protocol");
+    }
+
+    #[test]
+    fn abstract_is_found_across_a_page_break() {
+        let text = "\
+Request for Comments: 9999                                      May 2021
+
+                          An Example Protocol
+
+Abstract
+
+   This document describes an example
+   protocol.
+
+   It has two
+
+Author                       Informational                      [Page 1]
+\x0c
+RFC 9999                    Example Protocol                    May 2021
+
+
+   paragraphs.
+
+Status of This Memo
+
+   Boilerplate.
+";
+        assert_eq!(
+            abstract_section(text).as_deref(),
+            Some("This document describes an example\nprotocol.\n\nIt has two\nparagraphs.")
+        );
+        assert_eq!(abstract_section(FIXTURE), None);
+    }
+
+    #[test]
+    fn headings() {
+        assert_eq!(heading("4.2.  Frames"), Some(("4.2".to_string(), "Frames")));
+        assert_eq!(
+            heading("Appendix A.  Notes"),
+            Some(("A".to_string(), "Notes"))
+        );
+        assert_eq!(heading("A.1.  More"), Some(("A.1".to_string(), "More")));
+        assert_eq!(heading("   1.  Indented TOC entry"), None);
+        assert_eq!(heading("Request for Comments: 9999"), None);
+        assert_eq!(heading("4.2."), None);
+    }
+
+    #[test]
+    fn drafts_by_name_and_tag() {
+        let text = "See draft-ietf-quic-transport-34, [I-D.ietf-quic-recovery], \
+                    draft-standard status, and foo-draft-x-y.";
+        let found: Vec<_> = draft_references(text)
+            .into_iter()
+            .map(|(range, name)| (&text[range], name))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "draft-ietf-quic-transport-34",
+                    "draft-ietf-quic-transport-34".to_string()
+                ),
+                (
+                    "I-D.ietf-quic-recovery",
+                    "draft-ietf-quic-recovery".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn references_by_number() {
+        let text = "See RFC 9000, [RFC793], RFCs, XRFC 1, RFC 90001x, and RFC 8200.";
+        let found: Vec<_> = rfc_references(text)
+            .into_iter()
+            .map(|(range, num)| (&text[range], num))
+            .collect();
+        assert_eq!(
+            found,
+            [("RFC 9000", 9000), ("RFC793", 793), ("RFC 8200", 8200)]
+        );
+    }
+
+    #[test]
+    fn context_hunks_merge_and_stay_in_bounds() {
+        let hunks = |matches: &[usize], before, after, lines| -> Vec<(usize, usize)> {
+            context_hunks(matches, before, after, lines)
+                .into_iter()
+                .map(|hunk| (hunk.start, hunk.end))
+                .collect()
+        };
+        assert_eq!(hunks(&[5], 0, 0, 10), [(5, 6)]);
+        assert_eq!(hunks(&[5], 2, 1, 10), [(3, 7)]);
+        // Overlapping and touching hunks merge; separate ones don't.
+        assert_eq!(hunks(&[2, 4, 9], 1, 1, 20), [(1, 6), (8, 11)]);
+        assert_eq!(hunks(&[2, 5], 1, 1, 20), [(1, 7)]);
+        assert_eq!(hunks(&[2, 6], 1, 1, 20), [(1, 4), (5, 8)]);
+        // Matches at either end, with context running past them.
+        assert_eq!(hunks(&[0, 9], 3, 3, 10), [(0, 4), (6, 10)]);
+        assert_eq!(hunks(&[0], usize::MAX, usize::MAX, 3), [(0, 3)]);
+        assert!(hunks(&[], 2, 2, 10).is_empty());
+        assert!(hunks(&[3], 1, 1, 0).is_empty());
+    }
+}
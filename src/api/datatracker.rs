@@ -1,15 +1,138 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
-use reqwest::Client;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures::future::join_all;
+use futures::join;
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use tracing::Instrument;
+
+use super::throttle::{self, check_rate_limit, RateLimiter};
+use super::ProxySettings;
 
-use crate::models::{Document, DocumentType, SearchFilter, SearchResult};
+use crate::models::{
+    AuthorDocuments, Ballot, BallotPosition, DocEvent, Document, DocumentDetails, DocumentType,
+    DraftState, Lineage, LineageHop, LineageLink, Person, Position, SearchFilter, SearchOptions,
+    SearchPage, SearchResult, StdLevel,
+};
 
 pub const DATATRACKER_BASE_URL: &str = "https://datatracker.ietf.org";
 
+/// Longest search query accepted, in characters. Real queries are a few
+/// words; anything near this is a paste gone wrong.
+pub const MAX_QUERY_LEN: usize = 512;
+
+/// Most names in one [`DataTrackerClient::get_documents`] request.
+pub const NAMES_PER_REQUEST: usize = 50;
+
+/// Most bytes of comma-joined names in one `name__in` filter, keeping the
+/// URL well inside what servers and proxies accept.
+const MAX_NAMES_LEN: usize = 1500;
+
+/// Documents requested per page by [`DataTrackerClient::search_page`].
+pub const SEARCH_PAGE_SIZE: u32 = 50;
+
+/// Most events requested per page of a document's history.
+const EVENT_PAGE_SIZE: usize = 100;
+
+/// Most people [`DataTrackerClient::documents_by_author`] offers when a
+/// name is ambiguous.
+const MAX_AUTHOR_CANDIDATES: usize = 10;
+
+/// Most documents [`DataTrackerClient::lineage`] follows, in case the
+/// relationships loop.
+const MAX_LINEAGE_HOPS: usize = 12;
+
 /// Client for the IETF Datatracker REST API. Used for search and for
 /// metadata lookups (titles, draft revisions).
 pub struct DataTrackerClient {
-    client: Client,
+    pub(super) client: Client,
+    pub(super) base_url: String,
+    pub(super) limiter: Arc<RateLimiter>,
+}
+
+/// Builder for [`DataTrackerClient`], for pointing at a mirror or mock
+/// server or tuning the HTTP client.
+///
+/// `timeout`, `user_agent`, and `proxy` configure a freshly-built HTTP
+/// client and are ignored when an existing one is injected with `client`.
+#[derive(Debug, Default)]
+pub struct DataTrackerClientBuilder {
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    client: Option<Client>,
+    rate_limit: Option<f64>,
+    proxy: Option<ProxySettings>,
+}
+
+impl DataTrackerClientBuilder {
+    /// Base URL of the Datatracker instance (default
+    /// [`DATATRACKER_BASE_URL`]). A trailing slash is ignored.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// Per-request timeout (default [`super::DEFAULT_TIMEOUT`]).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// User-agent header (default [`super::DEFAULT_USER_AGENT`]; see
+    /// [`super::user_agent_with_contact`]).
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Proxy settings (default [`ProxySettings::from_env`]).
+    pub fn proxy(mut self, proxy: ProxySettings) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Reuse an existing HTTP client instead of building one.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Average requests per second (default
+    /// [`super::DEFAULT_RATE_LIMIT`], at least [`super::MIN_RATE_LIMIT`]);
+    /// `f64::INFINITY` disables limiting.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(per_second);
+        self
+    }
+
+    pub fn build(self) -> Result<DataTrackerClient> {
+        let client = match self.client {
+            Some(client) => client,
+            None => super::build_http_client_with(
+                self.user_agent
+                    .as_deref()
+                    .unwrap_or(super::DEFAULT_USER_AGENT),
+                self.timeout.unwrap_or(super::DEFAULT_TIMEOUT),
+                &self.proxy.unwrap_or_else(ProxySettings::from_env),
+            )?,
+        };
+        let base_url =
+            super::normalize_base_url(self.base_url.as_deref().unwrap_or(DATATRACKER_BASE_URL));
+        let rate_limit = check_rate_limit(self.rate_limit.unwrap_or(super::DEFAULT_RATE_LIMIT))?;
+        let limiter = Arc::new(RateLimiter::new(rate_limit));
+        Ok(DataTrackerClient {
+            client,
+            base_url,
+            limiter,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,15 +152,142 @@ struct SearchMeta {
 /// Document as returned by the Datatracker API.
 ///
 /// Only the fields the CLI consumes are deserialized; the API returns a
-/// great deal more (pages, authors, timestamps, etc.) that we ignore.
-/// `abstract_text` is read by the multi-token local filter in `search`,
-/// not stored on `Document`.
+/// great deal more (pages, authors, etc.) that we ignore.
 #[derive(Debug, Deserialize)]
 struct ApiDocument {
     name: String,
     title: String,
     #[serde(rename = "abstract")]
     abstract_text: Option<String>,
+    /// Last-modified timestamp. For RFCs this is the publication time; for
+    /// drafts it tracks the latest revision.
+    #[serde(default)]
+    time: Option<String>,
+    /// Draft revision, e.g. `"34"`.
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default)]
+    expires: Option<String>,
+    /// Resource URIs of the document's states (`/api/v1/doc/state/1/`).
+    #[serde(default)]
+    states: Vec<String>,
+    /// Set on RFCs by newer Datatracker versions.
+    #[serde(default)]
+    rfc_number: Option<u32>,
+    /// Resource URI of the status, e.g. `/api/v1/name/stdlevelname/ps/`.
+    #[serde(default)]
+    std_level: Option<String>,
+    /// Resource URIs of the document's tags
+    /// (`/api/v1/name/doctagname/errata/`).
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocEventResponse {
+    meta: SearchMeta,
+    objects: Vec<ApiDocEvent>,
+}
+
+/// One entry from the `docevent` endpoint.
+#[derive(Debug, Deserialize)]
+struct ApiDocEvent {
+    time: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    /// May contain HTML markup.
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    rev: Option<String>,
+}
+
+/// One entry from the `ballotdocevent` endpoint.
+#[derive(Debug, Deserialize)]
+struct ApiBallotEvent {
+    id: u64,
+    time: String,
+    /// `created_ballot` or `closed_ballot`.
+    #[serde(rename = "type")]
+    event_type: String,
+}
+
+/// One entry from the `ballotpositiondocevent` endpoint. `balloter` and
+/// `pos` are resource URIs; `pos` ends in a slug such as `noobj`.
+#[derive(Debug, Deserialize)]
+struct ApiBallotPosition {
+    balloter: String,
+    pos: String,
+    #[serde(default)]
+    discuss: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPerson {
+    #[serde(default)]
+    id: u64,
+    name: String,
+    /// The name in ASCII, for people whose `name` isn't.
+    #[serde(default)]
+    ascii: String,
+}
+
+impl ApiPerson {
+    /// This person, with the affiliation and email from `authored`, one of
+    /// their authorship records.
+    fn to_person(&self, authored: Option<&ApiDocumentAuthor>) -> Person {
+        Person {
+            id: self.id,
+            name: self.name.clone(),
+            affiliation: authored
+                .map(|author| author.affiliation.trim().to_string())
+                .filter(|affiliation| !affiliation.is_empty()),
+            email: authored
+                .and_then(|author| author.email.as_deref())
+                .and_then(resource_name)
+                .map(str::to_string),
+        }
+    }
+}
+
+/// One entry from the `documentauthor` endpoint. `document` and `email`
+/// are resource URIs, the latter ending in the address itself.
+#[derive(Debug, Deserialize)]
+struct ApiDocumentAuthor {
+    #[serde(default)]
+    id: u64,
+    document: String,
+    #[serde(default)]
+    affiliation: String,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+/// One entry from the `state` endpoint.
+#[derive(Debug, Deserialize)]
+struct ApiState {
+    #[serde(default)]
+    resource_uri: String,
+    #[serde(default)]
+    slug: String,
+    name: String,
+    /// Resource URI of the state type, e.g. `/api/v1/doc/statetype/draft-iesg/`.
+    #[serde(rename = "type")]
+    state_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ObjectList<T> {
+    objects: Vec<T>,
+}
+
+/// One edge from the `relateddocument` endpoint. Both ends are resource
+/// URIs such as `/api/v1/doc/document/rfc7230/`.
+#[derive(Debug, Deserialize)]
+struct ApiRelatedDocument {
+    source: String,
+    #[serde(default)]
+    target: String,
 }
 
 impl DataTrackerClient {
@@ -48,20 +298,35 @@ impl DataTrackerClient {
 
     /// Build a client that reuses an existing HTTP client.
     pub fn with_client(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            base_url: DATATRACKER_BASE_URL.to_string(),
+            limiter: Arc::default(),
+        }
+    }
+
+    /// Start configuring a client with a custom base URL, timeout, etc.
+    pub fn builder() -> DataTrackerClientBuilder {
+        DataTrackerClientBuilder::default()
+    }
+
+    /// Base URL this client sends requests to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
     }
 
     /// Search for documents matching the query.
     ///
-    /// The query is tokenized on whitespace and pushed to the server as
-    /// much as possible:
+    /// The query is split into terms: words, with `"quoted phrases"` kept
+    /// whole (see [`QueryMode::Words`](crate::models::QueryMode::Words)).
+    /// The terms are pushed to the server as much as possible:
     ///
-    /// - the longest token becomes a `title__icontains` filter,
+    /// - the longest term becomes a `title__icontains` filter,
     /// - the second-longest (if any) becomes an `abstract__icontains` filter,
     /// - `type__in` honors the caller's `SearchFilter`, defaulting to
     ///   `rfc,draft` so the response doesn't include slides, charters, etc.
     ///
-    /// Any remaining (3rd+) tokens are AND-ed locally against
+    /// Any remaining (3rd+) terms are AND-ed locally against
     /// title+abstract. This makes queries like "bgp message" work without
     /// the user having to guess the exact phrase, while keeping the JSON
     /// payload (and latency) small.
@@ -71,20 +336,31 @@ impl DataTrackerClient {
         filter: SearchFilter,
         limit: u32,
     ) -> Result<SearchResult> {
-        let tokens: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+        self.search_with_options(query, &SearchOptions::new(filter), limit)
+            .await
+    }
 
-        // Pick the longest token for the title filter, the second-longest for
-        // the abstract filter. Falls back to the raw query when there are no
-        // whitespace-separated tokens (e.g. empty input).
-        let mut by_length: Vec<&str> = tokens.iter().map(String::as_str).collect();
-        by_length.sort_by_key(|t| std::cmp::Reverse(t.len()));
-        let primary_token = by_length.first().copied().unwrap_or(query);
-        let secondary_token = by_length.get(1).copied();
+    /// Like [`search`](Self::search), also applying the status and date
+    /// filters in `options` server-side, and splitting the query per its
+    /// [`QueryMode`](crate::models::QueryMode).
+    pub async fn search_with_options(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        limit: u32,
+    ) -> Result<SearchResult> {
+        self.search_documents(query, options, limit)
+            .instrument(tracing::info_span!("search", query))
+            .await
+    }
 
-        // Server-side type filter. If the user asked for --rfc or --draft we
-        // honor that; otherwise we restrict to rfc+draft so the response
-        // doesn't waste rows on slides, charters, reviews, etc.
-        let type_filter = filter.api_param().unwrap_or("rfc,draft");
+    async fn search_documents(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        limit: u32,
+    ) -> Result<SearchResult> {
+        let terms = SearchTerms::new(query, options)?;
 
         // Cushion sizing. With both title and abstract filters server-side,
         // multi-token queries are already very selective — asking for the
@@ -92,102 +368,758 @@ impl DataTrackerClient {
         // abstract filter, so we keep a small cushion (3x) for the
         // ID-ordering-fallthrough effect we observed in benchmarks.
         let base_limit = limit.max(25);
-        let api_limit = if secondary_token.is_some() {
+        let api_limit = if terms.secondary.is_some() {
             base_limit
         } else {
             base_limit.saturating_mul(3)
         };
 
-        let mut url = format!(
-            "{}/api/v1/doc/document/?title__icontains={}&type__in={}&limit={}&format=json",
-            DATATRACKER_BASE_URL,
-            urlencoding::encode(primary_token),
-            type_filter,
-            api_limit
+        let params = search_params(
+            &terms.primary,
+            terms.secondary.as_deref(),
+            api_limit,
+            options,
         );
-        if let Some(s) = secondary_token {
-            url.push_str(&format!("&abstract__icontains={}", urlencoding::encode(s)));
-        }
+        let search_response = self.search_request(&params).await?;
+
+        // Filter to only RFCs and drafts that match all query tokens, then take
+        // up to the requested limit.
+        let matches: Vec<ApiDocument> = search_response
+            .objects
+            .into_iter()
+            .filter(|doc| terms.keeps(doc))
+            .take(limit as usize)
+            .collect();
+        let documents = self.with_draft_states(matches).await;
 
+        Ok(SearchResult {
+            documents,
+            has_more: search_response.meta.next.is_some(),
+            total_count: terms.total_count(&search_response.meta),
+            query: query.to_string(),
+            filter: options.filter,
+            status: options.status.clone(),
+            since: options.since,
+            before: options.before,
+            mode: options.mode,
+            keyword: options.keyword.clone(),
+        })
+    }
+
+    /// The page of results for `query` starting `offset` documents into
+    /// the server's result list, split and filtered as
+    /// [`search`](Self::search) does. Pass the page's `next_offset` back
+    /// in for the one after it.
+    ///
+    /// The local filtering means a page can hold fewer than
+    /// [`SEARCH_PAGE_SIZE`] documents, or none, and still not be the last.
+    pub async fn search_page(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        offset: u32,
+    ) -> Result<SearchPage> {
+        let terms = SearchTerms::new(query, options)?;
+        let mut params = search_params(
+            &terms.primary,
+            terms.secondary.as_deref(),
+            SEARCH_PAGE_SIZE,
+            options,
+        );
+        params.push(("offset", offset.to_string()));
         let response = self
-            .client
-            .get(&url)
-            .send()
+            .search_request(&params)
+            .instrument(tracing::info_span!("search", query, offset))
+            .await?;
+        let received = response.objects.len() as u32;
+        let total_count = terms.total_count(&response.meta);
+        let next_offset = response
+            .meta
+            .next
+            .is_some()
+            .then(|| offset + received)
+            .filter(|_| received > 0);
+        let matches = response
+            .objects
+            .into_iter()
+            .filter(|doc| terms.keeps(doc))
+            .collect();
+        Ok(SearchPage {
+            documents: self.with_draft_states(matches).await,
+            next_offset,
+            total_count,
+        })
+    }
+
+    /// Every result for `query`, following the server's pages as the
+    /// stream is read: a page is only requested once the documents before
+    /// it have been taken. An error ends the stream.
+    ///
+    /// ```no_run
+    /// use futures::TryStreamExt;
+    /// use rfc::{DataTrackerClient, SearchFilter};
+    /// use rfc::models::SearchOptions;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = DataTrackerClient::new()?;
+    /// let options = SearchOptions::new(SearchFilter::RfcsOnly);
+    /// let titles: Vec<String> = client
+    ///     .search_stream("quic", &options)
+    ///     .map_ok(|doc| doc.title)
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_stream<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> impl Stream<Item = Result<Document>> + 'a {
+        stream::try_unfold(Some(0u32), move |offset| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, anyhow::Error>(None);
+            };
+            let page = self.search_page(query, options, offset).await?;
+            let documents = stream::iter(page.documents.into_iter().map(Ok));
+            Ok(Some((documents, page.next_offset)))
+        })
+        .try_flatten()
+    }
+
+    /// `docs` as [`Document`]s, with each draft's [`DraftState`] filled in
+    /// from one lookup of the draft state table. The states are left out
+    /// if that lookup fails, or when there are no drafts to need it.
+    async fn with_draft_states(&self, docs: Vec<ApiDocument>) -> Vec<Document> {
+        let states = self
+            .draft_states_for(docs.iter().map(|doc| doc.name.as_str()))
+            .await;
+        docs.into_iter()
+            .map(|doc| with_draft_state(doc, &states))
+            .collect()
+    }
+
+    /// The draft state table when any of `names` is a draft, and empty
+    /// when none is or the lookup fails.
+    async fn draft_states_for<'a>(
+        &self,
+        mut names: impl Iterator<Item = &'a str>,
+    ) -> HashMap<String, DraftState> {
+        if !names.any(|name| name.starts_with("draft-")) {
+            return HashMap::new();
+        }
+        self.draft_states().await.unwrap_or_else(|e| {
+            tracing::debug!("draft state lookup failed: {:#}", e);
+            HashMap::new()
+        })
+    }
+
+    /// The draft states, by resource URI.
+    async fn draft_states(&self) -> Result<HashMap<String, DraftState>> {
+        let states: Vec<ApiState> = self
+            .list("doc/state/", &[("type", "draft"), ("limit", "50")])
+            .await
+            .context("State lookup failed")?;
+        Ok(states
+            .into_iter()
+            .filter_map(|state| Some((state.resource_uri, DraftState::from_slug(&state.slug)?)))
+            .collect())
+    }
+
+    /// Send a document search with `params` (from [`search_params`]) and
+    /// read its response.
+    async fn search_request(&self, params: &[(&str, String)]) -> Result<SearchResponse> {
+        let url = api_url(&self.base_url, "/api/v1/doc/document/", params)?;
+        let response = throttle::send(&self.limiter, self.client.get(url))
             .await
             .context("Failed to send search request")?;
 
         if !response.status().is_success() {
             anyhow::bail!(
                 "Search request to {} failed: HTTP {}",
-                url,
+                response.url(),
                 response.status()
             );
         }
 
-        let search_response: SearchResponse = response
+        response
             .json()
             .await
-            .context("Failed to parse search response")?;
+            .context("Failed to parse search response")
+    }
 
-        // 3rd+ tokens weren't sent to the API; each must still match locally
-        // against title or abstract for the document to be included.
-        let extra_tokens: Vec<&str> = by_length.iter().copied().skip(2).collect();
+    fn is_rfc_or_draft(name: &str) -> bool {
+        name.starts_with("rfc") || name.starts_with("draft-")
+    }
+
+    /// RFC numbers of the documents that obsolete `rfc`. Empty when the
+    /// RFC is current.
+    pub async fn obsoleted_by(&self, rfc: u32) -> Result<Vec<u32>> {
+        let related = self
+            .related(&[
+                ("target__name", &format!("rfc{}", rfc)),
+                ("relationship__slug", "obs"),
+            ])
+            .await
+            .with_context(|| format!("Relationship lookup for RFC {} failed", rfc))?;
+
+        let mut numbers: Vec<u32> = related
+            .iter()
+            .filter_map(|r| match resource_name(&r.source) {
+                Some(name) => match DocumentType::from_canonical_name(name) {
+                    DocumentType::Rfc(n) => Some(n),
+                    DocumentType::Draft(_) => None,
+                },
+                None => None,
+            })
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        Ok(numbers)
+    }
+
+    /// Relationship edges matching `filter` (`relateddocument` query
+    /// parameters such as `target__name=rfc2616`, `relationship__slug=obs`).
+    async fn related(&self, filter: &[(&str, &str)]) -> Result<Vec<ApiRelatedDocument>> {
+        self.list("doc/relateddocument/", filter)
+            .await
+            .context("Failed to fetch document relationships")
+    }
+
+    /// The `objects` of one page from an API list endpoint, given its path
+    /// below `/api/v1/` and its query parameters.
+    async fn list<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let url = api_url(&self.base_url, &format!("/api/v1/{}", endpoint), params)?;
+        let response = throttle::send(&self.limiter, self.client.get(url)).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("HTTP {}", response.status());
+        }
+        let list: ObjectList<T> = response.json().await?;
+        Ok(list.objects)
+    }
+
+    /// Fetch a single document's metadata by canonical name.
+    pub async fn get_document(&self, name: &str) -> Result<Document> {
+        let span = tracing::info_span!("metadata", document = name);
+        Ok(self.api_document(name).instrument(span).await?.into())
+    }
+
+    /// Metadata for many documents at once, by canonical name, with
+    /// `obsoleted_by` filled in for the RFCs and `draft_state` (when the
+    /// state table can be had) for the drafts. The names are looked up with
+    /// `name__in` filters, [`NAMES_PER_REQUEST`] at a time (fewer when they
+    /// are long), so a few requests stand in for one per document. Names
+    /// the Datatracker doesn't know are absent from the map; a failed
+    /// request fails the whole lookup.
+    pub async fn get_documents(&self, names: &[&str]) -> Result<HashMap<String, Document>> {
+        let span = tracing::info_span!("metadata", documents = names.len());
+        let chunks = name_chunks(names);
+        let found = async {
+            let states = self.draft_states_for(names.iter().copied()).await;
+            join_all(
+                chunks
+                    .iter()
+                    .map(|chunk| self.document_batch(chunk, &states)),
+            )
+            .await
+        }
+        .instrument(span)
+        .await;
+        let mut documents = HashMap::new();
+        for batch in found {
+            documents.extend(batch?.into_iter().map(|doc| (doc.name.clone(), doc)));
+        }
+        Ok(documents)
+    }
 
-        let matches_extra_tokens = |doc: &ApiDocument| -> bool {
-            if extra_tokens.is_empty() {
-                return true;
+    /// One `name__in` lookup for [`get_documents`](Self::get_documents),
+    /// with a second for what obsoletes the RFCs among `names`. Drafts'
+    /// states are looked up in `states`.
+    async fn document_batch(
+        &self,
+        names: &[&str],
+        states: &HashMap<String, DraftState>,
+    ) -> Result<Vec<Document>> {
+        let joined = names.join(",");
+        let limit = names.len().to_string();
+        let rfcs: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| {
+                matches!(
+                    DocumentType::from_canonical_name(name),
+                    DocumentType::Rfc(_)
+                )
+            })
+            .collect();
+        let rfcs = rfcs.join(",");
+        let obsoleting = async {
+            if rfcs.is_empty() {
+                return Ok(Vec::new());
             }
-            let title_lc = doc.title.to_lowercase();
-            let abstract_lc = doc.abstract_text.as_deref().unwrap_or("").to_lowercase();
-            extra_tokens
-                .iter()
-                .all(|tok| title_lc.contains(tok) || abstract_lc.contains(tok))
+            self.related(&[
+                ("target__name__in", &rfcs),
+                ("relationship__slug", "obs"),
+                ("limit", "500"),
+            ])
+            .await
         };
+        let params = [("name__in", joined.as_str()), ("limit", limit.as_str())];
+        let (docs, obsoleting) = join!(
+            self.list::<ApiDocument>("doc/document/", &params),
+            obsoleting
+        );
+        let docs = docs.context("Failed to fetch document metadata")?;
+        let obsoleting = obsoleting?;
 
-        // Filter to only RFCs and drafts that match all query tokens, then take
-        // up to the requested limit.
-        let documents: Vec<Document> = search_response
-            .objects
+        let mut obsoleted_by: HashMap<&str, Vec<u32>> = HashMap::new();
+        for edge in &obsoleting {
+            let (Some(target), Some(source)) =
+                (resource_name(&edge.target), resource_name(&edge.source))
+            else {
+                continue;
+            };
+            if let DocumentType::Rfc(n) = DocumentType::from_canonical_name(source) {
+                obsoleted_by.entry(target).or_default().push(n);
+            }
+        }
+        Ok(docs
             .into_iter()
-            .filter(|doc| Self::is_rfc_or_draft(&doc.name))
-            .filter(matches_extra_tokens)
-            .map(Document::from)
-            .take(limit as usize)
-            .collect();
+            .map(|doc| {
+                let mut by = obsoleted_by.remove(doc.name.as_str()).unwrap_or_default();
+                by.sort_unstable();
+                by.dedup();
+                Document {
+                    obsoleted_by: by,
+                    ..with_draft_state(doc, states)
+                }
+            })
+            .collect())
+    }
 
-        // The API's total_count reflects all server-side filters (title,
-        // abstract, type) — it's accurate when we have no further local
-        // filtering to do. With 3+ tokens we filter locally too, so drop it.
-        let total_count = if extra_tokens.is_empty() {
-            search_response.meta.total_count
+    /// Like [`get_document`](Self::get_document), plus lifecycle facts.
+    ///
+    /// For drafts this resolves the draft, IESG, and WG states, the RFC
+    /// the draft became, and the drafts replacing it, with the sub-requests
+    /// made concurrently. Only the document lookup itself can fail; a
+    /// failed sub-request leaves its fields empty.
+    pub async fn get_document_details(&self, name: &str) -> Result<DocumentDetails> {
+        self.document_details(name)
+            .instrument(tracing::info_span!("metadata", document = name))
+            .await
+    }
+
+    async fn document_details(&self, name: &str) -> Result<DocumentDetails> {
+        let mut api_doc = self.api_document(name).await?;
+        let state_uris = std::mem::take(&mut api_doc.states);
+        let mut details = DocumentDetails {
+            rev: api_doc.rev.take(),
+            expires: api_doc.expires.take().as_deref().and_then(parse_api_date),
+            rfc_number: api_doc.rfc_number,
+            ..DocumentDetails::new(api_doc.into())
+        };
+        if !name.starts_with("draft-") {
+            return Ok(details);
+        }
+
+        let states = join_all(state_uris.iter().map(|uri| self.state(uri)));
+        let became_rfc = [("source__name", name), ("relationship__slug", "became_rfc")];
+        let replaced_by = [("target__name", name), ("relationship__slug", "replaces")];
+        let (states, became_rfc, replaced_by) = join!(
+            states,
+            self.related(&became_rfc),
+            self.related(&replaced_by)
+        );
+
+        for state in states.into_iter().flatten() {
+            let slot = match resource_name(&state.state_type) {
+                Some("draft") => &mut details.state,
+                Some("draft-iesg") => &mut details.iesg_state,
+                Some(kind) if kind.starts_with("draft-stream-") => &mut details.wg_state,
+                _ => continue,
+            };
+            *slot = Some(state.name);
+        }
+        details.rfc_number = details.rfc_number.or_else(|| {
+            became_rfc.ok()?.iter().find_map(|r| {
+                match DocumentType::from_canonical_name(resource_name(&r.target)?) {
+                    DocumentType::Rfc(n) => Some(n),
+                    DocumentType::Draft(_) => None,
+                }
+            })
+        });
+        details.replaced_by = replaced_by
+            .map(|edges| {
+                edges
+                    .iter()
+                    .filter_map(|r| resource_name(&r.source).map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(details)
+    }
+
+    /// Where `name` came from and what it became, oldest first: the
+    /// drafts it replaced or was published from, back to the first, and
+    /// the drafts that replaced it or the RFC it became, up to the last.
+    ///
+    /// Each hop costs a document lookup and a relationship query or two.
+    /// Only the lookup of `name` itself can fail; the lineage stops at a
+    /// relationship or document that can't be fetched.
+    pub async fn lineage(&self, name: &str) -> Result<Lineage> {
+        self.document_lineage(name)
+            .instrument(tracing::info_span!("metadata", document = name))
+            .await
+    }
+
+    async fn document_lineage(&self, name: &str) -> Result<Lineage> {
+        let start = self.api_document(name).await?;
+        let mut seen = HashSet::from([start.name.clone()]);
+
+        let mut hops = Vec::new();
+        let mut current = start.name.clone();
+        while hops.len() + 1 < MAX_LINEAGE_HOPS {
+            let Some((previous, link)) = self.predecessor(&current).await else {
+                break;
+            };
+            if !seen.insert(previous.clone()) {
+                break;
+            }
+            let Ok(doc) = self.api_document(&previous).await else {
+                break;
+            };
+            hops.push(lineage_hop(doc, Some(link)));
+            current = previous;
+        }
+        hops.reverse();
+
+        let mut doc = start;
+        loop {
+            let next = match self.successor(&doc).await {
+                Some((name, link))
+                    if hops.len() + 1 < MAX_LINEAGE_HOPS && seen.insert(name.clone()) =>
+                {
+                    self.api_document(&name).await.ok().map(|next| (next, link))
+                }
+                _ => None,
+            };
+            let Some((next, link)) = next else {
+                hops.push(lineage_hop(doc, None));
+                break;
+            };
+            hops.push(lineage_hop(doc, Some(link)));
+            doc = next;
+        }
+        Ok(Lineage { hops })
+    }
+
+    /// The document `name` came from: the draft an RFC was published
+    /// from, or the draft a draft replaced.
+    async fn predecessor(&self, name: &str) -> Option<(String, LineageLink)> {
+        if name.starts_with("draft-") {
+            let edges = self
+                .related(&[("source__name", name), ("relationship__slug", "replaces")])
+                .await
+                .ok()?;
+            let previous = edges.iter().find_map(|r| resource_name(&r.target))?;
+            Some((previous.to_string(), LineageLink::ReplacedBy))
         } else {
-            None
+            let edges = self
+                .related(&[("target__name", name), ("relationship__slug", "became_rfc")])
+                .await
+                .ok()?;
+            let draft = edges.iter().find_map(|r| resource_name(&r.source))?;
+            Some((draft.to_string(), LineageLink::PublishedAs))
+        }
+    }
+
+    /// What draft `doc` led to: the RFC it became, or else the draft
+    /// replacing it. RFCs lead nowhere.
+    async fn successor(&self, doc: &ApiDocument) -> Option<(String, LineageLink)> {
+        if !doc.name.starts_with("draft-") {
+            return None;
+        }
+        if let Some(number) = doc.rfc_number {
+            return Some((format!("rfc{}", number), LineageLink::PublishedAs));
+        }
+        let became_rfc = self
+            .related(&[
+                ("source__name", &doc.name),
+                ("relationship__slug", "became_rfc"),
+            ])
+            .await;
+        if let Some(rfc) = became_rfc
+            .iter()
+            .flatten()
+            .find_map(|r| resource_name(&r.target))
+        {
+            return Some((rfc.to_string(), LineageLink::PublishedAs));
+        }
+        let replaced_by = self
+            .related(&[
+                ("target__name", &doc.name),
+                ("relationship__slug", "replaces"),
+            ])
+            .await
+            .ok()?;
+        let next = replaced_by.iter().find_map(|r| resource_name(&r.source))?;
+        Some((next.to_string(), LineageLink::ReplacedBy))
+    }
+
+    /// The most recent ballot on `name` with each balloter's latest
+    /// position, or `None` if the document has never been balloted.
+    ///
+    /// Balloters' names are looked up concurrently; one that can't be
+    /// found is shown by its Datatracker person ID.
+    pub async fn ballot(&self, name: &str) -> Result<Option<Ballot>> {
+        let events: Vec<ApiBallotEvent> = self
+            .list(
+                "doc/ballotdocevent/",
+                &[
+                    ("doc__name", name),
+                    ("type__in", "created_ballot,closed_ballot"),
+                    ("order_by", "-time"),
+                    ("limit", "20"),
+                ],
+            )
+            .await
+            .with_context(|| format!("Ballot lookup for {} failed", name))?;
+        let Some(pos) = events.iter().position(|e| e.event_type == "created_ballot") else {
+            return Ok(None);
         };
+        let created = &events[pos];
+        // Newest first, so a close of this ballot comes before its creation.
+        let closed = events[..pos]
+            .iter()
+            .rev()
+            .find(|e| e.event_type == "closed_ballot")
+            .and_then(|e| parse_api_time(&e.time));
 
-        Ok(SearchResult {
+        let api_positions: Vec<ApiBallotPosition> = self
+            .list(
+                "doc/ballotpositiondocevent/",
+                &[
+                    ("ballot", &created.id.to_string()),
+                    ("order_by", "-time"),
+                    ("limit", "500"),
+                ],
+            )
+            .await
+            .with_context(|| format!("Ballot position lookup for {} failed", name))?;
+        let mut seen = HashSet::new();
+        let latest: Vec<ApiBallotPosition> = api_positions
+            .into_iter()
+            .filter(|p| seen.insert(p.balloter.clone()))
+            .collect();
+
+        let names = join_all(latest.iter().map(|p| self.person_name(&p.balloter))).await;
+        let mut positions: Vec<BallotPosition> = latest
+            .into_iter()
+            .zip(names)
+            .filter_map(|(p, balloter)| {
+                let position = Position::from_slug(resource_name(&p.pos)?)?;
+                let discuss = matches!(position, Position::Discuss | Position::Block)
+                    .then(|| plain_text(&p.discuss))
+                    .filter(|d| !d.is_empty());
+                Some(BallotPosition {
+                    balloter,
+                    position,
+                    discuss,
+                })
+            })
+            .collect();
+        positions.sort_by(|a, b| (a.position, &a.balloter).cmp(&(b.position, &b.balloter)));
+
+        Ok(Some(Ballot {
+            opened: parse_api_time(&created.time).context("Ballot has no valid time")?,
+            closed,
+            positions,
+        }))
+    }
+
+    /// Everything the person called `name` is listed as an author of,
+    /// newest first, as [`get_documents`](Self::get_documents) describes
+    /// it. `name` may also be a Datatracker person ID.
+    ///
+    /// A name matching several people is [`AuthorDocuments::Ambiguous`]
+    /// unless exactly one of them has it as their whole name; the
+    /// candidates come with an affiliation and email to choose by.
+    pub async fn documents_by_author(&self, name: &str) -> Result<AuthorDocuments> {
+        let span = tracing::info_span!("author", name);
+        self.author_documents(name.trim()).instrument(span).await
+    }
+
+    async fn author_documents(&self, name: &str) -> Result<AuthorDocuments> {
+        let people = self.people_named(name).await?;
+        let person = match &matching_people(name, &people)[..] {
+            [person] => *person,
+            [] => anyhow::bail!("No one in the Datatracker is named \"{}\"", name),
+            candidates => {
+                let hinted = join_all(
+                    candidates
+                        .iter()
+                        .take(MAX_AUTHOR_CANDIDATES)
+                        .map(|person| self.hinted_person(person)),
+                )
+                .await;
+                return Ok(AuthorDocuments::Ambiguous(hinted));
+            }
+        };
+
+        let id = person.id.to_string();
+        // Prolific authors have a few hundred documents.
+        let authored: Vec<ApiDocumentAuthor> = self
+            .list("doc/documentauthor/", &[("person", &id), ("limit", "1000")])
+            .await
+            .with_context(|| format!("Failed to fetch the documents of {}", person.name))?;
+        let names: Vec<&str> = authored
+            .iter()
+            .filter_map(|author| resource_name(&author.document))
+            .filter(|name| Self::is_rfc_or_draft(name))
+            .collect();
+        let mut documents: Vec<Document> =
+            self.get_documents(&names).await?.into_values().collect();
+        documents.sort_by(|a, b| {
+            b.published
+                .cmp(&a.published)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let latest = authored.iter().max_by_key(|author| author.id);
+        Ok(AuthorDocuments::Found {
+            person: person.to_person(latest),
             documents,
-            has_more: search_response.meta.next.is_some(),
-            total_count,
-            query: query.to_string(),
-            filter,
         })
     }
 
-    fn is_rfc_or_draft(name: &str) -> bool {
-        name.starts_with("rfc") || name.starts_with("draft-")
+    /// The people whose name contains `name`, or the one whose ID it is.
+    async fn people_named(&self, name: &str) -> Result<Vec<ApiPerson>> {
+        if name.is_empty() {
+            anyhow::bail!("Name someone to list the documents of");
+        }
+        if let Ok(id) = name.parse::<u64>() {
+            let url = api_url::<&str, &str>(
+                &self.base_url,
+                &format!("/api/v1/person/person/{}/", id),
+                &[],
+            )?;
+            let response = throttle::send(&self.limiter, self.client.get(url)).await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(Vec::new());
+            }
+            if !response.status().is_success() {
+                anyhow::bail!("Person lookup failed: HTTP {}", response.status());
+            }
+            return Ok(vec![response.json().await?]);
+        }
+        self.list(
+            "person/person/",
+            &[("name__icontains", name), ("limit", "50")],
+        )
+        .await
+        .context("Person lookup failed")
     }
 
-    /// Fetch a single document's metadata by canonical name.
-    pub async fn get_document(&self, name: &str) -> Result<Document> {
-        let url = format!(
-            "{}/api/v1/doc/document/{}/?format=json",
-            DATATRACKER_BASE_URL, name
-        );
+    /// `person` with the affiliation and email from one of their
+    /// documents, or without when that lookup fails.
+    async fn hinted_person(&self, person: &ApiPerson) -> Person {
+        let id = person.id.to_string();
+        let authored = self
+            .list::<ApiDocumentAuthor>("doc/documentauthor/", &[("person", &id), ("limit", "1")])
+            .await
+            .unwrap_or_else(|e| {
+                tracing::debug!("authorship lookup for person {} failed: {:#}", id, e);
+                Vec::new()
+            });
+        person.to_person(authored.first())
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
+    /// A person's name from their resource URI, falling back to the ID.
+    async fn person_name(&self, uri: &str) -> String {
+        let fallback = || format!("person {}", resource_name(uri).unwrap_or("?"));
+        let Ok(url) = api_url::<&str, &str>(&self.base_url, uri, &[]) else {
+            return fallback();
+        };
+        let Ok(response) = throttle::send(&self.limiter, self.client.get(url)).await else {
+            return fallback();
+        };
+        if !response.status().is_success() {
+            return fallback();
+        }
+        match response.json::<ApiPerson>().await {
+            Ok(person) => person.name,
+            Err(_) => fallback(),
+        }
+    }
+
+    /// Up to `limit` events from `name`'s timeline, newest first.
+    ///
+    /// Long-lived drafts have thousands of events, so pages are requested
+    /// only until `limit` is reached, each no larger than what's still
+    /// needed (and at most 100).
+    pub async fn document_events(&self, name: &str, limit: usize) -> Result<Vec<DocEvent>> {
+        let mut events = Vec::new();
+        // The API reads `limit=0` as "no limit".
+        if limit == 0 {
+            return Ok(events);
+        }
+        let first = api_url(
+            &self.base_url,
+            "/api/v1/doc/docevent/",
+            &[
+                ("doc__name", name),
+                ("order_by", "-time"),
+                ("limit", &limit.min(EVENT_PAGE_SIZE).to_string()),
+            ],
+        )?;
+        let mut request = Some(self.client.get(first));
+        while let Some(page) = request.take() {
+            if events.len() >= limit {
+                break;
+            }
+            let response = throttle::send(&self.limiter, page)
+                .await
+                .context("Failed to fetch document history")?;
+            if !response.status().is_success() {
+                anyhow::bail!(
+                    "History lookup for {} failed: HTTP {}",
+                    name,
+                    response.status()
+                );
+            }
+            let page: DocEventResponse = response
+                .json()
+                .await
+                .context("Failed to parse document history")?;
+
+            events.extend(page.objects.into_iter().filter_map(DocEvent::from_api));
+            // `next` comes encoded from the server, so it's used as given.
+            request = page.meta.next.map(|next| {
+                let next = with_limit(&next, (limit - events.len()).min(EVENT_PAGE_SIZE));
+                self.client.get(format!("{}{}", self.base_url, next))
+            });
+        }
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    /// Look up a state resource such as `/api/v1/doc/state/1/`.
+    async fn state(&self, uri: &str) -> Result<ApiState> {
+        let url = api_url::<&str, &str>(&self.base_url, uri, &[])?;
+        let response = throttle::send(&self.limiter, self.client.get(url)).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("State lookup failed: HTTP {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn api_document(&self, name: &str) -> Result<ApiDocument> {
+        let path = format!("/api/v1/doc/document/{}/", urlencoding::encode(name));
+        let url = api_url::<&str, &str>(&self.base_url, &path, &[])?;
+        let response = throttle::send(&self.limiter, self.client.get(url))
             .await
             .context("Failed to fetch document metadata")?;
 
@@ -195,22 +1127,527 @@ impl DataTrackerClient {
             anyhow::bail!("Document not found: {}", name);
         }
 
-        let api_doc: ApiDocument = response
+        response
             .json()
             .await
-            .context("Failed to parse document metadata")?;
-
-        Ok(api_doc.into())
+            .context("Failed to parse document metadata")
     }
 }
 
 impl From<ApiDocument> for Document {
     fn from(doc: ApiDocument) -> Self {
         let doc_type = DocumentType::from_canonical_name(&doc.name);
+        let published = doc.time.as_deref().and_then(parse_api_date);
         Document {
             name: doc.name,
             title: doc.title,
             doc_type,
+            published,
+            abstract_text: doc.abstract_text.filter(|a| !a.trim().is_empty()),
+            obsoleted_by: Vec::new(),
+            std_level: doc.std_level.as_deref().and_then(StdLevel::from_slug),
+            draft_state: None,
+            expires: doc.expires.as_deref().and_then(parse_api_date),
+            authors: Vec::new(),
+            wg: None,
+            updated_by: Vec::new(),
+            keywords: doc
+                .tags
+                .iter()
+                .filter_map(|uri| resource_name(uri))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+impl DocEvent {
+    /// Convert a wire event, dropping it if its timestamp is unreadable.
+    fn from_api(event: ApiDocEvent) -> Option<Self> {
+        Some(DocEvent {
+            time: parse_api_time(&event.time)?,
+            event_type: event.event_type,
+            description: plain_text(&event.desc),
+            rev: event.rev.filter(|r| !r.is_empty()),
+        })
+    }
+}
+
+/// How a query is split between the server and local filtering: the
+/// longest term becomes the title filter, the second-longest the abstract
+/// filter, and the rest must match locally.
+struct SearchTerms {
+    primary: String,
+    secondary: Option<String>,
+    extra: Vec<String>,
+}
+
+impl SearchTerms {
+    /// Split `query`, which must be at most [`MAX_QUERY_LEN`] characters.
+    fn new(query: &str, options: &SearchOptions) -> Result<Self> {
+        let len = query.chars().count();
+        if len > MAX_QUERY_LEN {
+            anyhow::bail!(
+                "Search query is {} characters long; at most {} are allowed",
+                len,
+                MAX_QUERY_LEN
+            );
+        }
+        let mut tokens = options.mode.terms(query);
+        tokens.sort_by_key(|t| std::cmp::Reverse(t.len()));
+        let mut tokens = tokens.into_iter();
+        // Falls back to the raw query when there are no whitespace-separated
+        // tokens (e.g. empty input).
+        let primary = tokens.next().unwrap_or_else(|| query.to_string());
+        let secondary = tokens.next();
+        Ok(Self {
+            primary,
+            secondary,
+            extra: tokens.collect(),
+        })
+    }
+
+    /// Whether `doc` is an RFC or draft and its title or abstract holds
+    /// each of the terms not sent to the server.
+    fn keeps(&self, doc: &ApiDocument) -> bool {
+        if !DataTrackerClient::is_rfc_or_draft(&doc.name) {
+            return false;
+        }
+        if self.extra.is_empty() {
+            return true;
+        }
+        let title_lc = doc.title.to_lowercase();
+        let abstract_lc = doc.abstract_text.as_deref().unwrap_or("").to_lowercase();
+        self.extra
+            .iter()
+            .all(|tok| title_lc.contains(tok) || abstract_lc.contains(tok))
+    }
+
+    /// The API's total_count reflects all server-side filters (title,
+    /// abstract, type) — it's accurate when we have no further local
+    /// filtering to do. With 3+ tokens we filter locally too, so drop it.
+    fn total_count(&self, meta: &SearchMeta) -> Option<u32> {
+        meta.total_count.filter(|_| self.extra.is_empty())
+    }
+}
+
+/// Which of `people` (found by a substring search for `name`) `name`
+/// means: the one whose whole name it is, or every candidate when no
+/// single one is.
+fn matching_people<'a>(name: &str, people: &'a [ApiPerson]) -> Vec<&'a ApiPerson> {
+    let name = name.to_lowercase();
+    let exact: Vec<&ApiPerson> = people
+        .iter()
+        .filter(|p| p.name.to_lowercase() == name || p.ascii.to_lowercase() == name)
+        .collect();
+    match exact.len() {
+        0 => people.iter().collect(),
+        _ => exact,
+    }
+}
+
+/// `names`, without duplicates, in groups for one `name__in` filter each:
+/// at most [`NAMES_PER_REQUEST`] names and [`MAX_NAMES_LEN`] bytes.
+fn name_chunks<'a>(names: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let mut seen = HashSet::new();
+    let mut chunks: Vec<Vec<&str>> = Vec::new();
+    let mut len = 0;
+    for &name in names.iter().filter(|name| seen.insert(**name)) {
+        let full = chunks.last().is_none_or(|chunk| {
+            chunk.len() >= NAMES_PER_REQUEST || len + 1 + name.len() > MAX_NAMES_LEN
+        });
+        if full {
+            chunks.push(Vec::new());
+            len = 0;
         }
+        len += name.len() + 1;
+        chunks.last_mut().unwrap().push(name);
+    }
+    chunks
+}
+
+/// `path` (below `base_url`, already encoded) with `params` and
+/// `format=json` as the query string. The values are form-encoded here,
+/// so `&`, `=`, `+`, and non-ASCII text can't bleed into other parameters.
+fn api_url<K: AsRef<str>, V: AsRef<str>>(
+    base_url: &str,
+    path: &str,
+    params: &[(K, V)],
+) -> Result<Url> {
+    let mut url = Url::parse(&format!("{}{}", base_url, path))
+        .with_context(|| format!("Invalid Datatracker URL: {}{}", base_url, path))?;
+    url.query_pairs_mut()
+        .extend_pairs(params)
+        .append_pair("format", "json");
+    Ok(url)
+}
+
+/// The document search's query parameters: title and abstract filters,
+/// the type filter, a `std_level__in` filter OR-ing the statuses, `time`
+/// bounds for the date range, and a `tags__slug` filter for the keyword,
+/// each only when `options` asks for it.
+/// They're encoded when the request is built, so any text is safe in the
+/// filters.
+fn search_params(
+    title: &str,
+    abstract_token: Option<&str>,
+    limit: u32,
+    options: &SearchOptions,
+) -> Vec<(&'static str, String)> {
+    // Server-side type filter. If the user asked for --rfc or --draft we
+    // honor that; otherwise we restrict to rfc+draft so the response
+    // doesn't waste rows on slides, charters, reviews, etc.
+    let type_filter = options.filter.api_param().unwrap_or("rfc,draft");
+    let mut params = vec![
+        ("title__icontains", title.to_string()),
+        ("type__in", type_filter.to_string()),
+        ("limit", limit.to_string()),
+    ];
+    if let Some(s) = abstract_token {
+        params.push(("abstract__icontains", s.to_string()));
+    }
+    if !options.status.is_empty() {
+        let slugs: Vec<&str> = options.status.iter().map(StdLevel::slug).collect();
+        params.push(("std_level__in", slugs.join(",")));
+    }
+    // Ranges are half-open, from midnight of `since` to midnight of `before`.
+    if let Some(since) = options.since {
+        params.push(("time__gte", format!("{}T00:00:00", since)));
+    }
+    if let Some(before) = options.before {
+        params.push(("time__lt", format!("{}T00:00:00", before)));
+    }
+    if let Some(keyword) = &options.keyword {
+        params.push(("tags__slug", keyword.clone()));
+    }
+    params
+}
+
+/// Replace the `limit` parameter of a `meta.next` page path, so the last
+/// page asks only for the events still wanted.
+fn with_limit(next: &str, limit: usize) -> String {
+    let Some((path, query)) = next.split_once('?') else {
+        return next.to_string();
+    };
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some(("limit", _)) => format!("limit={}", limit),
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", path, params.join("&"))
+}
+
+/// Strip tags and the common entities from an event description, and
+/// collapse its whitespace.
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parse a Datatracker timestamp, which may or may not carry an offset
+/// (none means UTC) and fractional seconds.
+fn parse_api_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").map(|t| t.and_utc()))
+        .ok()
+}
+
+/// Last path segment of an API resource URI
+/// (`/api/v1/doc/document/rfc7230/` → `rfc7230`).
+fn resource_name(uri: &str) -> Option<&str> {
+    uri.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+}
+
+/// `doc` as a [`Document`], with its [`DraftState`] from `states` (the
+/// draft state table, by resource URI).
+fn with_draft_state(doc: ApiDocument, states: &HashMap<String, DraftState>) -> Document {
+    let draft_state = doc.states.iter().find_map(|uri| states.get(uri)).copied();
+    Document {
+        draft_state,
+        ..doc.into()
+    }
+}
+
+/// `doc` as a step of a [`Lineage`], followed by `next`.
+fn lineage_hop(doc: ApiDocument, next: Option<LineageLink>) -> LineageHop {
+    let is_draft = doc.name.starts_with("draft-");
+    LineageHop {
+        date: doc.time.as_deref().and_then(parse_api_date),
+        rev: doc.rev.filter(|_| is_draft),
+        name: doc.name,
+        title: doc.title,
+        next,
+    }
+}
+
+/// Parse the date portion of a Datatracker timestamp. The API is not
+/// consistent about offsets and fractional seconds, so only the leading
+/// `YYYY-MM-DD` is considered.
+fn parse_api_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The search URL `params` make.
+    fn search_url(params: &[(&str, String)]) -> String {
+        api_url("https://dt.example", "/api/v1/doc/document/", params)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn search_url_adds_filters_only_when_given() {
+        let rfcs = SearchOptions::new(SearchFilter::RfcsOnly);
+        assert_eq!(
+            search_url(&search_params("tls", None, 75, &rfcs)),
+            "https://dt.example/api/v1/doc/document/?title__icontains=tls&type__in=rfc\
+             &limit=75&format=json"
+        );
+        assert_eq!(
+            search_url(&search_params(
+                "transport",
+                Some("quic"),
+                25,
+                &SearchOptions::default()
+                    .with_status(vec![StdLevel::InternetStandard, StdLevel::Bcp]),
+            )),
+            "https://dt.example/api/v1/doc/document/?title__icontains=transport\
+             &type__in=rfc%2Cdraft&limit=25&abstract__icontains=quic\
+             &std_level__in=std%2Cbcp&format=json"
+        );
+    }
+
+    #[test]
+    fn search_url_bounds_time_at_midnight() {
+        let options = SearchOptions::default()
+            .with_dates(
+                NaiveDate::from_ymd_opt(2015, 1, 1),
+                NaiveDate::from_ymd_opt(2020, 6, 1),
+            )
+            .unwrap();
+        let url = search_url(&search_params("multicast", None, 25, &options));
+        assert!(url.ends_with(
+            "&time__gte=2015-01-01T00%3A00%3A00&time__lt=2020-06-01T00%3A00%3A00&format=json"
+        ));
+
+        let options = SearchOptions::default()
+            .with_dates(None, NaiveDate::from_ymd_opt(2020, 6, 1))
+            .unwrap();
+        let url = search_url(&search_params("multicast", None, 25, &options));
+        assert!(!url.contains("time__gte"));
+        assert!(url.ends_with("&time__lt=2020-06-01T00%3A00%3A00&format=json"));
+    }
+
+    #[test]
+    fn search_url_filters_by_keyword() {
+        let options = SearchOptions::default().with_keyword(Some(" Errata ".into()));
+        assert!(search_url(&search_params("http", None, 25, &options))
+            .ends_with("&limit=25&tags__slug=errata&format=json"));
+        let options = SearchOptions::default().with_keyword(Some(" ".into()));
+        assert!(!search_url(&search_params("http", None, 25, &options)).contains("tags__slug"));
+    }
+
+    #[test]
+    fn search_url_encodes_every_term() {
+        let options = SearchOptions::default();
+        let terms = SearchTerms::new("\"a=b&c d\" 国际化 x+y/z", &options).unwrap();
+        assert_eq!(
+            search_url(&search_params(
+                &terms.primary,
+                terms.secondary.as_deref(),
+                25,
+                &options
+            )),
+            "https://dt.example/api/v1/doc/document/?title__icontains=%E5%9B%BD%E9%99%85%E5%8C%96\
+             &type__in=rfc%2Cdraft&limit=25&abstract__icontains=a%3Db%26c+d&format=json"
+        );
+        assert_eq!(terms.extra, ["x+y/z"]);
+        assert!(search_url(&search_params("x+y/z", None, 25, &options))
+            .contains("?title__icontains=x%2By%2Fz&"));
+    }
+
+    #[test]
+    fn names_are_chunked_by_count_and_length() {
+        let rfcs: Vec<String> = (1..=120).map(|n| format!("rfc{}", n)).collect();
+        let mut names: Vec<&str> = rfcs.iter().map(String::as_str).collect();
+        names.push("rfc1");
+        let sizes: Vec<usize> = name_chunks(&names).iter().map(Vec::len).collect();
+        assert_eq!(sizes, [50, 50, 20]);
+
+        let long = "draft-".to_string() + &"x".repeat(494);
+        let sizes: Vec<usize> = name_chunks(&[&long, &long, "rfc1", &long[..499], &long[..498]])
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(sizes, [3, 1]);
+        assert!(name_chunks(&[]).is_empty());
+    }
+
+    #[test]
+    fn overlong_queries_are_rejected() {
+        let options = SearchOptions::default();
+        assert!(SearchTerms::new(&"q".repeat(MAX_QUERY_LEN), &options).is_ok());
+        let err = SearchTerms::new(&"é".repeat(MAX_QUERY_LEN + 1), &options)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Search query is 513 characters long; at most 512 are allowed"
+        );
+    }
+
+    #[test]
+    fn parse_api_date_variants() {
+        let expected = NaiveDate::from_ymd_opt(2021, 5, 27);
+        assert_eq!(parse_api_date("2021-05-27T18:07:41Z"), expected);
+        assert_eq!(parse_api_date("2021-05-27T11:07:41.123-07:00"), expected);
+        assert_eq!(parse_api_date("2021-05-27"), expected);
+        assert_eq!(parse_api_date("2021-05"), None);
+        assert_eq!(parse_api_date("not a date"), None);
+    }
+
+    #[test]
+    fn builder_rejects_unusable_rate_limits() {
+        for rate in [0.0, -2.0, f64::NAN, 1e-300] {
+            let err = DataTrackerClient::builder()
+                .rate_limit(rate)
+                .build()
+                .err()
+                .unwrap_or_else(|| panic!("{} should be rejected", rate));
+            assert!(err.to_string().contains("Rate limit"), "{}", err);
+        }
+        assert!(DataTrackerClient::builder().rate_limit(0.5).build().is_ok());
+    }
+
+    #[test]
+    fn builder_normalizes_base_url() {
+        let client = DataTrackerClient::builder()
+            .base_url("http://localhost:8080/")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url(), "http://localhost:8080");
+
+        let client = DataTrackerClient::builder().build().unwrap();
+        assert_eq!(client.base_url(), DATATRACKER_BASE_URL);
+    }
+
+    #[test]
+    fn resource_name_takes_last_segment() {
+        assert_eq!(
+            resource_name("/api/v1/doc/document/rfc7230/"),
+            Some("rfc7230")
+        );
+        assert_eq!(
+            resource_name("/api/v1/doc/document/rfc7230"),
+            Some("rfc7230")
+        );
+        assert_eq!(resource_name(""), None);
+    }
+
+    #[test]
+    fn api_document_reads_lifecycle_fields() {
+        let api: ApiDocument = serde_json::from_str(
+            r#"{"name": "draft-ietf-quic-transport", "title": "QUIC", "abstract": "",
+                "rev": "34", "expires": "2021-07-17T07:45:15Z",
+                "states": ["/api/v1/doc/state/3/", "/api/v1/doc/state/16/"],
+                "rfc_number": null, "pages": 151, "group": "/api/v1/group/group/2161/"}"#,
+        )
+        .unwrap();
+        assert_eq!(api.rev.as_deref(), Some("34"));
+        assert_eq!(
+            api.expires.as_deref().and_then(parse_api_date),
+            NaiveDate::from_ymd_opt(2021, 7, 17)
+        );
+        assert_eq!(api.states.len(), 2);
+        assert_eq!(api.rfc_number, None);
+    }
+
+    #[test]
+    fn doc_events_become_plain_text() {
+        let api: ApiDocEvent = serde_json::from_str(
+            r#"{"id": 1, "time": "2021-05-27T11:07:41-07:00", "type": "published_rfc",
+                "desc": "RFC published as <a href=\"/doc/rfc9000/\">RFC 9000</a> &amp; done",
+                "rev": "34", "by": "/api/v1/person/person/1/"}"#,
+        )
+        .unwrap();
+        let event = DocEvent::from_api(api).unwrap();
+        assert_eq!(
+            event.time,
+            DateTime::parse_from_rfc3339("2021-05-27T18:07:41Z").unwrap()
+        );
+        assert_eq!(event.event_type, "published_rfc");
+        assert_eq!(event.description, "RFC published as RFC 9000 & done");
+        assert_eq!(event.rev.as_deref(), Some("34"));
+    }
+
+    #[test]
+    fn api_times_without_offsets_are_utc() {
+        assert_eq!(
+            parse_api_time("2021-05-27T18:07:41.123"),
+            DateTime::parse_from_rfc3339("2021-05-27T18:07:41.123Z")
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        );
+        assert_eq!(parse_api_time("yesterday"), None);
+    }
+
+    #[test]
+    fn next_page_limit_is_replaced() {
+        assert_eq!(
+            with_limit("/api/v1/doc/docevent/?doc__name=x&limit=100&offset=100", 20),
+            "/api/v1/doc/docevent/?doc__name=x&limit=20&offset=100"
+        );
+    }
+
+    #[test]
+    fn api_state_type_is_a_resource_uri() {
+        let state: ApiState = serde_json::from_str(
+            r#"{"id": 16, "name": "RFC Ed Queue", "slug": "rfcqueue", "order": 31,
+                "type": "/api/v1/doc/statetype/draft-iesg/", "used": true}"#,
+        )
+        .unwrap();
+        assert_eq!(state.name, "RFC Ed Queue");
+        assert_eq!(resource_name(&state.state_type), Some("draft-iesg"));
+    }
+
+    #[test]
+    fn api_document_time_maps_to_published() {
+        let api: ApiDocument = serde_json::from_str(
+            r#"{"name": "rfc9000", "title": "QUIC", "abstract": null,
+                "time": "2021-05-27T18:07:41Z"}"#,
+        )
+        .unwrap();
+        let doc = Document::from(api);
+        assert_eq!(doc.published, NaiveDate::from_ymd_opt(2021, 5, 27));
+        assert_eq!(doc.doc_type, DocumentType::Rfc(9000));
     }
 }
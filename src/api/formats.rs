@@ -0,0 +1,142 @@
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Format;
+
+/// A rendering a document may be published in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rendering {
+    #[serde(rename = "txt")]
+    Text,
+    Html,
+    Xml,
+    Pdf,
+}
+
+impl Rendering {
+    /// Every rendering, in the order they're probed.
+    pub const ALL: [Rendering; 4] = [
+        Rendering::Text,
+        Rendering::Html,
+        Rendering::Xml,
+        Rendering::Pdf,
+    ];
+
+    /// The name used in the config and output: `txt`, `html`, `xml`, `pdf`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rendering::Text => "txt",
+            Rendering::Html => "html",
+            Rendering::Xml => "xml",
+            Rendering::Pdf => "pdf",
+        }
+    }
+
+    /// The format [`DocumentFetcher::fetch`](super::DocumentFetcher::fetch)
+    /// downloads this rendering as. XML and PDF can only be probed for now.
+    pub fn format(self) -> Option<Format> {
+        match self {
+            Rendering::Text => Some(Format::Text),
+            Rendering::Html => Some(Format::Html),
+            Rendering::Xml | Rendering::Pdf => None,
+        }
+    }
+}
+
+impl From<Format> for Rendering {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Text => Rendering::Text,
+            Format::Html => Rendering::Html,
+        }
+    }
+}
+
+impl fmt::Display for Rendering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether a rendering exists, as found by a HEAD request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Availability {
+    Available,
+    /// The server said 404 (or 410)
+    Missing,
+    /// The request failed or got some other answer
+    Unknown,
+}
+
+/// What probing one rendering of a document found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatProbe {
+    pub rendering: Rendering,
+    pub url: String,
+    pub availability: Availability,
+}
+
+/// The order [`DocumentFetcher::fetch`](super::DocumentFetcher::fetch)
+/// tries renderings in. Renderings it can't download (see
+/// [`Rendering::format`]) are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatPreference {
+    pub order: Vec<Rendering>,
+}
+
+impl Default for FormatPreference {
+    /// Plain text, then HTML.
+    fn default() -> Self {
+        Self {
+            order: vec![Rendering::Text, Rendering::Html],
+        }
+    }
+}
+
+impl FormatPreference {
+    /// The formats to fetch, most preferred first, without repeats.
+    pub fn fetch_order(&self) -> Vec<Format> {
+        let mut formats: Vec<Format> = Vec::new();
+        for format in self.order.iter().filter_map(|r| r.format()) {
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        formats
+    }
+
+    /// Fail unless the order names something that can be fetched.
+    pub fn validate(&self) -> Result<()> {
+        if self.fetch_order().is_empty() {
+            anyhow::bail!("format_preference must include txt or html");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_order_skips_renderings_that_cannot_be_fetched() {
+        let preference = FormatPreference {
+            order: vec![Rendering::Xml, Rendering::Html, Rendering::Text],
+        };
+        assert_eq!(preference.fetch_order(), [Format::Html, Format::Text]);
+        assert!(preference.validate().is_ok());
+
+        let unusable = FormatPreference {
+            order: vec![Rendering::Pdf],
+        };
+        assert!(unusable.validate().is_err());
+        assert_eq!(
+            FormatPreference::default().fetch_order(),
+            [Format::Text, Format::Html]
+        );
+    }
+}
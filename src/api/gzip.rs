@@ -0,0 +1,96 @@
+//! Bodies that arrive gzip-compressed without a `Content-Encoding` saying
+//! so, such as a mirror's `rfc9000.txt.gz`. Bodies sent with one (gzip,
+//! deflate, or brotli) are decoded by the HTTP client before they get
+//! here, so only the gzip magic number tells these apart.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+
+/// The first two bytes of every gzip stream.
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// `body`, decompressed if it's gzip. A corrupt stream is an error
+/// naming `url`.
+pub fn gunzip_body(url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+    if !body.starts_with(&MAGIC) {
+        return Ok(body);
+    }
+    let mut decoded = Vec::with_capacity(body.len() * 4);
+    MultiGzDecoder::new(body.as_slice())
+        .read_to_end(&mut decoded)
+        .with_context(|| format!("Failed to decompress {}", url))?;
+    Ok(decoded)
+}
+
+/// Decompress the file at `path` in place if it's gzip, streaming it
+/// through a sibling `.gunzip` file.
+pub fn gunzip_file(url: &str, path: &Path) -> Result<()> {
+    let mut head = [0; 2];
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    if file.read_exact(&mut head).is_err() || head != MAGIC {
+        return Ok(());
+    }
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".gunzip");
+    let staged = Path::new(&staged);
+    let decoded = File::create(staged)
+        .and_then(|mut out| io::copy(&mut MultiGzDecoder::new(file), &mut out))
+        .with_context(|| format!("Failed to decompress {}", url));
+    if let Err(e) = decoded {
+        let _ = fs::remove_file(staged);
+        return Err(e);
+    }
+    fs::rename(staged, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn gzip_bodies_are_decompressed_and_others_kept() {
+        let text = b"Request for Comments: 9000\n".repeat(40);
+        assert_eq!(gunzip_body("u", gzip(&text)).unwrap(), text);
+        assert_eq!(gunzip_body("u", text.clone()).unwrap(), text);
+        assert_eq!(gunzip_body("u", Vec::new()).unwrap(), b"");
+
+        let mut truncated = gzip(&text);
+        truncated.truncate(20);
+        let err = gunzip_body("http://x/rfc9000.txt.gz", truncated).unwrap_err();
+        assert!(err.to_string().contains("rfc9000.txt.gz"), "{}", err);
+    }
+
+    #[test]
+    fn gzip_files_are_decompressed_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = b"Request for Comments: 9000\n".repeat(40);
+        let path = dir.path().join("rfc9000.txt.part");
+
+        fs::write(&path, gzip(&text)).unwrap();
+        gunzip_file("u", &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), text);
+        gunzip_file("u", &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), text);
+
+        fs::write(&path, &gzip(&text)[..20]).unwrap();
+        assert!(gunzip_file("u", &path).is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
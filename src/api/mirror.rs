@@ -0,0 +1,43 @@
+use super::IETF_ARCHIVE_BASE_URL;
+
+/// Where document content is fetched from when the usual hosts fail.
+///
+/// A mirror is a base URL serving the same paths as the usual hosts
+/// (`/rfc/rfc9000.txt`, `/rfc/rfc9000.html`, `/archive/id/<draft>.txt`,
+/// `/doc/html/<draft>`). They're only tried after a connection error or a
+/// 5xx; a 404 means the document (in that format) isn't there to find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorSettings {
+    /// Base URLs tried in order after the usual hosts
+    pub fallbacks: Vec<String>,
+    /// Fetch from this base only, skipping the usual hosts and fallbacks
+    pub forced: Option<String>,
+}
+
+impl Default for MirrorSettings {
+    /// www.ietf.org, which republishes the RFC Editor's `/rfc/` tree.
+    fn default() -> Self {
+        Self {
+            fallbacks: vec![IETF_ARCHIVE_BASE_URL.to_string()],
+            forced: None,
+        }
+    }
+}
+
+impl MirrorSettings {
+    /// No mirrors: only the usual hosts are tried.
+    pub fn none() -> Self {
+        Self {
+            fallbacks: Vec::new(),
+            forced: None,
+        }
+    }
+
+    /// Fetch from `url` alone when one is given.
+    pub fn with_forced(mut self, url: Option<String>) -> Self {
+        if url.is_some() {
+            self.forced = url;
+        }
+        self
+    }
+}
@@ -1,22 +1,95 @@
 mod datatracker;
+mod formats;
+mod gzip;
+mod mirror;
+mod progress;
+mod proxy;
 mod rfc_editor;
+mod rfc_index;
+mod sniff;
+mod source;
+mod throttle;
 
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use reqwest::Client;
 
-pub use datatracker::{DataTrackerClient, DATATRACKER_BASE_URL};
-pub use rfc_editor::DocumentFetcher;
+pub use datatracker::{
+    DataTrackerClient, DataTrackerClientBuilder, DATATRACKER_BASE_URL, MAX_QUERY_LEN,
+    NAMES_PER_REQUEST, SEARCH_PAGE_SIZE,
+};
+pub use formats::{Availability, FormatPreference, FormatProbe, Rendering};
+pub use mirror::MirrorSettings;
+pub use progress::DownloadObserver;
+pub use proxy::ProxySettings;
+pub use rfc_editor::{
+    DocumentFetcher, DocumentFetcherBuilder, DocumentNotFound, IETF_ARCHIVE_BASE_URL,
+    RFC_EDITOR_BASE_URL,
+};
+pub use rfc_index::{parse_rfc_index, RfcIndexDownload};
+#[cfg(any(test, feature = "test-util"))]
+pub use source::MapSource;
+pub use source::{DocumentSource, FetchedDocument, Provenance};
+pub use throttle::{RateLimiter, DEFAULT_RATE_LIMIT, MAX_RETRY_AFTER, MIN_RATE_LIMIT};
 
-/// Build the shared HTTP client used by every API wrapper.
+/// User-agent sent when the caller doesn't supply one.
+pub const DEFAULT_USER_AGENT: &str = concat!("rfc-cli/", env!("CARGO_PKG_VERSION"));
+
+/// [`DEFAULT_USER_AGENT`] with a way to reach whoever runs it, as IETF
+/// operators ask of automated clients: `rfc-cli/0.3 (+mailto:me@example.com)`.
+/// A bare email address gets `mailto:`; URLs are used as given.
+///
+/// ```
+/// use rfc::api::{user_agent_with_contact, DEFAULT_USER_AGENT};
+///
+/// assert_eq!(
+///     user_agent_with_contact("https://example.com/bot"),
+///     format!("{} (+https://example.com/bot)", DEFAULT_USER_AGENT)
+/// );
+/// ```
+pub fn user_agent_with_contact(contact: &str) -> String {
+    let contact = contact.trim();
+    if contact.contains('@') && !contact.contains(':') {
+        format!("{} (+mailto:{})", DEFAULT_USER_AGENT, contact)
+    } else {
+        format!("{} (+{})", DEFAULT_USER_AGENT, contact)
+    }
+}
+
+/// Per-request timeout used when the caller doesn't supply one.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Build an HTTP client with the default user-agent and timeout, and
+/// proxies from the environment.
 ///
-/// All callers want the same user-agent and timeout, so creating a fresh
-/// `reqwest::Client` per call would just rebuild the connection pool.
+/// Share it between clients where possible: each `reqwest::Client` has a
+/// connection pool of its own.
 pub fn build_http_client() -> Result<Client> {
-    Client::builder()
-        .user_agent(concat!("rfc-cli/", env!("CARGO_PKG_VERSION")))
-        .timeout(Duration::from_secs(30))
+    build_http_client_with(
+        DEFAULT_USER_AGENT,
+        DEFAULT_TIMEOUT,
+        &ProxySettings::from_env(),
+    )
+}
+
+/// Build an HTTP client with a specific user-agent, timeout, and proxy
+/// settings. Used by the client builders when the caller overrides any.
+pub fn build_http_client_with(
+    user_agent: &str,
+    timeout: Duration,
+    proxy: &ProxySettings,
+) -> Result<Client> {
+    reqwest::header::HeaderValue::from_str(user_agent)
+        .with_context(|| format!("Invalid User-Agent '{}'", user_agent))?;
+    let builder = Client::builder().user_agent(user_agent).timeout(timeout);
+    proxy
+        .apply(builder)?
         .build()
         .context("Failed to create HTTP client")
 }
+
+/// Strip trailing slashes so base URLs can be joined with `/path`.
+fn normalize_base_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
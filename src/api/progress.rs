@@ -0,0 +1,14 @@
+/// Receives download progress from [`DocumentFetcher`](super::DocumentFetcher)
+/// as the response body is read in chunks. Attach one with
+/// `DocumentFetcher::with_observer`.
+pub trait DownloadObserver: Send + Sync {
+    /// A response body is about to be read. `total` is the
+    /// `Content-Length`, when the server sent one.
+    fn started(&self, url: &str, total: Option<u64>);
+
+    /// Another `bytes` have arrived.
+    fn advanced(&self, bytes: u64);
+
+    /// The body has been read completely (or the read failed).
+    fn finished(&self);
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+
+/// Proxy configuration for outgoing requests.
+///
+/// Usually built from the environment (`HTTPS_PROXY`, `HTTP_PROXY`,
+/// `NO_PROXY`, upper or lower case) with [`ProxySettings::from_env`], then
+/// overridden by an explicit URL from the command line or config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxySettings {
+    /// Proxy for every request, taking precedence over `https`/`http`
+    pub all: Option<String>,
+    /// Proxy for `https://` requests
+    pub https: Option<String>,
+    /// Proxy for `http://` requests
+    pub http: Option<String>,
+    /// Comma-separated hosts/domains/CIDRs to connect to directly
+    pub no_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    /// Read the conventional proxy environment variables.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// [`from_env`](Self::from_env) over an arbitrary variable lookup.
+    /// Lower-case names win, as with curl; empty values count as unset.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| {
+            [name.to_lowercase(), name.to_string()]
+                .iter()
+                .find_map(|n| lookup(n).filter(|v| !v.trim().is_empty()))
+        };
+        Self {
+            all: None,
+            https: var("HTTPS_PROXY"),
+            http: var("HTTP_PROXY"),
+            no_proxy: var("NO_PROXY"),
+        }
+    }
+
+    /// Send every request through `url` when one is given, leaving
+    /// `NO_PROXY` exclusions in place.
+    pub fn with_override(mut self, url: Option<String>) -> Self {
+        if let Some(url) = url {
+            self.all = Some(url);
+        }
+        self
+    }
+
+    /// Whether any proxy is configured.
+    pub fn is_empty(&self) -> bool {
+        self.all.is_none() && self.https.is_none() && self.http.is_none()
+    }
+
+    /// Check every configured URL, so a typo fails up front rather than
+    /// on the first request.
+    pub fn validate(&self) -> Result<()> {
+        self.proxies().map(|_| ())
+    }
+
+    /// Configure `builder` with these settings. With nothing configured,
+    /// reqwest's own defaults stay in effect.
+    pub(super) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder> {
+        if self.is_empty() {
+            return Ok(builder);
+        }
+        builder = builder.no_proxy();
+        for proxy in self.proxies()? {
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder)
+    }
+
+    fn proxies(&self) -> Result<Vec<Proxy>> {
+        let no_proxy = || self.no_proxy.as_deref().and_then(NoProxy::from_string);
+        let parse = |url: &str, make: fn(&str) -> reqwest::Result<Proxy>| {
+            make(url)
+                .map(|p| p.no_proxy(no_proxy()))
+                .with_context(|| format!("Invalid proxy URL '{}'", url))
+        };
+
+        let mut proxies = Vec::new();
+        if let Some(url) = &self.all {
+            proxies.push(parse(url, |u| Proxy::all(u))?);
+        } else {
+            if let Some(url) = &self.https {
+                proxies.push(parse(url, |u| Proxy::https(u))?);
+            }
+            if let Some(url) = &self.http {
+                proxies.push(parse(url, |u| Proxy::http(u))?);
+            }
+        }
+        Ok(proxies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn reads_both_cases_preferring_lower() {
+        let settings = ProxySettings::from_lookup(env(&[
+            ("HTTPS_PROXY", "http://upper:3128"),
+            ("https_proxy", "http://lower:3128"),
+            ("HTTP_PROXY", "http://plain:3128"),
+            ("NO_PROXY", "localhost,.internal"),
+        ]));
+        assert_eq!(settings.https.as_deref(), Some("http://lower:3128"));
+        assert_eq!(settings.http.as_deref(), Some("http://plain:3128"));
+        assert_eq!(settings.no_proxy.as_deref(), Some("localhost,.internal"));
+        assert_eq!(settings.all, None);
+    }
+
+    #[test]
+    fn empty_environment_means_no_proxy() {
+        let settings = ProxySettings::from_lookup(env(&[("HTTPS_PROXY", " ")]));
+        assert!(settings.is_empty());
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn override_wins_over_environment() {
+        let settings = ProxySettings::from_lookup(env(&[("HTTPS_PROXY", "http://env:3128")]))
+            .with_override(Some("http://flag:8080".to_string()));
+        assert_eq!(settings.all.as_deref(), Some("http://flag:8080"));
+        assert_eq!(settings.proxies().unwrap().len(), 1);
+
+        let unchanged = settings.clone().with_override(None);
+        assert_eq!(unchanged, settings);
+    }
+
+    #[test]
+    fn invalid_url_is_reported() {
+        let settings = ProxySettings::default().with_override(Some("http://bad host".to_string()));
+        let err = settings.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Invalid proxy URL 'http://bad host'"));
+    }
+}
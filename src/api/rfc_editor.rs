@@ -1,9 +1,33 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
-use reqwest::Client;
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use reqwest::header::{
+    ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_RANGE, ETAG, IF_MODIFIED_SINCE, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::Instrument;
 
+use super::gzip;
+use super::sniff;
+use super::throttle::{self, check_rate_limit, RateLimiter};
+use super::{Availability, FormatPreference, FormatProbe, Rendering};
+use super::{DataTrackerClient, DownloadObserver, FetchedDocument, DATATRACKER_BASE_URL};
+use super::{MirrorSettings, ProxySettings};
 use crate::models::{DocumentType, Format};
 
+/// Where RFC text and HTML renderings are published.
+pub const RFC_EDITOR_BASE_URL: &str = "https://www.rfc-editor.org";
+
+/// Where Internet-Draft plain text is archived.
+pub const IETF_ARCHIVE_BASE_URL: &str = "https://www.ietf.org";
+
 #[derive(Debug, Deserialize)]
 struct DraftInfo {
     rev: Option<String>,
@@ -13,9 +37,271 @@ struct DraftInfo {
 ///
 /// Talks primarily to rfc-editor.org and ietf.org/archive, with a side
 /// trip to datatracker.ietf.org to resolve `-NN` version suffixes for
-/// drafts the user supplied unversioned.
+/// drafts the user supplied unversioned. When those hosts can't be reached
+/// (or answer 5xx), the configured [`MirrorSettings`] are tried in order.
 pub struct DocumentFetcher {
-    client: Client,
+    pub(super) client: Client,
+    pub(super) rfc_editor_url: String,
+    archive_url: String,
+    datatracker_url: String,
+    mirrors: MirrorSettings,
+    /// Formats `fetch` tries, in order
+    formats: Vec<Format>,
+    pub(super) limiter: Arc<RateLimiter>,
+    observer: Option<Arc<dyn DownloadObserver>>,
+}
+
+/// A document download that has started: the response with its body
+/// unread, and where it came from.
+struct Opened {
+    url: String,
+    /// The mirror serving it, if not one of the usual hosts
+    mirror: Option<String>,
+    response: Response,
+    format: Format,
+}
+
+/// One attempt of [`DocumentFetcher::fetch_to`]: the response, and the
+/// offset in the `.part` file its body goes at.
+struct Attempt {
+    url: String,
+    format: Format,
+    /// `None` if this download can't be resumed
+    partial: Option<PartialDownload>,
+    response: Response,
+    offset: u64,
+}
+
+/// How many times [`DocumentFetcher::fetch_to`] downloads before giving
+/// up, resuming where the last attempt stopped when the server allows.
+const DOWNLOAD_ATTEMPTS: usize = 3;
+
+/// What a resumable download in progress came from, kept beside its
+/// `.part` file so a retry, or a later `fetch_to`, can ask for the rest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PartialDownload {
+    url: String,
+    format: Format,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// The full length, from `Content-Length` or `Content-Range`
+    total: Option<u64>,
+}
+
+impl PartialDownload {
+    /// The download `response` (a 200) starts, or `None` if it can't be
+    /// resumed: the server doesn't take byte ranges, or gives nothing to
+    /// check a resumed body against.
+    fn of(url: &str, format: Format, response: &Response) -> Option<Self> {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let ranges = header(ACCEPT_RANGES).is_some_and(|v| v.eq_ignore_ascii_case("bytes"));
+        let download = Self {
+            url: url.to_string(),
+            format,
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            total: response.content_length(),
+        };
+        (ranges && (download.etag.is_some() || download.total.is_some())).then_some(download)
+    }
+
+    /// Whether `response` (a 206) is the rest of this download from
+    /// `offset`: the range starts there, and the length and ETag match.
+    fn continued_by(&self, response: &Response, offset: u64) -> bool {
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok());
+        let Some((start, total)) = header(CONTENT_RANGE).and_then(parse_content_range) else {
+            return false;
+        };
+        let etag_matches = match (&self.etag, header(ETAG)) {
+            (Some(ours), Some(theirs)) => ours == theirs,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        start == offset && self.total.is_none_or(|ours| total == Some(ours)) && etag_matches
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// `bytes 500-999/1000` → `(500, Some(1000))`; the total may be `*`.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+/// `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returned when every host asked said the document doesn't exist (404
+/// or 410) in every format tried, so callers can tell "never published"
+/// apart from a network failure by downcasting.
+#[derive(Debug)]
+pub struct DocumentNotFound {
+    pub doc: DocumentType,
+    /// What each format's attempt ran into
+    message: String,
+}
+
+impl std::fmt::Display for DocumentNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DocumentNotFound {}
+
+/// Why a GET didn't produce a document.
+enum Miss {
+    /// No connection, or a 5xx: somewhere else might do better.
+    Unreachable(anyhow::Error),
+    /// 404 or 410: not at this URL, though it may be under another.
+    NotFound(anyhow::Error),
+    /// The server gave some other definite answer, such as 403.
+    Refused(anyhow::Error),
+}
+
+/// Builder for [`DocumentFetcher`].
+///
+/// The fetcher talks to three hosts; each can be overridden on its own, or
+/// all at once with `base_url` (handy for a single mock server, and which
+/// also drops the default mirrors). As with
+/// `DataTrackerClientBuilder`, `timeout`, `user_agent`, and `proxy` are
+/// ignored when an HTTP client is injected.
+#[derive(Debug, Default)]
+pub struct DocumentFetcherBuilder {
+    rfc_editor_url: Option<String>,
+    archive_url: Option<String>,
+    datatracker_url: Option<String>,
+    mirrors: Option<MirrorSettings>,
+    formats: Option<FormatPreference>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    client: Option<Client>,
+    rate_limit: Option<f64>,
+    proxy: Option<ProxySettings>,
+}
+
+impl DocumentFetcherBuilder {
+    /// Use one base URL for every host the fetcher talks to, with no
+    /// mirrors unless [`mirrors`](Self::mirrors) adds some.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        let url = url.into();
+        self.mirrors.get_or_insert_with(MirrorSettings::none);
+        self.rfc_editor_url(url.clone())
+            .archive_url(url.clone())
+            .datatracker_url(url)
+    }
+
+    /// Base URL for RFC text/HTML (default [`RFC_EDITOR_BASE_URL`]).
+    pub fn rfc_editor_url(mut self, url: impl Into<String>) -> Self {
+        self.rfc_editor_url = Some(url.into());
+        self
+    }
+
+    /// Base URL for draft plain text (default [`IETF_ARCHIVE_BASE_URL`]).
+    pub fn archive_url(mut self, url: impl Into<String>) -> Self {
+        self.archive_url = Some(url.into());
+        self
+    }
+
+    /// Base URL for draft HTML and revision lookups (default
+    /// [`DATATRACKER_BASE_URL`]).
+    pub fn datatracker_url(mut self, url: impl Into<String>) -> Self {
+        self.datatracker_url = Some(url.into());
+        self
+    }
+
+    /// Where to fetch content when the usual hosts fail (default
+    /// [`MirrorSettings::default`]).
+    pub fn mirrors(mut self, mirrors: MirrorSettings) -> Self {
+        self.mirrors = Some(mirrors);
+        self
+    }
+
+    /// The order formats are tried in (default
+    /// [`FormatPreference::default`]).
+    pub fn format_preference(mut self, preference: FormatPreference) -> Self {
+        self.formats = Some(preference);
+        self
+    }
+
+    /// Per-request timeout (default [`super::DEFAULT_TIMEOUT`]).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// User-agent header (default [`super::DEFAULT_USER_AGENT`]; see
+    /// [`super::user_agent_with_contact`]).
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Proxy settings (default [`ProxySettings::from_env`]).
+    pub fn proxy(mut self, proxy: ProxySettings) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Reuse an existing HTTP client instead of building one.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Average requests per second across all hosts (default
+    /// [`super::DEFAULT_RATE_LIMIT`], at least [`super::MIN_RATE_LIMIT`]);
+    /// `f64::INFINITY` disables limiting.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(per_second);
+        self
+    }
+
+    pub fn build(self) -> Result<DocumentFetcher> {
+        let rate_limit = check_rate_limit(self.rate_limit.unwrap_or(super::DEFAULT_RATE_LIMIT))?;
+        let client = match self.client {
+            Some(client) => client,
+            None => super::build_http_client_with(
+                self.user_agent
+                    .as_deref()
+                    .unwrap_or(super::DEFAULT_USER_AGENT),
+                self.timeout.unwrap_or(super::DEFAULT_TIMEOUT),
+                &self.proxy.unwrap_or_else(ProxySettings::from_env),
+            )?,
+        };
+        let base = |url: Option<String>, default: &str| {
+            super::normalize_base_url(url.as_deref().unwrap_or(default))
+        };
+        Ok(DocumentFetcher {
+            client,
+            rfc_editor_url: base(self.rfc_editor_url, RFC_EDITOR_BASE_URL),
+            archive_url: base(self.archive_url, IETF_ARCHIVE_BASE_URL),
+            datatracker_url: base(self.datatracker_url, DATATRACKER_BASE_URL),
+            mirrors: normalize_mirrors(self.mirrors.unwrap_or_default()),
+            formats: fetch_order(self.formats.unwrap_or_default()),
+            limiter: Arc::new(RateLimiter::new(rate_limit)),
+            observer: None,
+        })
+    }
 }
 
 impl DocumentFetcher {
@@ -28,32 +314,401 @@ impl DocumentFetcher {
     /// client back both this and `DataTrackerClient` so we don't pay for
     /// two connection pools per command invocation.
     pub fn with_client(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            rfc_editor_url: RFC_EDITOR_BASE_URL.to_string(),
+            archive_url: IETF_ARCHIVE_BASE_URL.to_string(),
+            datatracker_url: DATATRACKER_BASE_URL.to_string(),
+            mirrors: normalize_mirrors(MirrorSettings::default()),
+            formats: fetch_order(FormatPreference::default()),
+            limiter: Arc::default(),
+            observer: None,
+        }
     }
 
-    /// Fetch a document, preferring plain text and falling back to HTML.
+    /// Start configuring a fetcher with custom hosts, timeout, etc.
+    pub fn builder() -> DocumentFetcherBuilder {
+        DocumentFetcherBuilder::default()
+    }
+
+    /// Report body download progress of every document fetch to
+    /// `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn DownloadObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// A Datatracker client for the metadata lookups that go with a fetch,
+    /// sharing this fetcher's HTTP client (and so its connections),
+    /// Datatracker host, and rate limiter.
+    pub fn datatracker(&self) -> DataTrackerClient {
+        DataTrackerClient {
+            client: self.client.clone(),
+            base_url: self.datatracker_url.clone(),
+            limiter: Arc::clone(&self.limiter),
+        }
+    }
+
+    /// Fetch a document in the first format of the [`FormatPreference`]
+    /// that's available: plain text, else HTML, by default.
     ///
     /// Drafts without a version suffix are resolved to their latest
     /// revision via datatracker before fetching.
-    pub async fn fetch(&self, doc: &DocumentType) -> Result<(String, Format)> {
+    ///
+    /// A body served as text must look like the document: an HTML page
+    /// of it is returned as [`Format::Html`], and anything else (a captive
+    /// portal, a proxy's error page, a truncated file) is an error rather
+    /// than something to cache.
+    ///
+    /// Compressed bodies are decompressed: those sent with a
+    /// `Content-Encoding` of gzip, deflate, or brotli, and gzip files
+    /// served as they are, such as the `.txt.gz` tried when a host has no
+    /// `.txt`.
+    pub async fn fetch(&self, doc: &DocumentType) -> Result<FetchedDocument> {
+        self.fetch_if_modified(doc, None)
+            .await?
+            .context("Server reported the document unchanged")
+    }
+
+    /// Like [`fetch`](Self::fetch), but sends `If-Modified-Since` when
+    /// `since` is given and returns `None` if the server answers 304.
+    pub async fn fetch_if_modified(
+        &self,
+        doc: &DocumentType,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<FetchedDocument>> {
+        let span = tracing::info_span!("fetch", document = %doc);
+        let Some(Opened {
+            url,
+            mirror,
+            response,
+            format,
+        }) = self.open(doc, since).instrument(span.clone()).await?
+        else {
+            return Ok(None);
+        };
+        let retrieved_at = Utc::now();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED)
+            .and_then(|v| DateTime::parse_from_rfc2822(&v).ok())
+            .map(|t| t.with_timezone(&Utc));
+        let mut body = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
+        self.stream_body(&url, response, |chunk| {
+            body.extend_from_slice(chunk);
+            Ok(())
+        })
+        .instrument(span)
+        .await?;
+        let body = gzip::gunzip_body(&url, body)?;
+        let content = String::from_utf8_lossy(&body).into_owned();
+        let format = match format {
+            Format::Text => sniff::check_text(doc, &url, &content)?,
+            format => format,
+        };
+        Ok(Some(FetchedDocument {
+            content,
+            format,
+            source_url: url,
+            mirror,
+            retrieved_at,
+            etag,
+            last_modified,
+        }))
+    }
+
+    /// Stream a document straight to `path` without holding it in memory,
+    /// returning the format that was downloaded. The body is written to a
+    /// `.part` file next to `path` and renamed into place once complete,
+    /// so `path` never holds a partial download, and only after it's been
+    /// decompressed and checked as [`fetch`](Self::fetch) does.
+    ///
+    /// When the connection drops mid-body and the server takes byte
+    /// ranges (`Accept-Ranges: bytes`), the download is picked up where it
+    /// stopped, up to three times. The `.part` file and a
+    /// `.part.json` describing it are kept if it still fails, so the next
+    /// `fetch_to` resumes too. A resumed body must continue the same
+    /// length and ETag; otherwise the download starts over.
+    pub async fn fetch_to(&self, doc: &DocumentType, path: &Path) -> Result<Format> {
+        let span = tracing::info_span!("fetch", document = %doc);
+        let part = with_suffix(path, ".part");
+        let state = with_suffix(path, ".part.json");
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let resumed = self.resume(&part, &state).instrument(span.clone()).await;
+            let Attempt {
+                url,
+                format,
+                partial,
+                response,
+                offset,
+            } = match resumed {
+                Some(attempt) => attempt,
+                None => {
+                    let Opened {
+                        url,
+                        response,
+                        format,
+                        ..
+                    } = self
+                        .open(doc, None)
+                        .instrument(span.clone())
+                        .await?
+                        .context("Server reported the document unchanged")?;
+                    Attempt {
+                        partial: PartialDownload::of(&url, format, &response),
+                        url,
+                        format,
+                        response,
+                        offset: 0,
+                    }
+                }
+            };
+            match &partial {
+                Some(partial) => partial.save(&state)?,
+                None => {
+                    let _ = fs::remove_file(&state);
+                }
+            }
+
+            let file = if offset > 0 {
+                File::options().append(true).open(&part)
+            } else {
+                File::create(&part)
+            };
+            let mut file = BufWriter::new(
+                file.with_context(|| format!("Failed to create {}", part.display()))?,
+            );
+            let written = self
+                .stream_body(&url, response, |chunk| file.write_all(chunk))
+                .instrument(span.clone())
+                .await
+                .and_then(|()| file.flush().context("Failed to write document"));
+            drop(file);
+
+            match written {
+                Ok(()) => {
+                    let _ = fs::remove_file(&state);
+                    let format = gzip::gunzip_file(&url, &part).and_then(|()| match format {
+                        Format::Text => sniff::check_file(doc, &url, &part),
+                        format => Ok(format),
+                    });
+                    let format = format.inspect_err(|_| {
+                        let _ = fs::remove_file(&part);
+                    })?;
+                    fs::rename(&part, path).with_context(|| {
+                        format!("Failed to move download to {}", path.display())
+                    })?;
+                    return Ok(format);
+                }
+                // Only a dropped connection is worth resuming; the
+                // partial file is kept for a later call either way.
+                Err(e) if partial.is_some() && e.downcast_ref::<reqwest::Error>().is_some() => {
+                    if attempt >= DOWNLOAD_ATTEMPTS {
+                        return Err(e);
+                    }
+                }
+                Err(e) => {
+                    let _ = fs::remove_file(&part);
+                    let _ = fs::remove_file(&state);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Ask for the rest of the download `state` describes, which has
+    /// reached the length of `part`. `None` means start over: there's
+    /// nothing to resume, or the server's answer doesn't continue it. A
+    /// server ignoring the range sends the whole document, which is used
+    /// as a fresh start (offset 0).
+    async fn resume(&self, part: &Path, state: &Path) -> Option<Attempt> {
+        let download = PartialDownload::load(state)?;
+        let offset = fs::metadata(part).ok()?.len();
+        if offset == 0 {
+            return None;
+        }
+        // `part` holds the body as decoded, which is the unencoded
+        // representation the range has to be counted in.
+        let mut request = self
+            .client
+            .get(&download.url)
+            .header(RANGE, format!("bytes={}-", offset))
+            .header(ACCEPT_ENCODING, "identity");
+        if let Some(validator) = download.etag.as_ref().or(download.last_modified.as_ref()) {
+            request = request.header(IF_RANGE, validator);
+        }
+        let response = throttle::send(&self.limiter, request).await.ok()?;
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT if download.continued_by(&response, offset) => {
+                Some(Attempt {
+                    url: download.url.clone(),
+                    format: download.format,
+                    partial: Some(download),
+                    response,
+                    offset,
+                })
+            }
+            StatusCode::OK => Some(Attempt {
+                partial: PartialDownload::of(&download.url, download.format, &response),
+                url: download.url,
+                format: download.format,
+                response,
+                offset: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Start downloading a document in the first preferred format that's
+    /// available. `None` if a conditional request came back 304.
+    async fn open(
+        &self,
+        doc: &DocumentType,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<Opened>> {
         let doc = self.resolve_draft_version(doc).await?;
 
-        let text_url = self.text_url(&doc);
-        match self.fetch_content(&text_url).await {
-            Ok(content) => Ok((content, Format::Text)),
-            Err(text_err) => {
-                let html_url = self.html_url(&doc);
-                let content = self.fetch_content(&html_url).await.with_context(|| {
-                    format!(
-                        "Plain text fetch failed ({}); HTML fallback also failed",
-                        text_err
-                    )
-                })?;
-                Ok((content, Format::Html))
+        let mut failures = Vec::new();
+        let mut missing = true;
+        for &format in &self.formats {
+            let e = match self.open_format(&doc, format, since).await {
+                Ok(opened) => return Ok(opened),
+                Err(Miss::NotFound(e)) => e,
+                Err(Miss::Unreachable(e) | Miss::Refused(e)) => {
+                    missing = false;
+                    e
+                }
+            };
+            failures.push(format!("{} fetch failed ({})", Rendering::from(format), e));
+        }
+        let message = failures.join("; ");
+        if missing && !failures.is_empty() {
+            return Err(DocumentNotFound { doc, message }.into());
+        }
+        anyhow::bail!("{}", message)
+    }
+
+    /// Check which renderings of `doc` exist with a HEAD request each.
+    /// Failures never fail the probe; they leave that rendering
+    /// [`Availability::Unknown`]. Drafts are probed at their latest
+    /// revision.
+    pub async fn probe_formats(&self, doc: &DocumentType) -> Vec<FormatProbe> {
+        let resolved = self.resolve_draft_version(doc).await.ok();
+        let mut probes = Vec::new();
+        for rendering in Rendering::ALL {
+            let url = self.rendering_url(resolved.as_ref().unwrap_or(doc), rendering);
+            let availability = match &resolved {
+                Some(_) => self.head(&url).await,
+                None => Availability::Unknown,
+            };
+            probes.push(FormatProbe {
+                rendering,
+                url,
+                availability,
+            });
+        }
+        probes
+    }
+
+    /// HEAD `url`: 2xx is available, 404 or 410 missing, anything else
+    /// unknown.
+    async fn head(&self, url: &str) -> Availability {
+        match throttle::send(&self.limiter, self.client.head(url)).await {
+            Ok(response) if response.status().is_success() => Availability::Available,
+            Ok(response)
+                if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) =>
+            {
+                Availability::Missing
             }
+            _ => Availability::Unknown,
+        }
+    }
+
+    /// Where `doc` in `rendering` is published: on the forced mirror if
+    /// there is one, else the usual hosts.
+    fn rendering_url(&self, doc: &DocumentType, rendering: Rendering) -> String {
+        match &self.mirrors.forced {
+            Some(base) => document_url(doc, rendering, base, base, base),
+            None => document_url(
+                doc,
+                rendering,
+                &self.rfc_editor_url,
+                &self.archive_url,
+                &self.datatracker_url,
+            ),
         }
     }
 
+    /// Start downloading `doc` in `format` from the first host or mirror
+    /// that can be reached. Text missing from a host is asked for there
+    /// once more as `.txt.gz`, which some mirrors keep instead.
+    async fn open_format(
+        &self,
+        doc: &DocumentType,
+        format: Format,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<Opened>, Miss> {
+        let mut unreachable = None;
+        for (url, mirror) in self.candidate_urls(doc, format) {
+            let (url, response) = match self.get(&url, since).await {
+                Ok(response) => (url, response),
+                Err(Miss::NotFound(e)) if format == Format::Text => {
+                    let gz = format!("{}.gz", url);
+                    match self.get(&gz, since).await {
+                        Ok(response) => (gz, response),
+                        Err(_) => return Err(Miss::NotFound(e)),
+                    }
+                }
+                Err(miss @ (Miss::Refused(_) | Miss::NotFound(_))) => return Err(miss),
+                Err(Miss::Unreachable(e)) => {
+                    unreachable = Some(e);
+                    continue;
+                }
+            };
+            return Ok(response.map(|response| Opened {
+                url,
+                mirror,
+                response,
+                format,
+            }));
+        }
+        Err(Miss::Unreachable(unreachable.unwrap_or_else(|| {
+            anyhow::anyhow!("No hosts to fetch from")
+        })))
+    }
+
+    /// Where `doc` in `format` can be found, in the order to try them,
+    /// each with the mirror it's on (`None` for the usual hosts).
+    fn candidate_urls(&self, doc: &DocumentType, format: Format) -> Vec<(String, Option<String>)> {
+        let on_mirror = |base: &String| {
+            (
+                document_url(doc, format.into(), base, base, base),
+                Some(base.clone()),
+            )
+        };
+        if let Some(forced) = &self.mirrors.forced {
+            return vec![on_mirror(forced)];
+        }
+        let usual = document_url(
+            doc,
+            format.into(),
+            &self.rfc_editor_url,
+            &self.archive_url,
+            &self.datatracker_url,
+        );
+        std::iter::once((usual, None))
+            .chain(self.mirrors.fallbacks.iter().map(on_mirror))
+            .collect()
+    }
+
     /// Resolve a draft name to include its latest version suffix.
     /// RFCs and already-versioned drafts pass through unchanged.
     async fn resolve_draft_version(&self, doc: &DocumentType) -> Result<DocumentType> {
@@ -64,11 +719,8 @@ impl DocumentFetcher {
                     return Ok(doc.clone());
                 }
 
-                let url = format!("https://datatracker.ietf.org/doc/{}/doc.json", name);
-                let response = self
-                    .client
-                    .get(&url)
-                    .send()
+                let url = format!("{}/doc/{}/doc.json", self.datatracker_url, name);
+                let response = throttle::send(&self.limiter, self.client.get(&url))
                     .await
                     .context("Failed to query draft info")?;
 
@@ -92,7 +744,7 @@ impl DocumentFetcher {
     /// True when `name` ends in `-` followed by ASCII digits (e.g. `-06`,
     /// `-123456`). Used to detect whether a draft name is already pinned
     /// to a specific revision.
-    fn has_version_suffix(name: &str) -> bool {
+    pub(crate) fn has_version_suffix(name: &str) -> bool {
         if let Some(last_dash) = name.rfind('-') {
             let suffix = &name[last_dash + 1..];
             !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit())
@@ -101,46 +753,122 @@ impl DocumentFetcher {
         }
     }
 
-    /// HTML URL for a document.
+    /// HTML URL for a document on the usual hosts.
     pub fn html_url(&self, doc: &DocumentType) -> String {
-        match doc {
-            DocumentType::Rfc(num) => {
-                format!("https://www.rfc-editor.org/rfc/rfc{}.html", num)
-            }
-            DocumentType::Draft(name) => {
-                format!("https://datatracker.ietf.org/doc/html/{}", name)
-            }
-        }
+        self.candidate_urls(doc, Format::Html).remove(0).0
     }
 
-    /// Plain-text URL for a document.
+    /// Plain-text URL for a document on the usual hosts.
     pub fn text_url(&self, doc: &DocumentType) -> String {
-        match doc {
-            DocumentType::Rfc(num) => {
-                format!("https://www.rfc-editor.org/rfc/rfc{}.txt", num)
-            }
-            DocumentType::Draft(name) => {
-                format!("https://www.ietf.org/archive/id/{}.txt", name)
-            }
-        }
+        self.candidate_urls(doc, Format::Text).remove(0).0
     }
 
-    async fn fetch_content(&self, url: &str) -> Result<String> {
-        let response = self
-            .client
-            .get(url)
-            .send()
+    /// GET `url`, failing on non-success statuses; `None` means a
+    /// conditional request came back 304.
+    async fn get(&self, url: &str, since: Option<DateTime<Utc>>) -> Result<Option<Response>, Miss> {
+        let mut request = self.client.get(url);
+        if let Some(since) = since {
+            request = request.header(
+                IF_MODIFIED_SINCE,
+                since.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            );
+        }
+        let response = throttle::send(&self.limiter, request)
             .await
-            .context("Failed to fetch document")?;
+            .context("Failed to fetch document")
+            .map_err(Miss::Unreachable)?;
+
+        let status = response.status();
+        if since.is_some() && status == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let e = anyhow::anyhow!("Failed to fetch {}: HTTP {}", url, status);
+            return Err(match status {
+                _ if status.is_server_error() => Miss::Unreachable(e),
+                StatusCode::NOT_FOUND | StatusCode::GONE => Miss::NotFound(e),
+                _ => Miss::Refused(e),
+            });
+        }
+        Ok(Some(response))
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch {}: HTTP {}", url, response.status());
+    /// Feed a response body to `sink` chunk by chunk, reporting progress
+    /// to the observer.
+    async fn stream_body(
+        &self,
+        url: &str,
+        mut response: Response,
+        mut sink: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> Result<()> {
+        let observer = self.observer.as_deref();
+        if let Some(o) = observer {
+            o.started(url, response.content_length());
         }
 
-        response
-            .text()
-            .await
-            .context("Failed to read document content")
+        let result = loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Err(e) = sink(&chunk) {
+                        break Err(anyhow::Error::new(e).context("Failed to write document"));
+                    }
+                    if let Some(o) = observer {
+                        o.advanced(chunk.len() as u64);
+                    }
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => {
+                    break Err(anyhow::Error::new(e).context("Failed to read document content"))
+                }
+            }
+        };
+        if let Some(o) = observer {
+            o.finished();
+        }
+        result
+    }
+}
+
+/// Where `doc` lives in `rendering`, given the base URL of each host.
+fn document_url(
+    doc: &DocumentType,
+    rendering: Rendering,
+    rfc_editor: &str,
+    archive: &str,
+    datatracker: &str,
+) -> String {
+    match (doc, rendering) {
+        (DocumentType::Rfc(num), _) => {
+            format!("{}/rfc/rfc{}.{}", rfc_editor, num, rendering.name())
+        }
+        (DocumentType::Draft(name), Rendering::Text | Rendering::Xml) => {
+            format!("{}/archive/id/{}.{}", archive, name, rendering.name())
+        }
+        (DocumentType::Draft(name), Rendering::Html) => {
+            format!("{}/doc/html/{}", datatracker, name)
+        }
+        (DocumentType::Draft(name), Rendering::Pdf) => format!("{}/doc/pdf/{}", datatracker, name),
+    }
+}
+
+/// The formats `preference` lets `fetch` try; the default order if it
+/// names none.
+fn fetch_order(preference: FormatPreference) -> Vec<Format> {
+    match preference.fetch_order() {
+        formats if formats.is_empty() => FormatPreference::default().fetch_order(),
+        formats => formats,
+    }
+}
+
+/// `mirrors` with trailing slashes stripped, like the host base URLs.
+fn normalize_mirrors(mirrors: MirrorSettings) -> MirrorSettings {
+    MirrorSettings {
+        fallbacks: mirrors
+            .fallbacks
+            .iter()
+            .map(|url| super::normalize_base_url(url))
+            .collect(),
+        forced: mirrors.forced.as_deref().map(super::normalize_base_url),
     }
 }
 
@@ -148,6 +876,17 @@ impl DocumentFetcher {
 mod tests {
     use super::*;
 
+    #[test]
+    fn content_range_start_and_total() {
+        assert_eq!(
+            parse_content_range("bytes 600-999/1000"),
+            Some((600, Some(1000)))
+        );
+        assert_eq!(parse_content_range("bytes 600-999/*"), Some((600, None)));
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+        assert_eq!(parse_content_range("bytes */1000"), None);
+    }
+
     #[test]
     fn test_rfc_urls() {
         let editor = DocumentFetcher::new().unwrap();
@@ -177,6 +916,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_builder_rejects_unusable_rate_limits() {
+        for rate in [0.0, -1.0, f64::NAN, f64::NEG_INFINITY, 1e-300] {
+            let err = DocumentFetcher::builder()
+                .rate_limit(rate)
+                .build()
+                .err()
+                .unwrap_or_else(|| panic!("{} should be rejected", rate));
+            assert!(err.to_string().contains("Rate limit"), "{}", err);
+        }
+        assert!(DocumentFetcher::builder()
+            .rate_limit(f64::INFINITY)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_builder_base_url_overrides_every_host() {
+        let fetcher = DocumentFetcher::builder()
+            .base_url("http://127.0.0.1:9999/")
+            .build()
+            .unwrap();
+        let draft = DocumentType::Draft("draft-foo-00".to_string());
+
+        assert_eq!(
+            fetcher.text_url(&DocumentType::Rfc(9000)),
+            "http://127.0.0.1:9999/rfc/rfc9000.txt"
+        );
+        assert_eq!(
+            fetcher.text_url(&draft),
+            "http://127.0.0.1:9999/archive/id/draft-foo-00.txt"
+        );
+        assert_eq!(
+            fetcher.html_url(&draft),
+            "http://127.0.0.1:9999/doc/html/draft-foo-00"
+        );
+    }
+
     #[test]
     fn test_has_version_suffix() {
         // Has version suffix
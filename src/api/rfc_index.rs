@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::header::{IF_MODIFIED_SINCE, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+use super::DocumentFetcher;
+use crate::models::{RfcIndex, RfcIndexEntry};
+
+/// Result of a (possibly conditional) index download.
+#[derive(Debug)]
+pub enum RfcIndexDownload {
+    /// The server confirmed the copy we have is current (HTTP 304).
+    NotModified,
+    Updated {
+        xml: String,
+        /// `Last-Modified` response header, for the next conditional sync
+        last_modified: Option<String>,
+    },
+}
+
+impl DocumentFetcher {
+    /// Download `rfc-index.xml` from the RFC Editor. When
+    /// `if_modified_since` is given (the `Last-Modified` from a previous
+    /// sync), an unchanged index costs only a 304.
+    pub async fn fetch_rfc_index(
+        &self,
+        if_modified_since: Option<&str>,
+    ) -> Result<RfcIndexDownload> {
+        let url = format!("{}/rfc-index.xml", self.rfc_editor_url);
+        let mut request = self.client.get(&url);
+        if let Some(since) = if_modified_since {
+            request = request.header(IF_MODIFIED_SINCE, since);
+        }
+
+        let response = super::throttle::send(&self.limiter, request)
+            .await
+            .context("Failed to fetch RFC index")?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(RfcIndexDownload::NotModified);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to fetch {}: HTTP {}", url, response.status());
+        }
+
+        let last_modified = response
+            .headers()
+            .get(LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let xml = response.text().await.context("Failed to read RFC index")?;
+
+        Ok(RfcIndexDownload::Updated { xml, last_modified })
+    }
+}
+
+/// Parse the RFC Editor's `rfc-index.xml`.
+///
+/// `rfc-entry` elements become [`RfcIndexEntry`]s and
+/// `rfc-not-issued-entry` numbers land in `not_issued`; BCP/FYI/STD
+/// entries are skipped. Unknown elements are ignored so upstream schema
+/// additions don't break syncing. `synced_at`/`last_modified` are left for
+/// the caller to fill in.
+pub fn parse_rfc_index(xml: &str) -> Result<RfcIndex> {
+    let mut reader = Reader::from_str(xml);
+    let mut index = RfcIndex::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut entry: Option<PartialEntry> = None;
+
+    loop {
+        match reader.read_event().context("Malformed RFC index XML")? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if name == "rfc-entry" {
+                    entry = Some(PartialEntry::default());
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(t) => text.push_str(&t.decode().context("Bad text in RFC index")?),
+            Event::CData(t) => text.push_str(&t.decode().context("Bad text in RFC index")?),
+            Event::GeneralRef(r) => {
+                if let Some(c) = r.resolve_char_ref().ok().flatten() {
+                    text.push(c);
+                } else if let Some(s) = r
+                    .decode()
+                    .ok()
+                    .and_then(|name| resolve_predefined_entity(&name))
+                {
+                    text.push_str(s);
+                }
+            }
+            Event::End(_) => {
+                let value = text.trim();
+                let names: Vec<&str> = path.iter().map(String::as_str).collect();
+                if let Some(pos) = names.iter().position(|n| *n == "rfc-entry") {
+                    let below = &names[pos + 1..];
+                    if below.is_empty() {
+                        if let Some(e) = entry.take().and_then(PartialEntry::finish) {
+                            index.entries.insert(e.number, e);
+                        }
+                    } else if let Some(e) = entry.as_mut() {
+                        e.apply(below, value);
+                    }
+                } else if names.ends_with(&["rfc-not-issued-entry", "doc-id"]) {
+                    if let Some(n) = parse_doc_id(value) {
+                        index.not_issued.insert(n);
+                    }
+                }
+                path.pop();
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(index)
+}
+
+/// `RFC0791` → 791. Non-RFC ids (`BCP0014`, `STD0001`) yield `None`.
+fn parse_doc_id(id: &str) -> Option<u32> {
+    id.strip_prefix("RFC")?.parse().ok()
+}
+
+/// An `rfc-entry` being assembled from its child elements.
+#[derive(Default)]
+struct PartialEntry {
+    number: Option<u32>,
+    title: Option<String>,
+    authors: Vec<String>,
+    day: Option<u32>,
+    month: Option<String>,
+    year: Option<i32>,
+    status: Option<String>,
+    stream: Option<String>,
+    wg: Option<String>,
+    page_count: Option<u32>,
+    doi: Option<String>,
+    obsoletes: Vec<u32>,
+    obsoleted_by: Vec<u32>,
+    updates: Vec<u32>,
+    updated_by: Vec<u32>,
+}
+
+impl PartialEntry {
+    /// Record the text of a child element, given its path below
+    /// `rfc-entry`.
+    fn apply(&mut self, path: &[&str], value: &str) {
+        let non_empty = || Some(value.to_string()).filter(|v| !v.is_empty());
+        match path {
+            ["doc-id"] => self.number = parse_doc_id(value),
+            ["title"] => self.title = non_empty(),
+            ["author", "name"] => self.authors.extend(non_empty()),
+            ["date", "day"] => self.day = value.parse().ok(),
+            ["date", "month"] => self.month = non_empty(),
+            ["date", "year"] => self.year = value.parse().ok(),
+            ["current-status"] => self.status = non_empty(),
+            ["stream"] => self.stream = non_empty(),
+            ["wg_acronym"] => {
+                self.wg = non_empty().filter(|wg| wg != "NON WORKING GROUP");
+            }
+            ["page-count"] => self.page_count = value.parse().ok(),
+            ["doi"] => self.doi = non_empty(),
+            ["obsoletes", "doc-id"] => self.obsoletes.extend(parse_doc_id(value)),
+            ["obsoleted-by", "doc-id"] => self.obsoleted_by.extend(parse_doc_id(value)),
+            ["updates", "doc-id"] => self.updates.extend(parse_doc_id(value)),
+            ["updated-by", "doc-id"] => self.updated_by.extend(parse_doc_id(value)),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> Option<RfcIndexEntry> {
+        let published = match (self.month.as_deref(), self.year) {
+            (Some(month), Some(year)) => NaiveDate::parse_from_str(
+                &format!("{} {} {}", self.day.unwrap_or(1), month, year),
+                "%d %B %Y",
+            )
+            .ok(),
+            _ => None,
+        };
+        Some(RfcIndexEntry {
+            number: self.number?,
+            title: self.title?,
+            authors: self.authors,
+            published,
+            status: self.status,
+            stream: self.stream,
+            wg: self.wg,
+            page_count: self.page_count,
+            doi: self.doi,
+            obsoletes: self.obsoletes,
+            obsoleted_by: self.obsoleted_by,
+            updates: self.updates,
+            updated_by: self.updated_by,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IndexLookup;
+
+    const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rfc-index xmlns="https://www.rfc-editor.org/rfc-index">
+  <bcp-entry>
+    <doc-id>BCP0014</doc-id>
+    <is-also><doc-id>RFC2119</doc-id></is-also>
+  </bcp-entry>
+  <rfc-entry>
+    <doc-id>RFC1771</doc-id>
+    <title>A Border Gateway Protocol 4 (BGP-4)</title>
+    <author><name>Y. Rekhter</name></author>
+    <author><name>T. Li</name></author>
+    <date><month>March</month><year>1995</year></date>
+    <format><file-format>ASCII</file-format></format>
+    <page-count>57</page-count>
+    <obsoletes><doc-id>RFC1654</doc-id></obsoletes>
+    <obsoleted-by><doc-id>RFC4271</doc-id></obsoleted-by>
+    <current-status>DRAFT STANDARD</current-status>
+    <publication-status>DRAFT STANDARD</publication-status>
+    <stream>Legacy</stream>
+    <wg_acronym>idr</wg_acronym>
+    <doi>10.17487/RFC1771</doi>
+  </rfc-entry>
+  <rfc-not-issued-entry>
+    <doc-id>RFC1849</doc-id>
+  </rfc-not-issued-entry>
+  <rfc-entry>
+    <doc-id>RFC9000</doc-id>
+    <title>QUIC: A UDP-Based Multiplexed &amp; Secure Transport</title>
+    <author><name>J. Iyengar</name><title>Editor</title></author>
+    <author><name>M. Thomson</name><title>Editor</title></author>
+    <date><month>May</month><year>2021</year></date>
+    <page-count>151</page-count>
+    <keywords><kw>QUIC</kw></keywords>
+    <abstract><p>This document defines the core of the QUIC transport protocol.</p></abstract>
+    <updated-by><doc-id>RFC9368</doc-id><doc-id>RFC9369</doc-id></updated-by>
+    <current-status>PROPOSED STANDARD</current-status>
+    <stream>IETF</stream>
+    <area>tsv</area>
+    <wg_acronym>quic</wg_acronym>
+    <doi>10.17487/RFC9000</doi>
+  </rfc-entry>
+  <rfc-entry>
+    <doc-id>RFC0527</doc-id>
+    <title>ARPAWOCKY</title>
+    <author><name>R. Merryman</name></author>
+    <date><day>1</day><month>April</month><year>1973</year></date>
+    <current-status>UNKNOWN</current-status>
+    <wg_acronym>NON WORKING GROUP</wg_acronym>
+  </rfc-entry>
+</rfc-index>"#;
+
+    #[test]
+    fn parses_entries_and_relationships() {
+        let index = parse_rfc_index(FIXTURE).unwrap();
+        assert_eq!(index.len(), 3);
+
+        let bgp = &index.entries[&1771];
+        assert_eq!(bgp.title, "A Border Gateway Protocol 4 (BGP-4)");
+        assert_eq!(bgp.authors, vec!["Y. Rekhter", "T. Li"]);
+        assert_eq!(bgp.published, NaiveDate::from_ymd_opt(1995, 3, 1));
+        assert_eq!(bgp.status.as_deref(), Some("DRAFT STANDARD"));
+        assert_eq!(bgp.wg.as_deref(), Some("idr"));
+        assert_eq!(bgp.page_count, Some(57));
+        assert_eq!(bgp.obsoletes, vec![1654]);
+        assert_eq!(bgp.obsoleted_by, vec![4271]);
+        assert_eq!(bgp.doi.as_deref(), Some("10.17487/RFC1771"));
+    }
+
+    #[test]
+    fn author_titles_and_entities_do_not_leak() {
+        let index = parse_rfc_index(FIXTURE).unwrap();
+        let quic = &index.entries[&9000];
+        // `<author><title>Editor</title>` must not overwrite the RFC title.
+        assert_eq!(
+            quic.title,
+            "QUIC: A UDP-Based Multiplexed & Secure Transport"
+        );
+        assert_eq!(quic.authors, vec!["J. Iyengar", "M. Thomson"]);
+        assert_eq!(quic.updated_by, vec![9368, 9369]);
+        assert!(quic.obsoleted_by.is_empty());
+    }
+
+    #[test]
+    fn tolerates_not_issued_and_non_wg_entries() {
+        let index = parse_rfc_index(FIXTURE).unwrap();
+        assert_eq!(index.lookup(1849), IndexLookup::NotIssued);
+        // BCP entries are not RFCs.
+        assert_eq!(index.lookup(14), IndexLookup::Unknown);
+
+        let arpawocky = &index.entries[&527];
+        assert_eq!(arpawocky.wg, None);
+        assert_eq!(arpawocky.published, NaiveDate::from_ymd_opt(1973, 4, 1));
+    }
+
+    #[test]
+    fn parse_doc_id_forms() {
+        assert_eq!(parse_doc_id("RFC0791"), Some(791));
+        assert_eq!(parse_doc_id("RFC9000"), Some(9000));
+        assert_eq!(parse_doc_id("BCP0014"), None);
+        assert_eq!(parse_doc_id(""), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Checking that a body served as a document's plain text is that text,
+//! and not a captive portal, proxy error, or mirror's "not found" page
+//! sent with a 200.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::models::{draft_base_and_rev, DocumentType, Format};
+
+/// Bodies shorter than this aren't a whole RFC or draft; the shortest
+/// published text runs to a couple of kilobytes.
+pub const MIN_TEXT_LEN: usize = 500;
+
+/// How far into the body the document's name or the "Request for
+/// Comments" banner must appear.
+const HEADER_LEN: usize = 1024;
+
+/// What a body fetched as `doc`'s text turned out to be: the text, or an
+/// HTML rendering of it to convert. A page that isn't the document, or a
+/// body too short to be it, is an error naming `url`.
+pub fn check_text(doc: &DocumentType, url: &str, body: &str) -> Result<Format> {
+    check(doc, url, head(body), body.trim().len())
+}
+
+/// [`check_text`] for a body downloaded to `path`, reading only its
+/// first kilobyte.
+pub fn check_file(doc: &DocumentType, url: &str, path: &Path) -> Result<Format> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let len = file.metadata()?.len() as usize;
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    (&mut file)
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    check(doc, url, &String::from_utf8_lossy(&header), len)
+}
+
+fn check(doc: &DocumentType, url: &str, header: &str, len: usize) -> Result<Format> {
+    if is_html(header) {
+        if mentions(doc, header) {
+            return Ok(Format::Html);
+        }
+        anyhow::bail!(
+            "{} returned an HTML page instead of {}'s text (a captive portal or proxy error?)",
+            url,
+            doc
+        );
+    }
+    if len < MIN_TEXT_LEN {
+        anyhow::bail!(
+            "{} returned only {} bytes, too short to be {}",
+            url,
+            len,
+            doc
+        );
+    }
+    if !mentions(doc, header) {
+        anyhow::bail!(
+            "{} doesn't look like {}: its first kilobyte names neither it nor a Request for Comments",
+            url,
+            doc
+        );
+    }
+    Ok(Format::Text)
+}
+
+/// The start of `body`, cut at a character boundary.
+fn head(body: &str) -> &str {
+    let mut end = body.len().min(HEADER_LEN);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    &body[..end]
+}
+
+/// `<!DOCTYPE html>` or `<html`, after any byte-order mark and space.
+fn is_html(header: &str) -> bool {
+    let start = header.trim_start_matches('\u{feff}').trim_start();
+    let lower = start.get(..14).unwrap_or(start).to_ascii_lowercase();
+    lower.starts_with("<!doctype html") || lower.starts_with("<html")
+}
+
+/// Whether `header` carries `doc`'s banner: "Request for Comments" or
+/// `RFC 9000`/`RFC: 791` for an RFC, the name (any revision) or
+/// "Internet-Draft" for a draft.
+fn mentions(doc: &DocumentType, header: &str) -> bool {
+    let lower = header.to_ascii_lowercase();
+    match doc {
+        DocumentType::Rfc(number) => {
+            lower.contains("request for comments")
+                || lower.match_indices("rfc").any(|(i, _)| {
+                    let rest = lower[i + 3..].trim_start_matches([' ', ':', '-']);
+                    let digits =
+                        rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                    rest[..digits].parse() == Ok(*number)
+                })
+        }
+        DocumentType::Draft(name) => {
+            lower.contains("internet-draft")
+                || lower.contains(&draft_base_and_rev(name).0.to_ascii_lowercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc_text(header: &str) -> String {
+        format!("{}\n\n{}", header, "   Body text.\n".repeat(60))
+    }
+
+    #[test]
+    fn real_text_passes() {
+        let quic = DocumentType::Rfc(9000);
+        let body = rfc_text("Internet Engineering Task Force (IETF)\nRequest for Comments: 9000");
+        assert_eq!(check_text(&quic, "u", &body).unwrap(), Format::Text);
+        let ip = DocumentType::Rfc(791);
+        assert_eq!(
+            check_text(&ip, "u", &rfc_text("RFC:  791")).unwrap(),
+            Format::Text
+        );
+        let draft = DocumentType::Draft("draft-ietf-quic-transport-34".into());
+        let body = rfc_text("QUIC                                  draft-ietf-quic-transport-33");
+        assert_eq!(check_text(&draft, "u", &body).unwrap(), Format::Text);
+    }
+
+    #[test]
+    fn error_pages_and_fragments_fail() {
+        let quic = DocumentType::Rfc(9000);
+        let portal = "<!DOCTYPE html>\n<html><head><title>Sign in to Wi-Fi</title></head></html>";
+        let err = check_text(&quic, "http://x/rfc9000.txt", portal).unwrap_err();
+        assert!(err.to_string().contains("captive portal"), "{}", err);
+
+        let err = check_text(&quic, "u", "Request for Comments: 9000\n").unwrap_err();
+        assert!(err.to_string().contains("too short"), "{}", err);
+
+        let err = check_text(&quic, "u", &rfc_text("Request for Comment 90001")).unwrap_err();
+        assert!(
+            err.to_string().contains("doesn't look like RFC 9000"),
+            "{}",
+            err
+        );
+        assert!(check_text(&quic, "u", &rfc_text("See RFC 90001")).is_err());
+    }
+
+    #[test]
+    fn html_renderings_of_the_document_are_converted() {
+        let quic = DocumentType::Rfc(9000);
+        let page = format!(
+            "<html lang=\"en\"><head><title>RFC 9000: QUIC</title></head>{}",
+            "x".repeat(600)
+        );
+        assert_eq!(check_text(&quic, "u", &page).unwrap(), Format::Html);
+    }
+}
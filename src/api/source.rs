@@ -0,0 +1,155 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DocumentType, Format};
+
+/// A downloaded document and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedDocument {
+    pub content: String,
+    pub format: Format,
+    /// The URL that served it (the HTML one if plain text wasn't available)
+    pub source_url: String,
+    /// The mirror base URL it came from, if not one of the usual hosts
+    pub mirror: Option<String>,
+    pub retrieved_at: DateTime<Utc>,
+    /// The response's `ETag`, if it sent one
+    pub etag: Option<String>,
+    /// The response's `Last-Modified`, if it sent one
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl FetchedDocument {
+    /// Everything but the content, for keeping alongside it.
+    pub fn provenance(&self) -> Provenance {
+        Provenance {
+            source_url: self.source_url.clone(),
+            mirror: self.mirror.clone(),
+            format: self.format,
+            retrieved_at: self.retrieved_at,
+            etag: self.etag.clone(),
+            last_modified: self.last_modified,
+        }
+    }
+}
+
+/// Where and when a cached document's content was downloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source_url: String,
+    /// The mirror base URL it came from, if not one of the usual hosts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<String>,
+    /// The format served, before any conversion to text
+    pub format: Format,
+    pub retrieved_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Anything that can produce a document's raw content.
+///
+/// `DocumentFetcher` is the production implementation; the fetch/view
+/// pipeline only depends on this trait so it can be driven by an
+/// in-memory source in tests.
+#[async_trait]
+pub trait DocumentSource: Send + Sync {
+    /// Fetch `doc`, with where it came from.
+    async fn fetch(&self, doc: &DocumentType) -> Result<FetchedDocument>;
+
+    /// Fetch `doc` only if it changed after `since`; `None` means it
+    /// didn't. Sources that can't ask conditionally always fetch.
+    async fn fetch_if_modified(
+        &self,
+        doc: &DocumentType,
+        _since: Option<DateTime<Utc>>,
+    ) -> Result<Option<FetchedDocument>> {
+        self.fetch(doc).await.map(Some)
+    }
+}
+
+#[async_trait]
+impl DocumentSource for super::DocumentFetcher {
+    async fn fetch(&self, doc: &DocumentType) -> Result<FetchedDocument> {
+        super::DocumentFetcher::fetch(self, doc).await
+    }
+
+    async fn fetch_if_modified(
+        &self,
+        doc: &DocumentType,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<FetchedDocument>> {
+        super::DocumentFetcher::fetch_if_modified(self, doc, since).await
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+pub use test_util::MapSource;
+
+#[cfg(any(test, feature = "test-util"))]
+mod test_util {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// In-memory `DocumentSource` serving canned content per document,
+    /// from `memory:<name>` URLs. Documents not inserted fail as if the
+    /// server returned 404.
+    #[derive(Debug, Default)]
+    pub struct MapSource {
+        docs: HashMap<DocumentType, (Vec<u8>, Format)>,
+        calls: AtomicUsize,
+    }
+
+    impl MapSource {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Serve `content` in `format` for `doc`.
+        pub fn insert(&mut self, doc: DocumentType, content: impl Into<Vec<u8>>, format: Format) {
+            self.docs.insert(doc, (content.into(), format));
+        }
+
+        /// Builder-style [`MapSource::insert`].
+        pub fn with(
+            mut self,
+            doc: DocumentType,
+            content: impl Into<Vec<u8>>,
+            format: Format,
+        ) -> Self {
+            self.insert(doc, content, format);
+            self
+        }
+
+        /// Number of `fetch` calls made so far, hits and misses alike.
+        pub fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl DocumentSource for MapSource {
+        async fn fetch(&self, doc: &DocumentType) -> Result<FetchedDocument> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let (content, format) = self
+                .docs
+                .get(doc)
+                .ok_or_else(|| anyhow::anyhow!("Document not found: {}", doc))?;
+            Ok(FetchedDocument {
+                content: String::from_utf8_lossy(content).into_owned(),
+                format: *format,
+                source_url: format!("memory:{}", doc.name()),
+                mirror: None,
+                retrieved_at: Utc::now(),
+                etag: None,
+                last_modified: None,
+            })
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::Instant;
+
+/// Requests per second allowed when the caller doesn't configure a rate.
+pub const DEFAULT_RATE_LIMIT: f64 = 2.0;
+
+/// Slowest rate a client may be given: one request an hour. Anything
+/// slower is more likely a mistake, and would make waits overflow.
+pub const MIN_RATE_LIMIT: f64 = 1.0 / 3600.0;
+
+/// How many requests may go out back to back before the rate applies.
+const DEFAULT_BURST: f64 = 8.0;
+
+/// Longest we'll honour a `Retry-After` for; beyond this the 429 is
+/// returned to the caller.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Retries after a 429 before giving up and returning it.
+const MAX_RETRIES: u32 = 3;
+
+/// `per_second`, if it's usable as a rate limit: at least
+/// [`MIN_RATE_LIMIT`], and not NaN.
+pub(crate) fn check_rate_limit(per_second: f64) -> Result<f64> {
+    anyhow::ensure!(
+        per_second >= MIN_RATE_LIMIT,
+        "Rate limit must be at least one request an hour ({} per second), not {}",
+        MIN_RATE_LIMIT,
+        per_second
+    );
+    Ok(per_second)
+}
+
+/// Client-side token bucket shared by every request a client (and the
+/// clients derived from it) sends, so bulk operations stay polite no
+/// matter how many run concurrently.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// May go negative: each waiter reserves its token up front.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `per_second` requests per second on average, after an
+    /// initial burst. `f64::INFINITY` disables limiting. The client
+    /// builders pass rates through [`check_rate_limit`] first.
+    pub(crate) fn new(per_second: f64) -> Self {
+        assert!(per_second > 0.0, "rate limit must be positive");
+        Self {
+            per_second,
+            burst: DEFAULT_BURST,
+            bucket: Mutex::new(Bucket {
+                tokens: DEFAULT_BURST,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// A limiter that never waits.
+    pub fn unlimited() -> Self {
+        Self::new(f64::INFINITY)
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) {
+        if self.per_second.is_infinite() {
+            return;
+        }
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-bucket.tokens / self.per_second)
+        };
+        tokio::time::sleep(wait).await;
+    }
+
+    /// Make everyone sharing this limiter wait at least `delay` before the
+    /// next request, e.g. after the server said 429.
+    fn hold_off(&self, delay: Duration) {
+        if self.per_second.is_infinite() {
+            return;
+        }
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        bucket.tokens = bucket
+            .tokens
+            .min(1.0 - delay.as_secs_f64() * self.per_second);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_RATE_LIMIT)
+    }
+}
+
+/// Send `request` through `limiter`, retrying on HTTP 429 after the
+/// server's `Retry-After` (capped at [`MAX_RETRY_AFTER`]). Once retries
+/// run out, or the server asks for a longer wait, the 429 response is
+/// returned for the caller to report.
+pub(super) async fn send(
+    limiter: &RateLimiter,
+    request: RequestBuilder,
+) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        // Streaming bodies can't be replayed; send those once.
+        let Some(this_try) = request.try_clone() else {
+            limiter.acquire().await;
+            return request.send().await;
+        };
+        limiter.acquire().await;
+        let response = this_try.send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt == MAX_RETRIES {
+            return Ok(response);
+        }
+        attempt += 1;
+
+        let delay = retry_after(response.headers(), Utc::now())
+            .unwrap_or(Duration::from_secs(u64::from(attempt)));
+        if delay > MAX_RETRY_AFTER {
+            return Ok(response);
+        }
+        limiter.hold_off(delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Parse `Retry-After`, which is either delta-seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_seconds_and_dates() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            retry_after(&headers("5"), now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            retry_after(&headers("Wed, 01 Oct 2025 00:00:30 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        // Dates in the past mean "now".
+        assert_eq!(
+            retry_after(&headers("Tue, 30 Sep 2025 23:00:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers("soon"), now), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limiter_allows_burst_then_paces() {
+        let limiter = RateLimiter::new(2.0);
+        let start = Instant::now();
+        for _ in 0..DEFAULT_BURST as usize {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hold_off_delays_the_next_request() {
+        let limiter = RateLimiter::new(2.0);
+        let start = Instant::now();
+        limiter.hold_off(Duration::from_secs(5));
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlimited_never_waits() {
+        let limiter = RateLimiter::unlimited();
+        let start = Instant::now();
+        for _ in 0..100 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,108 @@
+//! Synchronous wrappers around the async API clients, for embedders that
+//! don't otherwise run an async runtime. Enabled with the `blocking`
+//! feature, mirroring reqwest's layout.
+//!
+//! Each wrapper owns a private current-thread tokio runtime and drives
+//! the async client on it, so URL construction, parsing, and fallbacks are
+//! shared with the async versions. As with `reqwest::blocking`, these
+//! must not be called from inside an async context: the nested
+//! `block_on` would panic.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::api::{DataTrackerClient, DocumentFetcher, FetchedDocument};
+use crate::models::{Document, DocumentDetails, DocumentType, SearchFilter, SearchResult};
+
+fn runtime() -> Result<Runtime> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start blocking runtime")
+}
+
+/// Blocking counterpart of [`DataTrackerClient`].
+pub struct BlockingDataTrackerClient {
+    inner: DataTrackerClient,
+    runtime: Runtime,
+}
+
+impl BlockingDataTrackerClient {
+    /// Build a client with default settings.
+    pub fn new() -> Result<Self> {
+        Self::from_async(DataTrackerClient::new()?)
+    }
+
+    /// Wrap an already-configured async client (e.g. one built with
+    /// `DataTrackerClient::builder()`).
+    pub fn from_async(inner: DataTrackerClient) -> Result<Self> {
+        Ok(Self {
+            inner,
+            runtime: runtime()?,
+        })
+    }
+
+    /// See [`DataTrackerClient::search`].
+    pub fn search(&self, query: &str, filter: SearchFilter, limit: u32) -> Result<SearchResult> {
+        self.runtime
+            .block_on(self.inner.search(query, filter, limit))
+    }
+
+    /// See [`DataTrackerClient::get_document`].
+    pub fn get_document(&self, name: &str) -> Result<Document> {
+        self.runtime.block_on(self.inner.get_document(name))
+    }
+
+    /// See [`DataTrackerClient::get_documents`].
+    pub fn get_documents(&self, names: &[&str]) -> Result<HashMap<String, Document>> {
+        self.runtime.block_on(self.inner.get_documents(names))
+    }
+
+    /// See [`DataTrackerClient::get_document_details`].
+    pub fn get_document_details(&self, name: &str) -> Result<DocumentDetails> {
+        self.runtime.block_on(self.inner.get_document_details(name))
+    }
+
+    /// See [`DataTrackerClient::obsoleted_by`].
+    pub fn obsoleted_by(&self, rfc: u32) -> Result<Vec<u32>> {
+        self.runtime.block_on(self.inner.obsoleted_by(rfc))
+    }
+}
+
+/// Blocking counterpart of [`DocumentFetcher`].
+pub struct BlockingDocumentFetcher {
+    inner: DocumentFetcher,
+    runtime: Runtime,
+}
+
+impl BlockingDocumentFetcher {
+    /// Build a fetcher with default settings.
+    pub fn new() -> Result<Self> {
+        Self::from_async(DocumentFetcher::new()?)
+    }
+
+    /// Wrap an already-configured async fetcher.
+    pub fn from_async(inner: DocumentFetcher) -> Result<Self> {
+        Ok(Self {
+            inner,
+            runtime: runtime()?,
+        })
+    }
+
+    /// See [`DocumentFetcher::fetch`].
+    pub fn fetch(&self, doc: &DocumentType) -> Result<FetchedDocument> {
+        self.runtime.block_on(self.inner.fetch(doc))
+    }
+
+    /// See [`DocumentFetcher::html_url`].
+    pub fn html_url(&self, doc: &DocumentType) -> String {
+        self.inner.html_url(doc)
+    }
+
+    /// See [`DocumentFetcher::text_url`].
+    pub fn text_url(&self, doc: &DocumentType) -> String {
+        self.inner.text_url(doc)
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::cache::FsCache;
+use crate::models::DocumentType;
+
+/// Problems found by [`FsCache::audit`]. Lists are sorted so reports are
+/// stable.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheAuditReport {
+    /// Number of entries examined in the documents directory
+    pub files_checked: usize,
+    /// `.meta` files with no document beside them
+    pub orphaned_metadata: Vec<PathBuf>,
+    /// Cached documents with no `.meta` file (so no title in listings)
+    pub missing_metadata: Vec<DocumentType>,
+    /// Zero-byte documents or metadata, left by interrupted writes
+    pub empty_files: Vec<PathBuf>,
+    /// `.part` files (and `.part.json` resume state) from downloads that
+    /// never completed
+    pub partial_writes: Vec<PathBuf>,
+    /// Files whose names don't belong to any document. Reported only;
+    /// repairs never delete them.
+    pub unrecognized: Vec<PathBuf>,
+}
+
+impl CacheAuditReport {
+    /// Whether the audit found nothing to report.
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_metadata.is_empty()
+            && self.missing_metadata.is_empty()
+            && self.empty_files.is_empty()
+            && self.partial_writes.is_empty()
+            && self.unrecognized.is_empty()
+    }
+
+    /// Files [`FsCache::repair`] would delete.
+    pub fn removable(&self) -> impl Iterator<Item = &PathBuf> {
+        self.orphaned_metadata
+            .iter()
+            .chain(&self.empty_files)
+            .chain(&self.partial_writes)
+    }
+}
+
+/// What a file in the documents directory holds.
+enum Entry {
+    Document(DocumentType),
+    Metadata(DocumentType),
+    Partial,
+    /// User notes, which may outlive their document
+    Notes,
+}
+
+impl FsCache {
+    /// Scan the cache for inconsistencies without changing anything.
+    pub fn audit(&self) -> Result<CacheAuditReport> {
+        let mut report = CacheAuditReport::default();
+        let docs_dir = self.cache_dir().join("documents");
+        if !docs_dir.exists() {
+            return Ok(report);
+        }
+
+        let mut documents = BTreeSet::new();
+        let mut metadata = BTreeMap::new();
+        for entry in fs::read_dir(&docs_dir).context("Failed to read cache directory")? {
+            let path = entry?.path();
+            report.files_checked += 1;
+            let Some(kind) = classify(&path) else {
+                report.unrecognized.push(path);
+                continue;
+            };
+            match kind {
+                Entry::Partial => {
+                    report.partial_writes.push(path);
+                    continue;
+                }
+                Entry::Notes => continue,
+                _ => {}
+            }
+            if fs::metadata(&path)?.len() == 0 {
+                report.empty_files.push(path);
+                continue;
+            }
+            match kind {
+                Entry::Document(doc) => {
+                    documents.insert(doc.name());
+                }
+                Entry::Metadata(doc) => {
+                    metadata.insert(doc.name(), path);
+                }
+                Entry::Partial | Entry::Notes => unreachable!(),
+            }
+        }
+
+        report.missing_metadata = documents
+            .iter()
+            .filter(|name| !metadata.contains_key(*name))
+            .map(|name| DocumentType::from_canonical_name(name))
+            .collect();
+        report.orphaned_metadata = metadata
+            .into_iter()
+            .filter(|(name, _)| !documents.contains(name))
+            .map(|(_, path)| path)
+            .collect();
+
+        report.empty_files.sort();
+        report.partial_writes.sort();
+        report.unrecognized.sort();
+        Ok(report)
+    }
+
+    /// Delete the orphaned, empty, and partial files `report` found.
+    /// Returns how many were removed. Missing metadata needs the network
+    /// and is left to the caller.
+    pub fn repair(&self, report: &CacheAuditReport) -> Result<usize> {
+        let mut removed = 0;
+        if self.is_read_only() {
+            return Ok(removed);
+        }
+        for path in report.removable() {
+            match fs::remove_file(path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Work out which document a cache file belongs to from its name, e.g.
+/// `rfc9000.txt`, `draft-foo-00.html.gz`, `rfc9000.meta`.
+fn classify(path: &Path) -> Option<Entry> {
+    if !path.is_file() {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    // `.part.json` describes a resumable download's `.part` file.
+    if name.ends_with(".part") || name.ends_with(".part.json") {
+        return Some(Entry::Partial);
+    }
+    if let Some(stem) = name.strip_suffix(".notes.md") {
+        return parse_name(stem).map(|_| Entry::Notes);
+    }
+    let (name, gzipped) = match name.strip_suffix(".gz") {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    let (stem, ext) = name.rsplit_once('.')?;
+    let doc = parse_name(stem)?;
+    match ext {
+        "txt" | "html" => Some(Entry::Document(doc)),
+        "meta" if !gzipped => Some(Entry::Metadata(doc)),
+        _ => None,
+    }
+}
+
+/// Accept only the names the cache itself writes: `rfcN` and
+/// lowercase `draft-...` names.
+fn parse_name(stem: &str) -> Option<DocumentType> {
+    if let Some(num) = stem.strip_prefix("rfc") {
+        return num
+            .chars()
+            .all(|c| c.is_ascii_digit())
+            .then(|| num.parse().ok().map(DocumentType::Rfc))
+            .flatten();
+    }
+    let valid = stem.len() > "draft-".len()
+        && stem.starts_with("draft-")
+        && stem
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+    valid.then(|| DocumentType::Draft(stem.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use crate::models::Format;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn meta(title: &str) -> CacheMetadata {
+        CacheMetadata::new(title, Utc::now())
+    }
+
+    /// A cache with one healthy document and one of every problem.
+    fn corrupted_cache() -> (FsCache, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        let healthy = DocumentType::Rfc(9000);
+        cache
+            .store_document(&healthy, Format::Text, "QUIC")
+            .unwrap();
+        cache.store_metadata(&healthy, &meta("QUIC")).unwrap();
+
+        // Document without metadata
+        cache
+            .store_document(&DocumentType::Rfc(8446), Format::Html, "<p>TLS</p>")
+            .unwrap();
+        // Metadata without a document
+        cache
+            .store_metadata(&DocumentType::Rfc(1), &meta("Host Software"))
+            .unwrap();
+
+        let docs = temp.path().join("documents");
+        fs::write(docs.join("rfc2616.txt"), "").unwrap();
+        fs::write(docs.join("draft-ietf-quic-http-34.txt.part"), "partial").unwrap();
+        fs::write(docs.join("notes.md"), "mine").unwrap();
+        fs::write(docs.join("rfcxyz.txt"), "junk").unwrap();
+        (cache, temp)
+    }
+
+    #[test]
+    fn audit_clean_cache() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        assert!(cache.audit().unwrap().is_clean());
+
+        let doc = DocumentType::Draft("draft-ietf-quic-http-34".into());
+        cache.store_document(&doc, Format::Text, "HTTP/3").unwrap();
+        cache.store_metadata(&doc, &meta("HTTP/3")).unwrap();
+        let report = cache.audit().unwrap();
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.files_checked, 2);
+    }
+
+    #[test]
+    fn audit_reports_each_problem() {
+        let (cache, temp) = corrupted_cache();
+        let docs = temp.path().join("documents");
+        let report = cache.audit().unwrap();
+
+        assert_eq!(report.files_checked, 8);
+        assert_eq!(report.orphaned_metadata, vec![docs.join("rfc1.meta")]);
+        assert_eq!(report.missing_metadata, vec![DocumentType::Rfc(8446)]);
+        assert_eq!(report.empty_files, vec![docs.join("rfc2616.txt")]);
+        assert_eq!(
+            report.partial_writes,
+            vec![docs.join("draft-ietf-quic-http-34.txt.part")]
+        );
+        assert_eq!(
+            report.unrecognized,
+            vec![docs.join("notes.md"), docs.join("rfcxyz.txt")]
+        );
+    }
+
+    #[test]
+    fn audit_understands_compressed_documents() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_compression(true);
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        cache.store_metadata(&doc, &meta("QUIC")).unwrap();
+
+        assert!(cache.audit().unwrap().is_clean());
+    }
+
+    #[test]
+    fn empty_document_orphans_its_metadata() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "").unwrap();
+        cache.store_metadata(&doc, &meta("QUIC")).unwrap();
+
+        let report = cache.audit().unwrap();
+        assert_eq!(report.empty_files.len(), 1);
+        assert_eq!(report.orphaned_metadata.len(), 1);
+        assert!(report.missing_metadata.is_empty());
+    }
+
+    #[test]
+    fn repair_removes_only_safe_files() {
+        let (cache, temp) = corrupted_cache();
+        let docs = temp.path().join("documents");
+        let report = cache.audit().unwrap();
+
+        assert_eq!(cache.repair(&report).unwrap(), 3);
+        // Idempotent: files already gone aren't errors.
+        assert_eq!(cache.repair(&report).unwrap(), 0);
+        assert!(docs.join("notes.md").exists());
+
+        let after = cache.audit().unwrap();
+        assert!(after.orphaned_metadata.is_empty());
+        assert!(after.empty_files.is_empty());
+        assert!(after.partial_writes.is_empty());
+        assert_eq!(after.missing_metadata, vec![DocumentType::Rfc(8446)]);
+        assert_eq!(after.unrecognized.len(), 2);
+        assert_eq!(
+            cache
+                .get_document(&DocumentType::Rfc(9000), Format::Text)
+                .as_deref(),
+            Some("QUIC")
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::cache::metadata::normalize_tag;
+use crate::cache::{
+    Bookmark, CacheCounters, CacheMetadata, CachedDocument, CachedLineage, DraftStatus,
+    FormatAvailability,
+};
+use crate::models::{DocumentType, Format};
+
+/// Storage backend for cached documents and their metadata.
+///
+/// `FsCache` (a.k.a. `CacheManager`) is what the CLI uses; `MemoryCache`
+/// is there for tests and for embedders that don't want files on disk.
+/// Implementations must agree on the semantics exercised by the
+/// conformance suite in `tests/cache_conformance.rs`.
+pub trait DocumentCache: Send + Sync {
+    /// Get cached document content
+    fn get_document(&self, doc: &DocumentType, format: Format) -> Option<String>;
+
+    /// Store document content, replacing any previous copy in that format
+    fn store_document(&self, doc: &DocumentType, format: Format, content: &str) -> Result<()>;
+
+    /// Get cached metadata for a document
+    fn get_metadata(&self, doc: &DocumentType) -> Option<CacheMetadata>;
+
+    /// Store metadata for a document
+    fn store_metadata(&self, doc: &DocumentType, meta: &CacheMetadata) -> Result<()>;
+
+    /// List all cached documents, each exactly once
+    fn list_cached(&self) -> Vec<DocumentType>;
+
+    /// Remove a document's content (every format) and metadata.
+    /// Returns true if any content was found and removed.
+    fn remove(&self, doc: &DocumentType) -> Result<bool>;
+
+    /// Remove every cached document and all metadata
+    fn clear(&self) -> Result<()>;
+
+    /// The cached revision that lookups of unversioned draft `doc` are
+    /// served from, for backends that fall back that way.
+    fn newest_revision(&self, _doc: &DocumentType) -> Option<DocumentType> {
+        None
+    }
+
+    /// Note that `doc` was just shown to the user. Best effort: nothing
+    /// is recorded for documents without metadata, and write failures
+    /// (e.g. a read-only cache) are ignored.
+    fn record_access(&self, doc: &DocumentType) {
+        if let Some(mut meta) = self.get_metadata(doc) {
+            meta.last_accessed = Some(Utc::now());
+            let _ = self.store_metadata(doc, &meta);
+        }
+    }
+
+    /// Count cache hits, misses, and downloads, for backends that keep
+    /// statistics. Best effort, like [`record_access`](Self::record_access).
+    fn record_counters(&self, _delta: CacheCounters) {}
+
+    /// Attach `tag` (case-insensitive, one word) to `doc`, creating
+    /// untitled metadata if it has none. Returns false if it was already
+    /// tagged. Callers should make sure `doc` is cached first.
+    fn add_tag(&self, doc: &DocumentType, tag: &str) -> Result<bool> {
+        let tag = normalize_tag(tag);
+        if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+            anyhow::bail!("Tags must be a single word");
+        }
+        let mut meta = self
+            .get_metadata(doc)
+            .unwrap_or_else(|| CacheMetadata::new("", Utc::now()));
+        if meta.tags.contains(&tag) {
+            return Ok(false);
+        }
+        meta.tags.push(tag);
+        meta.tags.sort();
+        self.store_metadata(doc, &meta)?;
+        Ok(true)
+    }
+
+    /// Detach `tag` from `doc`. Returns false if it wasn't attached.
+    fn remove_tag(&self, doc: &DocumentType, tag: &str) -> Result<bool> {
+        let tag = normalize_tag(tag);
+        let Some(mut meta) = self.get_metadata(doc) else {
+            return Ok(false);
+        };
+        let before = meta.tags.len();
+        meta.tags.retain(|t| *t != tag);
+        if meta.tags.len() == before {
+            return Ok(false);
+        }
+        self.store_metadata(doc, &meta)?;
+        Ok(true)
+    }
+
+    /// Set or clear `doc`'s reading position, creating untitled metadata
+    /// if needed to hold it.
+    fn set_bookmark(&self, doc: &DocumentType, bookmark: Option<Bookmark>) -> Result<()> {
+        let meta = match (self.get_metadata(doc), bookmark) {
+            (Some(meta), _) if meta.bookmark == bookmark => return Ok(()),
+            (Some(meta), _) => meta,
+            (None, None) => return Ok(()),
+            (None, Some(_)) => CacheMetadata::new("", Utc::now()),
+        };
+        self.store_metadata(doc, &CacheMetadata { bookmark, ..meta })
+    }
+
+    /// Record what the Datatracker said about draft `doc`. Like
+    /// [`set_bookmark`](Self::set_bookmark), creates untitled metadata if
+    /// needed.
+    fn set_draft_status(&self, doc: &DocumentType, status: DraftStatus) -> Result<()> {
+        let meta = self
+            .get_metadata(doc)
+            .unwrap_or_else(|| CacheMetadata::new("", Utc::now()));
+        self.store_metadata(
+            doc,
+            &CacheMetadata {
+                draft_status: Some(status),
+                ..meta
+            },
+        )
+    }
+
+    /// Record which renderings of `doc` were found. Like
+    /// [`set_bookmark`](Self::set_bookmark), creates untitled metadata if
+    /// needed.
+    fn set_formats(&self, doc: &DocumentType, formats: FormatAvailability) -> Result<()> {
+        let meta = self
+            .get_metadata(doc)
+            .unwrap_or_else(|| CacheMetadata::new("", Utc::now()));
+        self.store_metadata(
+            doc,
+            &CacheMetadata {
+                formats: Some(formats),
+                ..meta
+            },
+        )
+    }
+
+    /// Record the lineage looked up for `doc`. Like
+    /// [`set_bookmark`](Self::set_bookmark), creates untitled metadata if
+    /// needed.
+    fn set_lineage(&self, doc: &DocumentType, lineage: CachedLineage) -> Result<()> {
+        let meta = self
+            .get_metadata(doc)
+            .unwrap_or_else(|| CacheMetadata::new("", Utc::now()));
+        self.store_metadata(
+            doc,
+            &CacheMetadata {
+                lineage: Some(lineage),
+                ..meta
+            },
+        )
+    }
+
+    /// Cached documents carrying `tag`
+    fn list_by_tag(&self, tag: &str) -> Vec<CachedDocument> {
+        let mut docs = self.list_cached_with_metadata();
+        docs.retain(|doc| doc.metadata.as_ref().is_some_and(|m| m.has_tag(tag)));
+        docs
+    }
+
+    /// List cached documents with their metadata, formats, and size
+    fn list_cached_with_metadata(&self) -> Vec<CachedDocument> {
+        self.list_cached()
+            .into_iter()
+            .map(|doc_type| {
+                let mut formats = Vec::new();
+                let mut size = 0;
+                for format in [Format::Text, Format::Html] {
+                    if let Some(content) = self.get_document(&doc_type, format) {
+                        formats.push(format);
+                        size += content.len() as u64;
+                    }
+                }
+                CachedDocument {
+                    metadata: self.get_metadata(&doc_type),
+                    doc_type,
+                    formats,
+                    size,
+                    modified: None,
+                    has_notes: false,
+                }
+            })
+            .collect()
+    }
+}
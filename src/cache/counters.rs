@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::FsCache;
+
+/// How often the cache saved a download, as counted by the fetch
+/// workflow ([`crate::workflow`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheCounters {
+    /// Documents served from the cache
+    pub hits: u64,
+    /// Documents that had to be fetched because they weren't cached
+    pub misses: u64,
+    /// Bytes downloaded, as served (before any HTML conversion)
+    pub bytes_fetched: u64,
+    /// Bytes of text served from the cache
+    pub bytes_from_cache: u64,
+    /// Cached documents re-checked against the server
+    pub refreshes: u64,
+}
+
+impl CacheCounters {
+    /// One document of `bytes` served from the cache.
+    pub fn hit(bytes: usize) -> Self {
+        Self {
+            hits: 1,
+            bytes_from_cache: bytes as u64,
+            ..Self::default()
+        }
+    }
+
+    /// One document that wasn't cached.
+    pub fn miss() -> Self {
+        Self {
+            misses: 1,
+            ..Self::default()
+        }
+    }
+
+    /// `bytes` downloaded.
+    pub fn fetched(bytes: usize) -> Self {
+        Self {
+            bytes_fetched: bytes as u64,
+            ..Self::default()
+        }
+    }
+
+    /// One cached document re-checked, downloading `bytes` (0 if the
+    /// server said it was unchanged).
+    pub fn refreshed(bytes: usize) -> Self {
+        Self {
+            refreshes: 1,
+            ..Self::fetched(bytes)
+        }
+    }
+
+    pub fn add(&mut self, other: &CacheCounters) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.bytes_fetched += other.bytes_fetched;
+        self.bytes_from_cache += other.bytes_from_cache;
+        self.refreshes += other.refreshes;
+    }
+
+    /// Hits as a fraction of lookups, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// The counters kept in the cache's `stats.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Everything counted since `since`
+    pub total: CacheCounters,
+    /// What the most recent `rfc` process to count anything counted
+    pub last_run: CacheCounters,
+    /// When counting started (the first count after a reset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl FsCache {
+    fn stats_path(&self) -> PathBuf {
+        self.cache_dir().join("stats.json")
+    }
+
+    /// The cumulative counters; zero if nothing has been counted.
+    pub fn stats(&self) -> CacheStats {
+        fs::read_to_string(self.stats_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// What this `FsCache` has counted since it was opened.
+    pub fn session_counters(&self) -> CacheCounters {
+        *self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add `delta` to this run's counters and the totals in `stats.json`.
+    /// Best effort: a failed write (or a read-only cache) loses the count
+    /// rather than failing whatever was being counted.
+    pub fn record_counters(&self, delta: CacheCounters) {
+        let run = {
+            let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+            session.add(&delta);
+            *session
+        };
+        if self.is_read_only() {
+            return;
+        }
+        let _ = self.with_lock(|| {
+            let mut stats = self.stats();
+            stats.total.add(&delta);
+            stats.last_run = run;
+            stats.since.get_or_insert_with(Utc::now);
+            let content =
+                serde_json::to_string_pretty(&stats).context("Failed to serialize stats")?;
+            fs::write(self.stats_path(), content).context("Failed to write stats")
+        });
+    }
+
+    /// Forget every count, starting over from zero.
+    pub fn reset_stats(&self) -> Result<()> {
+        self.ensure_writable()?;
+        self.with_lock(|| match fs::remove_file(self.stats_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).context("Failed to remove stats")
+            }
+            _ => Ok(()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn counts_accumulate_across_runs_and_reset() {
+        let temp = TempDir::new().unwrap();
+        let first = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        first.record_counters(CacheCounters::miss());
+        first.record_counters(CacheCounters::fetched(100));
+        first.record_counters(CacheCounters::hit(40));
+
+        let second = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        second.record_counters(CacheCounters::refreshed(0));
+        assert_eq!(second.session_counters(), CacheCounters::refreshed(0));
+
+        let stats = second.stats();
+        assert_eq!(
+            stats.total,
+            CacheCounters {
+                hits: 1,
+                misses: 1,
+                bytes_fetched: 100,
+                bytes_from_cache: 40,
+                refreshes: 1,
+            }
+        );
+        assert_eq!(stats.last_run, CacheCounters::refreshed(0));
+        assert!(stats.since.is_some());
+        assert_eq!(stats.total.hit_rate(), Some(0.5));
+
+        second.reset_stats().unwrap();
+        assert_eq!(second.stats(), CacheStats::default());
+        second.reset_stats().unwrap();
+    }
+
+    #[test]
+    fn read_only_caches_count_only_in_memory() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_read_only(true);
+        cache.record_counters(CacheCounters::hit(10));
+        assert_eq!(cache.session_counters(), CacheCounters::hit(10));
+        assert!(!temp.path().join("stats.json").exists());
+        assert!(cache.reset_stats().is_err());
+    }
+}
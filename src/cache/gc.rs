@@ -0,0 +1,382 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+
+use crate::cache::storage::tree_size;
+use crate::cache::FsCache;
+use crate::models::{DocumentType, Format};
+
+/// What [`FsCache::garbage_collect`] may delete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcPolicy {
+    /// Formats worth keeping (the config's `gc.keep`). A document's copy
+    /// in any other format goes when one of these is cached beside it;
+    /// a document's only copy is never removed. Dropping text means the
+    /// next viewing fetches it again.
+    pub keep: Vec<Format>,
+    /// Temporary and partial files younger than this are left alone, as
+    /// a viewer or a download may still be using them.
+    pub min_age: Duration,
+}
+
+impl Default for GcPolicy {
+    /// Keep both formats; collect only what no format needs.
+    fn default() -> Self {
+        Self::keeping(vec![Format::Text, Format::Html])
+    }
+}
+
+impl GcPolicy {
+    /// Keep `formats`, leaving files younger than an hour.
+    pub fn keeping(formats: Vec<Format>) -> Self {
+        Self {
+            keep: formats,
+            min_age: Duration::hours(1),
+        }
+    }
+
+    /// Leave temporary and partial files younger than `min_age`.
+    pub fn with_min_age(mut self, min_age: Duration) -> Self {
+        self.min_age = min_age;
+        self
+    }
+}
+
+/// What [`FsCache::garbage_collect`] deleted. Lists are sorted so
+/// reports are stable.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GcReport {
+    /// Copies dropped because the policy keeps another format of the
+    /// same document
+    pub redundant_formats: Vec<(DocumentType, Format)>,
+    /// Plain copies beside a compressed one of the same document and
+    /// format, or the reverse, whichever the compression setting doesn't
+    /// call for
+    pub duplicate_encodings: Vec<PathBuf>,
+    /// `.part` files (and `.part.json` resume state) from writes and
+    /// downloads that never completed
+    pub partial_writes: Vec<PathBuf>,
+    /// Files left in the cache's `tmp/`
+    pub temp_files: Vec<PathBuf>,
+    /// Directories left with nothing in them
+    pub empty_directories: Vec<PathBuf>,
+    /// Size on disk of every deleted file
+    pub bytes_freed: u64,
+}
+
+impl GcReport {
+    /// Whether nothing at all was deleted.
+    pub fn is_empty(&self) -> bool {
+        self.files_removed() == 0 && self.empty_directories.is_empty()
+    }
+
+    /// Number of files deleted, directories aside.
+    pub fn files_removed(&self) -> usize {
+        self.redundant_formats.len()
+            + self.duplicate_encodings.len()
+            + self.partial_writes.len()
+            + self.temp_files.len()
+    }
+}
+
+impl FsCache {
+    /// Delete what the cache can do without under `policy`: copies in
+    /// formats it doesn't keep, duplicate encodings, stale partial and
+    /// temporary files, and empty directories. Documents, metadata, and
+    /// notes otherwise stay. A read-only cache is left as it is.
+    pub fn garbage_collect(&self, policy: &GcPolicy) -> Result<GcReport> {
+        let mut report = GcReport::default();
+        if self.is_read_only() {
+            return Ok(report);
+        }
+        let cutoff = SystemTime::now() - policy.min_age.to_std().unwrap_or_default();
+
+        // Partial files first, so the document scan below never sees them.
+        for dir in [self.cache_dir().to_path_buf(), self.documents_dir()] {
+            for path in stale_files(&dir, cutoff, is_partial)? {
+                report.bytes_freed += remove(&path)?;
+                report.partial_writes.push(path);
+            }
+        }
+        for path in stale_files(&self.temp_dir(), cutoff, |_| true)? {
+            report.bytes_freed += remove(&path)?;
+            report.temp_files.push(path);
+        }
+
+        for doc in self.list_cached() {
+            self.collect_document(&doc, policy, &mut report)?;
+        }
+
+        remove_empty_dirs(self.cache_dir(), &mut report.empty_directories)?;
+
+        report
+            .redundant_formats
+            .sort_by_key(|(doc, format)| (doc.name(), format.extension()));
+        report.duplicate_encodings.sort();
+        report.partial_writes.sort();
+        report.temp_files.sort();
+        report.empty_directories.sort();
+        Ok(report)
+    }
+
+    /// Drop `doc`'s duplicate encodings, then its copies in formats
+    /// `policy` doesn't keep, provided a kept one remains.
+    fn collect_document(
+        &self,
+        doc: &DocumentType,
+        policy: &GcPolicy,
+        report: &mut GcReport,
+    ) -> Result<()> {
+        let formats = [Format::Text, Format::Html];
+        for format in formats {
+            let (preferred, other) = self.document_paths(doc, format);
+            if preferred.is_file() && other.is_file() {
+                report.bytes_freed += remove(&other)?;
+                report.duplicate_encodings.push(other);
+            }
+        }
+
+        let cached: Vec<Format> = formats
+            .into_iter()
+            .filter(|format| self.exact_document_file(doc, *format).is_some())
+            .collect();
+        if !cached.iter().any(|format| policy.keep.contains(format)) {
+            return Ok(());
+        }
+        let mut changed = false;
+        for format in cached {
+            if policy.keep.contains(&format) {
+                continue;
+            }
+            let (preferred, other) = self.document_paths(doc, format);
+            for path in [preferred, other] {
+                report.bytes_freed += remove(&path)?;
+            }
+            if self.checksum(doc, format).is_some() {
+                self.record_checksum(doc, format, None)?;
+            }
+            report.redundant_formats.push((doc.clone(), format));
+            changed = true;
+        }
+        if changed {
+            self.refresh_index(doc);
+        }
+        Ok(())
+    }
+
+    fn documents_dir(&self) -> PathBuf {
+        self.cache_dir().join("documents")
+    }
+}
+
+/// `rfc9000.txt.part`, or the `.part.json` describing a resumable
+/// download.
+fn is_partial(name: &str) -> bool {
+    name.ends_with(".part") || name.ends_with(".part.json")
+}
+
+/// Files directly in `dir` whose names pass `matches`, last written
+/// before `cutoff`. A missing `dir` has none.
+fn stale_files(
+    dir: &Path,
+    cutoff: SystemTime,
+    matches: impl Fn(&str) -> bool,
+) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut stale = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let name = entry.file_name();
+        let old = meta.modified().is_ok_and(|written| written < cutoff);
+        if meta.is_file() && old && name.to_str().is_some_and(&matches) {
+            stale.push(entry.path());
+        }
+    }
+    Ok(stale)
+}
+
+/// Delete the file at `path`, returning its size. One already gone
+/// frees nothing.
+fn remove(path: &Path) -> Result<u64> {
+    let size = tree_size(path);
+    match fs::remove_file(path) {
+        Ok(()) => Ok(size),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+/// Delete every directory under `dir` with nothing left in it, deepest
+/// first, adding each to `removed`. Returns whether `dir` itself is now
+/// empty; it's never removed.
+fn remove_empty_dirs(dir: &Path, removed: &mut Vec<PathBuf>) -> Result<bool> {
+    let mut empty = true;
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+        if is_dir && remove_empty_dirs(&path, removed)? {
+            fs::remove_dir(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            removed.push(path);
+        } else {
+            empty = false;
+        }
+    }
+    Ok(empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    /// Backdate `path` so it counts as stale.
+    fn age(path: &Path) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - std::time::Duration::from_secs(2 * 3600))
+            .unwrap();
+    }
+
+    /// A cache with a document in both formats, one in HTML only, one
+    /// cached both plain and compressed, and every kind of leftover.
+    fn messy_cache() -> (FsCache, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        let quic = DocumentType::Rfc(9000);
+        cache.store_document(&quic, Format::Text, "QUIC").unwrap();
+        cache
+            .store_document(&quic, Format::Html, "<pre>QUIC</pre>")
+            .unwrap();
+        cache
+            .store_metadata(&quic, &CacheMetadata::new("QUIC", Utc::now()))
+            .unwrap();
+        cache
+            .store_document(&DocumentType::Rfc(8446), Format::Html, "<p>TLS</p>")
+            .unwrap();
+        FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_compression(true)
+            .store_document(&DocumentType::Rfc(791), Format::Text, "IP")
+            .unwrap();
+
+        let docs = temp.path().join("documents");
+        fs::write(docs.join("rfc791.txt"), "IP").unwrap();
+        fs::write(docs.join("rfc9000.notes.md"), "mine").unwrap();
+        for stale in ["rfc2616.txt.part", "rfc2616.txt.part.json"] {
+            fs::write(docs.join(stale), "partial").unwrap();
+            age(&docs.join(stale));
+        }
+        fs::write(docs.join("rfc1.txt.part"), "downloading").unwrap();
+
+        let tmp = cache.temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("rfc9000.txt"), "QUIC").unwrap();
+        age(&tmp.join("rfc9000.txt"));
+        fs::write(tmp.join("rfc8446.txt"), "TLS").unwrap();
+        fs::create_dir_all(temp.path().join("exports/old")).unwrap();
+        (cache, temp)
+    }
+
+    #[test]
+    fn default_policy_keeps_every_format() {
+        let (cache, temp) = messy_cache();
+        let docs = temp.path().join("documents");
+        let gz_size = fs::metadata(docs.join("rfc791.txt.gz")).unwrap().len();
+        let report = cache.garbage_collect(&GcPolicy::default()).unwrap();
+
+        assert!(report.redundant_formats.is_empty());
+        assert_eq!(report.duplicate_encodings, [docs.join("rfc791.txt.gz")]);
+        assert_eq!(
+            report.partial_writes,
+            [
+                docs.join("rfc2616.txt.part"),
+                docs.join("rfc2616.txt.part.json")
+            ]
+        );
+        assert_eq!(report.temp_files, [cache.temp_dir().join("rfc9000.txt")]);
+        assert_eq!(
+            report.empty_directories,
+            [temp.path().join("exports"), temp.path().join("exports/old")]
+        );
+        assert_eq!(report.bytes_freed, gz_size + 2 * "partial".len() as u64 + 4);
+
+        // Fresh leftovers, notes, and every format stay.
+        assert!(docs.join("rfc1.txt.part").exists());
+        assert!(cache.temp_dir().join("rfc8446.txt").exists());
+        assert!(docs.join("rfc9000.notes.md").exists());
+        let quic = DocumentType::Rfc(9000);
+        assert!(cache.contains(&quic, Format::Text));
+        assert!(cache.contains(&quic, Format::Html));
+        assert_eq!(
+            cache
+                .get_document(&DocumentType::Rfc(791), Format::Text)
+                .as_deref(),
+            Some("IP")
+        );
+
+        assert!(cache
+            .garbage_collect(&GcPolicy::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn formats_outside_the_policy_go_unless_they_are_the_only_copy() {
+        let (cache, _temp) = messy_cache();
+        let quic = DocumentType::Rfc(9000);
+        let tls = DocumentType::Rfc(8446);
+        let html_size = "<pre>QUIC</pre>".len() as u64;
+        let gz = cache.cache_dir().join("documents/rfc791.txt.gz");
+        let gz_size = fs::metadata(&gz).unwrap().len();
+
+        let policy = GcPolicy::keeping(vec![Format::Text]).with_min_age(Duration::days(1));
+        let report = cache.garbage_collect(&policy).unwrap();
+        assert_eq!(report.redundant_formats, [(quic.clone(), Format::Html)]);
+        assert!(report.partial_writes.is_empty());
+        assert!(report.temp_files.is_empty());
+        assert_eq!(report.duplicate_encodings, [gz]);
+        assert_eq!(report.bytes_freed, html_size + gz_size);
+
+        assert!(cache.contains(&quic, Format::Text));
+        assert!(!cache.contains(&quic, Format::Html));
+        assert_eq!(cache.checksum(&quic, Format::Html), None);
+        assert!(cache.checksum(&quic, Format::Text).is_some());
+        assert!(cache.contains(&tls, Format::Html));
+        assert_eq!(
+            cache.cached_document(&quic).unwrap().formats,
+            [Format::Text]
+        );
+
+        let report = cache
+            .garbage_collect(&GcPolicy::keeping(vec![Format::Html]))
+            .unwrap();
+        assert!(report.redundant_formats.is_empty());
+    }
+
+    #[test]
+    fn read_only_caches_are_left_alone() {
+        let (cache, temp) = messy_cache();
+        let read_only = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_read_only(true);
+        let policy = GcPolicy::keeping(vec![Format::Text]);
+        assert!(read_only.garbage_collect(&policy).unwrap().is_empty());
+        assert!(cache.contains(&DocumentType::Rfc(9000), Format::Html));
+        assert!(temp.path().join("exports/old").is_dir());
+    }
+}
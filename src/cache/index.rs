@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{CacheMetadata, CachedDocument};
+use crate::models::{DocumentType, Format};
+
+/// Version of the `index.json` layout. An index written with any other
+/// version is rebuilt from the document files rather than migrated.
+pub const INDEX_VERSION: u32 = 1;
+
+/// Everything a cache listing needs, kept in one file so listing doesn't
+/// read every `.meta` sidecar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheIndex {
+    pub version: u32,
+    /// Keyed by document name
+    pub entries: BTreeMap<String, IndexEntry>,
+}
+
+/// One cached document as of its last write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// `rfc` or `draft`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The `.meta` sidecar's contents (title, cached_at, tags, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CacheMetadata>,
+    /// Formats present, text first
+    pub formats: Vec<Format>,
+    /// Bytes on disk across those formats
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl CacheIndex {
+    pub fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// The index of `docs`, as listed from the files.
+    pub fn from_listing(docs: &[CachedDocument]) -> Self {
+        let mut index = Self::new();
+        for doc in docs {
+            index.insert(doc);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, doc: &CachedDocument) {
+        self.entries.insert(
+            doc.doc_type.name(),
+            IndexEntry {
+                kind: match doc.doc_type {
+                    DocumentType::Rfc(_) => "rfc",
+                    DocumentType::Draft(_) => "draft",
+                }
+                .to_string(),
+                metadata: doc.metadata.clone(),
+                formats: doc.formats.clone(),
+                size: doc.size,
+                modified: doc.modified,
+            },
+        );
+    }
+
+    /// The listing this index describes, or `None` if it doesn't cover
+    /// exactly the documents in `names` (a write it missed, or files
+    /// changed by hand). `has_notes` answers the one thing notes editing
+    /// changes without going through the cache.
+    pub fn listing(
+        &self,
+        names: &[DocumentType],
+        has_notes: impl Fn(&DocumentType) -> bool,
+    ) -> Option<Vec<CachedDocument>> {
+        if self.version != INDEX_VERSION || self.entries.len() != names.len() {
+            return None;
+        }
+        names
+            .iter()
+            .map(|doc| {
+                let entry = self.entries.get(&doc.name())?;
+                Some(entry.describe(doc.clone(), has_notes(doc)))
+            })
+            .collect()
+    }
+
+    /// The entry for `doc`, if the index has one and is current.
+    pub fn entry(&self, doc: &DocumentType) -> Option<&IndexEntry> {
+        if self.version != INDEX_VERSION {
+            return None;
+        }
+        self.entries.get(&doc.name())
+    }
+}
+
+impl IndexEntry {
+    /// `doc_type` as this entry describes it.
+    pub fn describe(&self, doc_type: DocumentType, has_notes: bool) -> CachedDocument {
+        CachedDocument {
+            doc_type,
+            metadata: self.metadata.clone(),
+            formats: self.formats.clone(),
+            size: self.size,
+            modified: self.modified,
+            has_notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(name: &str) -> CachedDocument {
+        CachedDocument {
+            doc_type: DocumentType::from_canonical_name(name),
+            metadata: Some(CacheMetadata::new(name.to_uppercase(), Utc::now())),
+            formats: vec![Format::Text],
+            size: 42,
+            modified: None,
+            has_notes: false,
+        }
+    }
+
+    #[test]
+    fn listing_requires_exactly_the_indexed_documents() {
+        let index = CacheIndex::from_listing(&[cached("rfc9000"), cached("draft-ietf-foo-01")]);
+        let names = [
+            DocumentType::Rfc(9000),
+            DocumentType::Draft("draft-ietf-foo-01".to_string()),
+        ];
+        let listing = index
+            .listing(&names, |doc| *doc == DocumentType::Rfc(9000))
+            .unwrap();
+        assert_eq!(listing.len(), 2);
+        assert_eq!(listing[0].metadata.as_ref().unwrap().title, "RFC9000");
+        assert!(listing[0].has_notes);
+        assert!(!listing[1].has_notes);
+
+        assert!(index.listing(&names[..1], |_| false).is_none());
+        let other = [DocumentType::Rfc(9000), DocumentType::Rfc(9001)];
+        assert!(index.listing(&other, |_| false).is_none());
+
+        let old = CacheIndex {
+            version: INDEX_VERSION + 1,
+            ..index
+        };
+        assert!(old.listing(&names, |_| false).is_none());
+    }
+}
@@ -0,0 +1,257 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::cache::CachedDocument;
+use crate::models::{draft_base_and_rev, DocumentType, SearchFilter};
+
+/// Orderings for a cache listing. Each sorts ascending; ties fall back
+/// to the name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheSort {
+    /// Document name, with numbers compared numerically
+    #[default]
+    Name,
+    /// When the document was cached, oldest first. Unknown dates sort
+    /// as oldest.
+    Date,
+    /// Bytes on disk, smallest first
+    Size,
+    /// Title, case-insensitively. Documents without a title come last.
+    Title,
+    /// When the document was last opened, least recent first. Documents
+    /// never opened sort first.
+    Accessed,
+}
+
+/// Drop entries `filter` excludes and sort the rest by `sort`.
+pub fn arrange_cached(
+    docs: &mut Vec<CachedDocument>,
+    filter: SearchFilter,
+    sort: CacheSort,
+    reverse: bool,
+) {
+    docs.retain(|doc| filter.includes(&doc.doc_type));
+    docs.sort_by(|a, b| {
+        let by_name = || natural_cmp(&a.doc_type.name(), &b.doc_type.name());
+        let order = match sort {
+            CacheSort::Name => Ordering::Equal,
+            CacheSort::Date => a.cached_at().cmp(&b.cached_at()),
+            CacheSort::Size => a.size.cmp(&b.size),
+            CacheSort::Title => title_key(a).cmp(&title_key(b)),
+            CacheSort::Accessed => a.last_accessed().cmp(&b.last_accessed()),
+        };
+        order.then_with(by_name)
+    });
+    if reverse {
+        docs.reverse();
+    }
+}
+
+/// The `limit` most recently opened documents, most recent first.
+/// Documents never opened are left out.
+pub fn recently_accessed(mut docs: Vec<CachedDocument>, limit: usize) -> Vec<CachedDocument> {
+    docs.retain(|doc| doc.last_accessed().is_some());
+    arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Accessed, true);
+    docs.truncate(limit);
+    docs
+}
+
+/// Collapse each draft's cached revisions onto the newest one, which
+/// keeps its place in `docs`. Each entry comes with the other cached
+/// revision numbers, oldest first; RFCs and unversioned drafts have none.
+pub fn group_draft_revisions(docs: Vec<CachedDocument>) -> Vec<(CachedDocument, Vec<u32>)> {
+    let mut families: HashMap<String, Vec<u32>> = HashMap::new();
+    for doc in &docs {
+        if let (base, Some(rev)) = draft_revision(&doc.doc_type) {
+            families.entry(base.to_string()).or_default().push(rev);
+        }
+    }
+    docs.into_iter()
+        .filter_map(|doc| {
+            let (base, Some(rev)) = draft_revision(&doc.doc_type) else {
+                return Some((doc, Vec::new()));
+            };
+            let revs = &families[base];
+            if revs.iter().any(|&other| other > rev) {
+                return None;
+            }
+            let mut older: Vec<u32> = revs.iter().copied().filter(|&r| r != rev).collect();
+            older.sort_unstable();
+            Some((doc, older))
+        })
+        .collect()
+}
+
+/// Family name and revision of a draft; RFCs have no revision.
+pub(crate) fn draft_revision(doc: &DocumentType) -> (&str, Option<u32>) {
+    match doc {
+        DocumentType::Draft(name) => draft_base_and_rev(name),
+        DocumentType::Rfc(_) => ("", None),
+    }
+}
+
+/// Missing titles sort after every real one.
+fn title_key(doc: &CachedDocument) -> (bool, String) {
+    match doc.metadata.as_ref().map(|meta| meta.title.trim()) {
+        Some(title) if !title.is_empty() => (false, title.to_lowercase()),
+        _ => (true, String::new()),
+    }
+}
+
+/// Compare strings treating runs of digits as numbers, so `rfc900` sorts
+/// before `rfc9000` and `draft-foo-9` before `draft-foo-10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let (na, rest_a) = split_digits(a);
+            let (nb, rest_b) = split_digits(b);
+            let (ta, tb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+            let order = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb));
+            if order != Ordering::Equal {
+                return order;
+            }
+            (a, b) = (rest_a, rest_b);
+        } else {
+            if ca != cb {
+                return ca.cmp(&cb);
+            }
+            (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use crate::models::{DocumentType, Format};
+    use chrono::{TimeZone, Utc};
+
+    fn cached(name: &str, title: Option<&str>, day: u32, size: u64) -> CachedDocument {
+        CachedDocument {
+            doc_type: DocumentType::from_canonical_name(name),
+            metadata: title.map(|title| {
+                CacheMetadata::new(title, Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap())
+            }),
+            formats: vec![Format::Text],
+            size,
+            modified: Some(Utc.with_ymd_and_hms(2025, 2, day, 0, 0, 0).unwrap()),
+            has_notes: false,
+        }
+    }
+
+    fn sample() -> Vec<CachedDocument> {
+        vec![
+            cached("rfc9000", Some("QUIC"), 3, 300),
+            cached("draft-ietf-foo-10", Some("foo"), 1, 100),
+            cached("rfc900", Some("Assigned Numbers"), 2, 500),
+            cached("draft-ietf-foo-9", None, 4, 200),
+        ]
+    }
+
+    fn names(docs: &[CachedDocument]) -> Vec<String> {
+        docs.iter().map(|d| d.doc_type.name()).collect()
+    }
+
+    #[test]
+    fn natural_cmp_compares_digit_runs_numerically() {
+        assert_eq!(natural_cmp("rfc900", "rfc9000"), Ordering::Less);
+        assert_eq!(natural_cmp("rfc10", "rfc9"), Ordering::Greater);
+        assert_eq!(natural_cmp("draft-a-09", "draft-a-10"), Ordering::Less);
+        assert_eq!(natural_cmp("rfc1", "rfc1"), Ordering::Equal);
+        assert_eq!(natural_cmp("draft-a", "rfc1"), Ordering::Less);
+    }
+
+    #[test]
+    fn sorts_by_each_key() {
+        let mut docs = sample();
+        arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Name, false);
+        assert_eq!(
+            names(&docs),
+            ["draft-ietf-foo-9", "draft-ietf-foo-10", "rfc900", "rfc9000"]
+        );
+
+        // Metadata dates win over file times; the untitled draft falls
+        // back to its (later) modification time.
+        arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Date, false);
+        assert_eq!(
+            names(&docs),
+            ["draft-ietf-foo-10", "rfc900", "rfc9000", "draft-ietf-foo-9"]
+        );
+
+        arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Size, true);
+        assert_eq!(
+            names(&docs),
+            ["rfc900", "rfc9000", "draft-ietf-foo-9", "draft-ietf-foo-10"]
+        );
+
+        arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Title, false);
+        assert_eq!(
+            names(&docs),
+            ["rfc900", "draft-ietf-foo-10", "rfc9000", "draft-ietf-foo-9"]
+        );
+    }
+
+    #[test]
+    fn recently_accessed_orders_newest_first() {
+        let mut docs = sample();
+        for (doc, day) in docs.iter_mut().zip([Some(5), None, Some(9), Some(7)]) {
+            if let Some(meta) = doc.metadata.as_mut() {
+                meta.last_accessed =
+                    day.map(|d| Utc.with_ymd_and_hms(2025, 3, d, 0, 0, 0).unwrap());
+            }
+        }
+        // draft-ietf-foo-9 has no metadata, so it was never recorded.
+        assert_eq!(
+            names(&recently_accessed(docs.clone(), 10)),
+            ["rfc900", "rfc9000"]
+        );
+        assert_eq!(names(&recently_accessed(docs, 1)), ["rfc900"]);
+    }
+
+    #[test]
+    fn groups_draft_revisions_under_the_newest() {
+        let mut docs = vec![
+            cached("draft-ietf-quic-transport-29", Some("QUIC"), 1, 100),
+            cached("rfc9000", Some("QUIC"), 2, 100),
+            cached("draft-ietf-quic-transport-34", Some("QUIC"), 3, 100),
+            cached("draft-ietf-quic-transport", Some("QUIC"), 4, 100),
+            cached("draft-ietf-quic-transport-32", Some("QUIC"), 5, 100),
+            cached("draft-ietf-tls-esni-22", Some("ECH"), 6, 100),
+        ];
+        arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Date, true);
+        let grouped: Vec<(String, Vec<u32>)> = group_draft_revisions(docs)
+            .into_iter()
+            .map(|(doc, older)| (doc.doc_type.name(), older))
+            .collect();
+        assert_eq!(
+            grouped,
+            [
+                ("draft-ietf-tls-esni-22".to_string(), vec![]),
+                ("draft-ietf-quic-transport".to_string(), vec![]),
+                ("draft-ietf-quic-transport-34".to_string(), vec![29, 32]),
+                ("rfc9000".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_by_document_type() {
+        let mut rfcs = sample();
+        arrange_cached(&mut rfcs, SearchFilter::RfcsOnly, CacheSort::Name, false);
+        assert_eq!(names(&rfcs), ["rfc900", "rfc9000"]);
+
+        let mut drafts = sample();
+        arrange_cached(&mut drafts, SearchFilter::DraftsOnly, CacheSort::Name, true);
+        assert_eq!(names(&drafts), ["draft-ietf-foo-10", "draft-ietf-foo-9"]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::Result;
+
+use crate::cache::{CacheMetadata, DocumentCache};
+use crate::models::{DocumentType, Format};
+
+#[derive(Debug, Default)]
+struct Inner {
+    documents: HashMap<(DocumentType, Format), String>,
+    metadata: HashMap<DocumentType, CacheMetadata>,
+}
+
+/// In-memory `DocumentCache`. Nothing survives the process; useful for
+/// tests and for embedders that manage persistence themselves.
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    inner: Mutex<Inner>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // A panic mid-update can't leave the maps half-written in a way
+        // that matters for a cache, so recover from poisoning.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl DocumentCache for MemoryCache {
+    fn get_document(&self, doc: &DocumentType, format: Format) -> Option<String> {
+        self.lock().documents.get(&(doc.clone(), format)).cloned()
+    }
+
+    fn store_document(&self, doc: &DocumentType, format: Format, content: &str) -> Result<()> {
+        self.lock()
+            .documents
+            .insert((doc.clone(), format), content.to_string());
+        Ok(())
+    }
+
+    fn get_metadata(&self, doc: &DocumentType) -> Option<CacheMetadata> {
+        self.lock().metadata.get(doc).cloned()
+    }
+
+    fn store_metadata(&self, doc: &DocumentType, meta: &CacheMetadata) -> Result<()> {
+        self.lock().metadata.insert(doc.clone(), meta.clone());
+        Ok(())
+    }
+
+    fn list_cached(&self) -> Vec<DocumentType> {
+        let inner = self.lock();
+        let mut docs: Vec<DocumentType> = inner.documents.keys().map(|(d, _)| d.clone()).collect();
+        docs.sort_by_key(|d| d.name());
+        docs.dedup();
+        docs
+    }
+
+    fn remove(&self, doc: &DocumentType) -> Result<bool> {
+        let mut inner = self.lock();
+        let before = inner.documents.len();
+        inner.documents.retain(|(d, _), _| d != doc);
+        let removed = inner.documents.len() != before;
+        inner.metadata.remove(doc);
+        Ok(removed)
+    }
+
+    fn clear(&self) -> Result<()> {
+        let mut inner = self.lock();
+        inner.documents.clear();
+        inner.metadata.clear();
+        Ok(())
+    }
+}
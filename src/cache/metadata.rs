@@ -1,11 +1,315 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::api::{FormatProbe, Provenance};
+use crate::models::{Document, Lineage};
+
 /// Metadata associated with a cached document
+///
+/// The title and abstract are server-derived: a refresh replaces them
+/// (see [`merge_from_server`](Self::merge_from_server)). Tags, the
+/// bookmark, and the access time are user-owned and survive it, as do
+/// notes, which live in a file of their own. The remaining lookups keep
+/// their own `checked_at` and are redone when that expires.
+///
+/// Fields are added as the cache records more, so outside this crate
+/// start from [`new`](Self::new) and set the fields you need.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct CacheMetadata {
     /// Document title
     pub title: String,
+    /// The Datatracker's abstract, as of the last title lookup: empty if
+    /// it had none, missing if recorded before abstracts were kept. See
+    /// [`recorded_abstract`](Self::recorded_abstract).
+    #[serde(rename = "abstract", default, skip_serializing_if = "Option::is_none")]
+    pub abstract_text: Option<String>,
     /// When the document was cached
     pub cached_at: DateTime<Utc>,
+    /// When the document was last opened from the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_accessed: Option<DateTime<Utc>>,
+    /// User-assigned tags, lowercase and sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where to resume reading, set with `rfc mark`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bookmark: Option<Bookmark>,
+    /// What the Datatracker last said about a draft's lifecycle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft_status: Option<DraftStatus>,
+    /// Where the cached content was downloaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Provenance>,
+    /// Which renderings were published, as last probed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formats: Option<FormatAvailability>,
+    /// The drafts and RFC the document belongs to, as last looked up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<CachedLineage>,
+}
+
+impl CacheMetadata {
+    /// Metadata for a document that was just cached, with no access
+    /// history or tags.
+    pub fn new(title: impl Into<String>, cached_at: DateTime<Utc>) -> Self {
+        Self {
+            title: title.into(),
+            abstract_text: None,
+            cached_at,
+            last_accessed: None,
+            tags: Vec::new(),
+            bookmark: None,
+            draft_status: None,
+            source: None,
+            formats: None,
+            lineage: None,
+        }
+    }
+
+    /// Replace the server-derived fields with `fresh`, keeping what the
+    /// user attached. `cached_at` is left to the caller.
+    pub fn merge_from_server(&mut self, fresh: ServerMetadata) {
+        self.title = fresh.title;
+        self.set_abstract(fresh.abstract_text);
+    }
+
+    /// Record `text` as the Datatracker's abstract, cut to
+    /// [`MAX_ABSTRACT_LEN`]. `None` is kept as an empty abstract, so the
+    /// lookup isn't repeated by `rfc cache backfill`.
+    pub fn set_abstract(&mut self, text: Option<String>) {
+        let text = text.map(|text| text.trim().to_string()).unwrap_or_default();
+        self.abstract_text = Some(cap_abstract(text));
+    }
+
+    /// The recorded abstract, unless there's none or it was empty.
+    pub fn recorded_abstract(&self) -> Option<&str> {
+        self.abstract_text
+            .as_deref()
+            .filter(|text| !text.trim().is_empty())
+    }
+
+    /// Whether `tag` (compared case-insensitively) is attached.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(&normalize_tag(tag))
+    }
+}
+
+/// What a Datatracker lookup says about a document: the part of
+/// [`CacheMetadata`] a refresh replaces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerMetadata {
+    pub title: String,
+    pub abstract_text: Option<String>,
+}
+
+impl From<Document> for ServerMetadata {
+    fn from(doc: Document) -> Self {
+        Self {
+            title: doc.title,
+            abstract_text: doc.abstract_text,
+        }
+    }
+}
+
+/// Longest abstract kept in metadata, in bytes. Real ones run to a few
+/// hundred words; anything longer is cut at a word and ends in `…`.
+pub const MAX_ABSTRACT_LEN: usize = 4096;
+
+fn cap_abstract(mut text: String) -> String {
+    if text.len() <= MAX_ABSTRACT_LEN {
+        return text;
+    }
+    let mut end = MAX_ABSTRACT_LEN - '…'.len_utf8();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = text[..end].rfind(char::is_whitespace).unwrap_or(end);
+    text.truncate(end);
+    text.truncate(text.trim_end().len());
+    text.push('…');
+    text
+}
+
+/// A reading position within a document's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// 1-based line to resume at
+    pub line: usize,
+    /// How many lines the document had when the mark was set
+    pub lines: usize,
+}
+
+impl Bookmark {
+    /// Whether the mark still points somewhere sensible in a document of
+    /// `lines` lines. A revision that grows or shrinks the text by more
+    /// than 5% has probably moved the marked passage.
+    pub fn fits(&self, lines: usize) -> bool {
+        self.line <= lines && self.lines.abs_diff(lines) * 20 <= self.lines
+    }
+}
+
+/// How long cached draft lifecycle facts are trusted. Drafts change
+/// state far less often than they're read, but a day keeps a new
+/// replacement or RFC from going unnoticed for long.
+pub const DRAFT_STATUS_TTL: Duration = Duration::days(1);
+
+/// A draft's expiry, replacement, and publication, as last reported by
+/// the Datatracker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DraftStatus {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaced_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfc_number: Option<u32>,
+    /// When the Datatracker was last asked
+    pub checked_at: DateTime<Utc>,
+}
+
+impl DraftStatus {
+    /// Whether this entry is recent enough to use without re-querying.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.checked_at < DRAFT_STATUS_TTL
+    }
+}
+
+/// How long probed format availability is trusted. Renderings are only
+/// ever added, and rarely, so a day saves repeated HEADs at little risk.
+pub const FORMAT_PROBE_TTL: Duration = Duration::days(1);
+
+/// The result of probing a document's renderings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatAvailability {
+    pub probes: Vec<FormatProbe>,
+    /// When the probe was made
+    pub checked_at: DateTime<Utc>,
+}
+
+impl FormatAvailability {
+    /// Whether this probe is recent enough to use without probing again.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.checked_at < FORMAT_PROBE_TTL
+    }
+}
+
+/// How long a lineage that hasn't reached an RFC yet is trusted. One
+/// that has is final.
+pub const LINEAGE_TTL: Duration = Duration::days(1);
+
+/// A document's [`Lineage`] and when it was looked up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedLineage {
+    pub lineage: Lineage,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl CachedLineage {
+    /// Whether this lineage can be used without looking it up again.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.lineage.rfc().is_some() || now - self.checked_at < LINEAGE_TTL
+    }
+}
+
+/// Tags are compared and stored lowercase, without surrounding space.
+pub(crate) fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bookmark_tolerates_small_revisions() {
+        let mark = Bookmark {
+            line: 500,
+            lines: 1000,
+        };
+        assert!(mark.fits(1000));
+        assert!(mark.fits(1050));
+        assert!(mark.fits(950));
+        assert!(!mark.fits(1051));
+        assert!(!mark.fits(949));
+        assert!(!Bookmark {
+            line: 990,
+            lines: 1000
+        }
+        .fits(980));
+    }
+
+    #[test]
+    fn merging_from_server_keeps_user_state() {
+        let cached_at = Utc::now() - Duration::days(30);
+        let mark = Bookmark {
+            line: 40,
+            lines: 1000,
+        };
+        let mut meta = CacheMetadata {
+            abstract_text: Some("Old abstract.".into()),
+            last_accessed: Some(cached_at),
+            tags: vec!["transport".into()],
+            bookmark: Some(mark),
+            ..CacheMetadata::new("QUIC (old title)", cached_at)
+        };
+        meta.merge_from_server(ServerMetadata {
+            title: "QUIC".into(),
+            abstract_text: Some("QUIC is a transport.".into()),
+        });
+        assert_eq!(meta.title, "QUIC");
+        assert_eq!(meta.recorded_abstract(), Some("QUIC is a transport."));
+        assert_eq!(meta.tags, ["transport"]);
+        assert_eq!(meta.bookmark, Some(mark));
+        assert_eq!(meta.last_accessed, Some(cached_at));
+        assert_eq!(meta.cached_at, cached_at);
+
+        meta.merge_from_server(ServerMetadata {
+            title: "QUIC".into(),
+            abstract_text: None,
+        });
+        assert_eq!(meta.recorded_abstract(), None);
+        assert_eq!(meta.tags, ["transport"]);
+    }
+
+    #[test]
+    fn merging_from_server_fills_in_new_metadata() {
+        let mut meta = CacheMetadata::new("", Utc::now());
+        meta.merge_from_server(ServerMetadata {
+            title: "HTTP/3".into(),
+            abstract_text: Some("  HTTP over QUIC.\n".into()),
+        });
+        assert_eq!(meta.title, "HTTP/3");
+        assert_eq!(meta.recorded_abstract(), Some("HTTP over QUIC."));
+        assert!(meta.tags.is_empty());
+        assert_eq!(meta.bookmark, None);
+        assert_eq!(meta.last_accessed, None);
+    }
+
+    #[test]
+    fn abstracts_are_capped_and_old_metadata_has_none() {
+        let mut meta = CacheMetadata::new("QUIC", Utc::now());
+        meta.set_abstract(Some("  QUIC is a transport.\n".into()));
+        assert_eq!(meta.recorded_abstract(), Some("QUIC is a transport."));
+        meta.set_abstract(None);
+        assert_eq!(meta.abstract_text.as_deref(), Some(""));
+        assert_eq!(meta.recorded_abstract(), None);
+
+        meta.set_abstract(Some("naïve words ".repeat(1000)));
+        let capped = meta.recorded_abstract().unwrap();
+        assert!(capped.len() <= MAX_ABSTRACT_LEN);
+        let kept = capped.strip_suffix('…').unwrap();
+        assert!(
+            kept.ends_with("naïve") || kept.ends_with("words"),
+            "{}",
+            kept
+        );
+        assert!("naïve words ".repeat(1000).starts_with(kept));
+
+        let old: CacheMetadata =
+            serde_json::from_str(r#"{"title": "QUIC", "cached_at": "2024-01-03T00:00:00Z"}"#)
+                .unwrap();
+        assert_eq!(old.abstract_text, None);
+        assert_eq!(old.recorded_abstract(), None);
+    }
 }
@@ -1,5 +1,30 @@
+mod audit;
+mod backend;
+mod counters;
+mod gc;
+mod index;
+mod listing;
+mod memory;
 mod metadata;
+mod relations;
+mod searches;
 mod storage;
+mod verify;
 
-pub use metadata::CacheMetadata;
-pub use storage::{CacheManager, CachedDocument};
+pub use audit::CacheAuditReport;
+pub use backend::DocumentCache;
+pub use counters::{CacheCounters, CacheStats};
+pub use gc::{GcPolicy, GcReport};
+pub use listing::{arrange_cached, group_draft_revisions, recently_accessed, CacheSort};
+pub use memory::MemoryCache;
+pub use metadata::{
+    Bookmark, CacheMetadata, CachedLineage, DraftStatus, FormatAvailability, ServerMetadata,
+    DRAFT_STATUS_TTL, FORMAT_PROBE_TTL, LINEAGE_TTL, MAX_ABSTRACT_LEN,
+};
+pub use relations::{Obsolescence, OBSOLESCENCE_TTL};
+pub use searches::{CachedSearch, SEARCH_CACHE_TTL};
+pub use storage::{CachedDocument, DocumentUsage, FsCache, ReadOnlyCache, RemovalReport};
+pub use verify::CacheVerifyReport;
+
+/// The filesystem cache under its historical name.
+pub type CacheManager = FsCache;
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long a cached obsolescence lookup is trusted. RFCs are obsoleted
+/// rarely, so a month keeps repeated searches off the network without
+/// hiding new obsoletions for long.
+pub const OBSOLESCENCE_TTL: Duration = Duration::days(30);
+
+/// Cached answer to "which RFCs obsolete this one?"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Obsolescence {
+    /// RFC numbers that obsolete the document. Empty when it is current.
+    pub obsoleted_by: Vec<u32>,
+    /// When the Datatracker was last asked
+    pub checked_at: DateTime<Utc>,
+}
+
+impl Obsolescence {
+    /// Whether this entry is recent enough to use without re-querying.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.checked_at < OBSOLESCENCE_TTL
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{QueryMode, SearchOptions, SearchResult};
+
+/// How long search results are reused by default. Short enough that new
+/// drafts show up the same session, long enough to cover refining a query.
+pub const SEARCH_CACHE_TTL: Duration = Duration::minutes(15);
+
+/// A search result set as it came back from the Datatracker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSearch {
+    pub result: SearchResult,
+    /// When the Datatracker was asked
+    pub cached_at: DateTime<Utc>,
+}
+
+impl CachedSearch {
+    /// Whether this entry is younger than `ttl`.
+    pub fn is_fresh(&self, now: DateTime<Utc>, ttl: Duration) -> bool {
+        now - self.cached_at < ttl
+    }
+}
+
+/// The key a search is cached under. Queries differing only in case or
+/// spacing, or in the order statuses were given, share an entry.
+pub(crate) fn search_key(query: &str, options: &SearchOptions, limit: usize) -> String {
+    let query = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut status: Vec<&str> = options.status.iter().map(|level| level.slug()).collect();
+    status.sort_unstable();
+    status.dedup();
+    let status = if status.is_empty() {
+        String::new()
+    } else {
+        format!("+{}", status.join(","))
+    };
+    let dates = match (options.since, options.before) {
+        (None, None) => String::new(),
+        (since, before) => format!(
+            "@{}..{}",
+            since.map(|d| d.to_string()).unwrap_or_default(),
+            before.map(|d| d.to_string()).unwrap_or_default()
+        ),
+    };
+    let mode = match options.mode {
+        QueryMode::Words => "",
+        QueryMode::Phrase => "~phrase",
+    };
+    let keyword = options
+        .keyword
+        .as_ref()
+        .map(|k| format!("#{}", k))
+        .unwrap_or_default();
+    format!(
+        "{:?}{}{}{}{}/{}/{}",
+        options.filter, status, dates, mode, keyword, limit, query
+    )
+}
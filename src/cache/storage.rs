@@ -1,118 +1,577 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
-use directories::ProjectDirs;
-
-use crate::cache::CacheMetadata;
-use crate::models::{DocumentType, Format};
+use chrono::{DateTime, Duration, Utc};
+use directories::{BaseDirs, ProjectDirs};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Serialize, Serializer};
+
+use crate::cache::counters::CacheCounters;
+use crate::cache::index::CacheIndex;
+use crate::cache::listing::draft_revision;
+use crate::cache::searches::search_key;
+use crate::cache::verify::{sha256_file, sha256_hex};
+use crate::cache::{CacheMetadata, CachedSearch, DocumentCache, Obsolescence};
+use crate::models::{
+    draft_base_and_rev, DocumentType, Format, RfcIndex, SearchOptions, SearchResult,
+};
 
 /// A cached document with optional metadata
 #[derive(Debug, Clone)]
 pub struct CachedDocument {
     pub doc_type: DocumentType,
     pub metadata: Option<CacheMetadata>,
+    /// Formats present in the cache, text first
+    pub formats: Vec<Format>,
+    /// Bytes stored across those formats (compressed size for `.gz`)
+    pub size: u64,
+    /// When a file was last written, for backends that know
+    pub modified: Option<DateTime<Utc>>,
+    /// Whether the user has notes on it (filesystem cache only)
+    pub has_notes: bool,
+}
+
+/// Serializes flat, as `rfc cache list --json` prints it. The field set
+/// is a scripting interface: add fields, don't rename or remove them.
+impl Serialize for CachedDocument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            name: String,
+            #[serde(rename = "type")]
+            kind: &'static str,
+            title: Option<&'a str>,
+            #[serde(rename = "abstract")]
+            abstract_text: Option<&'a str>,
+            cached_at: Option<DateTime<Utc>>,
+            last_accessed: Option<DateTime<Utc>>,
+            size: u64,
+            formats: Vec<&'static str>,
+            tags: &'a [String],
+            has_notes: bool,
+        }
+        Entry {
+            name: self.doc_type.name(),
+            kind: match self.doc_type {
+                DocumentType::Rfc(_) => "rfc",
+                DocumentType::Draft(_) => "draft",
+            },
+            title: self
+                .metadata
+                .as_ref()
+                .map(|meta| meta.title.as_str())
+                .filter(|title| !title.trim().is_empty()),
+            abstract_text: self
+                .metadata
+                .as_ref()
+                .and_then(|meta| meta.recorded_abstract()),
+            cached_at: self.cached_at(),
+            last_accessed: self.last_accessed(),
+            size: self.size,
+            formats: self.formats.iter().map(|f| f.extension()).collect(),
+            tags: self.metadata.as_ref().map_or(&[], |meta| &meta.tags),
+            has_notes: self.has_notes,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl CachedDocument {
+    /// When the document was last opened, if that was recorded.
+    pub fn last_accessed(&self) -> Option<DateTime<Utc>> {
+        self.metadata.as_ref().and_then(|meta| meta.last_accessed)
+    }
+
+    /// When the document was cached: from its metadata, else the file
+    /// modification time.
+    pub fn cached_at(&self) -> Option<DateTime<Utc>> {
+        self.metadata
+            .as_ref()
+            .map(|meta| meta.cached_at)
+            .or(self.modified)
+    }
+}
+
+/// Disk usage of cached document files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentUsage {
+    /// Number of document files (metadata sidecars excluded)
+    pub files: usize,
+    /// How many of those are gzip-compressed
+    pub compressed_files: usize,
+    /// Bytes on disk
+    pub on_disk: u64,
+    /// Bytes the documents would take uncompressed. Taken from each gzip
+    /// trailer, so it's exact for documents under 4 GiB.
+    pub uncompressed: u64,
+}
+
+/// What clearing, removing, or pruning deleted, measured before the
+/// files went.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RemovalReport {
+    /// Documents whose content was deleted, in the order they went
+    pub documents_removed: Vec<DocumentType>,
+    /// Size on disk of every deleted file, metadata and indexes included
+    pub bytes_freed: u64,
+    /// Number of metadata sidecars deleted
+    pub metadata_removed: usize,
 }
 
-/// Manages local document caching
-pub struct CacheManager {
+impl RemovalReport {
+    /// Whether nothing at all was deleted.
+    pub fn is_empty(&self) -> bool {
+        self.documents_removed.is_empty() && self.metadata_removed == 0 && self.bytes_freed == 0
+    }
+
+    /// Add what `other` deleted to this report.
+    pub fn merge(&mut self, other: RemovalReport) {
+        self.documents_removed.extend(other.documents_removed);
+        self.bytes_freed += other.bytes_freed;
+        self.metadata_removed += other.metadata_removed;
+    }
+}
+
+/// Filesystem-backed document cache. Also exported as `CacheManager`,
+/// the name the rest of the crate (and existing embedders) use.
+pub struct FsCache {
     cache_dir: PathBuf,
+    /// Write documents gzip-compressed (`.txt.gz`). Both forms are
+    /// always readable.
+    compress: bool,
+    /// Check documents against their checksums on every read.
+    verify_reads: bool,
+    /// Never write: see [`with_read_only`](Self::with_read_only).
+    read_only: bool,
+    /// Read-only cache consulted when this one misses
+    secondary: Option<Box<FsCache>>,
+    /// Counted since this was opened; see [`record_counters`](Self::record_counters)
+    pub(crate) session: Mutex<CacheCounters>,
+}
+
+/// Returned by [`FsCache::ensure_writable`] for a cache opened read-only,
+/// so callers can tell it apart from I/O errors by downcasting.
+#[derive(Debug)]
+pub struct ReadOnlyCache {
+    pub dir: PathBuf,
 }
 
-impl CacheManager {
+impl std::fmt::Display for ReadOnlyCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The cache at {} is read-only", self.dir.display())
+    }
+}
+
+impl std::error::Error for ReadOnlyCache {}
+
+impl FsCache {
     /// Create a new cache manager
     pub fn new() -> Result<Self> {
-        let cache_dir = Self::default_cache_dir()?;
-        fs::create_dir_all(&cache_dir).context("Failed to create cache directory")?;
-        Ok(Self { cache_dir })
+        Self::with_dir(Self::default_cache_dir()?)
     }
 
     /// Create a cache manager with a custom directory
     pub fn with_dir(cache_dir: PathBuf) -> Result<Self> {
-        fs::create_dir_all(&cache_dir).context("Failed to create cache directory")?;
-        Ok(Self { cache_dir })
+        fs::create_dir_all(&cache_dir)
+            .with_context(|| format!("Failed to create cache directory {}", cache_dir.display()))?;
+        Ok(Self {
+            cache_dir,
+            compress: false,
+            verify_reads: false,
+            read_only: false,
+            secondary: None,
+            session: Mutex::default(),
+        })
     }
 
-    /// Get the default cache directory
+    /// Store documents gzip-compressed from now on (or stop doing so).
+    /// Documents already cached in the other form stay readable and are
+    /// converted the next time they're stored, or all at once by
+    /// [`recompress`](Self::recompress).
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Check each document read against the SHA-256 taken when it was
+    /// stored, treating a mismatch as a cache miss so it's fetched again.
+    /// Costs a hash per read; documents without a checksum are trusted.
+    pub fn with_verified_reads(mut self, verify: bool) -> Self {
+        self.verify_reads = verify;
+        self
+    }
+
+    /// Never write to this cache. Stores (including
+    /// [`store_document_file`](Self::store_document_file), which leaves
+    /// the file where it was), metadata updates, access times, and
+    /// removals silently do nothing, so a shared pre-warmed cache can
+    /// be used as is; commands that exist to change the cache should
+    /// call [`ensure_writable`](Self::ensure_writable) first.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`ReadOnlyCache`] if this cache was opened read-only.
+    pub fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(ReadOnlyCache {
+                dir: self.cache_dir.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Also look up documents, and their metadata, in the cache at
+    /// `dir` when this one doesn't have them. That cache is only read:
+    /// documents fetched later are stored here, and metadata for
+    /// documents found only there isn't copied over. Listings cover this
+    /// cache alone.
+    pub fn with_secondary(mut self, dir: PathBuf) -> Self {
+        self.secondary = Some(Box::new(FsCache {
+            cache_dir: dir,
+            compress: false,
+            verify_reads: self.verify_reads,
+            read_only: true,
+            secondary: None,
+            session: Mutex::default(),
+        }));
+        self
+    }
+
+    /// Whether new documents are written compressed.
+    pub fn compresses(&self) -> bool {
+        self.compress
+    }
+
+    /// Get the default cache directory: `$RFC_CACHE_DIR` when set,
+    /// otherwise the platform cache directory, then `~/.cache/rfc`, and
+    /// with no home directory at all, [`temp_cache_dir`](Self::temp_cache_dir).
     pub fn default_cache_dir() -> Result<PathBuf> {
-        if let Some(proj_dirs) = ProjectDirs::from("", "", "rfc") {
-            Ok(proj_dirs.cache_dir().to_path_buf())
-        } else {
-            // Fallback to home directory
-            let home = std::env::var("HOME").context("HOME not set")?;
-            Ok(PathBuf::from(home).join(".cache").join("rfc"))
+        if let Some(dir) = std::env::var_os("RFC_CACHE_DIR").filter(|d| !d.is_empty()) {
+            return Ok(PathBuf::from(dir));
         }
+        let platform = ProjectDirs::from("", "", "rfc").map(|dirs| dirs.cache_dir().to_path_buf());
+        let home = || {
+            BaseDirs::new()
+                .map(|dirs| dirs.home_dir().to_path_buf())
+                .or_else(|| home_from_env(|var| std::env::var_os(var)))
+        };
+        Ok(fallback_cache_dir(platform, home))
+    }
+
+    /// The cache directory of last resort, `rfc-cache` in the system's
+    /// temporary directory, for containers and services with no home
+    /// directory. The system may clear it at any time.
+    pub fn temp_cache_dir() -> PathBuf {
+        std::env::temp_dir().join("rfc-cache")
+    }
+
+    /// Whether this cache lives in [`temp_cache_dir`](Self::temp_cache_dir).
+    pub fn is_temporary(&self) -> bool {
+        self.cache_dir == Self::temp_cache_dir()
     }
 
     /// Get cached document content
     pub fn get_document(&self, doc: &DocumentType, format: Format) -> Option<String> {
-        let path = self.document_path(doc, format);
-        fs::read_to_string(path).ok()
+        let (tier, stored_as, path) = self.locate(doc, format)?;
+        let content = read_document(&path).ok()?;
+        if self.verify_reads {
+            let checksum = tier.checksum(&stored_as, format);
+            if checksum.is_some_and(|sum| sum != sha256_hex(content.as_bytes())) {
+                return None;
+            }
+        }
+        Some(content)
+    }
+
+    /// Whether `doc` is cached in `format`, without reading it.
+    pub fn contains(&self, doc: &DocumentType, format: Format) -> bool {
+        self.document_file(doc, format).is_some()
+    }
+
+    /// Path of the cached file for `doc` in `format`, if it's cached.
+    /// Lets callers hand large documents to other programs without
+    /// reading them into memory. The file is gzip-compressed when its
+    /// name ends in `.gz`. An unversioned draft that isn't cached under
+    /// its own name falls back to its [`newest_revision`](Self::newest_revision).
+    pub fn document_file(&self, doc: &DocumentType, format: Format) -> Option<PathBuf> {
+        self.locate(doc, format).map(|(_, _, path)| path)
+    }
+
+    /// [`document_file`](Self::document_file), along with the cache it's
+    /// in (this one or the secondary) and the document it's cached as.
+    fn locate(
+        &self,
+        doc: &DocumentType,
+        format: Format,
+    ) -> Option<(&FsCache, DocumentType, PathBuf)> {
+        if let Some(path) = self.exact_document_file(doc, format) {
+            return Some((self, doc.clone(), path));
+        }
+        if let Some(newest) = self.newest_revision(doc) {
+            if let Some(path) = self.exact_document_file(&newest, format) {
+                return Some((self, newest, path));
+            }
+        }
+        self.secondary.as_ref()?.locate(doc, format)
+    }
+
+    /// Whether this cache (not the secondary) has content for `doc`.
+    fn holds(&self, doc: &DocumentType) -> bool {
+        [Format::Text, Format::Html]
+            .into_iter()
+            .any(|format| self.exact_document_file(doc, format).is_some())
+    }
+
+    /// Like [`document_file`](Self::document_file), without the fallback.
+    pub(super) fn exact_document_file(
+        &self,
+        doc: &DocumentType,
+        format: Format,
+    ) -> Option<PathBuf> {
+        let (preferred, other) = self.document_paths(doc, format);
+        [preferred, other].into_iter().find(|p| p.is_file())
     }
 
-    /// Store document content in cache
+    /// Store document content in cache. The write goes to a temporary
+    /// file that is renamed into place, so readers never see a partial
+    /// document.
     pub fn store_document(&self, doc: &DocumentType, format: Format, content: &str) -> Result<()> {
-        let path = self.document_path(doc, format);
+        if self.read_only {
+            return Ok(());
+        }
+        let _span = tracing::info_span!("cache write", document = %doc).entered();
+        let (path, other) = self.document_paths(doc, format);
 
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).context("Failed to create document cache directory")?;
         }
 
-        fs::write(&path, content).context("Failed to write document to cache")?;
-        Ok(())
+        write_staged(&path, |file| {
+            write_document(file, content.as_bytes(), self.compress)
+        })
+        .context("Failed to write document to cache")?;
+        remove_if_exists(&other)?;
+        self.record_checksum(doc, format, Some(sha256_hex(content.as_bytes())))?;
+        self.refresh_index(doc);
+        self.note_revision(doc)
+    }
+
+    /// Move an already-downloaded file (e.g. from
+    /// `DocumentFetcher::fetch_to`) into the cache as `doc` in `format`,
+    /// returning its new path. `file` should be on the same filesystem as
+    /// the cache; otherwise (or when compressing) it is copied. A
+    /// read-only cache leaves `file` where it is and returns its path.
+    pub fn store_document_file(
+        &self,
+        doc: &DocumentType,
+        format: Format,
+        file: &Path,
+    ) -> Result<PathBuf> {
+        if self.read_only {
+            return Ok(file.to_path_buf());
+        }
+        let _span = tracing::info_span!("cache write", document = %doc).entered();
+        let (path, other) = self.document_paths(doc, format);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create document cache directory")?;
+        }
+        let checksum = sha256_file(file).context("Failed to read downloaded document")?;
+        if self.compress || fs::rename(file, &path).is_err() {
+            let content = fs::read(file).context("Failed to read downloaded document")?;
+            write_staged(&path, |staged| {
+                write_document(staged, &content, self.compress)
+            })
+            .context("Failed to copy document into cache")?;
+            let _ = fs::remove_file(file);
+        }
+        remove_if_exists(&other)?;
+        self.record_checksum(doc, format, Some(checksum))?;
+        self.refresh_index(doc);
+        self.note_revision(doc)?;
+        Ok(path)
+    }
+
+    /// Rewrite every cached document into the form the compression
+    /// setting calls for. Returns how many files were converted.
+    pub fn recompress(&self) -> Result<usize> {
+        if self.read_only {
+            return Ok(0);
+        }
+        let mut converted = 0;
+        for doc in self.list_cached() {
+            for format in [Format::Text, Format::Html] {
+                let (preferred, other) = self.document_paths(&doc, format);
+                if preferred.is_file() || !other.is_file() {
+                    continue;
+                }
+                let content = read_document(&other)
+                    .with_context(|| format!("Failed to read {}", other.display()))?;
+                self.store_document(&doc, format, &content)?;
+                converted += 1;
+            }
+        }
+        Ok(converted)
+    }
+
+    /// Sizes of the cached document files, compressed and not.
+    pub fn document_usage(&self) -> Result<DocumentUsage> {
+        let mut usage = DocumentUsage::default();
+        let docs_dir = self.cache_dir.join("documents");
+        if !docs_dir.exists() {
+            return Ok(usage);
+        }
+        for entry in fs::read_dir(&docs_dir).context("Failed to read cache directory")? {
+            let path = entry?.path();
+            let ext = path.extension().and_then(|e| e.to_str());
+            if matches!(ext, Some("meta") | Some("part")) || is_notes(&path) || !path.is_file() {
+                continue;
+            }
+            let size = fs::metadata(&path)?.len();
+            usage.files += 1;
+            usage.on_disk += size;
+            if is_gzip(&path) {
+                usage.compressed_files += 1;
+                usage.uncompressed += gzip_uncompressed_size(&path).unwrap_or(size);
+            } else {
+                usage.uncompressed += size;
+            }
+        }
+        Ok(usage)
     }
 
     /// Clear all cached documents
-    pub fn clear_cache(&self) -> Result<()> {
-        if self.cache_dir.exists() {
-            fs::remove_dir_all(&self.cache_dir).context("Failed to clear cache")?;
-            fs::create_dir_all(&self.cache_dir).context("Failed to recreate cache directory")?;
+    /// Notes are the user's own writing, so they survive; everything
+    /// else goes.
+    pub fn clear_cache(&self) -> Result<RemovalReport> {
+        let mut report = RemovalReport::default();
+        if self.read_only || !self.cache_dir.exists() {
+            return Ok(report);
         }
-        Ok(())
+        report.documents_removed = self.list_cached();
+        let docs_dir = self.cache_dir.join("documents");
+        for entry in fs::read_dir(&self.cache_dir).context("Failed to clear cache")? {
+            let path = entry?.path();
+            if path == docs_dir {
+                continue;
+            }
+            report.bytes_freed += tree_size(&path);
+            if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            }
+            .context("Failed to clear cache")?;
+        }
+        if docs_dir.exists() {
+            for entry in fs::read_dir(&docs_dir).context("Failed to clear cache")? {
+                let path = entry?.path();
+                if is_notes(&path) {
+                    continue;
+                }
+                report.bytes_freed += tree_size(&path);
+                if path.extension().is_some_and(|ext| ext == "meta") {
+                    report.metadata_removed += 1;
+                }
+                fs::remove_file(&path).context("Failed to clear cache")?;
+            }
+        }
+        Ok(report)
     }
 
     /// Remove a specific document from cache
-    /// Removes document content and associated metadata
-    /// Returns true if the document was found and removed
+    /// Returns true if the document was found and removed; see
+    /// [`remove_document`](Self::remove_document) for what went.
     pub fn remove(&self, doc: &DocumentType) -> Result<bool> {
-        let html_path = self.document_path(doc, Format::Html);
-        let text_path = self.document_path(doc, Format::Text);
-        let meta_path = self.metadata_path(doc);
-
-        let mut removed = false;
+        Ok(!self.remove_document(doc)?.documents_removed.is_empty())
+    }
 
-        if html_path.exists() {
-            fs::remove_file(&html_path).context("Failed to remove cached HTML file")?;
-            removed = true;
+    /// Remove document content (compressed or not, every format) and
+    /// associated metadata, reporting what was deleted.
+    pub fn remove_document(&self, doc: &DocumentType) -> Result<RemovalReport> {
+        let mut report = RemovalReport::default();
+        if self.read_only {
+            return Ok(report);
         }
+        let mut removed = false;
 
-        if text_path.exists() {
-            fs::remove_file(&text_path).context("Failed to remove cached text file")?;
-            removed = true;
+        for format in [Format::Html, Format::Text] {
+            let (preferred, other) = self.document_paths(doc, format);
+            for path in [preferred, other] {
+                if path.exists() {
+                    report.bytes_freed += tree_size(&path);
+                    fs::remove_file(&path).with_context(|| {
+                        format!("Failed to remove cached {} file", format.extension())
+                    })?;
+                    removed = true;
+                }
+            }
+            if self.checksum(doc, format).is_some() {
+                self.record_checksum(doc, format, None)?;
+            }
         }
 
+        let meta_path = self.metadata_path(doc);
         if meta_path.exists() {
+            report.bytes_freed += tree_size(&meta_path);
             fs::remove_file(&meta_path).context("Failed to remove cached metadata file")?;
+            report.metadata_removed += 1;
+        }
+        self.refresh_index(doc);
+        if removed {
+            self.forget_revision(doc)?;
+            report.documents_removed.push(doc.clone());
         }
 
-        Ok(removed)
+        Ok(report)
     }
 
     /// List all cached documents
     pub fn list_cached(&self) -> Vec<DocumentType> {
-        let docs_dir = self.cache_dir.join("documents");
-        if !docs_dir.exists() {
-            return Vec::new();
-        }
+        self.scan_documents().0
+    }
 
+    /// Cached documents, each once, and the documents with notes, from
+    /// one pass over the documents directory.
+    fn scan_documents(&self) -> (Vec<DocumentType>, HashSet<DocumentType>) {
         let mut seen = HashSet::new();
         let mut documents = Vec::new();
+        let mut notes = HashSet::new();
 
-        if let Ok(entries) = fs::read_dir(&docs_dir) {
+        if let Ok(entries) = fs::read_dir(self.cache_dir.join("documents")) {
             for entry in entries.flatten() {
                 let path = entry.path();
+                let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if let Some(name) = file_name.strip_suffix(NOTES_SUFFIX) {
+                    notes.insert(DocumentType::from_canonical_name(name));
+                    continue;
+                }
+                // Leftovers from an interrupted write.
+                if path.extension().is_some_and(|ext| ext == "part") {
+                    continue;
+                }
+                // `rfc9000.txt.gz` names the same document as `rfc9000.txt`.
+                let path = if is_gzip(&path) {
+                    path.with_extension("")
+                } else {
+                    path
+                };
                 if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
                     let doc_type = DocumentType::from_canonical_name(stem);
                     if seen.insert(doc_type.clone()) {
@@ -122,7 +581,107 @@ impl CacheManager {
             }
         }
 
-        documents
+        (documents, notes)
+    }
+
+    /// Cached draft revisions beyond the newest `keep` of each draft,
+    /// in name order.
+    pub fn old_draft_revisions(&self, keep: usize) -> Vec<DocumentType> {
+        let mut families: HashMap<String, Vec<(u32, DocumentType)>> = HashMap::new();
+        for doc in self.list_cached() {
+            if let (base, Some(rev)) = draft_revision(&doc) {
+                let base = base.to_string();
+                families.entry(base).or_default().push((rev, doc));
+            }
+        }
+        let mut old: Vec<DocumentType> = families
+            .into_values()
+            .flat_map(|mut revs| {
+                revs.sort_unstable_by_key(|&(rev, _)| std::cmp::Reverse(rev));
+                revs.into_iter().skip(keep).map(|(_, doc)| doc)
+            })
+            .collect();
+        old.sort_by_key(|doc| {
+            let (base, rev) = draft_revision(doc);
+            (base.to_string(), rev)
+        });
+        old
+    }
+
+    /// Remove all but the newest `keep` cached revisions of each draft,
+    /// reporting what was removed. Notes on the removed revisions stay,
+    /// as with [`remove`](Self::remove).
+    pub fn prune_old_revisions(&self, keep: usize) -> Result<RemovalReport> {
+        let mut report = RemovalReport::default();
+        if self.read_only {
+            return Ok(report);
+        }
+        for doc in self.old_draft_revisions(keep) {
+            report.merge(self.remove_document(&doc)?);
+        }
+        Ok(report)
+    }
+
+    /// Where the user's notes on `doc` live (`rfc9000.notes.md`), whether
+    /// or not they exist. Notes outlive the cached document: `remove`
+    /// and `clear_cache` leave them, so they reattach if it's re-cached.
+    pub fn notes_path(&self, doc: &DocumentType) -> PathBuf {
+        self.cache_dir
+            .join("documents")
+            .join(format!("{}{}", doc.name(), NOTES_SUFFIX))
+    }
+
+    /// The user's notes on `doc`, if any.
+    pub fn get_notes(&self, doc: &DocumentType) -> Option<String> {
+        fs::read_to_string(self.notes_path(doc)).ok()
+    }
+
+    pub fn has_notes(&self, doc: &DocumentType) -> bool {
+        self.notes_path(doc).is_file()
+    }
+
+    /// Delete the notes on `doc`. Returns false if there were none.
+    pub fn remove_notes(&self, doc: &DocumentType) -> Result<bool> {
+        let path = self.notes_path(doc);
+        if self.read_only || !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path).context("Failed to remove notes")?;
+        Ok(true)
+    }
+
+    /// Documents with notes, cached or not, sorted by name.
+    pub fn list_notes(&self) -> Vec<DocumentType> {
+        let Ok(entries) = fs::read_dir(self.cache_dir.join("documents")) else {
+            return Vec::new();
+        };
+        let mut docs: Vec<_> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let stem = name.strip_suffix(NOTES_SUFFIX)?;
+                Some(DocumentType::from_canonical_name(stem))
+            })
+            .collect();
+        docs.sort_by_key(|doc| doc.name());
+        docs
+    }
+
+    /// Copy every notes file into `dir` (created if needed), keeping the
+    /// `rfc9000.notes.md` names. Returns how many were copied.
+    pub fn export_notes(&self, dir: &Path) -> Result<usize> {
+        let docs = self.list_notes();
+        if docs.is_empty() {
+            return Ok(0);
+        }
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        for doc in &docs {
+            let source = self.notes_path(doc);
+            let target = dir.join(source.file_name().expect("notes path has a file name"));
+            fs::copy(&source, &target)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+        }
+        Ok(docs.len())
     }
 
     /// Get the cache directory path
@@ -130,6 +689,37 @@ impl CacheManager {
         &self.cache_dir
     }
 
+    /// Where `--keep-temp` leaves the text handed to a viewer.
+    pub fn temp_dir(&self) -> PathBuf {
+        self.cache_dir.join("tmp")
+    }
+
+    /// Delete files in [`temp_dir`](Self::temp_dir) last written more than
+    /// `max_age` ago, returning how many went.
+    pub fn clean_temp_files(&self, max_age: Duration) -> Result<usize> {
+        if self.read_only {
+            return Ok(0);
+        }
+        let entries = match fs::read_dir(self.temp_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).context("Failed to read the cache's tmp directory"),
+        };
+        let cutoff = Utc::now() - max_age;
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let written = meta.modified().map(DateTime::<Utc>::from);
+            if meta.is_file() && written.is_ok_and(|written| written < cutoff) {
+                remove_if_exists(&entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
     /// Get the path for a cached document
     fn document_path(&self, doc: &DocumentType, format: Format) -> PathBuf {
         self.cache_dir
@@ -137,6 +727,20 @@ impl CacheManager {
             .join(format!("{}.{}", doc.name(), format.extension()))
     }
 
+    /// Where `doc` should be written under the current compression
+    /// setting, and where its other form would be.
+    pub(super) fn document_paths(&self, doc: &DocumentType, format: Format) -> (PathBuf, PathBuf) {
+        let plain = self.document_path(doc, format);
+        let mut gz = plain.as_os_str().to_owned();
+        gz.push(".gz");
+        let gz = PathBuf::from(gz);
+        if self.compress {
+            (gz, plain)
+        } else {
+            (plain, gz)
+        }
+    }
+
     /// Get the path for metadata file
     fn metadata_path(&self, doc: &DocumentType) -> PathBuf {
         self.cache_dir
@@ -144,45 +748,560 @@ impl CacheManager {
             .join(format!("{}.meta", doc.name()))
     }
 
-    /// Get cached metadata for a document
+    /// Get cached metadata for a document, from the secondary cache if
+    /// this one has none
     pub fn get_metadata(&self, doc: &DocumentType) -> Option<CacheMetadata> {
-        let path = self.metadata_path(doc);
-        let content = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&content).ok()
+        fs::read_to_string(self.metadata_path(doc))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .or_else(|| self.secondary.as_ref()?.get_metadata(doc))
     }
 
-    /// Store metadata for a document
+    /// Store metadata for a document. Skipped for documents only the
+    /// secondary cache has, which would otherwise leave metadata here
+    /// with no document beside it.
     pub fn store_metadata(&self, doc: &DocumentType, meta: &CacheMetadata) -> Result<()> {
+        let secondary_only =
+            || !self.holds(doc) && self.secondary.as_ref().is_some_and(|s| s.holds(doc));
+        if self.read_only || secondary_only() {
+            return Ok(());
+        }
+        let _span = tracing::info_span!("cache write", document = %doc).entered();
         let path = self.metadata_path(doc);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).context("Failed to create metadata directory")?;
         }
         let content = serde_json::to_string_pretty(meta).context("Failed to serialize metadata")?;
-        fs::write(path, content).context("Failed to write metadata file")?;
+        write_staged(&path, |file| file.write_all(content.as_bytes()))
+            .context("Failed to write metadata file")?;
+        self.refresh_index(doc);
         Ok(())
     }
 
-    /// List cached documents with their metadata
-    pub fn list_cached_with_metadata(&self) -> Vec<CachedDocument> {
+    /// Path of the obsolescence facts file (shared by all RFCs)
+    fn relations_path(&self) -> PathBuf {
+        self.cache_dir.join("relations.json")
+    }
+
+    fn read_relations(&self) -> HashMap<u32, Obsolescence> {
+        fs::read_to_string(self.relations_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Get the cached obsolescence facts for an RFC, if present and fresh.
+    pub fn get_obsolescence(&self, rfc: u32) -> Option<Obsolescence> {
+        self.read_relations()
+            .remove(&rfc)
+            .filter(|o| o.is_fresh(Utc::now()))
+    }
+
+    /// Store obsolescence facts for a batch of RFCs in one write.
+    pub fn store_obsolescence(&self, facts: &[(u32, Vec<u32>)]) -> Result<()> {
+        if self.read_only || facts.is_empty() {
+            return Ok(());
+        }
+        let mut relations = self.read_relations();
+        let now = Utc::now();
+        for (rfc, obsoleted_by) in facts {
+            relations.insert(
+                *rfc,
+                Obsolescence {
+                    obsoleted_by: obsoleted_by.clone(),
+                    checked_at: now,
+                },
+            );
+        }
+        let content =
+            serde_json::to_string_pretty(&relations).context("Failed to serialize relations")?;
+        fs::write(self.relations_path(), content).context("Failed to write relations file")?;
+        Ok(())
+    }
+
+    /// Path of the recent search results file
+    fn searches_path(&self) -> PathBuf {
+        self.cache_dir.join("searches.json")
+    }
+
+    fn read_searches(&self) -> HashMap<String, CachedSearch> {
+        fs::read_to_string(self.searches_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Results of an earlier search with the same query, options, and
+    /// limit, if they're younger than `ttl`.
+    pub fn get_search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        limit: usize,
+        ttl: Duration,
+    ) -> Option<SearchResult> {
+        self.read_searches()
+            .remove(&search_key(query, options, limit))
+            .filter(|cached| cached.is_fresh(Utc::now(), ttl))
+            .map(|cached| cached.result)
+    }
+
+    /// Remember `result` as the answer for this query, options, and limit,
+    /// dropping entries older than `ttl` while at it.
+    pub fn store_search(&self, result: &SearchResult, limit: usize, ttl: Duration) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let mut searches = self.read_searches();
+        searches.retain(|_, cached| cached.is_fresh(now, ttl));
+        searches.insert(
+            search_key(&result.query, &result.options(), limit),
+            CachedSearch {
+                result: result.clone(),
+                cached_at: now,
+            },
+        );
+        let content =
+            serde_json::to_string(&searches).context("Failed to serialize search results")?;
+        fs::write(self.searches_path(), content).context("Failed to write search results")?;
+        Ok(())
+    }
+
+    /// Path of the newest cached revision of each draft, keyed by the
+    /// unversioned name, so unversioned lookups don't scan the directory
+    fn latest_revisions_path(&self) -> PathBuf {
+        self.cache_dir.join("latest-revisions.json")
+    }
+
+    fn read_latest_revisions(&self) -> HashMap<String, u32> {
+        fs::read_to_string(self.latest_revisions_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_latest_revisions(&self, latest: &HashMap<String, u32>) -> Result<()> {
+        let content = serde_json::to_string_pretty(latest)
+            .context("Failed to serialize latest draft revisions")?;
+        fs::write(self.latest_revisions_path(), content)
+            .context("Failed to write latest draft revisions")?;
+        Ok(())
+    }
+
+    /// Point `doc`'s draft family at it if it's the newest revision cached.
+    fn note_revision(&self, doc: &DocumentType) -> Result<()> {
+        let (base, Some(rev)) = draft_revision(doc) else {
+            return Ok(());
+        };
+        let mut latest = self.read_latest_revisions();
+        if latest.get(base).is_some_and(|&newest| newest >= rev) {
+            return Ok(());
+        }
+        latest.insert(base.to_string(), rev);
+        self.write_latest_revisions(&latest)
+    }
+
+    /// After `doc` is removed, point its family at the newest revision
+    /// left, if any.
+    fn forget_revision(&self, doc: &DocumentType) -> Result<()> {
+        let (base, Some(rev)) = draft_revision(doc) else {
+            return Ok(());
+        };
+        let mut latest = self.read_latest_revisions();
+        if latest.get(base) != Some(&rev) {
+            return Ok(());
+        }
+        match self.scan_newest_revision(base) {
+            Some(newest) => latest.insert(base.to_string(), newest),
+            None => latest.remove(base),
+        };
+        self.write_latest_revisions(&latest)
+    }
+
+    fn scan_newest_revision(&self, base: &str) -> Option<u32> {
         self.list_cached()
+            .iter()
+            .filter_map(|doc| match draft_revision(doc) {
+                (family, Some(rev)) if family == base => Some(rev),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// The newest cached revision of an unversioned draft, e.g.
+    /// `draft-ietf-quic-transport-34` for `draft-ietf-quic-transport`.
+    /// `None` for RFCs, versioned drafts, and drafts with no revision
+    /// cached.
+    pub fn newest_revision(&self, doc: &DocumentType) -> Option<DocumentType> {
+        let DocumentType::Draft(name) = doc else {
+            return None;
+        };
+        if draft_base_and_rev(name).1.is_some() {
+            return None;
+        }
+        let revision = |rev: u32| DocumentType::Draft(format!("{}-{:02}", name, rev));
+        let mut latest = self.read_latest_revisions();
+        if let Some(doc) = latest.get(name).map(|&rev| revision(rev)) {
+            if self.holds(&doc) {
+                return Some(doc);
+            }
+        }
+        // No pointer yet (a cache from before they were kept), or its
+        // revision was deleted behind our back.
+        let rev = self.scan_newest_revision(name)?;
+        if !self.read_only {
+            latest.insert(name.clone(), rev);
+            let _ = self.write_latest_revisions(&latest);
+        }
+        Some(revision(rev))
+    }
+
+    /// Path of the synced RFC Editor index
+    fn rfc_index_path(&self) -> PathBuf {
+        self.cache_dir.join("rfc-index.json")
+    }
+
+    /// Load the synced RFC index, if one has been stored.
+    pub fn load_rfc_index(&self) -> Option<RfcIndex> {
+        let content = fs::read_to_string(self.rfc_index_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store the RFC index, replacing any previous copy.
+    pub fn store_rfc_index(&self, index: &RfcIndex) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        // Compact rather than pretty: the index is ~10k entries and is
+        // parsed on every lookup.
+        let content = serde_json::to_string(index).context("Failed to serialize RFC index")?;
+        fs::write(self.rfc_index_path(), content).context("Failed to write RFC index")?;
+        Ok(())
+    }
+
+    /// List cached documents with their metadata. Served from
+    /// `index.json` when it covers exactly the documents on disk;
+    /// otherwise every sidecar is read and the index rebuilt.
+    pub fn list_cached_with_metadata(&self) -> Vec<CachedDocument> {
+        let (names, notes) = self.scan_documents();
+        let has_notes = |doc: &DocumentType| notes.contains(doc);
+        if let Some(listing) = self
+            .read_index()
+            .and_then(|index| index.listing(&names, has_notes))
+        {
+            return listing;
+        }
+        let listing: Vec<CachedDocument> = names
             .into_iter()
-            .map(|doc_type| {
-                let metadata = self.get_metadata(&doc_type);
-                CachedDocument { doc_type, metadata }
+            .map(|doc| {
+                let has_notes = notes.contains(&doc);
+                self.describe(doc, has_notes)
             })
-            .collect()
+            .collect();
+        // A cache we can't write still lists, just without the shortcut.
+        if !self.read_only {
+            let _ = self.with_lock(|| self.write_index(&CacheIndex::from_listing(&listing)));
+        }
+        listing
+    }
+
+    /// `doc` as [`list_cached_with_metadata`](Self::list_cached_with_metadata)
+    /// would list it, without listing the rest: its `index.json` entry,
+    /// or its own files when the index doesn't have it. `None` when it
+    /// isn't cached (an unversioned draft falls back to its newest
+    /// revision, as for [`document_file`](Self::document_file)).
+    pub fn cached_document(&self, doc: &DocumentType) -> Option<CachedDocument> {
+        let doc = if self.holds(doc) {
+            doc.clone()
+        } else {
+            match self.newest_revision(doc) {
+                Some(newest) => newest,
+                None => return self.secondary.as_ref()?.cached_document(doc),
+            }
+        };
+        let has_notes = self.has_notes(&doc);
+        let indexed = self
+            .read_index()
+            .and_then(|index| Some(index.entry(&doc)?.describe(doc.clone(), has_notes)));
+        Some(indexed.unwrap_or_else(|| self.describe(doc, has_notes)))
+    }
+
+    /// `doc` as read from its files.
+    fn describe(&self, doc_type: DocumentType, has_notes: bool) -> CachedDocument {
+        let mut cached = CachedDocument {
+            metadata: self.get_metadata(&doc_type),
+            formats: Vec::new(),
+            size: 0,
+            modified: None,
+            has_notes,
+            doc_type,
+        };
+        for format in [Format::Text, Format::Html] {
+            let Some(path) = self.exact_document_file(&cached.doc_type, format) else {
+                continue;
+            };
+            let Ok(stat) = fs::metadata(&path) else {
+                continue;
+            };
+            cached.formats.push(format);
+            cached.size += stat.len();
+            let modified = stat.modified().ok().map(DateTime::<Utc>::from);
+            cached.modified = cached.modified.max(modified);
+        }
+        cached
+    }
+
+    /// Path of the SHA-256 of each cached document file, taken when it
+    /// was stored, keyed like `rfc9000.txt`
+    fn checksums_path(&self) -> PathBuf {
+        self.cache_dir.join("checksums.json")
+    }
+
+    fn read_checksums(&self) -> HashMap<String, String> {
+        fs::read_to_string(self.checksums_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// The SHA-256 (lowercase hex) of `doc`'s uncompressed content in
+    /// `format` as it was stored, if one was recorded.
+    pub fn checksum(&self, doc: &DocumentType, format: Format) -> Option<String> {
+        self.read_checksums()
+            .remove(&format!("{}.{}", doc.name(), format.extension()))
+    }
+
+    /// Record (or, with `None`, forget) the checksum of `doc` in `format`.
+    pub(super) fn record_checksum(
+        &self,
+        doc: &DocumentType,
+        format: Format,
+        checksum: Option<String>,
+    ) -> Result<()> {
+        let key = format!("{}.{}", doc.name(), format.extension());
+        self.with_lock(|| {
+            let mut checksums = self.read_checksums();
+            match checksum {
+                Some(checksum) => checksums.insert(key, checksum),
+                None => checksums.remove(&key),
+            };
+            let content = serde_json::to_string_pretty(&checksums)
+                .context("Failed to serialize checksums")?;
+            fs::write(self.checksums_path(), content).context("Failed to write checksums")?;
+            Ok(())
+        })
+    }
+
+    /// Path of the listing index
+    fn index_path(&self) -> PathBuf {
+        self.cache_dir.join("index.json")
+    }
+
+    fn read_index(&self) -> Option<CacheIndex> {
+        let content = fs::read_to_string(self.index_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_index(&self, index: &CacheIndex) -> Result<()> {
+        let path = self.index_path();
+        let content = serde_json::to_string(index).context("Failed to serialize cache index")?;
+        write_staged(&path, |file| file.write_all(content.as_bytes()))
+            .context("Failed to write cache index")?;
+        Ok(())
+    }
+
+    /// Bring `doc`'s index entry up to date after a write or removal. An
+    /// index that can't be updated is deleted, so the next listing
+    /// rebuilds it instead of trusting a stale entry. Without an index
+    /// there's nothing to do: the next listing builds one.
+    pub(super) fn refresh_index(&self, doc: &DocumentType) {
+        let updated = self.with_lock(|| {
+            let Some(mut index) = self.read_index() else {
+                return Ok(());
+            };
+            if self.metadata_path(doc).is_file() || self.holds(doc) {
+                index.insert(&self.describe(doc.clone(), self.has_notes(doc)));
+            } else {
+                index.entries.remove(&doc.name());
+            }
+            self.write_index(&index)
+        });
+        if updated.is_err() {
+            let _ = fs::remove_file(self.index_path());
+        }
+    }
+
+    /// Run `f` holding the cache lock (`cache.lock`), so concurrent `rfc`
+    /// processes don't interleave their read-modify-write cycles.
+    pub(crate) fn with_lock<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let lock = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.cache_dir.join("cache.lock"))
+            .context("Failed to open cache lock")?;
+        lock.lock().context("Failed to lock cache")?;
+        f()
+    }
+}
+
+/// File name suffix for per-document notes.
+const NOTES_SUFFIX: &str = ".notes.md";
+
+fn is_notes(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(NOTES_SUFFIX))
+}
+
+/// `platform`'s cache directory, or else `.cache/rfc` under the home
+/// directory, or else [`FsCache::temp_cache_dir`].
+fn fallback_cache_dir(
+    platform: Option<PathBuf>,
+    home: impl FnOnce() -> Option<PathBuf>,
+) -> PathBuf {
+    platform
+        .or_else(|| home().map(|home| home.join(".cache").join("rfc")))
+        .unwrap_or_else(FsCache::temp_cache_dir)
+}
+
+/// `$HOME`, or `%USERPROFILE%` on Windows where `HOME` is usually unset.
+/// `var` reads the environment.
+fn home_from_env(var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    ["HOME", "USERPROFILE"]
+        .into_iter()
+        .filter_map(var)
+        .find(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Write `path` atomically: `write` fills a uniquely named `.part` file
+/// beside it (`rfc9000.txt.a1B2c3.part`), which is then renamed into
+/// place. Concurrent writers each stage their own file, so readers only
+/// ever see one writer's complete content.
+fn write_staged(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut staged = tempfile::Builder::new()
+        .prefix(&format!("{}.", name))
+        .suffix(".part")
+        .tempfile_in(parent)?;
+    write(staged.as_file_mut())?;
+    staged.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+fn is_gzip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+/// Read a cached document, decompressing `.gz` files.
+pub(super) fn read_document(path: &Path) -> std::io::Result<String> {
+    if !is_gzip(path) {
+        return fs::read_to_string(path);
+    }
+    let mut content = String::new();
+    GzDecoder::new(fs::File::open(path)?).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn write_document(file: &mut fs::File, content: &[u8], compress: bool) -> std::io::Result<()> {
+    if !compress {
+        return file.write_all(content);
+    }
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(content)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// The ISIZE field from a gzip trailer: the uncompressed length mod 2^32.
+fn gzip_uncompressed_size(path: &Path) -> std::io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::End(-4))?;
+    let mut trailer = [0u8; 4];
+    file.read_exact(&mut trailer)?;
+    Ok(u64::from(u32::from_le_bytes(trailer)))
+}
+
+/// Bytes taken by the file at `path`, or by every file under it if it's
+/// a directory. Unreadable entries count as empty.
+pub(super) fn tree_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| tree_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+impl DocumentCache for FsCache {
+    fn get_document(&self, doc: &DocumentType, format: Format) -> Option<String> {
+        FsCache::get_document(self, doc, format)
+    }
+
+    fn store_document(&self, doc: &DocumentType, format: Format, content: &str) -> Result<()> {
+        FsCache::store_document(self, doc, format, content)
+    }
+
+    fn get_metadata(&self, doc: &DocumentType) -> Option<CacheMetadata> {
+        FsCache::get_metadata(self, doc)
+    }
+
+    fn store_metadata(&self, doc: &DocumentType, meta: &CacheMetadata) -> Result<()> {
+        FsCache::store_metadata(self, doc, meta)
+    }
+
+    fn list_cached(&self) -> Vec<DocumentType> {
+        FsCache::list_cached(self)
+    }
+
+    fn remove(&self, doc: &DocumentType) -> Result<bool> {
+        FsCache::remove(self, doc)
+    }
+
+    fn newest_revision(&self, doc: &DocumentType) -> Option<DocumentType> {
+        FsCache::newest_revision(self, doc)
+    }
+
+    fn list_cached_with_metadata(&self) -> Vec<CachedDocument> {
+        FsCache::list_cached_with_metadata(self)
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.clear_cache().map(drop)
+    }
+
+    fn record_counters(&self, delta: CacheCounters) {
+        FsCache::record_counters(self, delta)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{SearchFilter, StdLevel};
     use chrono::Utc;
     use tempfile::TempDir;
 
-    fn test_cache() -> (CacheManager, TempDir) {
+    fn test_cache() -> (FsCache, TempDir) {
         let temp_dir = TempDir::new().unwrap();
-        let cache = CacheManager::with_dir(temp_dir.path().to_path_buf()).unwrap();
+        let cache = FsCache::with_dir(temp_dir.path().to_path_buf()).unwrap();
         (cache, temp_dir)
     }
 
@@ -219,10 +1338,17 @@ mod tests {
         let doc = DocumentType::Rfc(9000);
 
         cache.store_document(&doc, Format::Html, "test").unwrap();
+        cache
+            .store_metadata(&doc, &CacheMetadata::new("QUIC", Utc::now()))
+            .unwrap();
         assert!(cache.get_document(&doc, Format::Html).is_some());
 
-        cache.clear_cache().unwrap();
+        let report = cache.clear_cache().unwrap();
         assert!(cache.get_document(&doc, Format::Html).is_none());
+        assert_eq!(report.documents_removed, [doc]);
+        assert_eq!(report.metadata_removed, 1);
+        assert!(report.bytes_freed > 4, "{report:?}");
+        assert!(cache.clear_cache().unwrap().is_empty());
     }
 
     #[test]
@@ -241,13 +1367,16 @@ mod tests {
             .store_document(&doc, Format::Text, "text content")
             .unwrap();
 
-        let meta = CacheMetadata {
-            title: "Test Title".to_string(),
-            cached_at: Utc::now(),
-        };
+        let meta = CacheMetadata::new("Test Title", Utc::now());
         cache.store_metadata(&doc, &meta).unwrap();
 
-        assert!(cache.remove(&doc).unwrap());
+        let text_size = fs::metadata(cache.exact_document_file(&doc, Format::Text).unwrap())
+            .unwrap()
+            .len();
+        let report = cache.remove_document(&doc).unwrap();
+        assert_eq!(report.documents_removed, std::slice::from_ref(&doc));
+        assert_eq!(report.metadata_removed, 1);
+        assert!(report.bytes_freed > text_size, "{report:?}");
 
         // Verify both formats and metadata are gone
         assert!(cache.get_document(&doc, Format::Html).is_none());
@@ -258,6 +1387,402 @@ mod tests {
         assert!(!cache.remove(&doc).unwrap());
     }
 
+    #[test]
+    fn test_prune_old_revisions_keeps_newest_per_draft() {
+        let (cache, _temp) = test_cache();
+        let names = [
+            "draft-ietf-quic-transport-29",
+            "draft-ietf-quic-transport-34",
+            "draft-ietf-quic-transport-32",
+            "draft-ietf-quic-transport",
+            "draft-ietf-tls-esni-22",
+            "draft-ietf-tls-rfc8446bis",
+        ];
+        for name in names {
+            let doc = DocumentType::Draft(name.to_string());
+            cache.store_document(&doc, Format::Text, name).unwrap();
+        }
+        cache
+            .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC")
+            .unwrap();
+        let draft = |name: &str| DocumentType::Draft(name.to_string());
+
+        assert_eq!(
+            cache.old_draft_revisions(2),
+            [draft("draft-ietf-quic-transport-29")]
+        );
+        assert_eq!(
+            cache.prune_old_revisions(1).unwrap().documents_removed,
+            [
+                draft("draft-ietf-quic-transport-29"),
+                draft("draft-ietf-quic-transport-32"),
+            ]
+        );
+        let mut left: Vec<String> = cache.list_cached().iter().map(|d| d.name()).collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "draft-ietf-quic-transport",
+                "draft-ietf-quic-transport-34",
+                "draft-ietf-tls-esni-22",
+                "draft-ietf-tls-rfc8446bis",
+                "rfc9000",
+            ]
+        );
+        assert!(cache.prune_old_revisions(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unversioned_draft_falls_back_to_newest_revision() {
+        let (cache, temp) = test_cache();
+        let draft = |name: &str| DocumentType::Draft(name.to_string());
+        let family = draft("draft-ietf-quic-transport");
+        assert_eq!(cache.get_document(&family, Format::Text), None);
+
+        for rev in ["32", "34", "29"] {
+            let doc = draft(&format!("draft-ietf-quic-transport-{}", rev));
+            cache.store_document(&doc, Format::Text, rev).unwrap();
+        }
+        cache
+            .store_document(&draft("draft-ietf-tls-esni-22"), Format::Text, "ECH")
+            .unwrap();
+        assert_eq!(
+            cache.newest_revision(&family),
+            Some(draft("draft-ietf-quic-transport-34"))
+        );
+        assert_eq!(
+            cache.get_document(&family, Format::Text).as_deref(),
+            Some("34")
+        );
+        assert!(cache.contains(&family, Format::Text));
+        assert!(!cache.contains(&family, Format::Html));
+        assert_eq!(
+            cache.read_latest_revisions(),
+            HashMap::from([
+                ("draft-ietf-quic-transport".to_string(), 34),
+                ("draft-ietf-tls-esni".to_string(), 22),
+            ])
+        );
+
+        // Only unversioned drafts fall back.
+        assert_eq!(
+            cache.newest_revision(&draft("draft-ietf-quic-transport-33")),
+            None
+        );
+        assert_eq!(cache.newest_revision(&DocumentType::Rfc(9000)), None);
+
+        cache
+            .remove(&draft("draft-ietf-quic-transport-34"))
+            .unwrap();
+        assert_eq!(
+            cache.get_document(&family, Format::Text).as_deref(),
+            Some("32")
+        );
+
+        // A revision deleted by hand sends the lookup back to the directory.
+        fs::remove_file(
+            temp.path()
+                .join("documents")
+                .join("draft-ietf-quic-transport-32.txt"),
+        )
+        .unwrap();
+        assert_eq!(
+            cache.get_document(&family, Format::Text).as_deref(),
+            Some("29")
+        );
+        assert_eq!(
+            cache.read_latest_revisions()["draft-ietf-quic-transport"],
+            29
+        );
+
+        // A copy under the unversioned name itself wins.
+        cache
+            .store_document(&family, Format::Text, "unversioned")
+            .unwrap();
+        assert_eq!(
+            cache.get_document(&family, Format::Text).as_deref(),
+            Some("unversioned")
+        );
+    }
+
+    #[test]
+    fn test_listing_index_is_kept_up_to_date() {
+        let (cache, temp) = test_cache();
+        let titles = |cache: &FsCache| {
+            let mut docs = cache.list_cached_with_metadata();
+            docs.sort_by_key(|doc| doc.doc_type.name());
+            docs.into_iter()
+                .map(|doc| (doc.doc_type.name(), doc.metadata.map(|m| m.title)))
+                .collect::<Vec<_>>()
+        };
+        let rfc = DocumentType::Rfc(9000);
+        cache.store_document(&rfc, Format::Text, "QUIC").unwrap();
+        cache
+            .store_metadata(&rfc, &CacheMetadata::new("QUIC", Utc::now()))
+            .unwrap();
+        assert!(cache.read_index().is_none());
+
+        // The first listing builds the index...
+        assert_eq!(
+            titles(&cache),
+            [("rfc9000".to_string(), Some("QUIC".to_string()))]
+        );
+        let index = cache.read_index().unwrap();
+        assert_eq!(index.entries["rfc9000"].formats, [Format::Text]);
+        assert_eq!(index.entries["rfc9000"].size, 4);
+
+        // ...which later listings trust over the sidecars...
+        let meta_path = temp.path().join("documents").join("rfc9000.meta");
+        let edited = CacheMetadata::new("Edited by hand", Utc::now());
+        fs::write(&meta_path, serde_json::to_string(&edited).unwrap()).unwrap();
+        assert_eq!(titles(&cache)[0].1.as_deref(), Some("QUIC"));
+
+        // ...and writes through the cache keep up to date.
+        let draft = DocumentType::Draft("draft-ietf-tls-esni-22".to_string());
+        cache
+            .store_document(&draft, Format::Html, "<p>ECH</p>")
+            .unwrap();
+        cache
+            .store_metadata(&rfc, &CacheMetadata::new("QUIC transport", Utc::now()))
+            .unwrap();
+        assert_eq!(
+            titles(&cache),
+            [
+                ("draft-ietf-tls-esni-22".to_string(), None),
+                ("rfc9000".to_string(), Some("QUIC transport".to_string())),
+            ]
+        );
+        cache.remove(&draft).unwrap();
+        assert_eq!(cache.read_index().unwrap().entries.len(), 1);
+
+        // Files added behind the cache's back, or a damaged index, force
+        // a rescan.
+        fs::write(temp.path().join("documents").join("rfc791.txt"), "IP").unwrap();
+        assert_eq!(
+            titles(&cache),
+            [
+                ("rfc791".to_string(), None),
+                ("rfc9000".to_string(), Some("QUIC transport".to_string())),
+            ]
+        );
+        fs::write(&meta_path, serde_json::to_string(&edited).unwrap()).unwrap();
+        fs::write(cache.index_path(), "{").unwrap();
+        assert_eq!(titles(&cache)[1].1.as_deref(), Some("Edited by hand"));
+        assert_eq!(cache.read_index().unwrap().entries.len(), 2);
+    }
+
+    #[test]
+    fn test_cached_document_reads_one_entry_not_every_sidecar() {
+        let (cache, temp) = test_cache();
+        for n in 1..=300 {
+            let doc = DocumentType::Rfc(n);
+            cache.store_document(&doc, Format::Text, "text").unwrap();
+            cache
+                .store_metadata(&doc, &CacheMetadata::new(format!("RFC {}", n), Utc::now()))
+                .unwrap();
+        }
+        assert_eq!(cache.list_cached_with_metadata().len(), 300);
+        let index = fs::read_to_string(cache.index_path()).unwrap();
+
+        // With every sidecar unreadable, only the index can answer, and
+        // it isn't rebuilt.
+        for entry in fs::read_dir(temp.path().join("documents")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "meta") {
+                fs::write(&path, "{").unwrap();
+            }
+        }
+        let found = cache.cached_document(&DocumentType::Rfc(9)).unwrap();
+        assert_eq!(found.metadata.unwrap().title, "RFC 9");
+        assert_eq!(found.formats, [Format::Text]);
+        assert_eq!(fs::read_to_string(cache.index_path()).unwrap(), index);
+        assert!(cache.cached_document(&DocumentType::Rfc(9000)).is_none());
+
+        // Without an index entry, the document's own files answer.
+        let late = DocumentType::Rfc(9000);
+        fs::write(temp.path().join("documents").join("rfc9000.txt"), "QUIC").unwrap();
+        let found = cache.cached_document(&late).unwrap();
+        assert!(found.metadata.is_none());
+        assert_eq!(found.size, 4);
+    }
+
+    #[test]
+    fn test_home_from_env_falls_back_to_userprofile() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert_eq!(
+            home_from_env(env(&[
+                ("HOME", "/home/ietf"),
+                ("USERPROFILE", r"C:\Users\ietf")
+            ])),
+            Some(PathBuf::from("/home/ietf"))
+        );
+        assert_eq!(
+            home_from_env(env(&[("HOME", ""), ("USERPROFILE", r"C:\Users\ietf")])),
+            Some(PathBuf::from(r"C:\Users\ietf"))
+        );
+        assert_eq!(home_from_env(env(&[])), None);
+    }
+
+    #[test]
+    fn test_cache_dir_falls_back_to_home_then_temp() {
+        let platform = PathBuf::from("/home/ietf/.cache/rfc-platform");
+        assert_eq!(
+            fallback_cache_dir(Some(platform.clone()), || panic!("home not needed")),
+            platform
+        );
+        assert_eq!(
+            fallback_cache_dir(None, || Some(PathBuf::from("/home/ietf"))),
+            PathBuf::from("/home/ietf/.cache/rfc")
+        );
+        assert_eq!(
+            fallback_cache_dir(None, || None),
+            std::env::temp_dir().join("rfc-cache")
+        );
+        let (cache, _temp) = test_cache();
+        assert!(!cache.is_temporary());
+    }
+
+    #[test]
+    fn test_clean_temp_files_removes_only_stale_ones() {
+        let (cache, _temp) = test_cache();
+        assert_eq!(cache.clean_temp_files(Duration::days(1)).unwrap(), 0);
+
+        let tmp = cache.temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("rfc9000.txt"), "QUIC").unwrap();
+        let stale = fs::File::create(tmp.join("rfc8446.txt")).unwrap();
+        stale
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 86400))
+            .unwrap();
+        drop(stale);
+
+        assert_eq!(
+            FsCache::with_dir(cache.cache_dir().to_path_buf())
+                .unwrap()
+                .with_read_only(true)
+                .clean_temp_files(Duration::days(1))
+                .unwrap(),
+            0
+        );
+        assert_eq!(cache.clean_temp_files(Duration::days(1)).unwrap(), 1);
+        assert!(tmp.join("rfc9000.txt").exists());
+        assert!(!tmp.join("rfc8446.txt").exists());
+    }
+
+    #[test]
+    fn test_read_only_cache_never_writes() {
+        let (cache, temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        cache
+            .store_metadata(&doc, &CacheMetadata::new("QUIC", Utc::now()))
+            .unwrap();
+        let before = fs::read_dir(temp.path().join("documents")).unwrap().count();
+
+        let cache = cache.with_read_only(true);
+        let other = DocumentType::Rfc(8446);
+        cache.store_document(&other, Format::Text, "TLS").unwrap();
+        cache
+            .store_metadata(&doc, &CacheMetadata::new("Changed", Utc::now()))
+            .unwrap();
+        cache.record_access(&doc);
+        assert!(!cache.remove(&doc).unwrap());
+        cache.clear_cache().unwrap();
+
+        assert_eq!(cache.get_document(&other, Format::Text), None);
+        assert_eq!(
+            cache.get_document(&doc, Format::Text).as_deref(),
+            Some("QUIC")
+        );
+        let meta = cache.get_metadata(&doc).unwrap();
+        assert_eq!(meta.title, "QUIC");
+        assert_eq!(meta.last_accessed, None);
+        assert_eq!(
+            fs::read_dir(temp.path().join("documents")).unwrap().count(),
+            before
+        );
+
+        let err = cache.ensure_writable().unwrap_err();
+        assert!(err.downcast_ref::<ReadOnlyCache>().is_some());
+        let download = temp.path().join("download.txt");
+        fs::write(&download, "TLS").unwrap();
+        assert_eq!(
+            cache
+                .store_document_file(&other, Format::Text, &download)
+                .unwrap(),
+            download
+        );
+        assert_eq!(fs::read_to_string(&download).unwrap(), "TLS");
+        assert_eq!(cache.get_document(&other, Format::Text), None);
+        assert_eq!(
+            fs::read_dir(temp.path().join("documents")).unwrap().count(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_secondary_cache_is_consulted_after_a_miss() {
+        let (shared, _shared_temp) = test_cache();
+        let quic = DocumentType::Rfc(9000);
+        let tls = DocumentType::Rfc(8446);
+        for (doc, text) in [(&quic, "shared QUIC"), (&tls, "shared TLS")] {
+            shared.store_document(doc, Format::Text, text).unwrap();
+            shared
+                .store_metadata(doc, &CacheMetadata::new(text, Utc::now()))
+                .unwrap();
+        }
+        let (own, own_temp) = test_cache();
+        own.store_document(&quic, Format::Text, "own QUIC").unwrap();
+        let cache = own.with_secondary(shared.cache_dir().to_path_buf());
+
+        // The primary wins when both have a document.
+        assert_eq!(
+            cache.get_document(&quic, Format::Text).as_deref(),
+            Some("own QUIC")
+        );
+        assert_eq!(
+            cache.get_document(&tls, Format::Text).as_deref(),
+            Some("shared TLS")
+        );
+        assert!(cache.contains(&tls, Format::Text));
+        assert_eq!(cache.get_metadata(&tls).unwrap().title, "shared TLS");
+        assert_eq!(
+            cache.get_document(&DocumentType::Rfc(1), Format::Text),
+            None
+        );
+        assert_eq!(cache.list_cached(), vec![quic.clone()]);
+
+        // Metadata for documents only the secondary has stays there...
+        cache.record_access(&tls);
+        assert!(!own_temp
+            .path()
+            .join("documents")
+            .join("rfc8446.meta")
+            .exists());
+        assert_eq!(shared.get_metadata(&tls).unwrap().last_accessed, None);
+
+        // ...and newly stored documents land in the primary.
+        let ip = DocumentType::Rfc(791);
+        cache.store_document(&ip, Format::Text, "IP").unwrap();
+        cache.store_document(&tls, Format::Text, "own TLS").unwrap();
+        assert_eq!(shared.get_document(&ip, Format::Text), None);
+        assert_eq!(
+            shared.get_document(&tls, Format::Text).as_deref(),
+            Some("shared TLS")
+        );
+        assert_eq!(
+            cache.get_document(&tls, Format::Text).as_deref(),
+            Some("own TLS")
+        );
+    }
+
     #[test]
     fn test_remove_partial_formats() {
         let (cache, _temp) = test_cache();
@@ -289,10 +1814,10 @@ mod tests {
     fn test_store_and_retrieve_metadata() {
         let (cache, _temp) = test_cache();
         let doc = DocumentType::Rfc(9000);
-        let meta = CacheMetadata {
-            title: "QUIC: A UDP-Based Multiplexed and Secure Transport".to_string(),
-            cached_at: Utc::now(),
-        };
+        let meta = CacheMetadata::new(
+            "QUIC: A UDP-Based Multiplexed and Secure Transport",
+            Utc::now(),
+        );
 
         cache.store_metadata(&doc, &meta).unwrap();
 
@@ -312,10 +1837,7 @@ mod tests {
         cache.store_document(&doc1, Format::Text, "test").unwrap();
         cache.store_document(&doc2, Format::Text, "test").unwrap();
 
-        let meta1 = CacheMetadata {
-            title: "QUIC Transport".to_string(),
-            cached_at: Utc::now(),
-        };
+        let meta1 = CacheMetadata::new("QUIC Transport", Utc::now());
         cache.store_metadata(&doc1, &meta1).unwrap();
 
         let cached = cache.list_cached_with_metadata();
@@ -330,6 +1852,124 @@ mod tests {
         assert!(cached_doc2.metadata.is_none());
     }
 
+    #[test]
+    fn test_store_and_retrieve_obsolescence() {
+        let (cache, _temp) = test_cache();
+
+        assert!(cache.get_obsolescence(2616).is_none());
+
+        cache
+            .store_obsolescence(&[(2616, vec![7230, 7231]), (9000, vec![])])
+            .unwrap();
+
+        assert_eq!(
+            cache.get_obsolescence(2616).unwrap().obsoleted_by,
+            vec![7230, 7231]
+        );
+        assert!(cache
+            .get_obsolescence(9000)
+            .unwrap()
+            .obsoleted_by
+            .is_empty());
+
+        // A later batch merges with, rather than replaces, earlier facts.
+        cache.store_obsolescence(&[(793, vec![9293])]).unwrap();
+        assert!(cache.get_obsolescence(2616).is_some());
+        assert_eq!(
+            cache.get_obsolescence(793).unwrap().obsoleted_by,
+            vec![9293]
+        );
+    }
+
+    #[test]
+    fn test_obsolescence_expires() {
+        let fresh = Obsolescence {
+            obsoleted_by: vec![],
+            checked_at: Utc::now(),
+        };
+        assert!(fresh.is_fresh(Utc::now()));
+
+        let stale = Obsolescence {
+            obsoleted_by: vec![],
+            checked_at: Utc::now() - crate::cache::OBSOLESCENCE_TTL,
+        };
+        assert!(!stale.is_fresh(Utc::now()));
+    }
+
+    #[test]
+    fn test_search_results_are_reused_until_they_expire() {
+        let (cache, _temp) = test_cache();
+        let ttl = crate::cache::SEARCH_CACHE_TTL;
+        let both = SearchOptions::default();
+        let result = SearchResult {
+            documents: vec![crate::models::Document::new(
+                "rfc9000".to_string(),
+                "QUIC".to_string(),
+                DocumentType::Rfc(9000),
+            )],
+            query: "QUIC transport".to_string(),
+            ..Default::default()
+        };
+        assert!(cache.get_search("quic transport", &both, 25, ttl).is_none());
+        cache.store_search(&result, 25, ttl).unwrap();
+
+        let hit = cache
+            .get_search(" quic   Transport", &both, 25, ttl)
+            .unwrap();
+        assert_eq!(hit.documents[0].doc_type, DocumentType::Rfc(9000));
+        assert!(cache.get_search("quic transport", &both, 50, ttl).is_none());
+        assert!(cache
+            .get_search(
+                "quic transport",
+                &SearchOptions::new(SearchFilter::DraftsOnly),
+                25,
+                ttl
+            )
+            .is_none());
+        let standards = both.clone().with_status(vec![StdLevel::InternetStandard]);
+        assert!(cache
+            .get_search("quic transport", &standards, 25, ttl)
+            .is_none());
+        let errata = both.clone().with_keyword(Some("errata".into()));
+        assert!(cache
+            .get_search("quic transport", &errata, 25, ttl)
+            .is_none());
+
+        // Age the stored entry past the TTL.
+        let mut searches = cache.read_searches();
+        for cached in searches.values_mut() {
+            cached.cached_at -= ttl;
+        }
+        fs::write(
+            cache.searches_path(),
+            serde_json::to_string(&searches).unwrap(),
+        )
+        .unwrap();
+        assert!(cache.get_search("quic transport", &both, 25, ttl).is_none());
+        assert!(cache
+            .get_search("quic transport", &both, 25, ttl * 2)
+            .is_some());
+    }
+
+    #[test]
+    fn test_store_and_load_rfc_index() {
+        let (cache, _temp) = test_cache();
+        assert!(cache.load_rfc_index().is_none());
+
+        let mut index = RfcIndex {
+            last_modified: Some("Wed, 01 May 2024 00:00:00 GMT".to_string()),
+            ..Default::default()
+        };
+        index.not_issued.insert(1849);
+        cache.store_rfc_index(&index).unwrap();
+
+        let loaded = cache.load_rfc_index().unwrap();
+        assert_eq!(loaded.last_modified, index.last_modified);
+        assert!(loaded.not_issued.contains(&1849));
+        // The index lives beside, not among, the cached documents.
+        assert!(cache.list_cached().is_empty());
+    }
+
     #[test]
     fn test_metadata_missing() {
         let (cache, _temp) = test_cache();
@@ -340,4 +1980,212 @@ mod tests {
         // Should return None for missing metadata
         assert!(cache.get_metadata(&doc).is_none());
     }
+
+    #[test]
+    fn test_store_document_file_moves_into_cache() {
+        let (cache, temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        let download = temp.path().join("download.txt");
+        fs::write(&download, "QUIC").unwrap();
+
+        let path = cache
+            .store_document_file(&doc, Format::Text, &download)
+            .unwrap();
+        assert!(!download.exists());
+        assert_eq!(cache.document_file(&doc, Format::Text), Some(path));
+        assert_eq!(
+            cache.get_document(&doc, Format::Text).as_deref(),
+            Some("QUIC")
+        );
+        assert_eq!(cache.document_file(&doc, Format::Html), None);
+    }
+
+    #[test]
+    fn test_list_cached_ignores_partial_writes() {
+        let (cache, _temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        let docs = cache.cache_dir().join("documents");
+        fs::write(docs.join("rfc8200.txt.part"), "partial").unwrap();
+
+        assert_eq!(cache.list_cached(), vec![doc]);
+    }
+
+    #[test]
+    fn test_concurrent_stores_never_leave_a_mix() {
+        let (cache, _temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        let contents: Vec<String> = (0..8).map(|i| i.to_string().repeat(100_000)).collect();
+        std::thread::scope(|scope| {
+            for content in &contents {
+                let (cache, doc) = (&cache, &doc);
+                scope.spawn(move || {
+                    cache.store_document(doc, Format::Text, content).unwrap();
+                    let meta = CacheMetadata::new(&content[..1], Utc::now());
+                    cache.store_metadata(doc, &meta).unwrap();
+                });
+            }
+        });
+
+        let stored = cache.get_document(&doc, Format::Text).unwrap();
+        assert!(contents.contains(&stored));
+        assert!(cache.get_metadata(&doc).is_some());
+        let docs = cache.cache_dir().join("documents");
+        let leftovers: Vec<_> = fs::read_dir(docs)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "part"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let (cache, _temp) = test_cache();
+        let cache = cache.with_compression(true);
+        let doc = DocumentType::Rfc(9000);
+        let content = "QUIC ".repeat(1000);
+        cache.store_document(&doc, Format::Text, &content).unwrap();
+
+        let path = cache.document_file(&doc, Format::Text).unwrap();
+        assert!(is_gzip(&path));
+        assert!(fs::metadata(&path).unwrap().len() < content.len() as u64);
+        assert_eq!(
+            cache.get_document(&doc, Format::Text).as_deref(),
+            Some(content.as_str())
+        );
+        assert_eq!(cache.list_cached(), vec![doc.clone()]);
+
+        let usage = cache.document_usage().unwrap();
+        assert_eq!(usage.compressed_files, 1);
+        assert_eq!(usage.uncompressed, content.len() as u64);
+    }
+
+    #[test]
+    fn test_reads_either_form_after_toggling() {
+        let (plain, temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        plain.store_document(&doc, Format::Text, "plain").unwrap();
+
+        let gz = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_compression(true);
+        assert!(gz.contains(&doc, Format::Text));
+        assert_eq!(
+            gz.get_document(&doc, Format::Text).as_deref(),
+            Some("plain")
+        );
+
+        // Overwriting in the new form leaves no stale copy behind.
+        gz.store_document(&doc, Format::Text, "zipped").unwrap();
+        assert_eq!(
+            plain.get_document(&doc, Format::Text).as_deref(),
+            Some("zipped")
+        );
+        assert_eq!(plain.document_usage().unwrap().files, 1);
+    }
+
+    #[test]
+    fn test_recompress_converts_both_ways() {
+        let (plain, temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        plain.store_document(&doc, Format::Text, "text").unwrap();
+        plain
+            .store_document(&doc, Format::Html, "<p>html</p>")
+            .unwrap();
+
+        let gz = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_compression(true);
+        assert_eq!(gz.recompress().unwrap(), 2);
+        assert_eq!(gz.recompress().unwrap(), 0);
+        let usage = gz.document_usage().unwrap();
+        assert_eq!((usage.files, usage.compressed_files), (2, 2));
+        assert_eq!(gz.get_document(&doc, Format::Text).as_deref(), Some("text"));
+
+        assert_eq!(plain.recompress().unwrap(), 2);
+        let usage = plain.document_usage().unwrap();
+        assert_eq!((usage.files, usage.compressed_files), (2, 0));
+        assert_eq!(
+            plain.get_document(&doc, Format::Html).as_deref(),
+            Some("<p>html</p>")
+        );
+    }
+
+    #[test]
+    fn test_remove_drops_compressed_files() {
+        let (cache, _temp) = test_cache();
+        let cache = cache.with_compression(true);
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        cache.remove(&doc).unwrap();
+
+        assert!(cache.list_cached().is_empty());
+        assert_eq!(cache.document_usage().unwrap(), DocumentUsage::default());
+    }
+
+    #[test]
+    fn test_metadata_without_newer_fields_still_loads() {
+        let (cache, temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        fs::write(
+            temp.path().join("documents").join("rfc9000.meta"),
+            r#"{"title": "QUIC", "cached_at": "2021-05-27T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let meta = cache.get_metadata(&doc).unwrap();
+        assert_eq!(meta.title, "QUIC");
+        assert!(meta.tags.is_empty());
+        assert_eq!(meta.last_accessed, None);
+    }
+
+    #[test]
+    fn test_notes_outlive_their_document() {
+        let (cache, _temp) = test_cache();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        fs::write(cache.notes_path(&doc), "read section 4").unwrap();
+
+        // Notes aren't documents, and refreshing content leaves them.
+        assert_eq!(cache.list_cached(), vec![doc.clone()]);
+        cache.store_document(&doc, Format::Text, "QUIC v2").unwrap();
+        assert!(cache.list_cached_with_metadata()[0].has_notes);
+
+        cache.remove(&doc).unwrap();
+        assert!(cache.list_cached().is_empty());
+        assert_eq!(cache.get_notes(&doc).as_deref(), Some("read section 4"));
+
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        cache.clear_cache().unwrap();
+        assert!(cache.list_cached().is_empty());
+        assert_eq!(cache.list_notes(), vec![doc.clone()]);
+        assert!(cache.audit().unwrap().is_clean());
+
+        assert!(cache.remove_notes(&doc).unwrap());
+        assert!(!cache.remove_notes(&doc).unwrap());
+        assert!(cache.list_notes().is_empty());
+    }
+
+    #[test]
+    fn test_export_notes() {
+        let (cache, temp) = test_cache();
+        let quic = DocumentType::Rfc(9000);
+        let draft = DocumentType::Draft("draft-ietf-foo-01".to_string());
+        fs::create_dir_all(cache.notes_path(&quic).parent().unwrap()).unwrap();
+        fs::write(cache.notes_path(&quic), "quic").unwrap();
+        fs::write(cache.notes_path(&draft), "foo").unwrap();
+
+        let out = temp.path().join("export");
+        assert_eq!(cache.export_notes(&out).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(out.join("rfc9000.notes.md")).unwrap(),
+            "quic"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("draft-ietf-foo-01.notes.md")).unwrap(),
+            "foo"
+        );
+    }
 }
@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::cache::storage::read_document;
+use crate::cache::FsCache;
+use crate::models::{DocumentType, Format};
+
+/// What [`FsCache::verify`] found. Lists are in name order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheVerifyReport {
+    /// Number of document files hashed
+    pub files_checked: usize,
+    /// Documents whose content no longer matches the checksum taken when
+    /// they were stored
+    pub corrupted: Vec<(DocumentType, Format)>,
+    /// Documents stored before checksums were kept
+    pub missing_checksums: Vec<(DocumentType, Format)>,
+    /// Documents that couldn't be read (or decompressed), with the error
+    pub unreadable: Vec<(DocumentType, Format, String)>,
+}
+
+impl CacheVerifyReport {
+    /// Whether every document matched its checksum.
+    pub fn is_clean(&self) -> bool {
+        self.corrupted.is_empty() && self.unreadable.is_empty()
+    }
+}
+
+/// Lowercase hex SHA-256 of `content`.
+pub(crate) fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Lowercase hex SHA-256 of the file at `path`, read in chunks.
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+impl FsCache {
+    /// Re-hash every cached document and compare against the checksums
+    /// recorded when it was stored. Changes nothing.
+    pub fn verify(&self) -> CacheVerifyReport {
+        let mut report = CacheVerifyReport::default();
+        let mut docs = self.list_cached();
+        docs.sort_by_key(|doc| doc.name());
+        for doc in docs {
+            for format in [Format::Text, Format::Html] {
+                let Some(path) = self.exact_document_file(&doc, format) else {
+                    continue;
+                };
+                report.files_checked += 1;
+                let content = match read_document(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        report.unreadable.push((doc.clone(), format, e.to_string()));
+                        continue;
+                    }
+                };
+                match self.checksum(&doc, format) {
+                    None => report.missing_checksums.push((doc.clone(), format)),
+                    Some(expected) if expected != sha256_hex(content.as_bytes()) => {
+                        report.corrupted.push((doc.clone(), format))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn verify_flags_corrupted_unhashed_and_unreadable_files() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        let docs = temp.path().join("documents");
+        let healthy = DocumentType::Rfc(9000);
+        let truncated = DocumentType::Rfc(8446);
+        let flipped = DocumentType::Rfc(9114);
+        cache
+            .store_document(&healthy, Format::Text, "QUIC")
+            .unwrap();
+        cache
+            .store_document(&truncated, Format::Text, "TLS 1.3 in full")
+            .unwrap();
+        cache
+            .store_document(&flipped, Format::Html, "<p>HTTP/3</p>")
+            .unwrap();
+        assert!(cache.verify().is_clean());
+
+        fs::write(docs.join("rfc8446.txt"), "TLS 1.3").unwrap();
+        fs::write(docs.join("rfc9114.html"), "<p>HTTP/4</p>").unwrap();
+        fs::write(docs.join("rfc791.txt"), "IP").unwrap();
+        fs::write(docs.join("rfc793.txt.gz"), "not gzip").unwrap();
+
+        let report = cache.verify();
+        assert_eq!(report.files_checked, 5);
+        assert_eq!(
+            report.corrupted,
+            [(truncated, Format::Text), (flipped, Format::Html)]
+        );
+        assert_eq!(
+            report.missing_checksums,
+            [(DocumentType::Rfc(791), Format::Text)]
+        );
+        assert_eq!(report.unreadable.len(), 1);
+        assert_eq!(report.unreadable[0].0, DocumentType::Rfc(793));
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn checksums_cover_the_uncompressed_content() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        let plain = cache.checksum(&doc, Format::Text).unwrap();
+
+        let cache = cache.with_compression(true);
+        assert_eq!(cache.recompress().unwrap(), 1);
+        assert_eq!(cache.checksum(&doc, Format::Text), Some(plain));
+        assert!(cache.verify().is_clean());
+
+        cache.remove(&doc).unwrap();
+        assert_eq!(cache.checksum(&doc, Format::Text), None);
+    }
+
+    #[test]
+    fn verified_reads_treat_corruption_as_a_miss() {
+        let temp = TempDir::new().unwrap();
+        let cache = FsCache::with_dir(temp.path().to_path_buf())
+            .unwrap()
+            .with_verified_reads(true);
+        let doc = DocumentType::Rfc(9000);
+        cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+        assert_eq!(
+            cache.get_document(&doc, Format::Text).as_deref(),
+            Some("QUIC")
+        );
+
+        fs::write(temp.path().join("documents").join("rfc9000.txt"), "QUIK").unwrap();
+        assert_eq!(cache.get_document(&doc, Format::Text), None);
+        let trusting = FsCache::with_dir(temp.path().to_path_buf()).unwrap();
+        assert_eq!(
+            trusting.get_document(&doc, Format::Text).as_deref(),
+            Some("QUIK")
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+use crate::aliases::Aliases;
+use crate::models::DocumentType;
+
+/// Point `name` at `document`.
+pub fn add(name: &str, document: &str) -> Result<()> {
+    let mut aliases = Aliases::load()?;
+    let doc = aliases.resolve(document)?;
+    match aliases.set(name, &doc)? {
+        Some(old) if old != doc => println!("{} now refers to {} (was {})", name, doc, old),
+        _ => println!("{} now refers to {}", name, doc),
+    }
+    aliases.save()
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let mut aliases = Aliases::load()?;
+    match aliases.remove(name) {
+        Some(doc) => {
+            aliases.save()?;
+            println!("Removed {} (was {})", name, doc);
+        }
+        None => println!("{} is not an alias", name),
+    }
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let aliases = Aliases::load()?;
+    if aliases.is_empty() {
+        println!("No aliases defined");
+        return Ok(());
+    }
+    let width = aliases
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, doc) in aliases.iter() {
+        println!("{:<width$}  {}", name, doc, width = width);
+    }
+    Ok(())
+}
+
+/// Resolve a document argument through the user's aliases.
+pub(crate) fn resolve(input: &str) -> Result<DocumentType> {
+    Ok(Aliases::load()?.resolve(input)?)
+}
@@ -0,0 +1,194 @@
+//! `rfc --by-author`: the RFCs and active drafts someone has written.
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::models::{AuthorDocuments, Document, Person, SearchFilter};
+use crate::render::{OutputOptions, TableRow};
+
+use super::plural;
+use super::search::{is_active, row, table};
+use super::terminal;
+
+pub struct Args {
+    /// The author's name, or their Datatracker person ID.
+    pub name: String,
+    pub limit: usize,
+    pub filter: SearchFilter,
+    pub json: bool,
+    pub offline: bool,
+}
+
+/// List up to `limit` of the author's documents, newest first. A name
+/// matching several people lists them instead and fails.
+pub async fn run(args: Args) -> Result<()> {
+    if args.offline {
+        anyhow::bail!("Authorship comes from the Datatracker; drop --offline");
+    }
+    let found = super::datatracker_client()?
+        .documents_by_author(&args.name)
+        .await?;
+    let (person, documents) = match found {
+        AuthorDocuments::Found { person, documents } => (person, documents),
+        AuthorDocuments::Ambiguous(people) => {
+            eprintln!("Several people match \"{}\":\n", args.name);
+            for line in candidates(&people) {
+                eprintln!("  {}", line);
+            }
+            anyhow::bail!("Give a fuller name, or one of the IDs above, to --by-author");
+        }
+    };
+
+    let mut documents = select(documents, args.filter);
+    let total = documents.len();
+    documents.truncate(args.limit);
+
+    if args.json {
+        let listing = json!({ "person": person, "documents": documents });
+        println!("{}", serde_json::to_string_pretty(&listing)?);
+        return Ok(());
+    }
+
+    let hint = person.hint();
+    let who = if hint.is_empty() {
+        person.name.clone()
+    } else {
+        format!("{} ({})", person.name, hint)
+    };
+    if documents.is_empty() {
+        println!("No {} by {}", kinds(args.filter), who);
+        return Ok(());
+    }
+    if total > documents.len() {
+        println!(
+            "Showing {} of {} {} by {}. Increase --limit <N> to show more.\n",
+            documents.len(),
+            total,
+            kinds(args.filter),
+            who
+        );
+    } else {
+        println!("{} document{} by {}:\n", total, plural(total), who);
+    }
+
+    let table = table(terminal::width(), false, false)
+        .with_output(OutputOptions::stdout(Config::current().color));
+    let rows: Vec<TableRow> = documents.iter().map(row).collect();
+    for line in table.render(&rows) {
+        println!("{}", line);
+    }
+    println!("\nUse 'rfc <document>' to read a document");
+    Ok(())
+}
+
+/// The documents `filter` lets through, dropping drafts that are no longer
+/// active.
+fn select(documents: Vec<Document>, filter: SearchFilter) -> Vec<Document> {
+    documents
+        .into_iter()
+        .filter(|doc| filter.includes(&doc.doc_type) && is_active(doc))
+        .collect()
+}
+
+/// What `filter` lists, for messages.
+fn kinds(filter: SearchFilter) -> &'static str {
+    match filter {
+        SearchFilter::RfcsOnly => "RFCs",
+        SearchFilter::DraftsOnly => "active drafts",
+        SearchFilter::Both => "RFCs or active drafts",
+    }
+}
+
+/// One `ID  name  (affiliation, email)` line per candidate, the IDs
+/// aligned.
+fn candidates(people: &[Person]) -> Vec<String> {
+    let id_width = people
+        .iter()
+        .map(|p| p.id.to_string().len())
+        .max()
+        .unwrap_or(0);
+    people
+        .iter()
+        .map(|person| {
+            let hint = person.hint();
+            if hint.is_empty() {
+                format!("{:>id_width$}  {}", person.id, person.name)
+            } else {
+                format!("{:>id_width$}  {}  ({})", person.id, person.name, hint)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DocumentType, DraftState};
+
+    fn doc(name: &str, state: Option<DraftState>) -> Document {
+        let mut doc = Document::new(
+            name.to_string(),
+            name.to_uppercase(),
+            DocumentType::from_canonical_name(name),
+        );
+        doc.draft_state = state;
+        doc
+    }
+
+    fn names(documents: &[Document]) -> Vec<&str> {
+        documents.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    #[test]
+    fn only_rfcs_and_active_drafts_are_listed() {
+        let documents = vec![
+            doc("rfc9110", None),
+            doc("draft-ietf-httpbis-semantics", Some(DraftState::Rfc)),
+            doc("draft-nottingham-new", Some(DraftState::Active)),
+            doc("draft-nottingham-old", Some(DraftState::Expired)),
+            doc("draft-nottingham-unknown", None),
+        ];
+        assert_eq!(
+            names(&select(documents.clone(), SearchFilter::Both)),
+            [
+                "rfc9110",
+                "draft-nottingham-new",
+                "draft-nottingham-unknown"
+            ]
+        );
+        assert_eq!(
+            names(&select(documents.clone(), SearchFilter::RfcsOnly)),
+            ["rfc9110"]
+        );
+        assert_eq!(
+            names(&select(documents, SearchFilter::DraftsOnly)),
+            ["draft-nottingham-new", "draft-nottingham-unknown"]
+        );
+    }
+
+    #[test]
+    fn candidates_show_what_tells_them_apart() {
+        let people = [
+            Person {
+                id: 12345,
+                name: "Mark Nottingham".into(),
+                affiliation: Some("Fastly".into()),
+                email: Some("mnot@mnot.net".into()),
+            },
+            Person {
+                id: 987,
+                name: "Mark Nottingham".into(),
+                affiliation: None,
+                email: None,
+            },
+        ];
+        assert_eq!(
+            candidates(&people),
+            [
+                "12345  Mark Nottingham  (Fastly, mnot@mnot.net)",
+                "  987  Mark Nottingham"
+            ]
+        );
+    }
+}
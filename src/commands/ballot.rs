@@ -0,0 +1,175 @@
+//! `rfc ballot`: IESG ballot positions on a draft.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::models::{Ballot, DocumentType, Position};
+
+use super::alias::resolve;
+use super::draft_status::unversioned;
+use super::terminal;
+
+/// Indent for wrapped Discuss text.
+const DISCUSS_INDENT: usize = 4;
+
+/// `rfc ballot --json` output.
+#[derive(Serialize)]
+struct Output<'a> {
+    document: String,
+    ballot: Option<&'a Ballot>,
+}
+
+/// Print the current positions on `document`'s most recent ballot.
+pub async fn run(document: &str, json: bool, offline: bool) -> Result<()> {
+    if offline {
+        anyhow::bail!("Ballots come from the Datatracker; drop --offline");
+    }
+    let doc_type = resolve(document)?;
+    let name = match &doc_type {
+        DocumentType::Rfc(_) => doc_type.name(),
+        DocumentType::Draft(name) => unversioned(name).to_string(),
+    };
+    let ballot = super::datatracker_client()?.ballot(&name).await?;
+
+    if json {
+        let output = Output {
+            document: name,
+            ballot: ballot.as_ref(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+    let Some(ballot) = ballot else {
+        match doc_type {
+            DocumentType::Rfc(_) => println!(
+                "{} has no IESG ballot (ballots are held on the draft it came from)",
+                doc_type
+            ),
+            DocumentType::Draft(_) => println!("{} has no IESG ballot", name),
+        }
+        return Ok(());
+    };
+    print!("{}", render(&name, &ballot, terminal::width()));
+    Ok(())
+}
+
+fn render(name: &str, ballot: &Ballot, width: usize) -> String {
+    let mut out = format!(
+        "IESG ballot on {}, opened {}",
+        name,
+        ballot.opened.format("%Y-%m-%d")
+    );
+    if let Some(closed) = ballot.closed {
+        out.push_str(&format!(", closed {}", closed.format("%Y-%m-%d")));
+    }
+    out.push_str("\n\n");
+    if ballot.positions.is_empty() {
+        out.push_str("No positions recorded yet\n");
+        return out;
+    }
+
+    let name_width = ballot
+        .positions
+        .iter()
+        .map(|p| p.balloter.chars().count())
+        .max()
+        .unwrap_or(0);
+    for p in &ballot.positions {
+        out.push_str(&format!(
+            "{:<name_width$}  {}\n",
+            p.balloter,
+            p.position.label()
+        ));
+    }
+
+    let mut counts: BTreeMap<Position, usize> = BTreeMap::new();
+    for p in &ballot.positions {
+        *counts.entry(p.position).or_default() += 1;
+    }
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(position, n)| format!("{} {}", position.label(), n))
+        .collect();
+    out.push_str(&format!("\n{}\n", counts.join(", ")));
+
+    for p in &ballot.positions {
+        if let Some(discuss) = &p.discuss {
+            out.push_str(&format!("\n{} ({}):\n", p.position.label(), p.balloter));
+            out.push_str(&terminal::wrap(discuss, width, DISCUSS_INDENT));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BallotPosition;
+    use chrono::{TimeZone, Utc};
+
+    fn position(balloter: &str, position: Position, discuss: Option<&str>) -> BallotPosition {
+        BallotPosition {
+            balloter: balloter.to_string(),
+            position,
+            discuss: discuss.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn table_counts_and_discusses() {
+        let ballot = Ballot {
+            opened: Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap(),
+            closed: None,
+            positions: vec![
+                position(
+                    "Roman Danyliw",
+                    Position::Discuss,
+                    Some("Section 4 is unclear."),
+                ),
+                position("Éric Vyncke", Position::Yes, None),
+                position("Erik Kline", Position::NoObjection, None),
+                position("Warren Kumari", Position::NoObjection, None),
+            ],
+        };
+        assert_eq!(
+            render("draft-ietf-foo-bar", &ballot, 80),
+            "IESG ballot on draft-ietf-foo-bar, opened 2023-03-01\n\
+             \n\
+             Roman Danyliw  Discuss\n\
+             Éric Vyncke    Yes\n\
+             Erik Kline     No Objection\n\
+             Warren Kumari  No Objection\n\
+             \n\
+             Discuss 1, Yes 1, No Objection 2\n\
+             \n\
+             Discuss (Roman Danyliw):\n    Section 4 is unclear.\n"
+        );
+    }
+
+    #[test]
+    fn json_shape() {
+        let ballot = Ballot {
+            opened: Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap(),
+            closed: None,
+            positions: vec![position("Erik Kline", Position::NoObjection, None)],
+        };
+        let json = serde_json::to_value(Output {
+            document: "draft-ietf-foo-bar".to_string(),
+            ballot: Some(&ballot),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "document": "draft-ietf-foo-bar",
+                "ballot": {
+                    "opened": "2023-03-01T00:00:00Z",
+                    "positions": [{"balloter": "Erik Kline", "position": "no_objection"}]
+                }
+            })
+        );
+    }
+}
@@ -1,56 +1,118 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
+use serde::Serialize;
+
+use crate::analysis::context_hunks;
+use crate::cache::{
+    arrange_cached, group_draft_revisions, recently_accessed, CacheAuditReport, CacheCounters,
+    CacheManager, CacheSort, CacheStats, CachedDocument, DocumentCache, RemovalReport,
+};
+use crate::config::Config;
+use crate::models::{DocumentType, Format, SearchFilter};
+use crate::render::{Column, TableRenderer, TableRow};
+
+use super::alias::resolve;
+use super::fetch::fetch_into;
+use super::{open_cache, open_writable_cache, plural};
+use crate::workflow::{backfill_titles, missing_abstracts, missing_titles};
 
-use crate::cache::CacheManager;
-use crate::models::DocumentType;
+/// Listings are laid out for this many columns rather than the
+/// terminal's, so they read the same when piped.
+const LIST_WIDTH: usize = 80;
 
-pub fn list(wide: bool) -> Result<()> {
-    let cache = CacheManager::new()?;
-    let cached = cache.list_cached_with_metadata();
+pub struct ListArgs {
+    /// Don't truncate titles.
+    pub wide: bool,
+    /// Add cached date, size, and format columns.
+    pub long: bool,
+    pub sort: CacheSort,
+    pub reverse: bool,
+    pub filter: SearchFilter,
+    /// Print the listing as a JSON array.
+    pub json: bool,
+    /// Only list documents with this tag.
+    pub tagged: Option<String>,
+    /// Show each draft's newest cached revision, noting the older ones.
+    pub group_drafts: bool,
+}
+
+pub fn list(args: ListArgs) -> Result<()> {
+    let ListArgs { wide, long, .. } = args;
+    let cache = open_cache()?;
+    let mut cached = match &args.tagged {
+        Some(tag) => cache.list_by_tag(tag),
+        None => cache.list_cached_with_metadata(),
+    };
+    let total = cached.len();
+    arrange_cached(&mut cached, args.filter, args.sort, args.reverse);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&cached)?);
+        return Ok(());
+    }
 
     if cached.is_empty() {
-        println!("Cache is empty");
+        if let (0, Some(tag)) = (total, &args.tagged) {
+            println!("No cached documents are tagged '{}'", tag);
+        } else if total == 0 {
+            println!("Cache is empty");
+        } else {
+            println!("No cached documents match");
+        }
         return Ok(());
     }
 
     println!("Cached documents ({}):\n", cached.len());
 
-    let max_name_width = cached
-        .iter()
-        .map(|cd| cd.doc_type.name().len())
-        .max()
-        .unwrap_or(10);
+    // Name, then for `--long` the cached date, size, formats, and `N` if
+    // there are notes.
+    let mut table = TableRenderer::new(LIST_WIDTH)
+        .with_column(Column::fit())
+        .with_wide(wide);
+    if long {
+        table = table
+            .with_column(Column::fit())
+            .with_column(Column::fixed(8).right_aligned())
+            .with_column(Column::fixed(8))
+            .with_column(Column::fixed(1));
+    }
+    let mut missing_count = 0;
 
-    let title_width = if wide {
-        usize::MAX
+    let grouped = if args.group_drafts {
+        group_draft_revisions(cached)
     } else {
-        80_usize
-            .saturating_sub(max_name_width)
-            .saturating_sub(4)
-            .min(77)
+        cached.into_iter().map(|doc| (doc, Vec::new())).collect()
     };
-    let mut missing_count = 0;
-
-    for cached_doc in &cached {
-        let name = cached_doc.doc_type.name();
-        match &cached_doc.metadata {
-            Some(meta) => {
-                let title = truncate(&meta.title, title_width);
-                println!("{:<width$}  {}", name, title, width = max_name_width);
+    let rows: Vec<TableRow> = grouped
+        .iter()
+        .map(|(cached_doc, older)| {
+            let mut cells = vec![cached_doc.doc_type.name()];
+            let mut suffix = String::new();
+            if long {
+                cells.extend(long_cells(cached_doc));
+                suffix = tag_suffix(cached_doc);
             }
-            None => {
-                println!(
-                    "{:<width$}  (title unavailable)",
-                    name,
-                    width = max_name_width
-                );
-                missing_count += 1;
+            if !older.is_empty() {
+                suffix.insert_str(0, &revisions_suffix(older));
             }
-        }
+            let title = match &cached_doc.metadata {
+                Some(meta) if !meta.title.trim().is_empty() => meta.title.clone(),
+                _ => {
+                    missing_count += 1;
+                    "(title unavailable)".to_string()
+                }
+            };
+            TableRow::new(cells, title).with_suffix(suffix)
+        })
+        .collect();
+    for line in table.render(&rows) {
+        println!("{}", line);
     }
 
     if missing_count > 0 {
         println!(
-            "\n({} document{} without title - run 'rfc fetch <doc>' to refresh metadata)",
+            "\n({} document{} without title - run 'rfc cache backfill' to fetch them)",
             missing_count,
             if missing_count == 1 { "" } else { "s" }
         );
@@ -59,60 +121,599 @@ pub fn list(wide: bool) -> Result<()> {
     Ok(())
 }
 
-pub fn info() -> Result<()> {
-    let cache = CacheManager::new()?;
+/// Show the `limit` most recently opened documents.
+pub fn recent(limit: usize) -> Result<()> {
+    let cache = open_cache()?;
+    let recent = recently_accessed(cache.list_cached_with_metadata(), limit);
+    if recent.is_empty() {
+        println!("No document has been opened from the cache yet");
+        return Ok(());
+    }
+    let rows: Vec<TableRow> = recent
+        .iter()
+        .map(|cached| {
+            let opened = cached
+                .last_accessed()
+                .map(|at| Config::current().time_style.format(at))
+                .unwrap_or_default();
+            let title = cached.metadata.as_ref().map_or("", |m| m.title.as_str());
+            TableRow::new(vec![cached.doc_type.name(), opened], title)
+        })
+        .collect();
+    let table = TableRenderer::new(LIST_WIDTH)
+        .with_column(Column::fit())
+        .with_column(Column::fit());
+    for line in table.render(&rows) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Cached date, size, formats, and `N` if there are notes: the cells
+/// of the `--long` columns.
+fn long_cells(cached: &CachedDocument) -> [String; 4] {
+    let date = cached
+        .cached_at()
+        .map(|at| Config::current().time_style.format(at))
+        .unwrap_or_else(|| "-".to_string());
+    let formats: Vec<&str> = cached.formats.iter().map(|f| f.extension()).collect();
+    [
+        date,
+        format_size(cached.size),
+        formats.join(","),
+        if cached.has_notes { "N" } else { "" }.to_string(),
+    ]
+}
+
+/// Tags for `--long`, after the title: `  [routing, bgp]`.
+/// `  (also -29, -32)`: the older cached revisions of a grouped draft.
+fn revisions_suffix(older: &[u32]) -> String {
+    let revs: Vec<String> = older.iter().map(|rev| format!("-{:02}", rev)).collect();
+    format!("  (also {})", revs.join(", "))
+}
+
+fn tag_suffix(cached: &CachedDocument) -> String {
+    match &cached.metadata {
+        Some(meta) if !meta.tags.is_empty() => format!("  [{}]", meta.tags.join(", ")),
+        _ => String::new(),
+    }
+}
+
+/// `rfc cache info --json` output. Like the listing, a scripting
+/// interface: add fields, don't rename or remove them.
+#[derive(Debug, Serialize)]
+struct CacheInfo {
+    cache_dir: PathBuf,
+    /// Set when the cache is the temporary one used for lack of a home
+    /// directory
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    temporary: bool,
+    document_count: usize,
+    total_size_bytes: u64,
+    /// Only with `--stats`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<CacheStats>,
+}
+
+/// Describe the cache; with `stats`, also how often it has saved a
+/// download.
+pub fn info(json: bool, stats: bool) -> Result<()> {
+    let cache = open_cache()?;
     let path = cache.cache_dir();
     let cached = cache.list_cached();
 
-    println!("Cache directory: {}", path.display());
+    if json {
+        let info = CacheInfo {
+            cache_dir: path.to_path_buf(),
+            temporary: cache.is_temporary(),
+            document_count: cached.len(),
+            total_size_bytes: dir_size_recursive(path)?,
+            stats: stats.then(|| cache.stats()),
+        };
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    if cache.is_temporary() {
+        println!(
+            "Cache directory: {} (temporary: no home directory was found; set RFC_CACHE_DIR to keep documents)",
+            path.display()
+        );
+    } else {
+        println!("Cache directory: {}", path.display());
+    }
     println!("Cached documents: {}", cached.len());
 
     if let Ok(total_size) = dir_size_recursive(path) {
-        let size_str = if total_size < 1024 {
-            format!("{} B", total_size)
-        } else if total_size < 1024 * 1024 {
-            format!("{:.1} KB", total_size as f64 / 1024.0)
+        println!("Total size: {}", format_size(total_size));
+    }
+
+    let usage = cache.document_usage()?;
+    println!(
+        "Compression: {}",
+        if cache.compresses() { "on" } else { "off" }
+    );
+    if usage.compressed_files > 0 {
+        println!(
+            "Documents: {} on disk, ~{} uncompressed ({} of {} files compressed)",
+            format_size(usage.on_disk),
+            format_size(usage.uncompressed),
+            usage.compressed_files,
+            usage.files
+        );
+    }
+    if stats {
+        print_stats(&cache.stats());
+    }
+
+    Ok(())
+}
+
+fn print_stats(stats: &CacheStats) {
+    let Some(since) = stats.since else {
+        println!("\nNo cache statistics recorded yet");
+        return;
+    };
+    println!("\nStatistics since {}:", since.format("%Y-%m-%d %H:%M UTC"));
+    print_counters(&stats.total);
+    println!("Last run:");
+    print_counters(&stats.last_run);
+}
+
+fn print_counters(counters: &CacheCounters) {
+    let rate = counters
+        .hit_rate()
+        .map(|rate| format!(" ({:.0}% of lookups)", rate * 100.0))
+        .unwrap_or_default();
+    for (label, value) in [
+        ("Hits", format!("{}{}", counters.hits, rate)),
+        ("Misses", counters.misses.to_string()),
+        ("Refreshes", counters.refreshes.to_string()),
+        ("Fetched", format_size(counters.bytes_fetched)),
+        ("Served from cache", format_size(counters.bytes_from_cache)),
+    ] {
+        println!("  {:<19}{}", format!("{}:", label), value);
+    }
+}
+
+/// Zero the statistics shown by `rfc cache info --stats`.
+pub fn reset_stats() -> Result<()> {
+    open_writable_cache()?.reset_stats()?;
+    println!("Cache statistics reset");
+    Ok(())
+}
+
+/// Convert cached documents to match the `compress_cache` setting.
+pub fn recompress() -> Result<()> {
+    let cache = open_writable_cache()?;
+    let converted = cache.recompress()?;
+    println!(
+        "{} {} document file{}",
+        if cache.compresses() {
+            "Compressed"
         } else {
-            format!("{:.1} MB", total_size as f64 / (1024.0 * 1024.0))
-        };
-        println!("Total size: {}", size_str);
+            "Decompressed"
+        },
+        converted,
+        if converted == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Delete what the `[gc]` policy says the cache can do without, listing
+/// it and the space freed.
+pub fn gc() -> Result<()> {
+    let policy = Config::current().gc_policy()?;
+    let cache = open_writable_cache()?;
+    let report = cache.garbage_collect(&policy)?;
+    if report.is_empty() {
+        println!("Nothing to collect");
+        return Ok(());
+    }
+    let redundant: Vec<String> = report
+        .redundant_formats
+        .iter()
+        .map(|(doc, format)| format!("{}.{}", doc.name(), format.extension()))
+        .collect();
+    print_section("Copies in formats gc.keep leaves out", &redundant);
+    print_section(
+        "Duplicate encodings",
+        &file_names(&report.duplicate_encodings),
+    );
+    print_section("Interrupted writes", &file_names(&report.partial_writes));
+    print_section("Temporary files", &file_names(&report.temp_files));
+    let dirs: Vec<String> = report
+        .empty_directories
+        .iter()
+        .map(|dir| {
+            dir.strip_prefix(cache.cache_dir())
+                .unwrap_or(dir)
+                .display()
+                .to_string()
+        })
+        .collect();
+    print_section("Empty directories", &dirs);
+    let files = report.files_removed();
+    println!(
+        "\nRemoved {} file{}, freeing {}",
+        files,
+        plural(files),
+        format_size(report.bytes_freed)
+    );
+    Ok(())
+}
+
+/// Check the cache for inconsistencies; with `fix`, remove orphaned,
+/// empty, and partial files and backfill missing metadata.
+pub async fn doctor(fix: bool, offline: bool) -> Result<()> {
+    let cache = open_cache()?;
+    let report = cache.audit()?;
+    print_report(&report, cache.cache_dir());
+    if report.is_clean() || !fix {
+        if !report.is_clean() {
+            println!("\nRun 'rfc cache doctor --fix' to repair");
+        }
+        return Ok(());
+    }
+
+    cache.ensure_writable()?;
+    println!();
+    let removed = cache.repair(&report)?;
+    if removed > 0 {
+        println!("Removed {} file{}", removed, plural(removed));
+    }
+
+    let missing = &report.missing_metadata;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if offline {
+        println!(
+            "Skipped metadata for {} document{} (offline)",
+            missing.len(),
+            plural(missing.len())
+        );
+        return Ok(());
+    }
+    backfill(missing, &cache).await?;
+    Ok(())
+}
+
+/// Check every cached document against its stored checksum. With `fix`,
+/// corrupted and unreadable documents are fetched again.
+pub async fn verify(fix: bool, offline: bool, quiet: bool) -> Result<()> {
+    let cache = open_cache()?;
+    let report = cache.verify();
+    println!(
+        "Checked {} file{} in {}",
+        report.files_checked,
+        plural(report.files_checked),
+        cache.cache_dir().display()
+    );
+    let file =
+        |doc: &DocumentType, format: &Format| format!("{}.{}", doc.name(), format.extension());
+    let corrupted: Vec<String> = report
+        .corrupted
+        .iter()
+        .map(|(doc, format)| file(doc, format))
+        .collect();
+    let unreadable: Vec<String> = report
+        .unreadable
+        .iter()
+        .map(|(doc, format, error)| format!("{}: {}", file(doc, format), error))
+        .collect();
+    let unhashed: Vec<String> = report
+        .missing_checksums
+        .iter()
+        .map(|(doc, format)| file(doc, format))
+        .collect();
+    print_section("Corrupted", &corrupted);
+    print_section("Unreadable", &unreadable);
+    print_section("No checksum (cached before checksums were kept)", &unhashed);
+    if report.is_clean() {
+        println!("No corruption found");
+        return Ok(());
+    }
+    if !fix {
+        println!("\nRun 'rfc cache verify --fix' to fetch them again");
+        return Ok(());
+    }
+    cache.ensure_writable()?;
+    if offline {
+        anyhow::bail!("Fetching corrupted documents again needs the network");
+    }
+
+    // Fetching stores fresh text over the damaged copy; metadata (tags,
+    // bookmarks) is kept.
+    let mut docs: Vec<DocumentType> = report
+        .corrupted
+        .iter()
+        .map(|(doc, _)| doc.clone())
+        .chain(report.unreadable.iter().map(|(doc, _, _)| doc.clone()))
+        .collect();
+    docs.sort_by_key(|doc| doc.name());
+    docs.dedup();
+    println!();
+    let mut failed = 0;
+    for doc in &docs {
+        if let Err(e) = fetch_into(doc, &cache, quiet).await {
+            eprintln!("Warning: {}: {:#}", doc, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} document{} could not be fetched",
+            failed,
+            docs.len(),
+            plural(docs.len())
+        );
     }
+    println!(
+        "Fetched {} document{} again",
+        docs.len(),
+        plural(docs.len())
+    );
+    Ok(())
+}
 
+/// Fetch titles for cached documents that have none, and abstracts for
+/// those whose metadata predates them.
+pub async fn backfill_metadata(offline: bool) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let mut missing = missing_titles(&cache);
+    missing.extend(missing_abstracts(&cache));
+    missing.sort_by_key(|doc| doc.name());
+    if missing.is_empty() {
+        println!("Every cached document has a title and abstract");
+        return Ok(());
+    }
+    if offline {
+        anyhow::bail!(
+            "{} document{} need{} a title or abstract, but fetching them needs the network",
+            missing.len(),
+            plural(missing.len()),
+            if missing.len() == 1 { "s" } else { "" }
+        );
+    }
+    if backfill(&missing, &cache).await? == 0 {
+        anyhow::bail!("No titles could be fetched");
+    }
     Ok(())
 }
 
+/// Fetch titles for `docs`, warning about each failure, and print a
+/// summary. Returns how many succeeded.
+async fn backfill(docs: &[DocumentType], cache: &CacheManager) -> Result<usize> {
+    let datatracker = super::document_fetcher()?.datatracker();
+    let mut fetched = 0;
+    for (doc, result) in backfill_titles(docs, cache, &datatracker).await {
+        match result {
+            Ok(()) => fetched += 1,
+            Err(e) => eprintln!("Warning: no title for {}: {:#}", doc, e),
+        }
+    }
+    println!(
+        "Fetched titles for {} of {} document{}",
+        fetched,
+        docs.len(),
+        plural(docs.len())
+    );
+    Ok(fetched)
+}
+
+fn print_report(report: &CacheAuditReport, cache_dir: &Path) {
+    println!(
+        "Checked {} file{} in {}",
+        report.files_checked,
+        plural(report.files_checked),
+        cache_dir.display()
+    );
+    if report.is_clean() {
+        println!("No problems found");
+        return;
+    }
+    let names: Vec<String> = report.missing_metadata.iter().map(|d| d.name()).collect();
+    print_section("Orphaned metadata", &file_names(&report.orphaned_metadata));
+    print_section("Missing metadata", &names);
+    print_section("Empty files", &file_names(&report.empty_files));
+    print_section("Interrupted downloads", &file_names(&report.partial_writes));
+    print_section(
+        "Unrecognized files (left in place)",
+        &file_names(&report.unrecognized),
+    );
+}
+
+fn print_section(heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("\n{} ({}):", heading, items.len());
+    for item in items {
+        println!("  {}", item);
+    }
+}
+
+fn file_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| p.display().to_string())
+        })
+        .collect()
+}
+
+/// `3 documents and 3 metadata files, 48.2 KB freed`
+fn removal_summary(report: &RemovalReport) -> String {
+    let documents = report.documents_removed.len();
+    format!(
+        "{} document{} and {} metadata file{}, {} freed",
+        documents,
+        plural(documents),
+        report.metadata_removed,
+        plural(report.metadata_removed),
+        format_size(report.bytes_freed)
+    )
+}
+
 pub fn clear() -> Result<()> {
-    let cache = CacheManager::new()?;
-    cache.clear_cache()?;
-    println!("Cache cleared");
+    let cache = open_writable_cache()?;
+    let report = cache.clear_cache()?;
+    println!("Cache cleared: {}", removal_summary(&report));
+    let notes = cache.list_notes().len();
+    if notes > 0 {
+        println!("(kept notes on {} document{})", notes, plural(notes));
+    }
+    Ok(())
+}
+
+/// Remove all but the newest `keep` cached revisions of each draft.
+pub fn prune_old_revisions(keep: usize) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let report = cache.prune_old_revisions(keep)?;
+    let removed = &report.documents_removed;
+    if removed.is_empty() {
+        println!("No older draft revisions cached");
+        return Ok(());
+    }
+    for doc in removed {
+        println!("Removed {}", doc);
+    }
+    println!(
+        "\nRemoved {} older draft revision{}, freeing {}",
+        removed.len(),
+        plural(removed.len()),
+        format_size(report.bytes_freed)
+    );
     Ok(())
 }
 
-pub fn remove(document: &str) -> Result<()> {
-    let cache = CacheManager::new()?;
-    let doc_type = DocumentType::from_user_input(document);
+/// Drop a document from the cache. Its notes stay (and reattach if it's
+/// cached again) unless `purge_notes` is set.
+pub fn remove(document: &str, purge_notes: bool) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let doc_type = resolve(document)?;
 
-    if cache.remove(&doc_type)? {
-        println!("Removed {} from cache", doc_type);
+    let report = cache.remove_document(&doc_type)?;
+    if !report.documents_removed.is_empty() {
+        println!(
+            "Removed {} from cache, freeing {}",
+            doc_type,
+            format_size(report.bytes_freed)
+        );
     } else {
         println!("{} was not in cache", doc_type);
     }
+    if purge_notes {
+        if cache.remove_notes(&doc_type)? {
+            println!("Deleted notes on {}", doc_type);
+        }
+    } else if cache.has_notes(&doc_type) {
+        println!(
+            "Kept notes on {} (use --purge-notes to delete them)",
+            doc_type
+        );
+    }
     Ok(())
 }
 
-/// Truncate `s` to `max_width` characters, replacing the tail with `...`
-/// when the string would be longer. Counts unicode scalar values, not bytes.
-fn truncate(s: &str, max_width: usize) -> String {
-    if max_width == usize::MAX || s.chars().count() <= max_width {
-        s.to_string()
+/// Whether `document` is cached in any format. Prints nothing; the
+/// binary turns the answer into its exit status.
+pub fn has(document: &str) -> Result<bool> {
+    let cache = open_cache()?;
+    let doc_type = resolve(document)?;
+    Ok([Format::Text, Format::Html]
+        .iter()
+        .any(|format| cache.contains(&doc_type, *format)))
+}
+
+pub struct GrepArgs {
+    pub pattern: String,
+    /// Match regardless of case.
+    pub ignore_case: bool,
+    /// Lines of context to print before each match.
+    pub before: usize,
+    /// Lines of context to print after each match.
+    pub after: usize,
+}
+
+/// Print the lines of cached documents containing `pattern`, grouped under
+/// each document's name. Documents cached only as HTML aren't searched.
+/// Returns whether anything matched; the binary turns that into its exit
+/// status.
+pub fn grep(args: GrepArgs) -> Result<bool> {
+    anyhow::ensure!(!args.pattern.is_empty(), "The pattern must not be empty");
+    let cache = open_cache()?;
+    let mut cached = cache.list_cached_with_metadata();
+    arrange_cached(&mut cached, SearchFilter::Both, CacheSort::Name, false);
+
+    let pattern = match args.ignore_case {
+        true => args.pattern.to_lowercase(),
+        false => args.pattern.clone(),
+    };
+    let mut found = false;
+    for doc in cached {
+        let Some(text) = cache.get_document(&doc.doc_type, Format::Text) else {
+            continue;
+        };
+        let lines: Vec<&str> = text.lines().collect();
+        let matches: Vec<usize> = (0..lines.len())
+            .filter(|&i| match args.ignore_case {
+                true => lines[i].to_lowercase().contains(&pattern),
+                false => lines[i].contains(&pattern),
+            })
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        if found {
+            println!();
+        }
+        found = true;
+        print!(
+            "{}",
+            grep_group(&doc.doc_type.name(), &lines, &matches, &args)
+        );
+    }
+    Ok(found)
+}
+
+/// One document's `rfc cache grep` output, after ripgrep's: the name,
+/// then each hunk's lines numbered from 1, `:` marking matches and `-`
+/// context, with `--` between hunks.
+fn grep_group(name: &str, lines: &[&str], matches: &[usize], args: &GrepArgs) -> String {
+    let mut out = format!("{}\n", name);
+    let hunks = context_hunks(matches, args.before, args.after, lines.len());
+    for (n, hunk) in hunks.into_iter().enumerate() {
+        if n > 0 {
+            out.push_str("--\n");
+        }
+        for i in hunk {
+            let mark = if matches.binary_search(&i).is_ok() {
+                ':'
+            } else {
+                '-'
+            };
+            out.push_str(&format!("{}{}{}\n", i + 1, mark, lines[i]));
+        }
+    }
+    out
+}
+
+pub(super) fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
     } else {
-        let truncated: String = s.chars().take(max_width.saturating_sub(3)).collect();
-        format!("{}...", truncated)
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
     }
 }
 
 /// Sum the sizes of all regular files under `dir`, recursively.
-fn dir_size_recursive(dir: &std::path::Path) -> std::io::Result<u64> {
+fn dir_size_recursive(dir: &Path) -> std::io::Result<u64> {
     let mut total = 0u64;
     let mut stack = vec![dir.to_path_buf()];
     while let Some(d) = stack.pop() {
@@ -132,8 +733,31 @@ fn dir_size_recursive(dir: &std::path::Path) -> std::io::Result<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Format;
     use tempfile::TempDir;
 
+    #[test]
+    fn grep_groups_hunks_under_the_document_name() {
+        let lines: Vec<String> = (1..=12).map(|n| format!("line {}", n)).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let args = |before, after| GrepArgs {
+            pattern: String::new(),
+            ignore_case: false,
+            before,
+            after,
+        };
+
+        assert_eq!(
+            grep_group("rfc9000", &lines, &[0, 5], &args(0, 0)),
+            "rfc9000\n1:line 1\n--\n6:line 6\n"
+        );
+        assert_eq!(
+            grep_group("rfc9000", &lines, &[0, 3, 11], &args(1, 1)),
+            "rfc9000\n1:line 1\n2-line 2\n3-line 3\n4:line 4\n5-line 5\n--\n\
+             11-line 11\n12:line 12\n"
+        );
+    }
+
     #[test]
     fn dir_size_recursive_sums_nested_files() {
         let dir = TempDir::new().unwrap();
@@ -147,17 +771,117 @@ mod tests {
     }
 
     #[test]
-    fn truncate_short_string() {
-        assert_eq!(truncate("hello", 80), "hello");
+    fn revisions_suffix_lists_older_revisions() {
+        assert_eq!(revisions_suffix(&[29, 32]), "  (also -29, -32)");
+        assert_eq!(revisions_suffix(&[0]), "  (also -00)");
     }
 
     #[test]
-    fn truncate_long_string() {
-        assert_eq!(truncate("hello world", 8), "hello...");
+    fn long_cells_cover_date_size_formats_and_notes() {
+        let cached = CachedDocument {
+            doc_type: DocumentType::Rfc(9000),
+            metadata: None,
+            formats: vec![Format::Text, Format::Html],
+            size: 123_456,
+            modified: Some(
+                chrono::DateTime::parse_from_rfc3339("2021-05-27T12:00:00Z")
+                    .unwrap()
+                    .into(),
+            ),
+            has_notes: true,
+        };
+        assert_eq!(
+            long_cells(&cached),
+            ["2021-05-27", "120.6 KB", "txt,html", "N"]
+        );
+
+        let bare = CachedDocument {
+            modified: None,
+            formats: vec![Format::Text],
+            size: 10,
+            has_notes: false,
+            ..cached
+        };
+        assert_eq!(long_cells(&bare), ["-", "10 B", "txt", ""]);
+    }
+
+    #[test]
+    fn list_json_shape_is_stable() {
+        let at = chrono::DateTime::parse_from_rfc3339("2021-05-27T12:00:00Z")
+            .unwrap()
+            .into();
+        let mut quic = crate::cache::CacheMetadata::new("QUIC", at);
+        quic.set_abstract(Some("QUIC is a transport.".into()));
+        let cached = vec![
+            CachedDocument {
+                doc_type: DocumentType::Rfc(9000),
+                metadata: Some(quic),
+                formats: vec![Format::Text, Format::Html],
+                size: 1234,
+                modified: None,
+                has_notes: false,
+            },
+            CachedDocument {
+                doc_type: DocumentType::Draft("draft-ietf-foo-00".to_string()),
+                metadata: None,
+                formats: vec![Format::Html],
+                size: 5,
+                modified: None,
+                has_notes: true,
+            },
+        ];
+        assert_eq!(
+            serde_json::to_string_pretty(&cached).unwrap(),
+            r#"[
+  {
+    "name": "rfc9000",
+    "type": "rfc",
+    "title": "QUIC",
+    "abstract": "QUIC is a transport.",
+    "cached_at": "2021-05-27T12:00:00Z",
+    "last_accessed": null,
+    "size": 1234,
+    "formats": [
+      "txt",
+      "html"
+    ],
+    "tags": [],
+    "has_notes": false
+  },
+  {
+    "name": "draft-ietf-foo-00",
+    "type": "draft",
+    "title": null,
+    "abstract": null,
+    "cached_at": null,
+    "last_accessed": null,
+    "size": 5,
+    "formats": [
+      "html"
+    ],
+    "tags": [],
+    "has_notes": true
+  }
+]"#
+        );
     }
 
     #[test]
-    fn truncate_unlimited() {
-        assert_eq!(truncate("hello world", usize::MAX), "hello world");
+    fn info_json_shape_is_stable() {
+        let info = CacheInfo {
+            cache_dir: PathBuf::from("/home/u/.cache/rfc"),
+            temporary: false,
+            document_count: 2,
+            total_size_bytes: 4096,
+            stats: None,
+        };
+        assert_eq!(
+            serde_json::to_string_pretty(&info).unwrap(),
+            r#"{
+  "cache_dir": "/home/u/.cache/rfc",
+  "document_count": 2,
+  "total_size_bytes": 4096
+}"#
+        );
     }
 }
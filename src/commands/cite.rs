@@ -0,0 +1,45 @@
+//! `rfc --cite`: a reference to an RFC in the text, BibTeX, or RIS style.
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::models::{Citation, CitationStyle, DocumentType, IndexLookup};
+use crate::workflow::check_against_index;
+
+use super::alias::resolve;
+use super::open_optional_cache;
+
+/// Print a citation of `document`. The synced RFC index supplies
+/// everything, offline; without it the Datatracker is asked, and the
+/// citation has no authors.
+pub async fn run(document: &str, style: CitationStyle, offline: bool, quiet: bool) -> Result<()> {
+    let doc_type = resolve(document)?;
+    let DocumentType::Rfc(num) = doc_type else {
+        anyhow::bail!("Only RFCs can be cited; {} is a draft", doc_type);
+    };
+    let index = open_optional_cache().and_then(|cache| cache.load_rfc_index());
+    check_against_index(&doc_type, index.as_ref(), Config::current().force_fetch)?;
+
+    let from_index = index.as_ref().and_then(|index| match index.lookup(num) {
+        IndexLookup::Published(entry) => Some(Citation::from_index_entry(entry)),
+        _ => None,
+    });
+    let citation = match from_index {
+        Some(citation) => citation,
+        None if offline => anyhow::bail!(
+            "{} is not in the local RFC index; run 'rfc sync-index' or drop --offline",
+            doc_type
+        ),
+        None => {
+            let doc = super::datatracker_client()?
+                .get_document(&doc_type.name())
+                .await?;
+            if !quiet {
+                eprintln!("Note: authors are only known from the RFC index; run 'rfc sync-index'");
+            }
+            Citation::from_document(&doc).unwrap_or_else(|| Citation::new(num, doc.title))
+        }
+    };
+    println!("{}", citation.format(style));
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! The note printed when viewing a draft that has expired, been replaced,
+//! or been published as an RFC.
+//!
+//! The facts come from the Datatracker and are kept in the document's
+//! metadata, so the note still shows offline. Looking them up is
+//! best-effort: a slow or unreachable Datatracker never delays viewing by
+//! more than [`LOOKUP_TIMEOUT`].
+
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::api::{DataTrackerClient, DocumentFetcher};
+use crate::cache::{DocumentCache, DraftStatus};
+use crate::models::{DocumentType, DraftState};
+
+/// Longest we wait on the Datatracker before viewing without fresh facts.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Print a note on stderr if draft `doc_type` is stale. A no-op for RFCs.
+pub async fn warn_if_stale(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    datatracker: &DataTrackerClient,
+    offline: bool,
+) {
+    let DocumentType::Draft(name) = doc_type else {
+        return;
+    };
+    let now = Utc::now();
+    let cached = cache.get_metadata(doc_type).and_then(|m| m.draft_status);
+    let status = match cached {
+        Some(status) if offline || status.is_fresh(now) => Some(status),
+        cached if offline => cached,
+        cached => match lookup(name, datatracker).await {
+            Some(fresh) => {
+                if let Err(e) = cache.set_draft_status(doc_type, fresh.clone()) {
+                    eprintln!("Warning: Failed to cache draft status: {}", e);
+                }
+                Some(fresh)
+            }
+            None => cached,
+        },
+    };
+    if let Some(note) = status.and_then(|s| notice(&s, now.date_naive())) {
+        eprintln!("Note: {} {}", name, note);
+    }
+}
+
+/// Ask the Datatracker about `name`, giving up quietly on any failure.
+async fn lookup(name: &str, datatracker: &DataTrackerClient) -> Option<DraftStatus> {
+    let details = tokio::time::timeout(
+        LOOKUP_TIMEOUT,
+        datatracker.get_document_details(unversioned(name)),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    Some(DraftStatus {
+        expires: details.expires,
+        replaced_by: details.replaced_by,
+        rfc_number: details.rfc_number,
+        checked_at: Utc::now(),
+    })
+}
+
+/// The Datatracker knows drafts by name alone, without the revision.
+pub(super) fn unversioned(name: &str) -> &str {
+    match name.rfind('-') {
+        Some(dash) if DocumentFetcher::has_version_suffix(name) => &name[..dash],
+        _ => name,
+    }
+}
+
+/// The [`DraftState`] `status` implies as of `today`, with the same
+/// precedence as [`notice`]. `None` when it says too little.
+pub(super) fn draft_state(status: &DraftStatus, today: NaiveDate) -> Option<DraftState> {
+    if status.rfc_number.is_some() {
+        Some(DraftState::Rfc)
+    } else if !status.replaced_by.is_empty() {
+        Some(DraftState::Replaced)
+    } else {
+        status.expires.map(|expires| {
+            if expires < today {
+                DraftState::Expired
+            } else {
+                DraftState::Active
+            }
+        })
+    }
+}
+
+/// What to say about a draft with `status` as of `today`, if anything.
+/// Publication outranks replacement, which outranks expiry.
+fn notice(status: &DraftStatus, today: NaiveDate) -> Option<String> {
+    if let Some(num) = status.rfc_number {
+        return Some(format!("was published as RFC {} — run `rfc {}`", num, num));
+    }
+    if !status.replaced_by.is_empty() {
+        return Some(format!("was replaced by {}", status.replaced_by.join(", ")));
+    }
+    match status.expires {
+        Some(expires) if expires < today => Some(format!("expired {}", expires.format("%Y-%m-%d"))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> DraftStatus {
+        DraftStatus {
+            expires: NaiveDate::from_ymd_opt(2023, 11, 2),
+            replaced_by: Vec::new(),
+            rfc_number: None,
+            checked_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn active_drafts_get_no_notice() {
+        let today = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        assert_eq!(notice(&status(), today), None);
+        let no_expiry = DraftStatus {
+            expires: None,
+            ..status()
+        };
+        assert_eq!(notice(&no_expiry, today), None);
+    }
+
+    #[test]
+    fn notices_rank_publication_over_replacement_over_expiry() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(
+            notice(&status(), today).as_deref(),
+            Some("expired 2023-11-02")
+        );
+
+        let replaced = DraftStatus {
+            replaced_by: vec!["draft-ietf-foo-bar".to_string()],
+            ..status()
+        };
+        assert_eq!(
+            notice(&replaced, today).as_deref(),
+            Some("was replaced by draft-ietf-foo-bar")
+        );
+
+        let published = DraftStatus {
+            rfc_number: Some(9230),
+            ..replaced
+        };
+        assert_eq!(
+            notice(&published, today).as_deref(),
+            Some("was published as RFC 9230 — run `rfc 9230`")
+        );
+    }
+
+    #[test]
+    fn states_follow_the_notices_precedence() {
+        let before = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        let after = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(draft_state(&status(), before), Some(DraftState::Active));
+        assert_eq!(draft_state(&status(), after), Some(DraftState::Expired));
+        let published = DraftStatus {
+            rfc_number: Some(9230),
+            replaced_by: vec!["draft-ietf-foo-bar".to_string()],
+            ..status()
+        };
+        assert_eq!(draft_state(&published, after), Some(DraftState::Rfc));
+        let unknown = DraftStatus {
+            expires: None,
+            ..status()
+        };
+        assert_eq!(draft_state(&unknown, after), None);
+    }
+
+    #[test]
+    fn revision_is_dropped_for_lookups() {
+        assert_eq!(
+            unversioned("draft-ietf-quic-transport-34"),
+            "draft-ietf-quic-transport"
+        );
+        assert_eq!(
+            unversioned("draft-ietf-quic-transport"),
+            "draft-ietf-quic-transport"
+        );
+        assert_eq!(unversioned("draft-foo-bar-v2"), "draft-foo-bar-v2");
+    }
+}
@@ -0,0 +1,268 @@
+//! `rfc --export-md`: one document as Markdown.
+//!
+//! `rfc cache export-html`: a static copy of the cache for offline
+//! browsing. Pages use only inline CSS and relative links, and the output
+//! depends only on the cache contents (plus `SOURCE_DATE_EPOCH`, if set,
+//! for the timestamp), so an export can be committed or rsynced.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::analysis::rfc_references;
+use crate::cache::{arrange_cached, CacheSort, CachedDocument};
+use crate::models::{DocumentType, Format, SearchFilter};
+use crate::render::to_markdown;
+
+use super::alias::resolve;
+use super::cache::format_size;
+use super::fetch::cached_text;
+use super::open_cache;
+
+const STYLE: &str = "\
+body{font-family:sans-serif;margin:2em auto;max-width:60em;padding:0 1em;color:#222}\
+table{border-collapse:collapse;width:100%}\
+th,td{text-align:left;padding:.25em .75em .25em 0;vertical-align:top}\
+th{border-bottom:1px solid #999}\
+td.size{text-align:right;white-space:nowrap}\
+.tag{background:#eee;border-radius:3px;padding:0 .3em;margin-right:.3em;font-size:.9em}\
+pre{font-size:.9em;line-height:1.25}\
+footer{margin-top:2em;color:#777;font-size:.85em}";
+
+/// Convert `document` to Markdown and write it to `output`, or stdout for
+/// `-`, fetching it first if it isn't cached.
+pub async fn markdown(document: &str, output: &Path, offline: bool, quiet: bool) -> Result<()> {
+    let cache = open_cache()?;
+    let doc_type = resolve(document)?;
+    let markdown = to_markdown(&cached_text(&doc_type, &cache, offline, quiet).await?);
+    if output == Path::new("-") {
+        print!("{}", markdown);
+        return Ok(());
+    }
+    fs::write(output, markdown).with_context(|| format!("Failed to write {}", output.display()))?;
+    if !quiet {
+        eprintln!("Exported {} to {}", doc_type, output.display());
+    }
+    Ok(())
+}
+
+/// Write `index.html` and one page per cached document into `dir`.
+pub fn html(dir: &Path) -> Result<()> {
+    let cache = open_cache()?;
+    let mut docs = cache.list_cached_with_metadata();
+    arrange_cached(&mut docs, SearchFilter::Both, CacheSort::Name, false);
+    // Older caches may hold HTML-only entries; there's no text to render.
+    docs.retain(|doc| doc.formats.contains(&Format::Text));
+
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let known: HashSet<String> = docs.iter().map(|doc| doc.doc_type.name()).collect();
+    for doc in &docs {
+        let Some(text) = cache.get_document(&doc.doc_type, Format::Text) else {
+            continue;
+        };
+        write(
+            dir,
+            &page_name(&doc.doc_type),
+            &render_document(doc, &text, &known),
+        )?;
+    }
+    write(dir, "index.html", &render_index(&docs, generated_at()))?;
+
+    println!(
+        "Exported {} document{} to {}",
+        docs.len(),
+        if docs.len() == 1 { "" } else { "s" },
+        dir.join("index.html").display()
+    );
+    Ok(())
+}
+
+fn write(dir: &Path, name: &str, html: &str) -> Result<()> {
+    let path = dir.join(name);
+    fs::write(&path, html).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The export time: `SOURCE_DATE_EPOCH` when set, for reproducible output.
+fn generated_at() -> DateTime<Utc> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(Utc::now)
+}
+
+fn page_name(doc_type: &DocumentType) -> String {
+    format!("{}.html", doc_type.name())
+}
+
+fn render_index(docs: &[CachedDocument], generated: DateTime<Utc>) -> String {
+    let mut rows = String::new();
+    for doc in docs {
+        let meta = doc.metadata.as_ref();
+        let title = meta.map(|m| m.title.as_str()).unwrap_or_default();
+        let tags: String = meta
+            .map(|m| &m.tags[..])
+            .unwrap_or_default()
+            .iter()
+            .map(|tag| format!("<span class=\"tag\">{}</span>", escape(tag)))
+            .collect();
+        let date = doc
+            .cached_at()
+            .map(|at| at.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td>\
+             <td class=\"size\">{}</td><td>{}</td></tr>\n",
+            page_name(&doc.doc_type),
+            escape(&doc.doc_type.to_string()),
+            escape(title),
+            date,
+            format_size(doc.size),
+            tags
+        ));
+    }
+    page(
+        "Cached documents",
+        &format!(
+            "<h1>Cached documents</h1>\n<table>\n\
+             <tr><th>Document</th><th>Title</th><th>Cached</th><th>Size</th><th>Tags</th></tr>\n\
+             {}</table>\n<footer>{} document{}. Generated {} by rfc {}.</footer>",
+            rows,
+            docs.len(),
+            if docs.len() == 1 { "" } else { "s" },
+            generated.format("%Y-%m-%d %H:%M UTC"),
+            env!("CARGO_PKG_VERSION")
+        ),
+    )
+}
+
+fn render_document(doc: &CachedDocument, text: &str, known: &HashSet<String>) -> String {
+    let title = match doc.metadata.as_ref().map(|m| m.title.as_str()) {
+        Some(title) if !title.is_empty() => format!("{}: {}", doc.doc_type, title),
+        _ => doc.doc_type.to_string(),
+    };
+    page(
+        &title,
+        &format!(
+            "<p><a href=\"index.html\">&larr; All documents</a></p>\n<pre>{}</pre>",
+            link_references(text, known)
+        ),
+    )
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Escape `text` for HTML, turning mentions like `RFC 9000` or `[RFC9000]`
+/// into links when that RFC is among the `known` (exported) documents.
+fn link_references(text: &str, known: &HashSet<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (range, num) in rfc_references(text) {
+        let target = DocumentType::Rfc(num).name();
+        if !known.contains(&target) {
+            continue;
+        }
+        out.push_str(&escape(&text[copied..range.start]));
+        out.push_str(&format!(
+            "<a href=\"{}.html\">{}</a>",
+            target,
+            &text[range.clone()]
+        ));
+        copied = range.end;
+    }
+    out.push_str(&escape(&text[copied..]));
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use chrono::TimeZone;
+
+    fn known(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn links_only_exported_rfcs() {
+        let known = known(&["rfc9000", "rfc793"]);
+        assert_eq!(
+            link_references("See RFC 9000 and [RFC793], not RFC 8200.", &known),
+            "See <a href=\"rfc9000.html\">RFC 9000</a> and \
+             [<a href=\"rfc793.html\">RFC793</a>], not RFC 8200."
+        );
+    }
+
+    #[test]
+    fn links_need_word_boundaries() {
+        let known = known(&["rfc9000"]);
+        assert_eq!(link_references("XRFC 9000", &known), "XRFC 9000");
+        assert_eq!(link_references("RFC 90001", &known), "RFC 90001");
+        assert_eq!(link_references("RFC 9000a", &known), "RFC 9000a");
+        assert_eq!(link_references("RFC", &known), "RFC");
+        assert_eq!(
+            link_references("RFCs like RFC 9000", &known),
+            "RFCs like <a href=\"rfc9000.html\">RFC 9000</a>"
+        );
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            link_references("a < b && \"c\" > RFC 1", &HashSet::new()),
+            "a &lt; b &amp;&amp; &quot;c&quot; &gt; RFC 1"
+        );
+    }
+
+    #[test]
+    fn index_lists_documents_deterministically() {
+        let cached_at = Utc.with_ymd_and_hms(2021, 5, 27, 12, 0, 0).unwrap();
+        let mut meta = CacheMetadata::new("QUIC <Transport>", cached_at);
+        meta.tags = vec!["transport".to_string()];
+        let docs = vec![CachedDocument {
+            doc_type: DocumentType::Rfc(9000),
+            metadata: Some(meta),
+            formats: vec![Format::Text],
+            size: 2048,
+            modified: None,
+            has_notes: false,
+        }];
+        let generated = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 0).unwrap();
+
+        let html = render_index(&docs, generated);
+        assert_eq!(html, render_index(&docs, generated));
+        assert!(html.contains(
+            "<tr><td><a href=\"rfc9000.html\">RFC 9000</a></td><td>QUIC &lt;Transport&gt;</td>\
+             <td>2021-05-27</td><td class=\"size\">2.0 KB</td>\
+             <td><span class=\"tag\">transport</span></td></tr>"
+        ));
+        assert!(html.contains("Generated 2026-01-02 03:04 UTC"));
+        assert!(!html.contains("http"));
+    }
+}
@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::extract::{extract, ArtifactKind};
+
+use super::alias::resolve;
+use super::fetch::cached_text;
+use super::open_cache;
+
+/// Write each `kind` artifact in a document to its own file in `output`
+/// (default: the current directory), fetching the document if needed.
+pub async fn run(
+    document: &str,
+    kind: ArtifactKind,
+    output: Option<&Path>,
+    offline: bool,
+    quiet: bool,
+) -> Result<()> {
+    let cache = open_cache()?;
+    let doc_type = resolve(document)?;
+    let text = cached_text(&doc_type, &cache, offline, quiet).await?;
+
+    let artifacts = extract(&text, kind, &doc_type.name());
+    if artifacts.is_empty() {
+        println!("No {} found in {}", kind.describe(), doc_type);
+        return Ok(());
+    }
+
+    let dir = output.unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for artifact in &artifacts {
+        let path = dir.join(&artifact.name);
+        fs::write(&path, &artifact.content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let lines = artifact.content.lines().count();
+        println!(
+            "Wrote {} ({} line{})",
+            path.display(),
+            lines,
+            if lines == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
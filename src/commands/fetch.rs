@@ -1,20 +1,468 @@
-use anyhow::Result;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::api::{DataTrackerClient, DocumentFetcher};
-use crate::cache::CacheManager;
-use crate::models::DocumentType;
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
 
-use super::fetch_pipeline::fetch_and_cache;
+use crate::api::DocumentNotFound;
+use crate::cache::{CacheCounters, CacheManager};
+use crate::config::Config;
+use crate::models::{DocumentType, Format, IndexLookup};
 
-/// Always-fresh fetch: hit the API, cache the result, do not open.
-pub async fn run(document: &str) -> Result<()> {
-    let doc_type = DocumentType::from_user_input(document);
-    let cache = CacheManager::new()?;
-    let http = crate::api::build_http_client()?;
-    let fetcher = DocumentFetcher::with_client(http.clone());
-    let datatracker = DataTrackerClient::with_client(http);
+use super::alias::resolve;
+use super::open_writable_cache;
+use super::progress::{self, DownloadBar};
+use crate::workflow::{check_against_index, fetch_and_cache, refresh_cached, store_metadata};
 
-    fetch_and_cache(&doc_type, &cache, &fetcher, &datatracker).await?;
-    eprintln!("Cached {}. Use 'rfc {}' to view.", doc_type, doc_type);
+/// Upper bound on concurrent downloads when fetching several documents.
+const BULK_CONCURRENCY: usize = 4;
+
+/// Most RFCs a single `A-B` range may name, so a typo like `9000-90000`
+/// doesn't start thousands of downloads.
+const MAX_RANGE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Fetched,
+    Skipped,
+    Failed,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Fetched => "fetched",
+            Status::Skipped => "skipped",
+            Status::Failed => "failed",
+        }
+    }
+}
+
+/// A document named on the command line or in a reading list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Wanted {
+    doc_type: DocumentType,
+    /// Came from an `A-B` range, so a number that was never issued is
+    /// expected rather than an error.
+    in_range: bool,
+}
+
+/// What `rfc fetch` did with a single document, and where it is now.
+#[derive(Debug, Serialize)]
+struct Stored {
+    document: String,
+    /// Whether it was downloaded, rather than found already cached.
+    fetched: bool,
+    /// Whether the download replaced an existing cache entry.
+    overwritten: bool,
+    /// `txt` or `html`.
+    format: &'static str,
+    path: PathBuf,
+    /// Size of the cached file, compressed if the cache compresses.
+    bytes: u64,
+}
+
+impl std::fmt::Display for Stored {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (document, path) = (self.document_type(), self.path.display());
+        let what = format!("{}, {} bytes", self.format, self.bytes);
+        match (self.fetched, self.overwritten) {
+            (false, _) => write!(
+                f,
+                "{} already cached (use -r to refresh): {} ({})",
+                document, path, what
+            ),
+            (true, false) => write!(f, "Cached {} at {} ({})", document, path, what),
+            (true, true) => write!(f, "Replaced cached {} at {} ({})", document, path, what),
+        }
+    }
+}
+
+impl Stored {
+    /// Where `doc_type` is cached, or `None` if it isn't.
+    fn find(
+        doc_type: &DocumentType,
+        cache: &CacheManager,
+        fetched: bool,
+        overwritten: bool,
+    ) -> Option<Self> {
+        let (format, path) = [Format::Text, Format::Html]
+            .into_iter()
+            .find_map(|format| Some((format, cache.document_file(doc_type, format)?)))?;
+        Some(Stored {
+            document: doc_type.name(),
+            fetched,
+            overwritten,
+            format: format.extension(),
+            bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path,
+        })
+    }
+
+    fn document_type(&self) -> DocumentType {
+        DocumentType::from_canonical_name(&self.document)
+    }
+}
+
+/// Fetch documents into the cache without opening them. Documents already
+/// cached are left alone unless `refresh`. Arguments may be RFC ranges
+/// like `9000-9010`; anything more than one plain document is fetched
+/// concurrently. A single document is reported with its cache path and
+/// size, as JSON if `json`.
+pub async fn run(documents: &[String], refresh: bool, json: bool, quiet: bool) -> Result<()> {
+    let mut wanted = Vec::new();
+    for document in documents {
+        wanted.extend(expand(document)?);
+    }
+    let wanted = dedupe(wanted);
+    let cache = open_writable_cache()?;
+
+    if let [only] = wanted.as_slice() {
+        if !only.in_range {
+            let stored = fetch_one(&only.doc_type, &cache, refresh, quiet).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stored)?);
+            } else {
+                println!("{}", stored);
+                if stored.fetched {
+                    eprintln!("Use 'rfc {}' to view.", only.doc_type);
+                }
+            }
+            return Ok(());
+        }
+    }
+    if json {
+        anyhow::bail!("--json reports on a single document; name just one");
+    }
+    fetch_all(&cache, wanted, 0, refresh, quiet).await
+}
+
+/// Fetch `doc_type` into `cache` unless it's there already and not
+/// `refresh`, and say where it ended up.
+async fn fetch_one(
+    doc_type: &DocumentType,
+    cache: &CacheManager,
+    refresh: bool,
+    quiet: bool,
+) -> Result<Stored> {
+    let cached = Stored::find(doc_type, cache, false, false);
+    let overwritten = cached.is_some();
+    if let Some(cached) = cached.filter(|_| !refresh) {
+        return Ok(cached);
+    }
+    fetch_into(doc_type, cache, quiet).await?;
+    Stored::find(doc_type, cache, true, overwritten)
+        .with_context(|| format!("{} was fetched but isn't in the cache", doc_type))
+}
+
+/// `doc_type`'s cached text, fetching it first if it isn't cached.
+pub(super) async fn cached_text(
+    doc_type: &DocumentType,
+    cache: &CacheManager,
+    offline: bool,
+    quiet: bool,
+) -> Result<String> {
+    if let Some(text) = cache.get_document(doc_type, Format::Text) {
+        cache.record_counters(CacheCounters::hit(text.len()));
+        return Ok(text);
+    }
+    if offline {
+        anyhow::bail!("{} is not cached and --offline was given", doc_type);
+    }
+    cache.record_counters(CacheCounters::miss());
+    fetch_into(doc_type, cache, quiet).await?;
+    cache
+        .get_document(doc_type, Format::Text)
+        .with_context(|| format!("{} is not cached", doc_type))
+}
+
+/// Fetch `doc_type` into `cache` with a progress bar, after checking it
+/// against the RFC index.
+pub(super) async fn fetch_into(
+    doc_type: &DocumentType,
+    cache: &CacheManager,
+    quiet: bool,
+) -> Result<()> {
+    check_against_index(
+        doc_type,
+        cache.load_rfc_index().as_ref(),
+        Config::current().force_fetch,
+    )?;
+
+    let fetcher = super::document_fetcher()?
+        .with_observer(Arc::new(DownloadBar::new(progress::enabled(quiet))));
+    let datatracker = fetcher.datatracker();
+
+    fetch_and_cache(doc_type, cache, &fetcher, &datatracker).await?;
     Ok(())
 }
+
+/// Fetch every document named in `list` (a file, or `-` for stdin), one
+/// per line. Documents already cached are skipped unless `refresh`.
+/// Fails if any document couldn't be fetched.
+pub async fn from_list(list: &Path, refresh: bool, quiet: bool) -> Result<()> {
+    let text = read_list(list)?;
+    let cache = open_writable_cache()?;
+
+    let mut wanted = Vec::new();
+    let mut invalid = 0;
+    for (line, entry) in parse_list(&text) {
+        match expand(entry) {
+            Ok(docs) => wanted.extend(docs),
+            Err(e) => {
+                invalid += 1;
+                println!("{:<8} {} (line {}): {:#}", "failed", entry, line, e);
+            }
+        }
+    }
+    if wanted.is_empty() && invalid == 0 {
+        println!("No documents listed in {}", list.display());
+        return Ok(());
+    }
+    fetch_all(&cache, dedupe(wanted), invalid, refresh, quiet).await
+}
+
+/// Fetch `wanted` concurrently, printing a status line per document and
+/// a summary. `failed` counts entries that were rejected before getting
+/// here. Fails if any document failed.
+async fn fetch_all(
+    cache: &CacheManager,
+    wanted: Vec<Wanted>,
+    failed: usize,
+    refresh: bool,
+    quiet: bool,
+) -> Result<()> {
+    let fetcher = super::document_fetcher()?;
+    let datatracker = fetcher.datatracker();
+    let index = cache.load_rfc_index();
+
+    let mut counts = [0, 0, failed];
+    let bar = progress::bulk_bar(wanted.len() as u64, progress::enabled(quiet));
+    let mut results = stream::iter(wanted)
+        .map(|Wanted { doc_type, in_range }| {
+            let (fetcher, datatracker, index, bar) = (&fetcher, &datatracker, &index, &bar);
+            async move {
+                if let (true, DocumentType::Rfc(num), Some(index)) =
+                    (in_range, &doc_type, index.as_ref())
+                {
+                    if index.lookup(*num) == IndexLookup::NotIssued {
+                        return (doc_type, Status::Skipped, Some("never issued".to_string()));
+                    }
+                }
+                let cached = cache.contains(&doc_type, Format::Text)
+                    || cache.contains(&doc_type, Format::Html);
+                if cached && !refresh {
+                    return (doc_type, Status::Skipped, None);
+                }
+                bar.set_message(doc_type.to_string());
+                let fetched = match check_against_index(
+                    &doc_type,
+                    index.as_ref(),
+                    Config::current().force_fetch,
+                ) {
+                    Ok(()) => refresh_cached(&doc_type, cache, fetcher, None).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = fetched {
+                    // Without a synced index, the hosts' 404s are how a
+                    // range finds its unissued numbers.
+                    if in_range && e.downcast_ref::<DocumentNotFound>().is_some() {
+                        return (doc_type, Status::Skipped, Some("not found".to_string()));
+                    }
+                    return (doc_type, Status::Failed, Some(format!("{:#}", e)));
+                }
+                if let Err(e) = store_metadata(&doc_type, cache, datatracker).await {
+                    bar.suspend(|| {
+                        eprintln!("Warning: Failed to fetch metadata for {}: {}", doc_type, e)
+                    });
+                }
+                (doc_type, Status::Fetched, None)
+            }
+        })
+        .buffer_unordered(BULK_CONCURRENCY);
+
+    while let Some((doc_type, status, detail)) = results.next().await {
+        counts[status as usize] += 1;
+        bar.suspend(|| match detail {
+            Some(detail) => println!("{:<8} {}: {}", status.label(), doc_type, detail),
+            None => println!("{:<8} {}", status.label(), doc_type),
+        });
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let [fetched, skipped, failed] = counts;
+    println!(
+        "\n{} fetched, {} skipped, {} failed",
+        fetched, skipped, failed
+    );
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} documents failed",
+            failed,
+            fetched + skipped + failed
+        );
+    }
+    Ok(())
+}
+
+/// The documents `input` names: every RFC in a range like `9000-9010`,
+/// or else the single document (or alias) it resolves to.
+fn expand(input: &str) -> Result<Vec<Wanted>> {
+    match parse_range(input)? {
+        Some(range) => Ok(range
+            .map(|num| Wanted {
+                doc_type: DocumentType::Rfc(num),
+                in_range: true,
+            })
+            .collect()),
+        None => Ok(vec![Wanted {
+            doc_type: resolve(input)?,
+            in_range: false,
+        }]),
+    }
+}
+
+/// Parse an inclusive range of RFC numbers. `None` if `input` isn't two
+/// numbers joined by `-` (draft names contain dashes too, but never
+/// consist only of digits on both sides).
+fn parse_range(input: &str) -> Result<Option<RangeInclusive<u32>>> {
+    let Some((start, end)) = input.split_once('-') else {
+        return Ok(None);
+    };
+    let (Ok(start), Ok(end)) = (start.trim().parse::<u32>(), end.trim().parse::<u32>()) else {
+        return Ok(None);
+    };
+    if start == 0 || start > end {
+        anyhow::bail!("Invalid range {}: expected e.g. 9000-9010", input);
+    }
+    if end - start >= MAX_RANGE {
+        anyhow::bail!(
+            "Range {} names {} RFCs; at most {} can be fetched at once",
+            input,
+            end - start + 1,
+            MAX_RANGE
+        );
+    }
+    Ok(Some(start..=end))
+}
+
+/// Drop repeats (keeping the first), noting how many there were.
+fn dedupe(wanted: Vec<Wanted>) -> Vec<Wanted> {
+    let mut unique: Vec<Wanted> = Vec::with_capacity(wanted.len());
+    let mut duplicates = 0;
+    for w in wanted {
+        if unique.iter().any(|u| u.doc_type == w.doc_type) {
+            duplicates += 1;
+        } else {
+            unique.push(w);
+        }
+    }
+    if duplicates > 0 {
+        eprintln!(
+            "Ignoring {} duplicate entr{}",
+            duplicates,
+            if duplicates == 1 { "y" } else { "ies" }
+        );
+    }
+    unique
+}
+
+fn read_list(list: &Path) -> Result<String> {
+    if list == Path::new("-") {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .context("Failed to read document list from stdin")?;
+        return Ok(text);
+    }
+    fs::read_to_string(list).with_context(|| format!("Failed to read {}", list.display()))
+}
+
+/// The entries of a reading list with their 1-based line numbers.
+/// Anything after `#` is a comment; blank lines are skipped.
+fn parse_list(text: &str) -> Vec<(usize, &str)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            (!entry.is_empty()).then_some((i + 1, entry))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(fetched: bool, overwritten: bool) -> Stored {
+        Stored {
+            document: "rfc9000".to_string(),
+            fetched,
+            overwritten,
+            format: "txt",
+            path: PathBuf::from("/cache/rfc9000.txt"),
+            bytes: 123,
+        }
+    }
+
+    #[test]
+    fn stored_documents_say_what_happened() {
+        assert_eq!(
+            stored(true, false).to_string(),
+            "Cached RFC 9000 at /cache/rfc9000.txt (txt, 123 bytes)"
+        );
+        assert_eq!(
+            stored(true, true).to_string(),
+            "Replaced cached RFC 9000 at /cache/rfc9000.txt (txt, 123 bytes)"
+        );
+        assert_eq!(
+            stored(false, false).to_string(),
+            "RFC 9000 already cached (use -r to refresh): /cache/rfc9000.txt (txt, 123 bytes)"
+        );
+    }
+
+    #[test]
+    fn parse_list_skips_comments_and_blanks() {
+        let text = "# team reading list\n9000\n\n  rfc4271   # BGP\n#8200\ndraft-ietf-foo-01\n";
+        assert_eq!(
+            parse_list(text),
+            vec![(2, "9000"), (4, "rfc4271"), (6, "draft-ietf-foo-01")]
+        );
+        assert!(parse_list("\n  \n# nothing\n").is_empty());
+    }
+
+    #[test]
+    fn parse_range_bounds() {
+        assert_eq!(parse_range("9000-9010").unwrap(), Some(9000..=9010));
+        assert_eq!(parse_range("791-791").unwrap(), Some(791..=791));
+        assert_eq!(parse_range("1-100").unwrap(), Some(1..=100));
+        assert!(parse_range("1-101").is_err());
+        assert!(parse_range("9010-9000").is_err());
+        assert!(parse_range("0-5").is_err());
+    }
+
+    #[test]
+    fn parse_range_leaves_other_names_alone() {
+        assert_eq!(parse_range("9000").unwrap(), None);
+        assert_eq!(parse_range("draft-ietf-quic-transport-34").unwrap(), None);
+        assert_eq!(parse_range("rfc9000-9010").unwrap(), None);
+    }
+
+    #[test]
+    fn expand_composes_with_dedupe() {
+        let mut wanted = Vec::new();
+        for arg in ["791", "9000-9002", "rfc9001"] {
+            wanted.extend(expand(arg).unwrap());
+        }
+        let docs: Vec<_> = dedupe(wanted).into_iter().map(|w| w.doc_type).collect();
+        assert_eq!(
+            docs,
+            [791, 9000, 9001, 9002].map(DocumentType::Rfc).to_vec()
+        );
+    }
+}
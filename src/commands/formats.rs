@@ -0,0 +1,63 @@
+//! `rfc --formats`: which renderings of a document are published, found
+//! with HEAD requests and remembered in its metadata for a day.
+
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::api::{Availability, Rendering};
+use crate::cache::{DocumentCache, FormatAvailability};
+use crate::config::Config;
+
+use super::links::identify;
+use super::open_cache;
+
+/// Print each rendering's availability and URL, then the order `fetch`
+/// tries them in.
+pub async fn run(document: &str, offline: bool) -> Result<()> {
+    let doc_type = identify(document)?;
+    let cache = open_cache()?;
+    let now = Utc::now();
+    let cached = cache.get_metadata(&doc_type).and_then(|m| m.formats);
+    let found = match cached {
+        Some(found) if offline || found.is_fresh(now) => found,
+        _ if offline => anyhow::bail!(
+            "{}'s formats haven't been probed and --offline was given",
+            doc_type
+        ),
+        _ => {
+            let found = FormatAvailability {
+                probes: super::document_fetcher()?.probe_formats(&doc_type).await,
+                checked_at: now,
+            };
+            if let Err(e) = cache.set_formats(&doc_type, found.clone()) {
+                eprintln!("Warning: Failed to cache format availability: {}", e);
+            }
+            found
+        }
+    };
+
+    println!("{}", doc_type);
+    for probe in &found.probes {
+        let availability = match probe.availability {
+            Availability::Available => "available",
+            Availability::Missing => "missing",
+            Availability::Unknown => "unknown",
+        };
+        println!(
+            "  {:<5} {:<10} {}",
+            probe.rendering, availability, probe.url
+        );
+    }
+    let order: Vec<&str> = Config::current()
+        .format_preference()
+        .fetch_order()
+        .into_iter()
+        .map(|format| Rendering::from(format).name())
+        .collect();
+    println!(
+        "Checked {}; fetch tries {}",
+        found.checked_at.format("%Y-%m-%d %H:%M UTC"),
+        order.join(", ")
+    );
+    Ok(())
+}
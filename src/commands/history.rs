@@ -0,0 +1,125 @@
+//! `rfc history`: a document's Datatracker timeline.
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::models::{DocEvent, DocumentType};
+use crate::render::TimeStyle;
+
+use super::alias::resolve;
+use super::draft_status::unversioned;
+use super::terminal;
+
+/// Print up to `limit` of `document`'s events, newest first.
+pub async fn run(document: &str, limit: usize, json: bool, offline: bool) -> Result<()> {
+    if offline {
+        anyhow::bail!("Document history comes from the Datatracker; drop --offline");
+    }
+    let doc_type = resolve(document)?;
+    let name = match &doc_type {
+        DocumentType::Rfc(_) => doc_type.name(),
+        DocumentType::Draft(name) => unversioned(name).to_string(),
+    };
+    let events = super::datatracker_client()?
+        .document_events(&name, limit)
+        .await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+    if events.is_empty() {
+        println!("No events recorded for {}", doc_type);
+        return Ok(());
+    }
+    println!("{}\n", doc_type);
+    for line in render(&events, Config::current().time_style, terminal::width()) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// One `when  type  rev  description` line per event, with the columns
+/// aligned and descriptions cut to fit `width`.
+fn render(events: &[DocEvent], style: TimeStyle, width: usize) -> Vec<String> {
+    let times: Vec<String> = events.iter().map(|e| style.format(e.time)).collect();
+    let time_width = times.iter().map(String::len).max().unwrap_or(0);
+    let type_width = events.iter().map(|e| e.event_type.len()).max().unwrap_or(0);
+    let rev_width = events
+        .iter()
+        .filter_map(|e| e.rev.as_ref().map(String::len))
+        .max()
+        .unwrap_or(0);
+    events
+        .iter()
+        .zip(&times)
+        .map(|(event, time)| {
+            let mut line = format!("{:<time_width$}  {:<type_width$}", time, event.event_type);
+            if rev_width > 0 {
+                line.push_str(&format!(
+                    "  {:<rev_width$}",
+                    event.rev.as_deref().unwrap_or("")
+                ));
+            }
+            line.push_str("  ");
+            // Leave the last column free so full lines don't wrap.
+            let room = width.saturating_sub(line.chars().count() + 1);
+            line.push_str(&terminal::excerpt(&event.description, room.max(20)));
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn event(day: u32, event_type: &str, rev: Option<&str>, description: &str) -> DocEvent {
+        DocEvent {
+            time: Utc.with_ymd_and_hms(2021, 5, day, 12, 0, 0).unwrap(),
+            event_type: event_type.to_string(),
+            description: description.to_string(),
+            rev: rev.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn columns_are_aligned() {
+        let events = [
+            event(27, "published_rfc", None, "RFC published"),
+            event(3, "new_revision", Some("34"), "New version available"),
+        ];
+        assert_eq!(
+            render(&events, TimeStyle::Absolute, 80),
+            [
+                "2021-05-27  published_rfc      RFC published",
+                "2021-05-03  new_revision   34  New version available",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_descriptions_are_cut() {
+        let events = [event(1, "changed_state", None, &"word ".repeat(40))];
+        let lines = render(&events, TimeStyle::Absolute, 60);
+        assert!(lines[0].chars().count() <= 60, "{}", lines[0]);
+    }
+
+    #[test]
+    fn recent_times_are_relative_and_padded() {
+        let mut recent = event(1, "new_revision", None, "New version available");
+        recent.time = Utc::now() - chrono::Duration::days(2);
+        let events = [
+            recent,
+            event(3, "new_revision", None, "New version available"),
+        ];
+        let lines = render(&events, TimeStyle::Relative, 80);
+        assert_eq!(lines[0], "2 days ago  new_revision  New version available");
+        assert!(
+            lines[1].starts_with("2021-05-03  new_revision"),
+            "{}",
+            lines[1]
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::api::{parse_rfc_index, RfcIndexDownload};
+
+use super::open_writable_cache;
+
+/// Download (or conditionally refresh) the RFC Editor index into the cache.
+pub async fn sync() -> Result<()> {
+    let cache = open_writable_cache()?;
+    let fetcher = super::document_fetcher()?;
+    let existing = cache.load_rfc_index();
+    let since = existing.as_ref().and_then(|i| i.last_modified.as_deref());
+
+    eprintln!("Syncing RFC index...");
+
+    match fetcher.fetch_rfc_index(since).await? {
+        RfcIndexDownload::NotModified => {
+            // `since` is only sent when an index exists, so a 304 without
+            // one means the server is misbehaving.
+            let Some(mut index) = existing else {
+                anyhow::bail!("Server reported the index unchanged, but none is cached");
+            };
+            index.synced_at = Some(Utc::now());
+            cache.store_rfc_index(&index)?;
+            println!("RFC index already up to date ({} RFCs)", index.len());
+        }
+        RfcIndexDownload::Updated { xml, last_modified } => {
+            let mut index = parse_rfc_index(&xml)?;
+            index.synced_at = Some(Utc::now());
+            index.last_modified = last_modified;
+            cache.store_rfc_index(&index)?;
+            match index.highest() {
+                Some(highest) => println!(
+                    "RFC index updated: {} RFCs (highest is RFC {})",
+                    index.len(),
+                    highest
+                ),
+                None => println!("RFC index updated: no entries found"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::models::{DocumentDetails, DocumentType, IndexLookup, RfcIndexEntry};
+
+use super::alias::resolve;
+use super::open_optional_cache;
+use super::terminal;
+use crate::workflow::check_against_index;
+
+/// Indent for the wrapped abstract body.
+const ABSTRACT_INDENT: usize = 2;
+
+/// Print a document's metadata without fetching its content.
+///
+/// RFCs are described from the synced RFC index when one is available, so
+/// title/status/date work offline; the abstract still comes from the
+/// Datatracker unless `offline` is set. A cached document's recorded
+/// title and abstract stand in for what the Datatracker would say when
+/// it isn't asked, or can't be reached.
+pub async fn run(document: &str, offline: bool) -> Result<()> {
+    let doc_type = resolve(document)?;
+    let cache = open_optional_cache();
+    let index = cache.as_ref().and_then(|c| c.load_rfc_index());
+    let cached = cache.as_ref().and_then(|c| c.get_metadata(&doc_type));
+    check_against_index(&doc_type, index.as_ref(), Config::current().force_fetch)?;
+
+    let entry = match (&doc_type, &index) {
+        (DocumentType::Rfc(num), Some(index)) => match index.lookup(*num) {
+            IndexLookup::Published(entry) => Some(entry),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    if offline && entry.is_none() && cached.is_none() {
+        anyhow::bail!(
+            "{} is not in the local RFC index; run 'rfc sync-index' or drop --offline",
+            doc_type
+        );
+    }
+
+    let remote = if offline {
+        None
+    } else {
+        let client = super::datatracker_client()?;
+        match client.get_document_details(&doc_type.name()).await {
+            Ok(doc) => Some(doc),
+            // The index or the cache already answered the important
+            // questions.
+            Err(e) if entry.is_some() || cached.is_some() => {
+                eprintln!("Warning: Failed to fetch abstract: {}", e);
+                None
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    println!("{}", doc_type);
+    match entry {
+        Some(entry) => print_index_entry(entry),
+        None => {
+            if let Some(doc) = remote.as_ref().map(|d| &d.document) {
+                field("Title", &doc.title);
+                if let Some(published) = doc.published {
+                    field("Published", &published.format("%Y-%m-%d").to_string());
+                }
+            } else if let Some(meta) = cached.as_ref().filter(|m| !m.title.trim().is_empty()) {
+                field("Title", &meta.title);
+            }
+        }
+    }
+    if let Some(details) = &remote {
+        print_details(details);
+    }
+    field("URL", &doc_type.datatracker_url());
+    if let Some(source) = cached.as_ref().and_then(|meta| meta.source.as_ref()) {
+        field("Cached from", &source.source_url);
+        field(
+            "Retrieved",
+            &source.retrieved_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+        );
+    }
+
+    let abstract_text = match &remote {
+        Some(details) => details.document.abstract_text.as_deref(),
+        None => cached.as_ref().and_then(|meta| meta.recorded_abstract()),
+    };
+    if let Some(abstract_text) = abstract_text {
+        println!("\nAbstract:\n");
+        println!(
+            "{}",
+            terminal::wrap(abstract_text, terminal::width(), ABSTRACT_INDENT)
+        );
+    }
+
+    Ok(())
+}
+
+fn print_index_entry(entry: &RfcIndexEntry) {
+    field("Title", &entry.title);
+    if let Some(status) = &entry.status {
+        field("Status", status);
+    }
+    if let Some(published) = entry.published {
+        field("Published", &published.format("%B %Y").to_string());
+    }
+    if !entry.authors.is_empty() {
+        field("Authors", &entry.authors.join(", "));
+    }
+    if let Some(wg) = &entry.wg {
+        field("WG", wg);
+    }
+    if let Some(pages) = entry.page_count {
+        field("Pages", &pages.to_string());
+    }
+    for (label, numbers) in [
+        ("Obsoletes", &entry.obsoletes),
+        ("Obsoleted by", &entry.obsoleted_by),
+        ("Updates", &entry.updates),
+        ("Updated by", &entry.updated_by),
+    ] {
+        if !numbers.is_empty() {
+            let list: Vec<String> = numbers
+                .iter()
+                .map(|n| DocumentType::Rfc(*n).to_string())
+                .collect();
+            field(label, &list.join(", "));
+        }
+    }
+}
+
+/// The Datatracker's lifecycle facts; only drafts have most of them.
+fn print_details(details: &DocumentDetails) {
+    if let Some(rev) = &details.rev {
+        field("Revision", rev);
+    }
+    if let Some(state) = &details.state {
+        field("State", state);
+    }
+    if let Some(state) = &details.wg_state {
+        field("WG state", state);
+    }
+    if let Some(state) = &details.iesg_state {
+        field("IESG state", state);
+    }
+    if let Some(expires) = details.expires.filter(|_| details.is_active()) {
+        field("Expires", &expires.format("%Y-%m-%d").to_string());
+    }
+    let is_draft = matches!(details.document.doc_type, DocumentType::Draft(_));
+    if let Some(num) = details.rfc_number.filter(|_| is_draft) {
+        field("Published as", &DocumentType::Rfc(num).to_string());
+    }
+    if !details.replaced_by.is_empty() {
+        field("Replaced by", &details.replaced_by.join(", "));
+    }
+}
+
+/// Print one `Label:  value` line with the values aligned.
+fn field(label: &str, value: &str) {
+    println!("{:<14}{}", format!("{}:", label), value);
+}
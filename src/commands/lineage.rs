@@ -0,0 +1,138 @@
+//! `rfc --lineage`: the drafts an RFC came from, or the RFC a draft
+//! became, with the date of each step. Remembered in the document's
+//! metadata; see [`CachedLineage::is_fresh`].
+
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::cache::{CachedLineage, DocumentCache};
+use crate::models::{display_width, draft_base_and_rev, pad_to_width, DocumentType, Lineage};
+
+use super::links::identify;
+use super::open_cache;
+
+/// Print the lineage of `document`, as JSON if `json`.
+pub async fn run(document: &str, json: bool, offline: bool) -> Result<()> {
+    let doc_type = match identify(document)? {
+        DocumentType::Draft(name) => DocumentType::Draft(draft_base_and_rev(&name).0.to_string()),
+        rfc => rfc,
+    };
+    let cache = open_cache()?;
+    let now = Utc::now();
+    let cached = cache.get_metadata(&doc_type).and_then(|m| m.lineage);
+    let found = match cached {
+        Some(found) if offline || found.is_fresh(now) => found,
+        _ if offline => anyhow::bail!(
+            "{}'s lineage hasn't been looked up and --offline was given",
+            doc_type
+        ),
+        _ => {
+            let found = CachedLineage {
+                lineage: super::datatracker_client()?
+                    .lineage(&doc_type.name())
+                    .await?,
+                checked_at: now,
+            };
+            if let Err(e) = cache.set_lineage(&doc_type, found.clone()) {
+                eprintln!("Warning: Failed to cache lineage: {}", e);
+            }
+            found
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&found.lineage)?);
+    } else {
+        print!("{}", render(&found.lineage));
+    }
+    Ok(())
+}
+
+/// One line per document, with the relationship to the next indented
+/// between them:
+///
+/// ```text
+/// draft-ietf-quic-transport-34  2021-01-14  QUIC: A UDP-Based ...
+///   published as
+/// RFC 9000                      2021-05-27  QUIC: A UDP-Based ...
+/// ```
+fn render(lineage: &Lineage) -> String {
+    let labels: Vec<String> = lineage.hops.iter().map(|hop| hop.label()).collect();
+    let width = labels.iter().map(|l| display_width(l)).max().unwrap_or(0);
+    let mut out = String::new();
+    for (hop, label) in lineage.hops.iter().zip(&labels) {
+        let date = hop
+            .date
+            .map_or_else(|| "?".to_string(), |d| d.format("%Y-%m-%d").to_string());
+        out.push_str(&format!(
+            "{}  {:<10}  {}\n",
+            pad_to_width(label, width),
+            date,
+            hop.title
+        ));
+        if let Some(link) = hop.next {
+            out.push_str(&format!("  {}\n", link.label()));
+        }
+    }
+    if lineage.hops.len() == 1 {
+        out.push_str("(no earlier drafts or later RFC found)\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::models::{LineageHop, LineageLink};
+
+    fn hop(name: &str, rev: Option<&str>, date: (i32, u32, u32)) -> LineageHop {
+        LineageHop {
+            name: name.to_string(),
+            title: "QUIC".to_string(),
+            rev: rev.map(str::to_string),
+            date: NaiveDate::from_ymd_opt(date.0, date.1, date.2),
+            next: None,
+        }
+    }
+
+    #[test]
+    fn render_lines_up_each_hop() {
+        let lineage = Lineage {
+            hops: vec![
+                LineageHop {
+                    next: Some(LineageLink::ReplacedBy),
+                    ..hop(
+                        "draft-hamilton-quic-transport-protocol",
+                        Some("01"),
+                        (2016, 7, 8),
+                    )
+                },
+                LineageHop {
+                    next: Some(LineageLink::PublishedAs),
+                    ..hop("draft-ietf-quic-transport", Some("34"), (2021, 1, 14))
+                },
+                hop("rfc9000", None, (2021, 5, 27)),
+            ],
+        };
+        assert_eq!(
+            render(&lineage),
+            "\
+draft-hamilton-quic-transport-protocol-01  2016-07-08  QUIC
+  replaced by
+draft-ietf-quic-transport-34               2021-01-14  QUIC
+  published as
+RFC 9000                                   2021-05-27  QUIC
+"
+        );
+
+        let alone = Lineage {
+            hops: vec![hop("draft-foo-bar", Some("00"), (2024, 2, 1))],
+        };
+        assert_eq!(
+            render(&alone),
+            "draft-foo-bar-00  2024-02-01  QUIC\n(no earlier drafts or later RFC found)\n"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! `rfc --url` and `rfc --browser`: where a document lives online. Neither
+//! touches the network, so both work offline.
+
+use anyhow::{Context, Result};
+
+use crate::api::{DocumentFetcher, RFC_EDITOR_BASE_URL};
+use crate::config::Config;
+use crate::models::DocumentType;
+
+use super::alias::resolve;
+use super::open_optional_cache;
+use crate::workflow::check_against_index;
+
+/// Print the document's URLs (`print`) and/or open its HTML rendering in
+/// the system browser (`open`).
+pub fn run(document: &str, print: bool, open: bool) -> Result<()> {
+    let doc_type = identify(document)?;
+    let fetcher = super::document_fetcher()?;
+
+    if print {
+        for (label, url) in urls(&doc_type, &fetcher) {
+            println!("{:<12} {}", label, url);
+        }
+    }
+    if open {
+        let url = fetcher.html_url(&doc_type);
+        eprintln!("Opening {} in browser...", doc_type);
+        opener::open(&url).with_context(|| format!("Failed to open URL: {}", url))?;
+    }
+    Ok(())
+}
+
+/// Resolve `document` and make sure it names something that could exist:
+/// a well-formed draft name, or an RFC the synced index doesn't rule out.
+pub(super) fn identify(document: &str) -> Result<DocumentType> {
+    let doc_type = resolve(document)?;
+    match &doc_type {
+        DocumentType::Rfc(0) => anyhow::bail!("There is no RFC 0"),
+        DocumentType::Rfc(_) => {
+            let index = open_optional_cache().and_then(|cache| cache.load_rfc_index());
+            check_against_index(&doc_type, index.as_ref(), Config::current().force_fetch)?;
+        }
+        DocumentType::Draft(name) => {
+            if !is_draft_name(name) {
+                anyhow::bail!("'{}' is not an RFC number, draft name, or alias", document);
+            }
+        }
+    }
+    Ok(doc_type)
+}
+
+fn is_draft_name(name: &str) -> bool {
+    name.strip_prefix("draft-").is_some_and(|rest| {
+        !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    })
+}
+
+/// Labelled URLs for `doc_type`. Drafts only get a text URL when pinned to
+/// a revision, since finding the latest one would need the network.
+fn urls(doc_type: &DocumentType, fetcher: &DocumentFetcher) -> Vec<(&'static str, String)> {
+    let mut urls = vec![
+        ("datatracker", doc_type.datatracker_url()),
+        ("html", fetcher.html_url(doc_type)),
+    ];
+    match doc_type {
+        DocumentType::Rfc(num) => {
+            urls.push(("txt", fetcher.text_url(doc_type)));
+            urls.push((
+                "pdf",
+                format!("{}/rfc/pdfrfc/rfc{}.txt.pdf", RFC_EDITOR_BASE_URL, num),
+            ));
+        }
+        DocumentType::Draft(name) if DocumentFetcher::has_version_suffix(name) => {
+            urls.push(("txt", fetcher.text_url(doc_type)));
+        }
+        DocumentType::Draft(_) => {}
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_urls() {
+        let fetcher = DocumentFetcher::new().unwrap();
+        assert_eq!(
+            urls(&DocumentType::Rfc(9000), &fetcher),
+            vec![
+                (
+                    "datatracker",
+                    "https://datatracker.ietf.org/doc/rfc9000/".to_string()
+                ),
+                (
+                    "html",
+                    "https://www.rfc-editor.org/rfc/rfc9000.html".to_string()
+                ),
+                (
+                    "txt",
+                    "https://www.rfc-editor.org/rfc/rfc9000.txt".to_string()
+                ),
+                (
+                    "pdf",
+                    "https://www.rfc-editor.org/rfc/pdfrfc/rfc9000.txt.pdf".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn draft_text_url_needs_a_revision() {
+        let fetcher = DocumentFetcher::new().unwrap();
+        let latest = urls(
+            &DocumentType::Draft("draft-ietf-quic-transport".into()),
+            &fetcher,
+        );
+        assert_eq!(
+            latest,
+            vec![
+                (
+                    "datatracker",
+                    "https://datatracker.ietf.org/doc/draft-ietf-quic-transport/".to_string()
+                ),
+                (
+                    "html",
+                    "https://datatracker.ietf.org/doc/html/draft-ietf-quic-transport".to_string()
+                ),
+            ]
+        );
+        let pinned = urls(
+            &DocumentType::Draft("draft-ietf-quic-transport-34".into()),
+            &fetcher,
+        );
+        assert_eq!(
+            pinned.last().unwrap().1,
+            "https://www.ietf.org/archive/id/draft-ietf-quic-transport-34.txt"
+        );
+    }
+
+    #[test]
+    fn draft_names_are_checked() {
+        assert!(is_draft_name("draft-ietf-quic-transport-34"));
+        assert!(!is_draft_name("draft-"));
+        assert!(!is_draft_name("draft-two words"));
+        assert!(!is_draft_name("draft-a/b"));
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+
+use crate::cache::{Bookmark, DocumentCache};
+
+use super::alias::resolve;
+use super::fetch::cached_text;
+use super::open_writable_cache;
+
+/// Bookmark a line of a document so the next `rfc DOC` resumes there.
+/// `position` is a line number or text to search for; the document is
+/// fetched first if it isn't cached.
+pub async fn run(document: &str, position: &str, offline: bool, quiet: bool) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let doc_type = resolve(document)?;
+    let text = cached_text(&doc_type, &cache, offline, quiet).await?;
+
+    let line = locate(&text, position)?;
+    cache.set_bookmark(
+        &doc_type,
+        Some(Bookmark {
+            line,
+            lines: text.lines().count(),
+        }),
+    )?;
+    println!("Marked {} at line {}", doc_type, line);
+    Ok(())
+}
+
+/// Resolve `position` to a 1-based line of `text`: a line number within
+/// the document, or else the first line containing it.
+fn locate(text: &str, position: &str) -> Result<usize> {
+    let lines = text.lines().count();
+    if let Ok(line) = position.parse::<usize>() {
+        if line == 0 || line > lines {
+            anyhow::bail!(
+                "Line {} is out of range (the document has {} lines)",
+                line,
+                lines
+            );
+        }
+        return Ok(line);
+    }
+    text.lines()
+        .position(|l| l.contains(position))
+        .map(|i| i + 1)
+        .with_context(|| format!("No line contains {:?}", position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Abstract\n\n1. Introduction\n\n2. Terminology\n";
+
+    #[test]
+    fn locate_by_line_number() {
+        assert_eq!(locate(TEXT, "3").unwrap(), 3);
+        assert_eq!(locate(TEXT, "5").unwrap(), 5);
+        assert!(locate(TEXT, "0").is_err());
+        assert!(locate(TEXT, "6").is_err());
+    }
+
+    #[test]
+    fn locate_by_pattern() {
+        assert_eq!(locate(TEXT, "Terminology").unwrap(), 5);
+        assert_eq!(locate(TEXT, "1. Intro").unwrap(), 3);
+        assert!(locate(TEXT, "Security Considerations").is_err());
+    }
+}
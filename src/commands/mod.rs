@@ -1,7 +1,106 @@
+pub mod alias;
+pub mod author;
+pub mod ballot;
 pub mod cache;
+pub mod cite;
+pub mod export;
+pub mod extract;
 pub mod fetch;
+pub mod formats;
+pub mod history;
+pub mod index;
+pub mod info;
+pub mod lineage;
+pub mod links;
+pub mod mark;
+pub mod notes;
+pub mod notices;
+pub mod random;
 pub mod search;
+pub mod stats;
+pub mod status_line;
+pub mod sync;
+pub mod tag;
 pub mod view;
+pub mod watch;
 
-mod fetch_pipeline;
+mod draft_status;
+mod picker;
+mod progress;
+mod related;
+mod terminal;
 mod viewer;
+
+use std::sync::Once;
+
+use anyhow::Result;
+use chrono::Duration;
+
+use crate::api::{DataTrackerClient, DocumentFetcher};
+use crate::cache::CacheManager;
+use crate::config::Config;
+
+/// Open the user's cache with the installed config applied.
+pub(crate) fn open_cache() -> Result<CacheManager> {
+    let config = Config::current();
+    let cache = CacheManager::new()?
+        .with_compression(config.compress_cache)
+        .with_verified_reads(config.verify_cache_reads)
+        .with_read_only(config.read_only_cache);
+    // Files kept by --keep-temp are only wanted while a viewer has them open.
+    let _ = cache.clean_temp_files(Duration::days(1));
+    Ok(match &config.extra_cache_dir {
+        Some(dir) => cache.with_secondary(dir.clone()),
+        None => cache,
+    })
+}
+
+/// The `s` to put after a noun counted `n` times.
+pub(crate) fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// A fetcher with the installed config's proxy, mirrors, format order, and
+/// contact.
+pub(crate) fn document_fetcher() -> Result<DocumentFetcher> {
+    let config = Config::current();
+    DocumentFetcher::builder()
+        .user_agent(config.user_agent())
+        .proxy(config.proxy_settings())
+        .mirrors(config.mirror_settings())
+        .format_preference(config.format_preference())
+        .build()
+}
+
+/// A Datatracker client with the installed config's proxy and contact.
+pub(crate) fn datatracker_client() -> Result<DataTrackerClient> {
+    let config = Config::current();
+    DataTrackerClient::builder()
+        .user_agent(config.user_agent())
+        .proxy(config.proxy_settings())
+        .build()
+}
+
+/// [`open_cache`] for commands that get by without a cache, only with
+/// less to go on. When it can't be opened (no writable place for it),
+/// they carry on without one after a single warning.
+pub(crate) fn open_optional_cache() -> Option<CacheManager> {
+    static WARNED: Once = Once::new();
+    open_cache()
+        .inspect_err(|e| {
+            WARNED.call_once(|| eprintln!("Warning: {:#}; continuing without a cache", e))
+        })
+        .ok()
+}
+
+/// [`open_cache`] for commands whose point is to change the cache, which
+/// fail up front rather than silently doing nothing when it's read-only.
+pub(crate) fn open_writable_cache() -> Result<CacheManager> {
+    let cache = open_cache()?;
+    cache.ensure_writable()?;
+    Ok(cache)
+}
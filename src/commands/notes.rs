@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::alias::resolve;
+use super::viewer;
+use super::{open_cache, open_writable_cache};
+
+/// Edit the notes on a document in `$EDITOR`. The document needn't be
+/// cached; notes attach by name.
+pub fn edit(document: &str) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let doc_type = resolve(document)?;
+    let path = cache.notes_path(&doc_type);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create notes directory")?;
+    }
+
+    viewer::edit(&path)?;
+
+    // Don't leave an empty file behind to flag "has notes".
+    if cache
+        .get_notes(&doc_type)
+        .is_some_and(|notes| notes.trim().is_empty())
+    {
+        cache.remove_notes(&doc_type)?;
+    }
+    Ok(())
+}
+
+pub fn show(document: &str) -> Result<()> {
+    let cache = open_cache()?;
+    let doc_type = resolve(document)?;
+    match cache.get_notes(&doc_type) {
+        Some(notes) => print!("{}", notes),
+        None => println!("No notes on {} (add some with 'rfc notes edit')", doc_type),
+    }
+    Ok(())
+}
+
+/// Copy every notes file into `dir`.
+pub fn export(dir: &Path) -> Result<()> {
+    let cache = open_cache()?;
+    let count = cache.export_notes(dir)?;
+    if count == 0 {
+        println!("No notes to export");
+    } else {
+        println!(
+            "Exported notes on {} document{} to {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            dir.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! Printing what [`crate::workflow`] reports. The pipeline only emits
+//! `tracing` events, so library users aren't written to; the CLI installs
+//! [`Notices`] to show them on stderr, as `Warning: ...` for warnings.
+
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+use crate::timings::TimingRecorder;
+
+/// Events from targets under this prefix are printed.
+const TARGET: &str = "rfc::workflow";
+
+/// The CLI's global `tracing` subscriber: prints the pipeline's events,
+/// and hands spans to a [`TimingRecorder`] when `--timings` wants one.
+pub struct Notices {
+    timings: Option<Arc<TimingRecorder>>,
+}
+
+impl Notices {
+    /// Install as the global subscriber, timing spans with `timings` if
+    /// given. Fails if a subscriber is already installed.
+    pub fn install(timings: Option<Arc<TimingRecorder>>) -> anyhow::Result<()> {
+        tracing::subscriber::set_global_default(Self { timings })
+            .map_err(|_| anyhow::anyhow!("A tracing subscriber is already installed"))
+    }
+}
+
+impl Subscriber for Notices {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if metadata.is_event() {
+            return is_notice(metadata);
+        }
+        self.timings
+            .as_ref()
+            .is_some_and(|timings| timings.enabled(metadata))
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        match &self.timings {
+            Some(timings) => timings.new_span(span),
+            // Not reached: spans are only enabled with a recorder.
+            None => Id::from_u64(u64::MAX),
+        }
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if !is_notice(event.metadata()) {
+            return;
+        }
+        let mut message = Message::default();
+        event.record(&mut message);
+        match *event.metadata().level() {
+            Level::WARN | Level::ERROR => eprintln!("Warning: {}", message.0),
+            _ => eprintln!("{}", message.0),
+        }
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+
+    fn clone_span(&self, id: &Id) -> Id {
+        match &self.timings {
+            Some(timings) => timings.clone_span(id),
+            None => id.clone(),
+        }
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        self.timings
+            .as_ref()
+            .is_some_and(|timings| timings.try_close(id))
+    }
+}
+
+/// Whether `metadata` is one of the pipeline's progress or warning events.
+fn is_notice(metadata: &Metadata<'_>) -> bool {
+    metadata.target().starts_with(TARGET) && *metadata.level() <= Level::INFO
+}
+
+/// An event's formatted message.
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_timed_only_with_a_recorder() {
+        let notices = Notices { timings: None };
+        tracing::subscriber::with_default(notices, || {
+            assert!(tracing::info_span!("fetch").is_disabled());
+        });
+
+        let timings = Arc::new(TimingRecorder::default());
+        let notices = Notices {
+            timings: Some(Arc::clone(&timings)),
+        };
+        tracing::subscriber::with_default(notices, || {
+            drop(tracing::info_span!("fetch").entered());
+        });
+        assert_eq!(timings.timings().phases[0].phase, "fetch");
+    }
+
+    #[test]
+    fn only_pipeline_progress_and_warnings_are_notices() {
+        struct Capture(std::sync::Mutex<Vec<String>>);
+        impl Subscriber for Capture {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                is_notice(metadata)
+            }
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut message = Message::default();
+                event.record(&mut message);
+                self.0.lock().unwrap().push(message.0);
+            }
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let capture = Arc::new(Capture(Default::default()));
+        tracing::subscriber::with_default(Arc::clone(&capture), || {
+            tracing::info!(target: "rfc::workflow", "Fetching {}...", "RFC 9000");
+            tracing::warn!(target: "rfc::workflow", "Failed to fetch metadata");
+            tracing::debug!(target: "rfc::workflow", "too chatty");
+            tracing::info!(target: "rfc::api", "not ours");
+        });
+        assert_eq!(
+            *capture.0.lock().unwrap(),
+            ["Fetching RFC 9000...", "Failed to fetch metadata"]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Choosing a search result interactively with `fzf` or `skim` (`sk`).
+//!
+//! Each line handed to the finder starts with the document's canonical
+//! name, so the selection maps back to a document whatever the rest of
+//! the line looks like.
+
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::models::DocumentType;
+
+/// Finders to try, in order of preference.
+const FINDERS: &[&str] = &["fzf", "sk"];
+
+/// The first supported finder on `PATH`.
+pub fn find() -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    FINDERS.iter().find_map(|name| {
+        env::split_paths(&path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Whether `RFC_FZF` asks for the finder by default (`1`, `true`, `yes`).
+pub fn requested_by_env() -> bool {
+    env::var("RFC_FZF").is_ok_and(|v| truthy(&v))
+}
+
+fn truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes"
+    )
+}
+
+/// Run `finder` over `lines` and return the document picked, or `None`
+/// when the user backs out.
+pub fn pick(finder: &Path, lines: &[String]) -> Result<Option<DocumentType>> {
+    let mut child = Command::new(finder)
+        .args(["--no-multi", "--prompt", "rfc> "])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", finder.display()))?;
+
+    // The finder may exit before reading everything; that isn't an error.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(lines.join("\n").as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    // fzf and sk exit 1 for "no match" and 130 when interrupted.
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let selected = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_selection(&selected))
+}
+
+/// The document named in the first column of a selected line.
+fn parse_selection(line: &str) -> Option<DocumentType> {
+    DocumentType::parse_identifier(line.split_whitespace().next()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_is_parsed_from_first_column() {
+        assert_eq!(
+            parse_selection("rfc9000  RFC  2021-05  QUIC: A UDP-Based Multiplexed\n"),
+            Some(DocumentType::Rfc(9000))
+        );
+        assert_eq!(
+            parse_selection("draft-ietf-quic-transport  I-D  2021-01  QUIC"),
+            Some(DocumentType::Draft("draft-ietf-quic-transport".to_string()))
+        );
+        assert_eq!(parse_selection(""), None);
+        assert_eq!(parse_selection("quic  something"), None);
+    }
+
+    #[test]
+    fn env_values() {
+        assert!(truthy("1"));
+        assert!(truthy("TRUE"));
+        assert!(truthy(" yes\n"));
+        assert!(!truthy("0"));
+        assert!(!truthy(""));
+    }
+}
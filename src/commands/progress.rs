@@ -0,0 +1,74 @@
+//! Progress bars on stderr. They're only drawn when stderr is a terminal
+//! and `--quiet` wasn't given; otherwise every bar here is hidden and
+//! costs nothing.
+
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::api::DownloadObserver;
+
+/// Whether progress should be drawn at all.
+pub(super) fn enabled(quiet: bool) -> bool {
+    !quiet && std::io::stderr().is_terminal()
+}
+
+/// Byte-count bar for single document downloads: a proper bar when the
+/// server sends `Content-Length`, a spinner otherwise.
+pub(super) struct DownloadBar {
+    enabled: bool,
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl DownloadBar {
+    pub(super) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            bar: Mutex::new(None),
+        }
+    }
+}
+
+impl DownloadObserver for DownloadBar {
+    fn started(&self, _url: &str, total: Option<u64>) {
+        if !self.enabled {
+            return;
+        }
+        let bar = match total {
+            Some(total) => ProgressBar::new(total).with_style(
+                ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} ({eta})")
+                    .expect("valid template"),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {bytes}").expect("valid template"),
+            ),
+        };
+        bar.enable_steady_tick(Duration::from_millis(100));
+        *self.bar.lock().unwrap() = Some(bar);
+    }
+
+    fn advanced(&self, bytes: u64) {
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            bar.inc(bytes);
+        }
+    }
+
+    fn finished(&self) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// "N of M" bar for bulk operations; set the message to the document
+/// currently being worked on.
+pub(super) fn bulk_bar(len: u64, enabled: bool) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+    ProgressBar::new(len).with_style(
+        ProgressStyle::with_template("{bar:30} {pos}/{len} {wide_msg}").expect("valid template"),
+    )
+}
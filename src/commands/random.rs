@@ -0,0 +1,172 @@
+//! `rfc --random`: pick a published RFC to read, from the synced index
+//! when there is one and by probing the Datatracker otherwise.
+
+use std::hash::{BuildHasher, RandomState};
+use std::time::Instant;
+
+use anyhow::Result;
+
+use crate::models::{DocumentType, RfcIndex, RfcIndexEntry, StdLevel};
+
+use super::open_optional_cache;
+
+/// Random numbers tried without an index before giving up.
+const PROBE_ATTEMPTS: usize = 5;
+
+/// Highest number probed without an index; a little below the latest
+/// RFCs so most probes land on something published.
+const PROBE_CEILING: u32 = 9700;
+
+/// What the random pick must satisfy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Acceptable statuses; empty accepts any
+    pub status: Vec<StdLevel>,
+    /// Working group acronym, compared case-insensitively
+    pub wg: Option<String>,
+    /// Lowest RFC number to pick
+    pub min: Option<u32>,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &RfcIndexEntry) -> bool {
+        self.min.is_none_or(|min| entry.number >= min)
+            && (self.status.is_empty()
+                || entry
+                    .status
+                    .as_deref()
+                    .and_then(StdLevel::from_index_status)
+                    .is_some_and(|level| self.status.contains(&level)))
+            && self.wg.as_ref().is_none_or(|wg| {
+                entry
+                    .wg
+                    .as_ref()
+                    .is_some_and(|entry_wg| entry_wg.eq_ignore_ascii_case(wg))
+            })
+    }
+
+    /// Every RFC in `index` the filter accepts, lowest first.
+    pub fn candidates(&self, index: &RfcIndex) -> Vec<u32> {
+        index
+            .entries
+            .values()
+            .filter(|entry| self.matches(entry))
+            .map(|entry| entry.number)
+            .collect()
+    }
+}
+
+/// Pick an RFC matching `filter` at random.
+pub async fn pick(filter: &Filter, offline: bool) -> Result<DocumentType> {
+    if let Some(index) = open_optional_cache().and_then(|cache| cache.load_rfc_index()) {
+        let candidates = filter.candidates(&index);
+        anyhow::ensure!(
+            !candidates.is_empty(),
+            "No RFC in the local index matches the --random filters"
+        );
+        return Ok(DocumentType::Rfc(
+            candidates[random_below(candidates.len())],
+        ));
+    }
+    if offline {
+        anyhow::bail!("--random --offline needs the RFC index; run 'rfc sync-index'");
+    }
+    if filter.wg.is_some() {
+        anyhow::bail!("--random --wg needs the RFC index; run 'rfc sync-index'");
+    }
+    probe(filter).await
+}
+
+/// Without an index, try random numbers up to [`PROBE_CEILING`] until the
+/// Datatracker knows one (numbers that were never issued are skipped)
+/// and it has an accepted status.
+async fn probe(filter: &Filter) -> Result<DocumentType> {
+    let low = filter.min.unwrap_or(1).max(1);
+    anyhow::ensure!(
+        low <= PROBE_CEILING,
+        "--min {} is past the RFCs probed without an index; run 'rfc sync-index'",
+        low
+    );
+    let client = super::datatracker_client()?;
+    let span = (PROBE_CEILING - low + 1) as usize;
+    for _ in 0..PROBE_ATTEMPTS {
+        let doc_type = DocumentType::Rfc(low + random_below(span) as u32);
+        let Ok(doc) = client.get_document(&doc_type.name()).await else {
+            continue;
+        };
+        let accepted = filter.status.is_empty()
+            || doc
+                .std_level
+                .is_some_and(|level| filter.status.contains(&level));
+        if accepted {
+            return Ok(doc_type);
+        }
+    }
+    anyhow::bail!(
+        "No published RFC found in {} random tries; try again, or run 'rfc sync-index' \
+         to pick from the index",
+        PROBE_ATTEMPTS
+    )
+}
+
+/// A number in `0..bound`, from the standard library's randomly keyed
+/// hasher; plenty for picking something to read.
+fn random_below(bound: usize) -> usize {
+    let seed = RandomState::new().hash_one(Instant::now());
+    (seed % bound as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(number: u32, status: &str, wg: Option<&str>) -> RfcIndexEntry {
+        RfcIndexEntry {
+            number,
+            title: format!("RFC {}", number),
+            status: Some(status.to_string()),
+            wg: wg.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn candidates_honor_every_filter() {
+        let mut index = RfcIndex::default();
+        for entry in [
+            entry(791, "INTERNET STANDARD", None),
+            entry(3031, "PROPOSED STANDARD", Some("mpls")),
+            entry(3469, "INFORMATIONAL", Some("mpls")),
+            entry(9000, "PROPOSED STANDARD", Some("quic")),
+        ] {
+            index.entries.insert(entry.number, entry);
+        }
+
+        assert_eq!(
+            Filter::default().candidates(&index),
+            [791, 3031, 3469, 9000]
+        );
+        let mpls = Filter {
+            wg: Some("MPLS".to_string()),
+            ..Filter::default()
+        };
+        assert_eq!(mpls.candidates(&index), [3031, 3469]);
+        let informational = Filter {
+            status: vec![StdLevel::Informational],
+            ..mpls.clone()
+        };
+        assert_eq!(informational.candidates(&index), [3469]);
+        let recent = Filter {
+            min: Some(5000),
+            status: vec![StdLevel::ProposedStandard],
+            ..Filter::default()
+        };
+        assert_eq!(recent.candidates(&index), [9000]);
+    }
+
+    #[test]
+    fn random_numbers_stay_below_the_bound() {
+        assert!((0..100).all(|_| random_below(3) < 3));
+        assert_eq!(random_below(1), 0);
+    }
+}
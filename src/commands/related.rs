@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::{draft_references, rfc_references};
+use crate::cache::{CacheManager, CacheMetadata};
+use crate::models::{DocumentType, Format, IndexLookup, RfcIndex};
+
+/// At most this many documents are suggested.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// How a suggested document relates to the one just read, strongest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    ObsoletedBy,
+    UpdatedBy,
+    PublishedAs,
+    ReplacedBy,
+    Obsoletes,
+    Updates,
+    /// Cited in the text
+    SeeAlso,
+}
+
+impl Relation {
+    fn label(self) -> &'static str {
+        match self {
+            Relation::ObsoletedBy => "obsoleted by",
+            Relation::UpdatedBy => "updated by",
+            Relation::PublishedAs => "published as",
+            Relation::ReplacedBy => "replaced by",
+            Relation::Obsoletes => "obsoletes",
+            Relation::Updates => "updates",
+            Relation::SeeAlso => "see also",
+        }
+    }
+}
+
+/// The `Related: ...` line for `doc_type`, built only from what's already
+/// cached (the RFC index, metadata, and the text itself), or `None` if
+/// nothing related is known.
+pub(super) fn related_line(
+    doc_type: &DocumentType,
+    text: &str,
+    cache: &CacheManager,
+) -> Option<String> {
+    let metadata = cache.get_metadata(doc_type);
+    let obsoleted_by = match doc_type {
+        DocumentType::Rfc(num) => cache
+            .get_obsolescence(*num)
+            .map(|facts| facts.obsoleted_by)
+            .unwrap_or_default(),
+        DocumentType::Draft(_) => Vec::new(),
+    };
+    let related = related(
+        doc_type,
+        text,
+        cache.load_rfc_index().as_ref(),
+        metadata.as_ref(),
+        &obsoleted_by,
+    );
+    format_related(&related, |doc| cache.contains(doc, Format::Text))
+}
+
+/// Up to [`MAX_SUGGESTIONS`] documents related to `doc_type`, without
+/// repeats: relationships from the index, cached obsolescence facts, and
+/// draft status first, then the documents the text cites most.
+fn related(
+    doc_type: &DocumentType,
+    text: &str,
+    index: Option<&RfcIndex>,
+    metadata: Option<&CacheMetadata>,
+    obsoleted_by: &[u32],
+) -> Vec<(DocumentType, Relation)> {
+    let mut found = Vec::new();
+    let rfcs = |numbers: &[u32], relation| {
+        numbers
+            .iter()
+            .map(move |n| (DocumentType::Rfc(*n), relation))
+            .collect::<Vec<_>>()
+    };
+
+    if let (DocumentType::Rfc(num), Some(index)) = (doc_type, index) {
+        if let IndexLookup::Published(entry) = index.lookup(*num) {
+            found.extend(rfcs(&entry.obsoleted_by, Relation::ObsoletedBy));
+            found.extend(rfcs(&entry.updated_by, Relation::UpdatedBy));
+            found.extend(rfcs(&entry.obsoletes, Relation::Obsoletes));
+            found.extend(rfcs(&entry.updates, Relation::Updates));
+        }
+    }
+    found.extend(rfcs(obsoleted_by, Relation::ObsoletedBy));
+    if let Some(status) = metadata.and_then(|m| m.draft_status.as_ref()) {
+        found.extend(
+            status
+                .rfc_number
+                .map(|n| (DocumentType::Rfc(n), Relation::PublishedAs)),
+        );
+        found.extend(
+            status
+                .replaced_by
+                .iter()
+                .map(|name| (DocumentType::Draft(name.clone()), Relation::ReplacedBy)),
+        );
+    }
+    found.extend(
+        most_cited(text)
+            .into_iter()
+            .map(|doc| (doc, Relation::SeeAlso)),
+    );
+
+    let mut seen = HashSet::from([doc_type.name()]);
+    found.retain(|(doc, _)| seen.insert(doc.name()));
+    found.truncate(MAX_SUGGESTIONS);
+    found
+}
+
+/// Documents cited in `text`, most often cited first (ties in order of
+/// first mention).
+fn most_cited(text: &str) -> Vec<DocumentType> {
+    let mentions = rfc_references(text)
+        .into_iter()
+        .map(|(range, num)| (range.start, DocumentType::Rfc(num)))
+        .chain(
+            draft_references(text)
+                .into_iter()
+                .map(|(range, name)| (range.start, DocumentType::Draft(name))),
+        );
+    let mut counts: HashMap<String, (usize, usize, DocumentType)> = HashMap::new();
+    for (at, doc) in mentions {
+        counts
+            .entry(doc.name())
+            .and_modify(|(count, first, _)| {
+                *count += 1;
+                *first = (*first).min(at);
+            })
+            .or_insert((1, at, doc));
+    }
+    let mut cited: Vec<_> = counts.into_values().collect();
+    cited.sort_by_key(|(count, first, _)| (std::cmp::Reverse(*count), *first));
+    cited.into_iter().map(|(_, _, doc)| doc).collect()
+}
+
+/// `Related: updated by RFC 8212 (cached), RFC 7606; see also draft-x`,
+/// with consecutive documents sharing a relation grouped under it.
+fn format_related(
+    related: &[(DocumentType, Relation)],
+    is_cached: impl Fn(&DocumentType) -> bool,
+) -> Option<String> {
+    let mut groups: Vec<(Relation, Vec<String>)> = Vec::new();
+    for (doc, relation) in related {
+        let name = if is_cached(doc) {
+            format!("{} (cached)", doc)
+        } else {
+            doc.to_string()
+        };
+        match groups.last_mut() {
+            Some((last, names)) if last == relation => names.push(name),
+            _ => groups.push((*relation, vec![name])),
+        }
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    let groups: Vec<String> = groups
+        .iter()
+        .map(|(relation, names)| format!("{} {}", relation.label(), names.join(", ")))
+        .collect();
+    Some(format!("Related: {}", groups.join("; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DraftStatus;
+    use crate::models::RfcIndexEntry;
+    use chrono::Utc;
+
+    fn index() -> RfcIndex {
+        let mut index = RfcIndex::default();
+        index.entries.insert(
+            4271,
+            RfcIndexEntry {
+                number: 4271,
+                title: "A Border Gateway Protocol 4 (BGP-4)".to_string(),
+                obsoletes: vec![1771],
+                updated_by: vec![8212, 7606],
+                ..Default::default()
+            },
+        );
+        index
+    }
+
+    #[test]
+    fn index_relations_come_before_citations_without_repeats() {
+        let text = "See RFC 8212 and [I-D.ietf-idr-bgp-extended-messages]. \
+                    RFC 4271 itself, RFC 2918, RFC 2918.";
+        let found = related(&DocumentType::Rfc(4271), text, Some(&index()), None, &[]);
+        let line = format_related(&found, |doc| *doc == DocumentType::Rfc(8212));
+        assert_eq!(
+            line.as_deref(),
+            Some(
+                "Related: updated by RFC 8212 (cached), RFC 7606; obsoletes RFC 1771; \
+                 see also RFC 2918, draft-ietf-idr-bgp-extended-messages"
+            )
+        );
+    }
+
+    #[test]
+    fn draft_status_and_obsolescence_facts_are_used() {
+        let draft = DocumentType::Draft("draft-ietf-quic-transport-34".to_string());
+        let metadata = CacheMetadata {
+            draft_status: Some(DraftStatus {
+                expires: None,
+                replaced_by: Vec::new(),
+                rfc_number: Some(9000),
+                checked_at: Utc::now(),
+            }),
+            ..CacheMetadata::new("QUIC", Utc::now())
+        };
+        let found = related(&draft, "", None, Some(&metadata), &[]);
+        assert_eq!(found, [(DocumentType::Rfc(9000), Relation::PublishedAs)]);
+
+        let text = "RFC 1945 RFC 2068 RFC 2119 RFC 2396 RFC 2617 RFC 3986";
+        let found = related(&DocumentType::Rfc(2616), text, None, None, &[7230, 7231]);
+        assert_eq!(found.len(), MAX_SUGGESTIONS);
+        assert_eq!(found[0], (DocumentType::Rfc(7230), Relation::ObsoletedBy));
+        assert_eq!(found[2], (DocumentType::Rfc(1945), Relation::SeeAlso));
+    }
+
+    #[test]
+    fn nothing_related_means_no_line() {
+        assert_eq!(format_related(&[], |_| false), None);
+    }
+}
@@ -1,31 +1,135 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use anyhow::Result;
+use chrono::Duration;
+use futures::stream::{StreamExt, TryStreamExt};
 
 use crate::api::DataTrackerClient;
-use crate::models::SearchFilter;
+use crate::cache::{CacheManager, DocumentCache};
+use crate::config::Config;
+use crate::models::{
+    match_title, Document, DocumentType, DraftState, QueryMode, RfcIndex, SearchOptions,
+    SearchResult,
+};
+use crate::render::{Column, OutputOptions, TableRenderer, TableRow};
+
+use super::{open_optional_cache, plural};
+use super::{picker, terminal, view};
+
+/// Abstracts are cut to roughly this many characters in `--abstracts` mode.
+const ABSTRACT_EXCERPT_CHARS: usize = 400;
+
+/// Indent for abstract text under each result line.
+const ABSTRACT_INDENT: usize = 4;
+
+/// Most results `--all-results` collects, so a vague query can't page
+/// through the whole Datatracker.
+const ALL_RESULTS_CAP: usize = 1000;
 
 pub struct Args {
     pub query: String,
-    pub filter: SearchFilter,
+    /// Type, status, date, and keyword filters. A status filter also adds a
+    /// status column.
+    pub options: SearchOptions,
     pub limit: usize,
+    /// Ignore `limit` and follow the server's pages, up to 1000 results.
+    pub all_results: bool,
+    /// Show full titles without truncation.
+    pub wide: bool,
+    /// Drop RFCs that have been obsoleted.
+    pub exclude_obsoleted: bool,
+    /// Drop drafts that are expired, replaced, published, or withdrawn.
+    pub active_only: bool,
+    /// Print each result's abstract beneath it.
+    pub abstracts: bool,
+    /// Emit the result set as JSON instead of a table.
+    pub json: bool,
+    /// Search cached titles and the synced RFC index instead of the
+    /// Datatracker.
+    pub offline: bool,
+    /// Pick a result with fzf/sk and open it. `None` leaves it to
+    /// `RFC_FZF` and the `fzf` config key.
+    pub fzf: Option<bool>,
+    /// Always ask the Datatracker, ignoring recently cached results.
+    pub no_search_cache: bool,
+    pub quiet: bool,
 }
 
 pub async fn run(args: Args) -> Result<()> {
-    let client = DataTrackerClient::new()?;
+    // The cache only speeds up the lookups; search still works without it.
+    let cache = open_optional_cache();
 
-    eprintln!("Searching for '{}'...", args.query);
+    if args.offline && args.options.keyword.is_some() {
+        anyhow::bail!("Keywords come from the Datatracker; drop --offline or --keyword");
+    }
+    let mut results = if args.offline {
+        offline_results(cache.as_ref(), &args)
+    } else {
+        match online_results(cache.as_ref(), &args).await {
+            Ok(results) => results,
+            // Nothing local to fall back on: the network error is the news.
+            Err(e) if cache.is_none() => return Err(e),
+            Err(e) => {
+                eprintln!("Warning: {:#}", e);
+                offline_results(cache.as_ref(), &args)
+            }
+        }
+    };
 
-    let results = client
-        .search(&args.query, args.filter, args.limit as u32)
-        .await?;
+    let mut hidden = 0;
+    if args.exclude_obsoleted {
+        let before = results.len();
+        results.documents.retain(|doc| !doc.is_obsoleted());
+        hidden = before - results.len();
+    }
+    let mut inactive = 0;
+    if args.active_only {
+        let before = results.len();
+        results.documents.retain(is_active);
+        inactive = before - results.len();
+    }
+
+    if args.json {
+        if !args.abstracts {
+            for doc in &mut results.documents {
+                doc.abstract_text = None;
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
 
     if results.is_empty() {
         println!("No results found for '{}'", args.query);
+        if hidden > 0 {
+            println!("({} obsoleted result{} hidden)", hidden, plural(hidden));
+        }
+        if inactive > 0 {
+            println!("({} inactive draft{} hidden)", inactive, plural(inactive));
+        }
         return Ok(());
     }
 
+    if !args.json
+        && args
+            .fzf
+            .unwrap_or_else(|| picker::requested_by_env() || Config::current().fzf)
+    {
+        match picker::find() {
+            Some(finder) => return choose(&finder, &results, &args).await,
+            None => eprintln!("Warning: neither fzf nor sk is on PATH; listing results instead"),
+        }
+    }
+
     let shown = results.len();
 
-    if let Some(total) = results.total_count {
+    if args.all_results && results.has_more {
+        println!(
+            "\nShowing the first {} results; --all-results stops there.\n",
+            shown
+        );
+    } else if let Some(total) = results.total_count {
         if results.has_more {
             println!(
                 "\nShowing {} of {} results. Increase --limit <N> to show more.\n",
@@ -43,28 +147,688 @@ pub async fn run(args: Args) -> Result<()> {
         println!("\nFound {} results:\n", shown);
     }
 
-    let max_name_width = results
-        .documents
+    let term_width = terminal::width();
+    let table = table(term_width, args.wide, !args.options.status.is_empty())
+        .with_output(OutputOptions::stdout(Config::current().color))
+        .with_highlight(Some(highlight_query(&args.query, args.options.mode)));
+    let rows: Vec<TableRow> = results.documents.iter().map(row).collect();
+    let layout = table.layout(&rows);
+
+    for (doc, row) in results.documents.iter().zip(&rows) {
+        println!("{}", layout.render(row));
+        if args.abstracts {
+            if let Some(abstract_text) = &doc.abstract_text {
+                let excerpt = terminal::excerpt(abstract_text, ABSTRACT_EXCERPT_CHARS);
+                println!("{}", terminal::wrap(&excerpt, term_width, ABSTRACT_INDENT));
+            }
+            println!();
+        }
+    }
+
+    if hidden > 0 {
+        println!("\n({} obsoleted result{} hidden)", hidden, plural(hidden));
+    }
+    if inactive > 0 {
+        println!("\n({} inactive draft{} hidden)", inactive, plural(inactive));
+    }
+
+    println!("\nUse 'rfc <document>' to read a document");
+    Ok(())
+}
+
+/// Hand the results to a fuzzy finder and open the one picked.
+async fn choose(finder: &Path, results: &SearchResult, args: &Args) -> Result<()> {
+    // The finder truncates to its own width, so titles go in whole.
+    let rows: Vec<TableRow> = results.documents.iter().map(row).collect();
+    let lines = table(terminal::width(), true, !args.options.status.is_empty()).render(&rows);
+
+    let Some(doc_type) = picker::pick(finder, &lines)? else {
+        return Ok(());
+    };
+    view::run(
+        &doc_type.name(),
+        &view::Args {
+            keep_temp: Config::current().keep_temp,
+            offline: args.offline,
+            quiet: args.quiet,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Datatracker results, reused from the search cache while fresh.
+async fn online_results(cache: Option<&CacheManager>, args: &Args) -> Result<SearchResult> {
+    let ttl = Config::current().search_cache_ttl();
+    let search_cache =
+        cache.filter(|_| !args.no_search_cache && !args.all_results && ttl > Duration::zero());
+    if let Some(results) =
+        search_cache.and_then(|c| c.get_search(&args.query, &args.options, args.limit, ttl))
+    {
+        eprintln!("(cached results)");
+        return Ok(results);
+    }
+
+    let client = super::datatracker_client()?;
+
+    eprintln!("Searching for '{}'...", args.query);
+    if args.all_results {
+        return all_results(&client, cache, args).await;
+    }
+
+    let mut results = client
+        .search_with_options(&args.query, &args.options, args.limit as u32)
+        .await?;
+    annotate_obsolescence(&client, cache, &mut results.documents).await;
+    if let Some(cache) = search_cache {
+        if let Err(e) = cache.store_search(&results, args.limit, ttl) {
+            eprintln!("Warning: Failed to cache search results: {}", e);
+        }
+    }
+    Ok(results)
+}
+
+/// Every Datatracker result, page by page, up to 1000.
+/// These aren't kept in the search cache, and obsolescence is only looked
+/// up when it's needed to hide obsoleted RFCs: one request per RFC would
+/// cost more than the search.
+async fn all_results(
+    client: &DataTrackerClient,
+    cache: Option<&CacheManager>,
+    args: &Args,
+) -> Result<SearchResult> {
+    let mut documents: Vec<Document> = client
+        .search_stream(&args.query, &args.options)
+        .take(ALL_RESULTS_CAP + 1)
+        .try_collect()
+        .await?;
+    let has_more = documents.len() > ALL_RESULTS_CAP;
+    documents.truncate(ALL_RESULTS_CAP);
+    if args.exclude_obsoleted {
+        annotate_obsolescence(client, cache, &mut documents).await;
+    }
+    Ok(SearchResult {
+        total_count: (!has_more).then_some(documents.len() as u32),
+        documents,
+        has_more,
+        query: args.query.clone(),
+        filter: args.options.filter,
+        status: args.options.status.clone(),
+        since: args.options.since,
+        before: args.options.before,
+        mode: args.options.mode,
+        keyword: args.options.keyword.clone(),
+    })
+}
+
+fn offline_results(cache: Option<&CacheManager>, args: &Args) -> SearchResult {
+    let index = cache.and_then(|c| c.load_rfc_index());
+    if index.is_none() {
+        eprintln!("No local RFC index; only cached documents are searched (run 'rfc sync-index')");
+    }
+    eprintln!("(offline results)");
+    search_local(
+        cache.map(|c| c as &dyn DocumentCache),
+        index.as_ref(),
+        &args.query,
+        &args.options,
+        if args.all_results {
+            ALL_RESULTS_CAP
+        } else {
+            args.limit
+        },
+    )
+}
+
+/// Title search over what's on disk: cached documents' metadata and, when
+/// synced, the RFC index. Every query word must match; results are ranked
+/// by where the first word appears, then newest RFC first. RFCs found in
+/// both places are taken from the index, which also knows dates and
+/// obsolescence. Cached documents carry their recorded abstracts.
+fn search_local(
+    cache: Option<&dyn DocumentCache>,
+    index: Option<&RfcIndex>,
+    query: &str,
+    options: &SearchOptions,
+    limit: usize,
+) -> SearchResult {
+    let words = options.mode.terms(query);
+    let mut hits: HashMap<DocumentType, (usize, Document)> = HashMap::new();
+    let mut abstracts = HashMap::new();
+
+    for cached in cache
+        .map(|c| c.list_cached_with_metadata())
+        .unwrap_or_default()
+    {
+        let Some(meta) = cached.metadata else {
+            continue;
+        };
+        if let Some(text) = meta.recorded_abstract() {
+            abstracts.insert(cached.doc_type.clone(), text.to_string());
+        }
+        if let Some(pos) = match_title(&meta.title, &words) {
+            let doc = Document::new(cached.doc_type.name(), meta.title, cached.doc_type.clone());
+            hits.insert(cached.doc_type, (pos, doc));
+        }
+    }
+    if let Some(index) = index {
+        for entry in index.entries.values() {
+            if let Some(pos) = match_title(&entry.title, &words) {
+                hits.insert(DocumentType::Rfc(entry.number), (pos, entry.to_document()));
+            }
+        }
+    }
+    for (_, doc) in hits.values_mut() {
+        doc.abstract_text = abstracts.remove(&doc.doc_type);
+    }
+
+    let mut hits: Vec<(usize, Document)> = hits
+        .into_values()
+        .filter(|(_, doc)| options.includes(doc))
+        .collect();
+    hits.sort_by(|(pa, a), (pb, b)| {
+        let number = |d: &Document| match d.doc_type {
+            DocumentType::Rfc(n) => n,
+            DocumentType::Draft(_) => 0,
+        };
+        pa.cmp(pb)
+            .then(number(b).cmp(&number(a)))
+            .then(a.name.cmp(&b.name))
+    });
+
+    let total = hits.len();
+    SearchResult {
+        documents: hits.into_iter().take(limit).map(|(_, doc)| doc).collect(),
+        has_more: total > limit,
+        total_count: Some(total as u32),
+        query: query.to_string(),
+        filter: options.filter,
+        status: options.status.clone(),
+        since: options.since,
+        before: options.before,
+        mode: options.mode,
+        keyword: options.keyword.clone(),
+    }
+}
+
+/// Fill in `obsoleted_by` for every RFC in `docs`.
+///
+/// Cached facts are used when fresh; the rest are looked up together with
+/// [`DataTrackerClient::get_documents`]. A failed lookup just leaves the
+/// documents unmarked — search results are more useful than an error.
+async fn annotate_obsolescence(
+    client: &DataTrackerClient,
+    cache: Option<&CacheManager>,
+    docs: &mut [Document],
+) {
+    let mut pending = Vec::new();
+    for doc in docs.iter_mut() {
+        let DocumentType::Rfc(num) = doc.doc_type else {
+            continue;
+        };
+        match cache.and_then(|c| c.get_obsolescence(num)) {
+            Some(facts) => doc.obsoleted_by = facts.obsoleted_by,
+            None => pending.push(num),
+        }
+    }
+
+    if pending.is_empty() {
+        return;
+    }
+
+    let names: Vec<String> = pending
         .iter()
-        .map(|doc| doc.doc_type.name().len())
-        .max()
-        .unwrap_or(10);
+        .map(|n| DocumentType::Rfc(*n).name())
+        .collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let Ok(found) = client.get_documents(&names).await else {
+        return;
+    };
+    let looked_up: Vec<(u32, Vec<u32>)> = pending
+        .into_iter()
+        .filter_map(|num| {
+            let doc = found.get(&DocumentType::Rfc(num).name())?;
+            Some((num, doc.obsoleted_by.clone()))
+        })
+        .collect();
 
-    // 80-col target line: name column + 2-space gutter + title.
-    let title_width = 80_usize
-        .saturating_sub(max_name_width)
-        .saturating_sub(4)
-        .min(77);
+    if let Some(cache) = cache {
+        if let Err(e) = cache.store_obsolescence(&looked_up) {
+            eprintln!("Warning: Failed to cache obsolescence info: {}", e);
+        }
+    }
 
-    for doc in &results.documents {
-        println!(
-            "{:<width$}  {}",
+    let by_num: HashMap<u32, Vec<u32>> = looked_up.into_iter().collect();
+    for doc in docs.iter_mut() {
+        if let DocumentType::Rfc(num) = doc.doc_type {
+            if let Some(by) = by_num.get(&num) {
+                doc.obsoleted_by = by.clone();
+            }
+        }
+    }
+}
+
+/// `[obsoleted by RFC 7230, RFC 7231]`, or `None` for current documents.
+fn obsolescence_marker(doc: &Document) -> Option<String> {
+    if !doc.is_obsoleted() {
+        return None;
+    }
+    let by: Vec<String> = doc
+        .obsoleted_by
+        .iter()
+        .map(|n| DocumentType::Rfc(*n).to_string())
+        .collect();
+    Some(format!("[obsoleted by {}]", by.join(", ")))
+}
+
+/// `[active]`, `[expired]`, `[replaced]`, `[rfc]`, or `[withdrawn]` for
+/// drafts whose state is known.
+fn state_marker(doc: &Document) -> Option<String> {
+    match doc.doc_type {
+        DocumentType::Draft(_) => doc.draft_state.map(|state| format!("[{}]", state.label())),
+        DocumentType::Rfc(_) => None,
+    }
+}
+
+/// Whether `doc` survives `--active-only`: RFCs, active drafts, and drafts
+/// whose state wasn't found (which can't be ruled out).
+pub(super) fn is_active(doc: &Document) -> bool {
+    match doc.doc_type {
+        DocumentType::Draft(_) => doc.draft_state.is_none_or(|s| s == DraftState::Active),
+        DocumentType::Rfc(_) => true,
+    }
+}
+
+/// Width of the type tag column (`RFC` / `I-D`).
+const TAG_WIDTH: usize = 3;
+/// Width of the date column (`YYYY-MM`).
+const DATE_WIDTH: usize = 7;
+/// Width of the status column (`PS`, `STD`, ..., `HIST`).
+const STATUS_WIDTH: usize = 4;
+/// Narrowest title column we're willing to show before dropping the
+/// optional tag/date columns to make room.
+const MIN_TITLE_WIDTH: usize = 30;
+
+/// Columns for search results: `name  tag  date  title`, with date and
+/// then tag dropped on narrow terminals. A status column, which is never
+/// dropped, follows the date when `show_status`.
+pub(super) fn table(term_width: usize, wide: bool, show_status: bool) -> TableRenderer {
+    let table = TableRenderer::new(term_width)
+        .with_column(Column::fit())
+        .with_column(Column::fixed(TAG_WIDTH).droppable(1))
+        .with_column(Column::fixed(DATE_WIDTH).droppable(2))
+        .with_min_title_width(MIN_TITLE_WIDTH)
+        .with_wide(wide);
+    if show_status {
+        table.with_column(Column::fixed(STATUS_WIDTH))
+    } else {
+        table
+    }
+}
+
+/// The query as [`highlight_match`](crate::render::highlight_match) takes it, searched per `mode`.
+fn highlight_query(query: &str, mode: QueryMode) -> String {
+    match mode {
+        QueryMode::Words => query.to_string(),
+        QueryMode::Phrase => format!("\"{}\"", query.replace('"', " ")),
+    }
+}
+
+/// `doc` as a row of [`table`], draft state and obsolescence markers
+/// included.
+pub(super) fn row(doc: &Document) -> TableRow {
+    let date = doc
+        .published
+        .map(|d| d.format("%Y-%m").to_string())
+        .unwrap_or_default();
+    let status = doc.std_level.map_or("", |level| level.label());
+    let row = TableRow::new(
+        vec![
             doc.doc_type.name(),
-            doc.short_title(title_width),
-            width = max_name_width
+            doc.doc_type.tag().to_string(),
+            date,
+            status.to_string(),
+        ],
+        doc.title.clone(),
+    );
+    let markers: Vec<String> = [state_marker(doc), obsolescence_marker(doc)]
+        .into_iter()
+        .flatten()
+        .collect();
+    if markers.is_empty() {
+        row
+    } else {
+        row.with_suffix(format!(" {}", markers.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use crate::models::{
+        display_width, DocumentType, Format, RfcIndexEntry, SearchFilter, StdLevel,
+    };
+    use chrono::NaiveDate;
+
+    fn doc(name: &str, title: &str, published: Option<NaiveDate>) -> Document {
+        let mut d = Document::new(
+            name.to_string(),
+            title.to_string(),
+            DocumentType::from_canonical_name(name),
         );
+        d.published = published;
+        d
     }
 
-    println!("\nUse 'rfc <document>' to read a document");
-    Ok(())
+    fn render(table: &TableRenderer, d: &Document) -> String {
+        table.render(&[row(d)]).remove(0)
+    }
+
+    #[test]
+    fn table_collapses_date_then_tag() {
+        let rows = [row(&doc(
+            "draft-ietf-quic-multipath-extensions",
+            "QUIC",
+            None,
+        ))];
+        // Long draft names eat the budget: date goes first.
+        let layout = table(80, false, false).layout(&rows);
+        assert!(layout.shows(1) && !layout.shows(2));
+        assert_eq!(layout.title_width, 79 - 38 - 5);
+
+        // Narrower still: tag goes too.
+        let layout = table(60, false, false).layout(&rows);
+        assert!(!layout.shows(1) && !layout.shows(2));
+        assert_eq!(layout.title_width, 59 - 38);
+
+        let layout = table(40, true, false).layout(&rows);
+        assert!(layout.shows(1) && layout.shows(2));
+        assert_eq!(layout.title_width, usize::MAX);
+    }
+
+    #[test]
+    fn status_column_narrows_the_title() {
+        let mut d = doc("rfc9000", "QUIC", NaiveDate::from_ymd_opt(2021, 5, 27));
+        d.std_level = Some(StdLevel::ProposedStandard);
+        let layout = table(80, false, true).layout(&[row(&d)]);
+        assert_eq!(layout.title_width, 56 - 6);
+        assert_eq!(
+            render(&table(80, false, true), &d),
+            "rfc9000  RFC  2021-05  PS    QUIC"
+        );
+    }
+
+    #[test]
+    fn render_highlights_within_the_title_column() {
+        let table = table(80, false, false)
+            .with_output(OutputOptions { color: true })
+            .with_highlight(Some(highlight_query("quic", QueryMode::Words)));
+        let d = doc("rfc9001", "Using TLS to Secure QUIC", None);
+        assert_eq!(
+            render(&table, &d),
+            "rfc9001  RFC           Using TLS to Secure \x1b[1;4mQUIC\x1b[0m"
+        );
+        assert_eq!(
+            highlight_query("say \"hi\"", QueryMode::Phrase),
+            "\"say  hi \""
+        );
+    }
+
+    #[test]
+    fn render_aligns_columns() {
+        let rows = [
+            row(&doc(
+                "rfc9000",
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                NaiveDate::from_ymd_opt(2021, 5, 27),
+            )),
+            // Missing date leaves the column blank rather than shifting the title.
+            row(&doc("rfc791", "Internet Protocol", None)),
+        ];
+        assert_eq!(
+            table(80, false, false).render(&rows),
+            [
+                "rfc9000  RFC  2021-05  QUIC: A UDP-Based Multiplexed and Secure Transport",
+                "rfc791   RFC           Internet Protocol",
+            ]
+        );
+    }
+
+    #[test]
+    fn render_appends_obsolescence_marker() {
+        let mut d = doc(
+            "rfc2616",
+            "Hypertext Transfer Protocol -- HTTP/1.1",
+            NaiveDate::from_ymd_opt(1999, 6, 1),
+        );
+        d.obsoleted_by = vec![7230, 7231];
+        let line = render(&table(80, false, false), &d);
+        assert!(line.ends_with("[obsoleted by RFC 7230, RFC 7231]"));
+        assert!(line.chars().count() <= 79);
+    }
+
+    #[test]
+    fn obsolescence_marker_absent_for_current_documents() {
+        let d = doc("rfc9110", "HTTP Semantics", None);
+        assert_eq!(obsolescence_marker(&d), None);
+    }
+
+    #[test]
+    fn render_appends_draft_state_marker() {
+        let mut d = doc("draft-ietf-quic-transport-34", "QUIC", None);
+        assert_eq!(state_marker(&d), None);
+        for (state, marker) in [
+            (DraftState::Active, "[active]"),
+            (DraftState::Expired, "[expired]"),
+            (DraftState::Replaced, "[replaced]"),
+            (DraftState::Rfc, "[rfc]"),
+            (DraftState::Withdrawn, "[withdrawn]"),
+        ] {
+            d.draft_state = Some(state);
+            let line = render(&table(80, false, false), &d);
+            assert!(line.ends_with(&format!("QUIC {}", marker)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn active_only_keeps_rfcs_and_unknown_drafts() {
+        let mut draft = doc("draft-foo-bar-01", "Foo", None);
+        assert!(is_active(&draft));
+        draft.draft_state = Some(DraftState::Active);
+        assert!(is_active(&draft));
+        draft.draft_state = Some(DraftState::Expired);
+        assert!(!is_active(&draft));
+        draft.draft_state = Some(DraftState::Rfc);
+        assert!(!is_active(&draft));
+        assert!(is_active(&doc("rfc9000", "QUIC", None)));
+    }
+
+    #[test]
+    fn render_truncates_title_to_budget() {
+        let d = doc(
+            "rfc9000",
+            "QUIC: A UDP-Based Multiplexed and Secure Transport",
+            None,
+        );
+        let line = render(&table(40, false, false), &d);
+        assert!(line.ends_with("..."));
+        assert!(line.chars().count() <= 39);
+    }
+
+    #[test]
+    fn render_truncates_wide_titles_by_display_width() {
+        for title in [
+            "国際化ドメイン名のための登録とアプリケーションの手続き",
+            "Emoji 👩‍💻 in 🇯🇵 Document 🎉 Titles 🎉🎉🎉🎉🎉",
+        ] {
+            let line = render(&table(40, false, false), &doc("rfc5891", title, None));
+            assert!(line.ends_with("..."), "{}", line);
+            assert!(display_width(&line) <= 39, "{}", line);
+        }
+    }
+
+    fn fs_cache(dir: &tempfile::TempDir) -> CacheManager {
+        let cache = CacheManager::with_dir(dir.path().to_path_buf()).unwrap();
+        for (doc, title) in [
+            (
+                DocumentType::Rfc(4271),
+                "A Border Gateway Protocol 4 (BGP-4)",
+            ),
+            (
+                DocumentType::Draft("draft-ietf-idr-bgp-ls-segment-routing-ext-16".to_string()),
+                "BGP Link-State extensions for Segment Routing",
+            ),
+            (
+                DocumentType::Rfc(9000),
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+            ),
+        ] {
+            cache.store_document(&doc, Format::Text, "text").unwrap();
+            let mut meta = CacheMetadata::new(title, chrono::Utc::now());
+            if doc == DocumentType::Rfc(4271) {
+                meta.set_abstract(Some("BGP-4 is an inter-AS routing protocol.".into()));
+            }
+            DocumentCache::store_metadata(&cache, &doc, &meta).unwrap();
+        }
+        cache
+    }
+
+    fn names(result: &SearchResult) -> Vec<&str> {
+        result.documents.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    #[test]
+    fn search_local_matches_cached_titles() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = fs_cache(&dir);
+
+        let result = search_local(
+            Some(&cache),
+            None,
+            "bgp",
+            &SearchOptions::new(SearchFilter::Both),
+            25,
+        );
+        assert_eq!(
+            names(&result),
+            vec!["draft-ietf-idr-bgp-ls-segment-routing-ext-16", "rfc4271"]
+        );
+        assert_eq!(result.total_count, Some(2));
+
+        // Case-insensitive, and every word must match.
+        let result = search_local(
+            Some(&cache),
+            None,
+            "Segment bgp",
+            &SearchOptions::new(SearchFilter::Both),
+            25,
+        );
+        assert_eq!(
+            names(&result),
+            vec!["draft-ietf-idr-bgp-ls-segment-routing-ext-16"]
+        );
+        let result = search_local(
+            Some(&cache),
+            None,
+            "bgp quic",
+            &SearchOptions::new(SearchFilter::Both),
+            25,
+        );
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn search_local_applies_filter_and_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = fs_cache(&dir);
+
+        let result = search_local(
+            Some(&cache),
+            None,
+            "bgp",
+            &SearchOptions::new(SearchFilter::RfcsOnly),
+            25,
+        );
+        assert_eq!(names(&result), vec!["rfc4271"]);
+
+        let result = search_local(
+            Some(&cache),
+            None,
+            "bgp",
+            &SearchOptions::new(SearchFilter::Both),
+            1,
+        );
+        assert_eq!(result.len(), 1);
+        assert!(result.has_more);
+    }
+
+    #[test]
+    fn search_local_prefers_index_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = fs_cache(&dir);
+        let mut index = RfcIndex::default();
+        for (number, title, obsoleted_by, status) in [
+            (
+                1771,
+                "A Border Gateway Protocol 4 (BGP-4)",
+                vec![4271],
+                "HISTORIC",
+            ),
+            (
+                4271,
+                "A Border Gateway Protocol 4 (BGP-4)",
+                vec![],
+                "DRAFT STANDARD",
+            ),
+        ] {
+            index.entries.insert(
+                number,
+                RfcIndexEntry {
+                    number,
+                    title: title.to_string(),
+                    published: NaiveDate::from_ymd_opt(2006, 1, 1),
+                    status: Some(status.to_string()),
+                    obsoleted_by,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let result = search_local(
+            Some(&cache),
+            Some(&index),
+            "border gateway",
+            &SearchOptions::new(SearchFilter::Both),
+            25,
+        );
+        assert_eq!(names(&result), vec!["rfc4271", "rfc1771"]);
+        // The cached copy of 4271 has no date; the index entry does.
+        assert!(result.documents[0].published.is_some());
+        // Its recorded abstract is kept all the same.
+        assert_eq!(
+            result.documents[0].abstract_text.as_deref(),
+            Some("BGP-4 is an inter-AS routing protocol.")
+        );
+        assert_eq!(result.documents[1].abstract_text, None);
+        assert!(result.documents[1].is_obsoleted());
+
+        let historic = SearchOptions::new(SearchFilter::Both).with_status(vec![StdLevel::Historic]);
+        let result = search_local(Some(&cache), Some(&index), "border gateway", &historic, 25);
+        assert_eq!(names(&result), vec!["rfc1771"]);
+        assert_eq!(result.status, [StdLevel::Historic]);
+    }
+
+    #[test]
+    fn search_local_without_cache_or_index_is_empty() {
+        let result = search_local(
+            None,
+            None,
+            "bgp",
+            &SearchOptions::new(SearchFilter::Both),
+            25,
+        );
+        assert!(result.is_empty());
+        assert_eq!(result.query, "bgp");
+    }
 }
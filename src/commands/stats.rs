@@ -0,0 +1,113 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::analysis::{text_stats, TextStats, REQUIREMENT_KEYWORDS};
+use crate::models::{DocumentType, IndexLookup};
+
+use super::alias::resolve;
+use super::fetch::cached_text;
+use super::open_cache;
+
+/// `rfc stats --json` output.
+#[derive(Serialize)]
+struct Stats {
+    document: String,
+    /// From the RFC index when it knows, else the page footers counted
+    pages: Option<u32>,
+    #[serde(flatten)]
+    text: TextStats,
+}
+
+/// Print size and structure statistics for a document, fetching it if
+/// it isn't cached.
+pub async fn run(document: &str, json: bool, offline: bool, quiet: bool) -> Result<()> {
+    let cache = open_cache()?;
+    let doc_type = resolve(document)?;
+    let text = cached_text(&doc_type, &cache, offline, quiet).await?;
+
+    let indexed_pages = match (&doc_type, cache.load_rfc_index()) {
+        (DocumentType::Rfc(num), Some(index)) => match index.lookup(*num) {
+            IndexLookup::Published(entry) => entry.page_count,
+            _ => None,
+        },
+        _ => None,
+    };
+    let text = text_stats(&text);
+    let stats = Stats {
+        document: doc_type.name(),
+        pages: indexed_pages.or_else(|| (text.pages > 0).then_some(text.pages as u32)),
+        text,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("{}", doc_type);
+    if let Some(pages) = stats.pages {
+        field("Pages", &pages.to_string());
+    }
+    field("Lines", &stats.text.lines.to_string());
+    field("Words", &stats.text.words.to_string());
+    field("Sections", &stats.text.sections.to_string());
+    field("References", &stats.text.references.to_string());
+    field(
+        "Reading time",
+        &format!("about {} min", stats.text.reading_minutes),
+    );
+
+    let keywords: Vec<String> = REQUIREMENT_KEYWORDS
+        .iter()
+        .filter(|k| stats.text.keywords[*k] > 0)
+        .map(|k| format!("{} {}", k, stats.text.keywords[k]))
+        .collect();
+    if keywords.is_empty() {
+        field("Keywords", "none");
+    } else {
+        field("Keywords", &keywords.join(", "));
+    }
+    Ok(())
+}
+
+/// Print one `Label:  value` line with the values aligned.
+fn field(label: &str, value: &str) {
+    println!("{:<14}{}", format!("{}:", label), value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_shape_is_stable() {
+        let stats = Stats {
+            document: "rfc9000".to_string(),
+            pages: Some(151),
+            text: text_stats("1.  Introduction\n\n   Senders MUST retry.\n"),
+        };
+        let value = serde_json::to_value(&stats).unwrap();
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "document",
+                "keywords",
+                "lines",
+                "pages",
+                "reading_minutes",
+                "references",
+                "sections",
+                "words"
+            ]
+        );
+        assert_eq!(value["pages"], 151);
+        assert_eq!(value["keywords"]["MUST"], 1);
+        assert_eq!(value["keywords"]["MAY"], 0);
+    }
+}
@@ -0,0 +1,101 @@
+//! `rfc --status-line`: one short line about a document's place in the
+//! cache, for shell prompts and status bars. It never touches the network
+//! and reads only `index.json` (or the document's own files), so it's
+//! quick enough to run on every prompt.
+
+use anyhow::Result;
+
+use crate::cache::CachedDocument;
+use crate::models::{display_width, truncate_to_width, DocumentType};
+
+use super::alias::resolve;
+use super::open_cache;
+
+/// Print the status line for `document`, at most `max_width` cells wide.
+/// Cached or not, this succeeds.
+pub fn run(document: &str, max_width: usize) -> Result<()> {
+    let doc_type = resolve(document)?;
+    let cache = open_cache()?;
+    let cached = cache.cached_document(&doc_type);
+    println!("{}", render(&doc_type, cached.as_ref(), max_width));
+    Ok(())
+}
+
+/// `rfc9000 ✓cached 2024-01-03 · QUIC: A UDP-Based...`, or `rfc9000 ✗ not
+/// cached`. The title is cut to fit first, then, if that's not enough, the
+/// whole line.
+fn render(doc_type: &DocumentType, cached: Option<&CachedDocument>, max_width: usize) -> String {
+    let Some(cached) = cached else {
+        return truncate_to_width(&format!("{} ✗ not cached", doc_type.name()), max_width);
+    };
+    let mut line = format!("{} ✓cached", cached.doc_type.name());
+    if let Some(at) = cached.cached_at() {
+        line.push_str(&format!(" {}", at.format("%Y-%m-%d")));
+    }
+    let title = cached
+        .metadata
+        .as_ref()
+        .map(|meta| meta.title.trim())
+        .filter(|title| !title.is_empty());
+    if let Some(title) = title {
+        let room = max_width.saturating_sub(display_width(&line) + display_width(" · "));
+        // A title cut to less than a word is just noise.
+        if room >= 8 {
+            line.push_str(&format!(" · {}", truncate_to_width(title, room)));
+        }
+    }
+    truncate_to_width(&line, max_width)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::cache::CacheMetadata;
+    use crate::models::Format;
+
+    fn quic() -> CachedDocument {
+        CachedDocument {
+            doc_type: DocumentType::Rfc(9000),
+            metadata: Some(CacheMetadata::new(
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap(),
+            )),
+            formats: vec![Format::Text],
+            size: 4,
+            modified: None,
+            has_notes: false,
+        }
+    }
+
+    #[test]
+    fn cached_documents_show_date_and_title() {
+        let rfc = DocumentType::Rfc(9000);
+        assert_eq!(
+            render(&rfc, Some(&quic()), 80),
+            "rfc9000 ✓cached 2024-01-03 · QUIC: A UDP-Based Multiplexed and Secure Transport"
+        );
+        assert_eq!(
+            render(&rfc, Some(&quic()), 45),
+            "rfc9000 ✓cached 2024-01-03 · QUIC: A UDP-B..."
+        );
+        assert_eq!(render(&rfc, None, 80), "rfc9000 ✗ not cached");
+    }
+
+    #[test]
+    fn width_is_never_exceeded() {
+        let rfc = DocumentType::Rfc(9000);
+        for width in 10..100 {
+            for cached in [Some(quic()), None] {
+                let line = render(&rfc, cached.as_ref(), width);
+                assert!(display_width(&line) <= width, "{width}: {line}");
+            }
+        }
+        assert_eq!(
+            render(&rfc, Some(&quic()), 30),
+            "rfc9000 ✓cached 2024-01-03"
+        );
+        assert_eq!(render(&rfc, Some(&quic()), 20), "rfc9000 ✓cached 2...");
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use futures::stream::{self, StreamExt};
+
+use crate::api::DataTrackerClient;
+use crate::cache::{CacheManager, CacheMetadata, CachedDocument};
+use crate::models::DocumentType;
+
+use super::open_writable_cache;
+use super::progress;
+use crate::workflow::{refresh_cached, store_metadata, Refresh};
+
+/// Upper bound on concurrent refreshes.
+const SYNC_CONCURRENCY: usize = 4;
+
+pub struct Args {
+    /// Refresh RFCs too, not just drafts.
+    pub all: bool,
+    /// Only refresh entries cached longer ago than this.
+    pub older_than: Option<Duration>,
+    /// Don't draw a progress bar.
+    pub quiet: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Updated,
+    Unchanged,
+    Failed,
+    Skipped,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Updated => "updated",
+            Status::Unchanged => "unchanged",
+            Status::Failed => "failed",
+            Status::Skipped => "skipped",
+        }
+    }
+}
+
+/// Refresh cached documents from the network. Drafts are refreshed by
+/// default (RFCs are immutable apart from errata); requests are
+/// conditional on when each document was cached.
+pub async fn run(args: Args) -> Result<()> {
+    let cache = open_writable_cache()?;
+    let mut cached = cache.list_cached_with_metadata();
+    if cached.is_empty() {
+        println!("Cache is empty");
+        return Ok(());
+    }
+    cached.sort_by_key(|c| c.doc_type.name());
+
+    let fetcher = super::document_fetcher()?;
+    let datatracker = fetcher.datatracker();
+
+    let bar = progress::bulk_bar(cached.len() as u64, progress::enabled(args.quiet));
+    let mut counts = [0usize; 4];
+    let mut results = stream::iter(cached)
+        .map(|entry| {
+            let (cache, fetcher, datatracker, bar) = (&cache, &fetcher, &datatracker, &bar);
+            let skip = !wants_refresh(&entry, &args);
+            async move {
+                if skip {
+                    return (entry.doc_type, Status::Skipped, None);
+                }
+                bar.set_message(entry.doc_type.to_string());
+                let since = entry.metadata.as_ref().map(|m| m.cached_at);
+                let (status, error) =
+                    match refresh_cached(&entry.doc_type, cache, fetcher, since).await {
+                        Ok(refresh) => {
+                            record_refresh(&entry, refresh, cache, datatracker).await;
+                            match refresh {
+                                Refresh::Updated => (Status::Updated, None),
+                                Refresh::Unchanged => (Status::Unchanged, None),
+                            }
+                        }
+                        Err(e) => (Status::Failed, Some(e)),
+                    };
+                (entry.doc_type, status, error)
+            }
+        })
+        .buffer_unordered(SYNC_CONCURRENCY);
+
+    while let Some((doc_type, status, error)) = results.next().await {
+        counts[status as usize] += 1;
+        bar.suspend(|| match error {
+            Some(e) => println!("{:<10} {}: {:#}", status.label(), doc_type, e),
+            None => println!("{:<10} {}", status.label(), doc_type),
+        });
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let [updated, unchanged, failed, skipped] = counts;
+    println!(
+        "\n{} updated, {} unchanged, {} failed, {} skipped",
+        updated, unchanged, failed, skipped
+    );
+
+    let attempted = updated + unchanged + failed;
+    if attempted > 0 && failed == attempted {
+        anyhow::bail!("Every refresh failed");
+    }
+    Ok(())
+}
+
+fn wants_refresh(entry: &CachedDocument, args: &Args) -> bool {
+    if matches!(entry.doc_type, DocumentType::Rfc(_)) && !args.all {
+        return false;
+    }
+    match (args.older_than, &entry.metadata) {
+        (Some(age), Some(meta)) => Utc::now() - meta.cached_at > age,
+        // Without metadata we can't tell how old it is; assume stale.
+        _ => true,
+    }
+}
+
+/// Bring metadata in line with a successful refresh. New content gets a
+/// fresh title lookup; unchanged content just has its age reset so
+/// `--older-than` skips it next time. Failures here are only warnings.
+async fn record_refresh(
+    entry: &CachedDocument,
+    refresh: Refresh,
+    cache: &CacheManager,
+    datatracker: &DataTrackerClient,
+) {
+    let result = match (refresh, &entry.metadata) {
+        (Refresh::Unchanged, Some(meta)) => cache.store_metadata(
+            &entry.doc_type,
+            &CacheMetadata {
+                cached_at: Utc::now(),
+                ..meta.clone()
+            },
+        ),
+        _ => store_metadata(&entry.doc_type, cache, datatracker).await,
+    };
+    if let Err(e) = result {
+        eprintln!(
+            "Warning: Failed to update metadata for {}: {}",
+            entry.doc_type, e
+        );
+    }
+}
+
+/// Parse a positive age like `30m`, `12h`, `7d`, or `2w`.
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.len() - s.chars().last().map_or(0, char::len_utf8);
+    let (number, unit) = s.split_at(split);
+    let n: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid age '{}': expected e.g. 12h, 7d, 2w", s))?;
+    if n <= 0 {
+        return Err(format!("invalid age '{}': must be more than zero", s));
+    }
+    let age = match unit {
+        "m" => Duration::try_minutes(n),
+        "h" => Duration::try_hours(n),
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => return Err(format!("invalid age unit in '{}': use m, h, d, or w", s)),
+    };
+    age.ok_or_else(|| format!("invalid age '{}': too large", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Format;
+
+    fn entry(doc_type: DocumentType, age: Option<Duration>) -> CachedDocument {
+        CachedDocument {
+            doc_type,
+            metadata: age.map(|age| CacheMetadata::new("t", Utc::now() - age)),
+            formats: vec![Format::Text],
+            size: 0,
+            modified: None,
+            has_notes: false,
+        }
+    }
+
+    #[test]
+    fn parse_age_units() {
+        assert_eq!(parse_age("30m"), Ok(Duration::minutes(30)));
+        assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_age("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_age("2w"), Ok(Duration::weeks(2)));
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("").is_err());
+        assert!(parse_age("0d").is_err());
+        assert!(parse_age("-3d").is_err());
+        assert!(parse_age("99999999999999w").is_err());
+        assert!(parse_age("9223372036854775807m").is_err());
+    }
+
+    #[test]
+    fn rfcs_need_all() {
+        let rfc = entry(DocumentType::Rfc(9000), None);
+        let draft = entry(DocumentType::Draft("draft-ietf-foo-01".into()), None);
+        let drafts_only = Args {
+            all: false,
+            older_than: None,
+            quiet: true,
+        };
+        assert!(!wants_refresh(&rfc, &drafts_only));
+        assert!(wants_refresh(&draft, &drafts_only));
+        let all = Args {
+            all: true,
+            older_than: None,
+            quiet: true,
+        };
+        assert!(wants_refresh(&rfc, &all));
+    }
+
+    #[test]
+    fn older_than_skips_fresh_entries() {
+        let args = Args {
+            all: true,
+            older_than: Some(Duration::days(7)),
+            quiet: true,
+        };
+        assert!(!wants_refresh(
+            &entry(DocumentType::Rfc(1), Some(Duration::days(1))),
+            &args
+        ));
+        assert!(wants_refresh(
+            &entry(DocumentType::Rfc(1), Some(Duration::days(8))),
+            &args
+        ));
+        // Unknown age counts as stale.
+        assert!(wants_refresh(&entry(DocumentType::Rfc(1), None), &args));
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use crate::cache::DocumentCache;
+use crate::models::Format;
+
+use super::alias::resolve;
+use super::fetch::fetch_into;
+use super::open_writable_cache;
+
+/// Tag documents, fetching any that aren't cached yet so the tag has
+/// something to attach to.
+pub async fn add(tag: &str, documents: &[String], offline: bool, quiet: bool) -> Result<()> {
+    let cache = open_writable_cache()?;
+    for document in documents {
+        let doc_type = resolve(document)?;
+        if !cache.contains(&doc_type, Format::Text) && !cache.contains(&doc_type, Format::Html) {
+            if offline {
+                anyhow::bail!("{} is not cached and --offline was given", doc_type);
+            }
+            fetch_into(&doc_type, &cache, quiet).await?;
+        }
+        if cache.add_tag(&doc_type, tag)? {
+            println!("Tagged {} '{}'", doc_type, tag);
+        } else {
+            println!("{} was already tagged '{}'", doc_type, tag);
+        }
+    }
+    Ok(())
+}
+
+pub fn remove(tag: &str, documents: &[String]) -> Result<()> {
+    let cache = open_writable_cache()?;
+    for document in documents {
+        let doc_type = resolve(document)?;
+        if cache.remove_tag(&doc_type, tag)? {
+            println!("Untagged {} '{}'", doc_type, tag);
+        } else {
+            println!("{} was not tagged '{}'", doc_type, tag);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! Terminal helpers shared by the commands: geometry, wrapping, and
+//! hyperlinks. Color is decided by [`OutputOptions`](crate::render::OutputOptions).
+
+use std::env;
+use std::ops::Range;
+
+use crate::analysis::{draft_references, rfc_references};
+use crate::api::RFC_EDITOR_BASE_URL;
+use crate::models::DocumentType;
+
+/// Width used when stdout isn't a terminal and `$COLUMNS` is unset. Keeps
+/// piped output stable regardless of the user's window size.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Detect the output width in columns.
+///
+/// `$COLUMNS` wins when it parses, so users (and tests) can pin the
+/// layout; otherwise the size of the terminal attached to stdout is used,
+/// falling back to [`DEFAULT_WIDTH`].
+pub fn width() -> usize {
+    if let Some(cols) = env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.trim().parse::<usize>().ok())
+        .filter(|&c| c > 0)
+    {
+        return cols;
+    }
+
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(w), _)| w as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Word-wrap `text` to `width` columns, prefixing every line with
+/// `indent` spaces. Runs of whitespace (including the hard line breaks the
+/// Datatracker leaves in abstracts) collapse to single spaces. Words longer
+/// than the available width are placed on their own line unbroken.
+pub fn wrap(text: &str, width: usize, indent: usize) -> String {
+    let avail = width.saturating_sub(indent).max(1);
+    let pad = " ".repeat(indent);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let needed = if current.is_empty() {
+            word.chars().count()
+        } else {
+            current.chars().count() + 1 + word.chars().count()
+        };
+        if needed > avail && !current.is_empty() {
+            lines.push(format!("{}{}", pad, current));
+            current.clear();
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(format!("{}{}", pad, current));
+    }
+
+    lines.join("\n")
+}
+
+/// Cut `text` to at most `max_chars` characters at a word boundary,
+/// appending `...` when anything was dropped. Whitespace is normalized the
+/// same way as [`wrap`].
+pub fn excerpt(text: &str, max_chars: usize) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.chars().count() <= max_chars {
+        return normalized;
+    }
+
+    let budget = max_chars.saturating_sub(3);
+    let mut out = String::new();
+    for word in normalized.split(' ') {
+        let len = out.chars().count() + usize::from(!out.is_empty()) + word.chars().count();
+        if len > budget {
+            break;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if out.is_empty() {
+        // A single enormous word: fall back to a hard cut.
+        out = normalized.chars().take(budget).collect();
+    }
+    format!("{}...", out)
+}
+
+/// Turn RFC and draft citations in `text` into OSC 8 hyperlinks (RFCs
+/// to the RFC Editor, drafts to the Datatracker). The escapes occupy no
+/// columns, so the document's layout is unchanged.
+pub fn hyperlink_references(text: &str) -> String {
+    let mut links: Vec<(Range<usize>, String)> = rfc_references(text)
+        .into_iter()
+        .map(|(range, num)| (range, format!("{}/rfc/rfc{}", RFC_EDITOR_BASE_URL, num)))
+        .collect();
+    links.extend(
+        draft_references(text)
+            .into_iter()
+            .map(|(range, name)| (range, DocumentType::Draft(name).datatracker_url())),
+    );
+    links.sort_by_key(|(range, _)| range.start);
+
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (range, url) in links {
+        if range.start < copied {
+            continue;
+        }
+        out.push_str(&text[copied..range.start]);
+        out.push_str(&format!(
+            "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+            url,
+            &text[range.clone()]
+        ));
+        copied = range.end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_respects_width_and_indent() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            wrap(text, 20, 4),
+            "    The quick brown\n    fox jumps over\n    the lazy dog"
+        );
+        for line in wrap(text, 20, 4).lines() {
+            assert!(line.chars().count() <= 20);
+        }
+    }
+
+    #[test]
+    fn wrap_collapses_hard_breaks() {
+        assert_eq!(wrap("one\n  two\n\nthree", 80, 0), "one two three");
+    }
+
+    #[test]
+    fn wrap_keeps_overlong_words_whole() {
+        assert_eq!(
+            wrap("a supercalifragilistic b", 10, 2),
+            "  a\n  supercalifragilistic\n  b"
+        );
+    }
+
+    #[test]
+    fn wrap_empty() {
+        assert_eq!(wrap("", 80, 2), "");
+        assert_eq!(wrap("   \n ", 80, 2), "");
+    }
+
+    #[test]
+    fn excerpt_short_text_untouched() {
+        assert_eq!(excerpt("short  abstract\n", 400), "short abstract");
+    }
+
+    #[test]
+    fn excerpt_cuts_at_word_boundary() {
+        assert_eq!(excerpt("alpha beta gamma delta", 15), "alpha beta...");
+        assert!(excerpt("alpha beta gamma delta", 15).chars().count() <= 15);
+    }
+
+    #[test]
+    fn excerpt_single_long_word() {
+        assert_eq!(excerpt("abcdefghijklmnop", 8), "abcde...");
+    }
+
+    #[test]
+    fn hyperlinks_wrap_citations_without_changing_text() {
+        let text = "per [RFC9000] and\n   draft-ietf-foo-bar-01.";
+        let linked = hyperlink_references(text);
+        assert_eq!(
+            linked,
+            "per [\x1b]8;;https://www.rfc-editor.org/rfc/rfc9000\x1b\\RFC9000\x1b]8;;\x1b\\] and\n   \
+             \x1b]8;;https://datatracker.ietf.org/doc/draft-ietf-foo-bar-01/\x1b\\\
+             draft-ietf-foo-bar-01\x1b]8;;\x1b\\."
+        );
+        // Stripping the escapes gives back the original text.
+        let stripped: String = linked
+            .split("\x1b]8;;")
+            .enumerate()
+            .map(|(i, part)| match i {
+                0 => part,
+                _ => part.split_once("\x1b\\").map_or(part, |(_, rest)| rest),
+            })
+            .collect();
+        assert_eq!(stripped, text);
+    }
+}
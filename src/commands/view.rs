@@ -1,41 +1,515 @@
+use std::io::IsTerminal;
+use std::sync::Arc;
+
 use anyhow::{Context, Result};
+use chrono::Utc;
 
+use crate::analysis::abstract_section;
 use crate::api::{DataTrackerClient, DocumentFetcher};
-use crate::cache::CacheManager;
-use crate::models::{DocumentType, Format};
+use crate::cache::{recently_accessed, CacheManager, DocumentCache};
+use crate::config::Config;
+use crate::iana::iana_considerations;
+use crate::models::{Document, DocumentType, Format, IndexLookup};
+use crate::render::{document_header, highlight, no_color_requested, to_roff, OutputOptions};
+use crate::workflow::{check_against_index, load_document};
+
+use super::alias::resolve;
+use super::open_cache;
+use super::progress::{self, DownloadBar};
+use super::related::related_line;
+use super::{draft_status, terminal, viewer};
+
+#[derive(Default)]
+pub struct Args {
+    /// Program to open the text with instead of `$EDITOR`/`$PAGER`.
+    pub open_with: Option<String>,
+    /// Open the Datatracker page in a browser instead.
+    pub web: bool,
+    /// Ignore any bookmark.
+    pub from_start: bool,
+    /// Make citations clickable with OSC 8 escapes.
+    pub hyperlinks: bool,
+    /// Don't note drafts that have expired, been replaced, or become RFCs.
+    pub no_banner: bool,
+    /// Put a header of the document's metadata above its text.
+    pub header: bool,
+    /// Leave the text in the cache's `tmp/` rather than a tempfile.
+    pub keep_temp: bool,
+    /// Don't pass the document's title to the viewer (less's prompt, the
+    /// terminal title, `$RFC_DOC_TITLE`).
+    pub plain_viewer: bool,
+    /// Print the title and this many lines instead of opening a viewer.
+    pub head: Option<usize>,
+    /// Print only the abstract instead of opening a viewer.
+    pub abstract_only: bool,
+    /// Print only the IANA Considerations and the registries they name.
+    pub iana: bool,
+    /// Print `iana` as JSON.
+    pub json: bool,
+    /// Format as a man page and show it with `man` instead of the viewer.
+    pub man: bool,
+    /// Print these lines instead of opening a viewer.
+    pub lines: Option<LineRange>,
+    /// Prefix printed lines with their line numbers.
+    pub number: bool,
+    /// After the viewer closes, list related documents known from the
+    /// cache.
+    pub suggest: bool,
+    pub offline: bool,
+    pub quiet: bool,
+}
+
+/// An inclusive, 1-based line range for `--lines`; either end may be
+/// left open (`120-`, `-80`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
 
-use super::fetch_pipeline::fetch_and_cache;
-use super::viewer;
+impl LineRange {
+    /// The first and last line to print from a document of `total`
+    /// lines. An end past the document is cut short; a start past it is
+    /// an error.
+    fn bounds(self, total: usize) -> Result<(usize, usize)> {
+        let start = self.start.unwrap_or(1);
+        if start > total {
+            anyhow::bail!(
+                "Line {} is past the end of the document ({} lines)",
+                start,
+                total
+            );
+        }
+        Ok((start, self.end.map_or(total, |end| end.min(total))))
+    }
+}
+
+/// Parse a line range like `120-180`, `120-`, `-80`, or `120`.
+pub fn parse_line_range(s: &str) -> Result<LineRange, String> {
+    let invalid = || {
+        format!(
+            "invalid line range '{}': expected e.g. 120-180, 120-, or -80",
+            s
+        )
+    };
+    let line = |part: &str| -> Result<Option<usize>, String> {
+        match part.trim() {
+            "" => Ok(None),
+            n => match n.parse() {
+                Ok(0) => Err(format!("invalid line range '{}': lines start at 1", s)),
+                Ok(n) => Ok(Some(n)),
+                Err(_) => Err(invalid()),
+            },
+        }
+    };
+    let range = match s.split_once('-') {
+        Some((start, end)) => LineRange {
+            start: line(start)?,
+            end: line(end)?,
+        },
+        None => {
+            let only = line(s)?.ok_or_else(invalid)?;
+            LineRange {
+                start: Some(only),
+                end: Some(only),
+            }
+        }
+    };
+    match range {
+        LineRange {
+            start: None,
+            end: None,
+        } => Err(invalid()),
+        LineRange {
+            start: Some(start),
+            end: Some(end),
+        } if start > end => Err(format!(
+            "invalid line range '{}': {} comes after {}",
+            s, start, end
+        )),
+        range => Ok(range),
+    }
+}
 
 /// Default-path command: cache-or-fetch then open in a viewer.
-pub async fn run(document: &str, open_with: Option<&str>, web: bool) -> Result<()> {
-    let doc_type = DocumentType::from_user_input(document);
+pub async fn run(document: &str, args: &Args) -> Result<()> {
+    let doc_type = resolve(document)?;
 
-    if web {
+    if args.web {
         return open_in_browser(&doc_type);
     }
-    let cache = CacheManager::new()?;
+    let cache = open_cache()?;
+    let fetcher = super::document_fetcher()?
+        .with_observer(Arc::new(DownloadBar::new(progress::enabled(args.quiet))));
+    let datatracker = fetcher.datatracker();
 
-    let content = match cache.get_document(&doc_type, Format::Text) {
-        Some(cached) => {
-            eprintln!("Using cached copy of {}", doc_type);
-            cached
+    // A recorded abstract needs neither the text nor the network.
+    if args.abstract_only {
+        if let Some(text) = cache
+            .get_metadata(&doc_type)
+            .and_then(|meta| meta.recorded_abstract().map(str::to_string))
+        {
+            println!("{}", terminal::wrap(&text, terminal::width(), 0));
+            return Ok(());
         }
-        None => {
-            let http = crate::api::build_http_client()?;
-            let fetcher = DocumentFetcher::with_client(http.clone());
-            let datatracker = DataTrackerClient::with_client(http);
-            fetch_and_cache(&doc_type, &cache, &fetcher, &datatracker).await?
+    }
+    let content = load(&doc_type, &cache, &fetcher, &datatracker, args.offline).await?;
+    if args.abstract_only {
+        return print_abstract(&doc_type, &content);
+    }
+    if args.iana {
+        return print_iana(&doc_type, &content, args.json);
+    }
+    if let Some(lines) = args.head {
+        print_head(&doc_type, &cache, &content, lines, args.number);
+        return Ok(());
+    }
+    if let Some(range) = args.lines {
+        let (start, end) = range.bounds(content.lines().count())?;
+        print_lines(&content, start, end, args.number);
+        return Ok(());
+    }
+    if !args.no_banner {
+        draft_status::warn_if_stale(&doc_type, &cache, &datatracker, args.offline).await;
+    }
+    if args.man {
+        return viewer::open_man(&to_roff(&content, &doc_type));
+    }
+
+    let start_line = if args.from_start {
+        None
+    } else {
+        resume_line(&doc_type, &cache, &content)
+    };
+    // Worked out before the viewer takes the text; only cached facts are
+    // used, so this never waits on the network.
+    let related = (args.suggest && !args.quiet && std::io::stdout().is_terminal())
+        .then(|| related_line(&doc_type, &content, &cache))
+        .flatten();
+    // Composed for this viewing only: the cache keeps the text as
+    // published, which is what bookmarks count lines of.
+    let (content, start_line) = if args.header {
+        let header = document_header(
+            &header_document(&doc_type, &cache),
+            terminal::width().min(HEADER_WIDTH),
+        );
+        let shift = header.lines().count();
+        (header + &content, start_line.map(|line| line + shift))
+    } else {
+        (content, start_line)
+    };
+    let title = display_title(&doc_type, &cache);
+    show(content, &doc_type.name(), &title, &cache, args, start_line)?;
+    if let Some(line) = related {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// `--cat`: load each document in turn and open them all in one viewer
+/// session, each under a banner. Documents that fail to load are skipped
+/// with a warning; only a total failure is an error.
+pub async fn run_concatenated(documents: &[String], args: &Args) -> Result<()> {
+    let cache = open_cache()?;
+    let fetcher = super::document_fetcher()?
+        .with_observer(Arc::new(DownloadBar::new(progress::enabled(args.quiet))));
+    let datatracker = fetcher.datatracker();
+
+    let mut loaded = Vec::new();
+    for document in documents {
+        let result = match resolve(document) {
+            Ok(doc_type) => load(&doc_type, &cache, &fetcher, &datatracker, args.offline)
+                .await
+                .map(|content| (doc_type, content)),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((doc_type, content)) => {
+                if !args.no_banner {
+                    draft_status::warn_if_stale(&doc_type, &cache, &datatracker, args.offline)
+                        .await;
+                }
+                loaded.push((doc_type, content))
+            }
+            Err(e) => eprintln!("Warning: {}: {:#}", document, e),
+        }
+    }
+    if loaded.is_empty() {
+        anyhow::bail!("No document could be loaded");
+    }
+    let name = loaded
+        .iter()
+        .map(|(doc_type, _)| doc_type.name())
+        .collect::<Vec<_>>()
+        .join("+");
+    let title = loaded
+        .iter()
+        .map(|(doc_type, _)| doc_type.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    show(concatenate(&loaded), &name, &title, &cache, args, None)
+}
+
+/// Cache-or-fetch `doc_type`, refusing to hit the network when `offline`.
+async fn load(
+    doc_type: &DocumentType,
+    cache: &CacheManager,
+    fetcher: &DocumentFetcher,
+    datatracker: &DataTrackerClient,
+    offline: bool,
+) -> Result<String> {
+    if !cache.contains(doc_type, Format::Text) {
+        if offline {
+            anyhow::bail!("{} is not cached and --offline was given", doc_type);
         }
+        check_against_index(
+            doc_type,
+            cache.load_rfc_index().as_ref(),
+            Config::current().force_fetch,
+        )?;
+    }
+    load_document(doc_type, cache, fetcher, datatracker).await
+}
+
+/// Apply the display options and hand `content`, named `name`, to the
+/// viewer, which is told its `title` unless `--plain-viewer` was given.
+fn show(
+    content: String,
+    name: &str,
+    title: &str,
+    cache: &CacheManager,
+    args: &Args,
+    start_line: Option<usize>,
+) -> Result<()> {
+    // Highlight first: its anchors wrap whole `[RFC1234]` tags, which
+    // leaves the citations inside them intact for the hyperlinker.
+    let renders_ansi =
+        viewer::renders_ansi(args.open_with.as_deref()) && std::io::stdout().is_terminal();
+    let content = highlight(
+        &content,
+        &OutputOptions::new(Config::current().color, renders_ansi),
+    );
+    let content = if args.hyperlinks && !no_color_requested() {
+        terminal::hyperlink_references(&content)
+    } else {
+        content
     };
+    let keep_in = if args.keep_temp {
+        cache.ensure_writable()?;
+        Some(cache.temp_dir())
+    } else {
+        None
+    };
+    viewer::open(
+        &content,
+        name,
+        args.open_with.as_deref(),
+        start_line,
+        keep_in.as_deref(),
+        (!args.plain_viewer).then_some(title),
+    )
+}
 
-    viewer::open(&content, open_with)?;
+/// `--head`: the document's name and title, then its first `lines` lines.
+fn print_head(
+    doc_type: &DocumentType,
+    cache: &CacheManager,
+    content: &str,
+    lines: usize,
+    number: bool,
+) {
+    println!("{}\n", display_title(doc_type, cache));
+    print_lines(content, 1, lines, number);
+}
+
+/// Widest `--header` composed, the width of the text below it.
+const HEADER_WIDTH: usize = 72;
+
+/// What's known about `doc_type` without asking the network, for its
+/// header: an RFC's entry in the synced index, or else the cached title
+/// and, for a draft, its cached status.
+fn header_document(doc_type: &DocumentType, cache: &CacheManager) -> Document {
+    if let DocumentType::Rfc(number) = doc_type {
+        if let Some(index) = cache.load_rfc_index() {
+            if let IndexLookup::Published(entry) = index.lookup(*number) {
+                return entry.to_document();
+            }
+        }
+    }
+    let meta = cache.get_metadata(doc_type);
+    let title = meta.as_ref().map(|m| m.title.clone()).unwrap_or_default();
+    let mut doc = Document::new(doc_type.name(), title, doc_type.clone());
+    if let Some(status) = meta.and_then(|m| m.draft_status) {
+        doc.draft_state = draft_status::draft_state(&status, Utc::now().date_naive());
+        doc.expires = status.expires;
+    }
+    doc
+}
+
+/// `RFC 9000: QUIC: ...` when the cache has the title, else `RFC 9000`.
+fn display_title(doc_type: &DocumentType, cache: &CacheManager) -> String {
+    match cache
+        .get_metadata(doc_type)
+        .map(|meta| meta.title)
+        .filter(|title| !title.trim().is_empty())
+    {
+        Some(title) => format!("{}: {}", doc_type, title),
+        None => doc_type.to_string(),
+    }
+}
+
+/// Print lines `start..=end` (1-based) of `content`, optionally numbered.
+/// Form feeds are dropped so page breaks don't garble the terminal.
+fn print_lines(content: &str, start: usize, end: usize, number: bool) {
+    let width = end.to_string().len();
+    for (i, line) in content
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take(end + 1 - start)
+    {
+        let line = line.trim_start_matches('\x0c');
+        if number {
+            println!("{:>width$}  {}", i + 1, line, width = width);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// `--abstract` for a document with no recorded abstract: the Abstract
+/// section of its text.
+fn print_abstract(doc_type: &DocumentType, content: &str) -> Result<()> {
+    let text =
+        abstract_section(content).with_context(|| format!("No abstract found in {}", doc_type))?;
+    println!("{}", text);
+    Ok(())
+}
+
+/// `--iana`: the IANA Considerations, then the registries they name under
+/// each subsection heading.
+fn print_iana(doc_type: &DocumentType, content: &str, json: bool) -> Result<()> {
+    let section = iana_considerations(content)
+        .with_context(|| format!("No IANA Considerations section in {}", doc_type))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&section)?);
+        return Ok(());
+    }
+    println!("{}.  {}\n", section.number, section.title);
+    println!("{}\n", section.text);
+    if section.no_actions {
+        println!("Summary: no IANA actions");
+        return Ok(());
+    }
+    if section.registries.is_empty() {
+        println!("Summary: no registries named");
+        return Ok(());
+    }
+    let count = section.registries.len();
+    println!(
+        "Summary: {} {}",
+        count,
+        if count == 1 { "registry" } else { "registries" }
+    );
+    let mut listed = 0;
+    for sub in &section.subsections {
+        let depth = sub.number.matches('.').count();
+        let indent = "  ".repeat(depth);
+        println!("{}{}.  {}", indent, sub.number, sub.title);
+        for registry in &sub.registries {
+            println!("{}  - {}", indent, registry);
+        }
+        listed += sub.registries.len();
+    }
+    if listed == 0 {
+        for registry in &section.registries {
+            println!("  - {}", registry);
+        }
+    }
     Ok(())
 }
 
+/// Join documents in order, each preceded by a `==== RFC 8200 ====` banner.
+fn concatenate(docs: &[(DocumentType, String)]) -> String {
+    let mut out = String::new();
+    for (doc_type, content) in docs {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("==== {} ====\n\n", doc_type));
+        out.push_str(content);
+        if !content.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The bookmarked line to reopen `doc_type` at, if it still fits `content`.
+fn resume_line(doc_type: &DocumentType, cache: &dyn DocumentCache, content: &str) -> Option<usize> {
+    let mark = cache.get_metadata(doc_type)?.bookmark?;
+    if !mark.fits(content.lines().count()) {
+        return None;
+    }
+    eprintln!(
+        "Resuming {} at line {} (--from-start to begin at the top)",
+        doc_type, mark.line
+    );
+    Some(mark.line)
+}
+
+/// The cached document opened most recently, for `rfc --last`.
+pub fn last_accessed() -> Result<DocumentType> {
+    let cache = open_cache()?;
+    recently_accessed(cache.list_cached_with_metadata(), 1)
+        .pop()
+        .map(|cached| cached.doc_type)
+        .context("No document has been opened from the cache yet")
+}
+
 fn open_in_browser(doc_type: &DocumentType) -> Result<()> {
     let url = doc_type.datatracker_url();
     eprintln!("Opening {} in browser...", doc_type);
     opener::open(&url).with_context(|| format!("Failed to open URL: {}", url))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_ranges_parse_open_and_closed_forms() {
+        let range = |start, end| LineRange { start, end };
+        assert_eq!(parse_line_range("120-180"), Ok(range(Some(120), Some(180))));
+        assert_eq!(parse_line_range("120-"), Ok(range(Some(120), None)));
+        assert_eq!(parse_line_range("-80"), Ok(range(None, Some(80))));
+        assert_eq!(parse_line_range("7"), Ok(range(Some(7), Some(7))));
+        for bad in ["", "-", "180-120", "0-5", "a-b", "1-2-3"] {
+            assert!(parse_line_range(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn line_range_bounds_clamp_the_end_but_not_the_start() {
+        let range = parse_line_range("5-").unwrap();
+        assert_eq!(range.bounds(10).unwrap(), (5, 10));
+        assert_eq!(
+            parse_line_range("-80").unwrap().bounds(10).unwrap(),
+            (1, 10)
+        );
+        assert!(parse_line_range("11-12").unwrap().bounds(10).is_err());
+    }
+
+    #[test]
+    fn documents_are_joined_in_order_under_banners() {
+        let docs = vec![
+            (DocumentType::Rfc(8200), "IPv6\n".to_string()),
+            (DocumentType::Rfc(8201), "PMTUD".to_string()),
+        ];
+        assert_eq!(
+            concatenate(&docs),
+            "==== RFC 8200 ====\n\nIPv6\n\n==== RFC 8201 ====\n\nPMTUD\n"
+        );
+    }
+}
@@ -1,59 +1,312 @@
-//! Spawn a user-supplied program against a document.
+//! Spawn a user-supplied program against a document (or, for notes, the
+//! user's editor against a file in the cache).
 //!
 //! The document is always written to a tempfile and passed as the final
 //! argument to the program — this works uniformly for editors and pagers
 //! and avoids second-guessing what kind of viewer the user picked.
 
+use std::borrow::Cow;
 use std::env;
-use std::io::Write;
-use std::process::Command;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
 
 use anyhow::{Context, Result};
 
 /// Open `text` in a viewer.
 ///
+/// The file is named after `name` (`rfc9000-a1B2c3.txt`) so editors treat
+/// it as text and their buffers are identifiable. With `keep_in`, it's
+/// written to `<keep_in>/<name>.txt` instead and left there for editors
+/// that return before they're done with it, and the path is printed.
+///
 /// Resolution order when `open_with` is `None`: `$EDITOR` → `$PAGER` →
-/// no-op. The no-op case (neither var set, no `--open-with`) lets `rfc
+/// the platform default. There is no default outside Windows, so with
+/// neither var set and no `--open-with` this is a no-op, which lets `rfc
 /// fetch` work on headless systems without forcing the user to invent a
-/// viewer.
-pub fn open(text: &str, open_with: Option<&str>) -> Result<()> {
-    let viewer_str = match open_with {
-        Some(program) => program.to_string(),
-        None => {
-            if let Ok(editor) = env::var("EDITOR") {
-                editor
-            } else if let Ok(pager) = env::var("PAGER") {
-                pager
-            } else {
-                return Ok(());
-            }
-        }
+/// viewer. On Windows, where neither var is usually set, it's Notepad.
+///
+/// `start_line` is passed as `+N` ahead of the file to the pagers and
+/// editors known to take it (see [`takes_start_line`]). Any other viewer
+/// would read `+N` as a file, so it gets just the file and the line is
+/// printed instead.
+///
+/// With a `title` (`RFC 9000: QUIC: ...`), the viewer is told which
+/// document it's showing: the terminal's title is set while it runs,
+/// `less` gets a `-Ps` prompt naming it, and every viewer gets it in
+/// `$RFC_DOC_TITLE` for editor plugins.
+pub fn open(
+    text: &str,
+    name: &str,
+    open_with: Option<&str>,
+    start_line: Option<usize>,
+    keep_in: Option<&Path>,
+    title: Option<&str>,
+) -> Result<()> {
+    let Some(viewer_str) = viewer_command(open_with) else {
+        return Ok(());
     };
 
     let (program, extra_args) = split_command(&viewer_str)
         .with_context(|| format!("Empty viewer command: {:?}", viewer_str))?;
 
-    let mut temp_file = tempfile::NamedTempFile::new()?;
+    let text = if cfg!(windows) {
+        Cow::Owned(to_crlf(text))
+    } else {
+        Cow::Borrowed(text)
+    };
+    let start_line = match start_line {
+        Some(line) if !takes_start_line(&program) => {
+            eprintln!("Bookmarked at line {}", line);
+            None
+        }
+        line => line,
+    };
+    let run = |path: &Path| -> Result<()> {
+        let mut command = Command::new(&program);
+        command.args(&extra_args);
+        if let Some(title) = title {
+            if is_less(&program) {
+                command.arg(less_prompt(title));
+            }
+            command.env("RFC_DOC_TITLE", title);
+        }
+        let _terminal_title = title.map(TerminalTitle::set);
+        let status = command
+            .args(start_line.map(|line| format!("+{}", line)))
+            .arg(path)
+            .status()
+            .with_context(|| format!("Failed to start viewer: {}", program))?;
+        if !status.success() {
+            anyhow::bail!("Viewer exited with non-zero status");
+        }
+        Ok(())
+    };
+
+    if let Some(dir) = keep_in {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.txt", name));
+        fs::write(&path, text.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let result = run(&path);
+        eprintln!("Kept {}", path.display());
+        return result;
+    }
+
+    let mut temp_file = tempfile::Builder::new()
+        .prefix(&format!("{}-", name))
+        .suffix(".txt")
+        .tempfile()?;
     temp_file.write_all(text.as_bytes())?;
     temp_file.flush()?;
+    run(temp_file.path())
+}
+
+/// `less`'s short prompt naming `title`, then `(END)` or how far in the
+/// reader is, as less's own prompt would.
+fn less_prompt(title: &str) -> String {
+    let mut prompt = String::from("-Ps");
+    for c in title.chars() {
+        if matches!(c, '?' | ':' | '.' | '%' | '\\') {
+            prompt.push('\\');
+        }
+        prompt.push(c);
+    }
+    prompt.push_str("?e (END):?pB (%pB\\%)..");
+    prompt
+}
+
+/// The terminal's title, set to a document's for as long as this lives.
+/// The old title is pushed on xterm's title stack and popped on drop;
+/// terminals without the stack keep the document's.
+struct TerminalTitle {
+    active: bool,
+}
+
+impl TerminalTitle {
+    fn set(title: &str) -> Self {
+        let active = io::stdout().is_terminal();
+        if active {
+            let title: String = title.chars().filter(|c| !c.is_control()).collect();
+            print!("\x1b[22;0t\x1b]0;{}\x07", title);
+            let _ = io::stdout().flush();
+        }
+        Self { active }
+    }
+}
+
+impl Drop for TerminalTitle {
+    fn drop(&mut self) {
+        if self.active {
+            print!("\x1b[23;0t");
+            let _ = io::stdout().flush();
+        }
+    }
+}
+
+/// Show `roff` as a man page with `man -l -`. Without `man`, it's formatted
+/// with `groff` and paged with `$PAGER` (default `less -R`).
+pub fn open_man(roff: &str) -> Result<()> {
+    let status = match feed(Command::new("man").args(["-l", "-"]), roff) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let mut groff = match Command::new("groff")
+                .args(["-k", "-man", "-Tutf8"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+            {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    anyhow::bail!("--man needs man or groff, and neither was found")
+                }
+                groff => groff.context("Failed to start groff")?,
+            };
+            let pager = env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+            let (program, args) = split_command(&pager)
+                .with_context(|| format!("Empty pager command: {:?}", pager))?;
+            let mut paging = Command::new(&program)
+                .args(&args)
+                .stdin(groff.stdout.take().context("groff has no stdout")?)
+                .spawn()
+                .with_context(|| format!("Failed to start pager: {}", program))?;
+            write_all(&mut groff, roff)?;
+            groff.wait()?;
+            paging.wait()?
+        }
+        status => status.context("Failed to start man")?,
+    };
+    if !status.success() {
+        anyhow::bail!("man exited with non-zero status");
+    }
+    Ok(())
+}
+
+/// Run `command` with `input` on its stdin and wait for it.
+fn feed(command: &mut Command, input: &str) -> io::Result<std::process::ExitStatus> {
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    write_all(&mut child, input)?;
+    child.wait()
+}
+
+/// Write `input` to `child`'s stdin and close it. A reader that quits
+/// early (the user leaving the pager) isn't an error.
+fn write_all(child: &mut Child, input: &str) -> io::Result<()> {
+    let Some(mut stdin) = child.stdin.take() else {
+        return Ok(());
+    };
+    match stdin.write_all(input.as_bytes()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// The viewer [`open`] would run, if any.
+fn viewer_command(open_with: Option<&str>) -> Option<String> {
+    match open_with {
+        Some(program) => Some(program.to_string()),
+        None => env::var("EDITOR")
+            .or_else(|_| env::var("PAGER"))
+            .ok()
+            .or_else(|| default_viewer().map(String::from)),
+    }
+}
+
+/// The viewer used when nothing is configured: Notepad on Windows, where
+/// `$EDITOR`/`$PAGER` are rarely set and there's no `less`; none elsewhere.
+fn default_viewer() -> Option<&'static str> {
+    cfg!(windows).then_some("notepad")
+}
+
+/// Whether `program` opens at `+N`: the pagers and terminal editors that
+/// do, recognized by name (by either separator, with or without `.exe`).
+fn takes_start_line(program: &str) -> bool {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let name = name.to_ascii_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    matches!(
+        name,
+        "less" | "more" | "most" | "vi" | "vim" | "nvim" | "nano" | "emacs"
+    )
+}
+
+/// `text` with every bare `\n` turned into `\r\n`, so Notepad shows lines.
+fn to_crlf(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+/// Whether the viewer [`open`] would run shows ANSI colors rather than
+/// the raw escapes: `less` with `-R`/`-r` (on the command line or in
+/// `$LESS`).
+pub fn renders_ansi(open_with: Option<&str>) -> bool {
+    let Some((program, args)) = viewer_command(open_with).as_deref().and_then(split_command) else {
+        return false;
+    };
+    let less_env = env::var("LESS").unwrap_or_default();
+    is_less_with_raw_control(&program, &args, &less_env)
+}
+
+fn is_less_with_raw_control(program: &str, args: &[String], less_env: &str) -> bool {
+    let raw = |flags: &str| flags.contains(['R', 'r']);
+    is_less(program)
+        && (raw(less_env)
+            || args.iter().any(|arg| {
+                arg == "--RAW-CONTROL-CHARS"
+                    || (!arg.starts_with("--") && arg.starts_with('-') && raw(arg))
+            }))
+}
+
+fn is_less(program: &str) -> bool {
+    Path::new(program)
+        .file_name()
+        .is_some_and(|name| name == "less")
+}
+
+/// Open `path` for editing in `$VISUAL` or `$EDITOR`, waiting for the
+/// editor to exit. Unlike [`open`], the file is edited in place.
+pub fn edit(path: &Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .or_else(|| default_viewer().map(String::from))
+        .context("Set $EDITOR (or $VISUAL) to edit notes")?;
+    let (program, extra_args) =
+        split_command(&editor).with_context(|| format!("Empty editor command: {:?}", editor))?;
 
     let status = Command::new(&program)
         .args(&extra_args)
-        .arg(temp_file.path())
+        .arg(path)
         .status()
-        .with_context(|| format!("Failed to start viewer: {}", program))?;
+        .with_context(|| format!("Failed to start editor: {}", program))?;
 
     if !status.success() {
-        anyhow::bail!("Viewer exited with non-zero status");
+        anyhow::bail!("Editor exited with non-zero status");
     }
-
     Ok(())
 }
 
-/// Split a viewer command string into `(program, args)` on whitespace.
-/// Returns `None` when the input is empty/whitespace-only.
+/// Split a viewer command string into `(program, args)` on whitespace,
+/// keeping double- or single-quoted runs together so programs under paths
+/// with spaces (`"C:\Program Files\Notepad++\notepad++.exe" -multiInst`)
+/// work. Backslashes are literal. Returns `None` when the input is
+/// empty/whitespace-only.
 fn split_command(s: &str) -> Option<(String, Vec<String>)> {
-    let mut parts = s.split_whitespace().map(String::from);
+    let mut parts = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    for c in s.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.get_or_insert_with(String::new).push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => parts.extend(current.take()),
+            None => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    parts.extend(current);
+    let mut parts = parts.into_iter();
     let program = parts.next()?;
     Some((program, parts.collect()))
 }
@@ -79,9 +332,157 @@ mod tests {
         );
     }
 
+    #[test]
+    fn less_needs_raw_control_chars() {
+        let args = |s: &str| split_command(s).unwrap().1;
+        assert!(is_less_with_raw_control("less", &args("less -R"), ""));
+        assert!(is_less_with_raw_control(
+            "/usr/bin/less",
+            &args("less -FRX"),
+            ""
+        ));
+        assert!(is_less_with_raw_control("less", &[], "-FRX"));
+        assert!(is_less_with_raw_control(
+            "less",
+            &args("less --RAW-CONTROL-CHARS"),
+            ""
+        ));
+        assert!(!is_less_with_raw_control("less", &[], ""));
+        assert!(!is_less_with_raw_control(
+            "less",
+            &args("less --follow-name"),
+            ""
+        ));
+        assert!(!is_less_with_raw_control("vim", &args("vim -R"), ""));
+    }
+
     #[test]
     fn split_command_empty() {
         assert_eq!(split_command(""), None);
         assert_eq!(split_command("   "), None);
     }
+
+    #[test]
+    fn split_command_keeps_quoted_paths_together() {
+        assert_eq!(
+            split_command(r#""C:\Program Files\Notepad++\notepad++.exe" -multiInst"#),
+            Some((
+                r"C:\Program Files\Notepad++\notepad++.exe".to_string(),
+                vec!["-multiInst".to_string()]
+            ))
+        );
+        assert_eq!(
+            split_command("'/opt/my editor/bin/ed' --title 'RFC text'"),
+            Some((
+                "/opt/my editor/bin/ed".to_string(),
+                vec!["--title".to_string(), "RFC text".to_string()]
+            ))
+        );
+        assert_eq!(
+            split_command(r#"vim "" -R"#),
+            Some(("vim".to_string(), vec![String::new(), "-R".to_string()]))
+        );
+    }
+
+    #[test]
+    fn to_crlf_only_converts_bare_newlines() {
+        assert_eq!(to_crlf("a\nb\r\nc\n"), "a\r\nb\r\nc\r\n");
+        assert_eq!(to_crlf("\n\n"), "\r\n\r\n");
+    }
+
+    #[test]
+    fn only_known_viewers_take_a_start_line() {
+        assert!(takes_start_line("less"));
+        assert!(takes_start_line("/usr/bin/nvim"));
+        assert!(takes_start_line(r"C:\Program Files\Vim\vim91\VIM.EXE"));
+        assert!(!takes_start_line("cat"));
+        assert!(!takes_start_line("code"));
+        assert!(!takes_start_line("notepad"));
+        assert!(!takes_start_line(r"C:\Windows\System32\NOTEPAD.EXE"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kept_text_is_named_after_the_document() {
+        let dir = tempfile::TempDir::new().unwrap();
+        open(
+            "QUIC\n",
+            "rfc9000",
+            Some("true"),
+            Some(3),
+            Some(dir.path()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("rfc9000.txt")).unwrap(),
+            "QUIC\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn other_viewers_get_the_file_without_the_start_line() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // `$0` is the first argument: the file, unless `+3` were passed.
+        let script = r#"sh -c 'cp "$0" "$0.seen"'"#;
+        open(
+            "QUIC\n",
+            "rfc9000",
+            Some(script),
+            Some(3),
+            Some(dir.path()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("rfc9000.txt.seen")).unwrap(),
+            "QUIC\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_title_is_exported_to_the_viewer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = r#"sh -c 'printf %s "$RFC_DOC_TITLE" > "$0.title"'"#;
+        let title = "RFC 9000: QUIC: A UDP-Based Multiplexed and Secure Transport";
+        open(
+            "QUIC\n",
+            "rfc9000",
+            Some(script),
+            None,
+            Some(dir.path()),
+            Some(title),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("rfc9000.txt.title")).unwrap(),
+            title
+        );
+    }
+
+    #[test]
+    fn less_prompt_escapes_its_metacharacters() {
+        assert_eq!(
+            less_prompt("RFC 9000: QUIC"),
+            r"-PsRFC 9000\: QUIC?e (END):?pB (%pB\%).."
+        );
+        assert_eq!(
+            less_prompt("50% done?"),
+            r"-Ps50\% done\??e (END):?pB (%pB\%).."
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_defaults_to_notepad() {
+        assert_eq!(default_viewer(), Some("notepad"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn other_platforms_have_no_default_viewer() {
+        assert_eq!(default_viewer(), None);
+    }
 }
@@ -0,0 +1,224 @@
+//! `rfc watch`: a watch list of drafts, and checking it for news.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::models::DocumentType;
+use crate::watches::{Change, Snapshot, WatchList};
+
+use super::alias::resolve;
+use super::draft_status::unversioned;
+
+/// Concurrent Datatracker lookups during `rfc watch check`. The client's
+/// rate limiter still paces the requests themselves.
+const CHECK_CONCURRENCY: usize = 4;
+
+/// A watched draft that changed since its baseline.
+#[derive(Debug, PartialEq, Serialize)]
+struct Report {
+    document: String,
+    changes: Vec<Change>,
+}
+
+/// Start watching `document`, recording its current state as the baseline.
+pub async fn add(document: &str, offline: bool) -> Result<()> {
+    let name = draft_name(document)?;
+    let mut watches = WatchList::load()?;
+    if watches.get(&name).is_some() {
+        println!("Already watching {}", name);
+        return Ok(());
+    }
+    if offline {
+        anyhow::bail!("Watching needs the Datatracker for a baseline; drop --offline");
+    }
+    let details = super::datatracker_client()?
+        .get_document_details(&name)
+        .await?;
+    let snapshot = Snapshot::new(&details, Utc::now());
+    match &snapshot.rev {
+        Some(rev) => println!("Watching {} (at -{})", name, rev),
+        None => println!("Watching {}", name),
+    }
+    watches.insert(&name, snapshot);
+    watches.save()
+}
+
+pub fn remove(document: &str) -> Result<()> {
+    let name = draft_name(document)?;
+    let mut watches = WatchList::load()?;
+    match watches.remove(&name) {
+        Some(_) => {
+            watches.save()?;
+            println!("Stopped watching {}", name);
+        }
+        None => println!("{} is not being watched", name),
+    }
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let watches = WatchList::load()?;
+    if watches.is_empty() {
+        println!("No drafts are being watched");
+        return Ok(());
+    }
+    let width = watches
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, snapshot) in watches.iter() {
+        let rev = snapshot
+            .rev
+            .as_ref()
+            .map_or(String::new(), |r| format!("-{}", r));
+        println!(
+            "{:<width$}  {:<4}  checked {}",
+            name,
+            rev,
+            Config::current().time_style.format(snapshot.checked_at),
+            width = width
+        );
+    }
+    Ok(())
+}
+
+/// Look up every watched draft and print what changed since it was last
+/// checked. Baselines move forward unless `dry_run`; a draft whose lookup
+/// fails keeps its old baseline and is tried again next time.
+pub async fn check(json: bool, dry_run: bool, offline: bool) -> Result<()> {
+    if offline {
+        anyhow::bail!("Checking watches needs the Datatracker; drop --offline");
+    }
+    let mut watches = WatchList::load()?;
+    if watches.is_empty() {
+        if json {
+            println!("[]");
+        } else {
+            println!("No drafts are being watched");
+        }
+        return Ok(());
+    }
+
+    let client = super::datatracker_client()?;
+    let names: Vec<String> = watches.iter().map(|(name, _)| name.to_string()).collect();
+    let mut looked_up: Vec<(String, Snapshot)> = stream::iter(names)
+        .map(|name| {
+            let client = &client;
+            async move {
+                match client.get_document_details(&name).await {
+                    Ok(details) => Some((name, Snapshot::new(&details, Utc::now()))),
+                    Err(e) => {
+                        eprintln!("Warning: {}: {:#}", name, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(CHECK_CONCURRENCY)
+        .filter_map(|r| async move { r })
+        .collect()
+        .await;
+    if looked_up.is_empty() {
+        anyhow::bail!("No watched draft could be checked");
+    }
+    looked_up.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let reports = compare(&watches, &looked_up);
+    let last_checked: Vec<Option<DateTime<Utc>>> = reports
+        .iter()
+        .map(|report| watches.get(&report.document).map(|s| s.checked_at))
+        .collect();
+    if !dry_run {
+        for (name, snapshot) in looked_up {
+            watches.insert(&name, snapshot);
+        }
+        watches.save()?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for (report, checked) in reports.iter().zip(last_checked) {
+            let since = checked
+                .map(|at| {
+                    format!(
+                        " (last checked {})",
+                        Config::current().time_style.format(at)
+                    )
+                })
+                .unwrap_or_default();
+            for change in &report.changes {
+                println!("{}: {}{}", report.document, change, since);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports for the drafts in `current` that differ from their baseline.
+fn compare(watches: &WatchList, current: &[(String, Snapshot)]) -> Vec<Report> {
+    current
+        .iter()
+        .filter_map(|(name, snapshot)| {
+            let changes = watches.get(name)?.changes(snapshot);
+            (!changes.is_empty()).then(|| Report {
+                document: name.clone(),
+                changes,
+            })
+        })
+        .collect()
+}
+
+/// The unversioned draft name `document` refers to; only drafts change
+/// in ways worth watching.
+fn draft_name(document: &str) -> Result<String> {
+    match resolve(document)? {
+        DocumentType::Draft(name) => Ok(unversioned(&name).to_string()),
+        rfc @ DocumentType::Rfc(_) => {
+            anyhow::bail!("{} is already published; only drafts can be watched", rfc)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn snapshot(rev: &str) -> Snapshot {
+        Snapshot {
+            rev: Some(rev.to_string()),
+            state: Some("Active".to_string()),
+            iesg_state: None,
+            wg_state: None,
+            rfc_number: None,
+            replaced_by: Vec::new(),
+            checked_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn only_changed_drafts_are_reported() {
+        let mut watches = WatchList::default();
+        watches.insert("draft-a", snapshot("07"));
+        watches.insert("draft-b", snapshot("03"));
+        let current = vec![
+            ("draft-a".to_string(), snapshot("08")),
+            ("draft-b".to_string(), snapshot("03")),
+        ];
+        assert_eq!(
+            compare(&watches, &current),
+            [Report {
+                document: "draft-a".to_string(),
+                changes: vec![Change::Revision {
+                    from: Some("07".to_string()),
+                    to: Some("08".to_string()),
+                }],
+            }]
+        );
+    }
+}
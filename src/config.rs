@@ -0,0 +1,307 @@
+//! User configuration, read from `config.toml` in the platform config
+//! directory (e.g. `~/.config/rfc/config.toml` on Linux). Every key is
+//! optional; command-line flags override the file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+use crate::api::{
+    user_agent_with_contact, FormatPreference, MirrorSettings, ProxySettings, Rendering,
+    DEFAULT_USER_AGENT,
+};
+use crate::cache::{GcPolicy, SEARCH_CACHE_TTL};
+use crate::render::{ColorChoice, TimeStyle};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Proxy URL for every request, overriding `HTTPS_PROXY`/`HTTP_PROXY`
+    pub proxy: Option<String>,
+    /// Email address or URL added to the User-Agent, so IETF operators
+    /// can reach you about your traffic
+    pub contact: Option<String>,
+    /// Store cached documents gzip-compressed
+    pub compress_cache: bool,
+    /// Pick search results with fzf/sk by default, like `RFC_FZF=1`
+    pub fzf: bool,
+    /// Minutes to reuse search results for (default 15; 0 turns the
+    /// search cache off)
+    pub search_cache_minutes: Option<u32>,
+    /// Check cached documents against their checksums on every read,
+    /// fetching corrupted ones again
+    pub verify_cache_reads: bool,
+    /// Never write to the cache, e.g. a shared one on a network mount
+    pub read_only_cache: bool,
+    /// Fetch RFC numbers even when the synced index says they don't
+    /// exist, like `--force-fetch`
+    pub force_fetch: bool,
+    /// A second, read-only cache consulted when the primary misses
+    pub extra_cache_dir: Option<PathBuf>,
+    /// Leave the text handed to the viewer in the cache's `tmp/`
+    pub keep_temp: bool,
+    /// Don't pass the document's title to the viewer, like
+    /// `--plain-viewer`
+    pub plain_viewer: bool,
+    /// After viewing, list related documents, like `--suggest`
+    pub suggest_related: bool,
+    /// Put a metadata header above viewed documents, like `--header`
+    pub header: bool,
+    /// Base URLs to fetch documents from, in order, when the usual hosts
+    /// are down (default www.ietf.org; `[]` turns fallback off)
+    pub mirrors: Option<Vec<String>>,
+    /// The order to try renderings in when fetching, e.g. `["html",
+    /// "txt"]` (default txt, then HTML; xml and pdf are accepted but can't
+    /// be fetched yet)
+    pub format_preference: Option<Vec<Rendering>>,
+    /// What `rfc cache gc` keeps (the `[gc]` table)
+    pub gc: GcConfig,
+    /// Fetch from this base URL alone, like `--mirror`. Command line only.
+    #[serde(skip)]
+    pub mirror: Option<String>,
+    /// `--color`. Command line only.
+    #[serde(skip)]
+    pub color: ColorChoice,
+    /// `--absolute-times`. Command line only.
+    #[serde(skip)]
+    pub time_style: TimeStyle,
+}
+
+/// The `[gc]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GcConfig {
+    /// Formats worth keeping, e.g. `["txt"]` to drop HTML copies of
+    /// documents whose text is cached (default both)
+    pub keep: Option<Vec<Rendering>>,
+}
+
+/// The config installed by [`Config::install`].
+static INSTALLED: OnceLock<Config> = OnceLock::new();
+
+impl Config {
+    /// Default location of the config file, if the platform has one.
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "rfc").map(|dirs| dirs.config_dir().join("config.toml"))
+    }
+
+    /// Load the config from its default location. A missing file is an
+    /// empty config; a malformed one is an error naming the file.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load the config from `path`, treating a missing file as empty.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content)
+                .with_context(|| format!("Invalid config file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read config file {}", path.display()))
+            }
+        }
+    }
+
+    /// Make this the config [`Config::current`] returns. Only the first
+    /// call (or a `current` before any call) takes effect; the CLI
+    /// installs the loaded config once at startup.
+    pub fn install(self) {
+        let _ = INSTALLED.set(self);
+    }
+
+    /// The installed config, or the defaults if none was installed.
+    pub fn current() -> &'static Config {
+        INSTALLED.get_or_init(Config::default)
+    }
+
+    /// Proxies from the environment, with `proxy` sending everything
+    /// through one URL.
+    pub fn proxy_settings(&self) -> ProxySettings {
+        ProxySettings::from_env().with_override(self.proxy.clone())
+    }
+
+    /// `mirrors`, or the default fallbacks, with `mirror` forced.
+    pub fn mirror_settings(&self) -> MirrorSettings {
+        self.mirrors
+            .clone()
+            .map_or_else(MirrorSettings::default, |fallbacks| MirrorSettings {
+                fallbacks,
+                forced: None,
+            })
+            .with_forced(self.mirror.clone())
+    }
+
+    /// `format_preference`, or the default order.
+    pub fn format_preference(&self) -> FormatPreference {
+        self.format_preference
+            .clone()
+            .map_or_else(FormatPreference::default, |order| FormatPreference {
+                order,
+            })
+    }
+
+    /// The User-Agent to send, with `contact` in it if set.
+    pub fn user_agent(&self) -> String {
+        self.contact
+            .as_deref()
+            .map_or_else(|| DEFAULT_USER_AGENT.to_string(), user_agent_with_contact)
+    }
+
+    /// Check the settings requests are made with, so a bad proxy URL or
+    /// contact fails up front rather than on the first request.
+    pub fn validate_network(&self) -> Result<()> {
+        self.proxy_settings().validate()?;
+        self.format_preference().validate()?;
+        reqwest::header::HeaderValue::from_str(&self.user_agent()).with_context(|| {
+            format!(
+                "Invalid contact '{}'",
+                self.contact.as_deref().unwrap_or("")
+            )
+        })?;
+        Ok(())
+    }
+
+    /// How long search results are reused.
+    pub fn search_cache_ttl(&self) -> Duration {
+        self.search_cache_minutes
+            .map_or(SEARCH_CACHE_TTL, |minutes| {
+                Duration::minutes(minutes.into())
+            })
+    }
+
+    /// The `[gc]` table as a policy for `rfc cache gc`.
+    pub fn gc_policy(&self) -> Result<GcPolicy> {
+        let Some(keep) = &self.gc.keep else {
+            return Ok(GcPolicy::default());
+        };
+        let formats = keep
+            .iter()
+            .map(|rendering| {
+                rendering.format().ok_or_else(|| {
+                    anyhow::anyhow!("gc.keep can only name txt and html, not {}", rendering)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if formats.is_empty() {
+            anyhow::bail!("gc.keep must name txt, html, or both");
+        }
+        Ok(GcPolicy::keeping(formats))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Format;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_file_is_default() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::load_from(&dir.path().join("config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn reads_contact() {
+        let config = Config::parse("contact = \"me@example.com\"\n").unwrap();
+        assert_eq!(config.contact.as_deref(), Some("me@example.com"));
+        assert_eq!(
+            config.user_agent(),
+            user_agent_with_contact("me@example.com")
+        );
+        assert_eq!(Config::default().user_agent(), DEFAULT_USER_AGENT);
+        let config = Config::parse("contact = \"bad\\nheader\"\n").unwrap();
+        assert!(config.validate_network().is_err());
+    }
+
+    #[test]
+    fn reads_proxy() {
+        let config = Config::parse("proxy = \"http://proxy.example:3128\"\n").unwrap();
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.example:3128"));
+    }
+
+    #[test]
+    fn reads_compress_cache() {
+        assert!(!Config::default().compress_cache);
+        let config = Config::parse("compress_cache = true\n").unwrap();
+        assert!(config.compress_cache);
+    }
+
+    #[test]
+    fn reads_fzf() {
+        assert!(!Config::default().fzf);
+        assert!(Config::parse("fzf = true\n").unwrap().fzf);
+    }
+
+    #[test]
+    fn reads_search_cache_minutes() {
+        assert_eq!(Config::default().search_cache_ttl(), SEARCH_CACHE_TTL);
+        let config = Config::parse("search_cache_minutes = 0\n").unwrap();
+        assert_eq!(config.search_cache_ttl(), Duration::zero());
+        let config = Config::parse("search_cache_minutes = 60\n").unwrap();
+        assert_eq!(config.search_cache_ttl(), Duration::hours(1));
+    }
+
+    #[test]
+    fn reads_format_preference() {
+        let config = Config::parse("format_preference = [\"html\", \"xml\", \"txt\"]\n").unwrap();
+        assert_eq!(
+            config.format_preference.unwrap(),
+            [Rendering::Html, Rendering::Xml, Rendering::Text]
+        );
+        assert!(Config::parse("format_preference = [\"docx\"]\n").is_err());
+    }
+
+    #[test]
+    fn reads_gc_keep() {
+        assert_eq!(Config::default().gc_policy().unwrap(), GcPolicy::default());
+        let config = Config::parse("[gc]\nkeep = [\"txt\"]\n").unwrap();
+        assert_eq!(config.gc_policy().unwrap().keep, [Format::Text]);
+        let config = Config::parse("[gc]\nkeep = [\"pdf\"]\n").unwrap();
+        assert!(config.gc_policy().is_err());
+        let config = Config::parse("[gc]\nkeep = []\n").unwrap();
+        assert!(config.gc_policy().is_err());
+        assert!(Config::parse("[gc]\nkept = [\"txt\"]\n").is_err());
+    }
+
+    #[test]
+    fn reads_mirrors() {
+        assert_eq!(Config::default().mirrors, None);
+        let config =
+            Config::parse("mirrors = [\"https://a.example\", \"https://b.example\"]\n").unwrap();
+        assert_eq!(
+            config.mirrors.unwrap(),
+            ["https://a.example", "https://b.example"]
+        );
+        let config = Config::parse("mirrors = []\n").unwrap();
+        assert_eq!(config.mirrors, Some(Vec::new()));
+        assert_eq!(config.mirror_settings(), MirrorSettings::none());
+        assert_eq!(
+            Config::default().mirror_settings(),
+            MirrorSettings::default()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_with_path() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "proxi = \"http://typo\"\n").unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("config.toml"));
+    }
+}
@@ -0,0 +1,609 @@
+//! Pull machine-readable artifacts — ABNF grammars, YANG modules, and
+//! source code — out of a document's plain text.
+//!
+//! This is all heuristics over the paginated text: page footers and
+//! running headers are dropped first (see [`crate::analysis`]), then blocks are found by
+//! `<CODE BEGINS>`/`<CODE ENDS>` markers, `module x { ... }` braces,
+//! ABNF rule shapes, and indentation.
+
+use std::path::Path;
+
+use crate::analysis::{lines_in_sections, strip_pagination};
+
+/// What to extract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// ABNF rules, collected into one grammar
+    Abnf,
+    /// YANG modules and submodules, one per artifact
+    Yang,
+    /// Other marked or fenced source code, plus code-like figures
+    Code,
+}
+
+impl ArtifactKind {
+    /// Plural noun for messages ("No YANG modules found").
+    pub fn describe(self) -> &'static str {
+        match self {
+            ArtifactKind::Abnf => "ABNF rules",
+            ArtifactKind::Yang => "YANG modules",
+            ArtifactKind::Code => "code blocks",
+        }
+    }
+}
+
+/// One extracted block and the file name to save it under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    /// A bare file name, never containing a path separator
+    pub name: String,
+    pub content: String,
+}
+
+/// Extract every `kind` artifact from `text`. `doc_name` (e.g. `rfc9000`)
+/// names artifacts that carry no name of their own.
+pub fn extract(text: &str, kind: ArtifactKind, doc_name: &str) -> Vec<Artifact> {
+    let lines = strip_pagination(text);
+    let blocks = code_blocks(&lines);
+    let mut artifacts = match kind {
+        ArtifactKind::Abnf => abnf(&lines, doc_name).into_iter().collect(),
+        ArtifactKind::Yang => yang(&lines, &blocks),
+        ArtifactKind::Code => code(&lines, &blocks, doc_name),
+    };
+    make_names_unique(&mut artifacts);
+    artifacts
+}
+
+/// A `<CODE BEGINS>` ... `<CODE ENDS>` block: the body's line range and
+/// the file name given on the opening marker, if any.
+struct CodeBlock {
+    body: std::ops::Range<usize>,
+    file: Option<String>,
+}
+
+fn code_blocks(lines: &[&str]) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(rest) = lines[i].trim().strip_prefix("<CODE BEGINS>") else {
+            i += 1;
+            continue;
+        };
+        let Some(len) = lines[i + 1..]
+            .iter()
+            .position(|l| l.trim().starts_with("<CODE ENDS>"))
+        else {
+            break;
+        };
+        blocks.push(CodeBlock {
+            body: i + 1..i + 1 + len,
+            file: marker_file_name(rest),
+        });
+        i += len + 2;
+    }
+    blocks
+}
+
+/// The name from `file "ietf-foo@2020-01-01.yang"` after a CODE BEGINS
+/// marker, reduced to its final path component.
+fn marker_file_name(rest: &str) -> Option<String> {
+    let quoted = rest.trim().strip_prefix("file")?.trim();
+    let name = quoted.trim_matches(|c| c == '"' || c == '\'');
+    let name = Path::new(name).file_name()?.to_str()?;
+    (!name.is_empty() && !name.starts_with('.')).then(|| name.to_string())
+}
+
+fn yang(lines: &[&str], blocks: &[CodeBlock]) -> Vec<Artifact> {
+    let mut artifacts = Vec::new();
+    for block in blocks {
+        let body = &lines[block.body.clone()];
+        if let Some(module) = body.iter().find_map(|l| module_name(l)) {
+            artifacts.push(Artifact {
+                name: block
+                    .file
+                    .clone()
+                    .unwrap_or_else(|| format!("{}.yang", module)),
+                content: dedent(body),
+            });
+        }
+    }
+
+    // Modules printed without CODE markers: follow the braces.
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some(block) = blocks.iter().find(|b| b.body.contains(&i)) {
+            i = block.body.end + 1;
+            continue;
+        }
+        let Some(module) = module_name(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let Some(len) = braced_len(&lines[i..]) else {
+            break;
+        };
+        artifacts.push(Artifact {
+            name: format!("{}.yang", module),
+            content: dedent(&lines[i..i + len]),
+        });
+        i += len;
+    }
+    artifacts
+}
+
+/// The name in a `module foo {` or `submodule foo {` line.
+fn module_name(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace();
+    if !matches!(words.next()?, "module" | "submodule") {
+        return None;
+    }
+    let name = words.next()?.trim_end_matches('{');
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    (valid && line.contains('{')).then_some(name)
+}
+
+/// How many lines it takes for the braces opened on the first line to
+/// close, ignoring braces in quoted strings and `//` comments.
+fn braced_len(lines: &[&str]) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for (n, line) in lines.iter().enumerate() {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '/') if chars.peek() == Some(&'/') => break,
+                (None, '{') => depth += 1,
+                (None, '}') => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 {
+            return Some(n + 1);
+        }
+    }
+    None
+}
+
+fn code(lines: &[&str], blocks: &[CodeBlock], doc_name: &str) -> Vec<Artifact> {
+    let mut artifacts = Vec::new();
+    let mut next_name = {
+        let mut n = 0;
+        move || {
+            n += 1;
+            format!("{}-code-{}.txt", doc_name, n)
+        }
+    };
+    let mut covered = vec![false; lines.len()];
+
+    for block in blocks {
+        covered[block.body.start - 1..=block.body.end].fill(true);
+        let body = &lines[block.body.clone()];
+        if body.iter().any(|l| module_name(l).is_some()) {
+            continue;
+        }
+        artifacts.push(Artifact {
+            name: block.file.clone().unwrap_or_else(&mut next_name),
+            content: dedent(body),
+        });
+    }
+
+    // Markdown-style fences, as some drafts use.
+    let mut i = 0;
+    while i < lines.len() {
+        if covered[i] || !lines[i].trim_start().starts_with("```") {
+            i += 1;
+            continue;
+        }
+        let Some(len) = lines[i + 1..]
+            .iter()
+            .position(|l| l.trim_start().starts_with("```"))
+        else {
+            break;
+        };
+        covered[i..=i + len + 1].fill(true);
+        artifacts.push(Artifact {
+            name: next_name(),
+            content: dedent(&lines[i + 1..i + 1 + len]),
+        });
+        i += len + 2;
+    }
+
+    // Unmarked figures: deeply indented paragraphs where most lines end
+    // like code. Neighbouring code paragraphs form one block.
+    let mut pending: Option<std::ops::Range<usize>> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let len = paragraph_len(&lines[i..], &covered[i..]);
+        if len == 0 {
+            if !lines[i].trim().is_empty() {
+                if let Some(range) = pending.take() {
+                    artifacts.push(Artifact {
+                        name: next_name(),
+                        content: dedent(&lines[range]),
+                    });
+                }
+            }
+            i += 1;
+            continue;
+        }
+        if looks_like_code(&lines[i..i + len]) {
+            let start = pending.take().map_or(i, |range| range.start);
+            pending = Some(start..i + len);
+        } else if let Some(range) = pending.take() {
+            artifacts.push(Artifact {
+                name: next_name(),
+                content: dedent(&lines[range]),
+            });
+        }
+        i += len;
+    }
+    if let Some(range) = pending {
+        artifacts.push(Artifact {
+            name: next_name(),
+            content: dedent(&lines[range]),
+        });
+    }
+    artifacts
+}
+
+/// Body text sits at three spaces; figures are indented further.
+const FIGURE_INDENT: usize = 6;
+
+/// Length of the run of non-blank, figure-indented lines starting at the
+/// first line (0 if it isn't one).
+fn paragraph_len(lines: &[&str], covered: &[bool]) -> usize {
+    lines
+        .iter()
+        .zip(covered)
+        .take_while(|(line, covered)| {
+            !**covered && !line.trim().is_empty() && indent(line) >= FIGURE_INDENT
+        })
+        .count()
+}
+
+fn looks_like_code(paragraph: &[&str]) -> bool {
+    let lines: Vec<&str> = paragraph
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let codeish = lines
+        .iter()
+        .filter(|l| l.ends_with([';', '{', '}']) || l.starts_with('}') || l.starts_with("//"))
+        .count();
+    let rules = lines.iter().filter(|l| is_rule_start(l)).count();
+    let yang = lines.iter().any(|l| module_name(l).is_some());
+    lines.len() >= 2 && !yang && codeish * 2 >= lines.len() && rules * 2 < lines.len()
+}
+
+/// The document's ABNF as one grammar: rules inside sections whose
+/// titles mention ABNF, grammar, or syntax, or failing that, groups of
+/// at least two rules anywhere.
+fn abnf(lines: &[&str], doc_name: &str) -> Option<Artifact> {
+    let in_sections = lines_in_sections(lines, |title| {
+        let title = title.to_ascii_lowercase();
+        ["abnf", "grammar", "syntax"]
+            .iter()
+            .any(|word| title.contains(word))
+    });
+    let mut groups = rule_groups(lines, |i| in_sections[i]);
+    if groups.is_empty() {
+        groups = rule_groups(lines, |_| true);
+        groups.retain(|group| group.iter().filter(|l| is_rule_start(l)).count() >= 2);
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    let mut content = groups
+        .iter()
+        .map(|group| group.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    content.push('\n');
+    Some(Artifact {
+        name: format!("{}.abnf", doc_name),
+        content,
+    })
+}
+
+/// `name = ...` or `name =/ ...`, with an ABNF rule name.
+fn is_rule_start(line: &str) -> bool {
+    let line = line.trim_start();
+    let name_len = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(line.len());
+    let name = &line[..name_len];
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && line[name_len..].trim_start().starts_with('=')
+        && !line[name_len..].trim_start().starts_with("==")
+}
+
+/// Runs of rules (with their continuation and comment lines) among the
+/// lines `in_scope`, each rule dedented to its own start.
+fn rule_groups(lines: &[&str], in_scope: impl Fn(usize) -> bool) -> Vec<Vec<String>> {
+    let mut groups = Vec::new();
+    let mut group: Vec<String> = Vec::new();
+    let mut rule_indent: Option<usize> = None;
+    let mut finish = |group: &mut Vec<String>| {
+        while group.last().is_some_and(|l| l.is_empty()) {
+            group.pop();
+        }
+        if !group.is_empty() {
+            groups.push(std::mem::take(group));
+        }
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if !in_scope(i) {
+            finish(&mut group);
+            rule_indent = None;
+            continue;
+        }
+        if line.trim().is_empty() {
+            if rule_indent.is_some() {
+                group.push(String::new());
+            }
+            continue;
+        }
+        let indent = indent(line);
+        let continues = rule_indent.is_some_and(|start| {
+            indent > start || (indent == start && line.trim_start().starts_with(';'))
+        });
+        if is_rule_start(line) && !(continues && indent > rule_indent.unwrap_or(0)) {
+            rule_indent = Some(indent);
+        } else if !continues {
+            finish(&mut group);
+            rule_indent = None;
+            continue;
+        }
+        let start = rule_indent.unwrap_or(0);
+        group.push(line[start.min(indent)..].trim_end().to_string());
+    }
+    finish(&mut group);
+    groups
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Join `lines` with their common indentation and surrounding blank
+/// lines removed.
+fn dedent(lines: &[&str]) -> String {
+    let start = lines.iter().position(|l| !l.trim().is_empty());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty());
+    let (Some(start), Some(end)) = (start, end) else {
+        return String::new();
+    };
+    let lines = &lines[start..=end];
+    let common = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| indent(l))
+        .min()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(line.get(common..).unwrap_or("").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Suffix repeated names (`foo.txt`, `foo-2.txt`, ...) so no artifact
+/// overwrites another.
+fn make_names_unique(artifacts: &mut [Artifact]) {
+    let mut seen: Vec<String> = Vec::new();
+    for artifact in artifacts.iter_mut() {
+        let (stem, ext) = match artifact.name.rsplit_once('.') {
+            Some((stem, ext)) => (stem.to_string(), format!(".{}", ext)),
+            None => (artifact.name.clone(), String::new()),
+        };
+        let mut n = 1;
+        while seen.contains(&artifact.name) {
+            n += 1;
+            artifact.name = format!("{}-{}{}", stem, n, ext);
+        }
+        seen.push(artifact.name.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YANG_DOC: &str = "\
+4.  YANG Module
+
+   <CODE BEGINS> file \"ietf-example@2020-01-01.yang\"
+
+   module ietf-example {
+     namespace \"urn:ietf:params:xml:ns:yang:ietf-example\";
+     prefix ex;
+     container top {
+       leaf name { type string; }
+
+
+
+Author                   Standards Track                    [Page 5]
+\x0c
+RFC 9999                    Example YANG                    May 2021
+
+
+       leaf pattern { type string { pattern \"[{]\"; } }
+     }
+   }
+
+   <CODE ENDS>
+
+5.  Tree Diagram
+
+   module ietf-other {
+     prefix ot;
+   }
+";
+
+    #[test]
+    fn yang_from_code_markers_across_a_page_break() {
+        let artifacts = extract(YANG_DOC, ArtifactKind::Yang, "rfc9999");
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0].name, "ietf-example@2020-01-01.yang");
+        assert_eq!(
+            artifacts[0].content,
+            "module ietf-example {\n  \
+             namespace \"urn:ietf:params:xml:ns:yang:ietf-example\";\n  \
+             prefix ex;\n  container top {\n    leaf name { type string; }\n    \
+             leaf pattern { type string { pattern \"[{]\"; } }\n  }\n}\n"
+        );
+        // Unmarked modules are found by following the braces.
+        assert_eq!(artifacts[1].name, "ietf-other.yang");
+        assert_eq!(
+            artifacts[1].content,
+            "module ietf-other {\n  prefix ot;\n}\n"
+        );
+    }
+
+    #[test]
+    fn yang_modules_are_not_code() {
+        let artifacts = extract(YANG_DOC, ArtifactKind::Code, "rfc9999");
+        assert!(artifacts.iter().all(|a| !a.content.contains("module")));
+    }
+
+    #[test]
+    fn marker_names_cannot_escape_the_output_dir() {
+        assert_eq!(
+            marker_file_name(" file \"../../etc/passwd\""),
+            Some("passwd".to_string())
+        );
+        assert_eq!(marker_file_name(" file \"..\""), None);
+        assert_eq!(marker_file_name(""), None);
+    }
+
+    const ABNF_DOC: &str = "\
+2.  Overview
+
+   The form is x = 1 in prose; not a grammar.
+
+3.  Collected ABNF
+
+   request   = method SP target
+             / method SP target SP version
+   ; methods are case-sensitive
+   method    = 1*ALPHA
+
+   The target is described below.
+
+3.1.  Targets
+
+   target    = 1*VCHAR
+   target    =/ \"*\"
+
+4.  Security Considerations
+
+   key = value
+";
+
+    #[test]
+    fn abnf_from_titled_sections() {
+        let artifacts = extract(ABNF_DOC, ArtifactKind::Abnf, "rfc9999");
+        assert_eq!(
+            artifacts,
+            vec![Artifact {
+                name: "rfc9999.abnf".to_string(),
+                content: "\
+request   = method SP target
+          / method SP target SP version
+; methods are case-sensitive
+method    = 1*ALPHA
+
+target    = 1*VCHAR
+target    =/ \"*\"
+"
+                .to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn abnf_falls_back_to_rule_groups_anywhere() {
+        let text = "\
+3.  Message Format
+
+   msg  = header body
+   body = *OCTET
+
+   Here a = b is a lone equation.
+";
+        let artifacts = extract(text, ArtifactKind::Abnf, "rfc1");
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].content, "msg  = header body\nbody = *OCTET\n");
+    }
+
+    #[test]
+    fn code_from_markers_fences_and_figures() {
+        let text = "\
+1.  Example
+
+   <CODE BEGINS>
+   int main(void) { return 0; }
+   <CODE ENDS>
+
+   ```
+   echo hi
+   ```
+
+   A figure follows.
+
+         struct point {
+             int x;
+             int y;
+         };
+
+         +--------+--------+
+         |  Type  | Length |
+         +--------+--------+
+";
+        let artifacts = extract(text, ArtifactKind::Code, "rfc7");
+        let names: Vec<_> = artifacts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["rfc7-code-1.txt", "rfc7-code-2.txt", "rfc7-code-3.txt"]
+        );
+        assert_eq!(artifacts[0].content, "int main(void) { return 0; }\n");
+        assert_eq!(artifacts[1].content, "echo hi\n");
+        assert_eq!(
+            artifacts[2].content,
+            "struct point {\n    int x;\n    int y;\n};\n"
+        );
+    }
+
+    #[test]
+    fn nothing_to_extract() {
+        let text = "1.  Introduction\n\n   Just prose.\n";
+        for kind in [ArtifactKind::Abnf, ArtifactKind::Yang, ArtifactKind::Code] {
+            assert!(extract(text, kind, "rfc1").is_empty());
+        }
+    }
+
+    #[test]
+    fn duplicate_names_get_suffixes() {
+        let mut artifacts = vec![
+            Artifact {
+                name: "a.yang".into(),
+                content: String::new(),
+            },
+            Artifact {
+                name: "a.yang".into(),
+                content: String::new(),
+            },
+        ];
+        make_names_unique(&mut artifacts);
+        assert_eq!(artifacts[1].name, "a-2.yang");
+    }
+}
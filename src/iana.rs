@@ -0,0 +1,342 @@
+//! Summarize a document's IANA Considerations: the section text, its
+//! subsections, and the registries it creates or changes.
+//!
+//! Registry names are found heuristically: quoted names next to the word
+//! "registry" (`the "QUIC Versions" registry`, `a registry titled "..."`),
+//! quoted names that end in "Registry", and subsection headings that do.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::analysis::{heading, strip_pagination};
+
+/// Words before a quoted name searched for "registry" (`a new registry
+/// titled "..."`).
+const WORDS_BEFORE: usize = 4;
+
+/// Words after a quoted name searched for "registry" (`"..." registry`).
+const WORDS_AFTER: usize = 2;
+
+/// Quoted text longer than this is prose, not a registry name.
+const MAX_NAME_LEN: usize = 100;
+
+/// A document's IANA Considerations section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IanaSection {
+    /// Section number, e.g. `22`
+    pub number: String,
+    pub title: String,
+    /// The section's text, subsections included, dedented
+    pub text: String,
+    /// Registries named anywhere in the section, in order of mention
+    pub registries: Vec<String>,
+    /// Nested subsections in document order; nesting is in their numbers
+    pub subsections: Vec<IanaSubsection>,
+    /// The section only says there's nothing for IANA to do
+    pub no_actions: bool,
+}
+
+/// One subsection (at any depth) of the IANA Considerations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IanaSubsection {
+    pub number: String,
+    pub title: String,
+    /// Registries named in this subsection's own text or heading
+    pub registries: Vec<String>,
+}
+
+/// Find and summarize the IANA Considerations section of `text`, or
+/// `None` if it has none.
+pub fn iana_considerations(text: &str) -> Option<IanaSection> {
+    let lines = strip_pagination(text);
+    let (start, number, title) = lines.iter().enumerate().find_map(|(i, line)| {
+        let (number, title) = heading(line)?;
+        title
+            .to_lowercase()
+            .contains("iana considerations")
+            .then(|| (i, number, title.to_string()))
+    })?;
+    let child_prefix = format!("{}.", number);
+    let body: Vec<&str> = lines[start + 1..]
+        .iter()
+        .take_while(|line| {
+            heading(line).is_none_or(|(n, _)| n.starts_with(&child_prefix))
+                && !is_unnumbered_heading(line)
+        })
+        .copied()
+        .collect();
+
+    let mut subsections = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut section_text = Vec::new();
+    for line in &body {
+        if let Some((number, title)) = heading(line) {
+            flush(&mut subsections, &current);
+            current.clear();
+            subsections.push(IanaSubsection {
+                number,
+                title: title.to_string(),
+                registries: Vec::new(),
+            });
+            current.push(title);
+        } else {
+            current.push(line);
+            section_text.push(*line);
+        }
+    }
+    flush(&mut subsections, &current);
+
+    let headings: Vec<&str> = subsections.iter().map(|s| s.title.as_str()).collect();
+    let mut registries = registry_names(&section_text.join("\n"));
+    registries.extend(
+        headings
+            .iter()
+            .filter(|t| names_registry(t))
+            .map(|t| t.to_string()),
+    );
+    let registries = dedup_names(registries);
+    let no_actions = registries.is_empty() && says_no_actions(&section_text.join(" "));
+    Some(IanaSection {
+        number,
+        title,
+        text: dedent(&body),
+        registries,
+        subsections,
+        no_actions,
+    })
+}
+
+/// Fill in the registries of the last subsection from its `lines` (its
+/// heading title first).
+fn flush(subsections: &mut [IanaSubsection], lines: &[&str]) {
+    let Some(last) = subsections.last_mut() else {
+        return;
+    };
+    let mut names = registry_names(&lines[1..].join("\n"));
+    if names_registry(lines[0]) {
+        names.insert(0, lines[0].to_string());
+    }
+    last.registries = dedup_names(names);
+}
+
+/// `Status of This Memo`, `Acknowledgments`, ...: an unnumbered heading
+/// ending the last numbered section.
+fn is_unnumbered_heading(line: &str) -> bool {
+    heading(line).is_none()
+        && !line.trim().is_empty()
+        && !line.starts_with(char::is_whitespace)
+        && line.split_whitespace().count() <= 6
+        && line
+            .trim_end()
+            .chars()
+            .last()
+            .is_some_and(char::is_alphanumeric)
+}
+
+fn names_registry(title: &str) -> bool {
+    let lower = title.to_lowercase();
+    lower.ends_with("registry") || lower.ends_with("registries")
+}
+
+/// Quoted names that look like registries, in order of appearance.
+fn registry_names(text: &str) -> Vec<String> {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut names = Vec::new();
+    let mut rest = flat.as_str();
+    let mut offset = 0;
+    while let Some(open) = rest.find('"') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('"') else {
+            break;
+        };
+        let name = after[..close].trim();
+        let before = &flat[..offset + open];
+        let following = &after[close + 1..];
+        let near = |words: Vec<&str>| words.iter().any(|w| is_registry_word(w));
+        let before_words: Vec<&str> = before.split_whitespace().rev().take(WORDS_BEFORE).collect();
+        let after_words: Vec<&str> = following.split_whitespace().take(WORDS_AFTER).collect();
+        if !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && (names_registry(name) || near(before_words) || near(after_words))
+        {
+            names.push(name.to_string());
+        }
+        let consumed = open + 1 + close + 1;
+        offset += consumed;
+        rest = &rest[consumed..];
+    }
+    names
+}
+
+fn is_registry_word(word: &str) -> bool {
+    let word = word
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase();
+    word == "registry" || word == "registries" || word == "sub-registry" || word == "subregistry"
+}
+
+/// Drop later mentions of the same registry, comparing case-insensitively
+/// and ignoring a trailing "Registry".
+fn dedup_names(names: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .filter(|name| {
+            let lower = name.to_lowercase();
+            let key = lower
+                .strip_suffix(" registry")
+                .unwrap_or(&lower)
+                .trim()
+                .to_string();
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// "This document has no IANA actions", "makes no requests of IANA", ...
+fn says_no_actions(text: &str) -> bool {
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    [
+        "no iana action",
+        "no actions for iana",
+        "no request to iana",
+        "no requests to iana",
+        "no request of iana",
+        "no requests of iana",
+        "no requests for iana",
+        "does not require any iana",
+        "requires no iana",
+    ]
+    .iter()
+    .any(|phrase| text.contains(phrase))
+}
+
+/// `lines` without the common indentation of their body text (headings
+/// stay flush left) or surrounding blank lines.
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty() && heading(l).is_none())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| {
+            let strip = indent.min(l.len() - l.trim_start().len());
+            l[strip..].trim_end()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIC: &str = "\
+21.  Security Considerations
+
+   Be careful.
+
+22.  IANA Considerations
+
+   This document establishes several registries for the management of
+   codepoints in QUIC.
+
+22.1.  QUIC Versions Registry
+
+   IANA has added a registry for \"QUIC Versions\" under a \"QUIC\"
+   heading.
+
+22.2.  QUIC Transport Parameters Registry
+
+   IANA has added a registry for \"QUIC Transport Parameters\" under a
+   \"QUIC\" heading.  See also the \"QUIC Frame Types\" registry.
+
+22.2.1.  Provisional Registrations
+
+   Nothing new here.
+
+23.  References
+
+   [RFC2119]  Bradner, S.
+";
+
+    #[test]
+    fn registries_and_subsections_are_summarized() {
+        let section = iana_considerations(QUIC).unwrap();
+        assert_eq!(section.number, "22");
+        assert_eq!(section.title, "IANA Considerations");
+        assert_eq!(
+            section.registries,
+            [
+                "QUIC Versions",
+                "QUIC Transport Parameters",
+                "QUIC Frame Types"
+            ]
+        );
+        let subsections: Vec<_> = section
+            .subsections
+            .iter()
+            .map(|s| (s.number.as_str(), s.registries.clone()))
+            .collect();
+        assert_eq!(
+            subsections,
+            [
+                ("22.1", vec!["QUIC Versions Registry".to_string()]),
+                (
+                    "22.2",
+                    vec![
+                        "QUIC Transport Parameters Registry".to_string(),
+                        "QUIC Frame Types".to_string()
+                    ]
+                ),
+                ("22.2.1", Vec::new()),
+            ]
+        );
+        assert!(section.text.starts_with("This document establishes"));
+        assert!(section.text.ends_with("Nothing new here."));
+        assert!(!section.no_actions);
+    }
+
+    #[test]
+    fn no_actions_is_recognized() {
+        let text = "\
+5.  IANA Considerations
+
+   This document has no IANA
+   actions.
+
+6.  Normative References
+";
+        let section = iana_considerations(text).unwrap();
+        assert!(section.no_actions);
+        assert!(section.registries.is_empty());
+        assert!(section.subsections.is_empty());
+        assert_eq!(section.text, "This document has no IANA\nactions.");
+    }
+
+    #[test]
+    fn last_section_ends_at_an_unnumbered_heading() {
+        let text = "\
+9.  IANA Considerations
+
+   IANA is asked to update the \"Foo Parameters\" registry.
+
+Acknowledgments
+
+   Thanks to the \"Bar\" registry experts.
+";
+        let section = iana_considerations(text).unwrap();
+        assert_eq!(section.registries, ["Foo Parameters"]);
+        assert_eq!(iana_considerations("1.  Introduction\n\n   Hi.\n"), None);
+    }
+}
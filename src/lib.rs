@@ -1,11 +1,53 @@
 //! Library backing the `rfc` CLI. Exposes the API clients, cache layer,
 //! and document models so the binary can stay a thin dispatcher.
+//!
+//! Cargo features:
+//!
+//! - `cli` (default): the command implementations used by the binary.
+//!   Implies `cache` and `html`.
+//! - `cache`: the local document cache.
+//! - `html`: HTML to plain-text conversion ([`render::html_to_text`]).
+//!   With `cache`, also the cache-or-fetch pipeline in [`workflow`].
+//! - `rustls` (default) / `native-tls`: TLS backend for reqwest.
+//! - `blocking`: synchronous wrappers in [`blocking`].
+//!
+//! The API clients, models, text analysis, artifact extraction, IANA
+//! summaries, highlighting, and [`timings`] are always available.
 
+#[cfg(feature = "cli")]
+pub mod aliases;
+pub mod analysis;
 pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "cache")]
 pub mod cache;
+#[cfg(feature = "cli")]
 pub mod commands;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod extract;
+pub mod iana;
 pub mod models;
+pub mod render;
+pub mod timings;
+#[cfg(feature = "cli")]
+pub mod watches;
+#[cfg(all(feature = "cache", feature = "html"))]
+pub mod workflow;
 
-pub use api::{DataTrackerClient, DocumentFetcher};
-pub use cache::{CacheManager, CacheMetadata, CachedDocument};
-pub use models::{Document, DocumentType, Format, SearchFilter, SearchResult};
+pub use api::{
+    DataTrackerClient, DataTrackerClientBuilder, DocumentFetcher, DocumentFetcherBuilder,
+    FetchedDocument,
+};
+#[cfg(feature = "cache")]
+pub use cache::{
+    Bookmark, CacheAuditReport, CacheCounters, CacheManager, CacheMetadata, CacheStats,
+    CachedDocument, DocumentCache, DraftStatus, FsCache, GcPolicy, GcReport, MemoryCache,
+    RemovalReport,
+};
+pub use models::{
+    AuthorDocuments, Ballot, BallotPosition, Citation, CitationStyle, DocEvent, Document,
+    DocumentDetails, DocumentType, Format, Person, Position, QueryMode, SearchFilter, SearchPage,
+    SearchResult,
+};
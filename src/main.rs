@@ -1,58 +1,482 @@
+use std::sync::Arc;
+
 use anyhow::Result;
-use clap::{Args, Parser, Subcommand};
+use chrono::NaiveDate;
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 
+use rfc::cache::CacheSort;
 use rfc::commands;
-use rfc::SearchFilter;
+use rfc::config::Config;
+use rfc::extract::ArtifactKind;
+use rfc::models::{parse_date_bound, SearchOptions, StdLevel};
+use rfc::render::{ColorChoice, TimeStyle};
+use rfc::timings::TimingRecorder;
+use rfc::{CitationStyle, QueryMode, SearchFilter};
 
 #[derive(Parser)]
 #[command(name = "rfc", version)]
 #[command(about = "Search, retrieve, and display IETF RFCs and drafts")]
-#[command(args_conflicts_with_subcommands = true)]
 #[command(arg_required_else_help = true)]
 struct Cli {
-    /// RFC number or draft name to view (default action — uses cache when present)
-    document: Option<String>,
+    #[command(flatten)]
+    view: ViewArgs,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+// What to view and how. Accepted both by `rfc view` and, for the bare
+// `rfc <DOCUMENT>` form, at the top level.
+#[derive(Args)]
+#[command(next_help_heading = "Viewing")]
+struct ViewArgs {
+    /// RFC number or draft name to view (uses the cache when present); several need --cat
+    #[arg(value_name = "DOCUMENT")]
+    documents: Vec<String>,
 
     /// Program to open the document with (defaults to $EDITOR, then $PAGER)
     #[arg(short = 'o', long, value_name = "PROGRAM", conflicts_with = "web")]
     open_with: Option<String>,
 
     /// Open document in web browser (IETF Datatracker)
-    #[arg(short = 'w', long, requires = "document")]
+    #[arg(short = 'w', long, requires = "documents")]
     web: bool,
 
-    #[command(subcommand)]
-    command: Option<Command>,
+    /// Open the HTML rendering (RFC Editor, or Datatracker for drafts) in a browser
+    #[arg(long, requires = "documents", conflicts_with_all = ["web", "open_with"])]
+    browser: bool,
+
+    /// Print the document's Datatracker, HTML, text, and PDF URLs
+    #[arg(long, requires = "documents", conflicts_with_all = ["web", "open_with"])]
+    url: bool,
+
+    /// Don't note drafts that have expired, been replaced, or been published as RFCs
+    #[arg(long, conflicts_with_all = ["web", "browser", "url"])]
+    no_banner: bool,
+
+    /// Reopen the most recently viewed cached document
+    #[arg(long, conflicts_with = "documents")]
+    last: bool,
+
+    /// List the RFCs and active drafts NAME has written, newest first (NAME may be a Datatracker person ID)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["documents", "last"])]
+    by_author: Option<String>,
+
+    /// With --by-author, the most documents to list
+    #[arg(long, value_name = "N", default_value_t = 25, requires = "by_author")]
+    limit: usize,
+
+    /// With --by-author, only list RFCs
+    #[arg(long, requires = "by_author", conflicts_with = "drafts_only")]
+    rfcs_only: bool,
+
+    /// With --by-author, only list active drafts
+    #[arg(long, requires = "by_author")]
+    drafts_only: bool,
+
+    /// Open a randomly picked published RFC (from the synced index if there is one)
+    #[arg(long, conflicts_with_all = ["documents", "last", "by_author"])]
+    random: bool,
+
+    /// With --random, only pick RFCs with one of these statuses
+    #[arg(long, value_enum, value_delimiter = ',', requires = "random")]
+    status: Vec<StatusArg>,
+
+    /// With --random, only pick RFCs from this working group (needs the RFC index)
+    #[arg(long, value_name = "ACRONYM", requires = "random")]
+    wg: Option<String>,
+
+    /// With --random, only pick RFCs numbered NUMBER or higher
+    #[arg(long, value_name = "NUMBER", requires = "random")]
+    min: Option<u32>,
+
+    /// View several documents one after another in a single viewer session
+    #[arg(long, requires = "documents", conflicts_with_all = ["web", "browser", "url"])]
+    cat: bool,
+
+    /// Leave the viewed text in the cache's tmp/ (for editors that return immediately) and print its path
+    #[arg(long, conflicts_with_all = ["web", "browser", "url"])]
+    keep_temp: bool,
+
+    /// Don't pass the document's title to the viewer (less's prompt, the terminal title, $RFC_DOC_TITLE)
+    #[arg(long, conflicts_with_all = ["web", "browser", "url"])]
+    plain_viewer: bool,
+
+    /// Print the title and the first LINES lines (default 40) instead of opening a viewer
+    #[arg(
+        long,
+        value_name = "LINES",
+        num_args = 0..=1,
+        default_missing_value = "40",
+        group = "excerpt",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp"]
+    )]
+    head: Option<usize>,
+
+    /// Print only this inclusive line range: 120-180, 120- (to the end), or -80 (from the start)
+    #[arg(
+        long,
+        value_name = "RANGE",
+        value_parser = commands::view::parse_line_range,
+        allow_hyphen_values = true,
+        group = "excerpt",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp"]
+    )]
+    lines: Option<commands::view::LineRange>,
+
+    /// Prefix lines printed by --head or --lines with their line numbers
+    #[arg(long, requires = "excerpt")]
+    number: bool,
+
+    /// Print only the abstract instead of opening a viewer
+    #[arg(
+        long = "abstract",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt"]
+    )]
+    abstract_only: bool,
+
+    /// After the viewer closes, list related documents (from cached facts only)
+    #[arg(long, conflicts_with_all = ["web", "browser", "url", "cat", "excerpt", "abstract_only"])]
+    suggest: bool,
+
+    /// Print only the IANA Considerations, summarizing the registries named in each subsection
+    #[arg(
+        long,
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest"]
+    )]
+    iana: bool,
+
+    /// Print the --iana summary, --lineage, or --by-author listing as machine-readable JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Print a citation of the RFC (authors and date from the synced index) instead of opening it
+    #[arg(
+        long,
+        requires = "documents",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana"]
+    )]
+    cite: bool,
+
+    /// Check which renderings (txt, html, xml, pdf) are published, without fetching any
+    #[arg(
+        long,
+        requires = "documents",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana", "cite"]
+    )]
+    formats: bool,
+
+    /// Show the drafts an RFC came from, or the RFC a draft became, with dates
+    #[arg(
+        long,
+        requires = "documents",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana", "cite", "formats"]
+    )]
+    lineage: bool,
+
+    /// Write the document as Markdown to FILE (`-` for stdout) instead of opening it
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "documents",
+        conflicts_with_all = ["web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana", "cite", "formats", "lineage"]
+    )]
+    export_md: Option<std::path::PathBuf>,
+
+    /// Format the document as a man page and show it with `man -l -` (or groff and $PAGER)
+    #[arg(
+        long,
+        requires = "documents",
+        conflicts_with_all = ["open_with", "web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana", "cite", "formats", "lineage", "export_md", "hyperlinks"]
+    )]
+    man: bool,
+
+    /// Print one short line on whether the document is cached, for shell prompts and status bars (never uses the network)
+    #[arg(
+        long,
+        requires = "documents",
+        conflicts_with_all = ["open_with", "web", "browser", "url", "cat", "keep_temp", "excerpt", "abstract_only", "suggest", "iana", "cite", "formats", "lineage", "export_md", "man"]
+    )]
+    status_line: bool,
+
+    /// With --status-line, the most terminal columns to use
+    #[arg(long, value_name = "COLS", default_value_t = 80, value_parser = clap::value_parser!(u16).range(10..), requires = "status_line")]
+    max_width: u16,
+
+    /// Citation style for --cite
+    #[arg(long, value_enum, default_value_t = StyleArg::Text, requires = "cite")]
+    style: StyleArg,
+
+    /// Start the viewed text with a header of the document's status, authors, date, and relations (from the synced index and cache)
+    #[arg(long, conflicts_with_all = ["web", "browser", "url", "man"])]
+    header: bool,
+
+    /// Open at the top, ignoring any bookmark set with 'rfc mark'
+    #[arg(long, conflicts_with = "web")]
+    from_start: bool,
+
+    /// Make RFC and draft citations clickable (OSC 8; needs a pager that passes escapes, e.g. less -R)
+    #[arg(long, conflicts_with = "web")]
+    hyperlinks: bool,
+}
+
+// Options every command takes.
+#[derive(Args)]
+#[command(next_help_heading = "Global options")]
+struct GlobalArgs {
+    /// Never touch the network; answer from the cache and local RFC index
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Don't show progress bars
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Proxy URL for all requests (overrides the config file and HTTPS_PROXY/HTTP_PROXY)
+    #[arg(long, value_name = "URL", global = true)]
+    proxy: Option<String>,
+
+    /// Never write to the cache (for a shared cache others maintain)
+    #[arg(long, global = true)]
+    read_only_cache: bool,
+
+    /// Also read documents from this cache when the main one misses
+    #[arg(long, value_name = "PATH", global = true)]
+    extra_cache_dir: Option<std::path::PathBuf>,
+
+    /// Fetch RFC numbers the synced index says don't exist (it may be stale)
+    #[arg(long, global = true)]
+    force_fetch: bool,
+
+    /// Fetch documents only from this mirror (serving rfc-editor.org's paths)
+    #[arg(long, value_name = "URL", global = true)]
+    mirror: Option<String>,
+
+    /// Print the time spent fetching, converting, and caching when done
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// Describe what's happening on stderr, e.g. the User-Agent sent
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Color documents (headings, keywords, citations) and search matches
+    #[arg(long, value_enum, default_value_t = ColorArg::Auto, global = true)]
+    color: ColorArg,
+
+    /// Show dates (2023-11-02) in listings instead of "3 days ago"
+    #[arg(long, global = true)]
+    absolute_times: bool,
 }
 
 #[derive(Subcommand)]
 enum Command {
+    /// Open documents in a viewer (what `rfc <DOCUMENT>` does)
+    View(ViewArgs),
+
     /// Fetch a document from the API and cache it without opening it
     Fetch {
-        /// RFC number or draft name to fetch
+        /// RFC numbers, draft names, or RFC ranges like 9000-9010 to fetch
+        #[arg(required_unless_present = "from")]
+        documents: Vec<String>,
+
+        /// Fetch every document listed in FILE (one per line, `#` comments; `-` reads stdin)
+        #[arg(long, value_name = "FILE", conflicts_with = "documents")]
+        from: Option<std::path::PathBuf>,
+
+        /// Re-fetch documents that are already cached
+        #[arg(short, long)]
+        refresh: bool,
+
+        /// Report the fetched document's cache path, format, and size as JSON
+        #[arg(long, conflicts_with = "from")]
+        json: bool,
+    },
+
+    /// Show a document's title, date, and abstract without fetching it
+    Info {
+        /// RFC number or draft name to describe
         document: String,
     },
 
     /// Search the IETF Datatracker
     Search(SearchArgs),
 
+    /// Download the RFC Editor index for offline lookups
+    SyncIndex,
+
+    /// Refresh cached drafts (and RFCs with --all) from the network
+    Sync {
+        /// Refresh cached RFCs as well as drafts
+        #[arg(short, long)]
+        all: bool,
+
+        /// Only refresh documents cached longer ago than AGE (e.g. 12h, 7d, 2w)
+        #[arg(long, value_name = "AGE", value_parser = commands::sync::parse_age)]
+        older_than: Option<chrono::Duration>,
+    },
+
+    /// Tag documents, fetching any that aren't cached yet
+    Tag {
+        /// Tag to attach (a single word)
+        tag: String,
+        /// RFC numbers or draft names to tag
+        #[arg(required = true)]
+        documents: Vec<String>,
+    },
+
+    /// Bookmark where to resume reading a document
+    Mark {
+        /// RFC number or draft name
+        document: String,
+        /// Line number, or text to find (the first matching line is marked)
+        position: String,
+    },
+
+    /// Extract ABNF, YANG modules, or source code from a document into files
+    Extract {
+        /// RFC number or draft name
+        document: String,
+        /// What to extract
+        #[arg(short, long, value_enum)]
+        kind: KindArg,
+        /// Directory to write the files into (default: current directory)
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Show the IESG ballot positions on a draft
+    Ballot {
+        /// Draft name
+        document: String,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show a document's Datatracker timeline: revisions, state changes, publication
+    History {
+        /// RFC number or draft name
+        document: String,
+        /// How many events to show, newest first
+        #[arg(short, long, default_value_t = 25)]
+        limit: usize,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show size, structure, and RFC 2119 keyword counts for a document
+    Stats {
+        /// RFC number or draft name
+        document: String,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove a tag from documents
+    Untag {
+        /// Tag to remove
+        tag: String,
+        /// RFC numbers or draft names to untag
+        #[arg(required = true)]
+        documents: Vec<String>,
+    },
+
     /// Manage the local document cache
     #[command(subcommand)]
     Cache(CacheCmd),
+
+    /// Manage short names for documents (e.g. `bgp` for RFC 4271)
+    #[command(subcommand)]
+    Alias(AliasCmd),
+
+    /// Keep personal notes on documents
+    #[command(subcommand)]
+    Notes(NotesCmd),
+
+    /// Watch drafts for new revisions, state changes, and publication
+    #[command(subcommand)]
+    Watch(WatchCmd),
 }
 
 #[derive(Args)]
 struct SearchArgs {
-    /// Query string (whitespace-separated tokens are AND-ed together)
+    /// Query: every word must appear in the title or abstract, in any order
+    /// and case; a "quoted phrase" must appear exactly (quote it for the shell
+    /// too: '"bgp communities" large')
     #[arg(required = true, num_args = 1..)]
     query: Vec<String>,
 
+    /// Match the whole query as one exact phrase, as if it were quoted
+    #[arg(long)]
+    phrase: bool,
+
     #[command(flatten)]
     filter: SearchFilterArgs,
 
+    /// Only show documents with this status (comma-separated values are OR'd)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    status: Vec<StatusArg>,
+
+    /// Only show documents with this Datatracker tag, e.g. errata or need-rev
+    #[arg(long, value_name = "WORD")]
+    keyword: Option<String>,
+
+    /// Only show documents dated on or after this (YYYY, YYYY-MM, or YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", value_parser = parse_date_bound)]
+    since: Option<NaiveDate>,
+
+    /// Only show documents dated before this (YYYY, YYYY-MM, or YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", value_parser = parse_date_bound)]
+    before: Option<NaiveDate>,
+
     /// Maximum number of results to display
     #[arg(short, long, default_value_t = 25)]
     limit: usize,
+
+    /// Follow the Datatracker's pages to the end instead of stopping at
+    /// --limit (up to 1000 results; obsolescence is only looked up with
+    /// --exclude-obsoleted)
+    #[arg(long, conflicts_with = "limit")]
+    all_results: bool,
+
+    /// Show full titles without truncation
+    #[arg(short, long)]
+    wide: bool,
+
+    /// Hide RFCs that have been obsoleted by a newer RFC
+    #[arg(long)]
+    exclude_obsoleted: bool,
+
+    /// Hide drafts that are expired, replaced, published as an RFC, or withdrawn
+    #[arg(long)]
+    active_only: bool,
+
+    /// Print each result's abstract beneath its title
+    #[arg(long)]
+    abstracts: bool,
+
+    /// Output results as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Pick a result with fzf (or sk) and open it (default with RFC_FZF=1 or `fzf = true`)
+    #[arg(long, conflicts_with = "json")]
+    fzf: bool,
+
+    /// List results even if RFC_FZF or the config asks for fzf
+    #[arg(long, overrides_with = "fzf")]
+    no_fzf: bool,
+
+    /// Query the Datatracker even if this search was run recently
+    #[arg(long)]
+    no_search_cache: bool,
 }
 
 #[derive(Args)]
@@ -79,50 +503,662 @@ impl From<&SearchFilterArgs> for SearchFilter {
     }
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum SortKey {
+    Name,
+    /// When the document was cached
+    Date,
+    Size,
+    Title,
+    /// When the document was last opened
+    Accessed,
+}
+
+impl From<SortKey> for CacheSort {
+    fn from(key: SortKey) -> Self {
+        match key {
+            SortKey::Name => CacheSort::Name,
+            SortKey::Date => CacheSort::Date,
+            SortKey::Size => CacheSort::Size,
+            SortKey::Title => CacheSort::Title,
+            SortKey::Accessed => CacheSort::Accessed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorArg {
+    /// On a terminal (for documents, a viewer that renders ANSI, like less -R) when NO_COLOR is unset
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<ColorArg> for ColorChoice {
+    fn from(color: ColorArg) -> Self {
+        match color {
+            ColorArg::Auto => ColorChoice::Auto,
+            ColorArg::Always => ColorChoice::Always,
+            ColorArg::Never => ColorChoice::Never,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StyleArg {
+    /// The RFC Editor's recommended reference
+    Text,
+    Bibtex,
+    /// For EndNote, Zotero, and other reference managers
+    Ris,
+}
+
+impl From<StyleArg> for CitationStyle {
+    fn from(style: StyleArg) -> Self {
+        match style {
+            StyleArg::Text => CitationStyle::Text,
+            StyleArg::Bibtex => CitationStyle::BibTex,
+            StyleArg::Ris => CitationStyle::Ris,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatusArg {
+    ProposedStandard,
+    InternetStandard,
+    Bcp,
+    Informational,
+    Experimental,
+    Historic,
+}
+
+impl From<StatusArg> for StdLevel {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::ProposedStandard => StdLevel::ProposedStandard,
+            StatusArg::InternetStandard => StdLevel::InternetStandard,
+            StatusArg::Bcp => StdLevel::Bcp,
+            StatusArg::Informational => StdLevel::Informational,
+            StatusArg::Experimental => StdLevel::Experimental,
+            StatusArg::Historic => StdLevel::Historic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KindArg {
+    /// ABNF rules, gathered into one grammar file
+    Abnf,
+    /// YANG modules, one file each
+    Yang,
+    /// Other source code blocks
+    Code,
+}
+
+impl From<KindArg> for ArtifactKind {
+    fn from(kind: KindArg) -> Self {
+        match kind {
+            KindArg::Abnf => ArtifactKind::Abnf,
+            KindArg::Yang => ArtifactKind::Yang,
+            KindArg::Code => ArtifactKind::Code,
+        }
+    }
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+struct CacheFilterArgs {
+    /// Only list RFCs
+    #[arg(long)]
+    rfcs_only: bool,
+
+    /// Only list Internet-Drafts
+    #[arg(long)]
+    drafts_only: bool,
+}
+
+impl From<&CacheFilterArgs> for SearchFilter {
+    fn from(a: &CacheFilterArgs) -> Self {
+        if a.rfcs_only {
+            SearchFilter::RfcsOnly
+        } else if a.drafts_only {
+            SearchFilter::DraftsOnly
+        } else {
+            SearchFilter::Both
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum CacheCmd {
     /// List cached documents
+    #[command(visible_alias = "ls")]
     List {
         /// Show full titles without truncation
         #[arg(short, long)]
         wide: bool,
+
+        /// Also show when each document was cached, its size, and formats
+        #[arg(short, long)]
+        long: bool,
+
+        /// Order of the listing
+        #[arg(short, long, value_enum, default_value_t = SortKey::Name)]
+        sort: SortKey,
+
+        /// Reverse the order
+        #[arg(short, long)]
+        reverse: bool,
+
+        #[command(flatten)]
+        filter: CacheFilterArgs,
+
+        /// Only list documents with this tag
+        #[arg(short, long, value_name = "TAG")]
+        tagged: Option<String>,
+
+        /// Output the listing as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show only the newest cached revision of each draft, noting older ones
+        #[arg(long, conflicts_with = "json")]
+        group_drafts: bool,
     },
     /// Show cache location and total size
-    Info,
-    /// Remove a single document from the cache
+    Info {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Also show cache hits, misses, and bytes fetched
+        #[arg(long)]
+        stats: bool,
+
+        /// Zero the statistics shown by --stats
+        #[arg(long, conflicts_with_all = ["json", "stats"])]
+        reset_stats: bool,
+    },
+    /// Remove a single document from the cache (its notes are kept)
+    #[command(visible_alias = "rm")]
     Remove {
         /// RFC number or draft name to remove
         document: String,
+
+        /// Delete the document's notes too
+        #[arg(long)]
+        purge_notes: bool,
+    },
+    /// Print lines of cached documents containing PATTERN; exits 1 if none do
+    Grep {
+        /// Text to look for (matched literally)
+        pattern: String,
+        /// Ignore case when matching
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Lines of context to show before and after each match
+        #[arg(short = 'C', long, value_name = "N", default_value_t = 0)]
+        context: usize,
+        /// Lines of context to show before each match (overrides --context)
+        #[arg(short = 'B', long, value_name = "N")]
+        before_context: Option<usize>,
+        /// Lines of context to show after each match (overrides --context)
+        #[arg(short = 'A', long, value_name = "N")]
+        after_context: Option<usize>,
+    },
+    /// Exit 0 if the document is cached (in any format), 1 if not; prints nothing
+    Has {
+        /// RFC number, draft name, or alias to look for
+        document: String,
     },
     /// Remove every cached document
     Clear,
+    /// Remove older cached revisions of each draft, keeping the newest
+    PruneOldRevisions {
+        /// How many revisions of each draft to keep
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        keep: u32,
+    },
+    /// Rewrite cached documents to match the compress_cache setting
+    Recompress,
+    /// Remove redundant copies, leftover temporary files, and empty directories
+    Gc,
+    /// List the most recently viewed cached documents
+    Recent {
+        /// How many to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Fetch titles for cached documents that have none
+    Backfill,
+    /// Write a self-contained HTML copy of the cache for offline browsing
+    ExportHtml {
+        /// Directory to write index.html and the document pages into
+        dir: std::path::PathBuf,
+    },
+    /// Check cached documents against the checksums taken when they were stored
+    Verify {
+        /// Fetch corrupted and unreadable documents again
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Check the cache for orphaned, empty, and unrecognized files
+    Doctor {
+        /// Remove orphaned and empty files and backfill missing metadata
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasCmd {
+    /// Define NAME as another way to refer to DOCUMENT
+    Add {
+        /// The alias (a single word that isn't itself an RFC or draft name)
+        name: String,
+        /// RFC number or draft name it stands for
+        document: String,
+    },
+    /// Delete an alias
+    Remove {
+        /// The alias to delete
+        name: String,
+    },
+    /// List aliases
+    List,
+}
+
+#[derive(Subcommand)]
+enum WatchCmd {
+    /// Add a draft to the watch list, recording its current state
+    Add {
+        /// Draft name
+        document: String,
+    },
+    /// Stop watching a draft
+    Remove {
+        /// Draft name
+        document: String,
+    },
+    /// List watched drafts
+    List,
+    /// Report what changed on each watched draft since the last check
+    Check {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+        /// Don't record the new state as the baseline for next time
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NotesCmd {
+    /// Edit the notes on a document in $EDITOR
+    Edit {
+        /// RFC number or draft name
+        document: String,
+    },
+    /// Print the notes on a document
+    Show {
+        /// RFC number or draft name
+        document: String,
+    },
+    /// Copy every notes file into a directory
+    Export {
+        /// Directory to write to (created if missing)
+        dir: std::path::PathBuf,
+    },
+}
+
+/// Parse the command line. The top-level [`ViewArgs`] are for the bare
+/// `rfc <DOCUMENT>` form, so they're refused alongside a subcommand; clap
+/// can't express that without also refusing global flags before one.
+fn parse_cli() -> Cli {
+    let mut command = Cli::command();
+    let matches = command.get_matches_mut();
+    if let Some((name, _)) = matches.subcommand() {
+        let view_args = ViewArgs::augment_args(clap::Command::new("view"));
+        let given = view_args.get_arguments().find(|arg| {
+            matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        });
+        if let Some(arg) = given {
+            let flag = match arg.get_long() {
+                Some(long) => format!("--{}", long),
+                None => arg.get_id().to_string(),
+            };
+            command
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    format!("'{}' can't be used with the '{}' subcommand", flag, name),
+                )
+                .exit();
+        }
+    }
+    Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-
-    match cli.command {
-        Some(Command::Fetch { document }) => commands::fetch::run(&document).await,
-        Some(Command::Search(args)) => {
-            let filter = SearchFilter::from(&args.filter);
-            commands::search::run(commands::search::Args {
-                query: args.query.join(" "),
-                filter,
-                limit: args.limit,
+    let cli = parse_cli();
+    let global = cli.global;
+
+    let mut config = Config::load()?;
+    config.read_only_cache |= global.read_only_cache;
+    config.force_fetch |= global.force_fetch;
+    if global.extra_cache_dir.is_some() {
+        config.extra_cache_dir = global.extra_cache_dir.clone();
+    }
+    config.proxy = global.proxy.clone().or(config.proxy);
+    config.mirror = global.mirror.clone();
+    config.color = global.color.into();
+    if global.absolute_times {
+        config.time_style = TimeStyle::Absolute;
+    }
+    config.validate_network()?;
+    if global.verbose {
+        eprintln!("User-Agent: {}", config.user_agent());
+    }
+    config.install();
+    let recorder = global.timings.then(|| Arc::new(TimingRecorder::default()));
+    commands::notices::Notices::install(recorder.clone())?;
+
+    let result = match cli.command {
+        None => view(cli.view, &global).await,
+        Some(Command::View(args)) => view(args, &global).await,
+        Some(Command::Fetch {
+            documents,
+            from,
+            refresh,
+            json,
+        }) => match from {
+            Some(list) => commands::fetch::from_list(&list, refresh, global.quiet).await,
+            None => commands::fetch::run(&documents, refresh, json, global.quiet).await,
+        },
+        Some(Command::Info { document }) => commands::info::run(&document, global.offline).await,
+        Some(Command::Search(args)) => search(args, &global).await,
+        Some(Command::SyncIndex) => commands::index::sync().await,
+        Some(Command::Sync { all, older_than }) => {
+            commands::sync::run(commands::sync::Args {
+                all,
+                older_than,
+                quiet: global.quiet,
             })
             .await
         }
-        Some(Command::Cache(c)) => match c {
-            CacheCmd::List { wide } => commands::cache::list(wide),
-            CacheCmd::Info => commands::cache::info(),
-            CacheCmd::Remove { document } => commands::cache::remove(&document),
-            CacheCmd::Clear => commands::cache::clear(),
-        },
-        None => match cli.document {
-            Some(doc) => commands::view::run(&doc, cli.open_with.as_deref(), cli.web).await,
-            // arg_required_else_help handles the "no args at all" case.
-            None => Ok(()),
+        Some(Command::Tag { tag, documents }) => {
+            commands::tag::add(&tag, &documents, global.offline, global.quiet).await
+        }
+        Some(Command::Mark { document, position }) => {
+            commands::mark::run(&document, &position, global.offline, global.quiet).await
+        }
+        Some(Command::Extract {
+            document,
+            kind,
+            output,
+        }) => {
+            commands::extract::run(
+                &document,
+                kind.into(),
+                output.as_deref(),
+                global.offline,
+                global.quiet,
+            )
+            .await
+        }
+        Some(Command::Ballot { document, json }) => {
+            commands::ballot::run(&document, json, global.offline).await
+        }
+        Some(Command::History {
+            document,
+            limit,
+            json,
+        }) => commands::history::run(&document, limit, json, global.offline).await,
+        Some(Command::Stats { document, json }) => {
+            commands::stats::run(&document, json, global.offline, global.quiet).await
+        }
+        Some(Command::Untag { tag, documents }) => commands::tag::remove(&tag, &documents),
+        Some(Command::Cache(cmd)) => cache(cmd, &global).await,
+        Some(Command::Alias(cmd)) => alias(cmd),
+        Some(Command::Notes(cmd)) => notes(cmd),
+        Some(Command::Watch(cmd)) => watch(cmd, &global).await,
+    };
+    if let Some(recorder) = recorder {
+        let timings = recorder.timings();
+        if timings.is_empty() {
+            eprintln!("Timings: nothing fetched, converted, or cached");
+        } else {
+            eprintln!("Timings: {}", timings);
+        }
+    }
+    result
+}
+
+/// `rfc view` and the bare `rfc <DOCUMENT>` form.
+async fn view(args: ViewArgs, global: &GlobalArgs) -> Result<()> {
+    if args.documents.len() > 1 && (args.web || args.browser || args.url) {
+        anyhow::bail!("--web, --browser, and --url take a single document")
+    }
+    if args.documents.len() > 1 && !args.cat {
+        anyhow::bail!("Pass --cat to view several documents in one session")
+    }
+    if args.browser || args.url {
+        return commands::links::run(&args.documents[0], args.url, args.browser);
+    }
+    if args.json && !(args.iana || args.lineage || args.by_author.is_some()) {
+        anyhow::bail!("--json needs --iana, --lineage, or --by-author")
+    }
+    if let Some(name) = args.by_author {
+        return commands::author::run(commands::author::Args {
+            name,
+            limit: args.limit,
+            filter: if args.rfcs_only {
+                SearchFilter::RfcsOnly
+            } else if args.drafts_only {
+                SearchFilter::DraftsOnly
+            } else {
+                SearchFilter::Both
+            },
+            json: args.json,
+            offline: global.offline,
+        })
+        .await;
+    }
+    if args.status_line {
+        return commands::status_line::run(&args.documents[0], args.max_width.into());
+    }
+    if args.formats {
+        return commands::formats::run(&args.documents[0], global.offline).await;
+    }
+    if args.lineage {
+        return commands::lineage::run(&args.documents[0], args.json, global.offline).await;
+    }
+    if let Some(output) = &args.export_md {
+        return commands::export::markdown(
+            &args.documents[0],
+            output,
+            global.offline,
+            global.quiet,
+        )
+        .await;
+    }
+    if args.cite {
+        return commands::cite::run(
+            &args.documents[0],
+            args.style.into(),
+            global.offline,
+            global.quiet,
+        )
+        .await;
+    }
+    let view_args = commands::view::Args {
+        open_with: args.open_with,
+        web: args.web,
+        from_start: args.from_start,
+        hyperlinks: args.hyperlinks,
+        no_banner: args.no_banner,
+        header: args.header || Config::current().header,
+        keep_temp: args.keep_temp || Config::current().keep_temp,
+        plain_viewer: args.plain_viewer || Config::current().plain_viewer,
+        head: args.head,
+        abstract_only: args.abstract_only,
+        iana: args.iana,
+        json: args.json,
+        man: args.man,
+        lines: args.lines,
+        number: args.number,
+        suggest: args.suggest || Config::current().suggest_related,
+        offline: global.offline,
+        quiet: global.quiet,
+    };
+    match &args.documents[..] {
+        [] if args.last => {
+            let doc = commands::view::last_accessed()?;
+            commands::view::run(&doc.name(), &view_args).await
+        }
+        [] if args.random => {
+            let filter = commands::random::Filter {
+                status: args.status.into_iter().map(StdLevel::from).collect(),
+                wg: args.wg,
+                min: args.min,
+            };
+            let doc = commands::random::pick(&filter, global.offline).await?;
+            if !global.quiet {
+                eprintln!("Picked {} at random", doc);
+            }
+            commands::view::run(&doc.name(), &view_args).await
+        }
+        [] => anyhow::bail!("Name a document to view, or pass --last or --random"),
+        [doc] if !args.cat => commands::view::run(doc, &view_args).await,
+        docs => commands::view::run_concatenated(docs, &view_args).await,
+    }
+}
+
+async fn search(args: SearchArgs, global: &GlobalArgs) -> Result<()> {
+    commands::search::run(commands::search::Args {
+        query: args.query.join(" "),
+        options: SearchOptions::new(SearchFilter::from(&args.filter))
+            .with_status(args.status.into_iter().map(StdLevel::from).collect())
+            .with_dates(args.since, args.before)?
+            .with_keyword(args.keyword)
+            .with_mode(if args.phrase {
+                QueryMode::Phrase
+            } else {
+                QueryMode::Words
+            }),
+        limit: args.limit,
+        all_results: args.all_results,
+        wide: args.wide,
+        exclude_obsoleted: args.exclude_obsoleted,
+        active_only: args.active_only,
+        abstracts: args.abstracts,
+        json: args.json,
+        offline: global.offline,
+        fzf: if args.fzf {
+            Some(true)
+        } else if args.no_fzf {
+            Some(false)
+        } else {
+            None
         },
+        no_search_cache: args.no_search_cache,
+        quiet: global.quiet,
+    })
+    .await
+}
+
+async fn cache(cmd: CacheCmd, global: &GlobalArgs) -> Result<()> {
+    match cmd {
+        CacheCmd::List {
+            wide,
+            long,
+            sort,
+            reverse,
+            filter,
+            json,
+            tagged,
+            group_drafts,
+        } => commands::cache::list(commands::cache::ListArgs {
+            wide,
+            long,
+            sort: sort.into(),
+            reverse,
+            filter: SearchFilter::from(&filter),
+            json,
+            tagged,
+            group_drafts,
+        }),
+        CacheCmd::Info {
+            reset_stats: true, ..
+        } => commands::cache::reset_stats(),
+        CacheCmd::Info { json, stats, .. } => commands::cache::info(json, stats),
+        CacheCmd::Remove {
+            document,
+            purge_notes,
+        } => commands::cache::remove(&document, purge_notes),
+        CacheCmd::Grep {
+            pattern,
+            ignore_case,
+            context,
+            before_context,
+            after_context,
+        } => {
+            let found = commands::cache::grep(commands::cache::GrepArgs {
+                pattern,
+                ignore_case,
+                before: before_context.unwrap_or(context),
+                after: after_context.unwrap_or(context),
+            })?;
+            if !found {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        CacheCmd::Has { document } => {
+            if !commands::cache::has(&document)? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        CacheCmd::Clear => commands::cache::clear(),
+        CacheCmd::PruneOldRevisions { keep } => commands::cache::prune_old_revisions(keep as usize),
+        CacheCmd::Recompress => commands::cache::recompress(),
+        CacheCmd::Gc => commands::cache::gc(),
+        CacheCmd::Recent { count } => commands::cache::recent(count),
+        CacheCmd::Backfill => commands::cache::backfill_metadata(global.offline).await,
+        CacheCmd::ExportHtml { dir } => commands::export::html(&dir),
+        CacheCmd::Verify { fix } => {
+            commands::cache::verify(fix, global.offline, global.quiet).await
+        }
+        CacheCmd::Doctor { fix } => commands::cache::doctor(fix, global.offline).await,
+    }
+}
+
+fn alias(cmd: AliasCmd) -> Result<()> {
+    match cmd {
+        AliasCmd::Add { name, document } => commands::alias::add(&name, &document),
+        AliasCmd::Remove { name } => commands::alias::remove(&name),
+        AliasCmd::List => commands::alias::list(),
+    }
+}
+
+fn notes(cmd: NotesCmd) -> Result<()> {
+    match cmd {
+        NotesCmd::Edit { document } => commands::notes::edit(&document),
+        NotesCmd::Show { document } => commands::notes::show(&document),
+        NotesCmd::Export { dir } => commands::notes::export(&dir),
+    }
+}
+
+async fn watch(cmd: WatchCmd, global: &GlobalArgs) -> Result<()> {
+    match cmd {
+        WatchCmd::Add { document } => commands::watch::add(&document, global.offline).await,
+        WatchCmd::Remove { document } => commands::watch::remove(&document),
+        WatchCmd::List => commands::watch::list(),
+        WatchCmd::Check { json, dry_run } => {
+            commands::watch::check(json, dry_run, global.offline).await
+        }
     }
 }
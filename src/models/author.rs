@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use super::Document;
+
+/// Someone the Datatracker knows, with what's needed to tell namesakes
+/// apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Person {
+    /// Datatracker person ID.
+    pub id: u64,
+    pub name: String,
+    /// Affiliation on their most recent document, if they gave one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affiliation: Option<String>,
+    /// Address on their most recent document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl Person {
+    /// `Fastly, mnot@mnot.net`: affiliation and email, whichever are known.
+    pub fn hint(&self) -> String {
+        [self.affiliation.as_deref(), self.email.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// What [`DataTrackerClient::documents_by_author`](crate::DataTrackerClient::documents_by_author)
+/// made of a name.
+#[derive(Debug, Clone)]
+pub enum AuthorDocuments {
+    /// The one person the name means, and everything they're listed as
+    /// an author of, newest first.
+    Found {
+        person: Person,
+        documents: Vec<Document>,
+    },
+    /// Several people match the name equally well.
+    Ambiguous(Vec<Person>),
+}
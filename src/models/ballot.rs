@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An IESG ballot on a document and the current positions on it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ballot {
+    /// When the ballot was opened.
+    pub opened: DateTime<Utc>,
+    /// When it was closed, if it has been.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<DateTime<Utc>>,
+    /// Each Area Director's latest position, Discusses first.
+    pub positions: Vec<BallotPosition>,
+}
+
+/// One Area Director's position on a ballot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BallotPosition {
+    pub balloter: String,
+    pub position: Position,
+    /// The Discuss text, for Discuss (and Block) positions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discuss: Option<String>,
+}
+
+/// A ballot position, in the order they're listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    Discuss,
+    Block,
+    Yes,
+    NoObjection,
+    Abstain,
+    Recuse,
+    NoRecord,
+}
+
+impl Position {
+    /// Map a Datatracker `ballotpositionname` slug, e.g. `noobj`.
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        Some(match slug {
+            "discuss" => Position::Discuss,
+            "block" => Position::Block,
+            "yes" => Position::Yes,
+            "noobj" => Position::NoObjection,
+            "abstain" => Position::Abstain,
+            "recuse" => Position::Recuse,
+            "norecord" => Position::NoRecord,
+            _ => return None,
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Position::Discuss => "Discuss",
+            Position::Block => "Block",
+            Position::Yes => "Yes",
+            Position::NoObjection => "No Objection",
+            Position::Abstain => "Abstain",
+            Position::Recuse => "Recuse",
+            Position::NoRecord => "No Record",
+        }
+    }
+}
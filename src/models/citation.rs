@@ -0,0 +1,295 @@
+use chrono::{Datelike, NaiveDate};
+
+use super::{Document, DocumentType, RfcIndexEntry};
+use crate::api::RFC_EDITOR_BASE_URL;
+
+/// Output formats for [`Citation`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CitationStyle {
+    /// The RFC Editor's recommended reference text
+    #[default]
+    Text,
+    BibTex,
+    /// RIS, as imported by EndNote and Zotero
+    Ris,
+}
+
+/// What's needed to cite an RFC, gathered from the RFC index or the
+/// Datatracker and formatted per [`CitationStyle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub number: u32,
+    pub title: String,
+    /// As the index writes them, initials first: `J. Iyengar`
+    pub authors: Vec<String>,
+    /// Only the month and year are cited
+    pub published: Option<NaiveDate>,
+    pub doi: String,
+    pub page_count: Option<u32>,
+}
+
+impl Citation {
+    /// A citation of RFC `number` with no authors, date, or page count, and
+    /// the DOI the RFC Editor assigns (`10.17487/RFC0791`).
+    pub fn new(number: u32, title: impl Into<String>) -> Self {
+        Self {
+            number,
+            title: title.into(),
+            authors: Vec::new(),
+            published: None,
+            doi: format!("10.17487/RFC{:04}", number),
+            page_count: None,
+        }
+    }
+
+    /// Cite `doc`, which the Datatracker describes without authors; `None`
+    /// for drafts.
+    pub fn from_document(doc: &Document) -> Option<Self> {
+        let DocumentType::Rfc(number) = doc.doc_type else {
+            return None;
+        };
+        Some(Self {
+            published: doc.published,
+            ..Self::new(number, doc.title.clone())
+        })
+    }
+
+    pub fn from_index_entry(entry: &RfcIndexEntry) -> Self {
+        let mut citation = Self::new(entry.number, entry.title.clone());
+        citation.authors = entry.authors.clone();
+        citation.published = entry.published;
+        citation.page_count = entry.page_count;
+        if let Some(doi) = &entry.doi {
+            citation.doi = doi.clone();
+        }
+        citation
+    }
+
+    /// The RFC Editor's info page, which every style links to.
+    pub fn url(&self) -> String {
+        format!("{}/info/rfc{}", RFC_EDITOR_BASE_URL, self.number)
+    }
+
+    pub fn format(&self, style: CitationStyle) -> String {
+        match style {
+            CitationStyle::Text => self.to_text(),
+            CitationStyle::BibTex => self.to_bibtex(),
+            CitationStyle::Ris => self.to_ris(),
+        }
+    }
+
+    /// `Iyengar, J. and M. Thomson, "QUIC: ...", RFC 9000, DOI
+    /// 10.17487/RFC9000, May 2021.`
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.authors.is_empty() {
+            parts.push(text_authors(&self.authors));
+        }
+        parts.push(format!("\"{}\"", self.title));
+        parts.push(format!("RFC {}", self.number));
+        parts.push(format!("DOI {}", self.doi));
+        if let Some(published) = self.published {
+            parts.push(published.format("%B %Y").to_string());
+        }
+        format!("{}.", parts.join(", "))
+    }
+
+    /// A `@misc` entry keyed `rfc9000`, laid out like the Datatracker's.
+    pub fn to_bibtex(&self) -> String {
+        let mut fields = vec![
+            ("series", "{Request for Comments}".to_string()),
+            ("number", self.number.to_string()),
+            ("howpublished", format!("{{RFC {}}}", self.number)),
+            ("publisher", "{RFC Editor}".to_string()),
+            ("doi", format!("{{{}}}", self.doi)),
+            ("url", format!("{{{}}}", self.url())),
+        ];
+        if !self.authors.is_empty() {
+            fields.push((
+                "author",
+                format!("{{{}}}", bibtex_escape(&self.authors.join(" and "))),
+            ));
+        }
+        fields.push(("title", format!("{{{{{}}}}}", bibtex_escape(&self.title))));
+        if let Some(pages) = self.page_count {
+            fields.push(("pagetotal", pages.to_string()));
+        }
+        if let Some(published) = self.published {
+            fields.push(("year", published.year().to_string()));
+            fields.push(("month", published.format("%b").to_string().to_lowercase()));
+        }
+        let mut out = format!("@misc{{rfc{},\n", self.number);
+        for (name, value) in fields {
+            out.push_str(&format!("  {} = {},\n", name, value));
+        }
+        out.push('}');
+        out
+    }
+
+    /// A `RPRT` record, authors surname first.
+    pub fn to_ris(&self) -> String {
+        let mut tags = vec![("TY", "RPRT".to_string())];
+        tags.extend(
+            self.authors
+                .iter()
+                .map(|author| ("AU", surname_first(author))),
+        );
+        tags.push(("TI", self.title.clone()));
+        tags.push(("T3", "Request for Comments".to_string()));
+        tags.push(("SN", format!("RFC {}", self.number)));
+        if let Some(published) = self.published {
+            tags.push(("PY", published.year().to_string()));
+            tags.push(("DA", published.format("%Y/%m").to_string()));
+        }
+        tags.push(("PB", "RFC Editor".to_string()));
+        tags.push(("DO", self.doi.clone()));
+        tags.push(("UR", self.url()));
+        let mut out: String = tags
+            .iter()
+            .map(|(tag, value)| format!("{}  - {}\n", tag, value))
+            .collect();
+        out.push_str("ER  - ");
+        out
+    }
+}
+
+/// Authors joined the RFC Editor's way, surname first except the last of
+/// several: `Li, T.`, `Li, T. and S. Hares`, `Rekhter, Y., Li, T., and S.
+/// Hares`.
+fn text_authors(authors: &[String]) -> String {
+    match authors {
+        [] => String::new(),
+        [one] => surname_first(one),
+        [rest @ .., last] => {
+            let rest: Vec<String> = rest.iter().map(|a| surname_first(a)).collect();
+            let comma = if rest.len() > 1 { "," } else { "" };
+            format!("{}{} and {}", rest.join(", "), comma, last)
+        }
+    }
+}
+
+/// `J. Iyengar` → `Iyengar, J.`; names without leading initials (`IAB`)
+/// are left alone.
+fn surname_first(name: &str) -> String {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let initials = words.iter().take_while(|w| w.ends_with('.')).count();
+    if initials == 0 || initials == words.len() {
+        return name.to_string();
+    }
+    format!(
+        "{}, {}",
+        words[initials..].join(" "),
+        words[..initials].join(" ")
+    )
+}
+
+fn bibtex_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '&' | '%' | '$' | '#' | '_') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quic() -> Citation {
+        Citation {
+            authors: vec!["J. Iyengar".to_string(), "M. Thomson".to_string()],
+            published: NaiveDate::from_ymd_opt(2021, 5, 1),
+            page_count: Some(151),
+            ..Citation::new(9000, "QUIC: A UDP-Based Multiplexed and Secure Transport")
+        }
+    }
+
+    #[test]
+    fn text_matches_the_rfc_editor() {
+        assert_eq!(
+            quic().to_text(),
+            "Iyengar, J. and M. Thomson, \"QUIC: A UDP-Based Multiplexed and Secure \
+             Transport\", RFC 9000, DOI 10.17487/RFC9000, May 2021."
+        );
+
+        let tls = Citation {
+            authors: vec!["E. Rescorla".to_string()],
+            published: NaiveDate::from_ymd_opt(2018, 8, 1),
+            ..Citation::new(
+                8446,
+                "The Transport Layer Security (TLS) Protocol Version 1.3",
+            )
+        };
+        assert_eq!(
+            tls.to_text(),
+            "Rescorla, E., \"The Transport Layer Security (TLS) Protocol Version 1.3\", \
+             RFC 8446, DOI 10.17487/RFC8446, August 2018."
+        );
+
+        let bgp = Citation {
+            authors: vec![
+                "Y. Rekhter".to_string(),
+                "T. Li".to_string(),
+                "S. Hares".to_string(),
+            ],
+            published: NaiveDate::from_ymd_opt(2006, 1, 1),
+            ..Citation::new(4271, "A Border Gateway Protocol 4 (BGP-4)")
+        };
+        assert_eq!(
+            bgp.to_text(),
+            "Rekhter, Y., Li, T., and S. Hares, \"A Border Gateway Protocol 4 (BGP-4)\", \
+             RFC 4271, DOI 10.17487/RFC4271, January 2006."
+        );
+
+        assert_eq!(
+            Citation::new(1, "Host Software").to_text(),
+            "\"Host Software\", RFC 1, DOI 10.17487/RFC0001."
+        );
+    }
+
+    #[test]
+    fn bibtex_and_ris_records() {
+        assert_eq!(
+            quic().to_bibtex(),
+            "@misc{rfc9000,
+  series = {Request for Comments},
+  number = 9000,
+  howpublished = {RFC 9000},
+  publisher = {RFC Editor},
+  doi = {10.17487/RFC9000},
+  url = {https://www.rfc-editor.org/info/rfc9000},
+  author = {J. Iyengar and M. Thomson},
+  title = {{QUIC: A UDP-Based Multiplexed and Secure Transport}},
+  pagetotal = 151,
+  year = 2021,
+  month = may,
+}"
+        );
+        assert_eq!(
+            quic().to_ris(),
+            "TY  - RPRT
+AU  - Iyengar, J.
+AU  - Thomson, M.
+TI  - QUIC: A UDP-Based Multiplexed and Secure Transport
+T3  - Request for Comments
+SN  - RFC 9000
+PY  - 2021
+DA  - 2021/05
+PB  - RFC Editor
+DO  - 10.17487/RFC9000
+UR  - https://www.rfc-editor.org/info/rfc9000
+ER  - "
+        );
+    }
+
+    #[test]
+    fn names_and_titles_are_adapted_per_style() {
+        assert_eq!(surname_first("IAB"), "IAB");
+        assert_eq!(surname_first("R. van Rein"), "van Rein, R.");
+        assert_eq!(surname_first("D. E. Eastlake 3rd"), "Eastlake 3rd, D. E.");
+        assert_eq!(bibtex_escape("A & B_C"), "A \\& B\\_C");
+    }
+}
@@ -1,6 +1,8 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 
+use super::{truncate_to_width, StdLevel};
 use crate::api::DATATRACKER_BASE_URL;
 
 /// The type of document - either an RFC or an Internet-Draft
@@ -13,31 +15,28 @@ pub enum DocumentType {
 }
 
 impl DocumentType {
-    /// Parse a user-supplied identifier into a `DocumentType`.
+    /// Parse a user-supplied identifier into a `DocumentType`, as
+    /// [`normalize_document_identifier`] names it.
     ///
     /// Recognized RFC forms (case-insensitive, leading/trailing whitespace
-    /// trimmed): bare numbers like `9000`, prefixed forms like `rfc9000`
-    /// or `RFC 9000`. Anything else is treated as a draft name; a missing
-    /// `draft-` prefix is added automatically so users can write either
-    /// `rfc 4271`-style shorthand or full draft names.
-    pub fn from_user_input(s: &str) -> Self {
-        let s = s.trim().to_lowercase();
-
-        if let Some(num_str) = s.strip_prefix("rfc") {
-            if let Ok(num) = num_str.trim().parse::<u32>() {
-                return DocumentType::Rfc(num);
-            }
-        }
-
-        if let Ok(num) = s.parse::<u32>() {
-            return DocumentType::Rfc(num);
-        }
+    /// trimmed): bare numbers like `9000`, prefixed forms like `rfc9000`,
+    /// `RFC 9000`, or `rfc-9000`. Anything else is treated as a draft name;
+    /// a missing `draft-` prefix is added automatically so users can write
+    /// either `rfc 4271`-style shorthand or full draft names.
+    pub fn from_user_input(s: &str) -> Result<Self, InvalidIdentifier> {
+        normalize_document_identifier(s).map(|name| Self::from_canonical_name(&name))
+    }
 
-        if s.starts_with("draft-") {
-            DocumentType::Draft(s)
-        } else {
-            DocumentType::Draft(format!("draft-{}", s))
+    /// Like [`from_user_input`](Self::from_user_input), but only for input
+    /// that unambiguously names a document: an RFC number in any of the
+    /// forms above, or a name starting with `draft-`. Returns `None` for
+    /// anything that would need a `draft-` prefix guessed.
+    pub fn parse_identifier(s: &str) -> Option<Self> {
+        let s = clean_identifier(s);
+        if let Some(num) = rfc_number(&s) {
+            return Some(DocumentType::Rfc(num));
         }
+        s.starts_with("draft-").then_some(DocumentType::Draft(s))
     }
 
     /// Parse a canonical, server-supplied document name (e.g. `rfc9000`,
@@ -69,6 +68,14 @@ impl DocumentType {
         }
     }
 
+    /// Short type tag for column display: `RFC` or `I-D`.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            DocumentType::Rfc(_) => "RFC",
+            DocumentType::Draft(_) => "I-D",
+        }
+    }
+
     /// Get the IETF Datatracker URL for this document
     pub fn datatracker_url(&self) -> String {
         match self {
@@ -80,6 +87,115 @@ impl DocumentType {
     }
 }
 
+/// The canonical name a user-supplied identifier stands for, so every
+/// spelling of a document caches and looks up under one name: lowercased,
+/// without surrounding punctuation or a `.txt`/`.html` extension, RFC forms
+/// as `rfcN`, and anything else a draft with a `draft-` prefix. Input
+/// that names nothing (empty, a bare `draft`, or an RFC number too large
+/// to be one) is an [`InvalidIdentifier`].
+///
+/// ```
+/// use rfc::models::normalize_document_identifier;
+///
+/// assert_eq!(normalize_document_identifier("RFC 9000.").unwrap(), "rfc9000");
+/// assert_eq!(
+///     normalize_document_identifier("IETF-QUIC-Transport-34.txt").unwrap(),
+///     "draft-ietf-quic-transport-34"
+/// );
+/// assert!(normalize_document_identifier("draft-").is_err());
+/// ```
+pub fn normalize_document_identifier(input: &str) -> Result<String, InvalidIdentifier> {
+    let id = clean_identifier(input);
+    if let Some(num) = rfc_number(&id) {
+        return Ok(format!("rfc{}", num));
+    }
+    let digits = rfc_digits(&id);
+    if id.is_empty() || id == "draft" || digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InvalidIdentifier {
+            input: input.to_string(),
+        });
+    }
+    Ok(if id.starts_with("draft-") {
+        id
+    } else {
+        format!("draft-{}", id)
+    })
+}
+
+/// Returned for a document argument that can't name a document, so
+/// callers can tell it apart from lookup failures by downcasting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidIdentifier {
+    pub input: String,
+}
+
+impl std::fmt::Display for InvalidIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.input.trim().is_empty() {
+            write!(
+                f,
+                "Expected an RFC number or draft name, got an empty argument"
+            )
+        } else {
+            write!(
+                f,
+                "Expected an RFC number or draft name, got '{}'",
+                self.input
+            )
+        }
+    }
+}
+
+impl std::error::Error for InvalidIdentifier {}
+
+/// `input` trimmed and lowercased, with the punctuation it was quoted or
+/// listed with (`RFC 9000,`, `"draft-foo-01".`) and a file extension
+/// pasted from a URL or directory listing removed.
+fn clean_identifier(input: &str) -> String {
+    let not_name = |c: char| !c.is_alphanumeric();
+    let id = input.trim().to_lowercase();
+    let id = id.trim_matches(not_name);
+    let id = [".txt", ".html"]
+        .iter()
+        .find_map(|ext| id.strip_suffix(ext))
+        .unwrap_or(id);
+    id.trim_end_matches(not_name).to_string()
+}
+
+/// The number of an RFC written `9000`, `rfc9000`, `rfc 9000`, or
+/// `rfc-9000` (already lowercased).
+fn rfc_number(id: &str) -> Option<u32> {
+    let digits = rfc_digits(id);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// What would be an RFC number in `id`: the rest after any `rfc` prefix
+/// and the space or hyphen after it.
+fn rfc_digits(id: &str) -> &str {
+    match id.strip_prefix("rfc") {
+        Some(rest) => rest.strip_prefix(['-', ' ']).unwrap_or(rest).trim(),
+        None => id,
+    }
+}
+
+/// Split a draft name into its family name and revision:
+/// `draft-ietf-quic-transport-34` is `("draft-ietf-quic-transport",
+/// Some(34))`. Revisions are the two-digit final token the Datatracker
+/// appends; any other name comes back whole with `None`.
+pub fn draft_base_and_rev(name: &str) -> (&str, Option<u32>) {
+    match name.rsplit_once('-') {
+        Some((base, rev))
+            if base.contains('-') && rev.len() == 2 && rev.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            (base, rev.parse().ok())
+        }
+        _ => (name, None),
+    }
+}
+
 impl std::fmt::Display for DocumentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.display_name())
@@ -87,7 +203,7 @@ impl std::fmt::Display for DocumentType {
 }
 
 /// Document content format
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Format {
     Html,
     Text,
@@ -102,11 +218,51 @@ impl Format {
     }
 }
 
+/// Where a draft stands: the Datatracker's "draft" state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DraftState {
+    Active,
+    Expired,
+    /// Replaced by another draft
+    Replaced,
+    /// Published as an RFC
+    Rfc,
+    /// Withdrawn by its authors or the IETF
+    Withdrawn,
+}
+
+impl DraftState {
+    /// Parse the Datatracker's slug for a draft state: `active`,
+    /// `expired`, `repl`, `rfc`, `auth-rm`, or `ietf-rm`.
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        match slug {
+            "active" => Some(DraftState::Active),
+            "expired" => Some(DraftState::Expired),
+            "repl" => Some(DraftState::Replaced),
+            "rfc" => Some(DraftState::Rfc),
+            "auth-rm" | "ietf-rm" => Some(DraftState::Withdrawn),
+            _ => None,
+        }
+    }
+
+    /// Lowercase label, as in the `[expired]` search marker.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DraftState::Active => "active",
+            DraftState::Expired => "expired",
+            DraftState::Replaced => "replaced",
+            DraftState::Rfc => "rfc",
+            DraftState::Withdrawn => "withdrawn",
+        }
+    }
+}
+
 /// An IETF document (RFC or Internet-Draft).
 ///
 /// Only the fields the CLI actually displays are kept; richer metadata
-/// (pages, authors, publication date, etc.) lives on the wire type and
-/// is dropped at the API boundary.
+/// (pages, authors, etc.) lives on the wire type and is dropped at the
+/// API boundary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     /// Canonical name (e.g. `rfc9000` or `draft-ietf-quic-transport-34`).
@@ -114,6 +270,37 @@ pub struct Document {
     /// Human-readable title.
     pub title: String,
     pub doc_type: DocumentType,
+    /// Publication date for RFCs, last revision date for drafts.
+    #[serde(default)]
+    pub published: Option<NaiveDate>,
+    /// Abstract text as published (unwrapped, may contain hard breaks).
+    #[serde(rename = "abstract", default, skip_serializing_if = "Option::is_none")]
+    pub abstract_text: Option<String>,
+    /// RFC numbers that obsolete this document. Only populated when the
+    /// caller looked the relationship up (see `DataTrackerClient::obsoleted_by`).
+    #[serde(default)]
+    pub obsoleted_by: Vec<u32>,
+    /// Standardization status, for RFCs that have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub std_level: Option<StdLevel>,
+    /// Where a draft stands, when the search that found it looked that up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft_state: Option<DraftState>,
+    /// When a draft expires (or expired).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+    /// Authors as the RFC index lists them, e.g. `J. Iyengar, Ed.`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Working group acronym, for documents published through one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wg: Option<String>,
+    /// RFC numbers that update this document, when known.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub updated_by: Vec<u32>,
+    /// Datatracker tags on the document, by slug (`errata`, `need-rev`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
 }
 
 impl Document {
@@ -122,18 +309,83 @@ impl Document {
             name,
             title,
             doc_type,
+            published: None,
+            abstract_text: None,
+            obsoleted_by: Vec::new(),
+            std_level: None,
+            draft_state: None,
+            expires: None,
+            authors: Vec::new(),
+            wg: None,
+            updated_by: Vec::new(),
+            keywords: Vec::new(),
         }
     }
 
-    /// Get a short display title (truncated if necessary)
-    pub fn short_title(&self, max_len: usize) -> String {
-        if self.title.chars().count() <= max_len {
-            self.title.clone()
-        } else {
-            let truncated: String = self.title.chars().take(max_len.saturating_sub(3)).collect();
-            format!("{}...", truncated)
+    /// Whether any newer RFC obsoletes this one.
+    pub fn is_obsoleted(&self) -> bool {
+        !self.obsoleted_by.is_empty()
+    }
+
+    /// Get a short display title, truncated to `max_width` terminal cells
+    /// if necessary
+    pub fn short_title(&self, max_width: usize) -> String {
+        truncate_to_width(&self.title, max_width)
+    }
+}
+
+/// A document plus the Datatracker's lifecycle facts about it, from
+/// [`DataTrackerClient::get_document_details`](crate::DataTrackerClient::get_document_details).
+/// Each optional field is `None` when the Datatracker doesn't say, or the
+/// lookup behind it failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDetails {
+    #[serde(flatten)]
+    pub document: Document,
+    /// Current revision of a draft, e.g. `34`.
+    #[serde(default)]
+    pub rev: Option<String>,
+    /// When an active draft expires.
+    #[serde(default)]
+    pub expires: Option<NaiveDate>,
+    /// Availability of a draft: `Active`, `Expired`, `Replaced`, `RFC`, ...
+    #[serde(default)]
+    pub state: Option<String>,
+    /// IESG processing state, e.g. `Publication Requested`.
+    #[serde(default)]
+    pub iesg_state: Option<String>,
+    /// Working group state, e.g. `WG Document`.
+    #[serde(default)]
+    pub wg_state: Option<String>,
+    /// The RFC a draft was published as.
+    #[serde(default)]
+    pub rfc_number: Option<u32>,
+    /// Drafts that replace this one.
+    #[serde(default)]
+    pub replaced_by: Vec<String>,
+}
+
+impl DocumentDetails {
+    pub fn new(document: Document) -> Self {
+        Self {
+            document,
+            rev: None,
+            expires: None,
+            state: None,
+            iesg_state: None,
+            wg_state: None,
+            rfc_number: None,
+            replaced_by: Vec::new(),
         }
     }
+
+    /// Whether the expiry date still matters: the draft is neither
+    /// published, replaced, nor otherwise retired.
+    pub fn is_active(&self) -> bool {
+        self.rfc_number.is_none()
+            && self.replaced_by.is_empty()
+            && self.state.as_deref().is_none_or(|s| s == "Active")
+    }
 }
 
 #[cfg(test)]
@@ -143,23 +395,23 @@ mod tests {
     #[test]
     fn test_from_user_input_rfc() {
         assert_eq!(
-            DocumentType::from_user_input("9000"),
+            DocumentType::from_user_input("9000").unwrap(),
             DocumentType::Rfc(9000)
         );
         assert_eq!(
-            DocumentType::from_user_input("rfc9000"),
+            DocumentType::from_user_input("rfc9000").unwrap(),
             DocumentType::Rfc(9000)
         );
         assert_eq!(
-            DocumentType::from_user_input("RFC9000"),
+            DocumentType::from_user_input("RFC9000").unwrap(),
             DocumentType::Rfc(9000)
         );
         assert_eq!(
-            DocumentType::from_user_input("RFC 9000"),
+            DocumentType::from_user_input("RFC 9000").unwrap(),
             DocumentType::Rfc(9000)
         );
         assert_eq!(
-            DocumentType::from_user_input("  rfc9000  "),
+            DocumentType::from_user_input("  rfc9000  ").unwrap(),
             DocumentType::Rfc(9000)
         );
     }
@@ -167,16 +419,107 @@ mod tests {
     #[test]
     fn test_from_user_input_draft() {
         assert_eq!(
-            DocumentType::from_user_input("draft-ietf-quic-transport-34"),
+            DocumentType::from_user_input("draft-ietf-quic-transport-34").unwrap(),
             DocumentType::Draft("draft-ietf-quic-transport-34".to_string())
         );
         // Bare names get a draft- prefix added.
         assert_eq!(
-            DocumentType::from_user_input("ietf-quic-transport"),
+            DocumentType::from_user_input("ietf-quic-transport").unwrap(),
             DocumentType::Draft("draft-ietf-quic-transport".to_string())
         );
     }
 
+    #[test]
+    fn identifiers_normalize_to_one_name() {
+        for input in [
+            "draft-ietf-quic-transport-34",
+            "Draft-IETF-QUIC-Transport-34",
+            "IETF-QUIC-Transport-34",
+            "  draft-ietf-quic-transport-34.txt ",
+            "draft-ietf-quic-transport-34.TXT,",
+            "\"draft-ietf-quic-transport-34\".",
+            "draft-ietf-quic-transport-34.html",
+        ] {
+            assert_eq!(
+                normalize_document_identifier(input).unwrap(),
+                "draft-ietf-quic-transport-34",
+                "{input}"
+            );
+        }
+        for input in [
+            "9000",
+            "RFC9000",
+            "rfc 9000",
+            "RFC 9000.",
+            "rfc9000.txt",
+            "RFC9000);",
+            "RFC-9000",
+            "rfc-9000.txt",
+        ] {
+            assert_eq!(
+                normalize_document_identifier(input).unwrap(),
+                "rfc9000",
+                "{input}"
+            );
+            assert_eq!(
+                DocumentType::from_user_input(input).unwrap(),
+                DocumentType::Rfc(9000)
+            );
+        }
+        assert_eq!(
+            DocumentType::from_user_input("IETF-QUIC-Transport-34.").unwrap(),
+            DocumentType::Draft("draft-ietf-quic-transport-34".to_string())
+        );
+    }
+
+    #[test]
+    fn identifiers_that_name_nothing_are_rejected() {
+        for input in [
+            "",
+            "  ",
+            "\"\".",
+            "draft",
+            "draft-",
+            "Draft-.txt",
+            "rfc",
+            "4294967296",
+            "rfc 99999999999",
+        ] {
+            assert_eq!(
+                normalize_document_identifier(input),
+                Err(InvalidIdentifier {
+                    input: input.to_string()
+                }),
+                "{input}"
+            );
+            assert!(DocumentType::parse_identifier(input).is_none(), "{input}");
+        }
+        assert_eq!(
+            normalize_document_identifier("").unwrap_err().to_string(),
+            "Expected an RFC number or draft name, got an empty argument"
+        );
+        assert_eq!(
+            normalize_document_identifier("4294967296")
+                .unwrap_err()
+                .to_string(),
+            "Expected an RFC number or draft name, got '4294967296'"
+        );
+    }
+
+    #[test]
+    fn test_parse_identifier() {
+        assert_eq!(
+            DocumentType::parse_identifier("RFC 4271"),
+            Some(DocumentType::Rfc(4271))
+        );
+        assert_eq!(
+            DocumentType::parse_identifier("Draft-IETF-Foo"),
+            Some(DocumentType::Draft("draft-ietf-foo".to_string()))
+        );
+        assert_eq!(DocumentType::parse_identifier("bgp"), None);
+        assert_eq!(DocumentType::parse_identifier("rfcfoo"), None);
+    }
+
     #[test]
     fn test_from_canonical_name() {
         assert_eq!(
@@ -203,6 +546,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_document_type_tag() {
+        assert_eq!(DocumentType::Rfc(9000).tag(), "RFC");
+        assert_eq!(
+            DocumentType::Draft("draft-ietf-quic-transport".to_string()).tag(),
+            "I-D"
+        );
+    }
+
     #[test]
     fn test_datatracker_url() {
         assert_eq!(
@@ -219,6 +571,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_draft_base_and_rev() {
+        assert_eq!(
+            draft_base_and_rev("draft-ietf-quic-transport-34"),
+            ("draft-ietf-quic-transport", Some(34))
+        );
+        assert_eq!(
+            draft_base_and_rev("draft-ietf-quic-transport-00"),
+            ("draft-ietf-quic-transport", Some(0))
+        );
+        assert_eq!(
+            draft_base_and_rev("draft-ietf-6man-rfc4291bis-09"),
+            ("draft-ietf-6man-rfc4291bis", Some(9))
+        );
+    }
+
+    #[test]
+    fn test_draft_base_and_rev_leaves_unversioned_names_alone() {
+        for name in [
+            "draft-ietf-quic-transport",
+            "draft-ietf-tls-rfc8446bis",
+            "draft-ietf-idr-bgp-2",
+            "draft-ietf-lamps-rfc5280-2024",
+            "draft-ietf-foo-3a",
+            "draft-ietf-foo-",
+            "draft-34",
+            "draft",
+            "",
+            "rfc9000",
+            "draft-ietf-foo-٣٤",
+        ] {
+            assert_eq!(draft_base_and_rev(name), (name, None), "{}", name);
+        }
+    }
+
     #[test]
     fn test_short_title() {
         let doc = Document::new(
@@ -238,7 +625,8 @@ mod tests {
 
         // Edge cases
         assert_eq!(doc.short_title(3), "...");
-        assert_eq!(doc.short_title(0), "...");
+        assert_eq!(doc.short_title(2), "A ");
+        assert_eq!(doc.short_title(0), "");
     }
 
     #[test]
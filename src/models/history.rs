@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One entry in a document's Datatracker timeline: a new revision, a
+/// state change, an IESG ballot, publication, and so on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocEvent {
+    pub time: DateTime<Utc>,
+    /// Datatracker event type slug, e.g. `new_revision` or `changed_state`.
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// What happened, as plain text.
+    pub description: String,
+    /// The document revision the event applies to, when it names one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+}
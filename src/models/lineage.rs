@@ -0,0 +1,96 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::DocumentType;
+
+/// How one document in a [`Lineage`] leads to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineageLink {
+    /// A newer draft replaced it, e.g. when a WG adopted an individual
+    /// submission
+    ReplacedBy,
+    /// The draft was published as the RFC
+    PublishedAs,
+}
+
+impl LineageLink {
+    pub fn label(self) -> &'static str {
+        match self {
+            LineageLink::ReplacedBy => "replaced by",
+            LineageLink::PublishedAs => "published as",
+        }
+    }
+}
+
+/// One document in a [`Lineage`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageHop {
+    /// Canonical name without a revision: `draft-ietf-quic-transport`,
+    /// `rfc9000`
+    pub name: String,
+    pub title: String,
+    /// A draft's final revision, e.g. `34`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Publication date for RFCs, date of the final revision for drafts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    /// How the next hop follows from this one; `None` on the last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<LineageLink>,
+}
+
+impl LineageHop {
+    /// `draft-ietf-quic-transport-34`, or `RFC 9000`.
+    pub fn label(&self) -> String {
+        match (DocumentType::from_canonical_name(&self.name), &self.rev) {
+            (DocumentType::Draft(name), Some(rev)) => format!("{}-{}", name, rev),
+            (doc_type, _) => doc_type.to_string(),
+        }
+    }
+}
+
+/// The drafts a document went through on its way to an RFC, oldest
+/// first: individual submissions replaced by WG drafts, and the draft
+/// published as the RFC.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lineage {
+    pub hops: Vec<LineageHop>,
+}
+
+impl Lineage {
+    /// The RFC the lineage ends in, if it got that far.
+    pub fn rfc(&self) -> Option<u32> {
+        match DocumentType::from_canonical_name(&self.hops.last()?.name) {
+            DocumentType::Rfc(number) => Some(number),
+            DocumentType::Draft(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hops_are_labelled_with_their_final_revision() {
+        let hop = |name: &str, rev: Option<&str>| LineageHop {
+            name: name.to_string(),
+            title: String::new(),
+            rev: rev.map(str::to_string),
+            date: None,
+            next: None,
+        };
+        let lineage = Lineage {
+            hops: vec![
+                hop("draft-ietf-quic-transport", Some("34")),
+                hop("rfc9000", None),
+            ],
+        };
+        assert_eq!(lineage.hops[0].label(), "draft-ietf-quic-transport-34");
+        assert_eq!(lineage.hops[1].label(), "RFC 9000");
+        assert_eq!(lineage.rfc(), Some(9000));
+        assert_eq!(Lineage::default().rfc(), None);
+    }
+}
@@ -1,5 +1,26 @@
+mod author;
+mod ballot;
+mod citation;
 mod document;
+mod history;
+mod lineage;
+mod rfc_index;
 mod search;
+mod text;
 
-pub use document::{Document, DocumentType, Format};
-pub use search::{SearchFilter, SearchResult};
+pub use author::{AuthorDocuments, Person};
+pub use ballot::{Ballot, BallotPosition, Position};
+pub use citation::{Citation, CitationStyle};
+pub use document::{
+    draft_base_and_rev, normalize_document_identifier, Document, DocumentDetails, DocumentType,
+    DraftState, Format, InvalidIdentifier,
+};
+pub use history::DocEvent;
+pub use lineage::{Lineage, LineageHop, LineageLink};
+pub use rfc_index::{IndexLookup, RfcIndex, RfcIndexEntry};
+#[cfg(feature = "cli")]
+pub(crate) use search::match_title;
+pub use search::{
+    parse_date_bound, QueryMode, SearchFilter, SearchOptions, SearchPage, SearchResult, StdLevel,
+};
+pub use text::{display_width, pad_to_width, truncate_to_width};
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::search::{match_title, query_words};
+use super::{Document, DocumentType, StdLevel};
+
+/// Local copy of the RFC Editor's index, as stored in the cache.
+///
+/// Only what the CLI uses for offline lookups is kept (no abstracts or
+/// keywords), which keeps the file small enough to load on every
+/// invocation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RfcIndex {
+    /// When the index was last synced (or confirmed unchanged)
+    pub synced_at: Option<DateTime<Utc>>,
+    /// `Last-Modified` of the upstream file, replayed as
+    /// `If-Modified-Since` on the next sync.
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Published RFCs by number
+    pub entries: BTreeMap<u32, RfcIndexEntry>,
+    /// Numbers that were reserved but never published
+    #[serde(default)]
+    pub not_issued: BTreeSet<u32>,
+}
+
+/// One published RFC from the index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RfcIndexEntry {
+    pub number: u32,
+    pub title: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// Publication date. The index usually only gives month and year, in
+    /// which case the day is the 1st.
+    #[serde(default)]
+    pub published: Option<NaiveDate>,
+    /// Current status as written in the index, e.g. `PROPOSED STANDARD`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub stream: Option<String>,
+    /// Working group acronym, when published through one.
+    #[serde(default)]
+    pub wg: Option<String>,
+    #[serde(default)]
+    pub page_count: Option<u32>,
+    #[serde(default)]
+    pub doi: Option<String>,
+    #[serde(default)]
+    pub obsoletes: Vec<u32>,
+    #[serde(default)]
+    pub obsoleted_by: Vec<u32>,
+    #[serde(default)]
+    pub updates: Vec<u32>,
+    #[serde(default)]
+    pub updated_by: Vec<u32>,
+}
+
+/// Outcome of looking an RFC number up in the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexLookup<'a> {
+    Published(&'a RfcIndexEntry),
+    /// The number was allocated but the RFC was never published.
+    NotIssued,
+    /// Beyond the highest number the index knows about (or otherwise
+    /// absent) — possibly published since the last sync.
+    Unknown,
+}
+
+impl RfcIndex {
+    /// Look up an RFC by number.
+    pub fn lookup(&self, number: u32) -> IndexLookup<'_> {
+        if let Some(entry) = self.entries.get(&number) {
+            IndexLookup::Published(entry)
+        } else if self.not_issued.contains(&number) {
+            IndexLookup::NotIssued
+        } else {
+            IndexLookup::Unknown
+        }
+    }
+
+    /// Highest published RFC number in the index.
+    pub fn highest(&self) -> Option<u32> {
+        self.entries.keys().next_back().copied()
+    }
+
+    /// Number of published RFCs in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Title search: every whitespace-separated word of `query` must
+    /// appear in the title (case-insensitive). Results are ranked by how
+    /// early the first word appears in the title, then newest first.
+    pub fn search(&self, query: &str) -> Vec<&RfcIndexEntry> {
+        let words = query_words(query);
+        let mut hits: Vec<(usize, &RfcIndexEntry)> = self
+            .entries
+            .values()
+            .filter_map(|entry| match_title(&entry.title, &words).map(|pos| (pos, entry)))
+            .collect();
+
+        hits.sort_by(|(pa, a), (pb, b)| pa.cmp(pb).then(b.number.cmp(&a.number)));
+        hits.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+impl RfcIndexEntry {
+    /// The `Document` view of this entry, for rendering alongside search
+    /// results and in the viewer's header.
+    pub fn to_document(&self) -> Document {
+        let doc_type = DocumentType::Rfc(self.number);
+        let mut doc = Document::new(doc_type.name(), self.title.clone(), doc_type);
+        doc.published = self.published;
+        doc.obsoleted_by = self.obsoleted_by.clone();
+        doc.updated_by = self.updated_by.clone();
+        doc.std_level = self.status.as_deref().and_then(StdLevel::from_index_status);
+        doc.authors = self.authors.clone();
+        doc.wg = self.wg.clone();
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(number: u32, title: &str) -> RfcIndexEntry {
+        RfcIndexEntry {
+            number,
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn index() -> RfcIndex {
+        let mut index = RfcIndex::default();
+        for e in [
+            entry(1771, "A Border Gateway Protocol 4 (BGP-4)"),
+            entry(4271, "A Border Gateway Protocol 4 (BGP-4)"),
+            entry(8654, "Extended Message Support for the BGP-4 Protocol"),
+            entry(9000, "QUIC: A UDP-Based Multiplexed and Secure Transport"),
+        ] {
+            index.entries.insert(e.number, e);
+        }
+        index.not_issued.insert(1849);
+        index
+    }
+
+    #[test]
+    fn lookup_distinguishes_published_not_issued_and_unknown() {
+        let index = index();
+        assert!(matches!(index.lookup(9000), IndexLookup::Published(e) if e.number == 9000));
+        assert_eq!(index.lookup(1849), IndexLookup::NotIssued);
+        assert_eq!(index.lookup(99999), IndexLookup::Unknown);
+        assert_eq!(index.highest(), Some(9000));
+    }
+
+    #[test]
+    fn search_requires_every_word() {
+        let index = index();
+        let hits: Vec<u32> = index
+            .search("bgp extended")
+            .iter()
+            .map(|e| e.number)
+            .collect();
+        assert_eq!(hits, vec![8654]);
+        assert!(index.search("bgp quic").is_empty());
+        assert!(index.search("   ").is_empty());
+    }
+
+    #[test]
+    fn search_ranks_by_position_then_newest() {
+        let index = index();
+        let hits: Vec<u32> = index.search("BGP").iter().map(|e| e.number).collect();
+        // "BGP" comes later in 8654's title than in the other two.
+        assert_eq!(hits, vec![4271, 1771, 8654]);
+    }
+
+    #[test]
+    fn to_document_carries_date_and_obsolescence() {
+        let mut e = entry(1771, "A Border Gateway Protocol 4 (BGP-4)");
+        e.published = NaiveDate::from_ymd_opt(1995, 3, 1);
+        e.obsoleted_by = vec![4271];
+        e.authors = vec!["Y. Rekhter".into(), "T. Li".into()];
+        e.wg = Some("idr".into());
+        let doc = e.to_document();
+        assert_eq!(doc.name, "rfc1771");
+        assert_eq!(doc.doc_type, DocumentType::Rfc(1771));
+        assert_eq!(doc.published, e.published);
+        assert!(doc.is_obsoleted());
+        assert_eq!(doc.authors, e.authors);
+        assert_eq!(doc.wg.as_deref(), Some("idr"));
+    }
+}
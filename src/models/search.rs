@@ -1,9 +1,10 @@
-use serde::Serialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
-use super::Document;
+use super::{Document, DocumentType};
 
 /// Filter for search results
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SearchFilter {
     /// Only return RFCs
     RfcsOnly,
@@ -23,10 +24,265 @@ impl SearchFilter {
             SearchFilter::Both => None,
         }
     }
+
+    /// Whether documents of this type pass the filter
+    pub fn includes(&self, doc_type: &DocumentType) -> bool {
+        !matches!(
+            (self, doc_type),
+            (SearchFilter::RfcsOnly, DocumentType::Draft(_))
+                | (SearchFilter::DraftsOnly, DocumentType::Rfc(_))
+        )
+    }
+}
+
+/// How a query is split into terms, every one of which must match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryMode {
+    /// Each word is a term, except that `"quoted phrases"` stay whole
+    #[default]
+    Words,
+    /// The whole query is one term, matched as an exact substring
+    Phrase,
+}
+
+impl QueryMode {
+    /// The lowercase terms of `query`. An unclosed quote runs to the end.
+    pub fn terms(self, query: &str) -> Vec<String> {
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        match self {
+            QueryMode::Phrase => {
+                let phrase = normalize(&query.replace('"', " ")).to_lowercase();
+                if phrase.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![phrase]
+                }
+            }
+            QueryMode::Words => query
+                .split('"')
+                .enumerate()
+                .flat_map(|(i, part)| {
+                    // Odd parts were between quotes.
+                    if i % 2 == 1 {
+                        vec![normalize(part)]
+                    } else {
+                        part.split_whitespace().map(str::to_string).collect()
+                    }
+                })
+                .filter(|term| !term.is_empty())
+                .map(|term| term.to_lowercase())
+                .collect(),
+        }
+    }
+
+    fn is_words(&self) -> bool {
+        *self == QueryMode::Words
+    }
+}
+
+/// An RFC's standardization status (the Datatracker's "std level").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StdLevel {
+    ProposedStandard,
+    InternetStandard,
+    /// Best Current Practice
+    Bcp,
+    Informational,
+    Experimental,
+    Historic,
+}
+
+impl StdLevel {
+    /// The Datatracker's slug, as used in `std_level` filters and URIs.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            StdLevel::ProposedStandard => "ps",
+            StdLevel::InternetStandard => "std",
+            StdLevel::Bcp => "bcp",
+            StdLevel::Informational => "inf",
+            StdLevel::Experimental => "exp",
+            StdLevel::Historic => "hist",
+        }
+    }
+
+    /// Parse a slug, or a `std_level` resource URI ending in one
+    /// (`/api/v1/name/stdlevelname/ps/`). Levels outside this enum (draft
+    /// standard, unknown) give `None`.
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        let slug = slug.trim_end_matches('/').rsplit('/').next()?;
+        [
+            StdLevel::ProposedStandard,
+            StdLevel::InternetStandard,
+            StdLevel::Bcp,
+            StdLevel::Informational,
+            StdLevel::Experimental,
+            StdLevel::Historic,
+        ]
+        .into_iter()
+        .find(|level| level.slug() == slug)
+    }
+
+    /// Parse a status as the RFC index writes it, e.g. `PROPOSED STANDARD`.
+    pub fn from_index_status(status: &str) -> Option<Self> {
+        match status {
+            "PROPOSED STANDARD" => Some(StdLevel::ProposedStandard),
+            "INTERNET STANDARD" => Some(StdLevel::InternetStandard),
+            "BEST CURRENT PRACTICE" => Some(StdLevel::Bcp),
+            "INFORMATIONAL" => Some(StdLevel::Informational),
+            "EXPERIMENTAL" => Some(StdLevel::Experimental),
+            "HISTORIC" => Some(StdLevel::Historic),
+            _ => None,
+        }
+    }
+
+    /// Full name, e.g. `Proposed Standard`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StdLevel::ProposedStandard => "Proposed Standard",
+            StdLevel::InternetStandard => "Internet Standard",
+            StdLevel::Bcp => "Best Current Practice",
+            StdLevel::Informational => "Informational",
+            StdLevel::Experimental => "Experimental",
+            StdLevel::Historic => "Historic",
+        }
+    }
+
+    /// Short column label: `PS`, `STD`, `BCP`, `INF`, `EXP`, `HIST`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            StdLevel::ProposedStandard => "PS",
+            StdLevel::InternetStandard => "STD",
+            StdLevel::Bcp => "BCP",
+            StdLevel::Informational => "INF",
+            StdLevel::Experimental => "EXP",
+            StdLevel::Historic => "HIST",
+        }
+    }
+}
+
+/// Everything that narrows a search besides the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchOptions {
+    pub filter: SearchFilter,
+    /// Keep only documents with one of these statuses; empty keeps all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status: Vec<StdLevel>,
+    /// Keep only documents dated on or after this day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<NaiveDate>,
+    /// Keep only documents dated before this day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "QueryMode::is_words")]
+    pub mode: QueryMode,
+    /// Keep only documents tagged with this Datatracker tag slug
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+}
+
+impl SearchOptions {
+    pub fn new(filter: SearchFilter) -> Self {
+        Self {
+            filter,
+            ..Self::default()
+        }
+    }
+
+    /// Keep only documents with one of `status`.
+    pub fn with_status(mut self, status: Vec<StdLevel>) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: QueryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Keep only documents tagged `keyword`, which is matched as a
+    /// lowercase slug. A blank keyword filters nothing.
+    pub fn with_keyword(mut self, keyword: Option<String>) -> Self {
+        self.keyword = keyword
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty());
+        self
+    }
+
+    /// Keep only documents dated in `[since, before)`; either end may be
+    /// open. Fails unless `since` is earlier than `before`.
+    pub fn with_dates(
+        mut self,
+        since: Option<NaiveDate>,
+        before: Option<NaiveDate>,
+    ) -> anyhow::Result<Self> {
+        if let (Some(since), Some(before)) = (since, before) {
+            if since >= before {
+                anyhow::bail!("--since {} must be earlier than --before {}", since, before);
+            }
+        }
+        self.since = since;
+        self.before = before;
+        Ok(self)
+    }
+
+    /// Whether `doc` passes the type, status, date, and keyword filters.
+    /// Undated documents fail any date filter.
+    pub fn includes(&self, doc: &Document) -> bool {
+        self.filter.includes(&doc.doc_type)
+            && (self.status.is_empty()
+                || doc
+                    .std_level
+                    .is_some_and(|level| self.status.contains(&level)))
+            && self.includes_date(doc.published)
+            && self
+                .keyword
+                .as_ref()
+                .is_none_or(|keyword| doc.keywords.contains(keyword))
+    }
+
+    fn includes_date(&self, date: Option<NaiveDate>) -> bool {
+        if self.since.is_none() && self.before.is_none() {
+            return true;
+        }
+        date.is_some_and(|date| {
+            self.since.is_none_or(|since| date >= since)
+                && self.before.is_none_or(|before| date < before)
+        })
+    }
+}
+
+/// Parse a `--since`/`--before` date: `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`,
+/// taken as the first day of the year or month.
+pub fn parse_date_bound(s: &str) -> Result<NaiveDate, String> {
+    let s = s.trim();
+    let parts: Vec<&str> = s.split('-').collect();
+    let number = |part: &str, digits: usize| {
+        (part.len() == digits && part.chars().all(|c| c.is_ascii_digit()))
+            .then(|| part.parse::<u32>().ok())
+            .flatten()
+    };
+    let ymd = match parts.as_slice() {
+        [y] => number(y, 4).map(|y| (y, 1, 1)),
+        [y, m] => number(y, 4).zip(number(m, 2)).map(|(y, m)| (y, m, 1)),
+        [y, m, d] => number(y, 4)
+            .zip(number(m, 2))
+            .zip(number(d, 2))
+            .map(|((y, m), d)| (y, m, d)),
+        _ => None,
+    };
+    ymd.and_then(|(y, m, d)| NaiveDate::from_ymd_opt(y as i32, m, d))
+        .ok_or_else(|| {
+            format!(
+                "invalid date '{}': expected YYYY, YYYY-MM, or YYYY-MM-DD",
+                s
+            )
+        })
 }
 
 /// Search results from the API
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchResult {
     /// List of matching documents
     pub documents: Vec<Document>,
@@ -38,6 +294,21 @@ pub struct SearchResult {
     pub query: String,
     /// The filter that was applied
     pub filter: SearchFilter,
+    /// The statuses results were limited to, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status: Vec<StdLevel>,
+    /// The start of the date range applied, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<NaiveDate>,
+    /// The (exclusive) end of the date range applied, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<NaiveDate>,
+    /// How the query was split into terms
+    #[serde(default, skip_serializing_if = "QueryMode::is_words")]
+    pub mode: QueryMode,
+    /// The Datatracker tag results were limited to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
 }
 
 impl SearchResult {
@@ -52,6 +323,50 @@ impl SearchResult {
     pub fn len(&self) -> usize {
         self.documents.len()
     }
+
+    /// The options that produced these results.
+    pub fn options(&self) -> SearchOptions {
+        SearchOptions {
+            filter: self.filter,
+            status: self.status.clone(),
+            since: self.since,
+            before: self.before,
+            mode: self.mode,
+            keyword: self.keyword.clone(),
+        }
+    }
+}
+
+/// One page of Datatracker search results, from
+/// [`DataTrackerClient::search_page`](crate::DataTrackerClient::search_page).
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    /// The page's RFCs and drafts that match every term
+    pub documents: Vec<Document>,
+    /// Where the next page starts, or `None` on the last page
+    pub next_offset: Option<u32>,
+    /// Total number of matches on the server, when it can be trusted (see
+    /// [`SearchResult::total_count`])
+    pub total_count: Option<u32>,
+}
+
+/// Split a query into lowercase terms for [`match_title`], keeping quoted
+/// phrases whole.
+pub(crate) fn query_words(query: &str) -> Vec<String> {
+    QueryMode::Words.terms(query)
+}
+
+/// Local title matching shared by the offline searches: every word must
+/// appear in `title` (case-insensitive). Returns where the first word
+/// starts, which callers rank by. `words` must come from [`query_words`].
+pub(crate) fn match_title(title: &str, words: &[String]) -> Option<usize> {
+    let first = words.first()?;
+    let title = title.to_lowercase();
+    if words.iter().all(|w| title.contains(w.as_str())) {
+        title.find(first.as_str())
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -80,4 +395,116 @@ mod tests {
         assert!(result.query.is_empty());
         assert_eq!(result.filter, SearchFilter::Both);
     }
+
+    #[test]
+    fn std_levels_parse_from_slugs_uris_and_the_index() {
+        assert_eq!(StdLevel::from_slug("std"), Some(StdLevel::InternetStandard));
+        assert_eq!(
+            StdLevel::from_slug("/api/v1/name/stdlevelname/ps/"),
+            Some(StdLevel::ProposedStandard)
+        );
+        assert_eq!(StdLevel::from_slug("/api/v1/name/stdlevelname/ds/"), None);
+        assert_eq!(
+            StdLevel::from_index_status("BEST CURRENT PRACTICE"),
+            Some(StdLevel::Bcp)
+        );
+        assert_eq!(StdLevel::from_index_status("UNKNOWN"), None);
+        assert_eq!(
+            serde_json::to_string(&StdLevel::InternetStandard).unwrap(),
+            "\"internet-standard\""
+        );
+    }
+
+    #[test]
+    fn options_filter_by_status() {
+        let mut doc = Document::new(
+            "rfc8446".to_string(),
+            "TLS 1.3".to_string(),
+            DocumentType::Rfc(8446),
+        );
+        let options = SearchOptions::new(SearchFilter::RfcsOnly)
+            .with_status(vec![StdLevel::ProposedStandard, StdLevel::InternetStandard]);
+        assert!(!options.includes(&doc));
+        doc.std_level = Some(StdLevel::ProposedStandard);
+        assert!(options.includes(&doc));
+        doc.std_level = Some(StdLevel::Informational);
+        assert!(!options.includes(&doc));
+        assert!(SearchOptions::default().includes(&doc));
+    }
+
+    #[test]
+    fn date_bounds_start_their_period() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(parse_date_bound("2015"), Ok(day(2015, 1, 1)));
+        assert_eq!(parse_date_bound("2020-06"), Ok(day(2020, 6, 1)));
+        assert_eq!(parse_date_bound("2020-06-15"), Ok(day(2020, 6, 15)));
+        for bad in [
+            "",
+            "15",
+            "2020-6",
+            "2020-13",
+            "2021-02-30",
+            "2020/06",
+            "june",
+        ] {
+            assert!(parse_date_bound(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn options_filter_by_date_range() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        let options = SearchOptions::default()
+            .with_dates(day(2015, 1, 1), day(2020, 6, 1))
+            .unwrap();
+        let mut doc = Document::new(
+            "rfc7540".to_string(),
+            "HTTP/2".to_string(),
+            DocumentType::Rfc(7540),
+        );
+        assert!(!options.includes(&doc));
+        doc.published = day(2015, 5, 1);
+        assert!(options.includes(&doc));
+        doc.published = day(2020, 6, 1);
+        assert!(!options.includes(&doc));
+        doc.published = day(2014, 12, 31);
+        assert!(!options.includes(&doc));
+
+        let err = SearchOptions::default()
+            .with_dates(day(2020, 1, 1), day(2020, 1, 1))
+            .unwrap_err();
+        assert!(err.to_string().contains("must be earlier"));
+    }
+
+    #[test]
+    fn quoted_phrases_stay_whole() {
+        let words = |query| QueryMode::Words.terms(query);
+        assert_eq!(words("BGP  Communities"), ["bgp", "communities"]);
+        assert_eq!(words("\"BGP  Communities\""), ["bgp communities"]);
+        assert_eq!(
+            words("large \"bgp communities\" attribute"),
+            ["large", "bgp communities", "attribute"]
+        );
+        assert_eq!(words("\"unclosed phrase"), ["unclosed phrase"]);
+        assert!(words("\"\"  ").is_empty());
+
+        let phrase = |query| QueryMode::Phrase.terms(query);
+        assert_eq!(phrase(" BGP \"Communities\" "), ["bgp communities"]);
+        assert!(phrase("  ").is_empty());
+    }
+
+    #[test]
+    fn test_match_title_requires_all_words() {
+        let words = query_words("BGP message");
+        assert_eq!(
+            match_title("Extended Message Support for BGP", &words),
+            Some(29)
+        );
+        assert_eq!(match_title("BGP-4", &words), None);
+        assert_eq!(match_title("anything", &query_words("  ")), None);
+
+        let words = query_words("\"bgp communities\"");
+        assert_eq!(match_title("BGP Communities Attribute", &words), Some(0));
+        assert_eq!(match_title("BGP Large Communities Attribute", &words), None);
+    }
 }
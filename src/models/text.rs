@@ -0,0 +1,112 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Terminal cells `s` takes up: wide (CJK, most emoji) characters count
+/// two, combining marks none.
+///
+/// ```
+/// assert_eq!(rfc::models::display_width("QUIC"), 4);
+/// assert_eq!(rfc::models::display_width("日本語"), 6);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// Cut `s` to at most `max_width` cells, replacing the tail with `...`
+/// when it would be wider. Under three cells there's no room for the
+/// `...`, so only what fits is kept. Grapheme clusters are kept or
+/// dropped whole, so accents stay on their letters and emoji sequences
+/// aren't split.
+///
+/// ```
+/// use rfc::models::truncate_to_width;
+///
+/// let title = "QUIC: A UDP-Based Multiplexed and Secure Transport";
+/// assert_eq!(truncate_to_width(title, 20), "QUIC: A UDP-Based...");
+/// assert_eq!(truncate_to_width("QUIC", 20), "QUIC");
+/// ```
+pub fn truncate_to_width(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    let ellipsis = if max_width >= 3 { "..." } else { "" };
+    let budget = max_width - ellipsis.len();
+    let mut out = String::new();
+    let mut used = 0;
+    for grapheme in s.graphemes(true) {
+        used += grapheme.width();
+        if used > budget {
+            break;
+        }
+        out.push_str(grapheme);
+    }
+    out.push_str(ellipsis);
+    out
+}
+
+/// `s` left-aligned in a `width`-cell column. `{:<width$}` pads by
+/// character count, which misaligns wide characters.
+pub fn pad_to_width(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_count_two_cells() {
+        assert_eq!(display_width("QUIC"), 4);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn truncation_never_exceeds_budget() {
+        let titles = [
+            "Internet Protocol",
+            "国際化ドメイン名のための登録とアプリケーションの手続き",
+            "Re\u{301}sume\u{301} of Combining Characters in Titles",
+            "Emoji 👩‍💻 in 🇯🇵 Document 🎉 Titles 🎉🎉",
+        ];
+        for title in titles {
+            for budget in 0..=40 {
+                let cut = truncate_to_width(title, budget);
+                assert!(
+                    display_width(&cut) <= budget,
+                    "{:?} at {} is {} wide",
+                    cut,
+                    budget,
+                    display_width(&cut)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn truncation_keeps_grapheme_clusters_whole() {
+        // A wide character that no longer fits is dropped, not halved.
+        assert_eq!(truncate_to_width("日本語の文書", 8), "日本...");
+        assert_eq!(truncate_to_width("ae\u{301}bcdefgh", 5), "ae\u{301}...");
+        assert_eq!(truncate_to_width("ab👩‍💻cdefg", 6), "ab...");
+        assert_eq!(truncate_to_width("ab👩‍💻cdefg", 7), "ab👩‍💻...");
+    }
+
+    #[test]
+    fn too_narrow_for_an_ellipsis_keeps_what_fits() {
+        assert_eq!(truncate_to_width("QUIC", 0), "");
+        assert_eq!(truncate_to_width("QUIC", 1), "Q");
+        assert_eq!(truncate_to_width("QUIC", 2), "QU");
+        assert_eq!(truncate_to_width("日本語", 1), "");
+        assert_eq!(truncate_to_width("日本語", 2), "日");
+        assert_eq!(truncate_to_width("QU", 2), "QU");
+    }
+
+    #[test]
+    fn pads_by_display_width() {
+        assert_eq!(pad_to_width("rfc1", 6), "rfc1  ");
+        assert_eq!(pad_to_width("日本", 6), "日本  ");
+        assert_eq!(pad_to_width("too long", 3), "too long");
+    }
+}
@@ -0,0 +1,348 @@
+//! A document's text split into blocks (title, headings, paragraphs,
+//! lists, preformatted artwork) for the Markdown and roff converters.
+//!
+//! Works on the paginated text the cache stores, either the RFC Editor's
+//! own or html2text's rendering of an HTML-only document.
+
+use std::collections::BTreeMap;
+
+use crate::analysis::{heading, strip_pagination};
+
+use super::{is_heading, title_lines};
+
+/// One piece of a document, in reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Block {
+    /// The title block, joined into one line
+    Title(String),
+    /// A draft's name, centered under its title
+    DraftName(String),
+    /// A section heading: 2 for `1.` and unnumbered sections like the
+    /// Abstract, 3 for `1.1.`, and so on
+    Heading { level: usize, text: String },
+    /// Prose, joined into one line
+    Paragraph(String),
+    /// Bulleted items, each joined into one line
+    List(Vec<String>),
+    /// Artwork, tables, the header block, and the table of contents,
+    /// dedented as a group
+    Preformatted(Vec<String>),
+}
+
+/// Split `text` into [`Block`]s.
+pub(super) fn parse(text: &str) -> Vec<Block> {
+    let converted = from_html2text(text);
+    let lines = strip_pagination(converted.as_deref().unwrap_or(text));
+    let mut blocks = Vec::new();
+
+    let mut body = &lines[..];
+    if converted.is_none() {
+        let title = title_lines(&lines.join("\n"));
+        if !title.is_empty() {
+            let header: Vec<&str> = lines[..title.start]
+                .iter()
+                .copied()
+                .skip_while(|l| l.trim().is_empty())
+                .collect();
+            let blank = header.iter().rev().take_while(|l| l.trim().is_empty());
+            let header = &header[..header.len() - blank.count()];
+            if !header.is_empty() {
+                blocks.push(preformatted(header));
+            }
+            let (names, words): (Vec<&str>, Vec<&str>) = lines[title.clone()]
+                .iter()
+                .map(|l| l.trim())
+                .partition(|l| l.starts_with("draft-"));
+            blocks.push(Block::Title(words.join(" ")));
+            blocks.extend(names.into_iter().map(|n| Block::DraftName(n.to_string())));
+            body = &lines[title.end..];
+        }
+    }
+
+    let base = base_indent(body, converted.is_some());
+    let mut run: Vec<&str> = Vec::new();
+    let mut in_contents = false;
+    for line in body {
+        let found = if converted.is_some() {
+            is_markdown_heading(line).then(|| markdown_heading(line))
+        } else if is_heading(line) {
+            Some(Block::Heading {
+                level: section_level(line),
+                text: collapse(line),
+            })
+        } else {
+            None
+        };
+        if let Some(found) = found {
+            push_run(&mut blocks, &run, base, in_contents);
+            run.clear();
+            in_contents =
+                matches!(&found, Block::Heading { text, .. } if text == "Table of Contents");
+            blocks.push(found);
+        } else if line.trim().is_empty() {
+            push_run(&mut blocks, &run, base, in_contents);
+            run.clear();
+        } else {
+            run.push(line);
+        }
+    }
+    push_run(&mut blocks, &run, base, in_contents);
+    blocks
+}
+
+/// Add one run of non-blank lines: preformatted when it's artwork or the
+/// table of contents, a list when it's bullets, a paragraph otherwise.
+fn push_run(blocks: &mut Vec<Block>, lines: &[&str], base: usize, verbatim: bool) {
+    let Some(least) = lines.iter().map(|l| indent(l)).min() else {
+        return;
+    };
+    if let [line] = lines {
+        if least > base && is_caption(line.trim()) {
+            blocks.push(Block::Paragraph(line.trim().to_string()));
+            return;
+        }
+    }
+    if verbatim || least > base || lines.iter().any(|l| is_art(l)) {
+        blocks.push(preformatted(lines));
+        return;
+    }
+
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    for line in lines {
+        let content = line.trim();
+        match (bullet(content), items.last_mut()) {
+            (Some(item), _) => items.push(vec![item]),
+            (None, Some(item)) => item.push(content),
+            (None, None) => paragraph.push(content),
+        }
+    }
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(join(&paragraph)));
+    }
+    if !items.is_empty() {
+        blocks.push(Block::List(items.iter().map(|item| join(item)).collect()));
+    }
+}
+
+fn preformatted(lines: &[&str]) -> Block {
+    let least = lines.iter().map(|l| indent(l)).min().unwrap_or(0);
+    Block::Preformatted(
+        lines
+            .iter()
+            .map(|l| l[least..].trim_end().to_string())
+            .collect(),
+    )
+}
+
+/// html2text's rendering with its link syntax taken out (`[text][3]`
+/// becomes `text`; the `[3]: #section-1` definitions and `¶` marks go),
+/// or `None` if `text` isn't one: those start with a `# ` title.
+fn from_html2text(text: &str) -> Option<String> {
+    let first = text.lines().find(|l| !l.trim().is_empty())?;
+    if !first.starts_with("# ") {
+        return None;
+    }
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        if is_link_definition(line) {
+            continue;
+        }
+        out.push_str(&strip_link_references(&line.replace('¶', "")));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// `[12]: #figure-1`
+fn is_link_definition(line: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once("]: "))
+        .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `line` with every `[text][n]` replaced by `text`; brackets that aren't
+/// part of one (`[[RFC8446][6]]`'s outer pair) are kept.
+fn strip_link_references(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut open: Vec<usize> = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '[' => {
+                open.push(out.len());
+                out.push('[');
+            }
+            ']' => {
+                let start = open.pop();
+                let reference = rest
+                    .strip_prefix('[')
+                    .and_then(|r| r.split_once(']'))
+                    .filter(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+                match (start, reference) {
+                    (Some(start), Some((_, after))) => {
+                        out.remove(start);
+                        rest = after;
+                    }
+                    _ => out.push(']'),
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `## 1. Overview`
+fn is_markdown_heading(line: &str) -> bool {
+    let text = line.trim_start_matches('#');
+    text.len() < line.len() && text.starts_with(' ')
+}
+
+/// An html2text heading (`## 1. Overview`) as a block, with sections
+/// re-levelled by their number as in the plain text.
+fn markdown_heading(line: &str) -> Block {
+    let text = collapse(line.trim_start_matches('#'));
+    if line.starts_with("# ") {
+        return Block::Title(text);
+    }
+    Block::Heading {
+        level: section_level(&text),
+        text,
+    }
+}
+
+/// `line` trimmed, with runs of spaces collapsed: `1.  Overview` reads
+/// `1. Overview`.
+fn collapse(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// See [`Block::Heading`]; never deeper than 6, Markdown's limit.
+fn section_level(line: &str) -> usize {
+    let level = heading(line.trim()).map_or(2, |(number, _)| number.split('.').count() + 1);
+    level.min(6)
+}
+
+/// The indent of most body text: 3 in the RFC Editor's format, none in
+/// html2text's. Anything further in is artwork.
+fn base_indent(lines: &[&str], html2text: bool) -> usize {
+    if html2text {
+        return 0;
+    }
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for line in lines
+        .iter()
+        .filter(|l| !l.trim().is_empty() && !is_heading(l))
+    {
+        *counts.entry(indent(line)).or_default() += 1;
+    }
+    // Ties go to the shallower indent, which iterates first.
+    counts
+        .into_iter()
+        .fold(
+            (0, 0),
+            |best, (indent, n)| if n > best.1 { (indent, n) } else { best },
+        )
+        .0
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// `Figure 3: Handshake`: a caption, centered under its figure.
+fn is_caption(line: &str) -> bool {
+    ["Figure ", "Table "].iter().any(|prefix| {
+        line.strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    })
+}
+
+/// Box-drawing in ASCII art and tables, which may start at the body's
+/// indent.
+fn is_art(line: &str) -> bool {
+    ["+--", "--+", "-->", "<--", "|  "]
+        .iter()
+        .any(|pattern| line.contains(pattern))
+}
+
+/// `o  item`, `*  item`, or `-  item`: the item's text.
+fn bullet(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix(['o', '*', '-', '+'])?;
+    rest.starts_with(' ').then(|| rest.trim_start())
+}
+
+/// Lines of one paragraph joined into one; a line broken after a hyphen
+/// (`flow-` / `controlled`) is joined without a space.
+fn join(lines: &[&str]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let hyphenated =
+            out.ends_with('-') && out[..out.len() - 1].ends_with(|c: char| c.is_alphanumeric());
+        if !out.is_empty() && !hyphenated {
+            out.push(' ');
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_references_are_stripped_but_citations_kept() {
+        assert_eq!(
+            strip_link_references("## [1. ][3][Overview][4]"),
+            "## 1. Overview"
+        );
+        assert_eq!(
+            strip_link_references("TLS 1.3 [[RFC8446][6]]. [a] b"),
+            "TLS 1.3 [RFC8446]. [a] b"
+        );
+        assert!(is_link_definition("[12]: #figure-1"));
+        assert!(!is_link_definition("[RFC8446]: x"));
+    }
+
+    #[test]
+    fn sections_are_levelled_by_number() {
+        assert_eq!(section_level("1.  Overview"), 2);
+        assert_eq!(section_level("17.2.5.  Retry"), 4);
+        assert_eq!(section_level("Appendix A.1.  Sample"), 3);
+        assert_eq!(section_level("Abstract"), 2);
+        assert_eq!(section_level("1.2.3.4.5.6.  Deep"), 6);
+    }
+
+    #[test]
+    fn runs_become_paragraphs_lists_and_preformatted_blocks() {
+        let text = "\
+1.  Overview
+
+   Streams are flow-
+   controlled.
+
+   *  One
+      item.
+   *  Two
+
+      +---+
+      | A |
+      +---+
+";
+        assert_eq!(
+            parse(text),
+            [
+                Block::Heading {
+                    level: 2,
+                    text: "1. Overview".into()
+                },
+                Block::Paragraph("Streams are flow-controlled.".into()),
+                Block::List(vec!["One item.".into(), "Two".into()]),
+                Block::Preformatted(vec!["+---+".into(), "| A |".into(), "+---+".into()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! The block `rfc --header` puts above a document's text: its name,
+//! title, and what the title page would say, so it's all visible without
+//! scrolling. It's composed for each viewing and never cached.
+
+use crate::models::{display_width, Document, DocumentType};
+
+/// `doc`'s header, fitted to `width` columns: the name and title, a
+/// `Label: value` line for each fact `doc` has, and a rule. Values too
+/// long for a line continue under the first, aligned. The header ends
+/// with a blank line, ready to go before the text.
+///
+/// ```
+/// use rfc::models::{Document, DocumentType};
+///
+/// let mut doc = Document::new("rfc791".into(), "Internet Protocol".into(), DocumentType::Rfc(791));
+/// doc.wg = Some("ipv4".into());
+/// assert_eq!(
+///     rfc::render::document_header(&doc, 40),
+///     format!("RFC 791\nInternet Protocol\n\nWorking group: ipv4\n{}\n\n", "-".repeat(40))
+/// );
+/// ```
+pub fn document_header(doc: &Document, width: usize) -> String {
+    let width = width.max(MIN_WIDTH);
+    let mut lines = vec![doc.doc_type.to_string()];
+    lines.extend(fill(doc.title.split_whitespace(), " ", width));
+
+    let fields = fields(doc);
+    let label_width = fields
+        .iter()
+        .map(|(label, _, _)| label.len() + 2)
+        .max()
+        .unwrap_or(0);
+    if !fields.is_empty() {
+        lines.push(String::new());
+    }
+    for (label, items, sep) in &fields {
+        let values = fill(items.iter().map(String::as_str), sep, width - label_width);
+        for (i, value) in values.into_iter().enumerate() {
+            let label = if i == 0 {
+                format!("{}:", label)
+            } else {
+                String::new()
+            };
+            lines.push(format!("{:<label_width$}{}", label, value));
+        }
+    }
+    lines.push("-".repeat(width));
+
+    let mut header = lines.join("\n");
+    header.push_str("\n\n");
+    header
+}
+
+/// Narrowest header composed, so labels keep some room beside them.
+const MIN_WIDTH: usize = 40;
+
+/// Each fact `doc` has, as a label, the items of its value, and what
+/// goes between them.
+fn fields(doc: &Document) -> Vec<(&'static str, Vec<String>, &'static str)> {
+    let rfcs = |numbers: &[u32]| numbers.iter().map(|n| format!("RFC {}", n)).collect();
+    let status = match doc.doc_type {
+        DocumentType::Rfc(_) => doc.std_level.map(|level| level.name().to_string()),
+        DocumentType::Draft(_) => Some(match doc.draft_state {
+            Some(state) => format!("Internet-Draft ({})", state.label()),
+            None => "Internet-Draft".to_string(),
+        }),
+    };
+    let date = doc.published.map(|date| match doc.doc_type {
+        // RFCs are dated by month, as on their title page.
+        DocumentType::Rfc(_) => date.format("%B %Y").to_string(),
+        DocumentType::Draft(_) => date.format("%Y-%m-%d").to_string(),
+    });
+
+    let mut fields = Vec::new();
+    if let Some(status) = status {
+        fields.push(("Status", words(&status), " "));
+    }
+    if !doc.authors.is_empty() {
+        fields.push(("Authors", doc.authors.clone(), ", "));
+    }
+    if let Some(date) = date {
+        fields.push(("Date", words(&date), " "));
+    }
+    if let Some(wg) = &doc.wg {
+        fields.push(("Working group", vec![wg.clone()], " "));
+    }
+    if !doc.obsoleted_by.is_empty() {
+        fields.push(("Obsoleted by", rfcs(&doc.obsoleted_by), ", "));
+    }
+    if !doc.updated_by.is_empty() {
+        fields.push(("Updated by", rfcs(&doc.updated_by), ", "));
+    }
+    fields
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+/// `items` joined by `sep` into lines of at most `width` columns, breaking
+/// only between items (an item wider than `width` gets a line to itself).
+/// A line that ends before the last item keeps the separator's
+/// punctuation, e.g. the comma of `", "`.
+fn fill<'a>(items: impl IntoIterator<Item = &'a str>, sep: &str, width: usize) -> Vec<String> {
+    let tail = sep.trim_end();
+    // Leave room for the punctuation a broken line ends with.
+    let width = width.saturating_sub(display_width(tail));
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for item in items {
+        if line.is_empty() {
+            line.push_str(item);
+        } else if display_width(&line) + display_width(sep) + display_width(item) <= width {
+            line.push_str(sep);
+            line.push_str(item);
+        } else {
+            line.push_str(tail);
+            lines.push(std::mem::replace(&mut line, item.to_string()));
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::models::{DraftState, StdLevel};
+
+    #[test]
+    fn every_fact_gets_an_aligned_line() {
+        let mut doc = Document::new(
+            "rfc9000".into(),
+            "QUIC: A UDP-Based Multiplexed and Secure Transport".into(),
+            DocumentType::Rfc(9000),
+        );
+        doc.std_level = Some(StdLevel::ProposedStandard);
+        doc.authors = vec!["J. Iyengar, Ed.".into(), "M. Thomson, Ed.".into()];
+        doc.published = NaiveDate::from_ymd_opt(2021, 5, 1);
+        doc.wg = Some("quic".into());
+        doc.obsoleted_by = vec![9999];
+        doc.updated_by = vec![9368, 9369];
+
+        assert_eq!(
+            document_header(&doc, 44),
+            "\
+RFC 9000
+QUIC: A UDP-Based Multiplexed and Secure
+Transport
+
+Status:        Proposed Standard
+Authors:       J. Iyengar, Ed.,
+               M. Thomson, Ed.
+Date:          May 2021
+Working group: quic
+Obsoleted by:  RFC 9999
+Updated by:    RFC 9368, RFC 9369
+--------------------------------------------
+
+"
+        );
+    }
+
+    #[test]
+    fn a_bare_document_gets_its_name_and_a_rule() {
+        let doc = Document::new(
+            "draft-ietf-foo-bar-03".into(),
+            String::new(),
+            DocumentType::Draft("draft-ietf-foo-bar-03".into()),
+        );
+        let rule = "-".repeat(60);
+        assert_eq!(
+            document_header(&doc, 60),
+            format!(
+                "draft-ietf-foo-bar-03\n\nStatus: Internet-Draft\n{}\n\n",
+                rule
+            )
+        );
+
+        let mut doc = doc;
+        doc.draft_state = Some(DraftState::Expired);
+        doc.published = NaiveDate::from_ymd_opt(2023, 2, 14);
+        assert!(document_header(&doc, 60)
+            .contains("Status: Internet-Draft (expired)\nDate:   2023-02-14\n"));
+    }
+
+    #[test]
+    fn lines_never_exceed_the_width() {
+        let mut doc = Document::new(
+            "rfc2616".into(),
+            "Hypertext Transfer Protocol -- HTTP/1.1".into(),
+            DocumentType::Rfc(2616),
+        );
+        doc.authors = ["R. Fielding", "J. Gettys", "J. Mogul", "H. Frystyk"]
+            .map(String::from)
+            .to_vec();
+        doc.obsoleted_by = vec![7230, 7231, 7232, 7233, 7234, 7235];
+        for width in [40, 50, 72] {
+            for line in document_header(&doc, width).lines() {
+                assert!(display_width(line) <= width, "{width}: {line}");
+            }
+        }
+        // Narrower than that isn't honored.
+        assert!(document_header(&doc, 10).contains(&"-".repeat(40)));
+    }
+}
@@ -0,0 +1,172 @@
+//! Markdown from a document's text, for `rfc --export-md`.
+//!
+//! The title block becomes a `#` heading, numbered sections `##` and
+//! deeper by their number, artwork, tables, and the table of contents
+//! fenced code blocks, and citation anchors like `[RFC8446]` links.
+//! Everything else is prose, escaped so it reads back as the same text.
+
+use crate::api::RFC_EDITOR_BASE_URL;
+use crate::models::DocumentType;
+
+use super::anchor_len;
+use super::blocks::{self, Block};
+
+/// Convert `text` to CommonMark.
+///
+/// ```
+/// let text = "1.  Introduction\n\n   QUIC is defined in [RFC9000].\n";
+/// assert_eq!(
+///     rfc::render::to_markdown(text),
+///     "## 1. Introduction\n\n\
+///      QUIC is defined in [\\[RFC9000\\]](https://www.rfc-editor.org/rfc/rfc9000).\n"
+/// );
+/// ```
+pub fn to_markdown(text: &str) -> String {
+    let rendered: Vec<String> = blocks::parse(text)
+        .iter()
+        .map(|block| match block {
+            Block::Title(title) => format!("# {}", inline(title)),
+            Block::DraftName(name) => format!("`{}`", name),
+            Block::Heading { level, text } => format!("{} {}", "#".repeat(*level), inline(text)),
+            Block::Paragraph(text) => escape_start(&inline(text)),
+            Block::List(items) => items
+                .iter()
+                .map(|item| format!("- {}", escape_start(&inline(item))))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Preformatted(lines) => fence(lines),
+        })
+        .collect();
+    let mut out = rendered.join("\n\n");
+    out.push('\n');
+    out
+}
+
+/// `lines` in a fenced code block, with a fence longer than any backtick
+/// run inside.
+fn fence(lines: &[String]) -> String {
+    let longest_run = lines
+        .iter()
+        .flat_map(|l| l.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat((longest_run + 1).max(3));
+    format!("{}\n{}\n{}", fence, lines.join("\n"), fence)
+}
+
+/// `text` with CommonMark's inline punctuation escaped, RFC and draft
+/// citation anchors linked, and `<https://...>` left as an autolink.
+fn inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 8);
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if let Some(len) = autolink_len(&text[i..]) {
+            out.push_str(&text[i..i + len]);
+            i += len;
+            continue;
+        }
+        let boundary = !text[..i].ends_with(|c: char| c.is_alphanumeric());
+        if let Some(len) = (c == '[' && boundary)
+            .then(|| anchor_len(&text[i..]))
+            .flatten()
+        {
+            let tag = &text[i + 1..i + len - 1];
+            match anchor_url(tag) {
+                Some(url) => out.push_str(&format!("[\\[{}\\]]({})", escape(tag), url)),
+                None => out.push_str(&format!("\\[{}\\]", escape(tag))),
+            }
+            i += len;
+            continue;
+        }
+        out.push_str(&escape(&text[i..i + c.len_utf8()]));
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Length of the `<https://...>` at the start of `s`.
+fn autolink_len(s: &str) -> Option<usize> {
+    if !(s.starts_with("<https://") || s.starts_with("<http://")) {
+        return None;
+    }
+    let end = s.find('>')?;
+    let url = &s[1..end];
+    (!url.contains(|c: char| c.is_whitespace() || c == '<')).then_some(end + 1)
+}
+
+/// Where a citation anchor points: the RFC Editor for `RFC8446`, the
+/// Datatracker for `I-D.ietf-quic-transport`.
+fn anchor_url(tag: &str) -> Option<String> {
+    if let Some(number) = tag.strip_prefix("RFC") {
+        let number: u32 = number.parse().ok()?;
+        return Some(format!("{}/rfc/rfc{}", RFC_EDITOR_BASE_URL, number));
+    }
+    let name = tag.strip_prefix("I-D.")?;
+    Some(DocumentType::Draft(format!("draft-{}", name)).datatracker_url())
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '<' | '[' | ']' | '&') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape what would make a paragraph start read as other syntax: a
+/// heading, quote, list item, or thematic break.
+fn escape_start(text: &str) -> String {
+    if text.starts_with(['#', '>', '-', '+', '=', '|']) {
+        return format!("\\{}", text);
+    }
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && text[digits..].starts_with(['.', ')']) {
+        return format!("{}\\{}", &text[..digits], &text[digits..]);
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = format!(
+            "{}/tests/fixtures/render/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    #[test]
+    fn converts_rfc_editor_text() {
+        assert_eq!(
+            to_markdown(&fixture("rfc9000-excerpt.txt")),
+            fixture("rfc9000-excerpt.md")
+        );
+    }
+
+    #[test]
+    fn converts_html2text_output() {
+        assert_eq!(
+            to_markdown(&fixture("rfc9000-html2text.txt")),
+            fixture("rfc9000-html2text.md")
+        );
+    }
+
+    #[test]
+    fn prose_is_escaped_and_anchors_linked() {
+        assert_eq!(
+            inline("a *b* <c> <https://example.com/x_y> [QUIC-TLS] [I-D.ietf-quic-qpack]"),
+            "a \\*b\\* \\<c> <https://example.com/x_y> \\[QUIC-TLS\\] \
+             [\\[I-D.ietf-quic-qpack\\]](https://datatracker.ietf.org/doc/draft-ietf-quic-qpack/)"
+        );
+        assert_eq!(escape_start("1. Not a list"), "1\\. Not a list");
+        assert_eq!(escape_start("# no"), "\\# no");
+    }
+}
@@ -0,0 +1,439 @@
+//! Preparing a document's text for display: ANSI highlighting for
+//! terminal pagers and, with the `html` feature, converting documents
+//! published only as HTML to plain text.
+//!
+//! Highlighting only adds escapes: every line keeps its text and column
+//! layout, so line numbers (bookmarks) and the 72-column formatting
+//! survive.
+//!
+//! Whether to color at all is decided once per destination, as
+//! [`OutputOptions`], from the global `--color` and `NO_COLOR`.
+//!
+//! Listings (search results, the cache) are laid out by
+//! [`TableRenderer`]. [`to_markdown`] and [`to_roff`] convert a
+//! document for `--export-md` and `--man`, and [`document_header`]
+//! composes the block `--header` shows above it. Listings give times
+//! the way [`TimeStyle`] says: `3 days ago`, or the date.
+
+mod blocks;
+mod header;
+mod markdown;
+mod output;
+mod roff;
+mod table;
+mod time;
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::analysis::{heading, REQUIREMENT_KEYWORDS};
+use crate::models::{display_width, QueryMode};
+
+pub use header::document_header;
+pub use markdown::to_markdown;
+pub use output::{no_color_requested, OutputOptions};
+pub use roff::to_roff;
+pub use table::{Column, TableLayout, TableRenderer, TableRow};
+pub use time::TimeStyle;
+
+/// Whether to colorize, as chosen with `--color`; see [`OutputOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// When the output will reach a terminal that renders ANSI
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+pub const HEADING: &str = "\x1b[1;34m";
+pub const TITLE: &str = "\x1b[1m";
+pub const KEYWORD: &str = "\x1b[1;33m";
+pub const ANCHOR: &str = "\x1b[36m";
+pub const MATCH: &str = "\x1b[1;4m";
+pub const RESET: &str = "\x1b[0m";
+
+/// Column the header block's right-hand side ends at.
+const RIGHT_MARGIN: usize = 72;
+
+/// Unnumbered sections that still get a heading at column 0.
+const UNNUMBERED_HEADINGS: &[&str] = &[
+    "Abstract",
+    "Status of This Memo",
+    "Status of this Memo",
+    "Copyright Notice",
+    "Table of Contents",
+    "Acknowledgments",
+    "Acknowledgements",
+    "Contributors",
+    "Index",
+    "Author's Address",
+    "Authors' Addresses",
+    "Full Copyright Statement",
+    "Intellectual Property",
+];
+
+/// Convert an HTML rendering to plain text wrapped at 80 columns, the
+/// form the cache stores.
+///
+/// ```
+/// let text = rfc::render::html_to_text("<h1>QUIC</h1><p>A UDP-based transport.</p>").unwrap();
+/// assert!(text.contains("QUIC"));
+/// assert!(text.contains("A UDP-based transport."));
+/// ```
+#[cfg(feature = "html")]
+pub fn html_to_text(html: &str) -> anyhow::Result<String> {
+    let _span = tracing::info_span!("convert").entered();
+    Ok(html2text::from_read(html.as_bytes(), 80)?)
+}
+
+/// Colorize section headings, the title block, RFC 2119 keywords, and
+/// citation anchors like `[RFC2119]` in `text`, if `output` is colored.
+pub fn highlight(text: &str, output: &OutputOptions) -> String {
+    if !output.color {
+        return text.to_string();
+    }
+    let title = title_lines(text);
+    let mut out = String::with_capacity(text.len() + text.len() / 8);
+    for (n, line) in text.split_inclusive('\n').enumerate() {
+        let (body, newline) = match line.strip_suffix('\n') {
+            Some(body) => (body, "\n"),
+            None => (line, ""),
+        };
+        if is_heading(body) {
+            paint(&mut out, HEADING, body);
+        } else if title.contains(&n) {
+            let content = body.trim_start();
+            out.push_str(&body[..body.len() - content.len()]);
+            paint(&mut out, TITLE, content);
+        } else {
+            highlight_inline(&mut out, body);
+        }
+        out.push_str(newline);
+    }
+    out
+}
+
+/// `title` cut to `width` cells like
+/// [`truncate_to_width`](crate::models::truncate_to_width), with the
+/// parts matching `query`'s terms (case-insensitively) in bold underline.
+/// When every match lies past the cut, the title is shown from the first
+/// match instead, after a leading `...`. `usize::MAX` means "don't
+/// truncate".
+///
+/// ```
+/// let shown = rfc::render::highlight_match("Using TLS to Secure QUIC", "quic", 20);
+/// assert_eq!(shown, "...to Secure \x1b[1;4mQUIC\x1b[0m");
+/// ```
+pub fn highlight_match(title: &str, query: &str, width: usize) -> String {
+    let matches = match_ranges(title, &QueryMode::Words.terms(query));
+    if display_width(title) <= width {
+        return paint_ranges(title, 0..title.len(), &matches);
+    }
+    let budget = width.saturating_sub(3);
+    let prefix = fit(title, budget);
+    let first = match matches.first() {
+        Some(first) if first.start >= prefix => first.clone(),
+        _ => return format!("{}...", paint_ranges(title, 0..prefix, &matches)),
+    };
+
+    // Start early enough to reach the end of the title if that fits, at a
+    // word; otherwise start at the match and cut again after it.
+    let mut start = first.start;
+    if display_width(&title[start..]) <= budget {
+        for (i, _) in title[..first.start].grapheme_indices(true).rev() {
+            if display_width(&title[i..]) > budget {
+                break;
+            }
+            start = i;
+        }
+        if !title[..start].ends_with(char::is_whitespace) {
+            if let Some((i, space)) = title[start..first.start]
+                .char_indices()
+                .find(|(_, c)| c.is_whitespace())
+            {
+                start += i + space.len_utf8();
+            }
+        }
+        return format!("...{}", paint_ranges(title, start..title.len(), &matches));
+    }
+    let end = start + fit(&title[start..], width.saturating_sub(6));
+    format!("...{}...", paint_ranges(title, start..end, &matches))
+}
+
+/// Byte length of the longest run of whole graphemes at the start of `s`
+/// that fits in `width` cells.
+fn fit(s: &str, width: usize) -> usize {
+    let mut used = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return i;
+        }
+    }
+    s.len()
+}
+
+/// Where `terms` (lowercase) occur in `text`, ignoring case, sorted and
+/// with overlaps merged.
+fn match_ranges(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for term in terms.iter().filter(|t| !t.is_empty()) {
+        for (i, _) in text.char_indices() {
+            if let Some(len) = match_len(&text[i..], term) {
+                ranges.push(i..i + len);
+            }
+        }
+    }
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Byte length of the prefix of `text` that lowercases to `term`.
+fn match_len(text: &str, term: &str) -> Option<usize> {
+    let mut wanted = term.chars().peekable();
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if wanted.next() != Some(lower) {
+                return None;
+            }
+        }
+        if wanted.peek().is_none() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// `text[window]` with the parts inside `ranges` painted as matches.
+fn paint_ranges(text: &str, window: Range<usize>, ranges: &[Range<usize>]) -> String {
+    let mut out = String::new();
+    let mut copied = window.start;
+    for range in ranges {
+        let start = range.start.clamp(window.start, window.end);
+        let end = range.end.clamp(window.start, window.end);
+        if start >= end {
+            continue;
+        }
+        out.push_str(&text[copied..start]);
+        paint(&mut out, MATCH, &text[start..end]);
+        copied = end;
+    }
+    out.push_str(&text[copied..window.end]);
+    out
+}
+
+fn paint(out: &mut String, color: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    out.push_str(color);
+    out.push_str(text);
+    out.push_str(RESET);
+}
+
+fn is_heading(line: &str) -> bool {
+    heading(line).is_some() || UNNUMBERED_HEADINGS.contains(&line.trim_end())
+}
+
+/// Indices of the title lines on the first page: the first run of
+/// centered lines (indented, and not the two-column header block or a
+/// right-aligned date under it) before any heading.
+fn title_lines(text: &str) -> Range<usize> {
+    let mut start = None;
+    for (n, line) in text.lines().enumerate() {
+        if is_heading(line) {
+            break;
+        }
+        let content = line.trim_start();
+        let centered = !content.is_empty()
+            && line.len() - content.len() >= 8
+            && line.trim_end().len() < RIGHT_MARGIN
+            && !content.trim_end().contains("   ");
+        match (start, centered) {
+            (None, true) => start = Some(n),
+            (Some(from), false) => return from..n,
+            _ => {}
+        }
+    }
+    0..0
+}
+
+/// Color keywords and citation anchors within one line.
+fn highlight_inline(out: &mut String, line: &str) {
+    let mut copied = 0;
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        let boundary = !line[..i].ends_with(|c: char| c.is_alphanumeric());
+        let found = if !boundary {
+            None
+        } else if rest.starts_with('[') {
+            anchor_len(rest).map(|len| (ANCHOR, len))
+        } else {
+            keyword_len(rest).map(|len| (KEYWORD, len))
+        };
+        match found {
+            Some((color, len)) => {
+                out.push_str(&line[copied..i]);
+                paint(out, color, &rest[..len]);
+                i += len;
+                copied = i;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    out.push_str(&line[copied..]);
+}
+
+/// Length of a `[TAG]` citation anchor at the start of `s`: letters,
+/// digits, `.`, `-`, and `_`, with at least one letter or digit.
+fn anchor_len(s: &str) -> Option<usize> {
+    let inner = &s[1..];
+    let len = inner.len()
+        - inner
+            .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+            .len();
+    let tag = &inner[..len];
+    (inner[len..].starts_with(']') && tag.contains(|c: char| c.is_ascii_alphanumeric()))
+        .then_some(len + 2)
+}
+
+/// Length of the requirement keyword (longest match) at the start of `s`.
+fn keyword_len(s: &str) -> Option<usize> {
+    REQUIREMENT_KEYWORDS
+        .iter()
+        .filter(|k| s.starts_with(*k))
+        .filter(|k| !s[k.len()..].starts_with(|c: char| c.is_alphanumeric()))
+        .map(|k| k.len())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORED: OutputOptions = OutputOptions { color: true };
+
+    const FIXTURE: &str = "\
+Internet Engineering Task Force (IETF)                         A. Author
+Request for Comments: 9999                                 Example, Inc.
+                                                                May 2021
+
+                          An Example Protocol
+                              for Testing
+
+Abstract
+
+   Senders MUST NOT retry; see [RFC2119] and [I-D.ietf-foo].
+
+1.  Introduction
+
+   A receiver MAY ignore MAYBE values in a[i] or [Page 3].
+";
+
+    #[test]
+    fn headings_are_painted() {
+        let out = highlight(FIXTURE, &COLORED);
+        assert!(out.contains(&format!("\n{}Abstract{}\n", HEADING, RESET)));
+        assert!(out.contains(&format!("\n{}1.  Introduction{}\n", HEADING, RESET)));
+    }
+
+    #[test]
+    fn title_block_is_painted_but_header_is_not() {
+        let out = highlight(FIXTURE, &COLORED);
+        assert!(out.contains(&format!(
+            "\n                          {}An Example Protocol{}\n",
+            TITLE, RESET
+        )));
+        assert!(out.contains(&format!(
+            "\n                              {}for Testing{}\n",
+            TITLE, RESET
+        )));
+        assert!(out.starts_with("Internet Engineering Task Force (IETF)"));
+    }
+
+    #[test]
+    fn keywords_and_anchors_are_painted() {
+        let out = highlight(FIXTURE, &COLORED);
+        assert!(out.contains(&format!("Senders {}MUST NOT{} retry", KEYWORD, RESET)));
+        assert!(out.contains(&format!("see {}[RFC2119]{} and", ANCHOR, RESET)));
+        assert!(out.contains(&format!("{}[I-D.ietf-foo]{}.", ANCHOR, RESET)));
+        assert!(out.contains(&format!("receiver {}MAY{} ignore MAYBE", KEYWORD, RESET)));
+        assert!(out.contains("a[i] or [Page 3]."));
+    }
+
+    fn marked(text: &str) -> String {
+        format!("{}{}{}", MATCH, text, RESET)
+    }
+
+    #[test]
+    fn matches_are_highlighted_when_the_title_fits() {
+        let title = "BGP Large Communities Attribute";
+        assert_eq!(
+            highlight_match(title, "large bgp", usize::MAX),
+            format!(
+                "{} {} Communities Attribute",
+                marked("BGP"),
+                marked("Large")
+            )
+        );
+        // Overlapping terms merge; a phrase is one match.
+        assert_eq!(
+            highlight_match(title, "commun communities \"large c\"", 40),
+            format!("BGP {} Attribute", marked("Large Communities"))
+        );
+        assert_eq!(highlight_match(title, "quic", 40), title);
+        assert_eq!(
+            highlight_match("Ärger", "ä", 40),
+            format!("{}rger", marked("Ä"))
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_a_match_in_view() {
+        let title = "QUIC: A UDP-Based Multiplexed and Secure Transport";
+        // A match before the cut: cut as usual.
+        assert_eq!(
+            highlight_match(title, "udp", 20),
+            format!("QUIC: A {}-Based...", marked("UDP"))
+        );
+        assert_eq!(
+            highlight_match(title, "based", 20),
+            format!("QUIC: A UDP-{}...", marked("Based"))
+        );
+        // Only past the cut: show the end of the title, from a word.
+        assert_eq!(
+            highlight_match(title, "secure", 20),
+            format!("...{} Transport", marked("Secure"))
+        );
+        // Not even the rest of the title fits: cut after the match too.
+        assert_eq!(
+            highlight_match(title, "multiplexed", 20),
+            format!("...{} an...", marked("Multiplexed"))
+        );
+        // Nothing matches: plain truncation.
+        assert_eq!(highlight_match(title, "tls", 20), "QUIC: A UDP-Based...");
+    }
+
+    #[test]
+    fn only_escapes_are_added() {
+        let out = highlight(FIXTURE, &COLORED);
+        let stripped = out
+            .replace(HEADING, "")
+            .replace(TITLE, "")
+            .replace(KEYWORD, "")
+            .replace(ANCHOR, "")
+            .replace(RESET, "");
+        assert_eq!(stripped, FIXTURE);
+    }
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use super::ColorChoice;
+
+/// How output is decorated, decided once per destination from `--color`
+/// and the environment and passed to whatever renders for it, so no
+/// feature checks the terminal on its own.
+///
+/// ```
+/// use rfc::render::{ColorChoice, OutputOptions};
+///
+/// assert!(OutputOptions::new(ColorChoice::Always, false).color);
+/// assert!(!OutputOptions::new(ColorChoice::Never, true).color);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// Emit ANSI color escapes
+    pub color: bool,
+}
+
+impl OutputOptions {
+    /// Options for output going somewhere that renders ANSI when
+    /// `terminal` (a terminal, or a pager like `less -R` on one). `auto`
+    /// colors only there, and only without `NO_COLOR`; `always` wins over
+    /// `NO_COLOR`.
+    pub fn new(choice: ColorChoice, terminal: bool) -> Self {
+        Self::resolve(choice, terminal, no_color_requested())
+    }
+
+    /// Options for stdout under `choice`.
+    pub fn stdout(choice: ColorChoice) -> Self {
+        Self::new(choice, io::stdout().is_terminal())
+    }
+
+    fn resolve(choice: ColorChoice, terminal: bool, no_color: bool) -> Self {
+        let color = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && !no_color,
+        };
+        Self { color }
+    }
+}
+
+/// Whether the environment opts out of escape sequences: `NO_COLOR` set
+/// to anything non-empty, or `TERM=dumb`.
+pub fn no_color_requested() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+    no_color || dumb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{highlight, Column, TableRenderer, TableRow};
+
+    #[test]
+    fn auto_needs_a_terminal_and_no_no_color() {
+        let color =
+            |choice, terminal, no_color| OutputOptions::resolve(choice, terminal, no_color).color;
+        assert!(color(ColorChoice::Auto, true, false));
+        assert!(!color(ColorChoice::Auto, false, false));
+        assert!(!color(ColorChoice::Auto, true, true));
+        assert!(color(ColorChoice::Always, false, true));
+        assert!(!color(ColorChoice::Never, true, false));
+    }
+
+    #[test]
+    fn never_and_no_color_leave_out_every_escape() {
+        let text = "1.  Introduction\n\n   Senders MUST NOT retry; see [RFC2119].\n";
+        let rows = [TableRow::new(vec!["rfc9000".into()], "QUIC over TLS")];
+        for output in [
+            OutputOptions::resolve(ColorChoice::Never, true, false),
+            OutputOptions::resolve(ColorChoice::Auto, true, true),
+        ] {
+            assert_eq!(highlight(text, &output), text);
+            let table = TableRenderer::new(80)
+                .with_column(Column::fit())
+                .with_output(output)
+                .with_highlight(Some("quic".into()));
+            assert_eq!(table.render(&rows), ["rfc9000  QUIC over TLS"]);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! roff, using the man(7) macros, from a document's text, for `rfc
+//! --man`.
+//!
+//! The title goes in the `NAME` section, sections become `.SH` and
+//! subsections `.SS`, and artwork, tables, and the table of contents are
+//! kept as they are in no-fill blocks. Deeper headings are set in bold.
+
+use crate::models::DocumentType;
+
+use super::blocks::{self, Block};
+
+/// Convert `text`, the text of `doc_type`, to roff for `man -l -` or
+/// `groff -man`.
+///
+/// ```
+/// use rfc::models::DocumentType;
+///
+/// let text = "1.  Introduction\n\n   .start and \\end\n";
+/// assert_eq!(
+///     rfc::render::to_roff(text, &DocumentType::Rfc(9000)),
+///     ".TH \"RFC9000\" \"7\" \"\" \"RFC 9000\" \"Request for Comments\"\n\
+///      .SH \"1. Introduction\"\n\
+///      .PP\n\
+///      \\&.start and \\eend\n"
+/// );
+/// ```
+pub fn to_roff(text: &str, doc_type: &DocumentType) -> String {
+    let manual = match doc_type {
+        DocumentType::Rfc(_) => "Request for Comments",
+        DocumentType::Draft(_) => "Internet-Draft",
+    };
+    let mut out = format!(
+        ".TH {} \"7\" \"\" {} {}\n",
+        argument(&doc_type.name().to_uppercase()),
+        argument(&doc_type.to_string()),
+        argument(manual)
+    );
+
+    let mut blocks = blocks::parse(text);
+    // The header block comes before the title on the page, but man pages
+    // open with NAME.
+    if let Some(at) = blocks.iter().position(|b| matches!(b, Block::Title(_))) {
+        let Block::Title(title) = blocks.remove(at) else {
+            unreachable!()
+        };
+        out.push_str(".SH NAME\n");
+        out.push_str(&line(&format!("{} \\- {}", doc_type, escape(&title))));
+    }
+    for block in &blocks {
+        match block {
+            Block::Title(title) => {
+                out.push_str(".PP\n");
+                out.push_str(&format!(".B {}\n", argument(title)));
+            }
+            Block::DraftName(name) => {
+                out.push_str(".PP\n");
+                out.push_str(&line(&escape(name)));
+            }
+            Block::Heading { level: 2, text } => {
+                out.push_str(&format!(".SH {}\n", argument(text)));
+            }
+            Block::Heading { level: 3, text } => {
+                out.push_str(&format!(".SS {}\n", argument(text)));
+            }
+            Block::Heading { text, .. } => {
+                out.push_str(".PP\n");
+                out.push_str(&format!(".B {}\n", argument(text)));
+            }
+            Block::Paragraph(text) => {
+                out.push_str(".PP\n");
+                out.push_str(&line(&escape(text)));
+            }
+            Block::List(items) => {
+                for item in items {
+                    out.push_str(".IP \\(bu 3\n");
+                    out.push_str(&line(&escape(item)));
+                }
+            }
+            Block::Preformatted(lines) => {
+                out.push_str(".PP\n.RS 4\n.nf\n");
+                for text in lines {
+                    out.push_str(&line(&escape(text)));
+                }
+                out.push_str(".fi\n.RE\n");
+            }
+        }
+    }
+    out
+}
+
+/// `text` (already escaped) as an input line, guarded so a leading `.`
+/// or `'` isn't read as a request.
+fn line(text: &str) -> String {
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{}\n", text)
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// `text` with roff's escape character spelled out.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+}
+
+/// `text` as one quoted macro argument.
+fn argument(text: &str) -> String {
+    format!("\"{}\"", escape(text).replace('"', "\\(dq"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = format!(
+            "{}/tests/fixtures/render/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    fn requests(roff: &str, macro_name: &str) -> Vec<String> {
+        roff.lines()
+            .filter_map(|l| l.strip_prefix(macro_name))
+            .map(|args| args.trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn sections_become_sh_and_ss() {
+        for name in ["rfc9000-excerpt.txt", "rfc9000-html2text.txt"] {
+            let roff = to_roff(&fixture(name), &DocumentType::Rfc(9000));
+            assert!(roff.starts_with(".TH \"RFC9000\" \"7\""), "{}", name);
+            assert!(
+                roff.contains(
+                    ".SH NAME\nRFC 9000 \\- QUIC: A UDP-Based Multiplexed and Secure Transport\n"
+                ),
+                "{}",
+                name
+            );
+            let sections = requests(&roff, ".SH ");
+            assert!(sections.contains(&"\"Abstract\"".to_string()), "{}", name);
+            assert!(
+                sections.contains(&"\"1. Overview\"".to_string()),
+                "{}",
+                name
+            );
+            assert_eq!(
+                requests(&roff, ".SS "),
+                ["\"1.1. Document Structure\""],
+                "{}",
+                name
+            );
+            assert!(
+                roff.contains(".nf\n+---------+          +---------+\n"),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        let text = "\
+1.  Quoting \"Names\"
+
+   .hidden request and 'another, with C:\\path
+
+   *  .item
+";
+        let roff = to_roff(text, &DocumentType::Draft("draft-foo-bar".into()));
+        assert!(roff.starts_with(
+            ".TH \"DRAFT-FOO-BAR\" \"7\" \"\" \"draft-foo-bar\" \"Internet-Draft\"\n"
+        ));
+        assert!(roff.contains(".SH \"1. Quoting \\(dqNames\\(dq\"\n"));
+        assert!(roff.contains("\n\\&.hidden request and 'another, with C:\\epath\n"));
+        assert!(roff.contains(".IP \\(bu 3\n\\&.item\n"));
+    }
+
+    /// Run the fixtures through groff, when it's installed, and check it
+    /// has nothing to warn about.
+    #[test]
+    fn groff_renders_without_warnings() {
+        if Command::new("groff").arg("--version").output().is_err() {
+            return;
+        }
+        for name in ["rfc9000-excerpt.txt", "rfc9000-html2text.txt"] {
+            let roff = to_roff(&fixture(name), &DocumentType::Rfc(9000));
+            let mut groff = Command::new("groff")
+                .args(["-k", "-man", "-Tutf8", "-ww", "-z"])
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            groff
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(roff.as_bytes())
+                .unwrap();
+            let out = groff.wait_with_output().unwrap();
+            assert!(out.status.success(), "{}", name);
+            assert_eq!(String::from_utf8_lossy(&out.stderr), "", "{}", name);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Aligned, width-budgeted listings: search results, the cache listing,
+//! recently opened documents.
+//!
+//! A table is a few short columns followed by a title that takes
+//! whatever width is left. On narrow terminals the title is truncated,
+//! and columns marked droppable go first rather than squeezing it below
+//! a readable width.
+
+use crate::models::{display_width, pad_to_width, truncate_to_width};
+
+use super::{highlight_match, OutputOptions};
+
+/// Space between columns.
+const GUTTER: usize = 2;
+
+/// How one column of a [`TableRenderer`] sizes and aligns its cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    /// Cells are padded to this width; `None` fits the widest cell
+    pub width: Option<usize>,
+    /// Pad on the left, for sizes and counts
+    pub right_align: bool,
+    /// Droppable columns go, highest rank first, when the title would
+    /// otherwise be narrower than the renderer's minimum; `None` stays
+    pub drop_rank: Option<u8>,
+}
+
+impl Column {
+    /// A left-aligned column as wide as its widest cell.
+    pub fn fit() -> Self {
+        Self {
+            width: None,
+            right_align: false,
+            drop_rank: None,
+        }
+    }
+
+    /// A left-aligned column `width` cells wide.
+    pub fn fixed(width: usize) -> Self {
+        Self {
+            width: Some(width),
+            ..Self::fit()
+        }
+    }
+
+    pub fn right_aligned(self) -> Self {
+        Self {
+            right_align: true,
+            ..self
+        }
+    }
+
+    /// Let the column be dropped to make room for the title; see
+    /// [`drop_rank`](Self::drop_rank).
+    pub fn droppable(self, rank: u8) -> Self {
+        Self {
+            drop_rank: Some(rank),
+            ..self
+        }
+    }
+}
+
+/// One line of a table: a cell per column, then the title.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableRow {
+    pub cells: Vec<String>,
+    pub title: String,
+    /// Printed whole right after the title, separator included; the
+    /// title is truncated further to make room
+    pub suffix: String,
+}
+
+impl TableRow {
+    pub fn new(cells: Vec<String>, title: impl Into<String>) -> Self {
+        Self {
+            cells,
+            title: title.into(),
+            suffix: String::new(),
+        }
+    }
+
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+}
+
+/// Lays out [`TableRow`]s in aligned columns within a width budget.
+///
+/// ```
+/// use rfc::render::{Column, TableRenderer, TableRow};
+///
+/// let table = TableRenderer::new(30)
+///     .with_column(Column::fit())
+///     .with_column(Column::fixed(3));
+/// let rows = [
+///     TableRow::new(vec!["rfc9000".into(), "RFC".into()], "QUIC: A UDP-Based Transport"),
+///     TableRow::new(vec!["draft-foo".into(), "I-D".into()], "Foo"),
+/// ];
+/// assert_eq!(
+///     table.render(&rows),
+///     ["rfc9000    RFC  QUIC: A UD...", "draft-foo  I-D  Foo"]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRenderer {
+    columns: Vec<Column>,
+    /// Terminal (or other) width the lines must fit in
+    width: usize,
+    /// Keep every column and never truncate titles
+    wide: bool,
+    /// Narrowest title worth keeping before dropping columns
+    min_title_width: usize,
+    /// Query whose matches are highlighted in titles
+    highlight: Option<String>,
+    output: OutputOptions,
+}
+
+impl TableRenderer {
+    /// A table fitting `width` cells, with no columns yet.
+    pub fn new(width: usize) -> Self {
+        Self {
+            columns: Vec::new(),
+            width,
+            wide: false,
+            min_title_width: 0,
+            highlight: None,
+            output: OutputOptions::default(),
+        }
+    }
+
+    pub fn with_column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Keep every column and show titles whole, however long the lines.
+    pub fn with_wide(mut self, wide: bool) -> Self {
+        self.wide = wide;
+        self
+    }
+
+    /// Drop droppable columns rather than leave the title narrower than
+    /// `width`.
+    pub fn with_min_title_width(mut self, width: usize) -> Self {
+        self.min_title_width = width;
+        self
+    }
+
+    /// Highlight matches of `query` (see [`highlight_match`]) in titles,
+    /// when the output is colored.
+    pub fn with_highlight(mut self, query: Option<String>) -> Self {
+        self.highlight = query;
+        self
+    }
+
+    /// Where the lines are going; uncolored unless this says otherwise.
+    pub fn with_output(mut self, output: OutputOptions) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Work out which columns fit and how wide each is, for `rows`.
+    pub fn layout(&self, rows: &[TableRow]) -> TableLayout {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                column.width.unwrap_or_else(|| {
+                    rows.iter()
+                        .filter_map(|row| row.cells.get(i))
+                        .map(|cell| display_width(cell))
+                        .max()
+                        .unwrap_or(0)
+                })
+            })
+            .collect();
+        // Leave one column spare so a full-width line doesn't wrap on
+        // terminals that auto-wrap at the last cell.
+        let budget = self.width.saturating_sub(1);
+        let used = |shown: &[bool]| -> usize {
+            widths
+                .iter()
+                .zip(shown)
+                .filter(|(_, shown)| **shown)
+                .map(|(width, _)| width + GUTTER)
+                .sum()
+        };
+
+        let mut shown = vec![true; self.columns.len()];
+        if !self.wide {
+            let mut droppable: Vec<(u8, usize)> = self
+                .columns
+                .iter()
+                .enumerate()
+                .filter_map(|(i, column)| Some((column.drop_rank?, i)))
+                .collect();
+            droppable.sort_unstable();
+            while budget.saturating_sub(used(&shown)) < self.min_title_width {
+                let Some((_, i)) = droppable.pop() else {
+                    break;
+                };
+                shown[i] = false;
+            }
+        }
+        let title_width = if self.wide {
+            usize::MAX
+        } else {
+            budget.saturating_sub(used(&shown))
+        };
+        TableLayout {
+            columns: self
+                .columns
+                .iter()
+                .zip(widths)
+                .zip(&shown)
+                .map(|((column, width), shown)| shown.then_some((width, column.right_align)))
+                .collect(),
+            title_width,
+            highlight: self.highlight.clone().filter(|_| self.output.color),
+        }
+    }
+
+    /// Lay out `rows` and render each as a line.
+    pub fn render(&self, rows: &[TableRow]) -> Vec<String> {
+        let layout = self.layout(rows);
+        rows.iter().map(|row| layout.render(row)).collect()
+    }
+}
+
+/// Column widths worked out by [`TableRenderer::layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLayout {
+    /// Width and right-alignment of each column, `None` where dropped
+    columns: Vec<Option<(usize, bool)>>,
+    /// `usize::MAX` means "don't truncate"
+    pub title_width: usize,
+    highlight: Option<String>,
+}
+
+impl TableLayout {
+    /// Whether column `index` made it into the layout.
+    pub fn shows(&self, index: usize) -> bool {
+        self.columns.get(index).is_some_and(Option::is_some)
+    }
+
+    /// `row` as one line, with no trailing newline.
+    pub fn render(&self, row: &TableRow) -> String {
+        let mut line = String::new();
+        for (column, cell) in self.columns.iter().zip(&row.cells) {
+            let Some((width, right_align)) = *column else {
+                continue;
+            };
+            if right_align {
+                line.push_str(&" ".repeat(width.saturating_sub(display_width(cell))));
+                line.push_str(cell);
+            } else {
+                line.push_str(&pad_to_width(cell, width));
+            }
+            line.push_str(&" ".repeat(GUTTER));
+        }
+        let title_width = if self.title_width == usize::MAX {
+            usize::MAX
+        } else {
+            // The suffix is the point of the annotation, so the title gives
+            // up room for it rather than the other way around.
+            self.title_width.saturating_sub(display_width(&row.suffix))
+        };
+        match &self.highlight {
+            Some(query) => line.push_str(&highlight_match(&row.title, query, title_width)),
+            None => line.push_str(&truncate_to_width(&row.title, title_width)),
+        }
+        line.push_str(&row.suffix);
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search-style rows: name, type tag, and date, the date dropped
+    /// first.
+    fn search_table(width: usize) -> TableRenderer {
+        TableRenderer::new(width)
+            .with_column(Column::fit())
+            .with_column(Column::fixed(3).droppable(1))
+            .with_column(Column::fixed(7).droppable(2))
+            .with_min_title_width(30)
+    }
+
+    fn rows() -> Vec<TableRow> {
+        vec![
+            TableRow::new(
+                vec!["rfc9000".into(), "RFC".into(), "2021-05".into()],
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+            ),
+            TableRow::new(
+                vec!["rfc2616".into(), "RFC".into(), "1999-06".into()],
+                "Hypertext Transfer Protocol -- HTTP/1.1",
+            )
+            .with_suffix(" [obsoleted by RFC 7230]"),
+            TableRow::new(
+                vec![
+                    "draft-ietf-quic-multipath".into(),
+                    "I-D".into(),
+                    String::new(),
+                ],
+                "Multipath Extension for QUIC",
+            ),
+        ]
+    }
+
+    fn golden(width: usize) -> String {
+        search_table(width).render(&rows()).join("\n")
+    }
+
+    #[test]
+    fn golden_output_at_60_columns_drops_tag_and_date() {
+        assert_eq!(
+            golden(60),
+            "\
+rfc9000                    QUIC: A UDP-Based Multiplexed...
+rfc2616                    Hyper... [obsoleted by RFC 7230]
+draft-ietf-quic-multipath  Multipath Extension for QUIC"
+        );
+    }
+
+    #[test]
+    fn golden_output_at_80_columns() {
+        assert_eq!(
+            golden(80),
+            "\
+rfc9000                    RFC  2021-05  QUIC: A UDP-Based Multiplexed and S...
+rfc2616                    RFC  1999-06  Hypertext T... [obsoleted by RFC 7230]
+draft-ietf-quic-multipath  I-D           Multipath Extension for QUIC"
+        );
+    }
+
+    #[test]
+    fn golden_output_at_120_columns_fits_everything() {
+        assert_eq!(
+            golden(120),
+            "\
+rfc9000                    RFC  2021-05  QUIC: A UDP-Based Multiplexed and Secure Transport
+rfc2616                    RFC  1999-06  Hypertext Transfer Protocol -- HTTP/1.1 [obsoleted by RFC 7230]
+draft-ietf-quic-multipath  I-D           Multipath Extension for QUIC"
+        );
+    }
+
+    #[test]
+    fn columns_drop_by_rank_until_the_title_fits() {
+        let rows = rows();
+        let layout = search_table(80).layout(&rows);
+        assert!(layout.shows(1) && layout.shows(2));
+        assert_eq!(layout.title_width, 79 - (25 + 2) - (3 + 2) - (7 + 2));
+
+        let layout = search_table(50).layout(&rows[..2]);
+        assert!(layout.shows(1) && !layout.shows(2));
+        assert_eq!(layout.title_width, 49 - (7 + 2) - (3 + 2));
+
+        let layout = search_table(40).layout(&rows);
+        assert!(!layout.shows(1) && !layout.shows(2));
+        assert_eq!(layout.title_width, 12);
+
+        let layout = search_table(40).with_wide(true).layout(&rows);
+        assert!(layout.shows(1) && layout.shows(2));
+        assert_eq!(layout.title_width, usize::MAX);
+    }
+
+    #[test]
+    fn right_aligned_columns_pad_on_the_left() {
+        let table = TableRenderer::new(80)
+            .with_column(Column::fit())
+            .with_column(Column::fixed(8).right_aligned());
+        let rows = [
+            TableRow::new(vec!["rfc1".into(), "1.2 KB".into()], "Host Software"),
+            TableRow::new(vec!["rfc9000".into(), "220.4 KB".into()], "QUIC"),
+        ];
+        assert_eq!(
+            table.render(&rows),
+            [
+                "rfc1       1.2 KB  Host Software",
+                "rfc9000  220.4 KB  QUIC"
+            ]
+        );
+    }
+}
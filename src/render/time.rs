@@ -0,0 +1,104 @@
+//! When something happened, as listings show it: `3 days ago` for the
+//! past week, the local date before that.
+
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+
+/// Whether listings may say `3 days ago`, or always give the date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeStyle {
+    /// Relative for the past week, dates before that
+    #[default]
+    Relative,
+    /// Always `YYYY-MM-DD`, for scripts (`--absolute-times`)
+    Absolute,
+}
+
+/// Events older than this are shown by date.
+const RELATIVE_LIMIT: Duration = Duration::days(7);
+
+impl TimeStyle {
+    /// `at` as of now, dated in the local time zone.
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use rfc::render::TimeStyle;
+    ///
+    /// let at = Utc::now() - Duration::days(3);
+    /// assert_eq!(TimeStyle::Relative.format(at), "3 days ago");
+    /// assert_eq!(TimeStyle::Absolute.format(at).len(), "2023-11-02".len());
+    /// ```
+    pub fn format(self, at: DateTime<Utc>) -> String {
+        self.format_at(at, Utc::now(), &Local)
+    }
+
+    fn format_at<Tz: TimeZone>(self, at: DateTime<Utc>, now: DateTime<Utc>, tz: &Tz) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let age = now - at;
+        if self == TimeStyle::Absolute || age > RELATIVE_LIMIT || age < -Duration::minutes(1) {
+            return at.with_timezone(tz).format("%Y-%m-%d").to_string();
+        }
+        let (count, unit) = if age < Duration::minutes(1) {
+            return "just now".to_string();
+        } else if age < Duration::hours(1) {
+            (age.num_minutes(), "minute")
+        } else if age < Duration::days(1) {
+            (age.num_hours(), "hour")
+        } else {
+            (age.num_days(), "day")
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        format!("{} {}{} ago", count, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    fn relative(age: Duration) -> String {
+        TimeStyle::Relative.format_at(now() - age, now(), &Utc)
+    }
+
+    #[test]
+    fn recent_events_are_relative() {
+        assert_eq!(relative(Duration::zero()), "just now");
+        assert_eq!(relative(Duration::seconds(59)), "just now");
+        assert_eq!(relative(Duration::seconds(61)), "1 minute ago");
+        assert_eq!(relative(Duration::minutes(59)), "59 minutes ago");
+        assert_eq!(relative(Duration::hours(1)), "1 hour ago");
+        assert_eq!(relative(Duration::hours(23)), "23 hours ago");
+        assert_eq!(relative(Duration::hours(25)), "1 day ago");
+        assert_eq!(relative(Duration::days(7)), "7 days ago");
+        // A clock a few seconds behind the server's.
+        assert_eq!(relative(Duration::seconds(-5)), "just now");
+    }
+
+    #[test]
+    fn older_and_future_events_are_dated() {
+        assert_eq!(relative(Duration::days(8)), "2024-03-02");
+        assert_eq!(relative(Duration::days(400)), "2023-02-04");
+        assert_eq!(relative(Duration::days(-2)), "2024-03-12");
+    }
+
+    #[test]
+    fn absolute_is_always_a_local_date() {
+        let at = now() - Duration::hours(23);
+        assert_eq!(TimeStyle::Absolute.format_at(at, now(), &Utc), "2024-03-09");
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        assert_eq!(
+            TimeStyle::Absolute.format_at(at, now(), &tokyo),
+            "2024-03-09"
+        );
+        let late = Utc.with_ymd_and_hms(2024, 3, 9, 20, 0, 0).unwrap();
+        assert_eq!(
+            TimeStyle::Absolute.format_at(late, now(), &tokyo),
+            "2024-03-10"
+        );
+    }
+}
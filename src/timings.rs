@@ -0,0 +1,185 @@
+//! Where the time goes: the API clients, HTML conversion, and the cache
+//! open a `tracing` span per unit of work, named for its phase
+//! (`fetch`, `metadata`, `search`, `convert`, `cache write`).
+//! [`TimingRecorder`] is a subscriber that adds up how long spans of each
+//! phase were open, for `--timings`.
+//!
+//! Without a subscriber installed the spans are disabled at their call
+//! sites and cost next to nothing.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Spans the recorder times, by name.
+pub const PHASES: &[&str] = &["fetch", "metadata", "search", "convert", "cache write"];
+
+/// Time spent in one phase, over all of its spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub total: Duration,
+    /// How many spans were added up
+    pub count: usize,
+}
+
+/// Per-phase totals, in the order phases were first seen.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl Timings {
+    /// Add one span of `phase` that was open for `elapsed`.
+    pub fn add(&mut self, phase: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|p| p.phase == phase) {
+            Some(timing) => {
+                timing.total += elapsed;
+                timing.count += 1;
+            }
+            None => self.phases.push(PhaseTiming {
+                phase,
+                total: elapsed,
+                count: 1,
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+}
+
+impl fmt::Display for Timings {
+    /// `fetch 812ms, metadata 203ms, convert 95ms, cache write 3ms`, with
+    /// the span count after phases seen more than once: `fetch 2410ms (12)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, timing) in self.phases.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} {}ms", timing.phase, timing.total.as_millis())?;
+            if timing.count > 1 {
+                write!(f, " ({})", timing.count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `tracing` subscriber timing the spans named in [`PHASES`]; every
+/// other span and event is ignored. Spans are timed from creation until
+/// they close, so async work counts its waiting too.
+#[derive(Default)]
+pub struct TimingRecorder {
+    next_id: AtomicU64,
+    /// Open spans: phase, start, and reference count
+    open: Mutex<HashMap<u64, (&'static str, Instant, usize)>>,
+    timings: Mutex<Timings>,
+}
+
+impl TimingRecorder {
+    /// Install a recorder as the global subscriber and return it to read
+    /// from later. Fails if a subscriber is already installed.
+    pub fn install() -> anyhow::Result<Arc<Self>> {
+        let recorder = Arc::new(Self::default());
+        tracing::subscriber::set_global_default(Arc::clone(&recorder))
+            .map_err(|_| anyhow::anyhow!("A tracing subscriber is already installed"))?;
+        Ok(recorder)
+    }
+
+    /// The totals of every span closed so far.
+    pub fn timings(&self) -> Timings {
+        self.timings.lock().unwrap().clone()
+    }
+}
+
+impl Subscriber for TimingRecorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span() && PHASES.contains(&metadata.name())
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.open
+            .lock()
+            .unwrap()
+            .insert(id, (span.metadata().name(), Instant::now(), 1));
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+
+    fn clone_span(&self, id: &Id) -> Id {
+        if let Some((_, _, refs)) = self.open.lock().unwrap().get_mut(&id.into_u64()) {
+            *refs += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let mut open = self.open.lock().unwrap();
+        let Some((_, _, refs)) = open.get_mut(&id.into_u64()) else {
+            return false;
+        };
+        *refs -= 1;
+        if *refs > 0 {
+            return false;
+        }
+        let (phase, started, _) = open.remove(&id.into_u64()).unwrap();
+        drop(open);
+        self.timings.lock().unwrap().add(phase, started.elapsed());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_lists_phases_in_order_with_counts() {
+        let mut timings = Timings::default();
+        timings.add("fetch", Duration::from_millis(500));
+        timings.add("metadata", Duration::from_millis(203));
+        timings.add("fetch", Duration::from_millis(312));
+        timings.add("convert", Duration::from_micros(95_400));
+        timings.add("cache write", Duration::from_millis(3));
+        assert_eq!(
+            timings.to_string(),
+            "fetch 812ms (2), metadata 203ms, convert 95ms, cache write 3ms"
+        );
+        assert_eq!(Timings::default().to_string(), "");
+    }
+
+    #[test]
+    fn recorder_adds_up_closed_phase_spans() {
+        let recorder = Arc::new(TimingRecorder::default());
+        tracing::subscriber::with_default(Arc::clone(&recorder), || {
+            for _ in 0..3 {
+                let span = tracing::info_span!("fetch");
+                let _clone = span.clone();
+                let _entered = span.entered();
+            }
+            let _ignored = tracing::info_span!("unrelated").entered();
+            let _open = tracing::info_span!("convert");
+            assert_eq!(recorder.timings().phases.len(), 1);
+        });
+        let timings = recorder.timings();
+        let phases: Vec<_> = timings.phases.iter().map(|t| (t.phase, t.count)).collect();
+        assert_eq!(phases, [("fetch", 3), ("convert", 1)]);
+    }
+}
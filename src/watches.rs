@@ -0,0 +1,315 @@
+//! Drafts to keep an eye on (`rfc watch add draft-ietf-idr-sr-policy-safi`),
+//! kept in `watches.json` beside `config.toml` along with what the
+//! Datatracker said about each one when it was last checked.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::models::DocumentDetails;
+
+/// Unversioned draft name to its last recorded snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WatchList(BTreeMap<String, Snapshot>);
+
+/// The facts about a watched draft that changes are reported against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)]
+    pub rev: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub iesg_state: Option<String>,
+    #[serde(default)]
+    pub wg_state: Option<String>,
+    #[serde(default)]
+    pub rfc_number: Option<u32>,
+    #[serde(default)]
+    pub replaced_by: Vec<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl Snapshot {
+    pub fn new(details: &DocumentDetails, checked_at: DateTime<Utc>) -> Self {
+        Self {
+            rev: details.rev.clone(),
+            state: details.state.clone(),
+            iesg_state: details.iesg_state.clone(),
+            wg_state: details.wg_state.clone(),
+            rfc_number: details.rfc_number,
+            replaced_by: details.replaced_by.clone(),
+            checked_at,
+        }
+    }
+
+    /// What differs in `newer`, in the order worth reading: revision,
+    /// then states, then publication and replacement.
+    pub fn changes(&self, newer: &Snapshot) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if newer.rev.is_some() && newer.rev != self.rev {
+            changes.push(Change::Revision {
+                from: self.rev.clone(),
+                to: newer.rev.clone(),
+            });
+        }
+        for (which, old, new) in [
+            (StateKind::Draft, &self.state, &newer.state),
+            (StateKind::Wg, &self.wg_state, &newer.wg_state),
+            (StateKind::Iesg, &self.iesg_state, &newer.iesg_state),
+        ] {
+            if old != new {
+                changes.push(Change::State {
+                    which,
+                    from: old.clone(),
+                    to: new.clone(),
+                });
+            }
+        }
+        if let (None, Some(rfc)) = (self.rfc_number, newer.rfc_number) {
+            changes.push(Change::Published { rfc });
+        }
+        let by: Vec<String> = newer
+            .replaced_by
+            .iter()
+            .filter(|name| !self.replaced_by.contains(name))
+            .cloned()
+            .collect();
+        if !by.is_empty() {
+            changes.push(Change::Replaced { by });
+        }
+        changes
+    }
+}
+
+/// Which of a draft's states changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateKind {
+    Draft,
+    Wg,
+    Iesg,
+}
+
+impl StateKind {
+    fn label(&self) -> &'static str {
+        match self {
+            StateKind::Draft => "state",
+            StateKind::Wg => "WG state",
+            StateKind::Iesg => "IESG state",
+        }
+    }
+}
+
+/// One difference between two snapshots of a draft.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    Revision {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    State {
+        which: StateKind,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    Published {
+        rfc: u32,
+    },
+    Replaced {
+        by: Vec<String>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        match self {
+            Change::Revision { from, to } => {
+                let rev = |v: &Option<String>| {
+                    v.as_ref()
+                        .map_or("(none)".to_string(), |r| format!("-{}", r))
+                };
+                write!(f, "{} → {}", rev(from), rev(to))
+            }
+            Change::State { which, from, to } => {
+                write!(f, "{}: {} → {}", which.label(), or_none(from), or_none(to))
+            }
+            Change::Published { rfc } => write!(f, "published as RFC {}", rfc),
+            Change::Replaced { by } => write!(f, "replaced by {}", by.join(", ")),
+        }
+    }
+}
+
+impl WatchList {
+    /// Default location of the watch list, if the platform has one.
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "rfc").map(|dirs| dirs.config_dir().join("watches.json"))
+    }
+
+    /// Load the watch list from its default location; empty if the file
+    /// is missing.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load the watch list from `path`, treating a missing file as empty.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid watch list {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read watch list {}", path.display()))
+            }
+        }
+    }
+
+    /// Write the watch list to its default location.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path().context("No config directory on this platform")?;
+        self.save_to(&path)
+    }
+
+    /// Write the watch list to `path`, creating its directory.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write watch list {}", path.display()))
+    }
+
+    /// Record `snapshot` as the baseline for `name`, returning the old one.
+    pub fn insert(&mut self, name: &str, snapshot: Snapshot) -> Option<Snapshot> {
+        self.0.insert(name.to_string(), snapshot)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Snapshot> {
+        self.0.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Snapshot> {
+        self.0.get(name)
+    }
+
+    /// Every watched draft and its baseline, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Snapshot)> {
+        self.0
+            .iter()
+            .map(|(name, snapshot)| (name.as_str(), snapshot))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn snapshot(rev: &str, iesg_state: &str) -> Snapshot {
+        Snapshot {
+            rev: Some(rev.to_string()),
+            state: Some("Active".to_string()),
+            iesg_state: Some(iesg_state.to_string()),
+            wg_state: Some("Submitted to IESG for Publication".to_string()),
+            rfc_number: None,
+            replaced_by: Vec::new(),
+            checked_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn unchanged_snapshots_have_no_changes() {
+        let old = snapshot("07", "AD Evaluation");
+        let mut new = old.clone();
+        new.checked_at = Utc::now();
+        assert!(old.changes(&new).is_empty());
+    }
+
+    #[test]
+    fn changes_read_like_a_changelog() {
+        let old = snapshot("07", "AD Evaluation");
+        let new = Snapshot {
+            state: Some("RFC".to_string()),
+            rfc_number: Some(9602),
+            ..snapshot("08", "IESG Evaluation")
+        };
+        let lines: Vec<String> = old.changes(&new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "-07 → -08",
+                "state: Active → RFC",
+                "IESG state: AD Evaluation → IESG Evaluation",
+                "published as RFC 9602",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_new_replacements_are_reported() {
+        let old = Snapshot {
+            replaced_by: vec!["draft-a".to_string()],
+            ..snapshot("07", "AD Evaluation")
+        };
+        let new = Snapshot {
+            replaced_by: vec!["draft-a".to_string(), "draft-b".to_string()],
+            ..old.clone()
+        };
+        assert_eq!(
+            old.changes(&new),
+            [Change::Replaced {
+                by: vec!["draft-b".to_string()]
+            }]
+        );
+    }
+
+    #[test]
+    fn changes_serialize_with_a_kind() {
+        let change = Change::State {
+            which: StateKind::Iesg,
+            from: Some("AD Evaluation".to_string()),
+            to: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            serde_json::json!({"kind": "state", "which": "iesg", "from": "AD Evaluation", "to": null})
+        );
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("watches.json");
+        assert!(WatchList::load_from(&path).unwrap().is_empty());
+
+        let mut watches = WatchList::default();
+        watches.insert(
+            "draft-ietf-idr-sr-policy-safi",
+            snapshot("07", "AD Evaluation"),
+        );
+        watches.save_to(&path).unwrap();
+        assert_eq!(WatchList::load_from(&path).unwrap(), watches);
+    }
+}
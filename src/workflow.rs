@@ -0,0 +1,412 @@
+//! The "serve from cache, else fetch from the API and cache content +
+//! metadata" pipeline behind `rfc <document>`, `rfc fetch`, and `rfc sync`,
+//! for library users who want the same behavior. Progress and warnings
+//! are `tracing` events (`info` and `warn`) with this module's target;
+//! nothing is printed unless a subscriber shows them, as the CLI does.
+//!
+//! Needs the `cache` and `html` features.
+
+use std::pin::pin;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::future::{self, Either};
+
+use crate::api::{DataTrackerClient, DocumentSource, Provenance};
+use crate::cache::{CacheCounters, CacheMetadata, DocumentCache};
+use crate::models::{Document, DocumentType, Format, IndexLookup, RfcIndex};
+use crate::render::html_to_text;
+
+/// Return a document's text from the cache, fetching and caching it via
+/// `source` on a miss, and record that it was accessed (and whether it
+/// was a hit).
+pub async fn load_document(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    source: &dyn DocumentSource,
+    datatracker: &DataTrackerClient,
+) -> Result<String> {
+    let content = match cache.get_document(doc_type, Format::Text) {
+        Some(cached) => {
+            match cache.newest_revision(doc_type) {
+                Some(revision) => tracing::info!("Using cached copy of {}", revision),
+                None => tracing::info!("Using cached copy of {}", doc_type),
+            }
+            cache.record_counters(CacheCounters::hit(cached.len()));
+            cached
+        }
+        None => {
+            cache.record_counters(CacheCounters::miss());
+            fetch_and_cache(doc_type, cache, source, datatracker).await?
+        }
+    };
+    cache.record_access(doc_type);
+    Ok(content)
+}
+
+/// Fetch a document and store both its content and metadata in the cache,
+/// including where the content came from. Metadata is looked up while the
+/// content downloads; failures, and lookups still running a couple of
+/// seconds after the content is stored, are non-fatal — the content is
+/// still returned.
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use rfc::workflow::fetch_and_cache;
+/// use rfc::{CacheManager, DocumentFetcher, DocumentType};
+///
+/// let cache = CacheManager::new()?;
+/// let fetcher = DocumentFetcher::new()?;
+/// let text = fetch_and_cache(
+///     &DocumentType::Rfc(9000),
+///     &cache,
+///     &fetcher,
+///     &fetcher.datatracker(),
+/// )
+/// .await?;
+/// assert!(text.contains("QUIC"));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn fetch_and_cache(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    source: &dyn DocumentSource,
+    datatracker: &DataTrackerClient,
+) -> Result<String> {
+    tracing::info!("Fetching {}...", doc_type);
+
+    // The metadata lookup is independent of the content, so it runs
+    // alongside, and once the content is stored it gets only a short
+    // grace to finish: the viewer waits on the content fetch alone.
+    let content = async {
+        let fetched = source.fetch(doc_type).await?;
+        match &fetched.mirror {
+            Some(mirror) => tracing::info!("Retrieved from mirror {}", mirror),
+            None => tracing::info!("Retrieved from {}", fetched.source_url),
+        }
+        let provenance = fetched.provenance();
+        cache.record_counters(CacheCounters::fetched(fetched.content.len()));
+        let text = to_text(fetched.content, fetched.format, true);
+
+        cache.store_document(doc_type, Format::Text, &text)?;
+        forget_stale_bookmark(doc_type, cache, &text);
+        anyhow::Ok((text, provenance))
+    };
+    let name = doc_type.name();
+    let metadata = datatracker.get_document(&name);
+    let ((text, provenance), found) = match future::select(pin!(content), pin!(metadata)).await {
+        Either::Left((content, metadata)) => {
+            let content = content?;
+            let found = tokio::time::timeout(METADATA_GRACE, metadata)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(anyhow::anyhow!(
+                        "no answer within {}s",
+                        METADATA_GRACE.as_secs()
+                    ))
+                });
+            (content, found)
+        }
+        Either::Right((found, content)) => (content.await?, found),
+    };
+
+    if let Err(e) = found.and_then(|found| record_metadata(doc_type, cache, found)) {
+        tracing::warn!("Failed to fetch metadata for {}: {}", doc_type, e);
+    }
+    record_source(doc_type, cache, provenance);
+
+    Ok(text)
+}
+
+/// How long [`fetch_and_cache`] waits for the metadata lookup once the
+/// content is stored. A document whose lookup takes longer is cached
+/// untitled, for `cache backfill` to fill in.
+const METADATA_GRACE: Duration = Duration::from_secs(2);
+
+/// Outcome of [`refresh_cached`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refresh {
+    /// New content was fetched and stored.
+    Updated,
+    /// The server said 304, or sent back what we already had.
+    Unchanged,
+}
+
+/// Re-fetch an already-cached document, asking the source only for
+/// changes since `since` (normally the metadata's `cached_at`). New
+/// content's source is recorded; the rest of the metadata is left to the
+/// caller.
+pub async fn refresh_cached(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    source: &dyn DocumentSource,
+    since: Option<DateTime<Utc>>,
+) -> Result<Refresh> {
+    let Some(fetched) = source.fetch_if_modified(doc_type, since).await? else {
+        cache.record_counters(CacheCounters::refreshed(0));
+        return Ok(Refresh::Unchanged);
+    };
+    cache.record_counters(CacheCounters::refreshed(fetched.content.len()));
+    let provenance = fetched.provenance();
+    let text = to_text(fetched.content, fetched.format, false);
+    if cache.get_document(doc_type, Format::Text).as_deref() == Some(text.as_str()) {
+        return Ok(Refresh::Unchanged);
+    }
+    cache.store_document(doc_type, Format::Text, &text)?;
+    forget_stale_bookmark(doc_type, cache, &text);
+    record_source(doc_type, cache, provenance);
+    Ok(Refresh::Updated)
+}
+
+/// Note where just-stored content came from. Without existing metadata
+/// this creates some with an empty title, for `cache backfill` to fill in.
+fn record_source(doc_type: &DocumentType, cache: &dyn DocumentCache, source: Provenance) {
+    let metadata = match cache.get_metadata(doc_type) {
+        Some(existing) => CacheMetadata {
+            source: Some(source),
+            ..existing
+        },
+        None => CacheMetadata {
+            source: Some(source),
+            ..CacheMetadata::new("", Utc::now())
+        },
+    };
+    if let Err(e) = cache.store_metadata(doc_type, &metadata) {
+        tracing::warn!("Failed to record source for {}: {}", doc_type, e);
+    }
+}
+
+/// Drop the reading position if the newly stored `text` has moved too far
+/// from what it was set against.
+fn forget_stale_bookmark(doc_type: &DocumentType, cache: &dyn DocumentCache, text: &str) {
+    let Some(mark) = cache.get_metadata(doc_type).and_then(|m| m.bookmark) else {
+        return;
+    };
+    if mark.fits(text.lines().count()) {
+        return;
+    }
+    if let Err(e) = cache.set_bookmark(doc_type, None) {
+        tracing::warn!("Failed to clear bookmark for {}: {}", doc_type, e);
+    }
+}
+
+/// Reject RFC numbers the synced index knows don't exist, before spending
+/// a network round trip on them. A no-op for drafts, when no index has
+/// been synced, and when `force`d (`--force-fetch`: the index may be
+/// stale).
+pub fn check_against_index(
+    doc_type: &DocumentType,
+    index: Option<&RfcIndex>,
+    force: bool,
+) -> Result<()> {
+    let (DocumentType::Rfc(num), Some(index), false) = (doc_type, index, force) else {
+        return Ok(());
+    };
+    match index.lookup(*num) {
+        IndexLookup::Published(_) => Ok(()),
+        IndexLookup::NotIssued => anyhow::bail!(
+            "RFC {} was never issued (pass --force-fetch to try anyway)",
+            num
+        ),
+        IndexLookup::Unknown => match index.highest() {
+            Some(highest) if *num > highest => {
+                let as_of = index
+                    .synced_at
+                    .map(|at| format!(" as of {}", at.format("%Y-%m-%d")))
+                    .unwrap_or_default();
+                anyhow::bail!(
+                    "RFC {} has not been published; highest known is RFC {}{}. \
+                     Run 'rfc sync-index' if it's new, or pass --force-fetch",
+                    num,
+                    highest,
+                    as_of
+                )
+            }
+            // A gap below the highest number that the index doesn't mark
+            // as not-issued: let the server decide.
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Turn fetched content into the plain text we cache, converting HTML.
+fn to_text(content: String, format: Format, verbose: bool) -> String {
+    match format {
+        Format::Text => content,
+        Format::Html => {
+            if verbose {
+                tracing::info!("Plain text not available, converting from HTML...");
+            }
+            html_to_text(&content).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "HTML to text conversion failed ({}), displaying raw HTML",
+                    e
+                );
+                content
+            })
+        }
+    }
+}
+
+/// Look up the document's title and abstract and record them with a
+/// fresh `cached_at`.
+pub async fn store_metadata(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    datatracker: &DataTrackerClient,
+) -> Result<()> {
+    let found = datatracker.get_document(&doc_type.name()).await?;
+    record_metadata(doc_type, cache, found)
+}
+
+/// Record `doc`'s title and abstract as `doc_type`'s, with a fresh
+/// `cached_at`, keeping what the user attached (tags, bookmark, access
+/// time).
+fn record_metadata(
+    doc_type: &DocumentType,
+    cache: &dyn DocumentCache,
+    doc: Document,
+) -> Result<()> {
+    let mut metadata = match cache.get_metadata(doc_type) {
+        Some(existing) => CacheMetadata {
+            cached_at: Utc::now(),
+            ..existing
+        },
+        None => CacheMetadata::new("", Utc::now()),
+    };
+    metadata.merge_from_server(doc.into());
+    cache.store_metadata(doc_type, &metadata)?;
+    Ok(())
+}
+
+/// Cached documents with no metadata, or metadata without a title.
+pub fn missing_titles(cache: &dyn DocumentCache) -> Vec<DocumentType> {
+    let mut missing: Vec<_> = cache
+        .list_cached()
+        .into_iter()
+        .filter(|doc| {
+            cache
+                .get_metadata(doc)
+                .is_none_or(|meta| meta.title.trim().is_empty())
+        })
+        .collect();
+    missing.sort_by_key(|doc| doc.name());
+    missing
+}
+
+/// Cached documents whose metadata has a title but predates abstracts
+/// being recorded.
+pub fn missing_abstracts(cache: &dyn DocumentCache) -> Vec<DocumentType> {
+    let mut missing: Vec<_> = cache
+        .list_cached()
+        .into_iter()
+        .filter(|doc| {
+            cache
+                .get_metadata(doc)
+                .is_some_and(|meta| !meta.title.trim().is_empty() && meta.abstract_text.is_none())
+        })
+        .collect();
+    missing.sort_by_key(|doc| doc.name());
+    missing
+}
+
+/// Look up and record titles and abstracts for `docs`, in batches (see
+/// [`DataTrackerClient::get_documents`]). An existing `cached_at` is kept
+/// so `sync --older-than` still sees the content's real age. Returns each
+/// document's outcome, in the order given.
+pub async fn backfill_titles(
+    docs: &[DocumentType],
+    cache: &dyn DocumentCache,
+    datatracker: &DataTrackerClient,
+) -> Vec<(DocumentType, Result<()>)> {
+    let names: Vec<String> = docs.iter().map(DocumentType::name).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let mut found = match datatracker.get_documents(&names).await {
+        Ok(found) => found,
+        Err(e) => {
+            let message = format!("{:#}", e);
+            return docs
+                .iter()
+                .map(|doc| (doc.clone(), Err(anyhow::anyhow!("{}", message))))
+                .collect();
+        }
+    };
+    docs.iter()
+        .map(|doc| {
+            let result = match found.remove(&doc.name()) {
+                Some(found) => {
+                    let mut metadata = cache
+                        .get_metadata(doc)
+                        .unwrap_or_else(|| CacheMetadata::new("", Utc::now()));
+                    metadata.merge_from_server(found.into());
+                    cache.store_metadata(doc, &metadata)
+                }
+                None => Err(anyhow::anyhow!("Document not found: {}", doc.name())),
+            };
+            (doc.clone(), result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RfcIndexEntry;
+    use chrono::TimeZone;
+
+    fn index() -> RfcIndex {
+        let mut index = RfcIndex::default();
+        index.entries.insert(
+            9000,
+            RfcIndexEntry {
+                number: 9000,
+                title: "QUIC".to_string(),
+                ..Default::default()
+            },
+        );
+        index.not_issued.insert(1849);
+        index.synced_at = Utc.with_ymd_and_hms(2026, 9, 30, 12, 0, 0).single();
+        index
+    }
+
+    #[test]
+    fn check_against_index_accepts_known_and_unindexed() {
+        let index = index();
+        assert!(check_against_index(&DocumentType::Rfc(9000), Some(&index), false).is_ok());
+        // Gaps below the highest number are left to the server.
+        assert!(check_against_index(&DocumentType::Rfc(100), Some(&index), false).is_ok());
+        // Without an index there's nothing to check against.
+        assert!(check_against_index(&DocumentType::Rfc(99999), None, false).is_ok());
+        // Nor when forced past a stale index.
+        assert!(check_against_index(&DocumentType::Rfc(99999), Some(&index), true).is_ok());
+        assert!(check_against_index(&DocumentType::Rfc(1849), Some(&index), true).is_ok());
+        let draft = DocumentType::Draft("draft-foo".to_string());
+        assert!(check_against_index(&draft, Some(&index), false).is_ok());
+    }
+
+    #[test]
+    fn check_against_index_rejects_not_issued_and_too_high() {
+        let index = index();
+        let err = check_against_index(&DocumentType::Rfc(1849), Some(&index), false).unwrap_err();
+        assert!(err.to_string().contains("never issued"));
+
+        let err = check_against_index(&DocumentType::Rfc(99999), Some(&index), false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "RFC 99999 has not been published; highest known is RFC 9000 as of 2026-09-30. \
+             Run 'rfc sync-index' if it's new, or pass --force-fetch"
+        );
+
+        let never_synced = RfcIndex {
+            synced_at: None,
+            ..index
+        };
+        let err =
+            check_against_index(&DocumentType::Rfc(9001), Some(&never_synced), false).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("RFC 9001 has not been published; highest known is RFC 9000. "));
+    }
+}
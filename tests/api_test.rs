@@ -0,0 +1,2142 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+
+use rfc::api::{
+    parse_rfc_index, Availability, DownloadObserver, FormatPreference, MirrorSettings,
+    ProxySettings, Rendering, RfcIndexDownload,
+};
+use rfc::models::{DraftState, Lineage, LineageLink, SearchOptions};
+use rfc::{
+    AuthorDocuments, BallotPosition, DataTrackerClient, DocumentFetcher, DocumentType,
+    FetchedDocument, Format, Position, QueryMode, SearchFilter,
+};
+use serde_json::json;
+use wiremock::matchers::{
+    header, header_exists, method, path, query_param, query_param_is_missing,
+};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+fn datatracker(server: &MockServer) -> DataTrackerClient {
+    DataTrackerClient::builder()
+        .base_url(server.uri())
+        .build()
+        .unwrap()
+}
+
+fn fetcher(server: &MockServer) -> DocumentFetcher {
+    DocumentFetcher::builder()
+        .base_url(server.uri())
+        .build()
+        .unwrap()
+}
+
+/// A plain-text body the fetcher takes for the document `header` names:
+/// `header`, then enough lines to pass as a whole document.
+fn document_text(header: &str) -> String {
+    format!(
+        "{}\n\n{}",
+        header,
+        "   Lorem ipsum dolor sit amet.\n".repeat(24)
+    )
+}
+
+#[tokio::test]
+async fn search_uses_configured_base_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "quic"))
+        .and(query_param("type__in", "rfc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 2, "next": null},
+            "objects": [
+                {"name": "rfc9000", "title": "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                 "abstract": "This document defines the core of the QUIC transport protocol.",
+                 "time": "2021-05-27T18:07:41Z"},
+                {"name": "rfc9001", "title": "Using TLS to Secure QUIC", "abstract": null}
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("quic", SearchFilter::RfcsOnly, 25)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.total_count, Some(2));
+    assert!(!results.has_more);
+    assert_eq!(results.documents[0].doc_type, DocumentType::Rfc(9000));
+    assert!(results.documents[0].published.is_some());
+    assert!(results.documents[0].abstract_text.is_some());
+    assert_eq!(results.documents[1].title, "Using TLS to Secure QUIC");
+}
+
+/// A Datatracker response recorded under `tests/fixtures/datatracker/`.
+fn fixture(name: &str) -> serde_json::Value {
+    let path = format!(
+        "{}/tests/fixtures/datatracker/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn search_keeps_only_rfcs_and_drafts_from_mixed_results() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "tls"))
+        .and(query_param("type__in", "rfc,draft"))
+        .and(query_param("limit", "75"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_mixed")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("TLS", SearchFilter::Both, 25)
+        .await
+        .unwrap();
+
+    let names: Vec<_> = results.documents.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "rfc8446",
+            "draft-ietf-tls-rfc8446bis",
+            "draft-example-tls13-notes"
+        ]
+    );
+    assert_eq!(results.documents[0].doc_type, DocumentType::Rfc(8446));
+    assert_eq!(
+        results.documents[1].doc_type,
+        DocumentType::Draft("draft-ietf-tls-rfc8446bis".to_string())
+    );
+    assert_eq!(
+        results.documents[1].published,
+        chrono::NaiveDate::from_ymd_opt(2024, 8, 30)
+    );
+    // A whitespace-only abstract counts as none.
+    assert_eq!(results.documents[2].abstract_text, None);
+    assert_eq!(results.total_count, Some(4));
+    assert!(!results.has_more);
+}
+
+#[tokio::test]
+async fn search_with_no_matches_is_empty_not_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_empty")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("xyzzy", SearchFilter::RfcsOnly, 25)
+        .await
+        .unwrap();
+    assert!(results.documents.is_empty());
+    assert_eq!(results.total_count, Some(0));
+    assert!(!results.has_more);
+}
+
+#[tokio::test]
+async fn search_reports_further_pages() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "transport"))
+        .and(query_param("abstract__icontains", "quic"))
+        .and(query_param("limit", "25"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_page")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("quic transport", SearchFilter::RfcsOnly, 10)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 10);
+    assert_eq!(results.documents[9].doc_type, DocumentType::Rfc(9009));
+    assert_eq!(results.total_count, Some(212));
+    assert!(results.has_more);
+}
+
+/// A search page of `names` (RFCs titled "QUIC ..."), saying a further
+/// page follows when `next` is.
+fn search_page_json(names: &[&str], next: Option<&str>) -> serde_json::Value {
+    let objects: Vec<_> = names
+        .iter()
+        .map(|name| json!({"name": name, "title": format!("QUIC {}", name), "abstract": null}))
+        .collect();
+    json!({"meta": {"total_count": 3, "next": next}, "objects": objects})
+}
+
+/// Two pages of results for `quic`: rfc9000 and a slide deck, then
+/// rfc9001 and rfc9002.
+async fn paged_search_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "quic"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(search_page_json(
+            &["rfc9000", "slides-110-quic"],
+            Some("/api/v1/doc/document/?limit=50&offset=2"),
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "quic"))
+        .and(query_param("offset", "2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(search_page_json(&["rfc9001", "rfc9002"], None)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn search_page_reports_where_the_next_page_starts() {
+    let server = paged_search_server().await;
+    let client = datatracker(&server);
+    let options = SearchOptions::new(SearchFilter::Both);
+
+    let first = client.search_page("quic", &options, 0).await.unwrap();
+    let names: Vec<&str> = first.documents.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["rfc9000"]);
+    assert_eq!(first.next_offset, Some(2));
+    assert_eq!(first.total_count, Some(3));
+
+    let second = client.search_page("quic", &options, 2).await.unwrap();
+    assert_eq!(second.documents.len(), 2);
+    assert_eq!(second.next_offset, None);
+}
+
+#[tokio::test]
+async fn search_stream_fetches_pages_as_they_are_read() {
+    use futures::StreamExt;
+
+    let server = paged_search_server().await;
+    let client = datatracker(&server);
+    let options = SearchOptions::new(SearchFilter::Both);
+    let requests = || async { server.received_requests().await.unwrap().len() };
+
+    let mut stream = std::pin::pin!(client.search_stream("quic", &options));
+    assert_eq!(requests().await, 0);
+    assert_eq!(stream.next().await.unwrap().unwrap().name, "rfc9000");
+    assert_eq!(requests().await, 1);
+    assert_eq!(stream.next().await.unwrap().unwrap().name, "rfc9001");
+    assert_eq!(requests().await, 2);
+    assert_eq!(stream.next().await.unwrap().unwrap().name, "rfc9002");
+    assert!(stream.next().await.is_none());
+    assert_eq!(requests().await, 2);
+}
+
+#[tokio::test]
+async fn search_stream_ends_with_the_error() {
+    use futures::StreamExt;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let client = datatracker(&server);
+    let options = SearchOptions::new(SearchFilter::Both);
+    let results: Vec<_> = client.search_stream("quic", &options).collect().await;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].as_ref().unwrap_err().to_string().contains("500"));
+}
+
+#[tokio::test]
+async fn keyword_searches_filter_by_tag_and_report_the_tags() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "http"))
+        .and(query_param("tags__slug", "errata"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_tagged")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = SearchOptions::new(SearchFilter::RfcsOnly).with_keyword(Some("Errata".into()));
+    let results = datatracker(&server)
+        .search_with_options("http", &options, 25)
+        .await
+        .unwrap();
+    assert_eq!(results.keyword.as_deref(), Some("errata"));
+    assert_eq!(results.documents[0].keywords, ["errata", "verified-errata"]);
+    assert_eq!(results.documents[1].keywords, ["errata"]);
+
+    let json = serde_json::to_value(&results).unwrap();
+    assert_eq!(json["keyword"], "errata");
+    assert_eq!(json["documents"][1]["keywords"], json!(["errata"]));
+}
+
+#[tokio::test]
+async fn search_reports_each_drafts_state() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("type__in", "draft"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_drafts")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/"))
+        .and(query_param("type", "draft"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("draft_states")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("quic", SearchFilter::DraftsOnly, 25)
+        .await
+        .unwrap();
+    let states: Vec<_> = results
+        .documents
+        .iter()
+        .map(|d| (d.name.as_str(), d.draft_state))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            ("draft-ietf-quic-multipath-14", Some(DraftState::Active)),
+            ("draft-huitema-quic-ts-08", Some(DraftState::Expired)),
+            ("draft-ietf-quic-transport-34", Some(DraftState::Rfc)),
+            (
+                "draft-deconinck-quic-multipath-07",
+                Some(DraftState::Replaced)
+            ),
+            ("draft-pauly-quic-datagram-05", Some(DraftState::Withdrawn)),
+        ]
+    );
+    assert_eq!(
+        results.documents[0].expires,
+        NaiveDate::from_ymd_opt(2025, 10, 12)
+    );
+    assert_eq!(results.documents[2].expires, None);
+}
+
+#[tokio::test]
+async fn search_without_draft_states_still_lists_drafts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_drafts")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let results = datatracker(&server)
+        .search("quic", SearchFilter::DraftsOnly, 25)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 5);
+    assert!(results.documents.iter().all(|d| d.draft_state.is_none()));
+}
+
+#[tokio::test]
+async fn search_encodes_reserved_and_non_ascii_characters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", "国际化域名"))
+        .and(query_param("abstract__icontains", "a=b&c d"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_empty")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    datatracker(&server)
+        .search("\"a=b&c d\" 国际化域名", SearchFilter::Both, 25)
+        .await
+        .unwrap();
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests[0].url.query(),
+        Some(
+            "title__icontains=%E5%9B%BD%E9%99%85%E5%8C%96%E5%9F%9F%E5%90%8D\
+             &type__in=rfc%2Cdraft&limit=25&abstract__icontains=a%3Db%26c+d&format=json"
+        )
+    );
+}
+
+#[tokio::test]
+async fn search_rejects_overlong_queries_without_asking() {
+    let server = MockServer::start().await;
+    let err = datatracker(&server)
+        .search(&"quic ".repeat(120), SearchFilter::Both, 25)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("at most 512"), "{}", err);
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn get_documents_batches_names_and_leaves_out_unknown_ones() {
+    let server = MockServer::start().await;
+    // Echo back every requested RFC except rfc10, rfc20, ...
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .respond_with(|request: &Request| {
+            let (_, names) = request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == "name__in")
+                .unwrap();
+            let objects: Vec<_> = names
+                .split(',')
+                .filter(|name| !name.ends_with('0'))
+                .map(|name| json!({"name": name, "title": name.to_uppercase(), "abstract": null}))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(json!({ "objects": objects }))
+        })
+        .expect(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .and(query_param("relationship__slug", "obs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {
+                    "source": "/api/v1/doc/document/rfc9110/",
+                    "target": "/api/v1/doc/document/rfc101/"
+                },
+                {
+                    "source": "/api/v1/doc/document/rfc7230/",
+                    "target": "/api/v1/doc/document/rfc101/"
+                }
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let names: Vec<String> = (1..=150).map(|n| format!("rfc{}", n)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let documents = datatracker(&server).get_documents(&names).await.unwrap();
+
+    assert_eq!(documents.len(), 135);
+    assert!(!documents.contains_key("rfc10"));
+    assert_eq!(documents["rfc149"].title, "RFC149");
+    assert_eq!(documents["rfc101"].obsoleted_by, [7230, 9110]);
+    assert!(documents["rfc102"].obsoleted_by.is_empty());
+
+    let batches: Vec<usize> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/api/v1/doc/document/")
+        .map(|request| {
+            let (_, names) = request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == "name__in")
+                .unwrap();
+            names.split(',').count()
+        })
+        .collect();
+    assert_eq!(batches, [50, 50, 50]);
+}
+
+#[tokio::test]
+async fn get_documents_fails_when_a_batch_does() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    let err = datatracker(&server)
+        .get_documents(&["draft-ietf-quic-qpack"])
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Failed to fetch document metadata"),
+        "{:#}",
+        err
+    );
+}
+
+/// Answer `documentauthor` lookups for `person` with one record per
+/// document in `documents`.
+async fn mount_authorship(server: &MockServer, person: u64, documents: &[&str]) {
+    let objects: Vec<_> = documents
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            json!({
+                "id": person * 100 + i as u64,
+                "document": format!("/api/v1/doc/document/{}/", name),
+                "person": format!("/api/v1/person/person/{}/", person),
+                "affiliation": if i == documents.len() - 1 { "Fastly" } else { "Akamai" },
+                "email": format!("/api/v1/person/email/person{}@example.com/", person)
+            })
+        })
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/documentauthor/"))
+        .and(query_param("person", person.to_string()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "objects": objects })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn documents_by_author_lists_their_documents_newest_first() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/person/person/"))
+        .and(query_param("name__icontains", "Mark Nottingham"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {"id": 12345, "name": "Mark Nottingham", "ascii": "Mark Nottingham"},
+                {"id": 777, "name": "Mark Nottinghamshire", "ascii": "Mark Nottinghamshire"}
+            ]
+        })))
+        .mount(&server)
+        .await;
+    mount_authorship(
+        &server,
+        12345,
+        &[
+            "rfc7234",
+            "charter-ietf-httpbis",
+            "draft-nottingham-old",
+            "rfc9110",
+        ],
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param(
+            "name__in",
+            "rfc7234,draft-nottingham-old,rfc9110",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {"name": "rfc7234", "title": "HTTP/1.1: Caching", "abstract": null,
+                 "time": "2014-06-07T00:00:00"},
+                {"name": "rfc9110", "title": "HTTP Semantics", "abstract": null,
+                 "time": "2022-06-06T00:00:00"},
+                {"name": "draft-nottingham-old", "title": "Old Ideas", "abstract": null,
+                 "time": "2019-01-02T00:00:00", "states": ["/api/v1/doc/state/2/"]}
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("draft_states")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .and(query_param("target__name__in", "rfc7234,rfc9110"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [{
+                "source": "/api/v1/doc/document/rfc9111/",
+                "target": "/api/v1/doc/document/rfc7234/"
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let found = datatracker(&server)
+        .documents_by_author(" Mark Nottingham ")
+        .await
+        .unwrap();
+    let AuthorDocuments::Found { person, documents } = found else {
+        panic!("{:?}", found);
+    };
+    assert_eq!(person.id, 12345);
+    assert_eq!(person.affiliation.as_deref(), Some("Fastly"));
+    assert_eq!(person.email.as_deref(), Some("person12345@example.com"));
+    let names: Vec<&str> = documents.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["rfc9110", "draft-nottingham-old", "rfc7234"]);
+    assert_eq!(documents[1].draft_state, Some(DraftState::Expired));
+    assert_eq!(documents[2].obsoleted_by, [9111]);
+}
+
+#[tokio::test]
+async fn ambiguous_authors_come_back_as_candidates() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/person/person/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {"id": 1, "name": "Jane Smith", "ascii": "Jane Smith"},
+                {"id": 2, "name": "Jane Smithers", "ascii": "Jane Smithers"}
+            ]
+        })))
+        .mount(&server)
+        .await;
+    mount_authorship(&server, 1, &["rfc9999"]).await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/documentauthor/"))
+        .and(query_param("person", "2"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let found = datatracker(&server)
+        .documents_by_author("jane smi")
+        .await
+        .unwrap();
+    let AuthorDocuments::Ambiguous(people) = found else {
+        panic!("{:?}", found);
+    };
+    assert_eq!(people.len(), 2);
+    assert_eq!(people[0].hint(), "Fastly, person1@example.com");
+    // A failed hint lookup still lists the person.
+    assert_eq!(people[1].name, "Jane Smithers");
+    assert_eq!(people[1].hint(), "");
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .all(|r| r.url.path() != "/api/v1/doc/document/"));
+}
+
+#[tokio::test]
+async fn authors_can_be_named_by_person_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/person/person/2/"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"id": 2, "name": "Jane Smithers", "ascii": ""})),
+        )
+        .mount(&server)
+        .await;
+    mount_authorship(&server, 2, &[]).await;
+
+    let found = datatracker(&server).documents_by_author("2").await.unwrap();
+    let AuthorDocuments::Found { person, documents } = found else {
+        panic!("{:?}", found);
+    };
+    assert_eq!(person.name, "Jane Smithers");
+    assert!(documents.is_empty());
+
+    let err = datatracker(&server)
+        .documents_by_author("3")
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("No one in the Datatracker"),
+        "{}",
+        err
+    );
+}
+
+/// Serve the `search_communities` fixture to requests with these title
+/// and abstract filters (`None`: no abstract filter).
+async fn communities_server(title: &str, abstract_token: Option<&str>) -> MockServer {
+    let server = MockServer::start().await;
+    let mock = Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("title__icontains", title));
+    let mock = match abstract_token {
+        Some(token) => mock.and(query_param("abstract__icontains", token)),
+        None => mock.and(query_param_is_missing("abstract__icontains")),
+    };
+    mock.respond_with(ResponseTemplate::new(200).set_body_json(fixture("search_communities")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn names(results: &rfc::SearchResult) -> Vec<&str> {
+    results.documents.iter().map(|d| d.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn search_requires_every_word_of_a_multi_word_query() {
+    let server = communities_server("communities", Some("attribute")).await;
+    let results = datatracker(&server)
+        .search("BGP communities attribute", SearchFilter::Both, 25)
+        .await
+        .unwrap();
+    // "bgp" went unsent, so the registry draft, which never mentions it, is
+    // dropped locally.
+    assert_eq!(
+        names(&results),
+        [
+            "rfc1997",
+            "rfc4360",
+            "rfc8092",
+            "draft-ietf-grow-large-communities-usage"
+        ]
+    );
+    assert_eq!(results.total_count, None);
+}
+
+#[tokio::test]
+async fn search_sends_a_quoted_phrase_whole() {
+    let server = communities_server("bgp communities", None).await;
+    let results = datatracker(&server)
+        .search("\"BGP Communities\"", SearchFilter::Both, 25)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results.total_count, Some(5));
+
+    let server = communities_server("bgp communities", None).await;
+    let options = rfc::models::SearchOptions::default().with_mode(QueryMode::Phrase);
+    let results = datatracker(&server)
+        .search_with_options("BGP  Communities", &options, 25)
+        .await
+        .unwrap();
+    assert_eq!(results.mode, QueryMode::Phrase);
+}
+
+#[tokio::test]
+async fn search_mixes_phrases_and_words() {
+    let server = communities_server("large communities", Some("bgp")).await;
+    let results = datatracker(&server)
+        .search("\"large communities\" bgp use", SearchFilter::Both, 25)
+        .await
+        .unwrap();
+    // Words match as substrings, so "use" finds RFC 1997's "may be used".
+    assert_eq!(
+        names(&results),
+        ["rfc1997", "draft-ietf-grow-large-communities-usage"]
+    );
+}
+
+#[tokio::test]
+async fn get_document_reads_draft_metadata() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/draft-ietf-quic-transport/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("draft_quic_transport")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let doc = datatracker(&server)
+        .get_document("draft-ietf-quic-transport")
+        .await
+        .unwrap();
+    assert_eq!(
+        doc.title,
+        "QUIC: A UDP-Based Multiplexed and Secure Transport"
+    );
+    assert_eq!(
+        doc.doc_type,
+        DocumentType::Draft("draft-ietf-quic-transport".to_string())
+    );
+    assert_eq!(doc.published, chrono::NaiveDate::from_ymd_opt(2021, 1, 14));
+    assert!(doc
+        .abstract_text
+        .unwrap()
+        .starts_with("This document defines the core of the QUIC transport protocol."));
+}
+
+#[tokio::test]
+async fn get_document_uses_configured_base_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc4271/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc4271",
+            "title": "A Border Gateway Protocol 4 (BGP-4)",
+            "abstract": "This document discusses the Border Gateway Protocol (BGP).",
+            "time": "2006-01-31T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+
+    let doc = datatracker(&server).get_document("rfc4271").await.unwrap();
+    assert_eq!(doc.title, "A Border Gateway Protocol 4 (BGP-4)");
+    assert_eq!(doc.doc_type, DocumentType::Rfc(4271));
+}
+
+#[tokio::test]
+async fn fetcher_datatracker_reuses_http_client() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .and(header("x-client-id", "shared"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc9000",
+            "title": "QUIC",
+            "abstract": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // A default header only the injected client sends marks its requests.
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-client-id", "shared".parse().unwrap());
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap();
+    let fetcher = DocumentFetcher::builder()
+        .base_url(server.uri())
+        .client(client)
+        .build()
+        .unwrap();
+
+    let doc = fetcher.datatracker().get_document("rfc9000").await.unwrap();
+    assert_eq!(doc.title, "QUIC");
+}
+
+#[tokio::test]
+async fn get_document_not_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let err = datatracker(&server)
+        .get_document("rfc99999")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rfc99999"));
+}
+
+#[tokio::test]
+async fn document_details_resolve_states_and_relationships() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/draft-ietf-quic-transport/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "draft-ietf-quic-transport",
+            "title": "QUIC: A UDP-Based Multiplexed and Secure Transport",
+            "abstract": "This document defines the core of the QUIC transport protocol.",
+            "time": "2021-01-15T00:00:00Z",
+            "rev": "34",
+            "expires": "2021-07-19T00:00:00Z",
+            "states": [
+                "/api/v1/doc/state/3/",
+                "/api/v1/doc/state/7/",
+                "/api/v1/doc/state/38/"
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/3/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "RFC", "slug": "rfc", "type": "/api/v1/doc/statetype/draft/"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/7/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "RFC Published", "slug": "pub", "type": "/api/v1/doc/statetype/draft-iesg/"
+        })))
+        .mount(&server)
+        .await;
+    // The WG state lookup fails; everything else still comes through.
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/state/38/"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .and(query_param("relationship__slug", "became_rfc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [{
+                "source": "/api/v1/doc/document/draft-ietf-quic-transport/",
+                "target": "/api/v1/doc/document/rfc9000/"
+            }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .and(query_param("relationship__slug", "replaces"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"objects": []})))
+        .mount(&server)
+        .await;
+
+    let details = datatracker(&server)
+        .get_document_details("draft-ietf-quic-transport")
+        .await
+        .unwrap();
+    assert_eq!(
+        details.document.title,
+        "QUIC: A UDP-Based Multiplexed and Secure Transport"
+    );
+    assert_eq!(details.rev.as_deref(), Some("34"));
+    assert_eq!(details.state.as_deref(), Some("RFC"));
+    assert_eq!(details.iesg_state.as_deref(), Some("RFC Published"));
+    assert_eq!(details.wg_state, None);
+    assert_eq!(details.rfc_number, Some(9000));
+    assert!(details.replaced_by.is_empty());
+    assert!(!details.is_active());
+}
+
+#[tokio::test]
+async fn document_details_for_rfcs_need_one_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc9000",
+            "title": "QUIC",
+            "abstract": null,
+            "states": ["/api/v1/doc/state/1/"],
+            "rfc_number": 9000
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let details = datatracker(&server)
+        .get_document_details("rfc9000")
+        .await
+        .unwrap();
+    assert_eq!(details.rfc_number, Some(9000));
+    assert_eq!(details.state, None);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+/// A Datatracker knowing that draft-hamilton-quic-transport-protocol was
+/// replaced by draft-ietf-quic-transport, which became RFC 9000.
+async fn quic_lineage_server() -> MockServer {
+    let server = MockServer::start().await;
+    for (name, rev, time) in [
+        (
+            "draft-hamilton-quic-transport-protocol",
+            Some("01"),
+            "2016-07-08T00:00:00Z",
+        ),
+        (
+            "draft-ietf-quic-transport",
+            Some("34"),
+            "2021-01-14T23:27:21.528Z",
+        ),
+        ("rfc9000", None, "2021-05-27T18:07:41Z"),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/doc/document/{}/", name)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": name,
+                "title": "QUIC",
+                "abstract": null,
+                "rev": rev,
+                "time": time
+            })))
+            .mount(&server)
+            .await;
+    }
+    let edge = |source: &str, target: &str| {
+        json!({"objects": [{
+            "source": format!("/api/v1/doc/document/{}/", source),
+            "target": format!("/api/v1/doc/document/{}/", target)
+        }]})
+    };
+    for (side, name, slug, body) in [
+        (
+            "target__name",
+            "rfc9000",
+            "became_rfc",
+            edge("draft-ietf-quic-transport", "rfc9000"),
+        ),
+        (
+            "source__name",
+            "draft-ietf-quic-transport",
+            "became_rfc",
+            edge("draft-ietf-quic-transport", "rfc9000"),
+        ),
+        (
+            "source__name",
+            "draft-ietf-quic-transport",
+            "replaces",
+            edge(
+                "draft-ietf-quic-transport",
+                "draft-hamilton-quic-transport-protocol",
+            ),
+        ),
+        (
+            "target__name",
+            "draft-hamilton-quic-transport-protocol",
+            "replaces",
+            edge(
+                "draft-ietf-quic-transport",
+                "draft-hamilton-quic-transport-protocol",
+            ),
+        ),
+    ] {
+        Mock::given(method("GET"))
+            .and(path("/api/v1/doc/relateddocument/"))
+            .and(query_param(side, name))
+            .and(query_param("relationship__slug", slug))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&server)
+            .await;
+    }
+    // Every other relationship query finds nothing.
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"objects": []})))
+        .with_priority(10)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn lineage_follows_replacements_and_publication_both_ways() {
+    let server = quic_lineage_server().await;
+    let client = datatracker(&server);
+    let steps = |lineage: &Lineage| -> Vec<(String, Option<LineageLink>)> {
+        lineage
+            .hops
+            .iter()
+            .map(|hop| (hop.label(), hop.next))
+            .collect()
+    };
+    let expected = vec![
+        (
+            "draft-hamilton-quic-transport-protocol-01".to_string(),
+            Some(LineageLink::ReplacedBy),
+        ),
+        (
+            "draft-ietf-quic-transport-34".to_string(),
+            Some(LineageLink::PublishedAs),
+        ),
+        ("RFC 9000".to_string(), None),
+    ];
+
+    let from_rfc = client.lineage("rfc9000").await.unwrap();
+    assert_eq!(steps(&from_rfc), expected);
+    assert_eq!(from_rfc.hops[1].date, NaiveDate::from_ymd_opt(2021, 1, 14));
+    assert_eq!(from_rfc.rfc(), Some(9000));
+
+    let from_first_draft = client
+        .lineage("draft-hamilton-quic-transport-protocol")
+        .await
+        .unwrap();
+    assert_eq!(from_first_draft, from_rfc);
+
+    assert!(client.lineage("draft-unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn document_events_page_only_as_far_as_the_limit() {
+    let server = MockServer::start().await;
+    let events = |count: usize, start: usize| -> Vec<serde_json::Value> {
+        (start..start + count)
+            .map(|i| {
+                json!({
+                    "time": format!("2021-05-{:02}T12:00:00", 28 - i % 28),
+                    "type": "new_revision",
+                    "desc": format!("New version {}", i),
+                    "rev": format!("{:02}", i)
+                })
+            })
+            .collect()
+    };
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/docevent/"))
+        .and(query_param("doc__name", "draft-ietf-quic-transport"))
+        .and(query_param("order_by", "-time"))
+        .and(query_param("offset", "100"))
+        .and(query_param("limit", "30"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 5000,
+                     "next": "/api/v1/doc/docevent/?doc__name=draft-ietf-quic-transport&order_by=-time&limit=30&offset=130"},
+            "objects": events(30, 100)
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/docevent/"))
+        .and(query_param("limit", "100"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 5000,
+                     "next": "/api/v1/doc/docevent/?doc__name=draft-ietf-quic-transport&order_by=-time&limit=100&offset=100"},
+            "objects": events(100, 0)
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let events = datatracker(&server)
+        .document_events("draft-ietf-quic-transport", 130)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 130);
+    assert_eq!(events[0].description, "New version 0");
+    assert_eq!(events[129].rev.as_deref(), Some("129"));
+}
+
+#[tokio::test]
+async fn ballot_keeps_each_balloters_latest_position() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/ballotdocevent/"))
+        .and(query_param("doc__name", "draft-ietf-foo-bar"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 1, "next": null},
+            "objects": [{
+                "id": 1033512,
+                "time": "2023-03-01T08:12:43Z",
+                "type": "created_ballot",
+                "ballot_type": "/api/v1/doc/ballottype/2/",
+                "doc": "/api/v1/doc/document/draft-ietf-foo-bar/",
+                "desc": "Created \"Approve\" ballot",
+                "resource_uri": "/api/v1/doc/ballotdocevent/1033512/"
+            }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/ballotpositiondocevent/"))
+        .and(query_param("ballot", "1033512"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 4, "next": null},
+            "objects": [
+                {
+                    "id": 1034101,
+                    "time": "2023-03-15T17:02:11Z",
+                    "type": "changed_ballot_position",
+                    "balloter": "/api/v1/person/person/21684/",
+                    "pos": "/api/v1/doc/ballotpositionname/noobj/",
+                    "discuss": "",
+                    "comment": "Thanks for addressing my DISCUSS."
+                },
+                {
+                    "id": 1033900,
+                    "time": "2023-03-14T09:40:00Z",
+                    "type": "changed_ballot_position",
+                    "balloter": "/api/v1/person/person/106812/",
+                    "pos": "/api/v1/doc/ballotpositionname/discuss/",
+                    "discuss": "<p>Section 4 doesn&#x27;t say what happens on timeout.</p>",
+                    "comment": ""
+                },
+                {
+                    "id": 1033700,
+                    "time": "2023-03-10T11:00:00Z",
+                    "type": "changed_ballot_position",
+                    "balloter": "/api/v1/person/person/21684/",
+                    "pos": "/api/v1/doc/ballotpositionname/discuss/",
+                    "discuss": "Needs a security considerations section.",
+                    "comment": ""
+                },
+                {
+                    "id": 1033513,
+                    "time": "2023-03-01T08:12:43Z",
+                    "type": "changed_ballot_position",
+                    "balloter": "/api/v1/person/person/999/",
+                    "pos": "/api/v1/doc/ballotpositionname/yes/",
+                    "discuss": "",
+                    "comment": ""
+                }
+            ]
+        })))
+        .mount(&server)
+        .await;
+    for (id, name) in [("21684", "Roman Danyliw"), ("106812", "Erik Kline")] {
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/person/person/{}/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": id.parse::<u64>().unwrap(),
+                "name": name,
+                "resource_uri": format!("/api/v1/person/person/{}/", id)
+            })))
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/api/v1/person/person/999/"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let ballot = datatracker(&server)
+        .ballot("draft-ietf-foo-bar")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ballot.opened.to_rfc3339(), "2023-03-01T08:12:43+00:00");
+    assert_eq!(ballot.closed, None);
+    assert_eq!(
+        ballot.positions,
+        vec![
+            BallotPosition {
+                balloter: "Erik Kline".to_string(),
+                position: Position::Discuss,
+                discuss: Some("Section 4 doesn't say what happens on timeout.".to_string()),
+            },
+            BallotPosition {
+                balloter: "person 999".to_string(),
+                position: Position::Yes,
+                discuss: None,
+            },
+            BallotPosition {
+                balloter: "Roman Danyliw".to_string(),
+                position: Position::NoObjection,
+                discuss: None,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn ballot_is_none_without_a_ballot() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/ballotdocevent/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 0, "next": null},
+            "objects": []
+        })))
+        .mount(&server)
+        .await;
+
+    let ballot = datatracker(&server)
+        .ballot("draft-someone-idea")
+        .await
+        .unwrap();
+    assert!(ballot.is_none());
+}
+
+#[tokio::test]
+async fn fetch_prefers_text() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(document_text("RFC 9000 plain text")),
+        )
+        .mount(&server)
+        .await;
+
+    let FetchedDocument {
+        content, format, ..
+    } = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(format, Format::Text);
+    assert_eq!(content, document_text("RFC 9000 plain text"));
+}
+
+#[tokio::test]
+async fn fetch_falls_back_to_html() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>RFC 9000</html>"))
+        .mount(&server)
+        .await;
+
+    let FetchedDocument {
+        content, format, ..
+    } = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(format, Format::Html);
+    assert_eq!(content, "<html>RFC 9000</html>");
+}
+
+#[tokio::test]
+async fn fetch_follows_format_preference() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>RFC 9000</html>"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(document_text("RFC 9000 plain text")),
+        )
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let fetcher = DocumentFetcher::builder()
+        .base_url(server.uri())
+        .format_preference(FormatPreference {
+            order: vec![Rendering::Xml, Rendering::Html, Rendering::Text],
+        })
+        .build()
+        .unwrap();
+    let fetched = fetcher.fetch(&DocumentType::Rfc(9000)).await.unwrap();
+    assert_eq!(fetched.format, Format::Html);
+}
+
+#[tokio::test]
+async fn probe_formats_reports_each_rendering() {
+    let server = MockServer::start().await;
+    for (file, status) in [
+        ("rfc9000.txt", 200),
+        ("rfc9000.html", 200),
+        ("rfc9000.xml", 404),
+        ("rfc9000.pdf", 500),
+    ] {
+        Mock::given(method("HEAD"))
+            .and(path(format!("/rfc/{}", file)))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+    }
+
+    let probes = fetcher(&server)
+        .probe_formats(&DocumentType::Rfc(9000))
+        .await;
+    let found: Vec<_> = probes
+        .iter()
+        .map(|p| (p.rendering, p.availability))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (Rendering::Text, Availability::Available),
+            (Rendering::Html, Availability::Available),
+            (Rendering::Xml, Availability::Missing),
+            (Rendering::Pdf, Availability::Unknown),
+        ]
+    );
+    assert_eq!(probes[2].url, format!("{}/rfc/rfc9000.xml", server.uri()));
+}
+
+#[tokio::test]
+async fn probe_formats_degrades_when_the_draft_cannot_be_resolved() {
+    let server = MockServer::start().await;
+    let probes = fetcher(&server)
+        .probe_formats(&DocumentType::Draft("draft-foo-bar".to_string()))
+        .await;
+    assert_eq!(probes.len(), 4);
+    assert!(probes
+        .iter()
+        .all(|p| p.availability == Availability::Unknown));
+}
+
+fn mirrored_fetcher(primary: &str, fallbacks: &[&MockServer]) -> DocumentFetcher {
+    DocumentFetcher::builder()
+        .base_url(primary)
+        .mirrors(MirrorSettings {
+            fallbacks: fallbacks.iter().map(|s| s.uri()).collect(),
+            forced: None,
+        })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn fetch_falls_over_to_mirror_on_server_error() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&primary)
+        .await;
+    let mirror = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(document_text("RFC 9000 from mirror")),
+        )
+        .expect(1)
+        .mount(&mirror)
+        .await;
+
+    let fetched = mirrored_fetcher(&primary.uri(), &[&mirror])
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(fetched.content, document_text("RFC 9000 from mirror"));
+    assert_eq!(fetched.format, Format::Text);
+    assert_eq!(fetched.mirror, Some(mirror.uri()));
+    assert_eq!(
+        fetched.source_url,
+        format!("{}/rfc/rfc9000.txt", mirror.uri())
+    );
+    assert_eq!(fetched.provenance().mirror, Some(mirror.uri()));
+}
+
+#[tokio::test]
+async fn fetch_falls_over_to_mirror_when_unreachable() {
+    let mirror = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(document_text("RFC 9000 from mirror")),
+        )
+        .mount(&mirror)
+        .await;
+
+    // Nothing listens on port 1.
+    let fetched = mirrored_fetcher("http://127.0.0.1:1", &[&mirror])
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(fetched.content, document_text("RFC 9000 from mirror"));
+    assert_eq!(fetched.mirror, Some(mirror.uri()));
+}
+
+#[tokio::test]
+async fn fetch_does_not_ask_mirrors_after_not_found() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&primary)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<p>QUIC</p>"))
+        .mount(&primary)
+        .await;
+    let mirror = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("mirror"))
+        .expect(0)
+        .mount(&mirror)
+        .await;
+
+    let fetched = mirrored_fetcher(&primary.uri(), &[&mirror])
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(fetched.format, Format::Html);
+    assert_eq!(fetched.mirror, None);
+}
+
+#[tokio::test]
+async fn fetch_reports_last_error_when_every_mirror_fails() {
+    let primary = MockServer::start().await;
+    let mirror = MockServer::start().await;
+    for server in [&primary, &mirror] {
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .mount(server)
+            .await;
+    }
+
+    let err = mirrored_fetcher(&primary.uri(), &[&mirror])
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains(&mirror.uri()), "{}", message);
+    assert!(message.contains("502"), "{}", message);
+}
+
+#[tokio::test]
+async fn forced_mirror_skips_the_usual_hosts() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("primary"))
+        .expect(0)
+        .mount(&primary)
+        .await;
+    let mirror = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/archive/id/draft-foo-01.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(document_text("draft-foo-01 from mirror")),
+        )
+        .mount(&mirror)
+        .await;
+
+    let fetched = DocumentFetcher::builder()
+        .base_url(primary.uri())
+        .mirrors(MirrorSettings::none().with_forced(Some(format!("{}/", mirror.uri()))))
+        .build()
+        .unwrap()
+        .fetch(&DocumentType::Draft("draft-foo-01".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(fetched.content, document_text("draft-foo-01 from mirror"));
+    assert_eq!(fetched.mirror, Some(mirror.uri()));
+}
+
+#[tokio::test]
+async fn fetch_reports_source_url_and_validators() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"9000-v1\"")
+                .insert_header("Last-Modified", "Thu, 27 May 2021 18:00:00 GMT")
+                .set_body_string("<p>QUIC</p>"),
+        )
+        .mount(&server)
+        .await;
+
+    let before = chrono::Utc::now();
+    let fetched = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+    assert_eq!(
+        fetched.source_url,
+        format!("{}/rfc/rfc9000.html", server.uri())
+    );
+    assert!(fetched.retrieved_at >= before);
+    assert_eq!(fetched.etag.as_deref(), Some("\"9000-v1\""));
+    assert_eq!(
+        fetched.last_modified.unwrap().to_rfc3339(),
+        "2021-05-27T18:00:00+00:00"
+    );
+    let provenance = fetched.provenance();
+    assert_eq!(provenance.format, Format::Html);
+    assert_eq!(provenance.source_url, fetched.source_url);
+}
+
+#[tokio::test]
+async fn fetch_falls_back_to_draft_html() {
+    let server = MockServer::start().await;
+    let html = include_str!("fixtures/draft-ietf-quic-transport-34.html");
+    Mock::given(method("GET"))
+        .and(path("/archive/id/draft-ietf-quic-transport-34.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/doc/html/draft-ietf-quic-transport-34"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(html))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let draft = DocumentType::Draft("draft-ietf-quic-transport-34".to_string());
+    let FetchedDocument {
+        content, format, ..
+    } = fetcher(&server).fetch(&draft).await.unwrap();
+    assert_eq!(format, Format::Html);
+    assert_eq!(content, html);
+}
+
+#[tokio::test]
+async fn fetch_resolves_unversioned_draft() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc/draft-ietf-quic-transport/doc.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"rev": "34"})))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/archive/id/draft-ietf-quic-transport-34.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(document_text("draft-ietf-quic-transport-34")),
+        )
+        .mount(&server)
+        .await;
+
+    let draft = DocumentType::Draft("draft-ietf-quic-transport".to_string());
+    let FetchedDocument {
+        content, format, ..
+    } = fetcher(&server).fetch(&draft).await.unwrap();
+    assert_eq!(format, Format::Text);
+    assert_eq!(content, document_text("draft-ietf-quic-transport-34"));
+}
+
+#[tokio::test]
+async fn fetch_rfc_index_records_last_modified() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc-index.xml"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Last-Modified", "Wed, 01 Oct 2025 00:00:00 GMT")
+                .set_body_string(
+                    "<rfc-index><rfc-entry><doc-id>RFC9000</doc-id>\
+                     <title>QUIC</title></rfc-entry></rfc-index>",
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let RfcIndexDownload::Updated { xml, last_modified } =
+        fetcher(&server).fetch_rfc_index(None).await.unwrap()
+    else {
+        panic!("expected a full download");
+    };
+    assert_eq!(
+        last_modified.as_deref(),
+        Some("Wed, 01 Oct 2025 00:00:00 GMT")
+    );
+    assert_eq!(parse_rfc_index(&xml).unwrap().entries[&9000].title, "QUIC");
+}
+
+#[tokio::test]
+async fn fetch_rfc_index_is_conditional() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc-index.xml"))
+        // `header()` splits values on commas, which HTTP dates contain.
+        .and(header_exists("If-Modified-Since"))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let download = fetcher(&server)
+        .fetch_rfc_index(Some("Wed, 01 Oct 2025 00:00:00 GMT"))
+        .await
+        .unwrap();
+    assert!(matches!(download, RfcIndexDownload::NotModified));
+}
+
+#[tokio::test]
+async fn fetch_if_modified_returns_none_on_304() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .and(header_exists("If-Modified-Since"))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fetched = fetcher(&server)
+        .fetch_if_modified(&DocumentType::Rfc(9000), Some(chrono::Utc::now()))
+        .await
+        .unwrap();
+    assert!(fetched.is_none());
+}
+
+#[tokio::test]
+async fn retries_after_429_with_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc9000",
+            "title": "QUIC",
+            "abstract": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let start = std::time::Instant::now();
+    let doc = datatracker(&server).get_document("rfc9000").await.unwrap();
+    assert_eq!(doc.title, "QUIC");
+    assert!(start.elapsed() >= std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn gives_up_when_retry_after_exceeds_cap() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("429"), "{:#}", err);
+}
+
+/// Records what a `DownloadObserver` was told.
+#[derive(Default)]
+struct Recorder {
+    total: Mutex<Option<Option<u64>>>,
+    bytes: AtomicU64,
+    finished: AtomicBool,
+}
+
+impl DownloadObserver for Recorder {
+    fn started(&self, _url: &str, total: Option<u64>) {
+        *self.total.lock().unwrap() = Some(total);
+    }
+    fn advanced(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::SeqCst);
+    }
+    fn finished(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn fetch_reports_download_progress() {
+    let server = MockServer::start().await;
+    let body = format!("Request for Comments: 9000\n{}", "x".repeat(64 * 1024));
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body.clone()))
+        .mount(&server)
+        .await;
+
+    let recorder = Arc::new(Recorder::default());
+    let FetchedDocument { content, .. } = fetcher(&server)
+        .with_observer(recorder.clone())
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+
+    assert_eq!(content, body);
+    assert_eq!(
+        *recorder.total.lock().unwrap(),
+        Some(Some(body.len() as u64))
+    );
+    assert_eq!(recorder.bytes.load(Ordering::SeqCst), body.len() as u64);
+    assert!(recorder.finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn fetch_to_streams_into_file() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.html"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>RFC 9000</html>"))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000");
+    let format = fetcher(&server)
+        .fetch_to(&DocumentType::Rfc(9000), &dest)
+        .await
+        .unwrap();
+
+    assert_eq!(format, Format::Html);
+    assert_eq!(
+        std::fs::read_to_string(&dest).unwrap(),
+        "<html>RFC 9000</html>"
+    );
+    assert!(!dir.path().join("rfc9000.part").exists());
+}
+
+#[tokio::test]
+async fn fetch_to_leaves_nothing_on_failure() {
+    let server = MockServer::start().await;
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+
+    assert!(fetcher(&server)
+        .fetch_to(&DocumentType::Rfc(9000), &dest)
+        .await
+        .is_err());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+/// A server that answers every request with a Wi-Fi sign-in page, as
+/// captive portals do, with a 200.
+async fn captive_portal() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/plain")
+                .set_body_string(
+                    "<!DOCTYPE html>\n<html><head><title>Hotel Wi-Fi</title></head>\
+                     <body><form action=\"/login\">Room number <input name=\"room\">\
+                     </form></body></html>",
+                ),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn fetch_rejects_a_captive_portal_page() {
+    let server = captive_portal().await;
+    let err = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("captive portal"), "{}", message);
+    assert!(message.contains("/rfc/rfc9000.txt"), "{}", message);
+}
+
+#[tokio::test]
+async fn fetch_rejects_text_that_is_not_the_document() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Service unavailable"))
+        .mount(&server)
+        .await;
+    let err = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("too short"), "{}", err);
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(document_text("RFC 9001")))
+        .mount(&server)
+        .await;
+    let err = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("doesn't look like RFC 9000"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn fetch_to_keeps_nothing_from_a_captive_portal() {
+    let server = captive_portal().await;
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+
+    let err = fetcher(&server)
+        .fetch_to(&DocumentType::Rfc(9000), &dest)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("captive portal"), "{}", err);
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+/// A server giving each connection the next of `responses` verbatim and
+/// then hanging up, for what wiremock can't do, like cutting a body short.
+/// Returns its URL and the request heads it received.
+async fn scripted_server(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requests);
+    tokio::spawn(async move {
+        for response in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            seen.lock()
+                .unwrap()
+                .push(String::from_utf8_lossy(&head).to_lowercase());
+            stream.write_all(&response).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    (url, requests)
+}
+
+fn response(head: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!("{}\r\nConnection: close\r\n\r\n", head).into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+fn large_body() -> Vec<u8> {
+    let mut body = b"Request for Comments: 9000\n".to_vec();
+    body.extend((body.len()..1000).map(|i| b'a' + (i % 26) as u8));
+    body
+}
+
+#[tokio::test]
+async fn fetch_to_resumes_a_dropped_download() {
+    let body = large_body();
+    let (url, requests) = scripted_server(vec![
+        response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nAccept-Ranges: bytes\r\nETag: \"v1\"",
+            &body[..600],
+        ),
+        response(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 600-999/1000\r\n\
+             Content-Length: 400\r\nETag: \"v1\"",
+            &body[600..],
+        ),
+    ])
+    .await;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+    let fetcher = DocumentFetcher::builder().base_url(url).build().unwrap();
+    let format = fetcher
+        .fetch_to(&DocumentType::Rfc(9000), &dest)
+        .await
+        .unwrap();
+
+    assert_eq!(format, Format::Text);
+    assert_eq!(std::fs::read(&dest).unwrap(), body);
+    let requests = requests.lock().unwrap();
+    assert!(!requests[0].contains("range:"));
+    assert!(requests[1].contains("range: bytes=600-\r\n"));
+    assert!(requests[1].contains("if-range: \"v1\"\r\n"));
+    assert!(requests[1].contains("accept-encoding: identity\r\n"));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn fetch_to_starts_over_when_the_range_is_ignored() {
+    let body = large_body();
+    let (url, requests) = scripted_server(vec![
+        response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nAccept-Ranges: bytes",
+            &body[..600],
+        ),
+        response("HTTP/1.1 200 OK\r\nContent-Length: 1000", &body),
+    ])
+    .await;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+    let fetcher = DocumentFetcher::builder().base_url(url).build().unwrap();
+    fetcher
+        .fetch_to(&DocumentType::Rfc(9000), &dest)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&dest).unwrap(), body);
+    assert!(requests.lock().unwrap()[1].contains("range: bytes=600-"));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn gzip_encoded_responses_are_decoded_buffered_and_streamed() {
+    let text = document_text("RFC 9000 compressed in transit");
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "gzip")
+                .set_body_bytes(gzip(text.as_bytes())),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let fetcher = fetcher(&server);
+    let quic = DocumentType::Rfc(9000);
+
+    let fetched = fetcher.fetch(&quic).await.unwrap();
+    assert_eq!(fetched.format, Format::Text);
+    assert_eq!(fetched.content, text);
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+    assert_eq!(fetcher.fetch_to(&quic, &dest).await.unwrap(), Format::Text);
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), text);
+
+    for request in server.received_requests().await.unwrap() {
+        let accepted = request.headers.get("accept-encoding").unwrap();
+        assert!(accepted.to_str().unwrap().contains("gzip"), "{accepted:?}");
+    }
+}
+
+#[tokio::test]
+async fn text_missing_as_txt_is_fetched_as_txt_gz() {
+    let text = document_text("RFC 9000 kept compressed");
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt.gz"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "application/gzip")
+                .set_body_bytes(gzip(text.as_bytes())),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let fetcher = fetcher(&server);
+    let quic = DocumentType::Rfc(9000);
+
+    let fetched = fetcher.fetch(&quic).await.unwrap();
+    assert_eq!(fetched.format, Format::Text);
+    assert_eq!(fetched.content, text);
+    assert_eq!(
+        fetched.source_url,
+        format!("{}/rfc/rfc9000.txt.gz", server.uri())
+    );
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("rfc9000.txt");
+    assert_eq!(fetcher.fetch_to(&quic, &dest).await.unwrap(), Format::Text);
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), text);
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn a_missing_txt_gz_reports_the_missing_txt() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt.gz"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let err = fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rfc9000.txt: HTTP 404"), "{}", err);
+}
+
+#[tokio::test]
+async fn builders_route_through_configured_proxy() {
+    // The mock server plays the proxy: requests for an unresolvable host
+    // only succeed if they're sent to it.
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc9000",
+            "title": "QUIC",
+            "abstract": null
+        })))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(document_text("RFC 9000: QUIC")))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+    let settings = ProxySettings::default().with_override(Some(proxy.uri()));
+
+    let datatracker = DataTrackerClient::builder()
+        .base_url("http://datatracker.invalid")
+        .proxy(settings.clone())
+        .build()
+        .unwrap();
+    assert_eq!(
+        datatracker.get_document("rfc9000").await.unwrap().title,
+        "QUIC"
+    );
+
+    let fetcher = DocumentFetcher::builder()
+        .base_url("http://rfc-editor.invalid")
+        .proxy(settings)
+        .build()
+        .unwrap();
+    let FetchedDocument { content, .. } = fetcher.fetch(&DocumentType::Rfc(9000)).await.unwrap();
+    assert_eq!(content, document_text("RFC 9000: QUIC"));
+}
+
+/// A server answering for RFC 9000's metadata and text, but only to
+/// requests sent with `user_agent`.
+async fn user_agent_server(user_agent: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc9000/"))
+        .and(header("user-agent", user_agent))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc9000",
+            "title": "QUIC",
+            "abstract": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .and(header("user-agent", user_agent))
+        .respond_with(ResponseTemplate::new(200).set_body_string(document_text("RFC 9000: QUIC")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn clients_send_the_default_user_agent() {
+    let server = user_agent_server(rfc::api::DEFAULT_USER_AGENT).await;
+    datatracker(&server).get_document("rfc9000").await.unwrap();
+    fetcher(&server)
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn builders_replace_the_user_agent_whole() {
+    let server = user_agent_server("acme-indexer/2.1").await;
+    DataTrackerClient::builder()
+        .base_url(server.uri())
+        .user_agent("acme-indexer/2.1")
+        .build()
+        .unwrap()
+        .get_document("rfc9000")
+        .await
+        .unwrap();
+    DocumentFetcher::builder()
+        .base_url(server.uri())
+        .user_agent("acme-indexer/2.1")
+        .build()
+        .unwrap()
+        .fetch(&DocumentType::Rfc(9000))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "blocking")]
+
+use rfc::blocking::{BlockingDataTrackerClient, BlockingDocumentFetcher};
+use rfc::{
+    DataTrackerClient, DocumentFetcher, DocumentType, FetchedDocument, Format, SearchFilter,
+};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Start a mock server on a throwaway runtime. wiremock serves from its
+/// own thread, so the blocking clients can call it from plain `#[test]`s.
+fn start_server(mocks: Vec<Mock>) -> MockServer {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let server = MockServer::start().await;
+        for mock in mocks {
+            mock.mount(&server).await;
+        }
+        server
+    })
+}
+
+#[test]
+fn blocking_search() {
+    let server = start_server(vec![Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": {"total_count": 1, "next": null},
+            "objects": [{"name": "rfc9000", "title": "QUIC", "abstract": null}]
+        })))]);
+
+    let client = BlockingDataTrackerClient::from_async(
+        DataTrackerClient::builder()
+            .base_url(server.uri())
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let results = client.search("quic", SearchFilter::RfcsOnly, 25).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results.documents[0].doc_type, DocumentType::Rfc(9000));
+}
+
+#[test]
+fn blocking_get_document() {
+    let server = start_server(vec![Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/rfc791/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "rfc791", "title": "Internet Protocol", "abstract": null
+        })))]);
+
+    let client = BlockingDataTrackerClient::from_async(
+        DataTrackerClient::builder()
+            .base_url(server.uri())
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        client.get_document("rfc791").unwrap().title,
+        "Internet Protocol"
+    );
+}
+
+#[test]
+fn blocking_fetch_with_html_fallback() {
+    let server = start_server(vec![
+        Mock::given(method("GET"))
+            .and(path("/rfc/rfc9000.txt"))
+            .respond_with(ResponseTemplate::new(404)),
+        Mock::given(method("GET"))
+            .and(path("/rfc/rfc9000.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>QUIC</p>")),
+    ]);
+
+    let fetcher = BlockingDocumentFetcher::from_async(
+        DocumentFetcher::builder()
+            .base_url(server.uri())
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let FetchedDocument {
+        content, format, ..
+    } = fetcher.fetch(&DocumentType::Rfc(9000)).unwrap();
+    assert_eq!(format, Format::Html);
+    assert_eq!(content, "<p>QUIC</p>");
+}
@@ -0,0 +1,226 @@
+//! Behavior every `DocumentCache` backend must share. Each test runs its
+//! checks against every backend and names the backend in failures.
+
+use chrono::Utc;
+use rfc::{CacheMetadata, DocumentCache, DocumentType, Format, FsCache, MemoryCache};
+use tempfile::TempDir;
+
+/// A backend under test, plus whatever must outlive it (the temp dir).
+struct Backend {
+    name: &'static str,
+    cache: Box<dyn DocumentCache>,
+    _dir: Option<TempDir>,
+}
+
+fn backends() -> Vec<Backend> {
+    let dir = TempDir::new().unwrap();
+    let fs = FsCache::with_dir(dir.path().to_path_buf()).unwrap();
+    let gz_dir = TempDir::new().unwrap();
+    let fs_gz = FsCache::with_dir(gz_dir.path().to_path_buf())
+        .unwrap()
+        .with_compression(true);
+    vec![
+        Backend {
+            name: "fs",
+            cache: Box::new(fs),
+            _dir: Some(dir),
+        },
+        Backend {
+            name: "fs-gz",
+            cache: Box::new(fs_gz),
+            _dir: Some(gz_dir),
+        },
+        Backend {
+            name: "memory",
+            cache: Box::new(MemoryCache::new()),
+            _dir: None,
+        },
+    ]
+}
+
+fn meta(title: &str) -> CacheMetadata {
+    CacheMetadata::new(title, Utc::now())
+}
+
+#[test]
+fn store_and_get_document() {
+    for b in backends() {
+        let doc = DocumentType::Rfc(9000);
+        assert_eq!(b.cache.get_document(&doc, Format::Text), None, "{}", b.name);
+
+        b.cache.store_document(&doc, Format::Text, "text").unwrap();
+        assert_eq!(
+            b.cache.get_document(&doc, Format::Text).as_deref(),
+            Some("text"),
+            "{}",
+            b.name
+        );
+        // Formats are independent.
+        assert_eq!(b.cache.get_document(&doc, Format::Html), None, "{}", b.name);
+    }
+}
+
+#[test]
+fn store_document_overwrites() {
+    for b in backends() {
+        let doc = DocumentType::Draft("draft-ietf-foo-01".to_string());
+        b.cache.store_document(&doc, Format::Text, "old").unwrap();
+        b.cache.store_document(&doc, Format::Text, "new").unwrap();
+        assert_eq!(
+            b.cache.get_document(&doc, Format::Text).as_deref(),
+            Some("new"),
+            "{}",
+            b.name
+        );
+    }
+}
+
+#[test]
+fn store_and_get_metadata() {
+    for b in backends() {
+        let doc = DocumentType::Rfc(4271);
+        assert!(b.cache.get_metadata(&doc).is_none(), "{}", b.name);
+
+        b.cache.store_metadata(&doc, &meta("BGP-4")).unwrap();
+        assert_eq!(
+            b.cache.get_metadata(&doc).unwrap().title,
+            "BGP-4",
+            "{}",
+            b.name
+        );
+    }
+}
+
+#[test]
+fn list_cached_dedupes_formats() {
+    for b in backends() {
+        let rfc = DocumentType::Rfc(9000);
+        let draft = DocumentType::Draft("draft-ietf-quic-transport-34".to_string());
+        b.cache.store_document(&rfc, Format::Text, "t").unwrap();
+        b.cache.store_document(&rfc, Format::Html, "h").unwrap();
+        b.cache.store_document(&draft, Format::Text, "t").unwrap();
+
+        let mut listed = b.cache.list_cached();
+        listed.sort_by_key(|d| d.name());
+        assert_eq!(listed, vec![draft.clone(), rfc.clone()], "{}", b.name);
+    }
+}
+
+#[test]
+fn list_cached_with_metadata_pairs_entries() {
+    for b in backends() {
+        let with = DocumentType::Rfc(9000);
+        let without = DocumentType::Rfc(8200);
+        b.cache.store_document(&with, Format::Text, "t").unwrap();
+        b.cache.store_document(&without, Format::Text, "t").unwrap();
+        b.cache.store_metadata(&with, &meta("QUIC")).unwrap();
+
+        let listed = b.cache.list_cached_with_metadata();
+        assert_eq!(listed.len(), 2, "{}", b.name);
+        let find = |d: &DocumentType| listed.iter().find(|cd| &cd.doc_type == d).unwrap();
+        assert_eq!(
+            find(&with).metadata.as_ref().unwrap().title,
+            "QUIC",
+            "{}",
+            b.name
+        );
+        assert!(find(&without).metadata.is_none(), "{}", b.name);
+    }
+}
+
+#[test]
+fn remove_drops_every_format_and_metadata() {
+    for b in backends() {
+        let doc = DocumentType::Rfc(9000);
+        assert!(!b.cache.remove(&doc).unwrap(), "{}", b.name);
+
+        b.cache.store_document(&doc, Format::Text, "t").unwrap();
+        b.cache.store_document(&doc, Format::Html, "h").unwrap();
+        b.cache.store_metadata(&doc, &meta("QUIC")).unwrap();
+
+        assert!(b.cache.remove(&doc).unwrap(), "{}", b.name);
+        assert!(
+            b.cache.get_document(&doc, Format::Text).is_none(),
+            "{}",
+            b.name
+        );
+        assert!(
+            b.cache.get_document(&doc, Format::Html).is_none(),
+            "{}",
+            b.name
+        );
+        assert!(b.cache.get_metadata(&doc).is_none(), "{}", b.name);
+        assert!(b.cache.list_cached().is_empty(), "{}", b.name);
+        assert!(!b.cache.remove(&doc).unwrap(), "{}", b.name);
+    }
+}
+
+#[test]
+fn remove_leaves_other_documents() {
+    for b in backends() {
+        let keep = DocumentType::Rfc(791);
+        let drop = DocumentType::Rfc(793);
+        b.cache.store_document(&keep, Format::Text, "ip").unwrap();
+        b.cache.store_document(&drop, Format::Text, "tcp").unwrap();
+
+        b.cache.remove(&drop).unwrap();
+        assert_eq!(b.cache.list_cached(), vec![keep.clone()], "{}", b.name);
+    }
+}
+
+#[test]
+fn clear_empties_cache() {
+    for b in backends() {
+        let doc = DocumentType::Rfc(9000);
+        b.cache.store_document(&doc, Format::Text, "t").unwrap();
+        b.cache.store_metadata(&doc, &meta("QUIC")).unwrap();
+
+        b.cache.clear().unwrap();
+        assert!(b.cache.list_cached().is_empty(), "{}", b.name);
+        assert!(
+            b.cache.get_document(&doc, Format::Text).is_none(),
+            "{}",
+            b.name
+        );
+        assert!(b.cache.get_metadata(&doc).is_none(), "{}", b.name);
+
+        // Still usable afterwards.
+        b.cache.store_document(&doc, Format::Text, "again").unwrap();
+        assert_eq!(b.cache.list_cached(), vec![doc.clone()], "{}", b.name);
+    }
+}
+
+#[test]
+fn tags_add_remove_and_filter() {
+    for b in backends() {
+        let bgp = DocumentType::Rfc(4271);
+        let quic = DocumentType::Rfc(9000);
+        b.cache.store_document(&bgp, Format::Text, "BGP").unwrap();
+        b.cache.store_document(&quic, Format::Text, "QUIC").unwrap();
+        b.cache.store_metadata(&quic, &meta("QUIC")).unwrap();
+
+        // Untitled documents get metadata to hold the tag.
+        assert!(b.cache.add_tag(&bgp, "Routing").unwrap(), "{}", b.name);
+        assert!(!b.cache.add_tag(&bgp, "routing").unwrap(), "{}", b.name);
+        assert!(b.cache.add_tag(&quic, "transport").unwrap(), "{}", b.name);
+        assert!(b.cache.add_tag(&bgp, "bad tag").is_err(), "{}", b.name);
+
+        let tagged: Vec<_> = b
+            .cache
+            .list_by_tag("ROUTING")
+            .into_iter()
+            .map(|c| c.doc_type)
+            .collect();
+        assert_eq!(tagged, vec![bgp.clone()], "{}", b.name);
+        assert_eq!(
+            b.cache.get_metadata(&quic).unwrap().title,
+            "QUIC",
+            "{}",
+            b.name
+        );
+
+        assert!(b.cache.remove_tag(&bgp, "routing").unwrap(), "{}", b.name);
+        assert!(!b.cache.remove_tag(&bgp, "routing").unwrap(), "{}", b.name);
+        assert!(b.cache.list_by_tag("routing").is_empty(), "{}", b.name);
+    }
+}
@@ -0,0 +1,953 @@
+//! End-to-end checks of the `rfc` binary against a temporary cache.
+
+#![cfg(feature = "cli")]
+
+use std::process::Command;
+
+use chrono::{NaiveDate, Utc};
+use rfc::models::{RfcIndex, RfcIndexEntry};
+use rfc::{CacheManager, CacheMetadata, DocumentType, Format};
+use tempfile::TempDir;
+
+/// Run `rfc args...` with its cache in `dir` and its config dir empty.
+fn rfc(dir: &TempDir, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rfc"))
+        .args(args)
+        .env("RFC_CACHE_DIR", dir.path().join("cache"))
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("HOME", dir.path())
+        .output()
+        .expect("rfc runs")
+}
+
+#[test]
+fn cache_has_reports_presence_through_exit_status() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC")
+        .unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(8446), Format::Html, "<p>TLS</p>")
+        .unwrap();
+
+    for input in ["9000", "rfc9000", "RFC 9000", "RFC-9000", "8446"] {
+        let out = rfc(&dir, &["cache", "has", input]);
+        assert_eq!(out.status.code(), Some(0), "{input} should be cached");
+        assert!(out.stdout.is_empty() && out.stderr.is_empty());
+    }
+
+    let out = rfc(&dir, &["cache", "has", "793"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty() && out.stderr.is_empty());
+}
+
+#[test]
+fn view_subcommand_and_bare_form_open_the_same_document() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC\n")
+        .unwrap();
+
+    for args in [
+        &["--offline", "-o", "cat", "9000"][..],
+        &["view", "--offline", "-o", "cat", "9000"],
+        &["view", "-o", "cat", "--offline", "rfc9000"],
+    ] {
+        let out = rfc(&dir, args);
+        assert!(out.status.success(), "{args:?}: {out:?}");
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "QUIC\n", "{args:?}");
+    }
+
+    let out = rfc(&dir, &["view", "--offline"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Name a document to view"));
+}
+
+#[test]
+fn color_is_a_global_option() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "1.  Overview\n")
+        .unwrap();
+
+    for args in [
+        &["--offline", "-o", "cat", "--color", "always", "9000"][..],
+        &[
+            "view",
+            "--color",
+            "always",
+            "--offline",
+            "-o",
+            "cat",
+            "9000",
+        ],
+    ] {
+        let out = rfc(&dir, args);
+        assert!(out.status.success(), "{args:?}: {out:?}");
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            "\x1b[1;34m1.  Overview\x1b[0m\n",
+            "{args:?}"
+        );
+    }
+    let out = rfc(
+        &dir,
+        &["--offline", "-o", "cat", "--color", "never", "9000"],
+    );
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "1.  Overview\n");
+}
+
+#[test]
+fn global_options_may_come_before_the_subcommand() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC\n")
+        .unwrap();
+
+    for args in [
+        &["--offline", "cache", "has", "9000"][..],
+        &["-q", "cache", "info"],
+        &["--color", "never", "cache", "list"],
+        &["--offline", "view", "-o", "cat", "9000"],
+    ] {
+        let out = rfc(&dir, args);
+        assert!(out.status.success(), "{args:?}: {out:?}");
+    }
+
+    let out = rfc(&dir, &["-o", "cat", "cache", "list"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr)
+        .contains("'--open-with' can't be used with the 'cache' subcommand"));
+}
+
+#[test]
+fn cache_subcommands_accept_their_short_aliases() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    for n in [9000, 8446] {
+        cache
+            .store_document(&DocumentType::Rfc(n), Format::Text, "text")
+            .unwrap();
+    }
+
+    let listed = rfc(&dir, &["cache", "ls"]);
+    assert!(listed.status.success());
+    assert_eq!(listed.stdout, rfc(&dir, &["cache", "list"]).stdout);
+
+    assert!(rfc(&dir, &["cache", "rm", "9000"]).status.success());
+    assert!(rfc(&dir, &["cache", "remove", "8446"]).status.success());
+    assert_eq!(rfc(&dir, &["cache", "has", "9000"]).status.code(), Some(1));
+    assert_eq!(rfc(&dir, &["cache", "has", "8446"]).status.code(), Some(1));
+}
+
+/// stdout and stderr of a finished run, lossily decoded.
+fn text(out: &std::process::Output) -> (String, String) {
+    (
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+        String::from_utf8_lossy(&out.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn cache_grep_prints_matches_with_context() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(
+            &DocumentType::Rfc(9000),
+            Format::Text,
+            "QUIC\nStreams\nFlow control\nframes\nPadding\n",
+        )
+        .unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9114), Format::Text, "HTTP/3 FRAMES\n")
+        .unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(8446), Format::Text, "TLS 1.3\n")
+        .unwrap();
+
+    let out = rfc(&dir, &["cache", "grep", "frames"]);
+    assert!(out.status.success());
+    assert_eq!(text(&out).0, "rfc9000\n4:frames\n");
+
+    let out = rfc(
+        &dir,
+        &["cache", "grep", "-i", "frames", "-C", "1", "-A", "0"],
+    );
+    assert_eq!(
+        text(&out).0,
+        "rfc9000\n3-Flow control\n4:frames\n\nrfc9114\n1:HTTP/3 FRAMES\n"
+    );
+
+    let out = rfc(&dir, &["cache", "grep", "QUIC", "--context", "9"]);
+    assert_eq!(text(&out).0.lines().count(), 6);
+
+    let out = rfc(&dir, &["cache", "grep", "SCTP"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(text(&out).0, "");
+}
+
+#[test]
+fn cache_list_on_an_empty_cache() {
+    let dir = TempDir::new().unwrap();
+    let out = rfc(&dir, &["cache", "list"]);
+    assert!(out.status.success());
+    assert_eq!(text(&out), ("Cache is empty\n".to_string(), String::new()));
+}
+
+#[test]
+fn cache_list_and_info_show_stored_documents() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let quic = DocumentType::Rfc(9000);
+    cache.store_document(&quic, Format::Text, "QUIC").unwrap();
+    cache
+        .store_metadata(
+            &quic,
+            &CacheMetadata::new(
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                Utc::now(),
+            ),
+        )
+        .unwrap();
+
+    let out = rfc(&dir, &["cache", "list"]);
+    assert!(out.status.success());
+    let (stdout, stderr) = text(&out);
+    assert!(stderr.is_empty(), "{stderr}");
+    let line = stdout.lines().find(|l| l.contains("rfc9000")).unwrap();
+    assert!(line.contains("QUIC: A UDP-Based Multiplexed and Secure Transport"));
+
+    let out = rfc(&dir, &["cache", "info"]);
+    assert!(out.status.success());
+    let (stdout, _) = text(&out);
+    assert!(stdout.starts_with("Cache directory: "), "{stdout}");
+    assert!(stdout.contains("\nCached documents: 1\n"), "{stdout}");
+    assert!(stdout.contains("\nCompression: off\n"), "{stdout}");
+}
+
+#[test]
+fn cache_remove_reports_whether_the_document_was_cached() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC")
+        .unwrap();
+
+    let out = rfc(&dir, &["cache", "remove", "9000"]);
+    assert!(out.status.success());
+    assert_eq!(text(&out).0, "Removed RFC 9000 from cache, freeing 4 B\n");
+
+    let out = rfc(&dir, &["cache", "remove", "9000"]);
+    assert!(out.status.success());
+    assert_eq!(text(&out).0, "RFC 9000 was not in cache\n");
+
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC")
+        .unwrap();
+    let out = rfc(&dir, &["cache", "clear"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert!(
+        stdout.starts_with("Cache cleared: 1 document and 0 metadata files, "),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn unusable_document_arguments_fail_with_a_message() {
+    let dir = TempDir::new().unwrap();
+
+    let out = rfc(&dir, &["--offline", ""]);
+    assert_eq!(out.status.code(), Some(1));
+    let (stdout, stderr) = text(&out);
+    assert!(stdout.is_empty());
+    assert!(
+        stderr.starts_with("Error: Expected an RFC number or draft name, got an empty argument"),
+        "{stderr}"
+    );
+
+    for args in [["cache", "has", "draft-"], ["cache", "rm", "4294967296"]] {
+        let out = rfc(&dir, &args);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(
+            text(&out)
+                .1
+                .starts_with("Error: Expected an RFC number or draft name, got '"),
+            "{args:?}: {out:?}"
+        );
+    }
+
+    let out = rfc(&dir, &["fetch", "9010-9000"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(text(&out)
+        .1
+        .starts_with("Error: Invalid range 9010-9000: expected e.g. 9000-9010"));
+
+    let out = rfc(&dir, &["--offline", "9000"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(text(&out)
+        .1
+        .starts_with("Error: RFC 9000 is not cached and --offline was given"));
+
+    let out = rfc(
+        &dir,
+        &[
+            "search",
+            "bgp",
+            "--offline",
+            "--since",
+            "2020-06",
+            "--before",
+            "2015",
+        ],
+    );
+    assert_eq!(out.status.code(), Some(1));
+    assert!(text(&out)
+        .1
+        .starts_with("Error: --since 2020-06-01 must be earlier than --before 2015-01-01"));
+}
+
+#[test]
+fn head_and_abstract_print_without_a_viewer() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let content = "Request for Comments: 9000\n\nAbstract\n\n   QUIC is a transport.\n\n\
+                Status of This Memo\n\n   Boilerplate.\n";
+    let quic = DocumentType::Rfc(9000);
+    cache.store_document(&quic, Format::Text, content).unwrap();
+    cache
+        .store_metadata(&quic, &CacheMetadata::new("QUIC", Utc::now()))
+        .unwrap();
+
+    let out = rfc(&dir, &["--offline", "9000", "--head", "3"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(
+        stdout,
+        "RFC 9000: QUIC\n\nRequest for Comments: 9000\n\nAbstract\n"
+    );
+    assert!(stderr.contains("Using cached copy"), "{stderr}");
+
+    let out = rfc(&dir, &["--offline", "9000", "--head"]);
+    assert_eq!(text(&out).0.lines().count(), 2 + content.lines().count());
+
+    let out = rfc(&dir, &["--offline", "9000", "--abstract"]);
+    assert_eq!(text(&out).0, "QUIC is a transport.\n");
+
+    let mut with_abstract = CacheMetadata::new("QUIC", Utc::now());
+    with_abstract.set_abstract(Some("The Datatracker's\nabstract.".to_string()));
+    cache.store_metadata(&quic, &with_abstract).unwrap();
+    let out = rfc(&dir, &["--offline", "9000", "--abstract"]);
+    assert_eq!(text(&out).0, "The Datatracker's abstract.\n");
+
+    // The recorded abstract doesn't need the text, or any other
+    // metadata source, to be shown.
+    std::fs::remove_file(cache.document_file(&quic, Format::Text).unwrap()).unwrap();
+    let out = rfc(&dir, &["--offline", "9000", "--abstract"]);
+    assert_eq!(text(&out).0, "The Datatracker's abstract.\n");
+    let out = rfc(&dir, &["info", "9000", "--offline"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert!(
+        stdout
+            .lines()
+            .any(|l| l.starts_with("Title:") && l.ends_with("QUIC")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Abstract:\n\n  The Datatracker's abstract.\n"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn status_line_reports_cached_and_missing_documents() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let quic = DocumentType::Rfc(9000);
+    cache.store_document(&quic, Format::Text, "QUIC\n").unwrap();
+    let cached_at = NaiveDate::from_ymd_opt(2024, 1, 3)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_utc();
+    cache
+        .store_metadata(
+            &quic,
+            &CacheMetadata::new(
+                "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                cached_at,
+            ),
+        )
+        .unwrap();
+
+    let out = rfc(&dir, &["--status-line", "9000", "--max-width", "40"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(stdout, "rfc9000 ✓cached 2024-01-03 · QUIC: A ...\n");
+
+    let out = rfc(&dir, &["--status-line", "9001"]);
+    assert!(out.status.success());
+    assert_eq!(text(&out).0, "rfc9001 ✗ not cached\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn the_viewer_is_told_the_document_title() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let quic = DocumentType::Rfc(9000);
+    cache.store_document(&quic, Format::Text, "QUIC\n").unwrap();
+    cache
+        .store_metadata(&quic, &CacheMetadata::new("QUIC", Utc::now()))
+        .unwrap();
+
+    let viewer = r#"sh -c 'printf %s "${RFC_DOC_TITLE-unset}"'"#;
+    let out = rfc(&dir, &["--offline", "-o", viewer, "9000"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(stdout, "RFC 9000: QUIC");
+
+    let out = rfc(&dir, &["--offline", "-o", viewer, "--plain-viewer", "9000"]);
+    assert_eq!(text(&out).0, "unset");
+}
+
+#[test]
+fn lines_prints_an_inclusive_range() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let content: String = (1..=12).map(|n| format!("line {n}\n")).collect();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, &content)
+        .unwrap();
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "3-4"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(text(&out).0, "line 3\nline 4\n");
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "11-", "--number"]);
+    assert_eq!(text(&out).0, "11  line 11\n12  line 12\n");
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "-2", "--number"]);
+    assert_eq!(text(&out).0, "1  line 1\n2  line 2\n");
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "10-99"]);
+    assert_eq!(text(&out).0.lines().count(), 3);
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "13-"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("past the end"), "{out:?}");
+
+    let out = rfc(&dir, &["--offline", "9000", "--lines", "5-4"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("5 comes after 4"), "{out:?}");
+
+    let out = rfc(&dir, &["--offline", "9000", "--number"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn iana_summarizes_registries() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let content = "1.  Introduction\n\n   Hi.\n\n2.  IANA Considerations\n\n\
+                2.1.  Foo Registry\n\n   IANA has created the \"Foo\" registry.\n\n\
+                3.  References\n";
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, content)
+        .unwrap();
+    cache
+        .store_document(
+            &DocumentType::Rfc(9001),
+            Format::Text,
+            "5.  IANA Considerations\n\n   This document has no IANA actions.\n",
+        )
+        .unwrap();
+
+    let out = rfc(&dir, &["--offline", "9000", "--iana"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert!(stdout.starts_with("2.  IANA Considerations\n\n2.1.  Foo Registry\n"));
+    assert!(
+        stdout.ends_with("Summary: 1 registry\n  2.1.  Foo Registry\n    - Foo Registry\n"),
+        "{stdout}"
+    );
+
+    let out = rfc(&dir, &["--offline", "9000", "--iana", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["registries"][0], "Foo");
+    assert_eq!(json["subsections"][0]["number"], "2.1");
+
+    let (stdout, _) = text(&rfc(&dir, &["--offline", "9001", "--iana"]));
+    assert!(stdout.ends_with("Summary: no IANA actions\n"), "{stdout}");
+
+    let out = rfc(&dir, &["--offline", "9000", "--json"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn cite_uses_the_synced_index_offline() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let mut index = RfcIndex::default();
+    index.entries.insert(
+        8446,
+        RfcIndexEntry {
+            number: 8446,
+            title: "The Transport Layer Security (TLS) Protocol Version 1.3".to_string(),
+            authors: vec!["E. Rescorla".to_string()],
+            published: NaiveDate::from_ymd_opt(2018, 8, 1),
+            doi: Some("10.17487/RFC8446".to_string()),
+            ..Default::default()
+        },
+    );
+    cache.store_rfc_index(&index).unwrap();
+
+    let out = rfc(&dir, &["--offline", "8446", "--cite"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(
+        stdout,
+        "Rescorla, E., \"The Transport Layer Security (TLS) Protocol Version 1.3\", \
+         RFC 8446, DOI 10.17487/RFC8446, August 2018.\n"
+    );
+
+    let (stdout, _) = text(&rfc(
+        &dir,
+        &["--offline", "8446", "--cite", "--style", "ris"],
+    ));
+    assert!(
+        stdout.starts_with("TY  - RPRT\nAU  - Rescorla, E.\n"),
+        "{stdout}"
+    );
+    let (stdout, _) = text(&rfc(
+        &dir,
+        &["--offline", "8446", "--cite", "--style", "bibtex"],
+    ));
+    assert!(stdout.starts_with("@misc{rfc8446,\n"), "{stdout}");
+
+    let out = rfc(&dir, &["--offline", "1000", "--cite"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("not in the local RFC index"));
+    let out = rfc(&dir, &["--offline", "draft-ietf-quic-transport", "--cite"]);
+    assert!(text(&out).1.contains("Only RFCs can be cited"));
+}
+
+#[test]
+fn random_picks_from_the_filtered_index() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let mut index = RfcIndex::default();
+    for (number, status, wg) in [
+        (791, "INTERNET STANDARD", None),
+        (9000, "PROPOSED STANDARD", Some("quic")),
+    ] {
+        index.entries.insert(
+            number,
+            RfcIndexEntry {
+                number,
+                title: format!("RFC {number}"),
+                status: Some(status.to_string()),
+                wg: wg.map(str::to_string),
+                ..Default::default()
+            },
+        );
+    }
+    cache.store_rfc_index(&index).unwrap();
+    let quic = DocumentType::Rfc(9000);
+    cache
+        .store_document(&quic, Format::Text, "Request for Comments: 9000\n")
+        .unwrap();
+    cache
+        .store_metadata(&quic, &CacheMetadata::new("QUIC", Utc::now()))
+        .unwrap();
+
+    for filter in [
+        ["--min", "5000"],
+        ["--wg", "QUIC"],
+        ["--status", "proposed-standard"],
+    ] {
+        let mut args = vec!["--offline", "--random", "--head", "3"];
+        args.extend(filter);
+        let out = rfc(&dir, &args);
+        let (stdout, stderr) = text(&out);
+        assert!(out.status.success(), "{stderr}");
+        assert!(
+            stderr.starts_with("Picked RFC 9000 at random\n"),
+            "{stderr}"
+        );
+        assert_eq!(stdout, "RFC 9000: QUIC\n\nRequest for Comments: 9000\n");
+    }
+
+    let out = rfc(&dir, &["--offline", "--random", "--wg", "mpls"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("No RFC in the local index matches"));
+    let out = rfc(&dir, &["--offline", "--min", "5000"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn cache_info_stats_count_hits_and_reset() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(&DocumentType::Rfc(9000), Format::Text, "QUIC\n")
+        .unwrap();
+
+    let (stdout, _) = text(&rfc(&dir, &["cache", "info", "--stats"]));
+    assert!(
+        stdout.contains("No cache statistics recorded yet"),
+        "{stdout}"
+    );
+
+    for _ in 0..2 {
+        let out = rfc(&dir, &["--offline", "9000", "--head"]);
+        assert!(out.status.success(), "{out:?}");
+    }
+    let (stdout, _) = text(&rfc(&dir, &["cache", "info", "--stats"]));
+    assert!(
+        stdout.contains("Hits:              2 (100% of lookups)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Served from cache: 10 B"), "{stdout}");
+    assert!(
+        stdout.contains("Last run:\n  Hits:              1"),
+        "{stdout}"
+    );
+
+    let out = rfc(&dir, &["cache", "info", "--stats", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["stats"]["total"]["hits"], 2);
+
+    let (stdout, _) = text(&rfc(&dir, &["cache", "info", "--reset-stats"]));
+    assert_eq!(stdout, "Cache statistics reset\n");
+    let (stdout, _) = text(&rfc(&dir, &["cache", "info", "--stats"]));
+    assert!(
+        stdout.contains("No cache statistics recorded yet"),
+        "{stdout}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn configured_contact_is_added_to_the_user_agent() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let user_agent = rfc::api::user_agent_with_contact("me@example.com");
+    assert!(user_agent.ends_with(" (+mailto:me@example.com)"));
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .and(header("user-agent", user_agent.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+            "Request for Comments: 9000\n\n{}",
+            "   QUIC.\n".repeat(80)
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config/rfc");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(config.join("config.toml"), "contact = \"me@example.com\"\n").unwrap();
+
+    let out = rfc(
+        &dir,
+        &[
+            "fetch",
+            "9000",
+            "--mirror",
+            &server.uri(),
+            "--verbose",
+            "-q",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let (_, stderr) = text(&out);
+    assert!(
+        stderr.contains(&format!("User-Agent: {}\n", user_agent)),
+        "{stderr}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fetch_range_skips_numbers_the_hosts_dont_have() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // No index is synced, so only the 404s say 9001 and 9002 don't exist.
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+            "Request for Comments: 9000\n\n{}",
+            "   QUIC.\n".repeat(80)
+        )))
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let mirror = server.uri();
+
+    let out = rfc(&dir, &["fetch", "9000-9002", "--mirror", &mirror, "-q"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stdout}{stderr}");
+    assert!(
+        stdout.contains("skipped  RFC 9001: not found\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("1 fetched, 2 skipped, 0 failed"),
+        "{stdout}"
+    );
+
+    // Named on its own, a missing document is still an error.
+    let out = rfc(&dir, &["fetch", "9001", "--mirror", &mirror, "-q"]);
+    assert!(!out.status.success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fetch_reports_where_it_cached_and_skips_cached_documents() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rfc/rfc9000.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+            "Request for Comments: 9000\n\n{}",
+            "   QUIC.\n".repeat(80)
+        )))
+        // The first fetch and the refresh; the run in between is served
+        // from the cache.
+        .expect(2)
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let mirror = server.uri();
+    let fetch = |extra: &[&str]| {
+        let mut args = vec!["fetch", "9000", "--mirror", &mirror, "-q"];
+        args.extend(extra);
+        rfc(&dir, &args)
+    };
+
+    let out = fetch(&[]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert!(stdout.starts_with("Cached RFC 9000 at "), "{stdout}");
+    assert!(stdout.contains("(txt, "), "{stdout}");
+
+    let out = fetch(&[]);
+    let (stdout, _) = text(&out);
+    assert!(
+        stdout.starts_with("RFC 9000 already cached (use -r to refresh): "),
+        "{stdout}"
+    );
+
+    let out = fetch(&["--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["document"], "rfc9000");
+    assert_eq!(json["fetched"], false);
+    assert_eq!(json["format"], "txt");
+    let path = std::path::PathBuf::from(json["path"].as_str().unwrap());
+    assert!(path.starts_with(dir.path().join("cache")), "{path:?}");
+    assert_eq!(
+        json["bytes"].as_u64(),
+        Some(std::fs::metadata(&path).unwrap().len())
+    );
+
+    let out = fetch(&["-r", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["fetched"], true);
+    assert_eq!(json["overwritten"], true);
+
+    let out = rfc(&dir, &["fetch", "9000", "9001", "--json"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(text(&out).1.contains("--json reports on a single document"));
+}
+
+#[test]
+fn export_md_writes_the_cached_text_as_markdown() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    cache
+        .store_document(
+            &DocumentType::Rfc(9000),
+            Format::Text,
+            "1.  Overview\n\n   QUIC relies on TLS [RFC8446].\n",
+        )
+        .unwrap();
+    let expected = "## 1. Overview\n\n\
+                    QUIC relies on TLS [\\[RFC8446\\]](https://www.rfc-editor.org/rfc/rfc8446).\n";
+
+    let path = dir.path().join("rfc9000.md");
+    let out = rfc(
+        &dir,
+        &["--offline", "9000", "--export-md", path.to_str().unwrap()],
+    );
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+
+    let out = rfc(&dir, &["--offline", "9000", "--export-md", "-"]);
+    assert_eq!(text(&out).0, expected);
+}
+
+#[test]
+fn by_author_options_need_by_author() {
+    let dir = TempDir::new().unwrap();
+    for args in [
+        &["--rfcs-only"][..],
+        &["--limit", "5"],
+        &["--by-author", "Mark Nottingham", "9000"],
+        &[
+            "--by-author",
+            "Mark Nottingham",
+            "--rfcs-only",
+            "--drafts-only",
+        ],
+    ] {
+        let out = rfc(&dir, args);
+        assert_eq!(out.status.code(), Some(2), "{args:?}: {out:?}");
+    }
+
+    let out = rfc(&dir, &["--offline", "--by-author", "Mark Nottingham"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("drop --offline"), "{out:?}");
+}
+
+#[test]
+fn mixed_case_identifiers_hit_the_same_cache_entry() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let draft = DocumentType::from_user_input("IETF-QUIC-Transport-34").unwrap();
+    assert_eq!(
+        draft,
+        DocumentType::Draft("draft-ietf-quic-transport-34".to_string())
+    );
+    cache
+        .store_document(&draft, Format::Text, "QUIC transport\n")
+        .unwrap();
+
+    for input in [
+        "draft-ietf-quic-transport-34",
+        "Draft-IETF-QUIC-Transport-34.txt",
+        "IETF-QUIC-Transport-34,",
+    ] {
+        let out = rfc(&dir, &["cache", "has", input]);
+        assert_eq!(out.status.code(), Some(0), "{input} should be cached");
+        let out = rfc(&dir, &["--offline", "-o", "cat", input]);
+        let (stdout, stderr) = text(&out);
+        assert!(out.status.success(), "{input}: {stderr}");
+        assert!(stdout.contains("QUIC transport"), "{input}: {stdout}");
+    }
+    assert_eq!(cache.list_cached().len(), 1);
+}
+
+#[test]
+fn cache_gc_follows_the_configured_policy() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let quic = DocumentType::Rfc(9000);
+    cache.store_document(&quic, Format::Text, "QUIC").unwrap();
+    cache
+        .store_document(&quic, Format::Html, "<pre>QUIC</pre>")
+        .unwrap();
+
+    let out = rfc(&dir, &["cache", "gc"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(text(&out).0, "Nothing to collect\n");
+
+    let config = dir.path().join("config/rfc");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(config.join("config.toml"), "[gc]\nkeep = [\"txt\"]\n").unwrap();
+    let out = rfc(&dir, &["cache", "gc"]);
+    assert!(out.status.success(), "{out:?}");
+    let (stdout, _) = text(&out);
+    assert!(stdout.contains("\n  rfc9000.html\n"), "{stdout}");
+    assert!(
+        stdout.ends_with("Removed 1 file, freeing 15 B\n"),
+        "{stdout}"
+    );
+    assert!(cache.contains(&quic, Format::Text));
+    assert!(!cache.contains(&quic, Format::Html));
+
+    std::fs::write(config.join("config.toml"), "[gc]\nkeep = [\"pdf\"]\n").unwrap();
+    let out = rfc(&dir, &["cache", "gc"]);
+    assert!(!out.status.success());
+    assert!(text(&out).1.contains("gc.keep can only name txt and html"));
+}
+
+#[test]
+fn search_works_without_home_or_a_usable_cache() {
+    let dir = TempDir::new().unwrap();
+    // A file where the cache directory should go can't be made into one.
+    let blocked = dir.path().join("not-a-dir");
+    std::fs::write(&blocked, "").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_rfc"))
+        .args(["search", "quic", "--offline", "--json"])
+        .env_remove("HOME")
+        .env_remove("USERPROFILE")
+        .env_remove("XDG_CACHE_HOME")
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("RFC_CACHE_DIR", &blocked)
+        .output()
+        .expect("rfc runs");
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    assert_eq!(
+        stderr.matches("continuing without a cache").count(),
+        1,
+        "{stderr}"
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["documents"], serde_json::json!([]));
+}
+
+#[test]
+fn header_is_shown_above_the_text_but_never_cached() {
+    let dir = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(dir.path().join("cache")).unwrap();
+    let tls = DocumentType::Rfc(8446);
+    let body = "1.  Introduction\n\n   TLS 1.3.\n";
+    cache.store_document(&tls, Format::Text, body).unwrap();
+    let mut index = RfcIndex::default();
+    index.entries.insert(
+        8446,
+        RfcIndexEntry {
+            number: 8446,
+            title: "The Transport Layer Security (TLS) Protocol Version 1.3".to_string(),
+            authors: vec!["E. Rescorla".to_string()],
+            published: NaiveDate::from_ymd_opt(2018, 8, 1),
+            status: Some("PROPOSED STANDARD".to_string()),
+            wg: Some("tls".to_string()),
+            updated_by: vec![9846],
+            ..Default::default()
+        },
+    );
+    cache.store_rfc_index(&index).unwrap();
+
+    let out = rfc(&dir, &["--offline", "-o", "cat", "--header", "8446"]);
+    let (stdout, stderr) = text(&out);
+    assert!(out.status.success(), "{stderr}");
+    let expected_header = "\
+RFC 8446
+The Transport Layer Security (TLS) Protocol Version 1.3
+
+Status:        Proposed Standard
+Authors:       E. Rescorla
+Date:          August 2018
+Working group: tls
+Updated by:    RFC 9846
+";
+    assert!(stdout.starts_with(expected_header), "{stdout}");
+    assert!(stdout.ends_with(&format!("\n\n{}", body)), "{stdout}");
+    assert_eq!(cache.get_document(&tls, Format::Text).unwrap(), body);
+
+    let out = rfc(&dir, &["--offline", "-o", "cat", "8446"]);
+    assert_eq!(text(&out).0, body);
+}
@@ -18,21 +18,12 @@ fn cache_listing_pairs_documents_with_metadata() {
     let title_1 = "A Border Gateway Protocol 4 (BGP-4)".to_string();
     let title_2 = "QUIC: A UDP-Based Multiplexed and Secure Transport".to_string();
     cache
-        .store_metadata(
-            &with_meta,
-            &CacheMetadata {
-                title: title_1.clone(),
-                cached_at: Utc::now(),
-            },
-        )
+        .store_metadata(&with_meta, &CacheMetadata::new(title_1.clone(), Utc::now()))
         .unwrap();
     cache
         .store_metadata(
             &with_meta_2,
-            &CacheMetadata {
-                title: title_2.clone(),
-                cached_at: Utc::now(),
-            },
+            &CacheMetadata::new(title_2.clone(), Utc::now()),
         )
         .unwrap();
 
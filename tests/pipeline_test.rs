@@ -0,0 +1,481 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use rfc::api::{DocumentSource, FetchedDocument, MapSource};
+use rfc::workflow::{
+    backfill_titles, fetch_and_cache, load_document, missing_abstracts, missing_titles,
+    refresh_cached, Refresh,
+};
+use rfc::{
+    Bookmark, CacheManager, CacheMetadata, DataTrackerClient, DocumentCache, DocumentType, Format,
+    MemoryCache,
+};
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn metadata_server(name: &str, title: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/v1/doc/document/{}/", name)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": name,
+            "title": title,
+            "abstract": null
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("name__in", name))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [{"name": name, "title": title, "abstract": null}]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"objects": []})))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn datatracker(server: &MockServer) -> DataTrackerClient {
+    DataTrackerClient::builder()
+        .base_url(server.uri())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn fetch_and_cache_stores_text_and_metadata() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(9000);
+    let source = MapSource::new().with(doc.clone(), "QUIC text", Format::Text);
+    let server = metadata_server("rfc9000", "QUIC").await;
+
+    let text = fetch_and_cache(&doc, &cache, &source, &datatracker(&server))
+        .await
+        .unwrap();
+
+    assert_eq!(text, "QUIC text");
+    assert_eq!(
+        cache.get_document(&doc, Format::Text),
+        Some("QUIC text".to_string())
+    );
+    let meta = cache.get_metadata(&doc).unwrap();
+    assert_eq!(meta.title, "QUIC");
+    let source = meta.source.unwrap();
+    assert_eq!(source.source_url, "memory:rfc9000");
+    assert_eq!(source.format, Format::Text);
+}
+
+#[tokio::test]
+async fn fetch_and_cache_converts_html_fallback() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Draft("draft-ietf-foo-bar-01".to_string());
+    let source = MapSource::new().with(
+        doc.clone(),
+        "<html><body><h1>Foo Bar</h1><p>Some draft text.</p></body></html>",
+        Format::Html,
+    );
+    let server = metadata_server("draft-ietf-foo-bar-01", "Foo Bar").await;
+
+    let text = fetch_and_cache(&doc, &cache, &source, &datatracker(&server))
+        .await
+        .unwrap();
+
+    assert!(!text.contains("<html>"));
+    assert!(text.contains("Foo Bar"));
+    assert!(text.contains("Some draft text."));
+    // Converted text is what gets cached; no HTML copy is kept.
+    assert_eq!(cache.get_document(&doc, Format::Text), Some(text));
+    assert!(cache.get_document(&doc, Format::Html).is_none());
+}
+
+#[tokio::test]
+async fn fetch_and_cache_tolerates_metadata_failure() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(791);
+    let source = MapSource::new().with(doc.clone(), "IP", Format::Text);
+    // No metadata route mounted: every request 404s.
+    let server = MockServer::start().await;
+
+    let text = fetch_and_cache(&doc, &cache, &source, &datatracker(&server))
+        .await
+        .unwrap();
+
+    assert_eq!(text, "IP");
+    // The source is still recorded, leaving the title for backfill.
+    let meta = cache.get_metadata(&doc).unwrap();
+    assert_eq!(meta.title, "");
+    assert_eq!(meta.source.unwrap().source_url, "memory:rfc791");
+    assert_eq!(missing_titles(&cache), [doc]);
+}
+
+/// A source that takes `delay` to answer.
+struct SlowSource {
+    inner: MapSource,
+    delay: Duration,
+}
+
+#[async_trait]
+impl DocumentSource for SlowSource {
+    async fn fetch(&self, doc: &DocumentType) -> anyhow::Result<FetchedDocument> {
+        tokio::time::sleep(self.delay).await;
+        self.inner.fetch(doc).await
+    }
+}
+
+async fn slow_metadata_server(name: &str, title: &str, delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/v1/doc/document/{}/", name)))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "name": name, "title": title, "abstract": null }))
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn fetch_and_cache_looks_up_metadata_alongside_the_content() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(9000);
+    let delay = Duration::from_millis(600);
+    let source = SlowSource {
+        inner: MapSource::new().with(doc.clone(), "QUIC text", Format::Text),
+        delay,
+    };
+    let server = slow_metadata_server("rfc9000", "QUIC", delay).await;
+
+    let started = Instant::now();
+    let text = fetch_and_cache(&doc, &cache, &source, &datatracker(&server))
+        .await
+        .unwrap();
+
+    // One round trip's wait, not two.
+    assert!(started.elapsed() < delay * 2, "{:?}", started.elapsed());
+    assert_eq!(text, "QUIC text");
+    assert_eq!(cache.get_metadata(&doc).unwrap().title, "QUIC");
+}
+
+#[tokio::test]
+async fn fetch_and_cache_does_not_wait_on_slow_metadata() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(9000);
+    let source = MapSource::new().with(doc.clone(), "QUIC text", Format::Text);
+    let server = slow_metadata_server("rfc9000", "QUIC", Duration::from_secs(30)).await;
+
+    let started = Instant::now();
+    let text = fetch_and_cache(&doc, &cache, &source, &datatracker(&server))
+        .await
+        .unwrap();
+
+    assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "{:?}",
+        started.elapsed()
+    );
+    assert_eq!(text, "QUIC text");
+    assert_eq!(cache.get_document(&doc, Format::Text).unwrap(), "QUIC text");
+    // Left for backfill, as when the lookup fails.
+    assert_eq!(missing_titles(&cache), [doc]);
+}
+
+#[tokio::test]
+async fn load_document_serves_cache_after_first_fetch() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(9000);
+    let source = MapSource::new().with(doc.clone(), "QUIC text", Format::Text);
+    let server = metadata_server("rfc9000", "QUIC").await;
+    let datatracker = datatracker(&server);
+
+    let first = load_document(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    let second = load_document(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+
+    assert_eq!(first, "QUIC text");
+    assert_eq!(second, first);
+    assert_eq!(source.calls(), 1);
+}
+
+#[tokio::test]
+async fn load_document_records_access_but_listing_does_not() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let doc = DocumentType::Rfc(9000);
+    let source = MapSource::new().with(doc.clone(), "QUIC text", Format::Text);
+    let server = metadata_server("rfc9000", "QUIC").await;
+    let datatracker = datatracker(&server);
+
+    load_document(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    let first = cache.get_metadata(&doc).unwrap().last_accessed.unwrap();
+
+    let listed = cache.list_cached_with_metadata();
+    assert_eq!(listed[0].last_accessed(), Some(first));
+    assert_eq!(cache.get_metadata(&doc).unwrap().last_accessed, Some(first));
+
+    load_document(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    let second = cache.get_metadata(&doc).unwrap().last_accessed.unwrap();
+    assert!(second >= first);
+    assert_eq!(cache.get_metadata(&doc).unwrap().title, "QUIC");
+}
+
+#[tokio::test]
+async fn load_document_tolerates_missing_metadata() {
+    let cache = MemoryCache::new();
+    let doc = DocumentType::Rfc(9000);
+    cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+    let server = MockServer::start().await;
+
+    let text = load_document(&doc, &cache, &MapSource::new(), &datatracker(&server))
+        .await
+        .unwrap();
+    assert_eq!(text, "QUIC");
+    assert!(cache.get_metadata(&doc).is_none());
+}
+
+#[tokio::test]
+async fn load_document_propagates_source_errors() {
+    let temp = TempDir::new().unwrap();
+    let cache = CacheManager::with_dir(temp.path().to_path_buf()).unwrap();
+    let server = MockServer::start().await;
+
+    let err = load_document(
+        &DocumentType::Rfc(1),
+        &cache,
+        &MapSource::new(),
+        &datatracker(&server),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("RFC 1"));
+}
+
+#[tokio::test]
+async fn refresh_cached_reports_updated_and_unchanged() {
+    let cache = MemoryCache::new();
+    let doc = DocumentType::Draft("draft-ietf-foo-bar".to_string());
+    cache.store_document(&doc, Format::Text, "rev 01").unwrap();
+
+    let same = MapSource::new().with(doc.clone(), "rev 01", Format::Text);
+    assert_eq!(
+        refresh_cached(&doc, &cache, &same, None).await.unwrap(),
+        Refresh::Unchanged
+    );
+
+    let newer = MapSource::new().with(doc.clone(), "rev 02", Format::Text);
+    assert_eq!(
+        refresh_cached(&doc, &cache, &newer, None).await.unwrap(),
+        Refresh::Updated
+    );
+    assert_eq!(
+        cache.get_document(&doc, Format::Text).as_deref(),
+        Some("rev 02")
+    );
+}
+
+#[tokio::test]
+async fn refresh_cached_leaves_cache_alone_on_failure() {
+    let cache = MemoryCache::new();
+    let doc = DocumentType::Rfc(9000);
+    cache.store_document(&doc, Format::Text, "QUIC").unwrap();
+
+    assert!(refresh_cached(&doc, &cache, &MapSource::new(), None)
+        .await
+        .is_err());
+    assert_eq!(
+        cache.get_document(&doc, Format::Text).as_deref(),
+        Some("QUIC")
+    );
+}
+
+#[tokio::test]
+async fn backfill_titles_fills_gaps_and_reports_failures() {
+    let cache = MemoryCache::new();
+    let titled = DocumentType::Rfc(9000);
+    let untitled = DocumentType::Rfc(8446);
+    let blank = DocumentType::Rfc(9110);
+    let unknown = DocumentType::Rfc(1);
+    for doc in [&titled, &untitled, &blank, &unknown] {
+        cache.store_document(doc, Format::Text, "body").unwrap();
+    }
+    let cached_at = chrono::Utc::now() - chrono::Duration::days(30);
+    cache
+        .store_metadata(&titled, &CacheMetadata::new("QUIC", cached_at))
+        .unwrap();
+    cache
+        .store_metadata(&blank, &CacheMetadata::new(String::new(), cached_at))
+        .unwrap();
+
+    let missing = missing_titles(&cache);
+    assert_eq!(
+        missing,
+        vec![unknown.clone(), untitled.clone(), blank.clone()]
+    );
+
+    // One batched lookup answers for every document it knows.
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("name__in", "rfc1,rfc8446,rfc9110"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {"name": "rfc8446", "title": "TLS 1.3", "abstract": null},
+                {"name": "rfc9110", "title": "HTTP Semantics", "abstract": null}
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"objects": []})))
+        .mount(&server)
+        .await;
+    let mut results = backfill_titles(&missing, &cache, &datatracker(&server)).await;
+    results.sort_by_key(|(doc, _)| doc.name());
+
+    let outcomes: Vec<_> = results
+        .iter()
+        .map(|(doc, result)| (doc.clone(), result.is_ok()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (unknown.clone(), false),
+            (untitled.clone(), true),
+            (blank.clone(), true)
+        ]
+    );
+    assert_eq!(cache.get_metadata(&untitled).unwrap().title, "TLS 1.3");
+    // An existing timestamp survives the backfill.
+    let filled = cache.get_metadata(&blank).unwrap();
+    assert_eq!(filled.title, "HTTP Semantics");
+    assert_eq!(filled.cached_at, cached_at);
+    assert_eq!(missing_titles(&cache), vec![unknown]);
+}
+
+#[tokio::test]
+async fn backfill_records_abstracts_missing_from_old_metadata() {
+    let cache = MemoryCache::new();
+    let quic = DocumentType::Rfc(9000);
+    let tls = DocumentType::Rfc(8446);
+    for doc in [&quic, &tls] {
+        cache.store_document(doc, Format::Text, "body").unwrap();
+        let title = doc.name().to_uppercase();
+        cache
+            .store_metadata(doc, &CacheMetadata::new(title, chrono::Utc::now()))
+            .unwrap();
+    }
+    assert!(missing_titles(&cache).is_empty());
+    let missing = missing_abstracts(&cache);
+    assert_eq!(missing, [tls.clone(), quic.clone()]);
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/document/"))
+        .and(query_param("name__in", "rfc8446,rfc9000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "objects": [
+                {"name": "rfc8446", "title": "TLS 1.3", "abstract": null},
+                {"name": "rfc9000", "title": "QUIC", "abstract": "QUIC is a transport."}
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/doc/relateddocument/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"objects": []})))
+        .mount(&server)
+        .await;
+    for (_, result) in backfill_titles(&missing, &cache, &datatracker(&server)).await {
+        result.unwrap();
+    }
+
+    let meta = cache.get_metadata(&quic).unwrap();
+    assert_eq!(meta.recorded_abstract(), Some("QUIC is a transport."));
+    // Having none is recorded too, so it isn't asked for again.
+    assert_eq!(cache.get_metadata(&tls).unwrap().recorded_abstract(), None);
+    assert!(missing_abstracts(&cache).is_empty());
+}
+
+#[tokio::test]
+async fn user_state_survives_metadata_rewrites() {
+    let cache = MemoryCache::new();
+    let doc = DocumentType::Rfc(9000);
+    let source = MapSource::new().with(doc.clone(), "QUIC text", Format::Text);
+    let server = metadata_server("rfc9000", "QUIC").await;
+    let datatracker = datatracker(&server);
+
+    fetch_and_cache(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    cache.add_tag(&doc, "transport").unwrap();
+    let mark = Bookmark { line: 1, lines: 1 };
+    cache.set_bookmark(&doc, Some(mark)).unwrap();
+
+    // Re-fetching rewrites the title and cached_at...
+    fetch_and_cache(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    // ...as do viewing and backfilling.
+    load_document(&doc, &cache, &source, &datatracker)
+        .await
+        .unwrap();
+    backfill_titles(std::slice::from_ref(&doc), &cache, &datatracker).await;
+
+    let meta = cache.get_metadata(&doc).unwrap();
+    assert_eq!(meta.title, "QUIC");
+    assert_eq!(meta.tags, vec!["transport".to_string()]);
+    assert_eq!(meta.bookmark, Some(mark));
+    assert!(meta.last_accessed.is_some());
+}
+
+#[tokio::test]
+async fn refresh_drops_bookmarks_that_no_longer_fit() {
+    let cache = MemoryCache::new();
+    let doc = DocumentType::Rfc(9000);
+    let text = |lines: usize| "line\n".repeat(lines);
+    cache
+        .store_document(&doc, Format::Text, &text(100))
+        .unwrap();
+    let mark = Bookmark {
+        line: 40,
+        lines: 100,
+    };
+    cache.set_bookmark(&doc, Some(mark)).unwrap();
+
+    // An erratum-sized change keeps the place...
+    let source = MapSource::new().with(doc.clone(), text(103), Format::Text);
+    assert_eq!(
+        refresh_cached(&doc, &cache, &source, None).await.unwrap(),
+        Refresh::Updated
+    );
+    assert_eq!(cache.get_metadata(&doc).unwrap().bookmark, Some(mark));
+
+    // ...a substantial rewrite doesn't.
+    let source = MapSource::new().with(doc.clone(), text(150), Format::Text);
+    refresh_cached(&doc, &cache, &source, None).await.unwrap();
+    assert_eq!(cache.get_metadata(&doc).unwrap().bookmark, None);
+}